@@ -220,6 +220,9 @@ pub enum AppError {
 
   #[error("{0}")]
   TooManyRequests(String),
+
+  #[error("Collab structure limit exceeded: {0}")]
+  CollabStructureLimitExceeded(String),
 }
 
 impl AppError {
@@ -312,6 +315,7 @@ impl AppError {
       AppError::RecordDeleted(_) => ErrorCode::RecordDeleted,
       AppError::RetryLater(_) => ErrorCode::RetryLater,
       AppError::TooManyRequests(_) => ErrorCode::TooManyRequests,
+      AppError::CollabStructureLimitExceeded(_) => ErrorCode::CollabStructureLimitExceeded,
     }
   }
 }
@@ -497,6 +501,7 @@ pub enum ErrorCode {
   PaidPlanGuestLimitExceeded = 1071,
   PlanLimitExceeded = 1072,
   TooManyRequests = 1073,
+  CollabStructureLimitExceeded = 1074,
 }
 
 impl ErrorCode {
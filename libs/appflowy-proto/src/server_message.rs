@@ -23,6 +23,13 @@ use uuid::Uuid;
 pub enum AccessChangedReason {
   PermissionDenied = 0,
   ObjectDeleted = 1,
+  /// The object belongs to an archived space and is read-only until the space is unarchived.
+  SpaceArchived = 2,
+  /// The update was rejected because the object would exceed the structure limits of the
+  /// server, such as the maximum number of blocks of a document.
+  StructureLimitExceeded = 3,
+  /// The workspace is being merged into another one and is read-only until the merge ends.
+  WorkspaceMerging = 4,
 }
 
 impl Display for AccessChangedReason {
@@ -30,6 +37,9 @@ impl Display for AccessChangedReason {
     match self {
       AccessChangedReason::PermissionDenied => write!(f, "PermissionDenied"),
       AccessChangedReason::ObjectDeleted => write!(f, "ObjectDeleted"),
+      AccessChangedReason::SpaceArchived => write!(f, "SpaceArchived"),
+      AccessChangedReason::StructureLimitExceeded => write!(f, "StructureLimitExceeded"),
+      AccessChangedReason::WorkspaceMerging => write!(f, "WorkspaceMerging"),
     }
   }
 }
@@ -399,6 +409,9 @@ impl From<i32> for AccessChangedReason {
     match value {
       0 => AccessChangedReason::PermissionDenied,
       1 => AccessChangedReason::ObjectDeleted,
+      2 => AccessChangedReason::SpaceArchived,
+      3 => AccessChangedReason::StructureLimitExceeded,
+      4 => AccessChangedReason::WorkspaceMerging,
       _ => AccessChangedReason::PermissionDenied,
     }
   }
@@ -409,6 +422,9 @@ impl From<u32> for AccessChangedReason {
     match value {
       0 => AccessChangedReason::PermissionDenied,
       1 => AccessChangedReason::ObjectDeleted,
+      2 => AccessChangedReason::SpaceArchived,
+      3 => AccessChangedReason::StructureLimitExceeded,
+      4 => AccessChangedReason::WorkspaceMerging,
       _ => AccessChangedReason::PermissionDenied,
     }
   }
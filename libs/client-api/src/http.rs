@@ -4,9 +4,12 @@ use app_error::ErrorCode;
 use client_api_entity::auth_dto::DeleteUserQuery;
 use client_api_entity::server_info_dto::ServerInfoResponseItem;
 use client_api_entity::workspace_dto::FavoriteSectionItems;
+use client_api_entity::workspace_dto::QueryTrashViews;
 use client_api_entity::workspace_dto::RecentSectionItems;
 use client_api_entity::workspace_dto::TrashSectionItems;
-use client_api_entity::workspace_dto::{FolderView, QueryWorkspaceFolder, QueryWorkspaceParam};
+use client_api_entity::workspace_dto::{
+  FolderView, QueryWorkspaceFolder, QueryWorkspaceFolderForest, QueryWorkspaceParam,
+};
 use client_api_entity::AuthProvider;
 use client_api_entity::GetInvitationCodeInfoQuery;
 use client_api_entity::InvitationCodeInfo;
@@ -19,7 +22,9 @@ use gotrue::grant::{Grant, RefreshTokenGrant};
 use gotrue::params::{AdminUserParams, GenerateLinkParams};
 use gotrue::params::{MagicLinkParams, VerifyParams, VerifyType};
 use reqwest::StatusCode;
-use shared_entity::dto::workspace_dto::{CreateWorkspaceParam, PatchWorkspaceParam};
+use shared_entity::dto::workspace_dto::{
+  CreateWorkspaceParam, MergeWorkspaceParams, MergedWorkspace, PatchWorkspaceParam,
+};
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 #[cfg(feature = "enable_brotli")]
@@ -40,7 +45,10 @@ use shared_entity::dto::auth_dto::UpdateUserParams;
 use shared_entity::dto::auth_dto::{
   GetUidByEmailOrPhoneResponse, SignInPasswordResponse, SignInTokenResponse,
 };
-use shared_entity::dto::workspace_dto::WorkspaceSpaceUsage;
+use shared_entity::dto::workspace_dto::{
+  BlobIntegrityReport, BlobTieringSummary, DeadReferenceReport, PublishIntegrityReport,
+  ScanPublishIntegrityParams, WorkspaceHealthReport, WorkspaceSpaceUsage,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -842,6 +850,24 @@ impl Client {
     process_response_error(resp).await
   }
 
+  /// Merges `params.source_workspace_id` into `workspace_id`. Both workspaces must be owned by
+  /// the user. The source workspace is deleted once merged.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn merge_workspace(
+    &self,
+    workspace_id: &Uuid,
+    params: &MergeWorkspaceParams,
+  ) -> Result<MergedWorkspace, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/merge", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<MergedWorkspace>(resp).await
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn create_workspace(
     &self,
@@ -922,6 +948,31 @@ impl Client {
     process_response_data::<FolderView>(resp).await
   }
 
+  /// Returns the folder trees below each of the given root views, e.g. several spaces, in one
+  /// request. Roots that no longer exist are left out.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_folder_forest(
+    &self,
+    workspace_id: &Uuid,
+    depth: Option<u32>,
+    root_view_ids: Vec<Uuid>,
+  ) -> Result<Vec<FolderView>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/forest",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&QueryWorkspaceFolderForest {
+        depth,
+        root_view_ids,
+      })
+      .send()
+      .await?;
+    process_response_data::<Vec<FolderView>>(resp).await
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn open_workspace(&self, workspace_id: &Uuid) -> Result<AFWorkspace, AppResponseError> {
     let url = format!("{}/api/workspace/{}/open", self.base_url, workspace_id);
@@ -975,6 +1026,23 @@ impl Client {
     process_response_data::<TrashSectionItems>(resp).await
   }
 
+  /// A page of the trash, most recently deleted first.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_trash_page(
+    &self,
+    workspace_id: &Uuid,
+    query: &QueryTrashViews,
+  ) -> Result<TrashSectionItems, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/trash", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<TrashSectionItems>(resp).await
+  }
+
   pub async fn join_workspace_by_invitation_code(
     &self,
     invitation_code: &str,
@@ -1165,6 +1233,170 @@ impl Client {
     process_response_data::<WorkspaceSpaceUsage>(resp).await
   }
 
+  /// Returns the latest attachment integrity report of the workspace.
+  #[instrument(level = "info", skip_all)]
+  pub async fn get_blob_integrity_report(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<BlobIntegrityReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/blob-integrity",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<BlobIntegrityReport>(resp).await
+  }
+
+  /// Scans the workspace for broken attachment links and orphan blobs. Only the workspace
+  /// owner is allowed to trigger a scan.
+  #[instrument(level = "info", skip_all)]
+  pub async fn scan_blob_integrity(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<BlobIntegrityReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/blob-integrity",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<BlobIntegrityReport>(resp).await
+  }
+
+  /// Returns the latest report of published pages whose published data is broken.
+  #[instrument(level = "info", skip_all)]
+  pub async fn get_publish_integrity_report(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<PublishIntegrityReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-integrity",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PublishIntegrityReport>(resp).await
+  }
+
+  /// Checks the published data of the published pages of the workspace. With `regenerate`, the
+  /// published data of broken pages is rebuilt from their current content. Only the workspace
+  /// owner can scan.
+  #[instrument(level = "info", skip_all)]
+  pub async fn scan_publish_integrity(
+    &self,
+    workspace_id: &Uuid,
+    regenerate: bool,
+  ) -> Result<PublishIntegrityReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-integrity",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .query(&ScanPublishIntegrityParams { regenerate })
+      .send()
+      .await?;
+    process_response_data::<PublishIntegrityReport>(resp).await
+  }
+
+  /// Returns the latest report of mentions to trashed or deleted pages and removed members.
+  #[instrument(level = "info", skip_all)]
+  pub async fn get_dead_reference_report(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<DeadReferenceReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/dead-references",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<DeadReferenceReport>(resp).await
+  }
+
+  /// Scans the documents of the workspace for dead page and member mentions. Only the
+  /// workspace owner is allowed to trigger a scan.
+  #[instrument(level = "info", skip_all)]
+  pub async fn scan_dead_references(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<DeadReferenceReport, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/dead-references",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<DeadReferenceReport>(resp).await
+  }
+
+  /// Returns the latest maintenance score of the workspace and its recommendations, which
+  /// are recomputed daily. Only the workspace owner is allowed to read it.
+  #[instrument(level = "info", skip_all)]
+  pub async fn get_workspace_health(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<WorkspaceHealthReport, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/health", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<WorkspaceHealthReport>(resp).await
+  }
+
+  /// Recomputes the maintenance score of the workspace right away.
+  #[instrument(level = "info", skip_all)]
+  pub async fn compute_workspace_health(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<WorkspaceHealthReport, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/health", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<WorkspaceHealthReport>(resp).await
+  }
+
+  /// Returns how much of the workspace storage was moved to infrequent access storage and
+  /// the estimated monthly savings.
+  #[instrument(level = "info", skip_all)]
+  pub async fn get_blob_tiering_summary(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<BlobTieringSummary, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/blob-tiering",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<BlobTieringSummary>(resp).await
+  }
+
   #[instrument(level = "info", skip_all)]
   pub async fn get_server_info(&self) -> Result<ServerInfoResponseItem, AppResponseError> {
     let url = format!("{}/api/server", self.base_url);
@@ -0,0 +1,44 @@
+use client_api_entity::{CreateUserApiKeyParams, CreatedUserApiKey, UserApiKeys};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+// User API Key API
+impl Client {
+  /// The secret of the created key is only returned here.
+  pub async fn create_user_api_key(
+    &self,
+    params: &CreateUserApiKeyParams,
+  ) -> Result<CreatedUserApiKey, AppResponseError> {
+    let url = format!("{}/api/user/api-keys", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<CreatedUserApiKey>(resp).await
+  }
+
+  pub async fn list_user_api_keys(&self) -> Result<UserApiKeys, AppResponseError> {
+    let url = format!("{}/api/user/api-keys", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<UserApiKeys>(resp).await
+  }
+
+  pub async fn delete_user_api_key(&self, key_id: Uuid) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/api-keys/{}", self.base_url, key_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+}
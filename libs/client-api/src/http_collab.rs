@@ -8,14 +8,15 @@ use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use client_api_entity::workspace_dto::{
   AFDatabase, AFDatabaseField, AFDatabaseRow, AFDatabaseRowDetail, AFInsertDatabaseField,
-  AddDatatabaseRow, DatabaseRowUpdatedItem, ListDatabaseRowDetailParam,
-  ListDatabaseRowUpdatedParam, UpsertDatatabaseRow,
+  AddDatatabaseRow, CollabAccessCheckItem, CollabAccessCheckParams, DatabaseRowUpdatedItem,
+  GetDatabaseRowDetailParam, ListDatabaseRowDetailParam, ListDatabaseRowUpdatedParam,
+  UpsertDatatabaseRow,
 };
 use client_api_entity::{
   AFCollabEmbedInfo, AFDatabaseRowDocumentCollabExistenceInfo, BatchQueryCollabParams,
-  BatchQueryCollabResult, CollabParams, CreateCollabData, CreateCollabParams, DeleteCollabParams,
-  PublishCollabItem, QueryCollab, QueryCollabParams, RepeatedAFCollabEmbedInfo,
-  UpdateCollabWebParams,
+  BatchQueryCollabResult, CollabConflicts, CollabParams, CreateCollabData, CreateCollabParams,
+  DeleteCollabParams, PublishCollabItem, QueryCollab, QueryCollabParams,
+  RepeatedAFCollabEmbedInfo, UpdateCollabWebParams,
 };
 use collab_rt_entity::collab_proto::{CollabDocStateParams, PayloadCompressionType};
 use collab_rt_entity::HttpRealtimeMessage;
@@ -331,6 +332,28 @@ impl Client {
     process_response_data::<Vec<AFDatabaseRowDetail>>(resp).await
   }
 
+  pub async fn get_database_row_detail(
+    &self,
+    workspace_id: &Uuid,
+    database_id: &str,
+    row_id: &str,
+    with_doc: bool,
+  ) -> Result<AFDatabaseRowDetail, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/row/{}",
+      self.base_url, workspace_id, database_id, row_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&GetDatabaseRowDetailParam {
+        with_doc: Some(with_doc),
+      })
+      .send()
+      .await?;
+    process_response_data::<AFDatabaseRowDetail>(resp).await
+  }
+
   /// Example payload:
   /// {
   ///   "Name": "some_data",        # using column name
@@ -510,6 +533,44 @@ impl Client {
     Ok(info.exists)
   }
 
+  /// Checks existence and permissions of several collabs without fetching them.
+  pub async fn check_collab_access(
+    &self,
+    workspace_id: &Uuid,
+    object_ids: Vec<Uuid>,
+  ) -> Result<Vec<CollabAccessCheckItem>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{workspace_id}/collab/access-check",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CollabAccessCheckParams { object_ids })
+      .send()
+      .await?;
+    process_response_data::<Vec<CollabAccessCheckItem>>(resp).await
+  }
+
+  /// Updates of the current user that the server did not apply to the collab, most recent
+  /// first.
+  pub async fn get_collab_conflicts(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &Uuid,
+  ) -> Result<CollabConflicts, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{workspace_id}/collab/{object_id}/conflicts",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<CollabConflicts>(resp).await
+  }
+
   pub async fn get_collab_embed_info(
     &self,
     workspace_id: &Uuid,
@@ -0,0 +1,137 @@
+use client_api_entity::{
+  DeviceHandoffParams, DeviceHandoffs, QuietHours, RegisterPushTokenParams, SetQuietHoursParams,
+  UserDevices, UserQuietHours,
+};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+// Device Handoff API
+impl Client {
+  /// Devices of the user that connected recently, to pick the target of a handoff.
+  pub async fn list_user_devices(&self) -> Result<UserDevices, AppResponseError> {
+    let url = format!("{}/api/user/devices", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<UserDevices>(resp).await
+  }
+
+  /// The target devices receive a `device_handoff` notification with the id of the handoff, and
+  /// fetch its content with [Client::list_device_handoffs] before it expires.
+  pub async fn send_device_handoff(
+    &self,
+    params: &DeviceHandoffParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/handoff", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Handoffs sent to this device that it has not marked as processed yet.
+  pub async fn list_device_handoffs(&self) -> Result<DeviceHandoffs, AppResponseError> {
+    let url = format!("{}/api/user/handoff", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<DeviceHandoffs>(resp).await
+  }
+
+  /// Only this device stops listing the handoff; the other devices it was sent to still do.
+  pub async fn mark_device_handoff_processed(
+    &self,
+    handoff_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/user/handoff/{}/processed",
+      self.base_url, handoff_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Push notifications of the user are sent to this device with the token.
+  pub async fn register_push_token(
+    &self,
+    params: &RegisterPushTokenParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/push-token", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn unregister_push_token(&self) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/push-token", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn list_quiet_hours(&self) -> Result<UserQuietHours, AppResponseError> {
+    let url = format!(
+      "{}/api/user/notification-preferences/quiet-hours",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<UserQuietHours>(resp).await
+  }
+
+  /// Push notifications to the device are held back during its quiet hours.
+  pub async fn set_quiet_hours(
+    &self,
+    device_id: &str,
+    params: &SetQuietHoursParams,
+  ) -> Result<QuietHours, AppResponseError> {
+    let url = format!(
+      "{}/api/user/notification-preferences/quiet-hours/{}",
+      self.base_url, device_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<QuietHours>(resp).await
+  }
+
+  pub async fn remove_quiet_hours(&self, device_id: &str) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/user/notification-preferences/quiet-hours/{}",
+      self.base_url, device_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+}
@@ -1,6 +1,8 @@
 use crate::{process_response_data, process_response_error, Client};
+use bytes::Bytes;
 use client_api_entity::{
-  AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceMember, QueryWorkspaceMember,
+  AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceMember, CollabPermissionSync,
+  QueryWorkspaceMember,
 };
 use reqwest::Method;
 use shared_entity::dto::workspace_dto::{
@@ -37,6 +39,29 @@ impl Client {
     process_response_data::<Vec<AFWorkspaceMember>>(resp).await
   }
 
+  /// Exports the members and pending invitations of the workspace as CSV. Only the owner of the
+  /// workspace can export it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn export_workspace_members(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<Bytes, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member/export",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    let bytes = resp.error_for_status()?.bytes().await?;
+    if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
+      return Err(app_err);
+    }
+    Ok(bytes)
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn invite_workspace_members(
     &self,
@@ -134,6 +159,26 @@ impl Client {
     process_response_error(resp).await
   }
 
+  /// Progress of the recomputation of the collab-level permissions of the member after their
+  /// latest role change.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_member_collab_permission_sync(
+    &self,
+    workspace_id: &Uuid,
+    uid: i64,
+  ) -> Result<CollabPermissionSync, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member/user/{}/permission-sync",
+      self.base_url, workspace_id, uid
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<CollabPermissionSync>(resp).await
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn remove_workspace_members(
     &self,
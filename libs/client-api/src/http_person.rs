@@ -1,7 +1,9 @@
 use app_error::AppError;
 use client_api_entity::{
-  MentionablePerson, MentionablePersons, MentionablePersonsWithAccess, PageMentionUpdate,
-  UserImageAssetSource, WorkspaceMemberProfile,
+  CreateWorkspaceMemberFieldParams, MentionablePerson, MentionablePersons,
+  MentionablePersonsWithAccess, PageMentionUpdate, QueryWorkspaceDirectory,
+  UpdateWorkspaceMemberFieldParams, UserImageAssetSource, WorkspaceDirectory, WorkspaceMemberField,
+  WorkspaceMemberFields, WorkspaceMemberProfile, WorkspaceMemberStatus,
 };
 use reqwest::{multipart, Method, StatusCode};
 use shared_entity::response::AppResponseError;
@@ -46,6 +48,96 @@ impl Client {
     process_response_data::<MentionablePerson>(resp).await
   }
 
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_directory(
+    &self,
+    workspace_id: &Uuid,
+    query: &QueryWorkspaceDirectory,
+  ) -> Result<WorkspaceDirectory, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/directory", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<WorkspaceDirectory>(resp).await
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_workspace_member_fields(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<WorkspaceMemberFields, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-field",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<WorkspaceMemberFields>(resp).await
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_workspace_member_field(
+    &self,
+    workspace_id: &Uuid,
+    params: &CreateWorkspaceMemberFieldParams,
+  ) -> Result<WorkspaceMemberField, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-field",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<WorkspaceMemberField>(resp).await
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_member_field(
+    &self,
+    workspace_id: &Uuid,
+    field_id: &Uuid,
+    params: &UpdateWorkspaceMemberFieldParams,
+  ) -> Result<WorkspaceMemberField, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-field/{}",
+      self.base_url, workspace_id, field_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<WorkspaceMemberField>(resp).await
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_member_field(
+    &self,
+    workspace_id: &Uuid,
+    field_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-field/{}",
+      self.base_url, workspace_id, field_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
   pub async fn update_workspace_member_profile(
     &self,
     workspace_id: &Uuid,
@@ -64,6 +156,40 @@ impl Client {
     process_response_error(resp).await
   }
 
+  pub async fn set_workspace_member_status(
+    &self,
+    workspace_id: &Uuid,
+    status: &WorkspaceMemberStatus,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-status",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(status)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn clear_workspace_member_status(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-status",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
   pub async fn list_page_mentionable_persons(
     &self,
     workspace_id: &Uuid,
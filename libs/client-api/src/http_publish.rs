@@ -1,6 +1,13 @@
 use crate::{process_response_data, process_response_error, Client};
 use bytes::Bytes;
-use client_api_entity::publish_dto::DuplicatePublishedPageResponse;
+use client_api_entity::publish_dto::{
+  DuplicatePublishedPageResponse, PublishRowFilter, PublishTheme, PublishedDatabaseViews,
+  PublishedViewVariant, SetPublishedViewVariant, UpdatePublishedDatabaseViews,
+};
+pub use client_api_entity::workspace_dto::{
+  AllPublishedCollabItem, ListAllPublishedCollabResponse, ReceivePublishedCollabRequest,
+  ReceivePublishedCollabResponse, ReceivedPublishedCollabReadonlyResponse,
+};
 use client_api_entity::workspace_dto::{PublishInfoView, PublishedView};
 use client_api_entity::{workspace_dto::PublishedDuplicate, PublishInfo, UpdatePublishNamespace};
 use client_api_entity::{
@@ -13,44 +20,6 @@ use shared_entity::response::AppResponseError;
 use tracing::instrument;
 use uuid::Uuid;
 
-// Add new DTOs for receive published collab
-use serde::{Deserialize, Serialize};
-
-/// 用户接收的发布文档请求
-#[derive(Debug, Serialize)]
-pub struct ReceivePublishedCollabRequest {
-  pub published_view_id: Uuid,
-  pub dest_workspace_id: Uuid,
-  pub dest_view_id: Uuid,
-}
-
-/// 用户接收的发布文档响应
-#[derive(Debug, Deserialize)]
-pub struct ReceivePublishedCollabResponse {
-  pub view_id: Uuid,
-  pub is_readonly: bool,
-}
-
-/// 所有发布的文档列表项（包含发布者和接收者的信息）
-#[derive(Debug, Deserialize)]
-pub struct AllPublishedCollabItem {
-  pub published_view_id: Uuid,
-  pub view_id: Uuid,
-  pub workspace_id: Uuid,
-  pub name: String,
-  pub publish_name: String,
-  pub publisher_email: Option<String>,
-  pub published_at: chrono::DateTime<chrono::Utc>,
-  pub is_received: bool,
-  pub is_readonly: bool,
-}
-
-/// 获取所有发布的文档列表响应
-#[derive(Debug, Deserialize)]
-pub struct ListAllPublishedCollabResponse {
-  pub items: Vec<AllPublishedCollabItem>,
-}
-
 // Publisher API
 impl Client {
   #[instrument(level = "debug", skip_all)]
@@ -106,6 +75,25 @@ impl Client {
     process_response_data::<ReceivePublishedCollabResponse>(resp).await
   }
 
+  /// Whether the view was received from a published collab, and if so whether it is readonly.
+  /// `view_id` can be either the id of the received copy or the id of the published view.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_received_published_collab_readonly(
+    &self,
+    view_id: &Uuid,
+  ) -> Result<ReceivedPublishedCollabReadonlyResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/received/{}/readonly",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ReceivedPublishedCollabReadonlyResponse>(resp).await
+  }
+
   /// Changes the namespace for the first non-original publish namespace
   /// or the original publish namespace if not exists.
   pub async fn set_workspace_publish_namespace(
@@ -114,19 +102,32 @@ impl Client {
     new_namespace: String,
   ) -> Result<(), AppResponseError> {
     let old_namespace = self.get_workspace_publish_namespace(workspace_id).await?;
+    self
+      .update_workspace_publish_namespace(
+        workspace_id,
+        &UpdatePublishNamespace {
+          old_namespace,
+          new_namespace,
+        },
+      )
+      .await
+  }
 
+  /// Replaces `old_namespace` of the workspace with `new_namespace`. When `old_namespace` is the
+  /// original namespace, `new_namespace` is added next to it instead.
+  pub async fn update_workspace_publish_namespace(
+    &self,
+    workspace_id: &Uuid,
+    params: &UpdatePublishNamespace,
+  ) -> Result<(), AppResponseError> {
     let url = format!(
       "{}/api/workspace/{}/publish-namespace",
       self.base_url, workspace_id
     );
-
     let resp = self
       .http_client_with_auth(Method::PUT, &url)
       .await?
-      .json(&UpdatePublishNamespace {
-        old_namespace,
-        new_namespace,
-      })
+      .json(params)
       .send()
       .await?;
     process_response_error(resp).await
@@ -178,6 +179,250 @@ impl Client {
     process_response_error(resp).await
   }
 
+  /// Sets the theme of one of the publish namespaces of the workspace. Only the workspace
+  /// owner is allowed to change it. Returns the theme as stored, with the footer sanitized.
+  pub async fn set_publish_namespace_theme(
+    &self,
+    workspace_id: &Uuid,
+    namespace: &str,
+    theme: &PublishTheme,
+  ) -> Result<PublishTheme, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/{}/theme",
+      self.base_url, workspace_id, namespace
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(theme)
+      .send()
+      .await?;
+    process_response_data::<PublishTheme>(resp).await
+  }
+
+  pub async fn get_publish_namespace_theme(
+    &self,
+    workspace_id: &Uuid,
+    namespace: &str,
+  ) -> Result<PublishTheme, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/{}/theme",
+      self.base_url, workspace_id, namespace
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PublishTheme>(resp).await
+  }
+
+  pub async fn delete_publish_namespace_theme(
+    &self,
+    workspace_id: &Uuid,
+    namespace: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/{}/theme",
+      self.base_url, workspace_id, namespace
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Sets the theme of a published view, which takes precedence over the namespace theme.
+  pub async fn set_published_view_theme(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    theme: &PublishTheme,
+  ) -> Result<PublishTheme, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/publish-theme",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(theme)
+      .send()
+      .await?;
+    process_response_data::<PublishTheme>(resp).await
+  }
+
+  pub async fn get_published_view_theme(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+  ) -> Result<PublishTheme, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/publish-theme",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PublishTheme>(resp).await
+  }
+
+  pub async fn delete_published_view_theme(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/publish-theme",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Views in the published data of the database, and which of them are visible.
+  pub async fn get_published_database_views(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+  ) -> Result<PublishedDatabaseViews, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/database-views",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PublishedDatabaseViews>(resp).await
+  }
+
+  /// Changes which views of a published database are visible, without republishing it.
+  pub async fn update_published_database_views(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    visible_database_view_ids: Vec<Uuid>,
+  ) -> Result<PublishedDatabaseViews, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/database-views",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(&UpdatePublishedDatabaseViews {
+        visible_database_view_ids,
+      })
+      .send()
+      .await?;
+    process_response_data::<PublishedDatabaseViews>(resp).await
+  }
+
+  pub async fn get_published_row_filter(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+  ) -> Result<PublishRowFilter, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/row-filter",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PublishRowFilter>(resp).await
+  }
+
+  /// Removes the row filter of a published database. It takes effect on the next publish.
+  pub async fn delete_published_row_filter(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/row-filter",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Language variants served under the publish name of the view.
+  pub async fn list_published_view_variants(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+  ) -> Result<Vec<PublishedViewVariant>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/publish-variants",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<PublishedViewVariant>>(resp).await
+  }
+
+  /// Serves the published view `variant_view_id` under the publish name of `view_id` when
+  /// `lang` is requested.
+  pub async fn set_published_view_variant(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    lang: &str,
+    variant_view_id: &Uuid,
+  ) -> Result<PublishedViewVariant, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/publish-variants/{}",
+      self.base_url, workspace_id, view_id, lang
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&SetPublishedViewVariant {
+        view_id: *variant_view_id,
+      })
+      .send()
+      .await?;
+    process_response_data::<PublishedViewVariant>(resp).await
+  }
+
+  pub async fn delete_published_view_variant(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    lang: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/publish-variants/{}",
+      self.base_url, workspace_id, view_id, lang
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
   pub async fn create_comment_on_published_view(
     &self,
     view_id: &Uuid,
@@ -424,6 +669,32 @@ impl Client {
     process_response_data::<T>(resp).await
   }
 
+  /// Like [Client::get_published_collab], but serves the variant of the view in `lang` if
+  /// there is one.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_collab_in_language<T>(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    lang: &str,
+  ) -> Result<T, AppResponseError>
+  where
+    T: serde::de::DeserializeOwned + 'static,
+  {
+    let url = format!(
+      "{}/api/workspace/v1/published/{}/{}",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .query(&[("lang", lang)])
+      .send()
+      .await?
+      .error_for_status()?;
+    process_response_data::<T>(resp).await
+  }
+
   #[instrument(level = "debug", skip_all)]
   pub async fn get_published_collab_blob(
     &self,
@@ -449,6 +720,20 @@ impl Client {
     Ok(bytes)
   }
 
+  /// Theme to render a published view with: the view theme completed by the namespace theme.
+  pub async fn get_published_view_effective_theme(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<PublishTheme, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/theme",
+      self.base_url, publish_namespace, publish_name
+    );
+    let resp = self.cloud_client.get(&url).send().await?;
+    process_response_data::<PublishTheme>(resp).await
+  }
+
   pub async fn duplicate_published_to_workspace(
     &self,
     workspace_id: Uuid,
@@ -22,12 +22,30 @@ impl Client {
     &self,
     workspace_id: Uuid,
     data: Option<serde_json::Value>,
+  ) -> Result<QuickNote, AppResponseError> {
+    self
+      .create_quick_note_with_params(
+        workspace_id,
+        &CreateQuickNoteParams {
+          data,
+          dedupe: false,
+        },
+      )
+      .await
+  }
+
+  /// Creates a quick note, returning the existing note instead when `params.dedupe` is set and
+  /// the same note was created shortly before. Mobile clients should set it when retrying.
+  pub async fn create_quick_note_with_params(
+    &self,
+    workspace_id: Uuid,
+    params: &CreateQuickNoteParams,
   ) -> Result<QuickNote, AppResponseError> {
     let url = quick_note_resources_url(&self.base_url, workspace_id);
     let resp = self
       .http_client_with_auth(Method::POST, &url)
       .await?
-      .json(&CreateQuickNoteParams { data })
+      .json(params)
       .send()
       .await?;
     process_response_data::<QuickNote>(resp).await
@@ -39,16 +57,31 @@ impl Client {
     search_term: Option<String>,
     offset: Option<i32>,
     limit: Option<i32>,
+  ) -> Result<QuickNotes, AppResponseError> {
+    self
+      .list_quick_notes_with_params(
+        workspace_id,
+        &ListQuickNotesQueryParams {
+          search_term,
+          offset,
+          limit,
+          ..Default::default()
+        },
+      )
+      .await
+  }
+
+  /// Lists quick notes, optionally only those created in a date range.
+  pub async fn list_quick_notes_with_params(
+    &self,
+    workspace_id: Uuid,
+    params: &ListQuickNotesQueryParams,
   ) -> Result<QuickNotes, AppResponseError> {
     let url = quick_note_resources_url(&self.base_url, workspace_id);
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .query(&ListQuickNotesQueryParams {
-        search_term,
-        offset,
-        limit,
-      })
+      .query(params)
       .send()
       .await?;
     process_response_data::<QuickNotes>(resp).await
@@ -0,0 +1,143 @@
+use bytes::Bytes;
+use client_api_entity::{
+  CreateScheduledExportParams, ExportFormat, ExportSpaceParams, ListScheduledExportRunsQueryParams,
+  ScheduledExport, ScheduledExportRun, ScheduledExportRuns, ScheduledExports,
+  UpdateScheduledExportParams,
+};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+fn scheduled_export_resources_url(base_url: &str, workspace_id: Uuid) -> String {
+  format!("{base_url}/api/workspace/{workspace_id}/scheduled-exports")
+}
+
+fn scheduled_export_resource_url(base_url: &str, workspace_id: Uuid, export_id: Uuid) -> String {
+  let scheduled_export_resources_prefix = scheduled_export_resources_url(base_url, workspace_id);
+  format!("{scheduled_export_resources_prefix}/{export_id}")
+}
+
+// Scheduled Export API
+impl Client {
+  pub async fn create_scheduled_export(
+    &self,
+    workspace_id: Uuid,
+    params: &CreateScheduledExportParams,
+  ) -> Result<ScheduledExport, AppResponseError> {
+    let url = scheduled_export_resources_url(&self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<ScheduledExport>(resp).await
+  }
+
+  pub async fn list_scheduled_exports(
+    &self,
+    workspace_id: Uuid,
+  ) -> Result<ScheduledExports, AppResponseError> {
+    let url = scheduled_export_resources_url(&self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ScheduledExports>(resp).await
+  }
+
+  pub async fn update_scheduled_export(
+    &self,
+    workspace_id: Uuid,
+    export_id: Uuid,
+    params: &UpdateScheduledExportParams,
+  ) -> Result<ScheduledExport, AppResponseError> {
+    let url = scheduled_export_resource_url(&self.base_url, workspace_id, export_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<ScheduledExport>(resp).await
+  }
+
+  pub async fn delete_scheduled_export(
+    &self,
+    workspace_id: Uuid,
+    export_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = scheduled_export_resource_url(&self.base_url, workspace_id, export_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// History of the export, most recent run first.
+  pub async fn list_scheduled_export_runs(
+    &self,
+    workspace_id: Uuid,
+    export_id: Uuid,
+    limit: Option<i64>,
+  ) -> Result<ScheduledExportRuns, AppResponseError> {
+    let url = format!(
+      "{}/runs",
+      scheduled_export_resource_url(&self.base_url, workspace_id, export_id)
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&ListScheduledExportRunsQueryParams { limit })
+      .send()
+      .await?;
+    process_response_data::<ScheduledExportRuns>(resp).await
+  }
+
+  /// Starts a run of the export outside of its schedule.
+  pub async fn run_scheduled_export(
+    &self,
+    workspace_id: Uuid,
+    export_id: Uuid,
+  ) -> Result<ScheduledExportRun, AppResponseError> {
+    let url = format!(
+      "{}/run",
+      scheduled_export_resource_url(&self.base_url, workspace_id, export_id)
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ScheduledExportRun>(resp).await
+  }
+
+  /// Exports the documents of a space as a zip archive of Markdown or JSON files.
+  pub async fn export_space(
+    &self,
+    workspace_id: Uuid,
+    space_id: Uuid,
+    format: ExportFormat,
+  ) -> Result<Bytes, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/spaces/{}/export",
+      self.base_url, workspace_id, space_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&ExportSpaceParams { format })
+      .send()
+      .await?;
+    let bytes = resp.error_for_status()?.bytes().await?;
+    if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
+      return Err(app_err);
+    }
+    Ok(bytes)
+  }
+}
@@ -0,0 +1,101 @@
+use client_api_entity::{
+  CreateWorkspaceSnippetParams, ExpandWorkspaceSnippetParams, ExpandedWorkspaceSnippet,
+  ListWorkspaceSnippetsQueryParams, UpdateWorkspaceSnippetParams, WorkspaceSnippet,
+  WorkspaceSnippets,
+};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+fn snippet_resources_url(base_url: &str, workspace_id: Uuid) -> String {
+  format!("{base_url}/api/workspace/{workspace_id}/snippets")
+}
+
+fn snippet_resource_url(base_url: &str, workspace_id: Uuid, snippet_id: Uuid) -> String {
+  let snippet_resources_prefix = snippet_resources_url(base_url, workspace_id);
+  format!("{snippet_resources_prefix}/{snippet_id}")
+}
+
+// Workspace Snippet API
+impl Client {
+  pub async fn create_workspace_snippet(
+    &self,
+    workspace_id: Uuid,
+    params: &CreateWorkspaceSnippetParams,
+  ) -> Result<WorkspaceSnippet, AppResponseError> {
+    let url = snippet_resources_url(&self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<WorkspaceSnippet>(resp).await
+  }
+
+  pub async fn list_workspace_snippets(
+    &self,
+    workspace_id: Uuid,
+    prefix: Option<String>,
+  ) -> Result<WorkspaceSnippets, AppResponseError> {
+    let url = snippet_resources_url(&self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&ListWorkspaceSnippetsQueryParams { prefix })
+      .send()
+      .await?;
+    process_response_data::<WorkspaceSnippets>(resp).await
+  }
+
+  pub async fn update_workspace_snippet(
+    &self,
+    workspace_id: Uuid,
+    snippet_id: Uuid,
+    params: &UpdateWorkspaceSnippetParams,
+  ) -> Result<WorkspaceSnippet, AppResponseError> {
+    let url = snippet_resource_url(&self.base_url, workspace_id, snippet_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<WorkspaceSnippet>(resp).await
+  }
+
+  pub async fn delete_workspace_snippet(
+    &self,
+    workspace_id: Uuid,
+    snippet_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = snippet_resource_url(&self.base_url, workspace_id, snippet_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  /// Content of the snippet with its placeholders filled in, ready to be inserted.
+  pub async fn expand_workspace_snippet(
+    &self,
+    workspace_id: Uuid,
+    params: &ExpandWorkspaceSnippetParams,
+  ) -> Result<ExpandedWorkspaceSnippet, AppResponseError> {
+    let url = format!(
+      "{}/expand",
+      snippet_resources_url(&self.base_url, workspace_id)
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<ExpandedWorkspaceSnippet>(resp).await
+  }
+}
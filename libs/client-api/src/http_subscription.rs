@@ -0,0 +1,105 @@
+use crate::{process_response_data, process_response_error, Client};
+use client_api_entity::subscription_dto::{
+  CancelSubscriptionRequest, SubscribeRequest, SubscriptionCurrentResponse, SubscriptionPlanInfo,
+  SubscriptionUsageQuery, SubscriptionUsageResponse, UsageRecordRequest, UserSubscriptionRecord,
+};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use tracing::instrument;
+
+impl Client {
+  /// All subscription plans, including the ones that are no longer offered.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_subscription_plans(
+    &self,
+  ) -> Result<Vec<SubscriptionPlanInfo>, AppResponseError> {
+    let url = format!("{}/api/subscription/plans", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<SubscriptionPlanInfo>>(resp).await
+  }
+
+  /// The subscription of the current user. Users without one are put on the free plan.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_current_subscription(
+    &self,
+  ) -> Result<SubscriptionCurrentResponse, AppResponseError> {
+    let url = format!("{}/api/subscription/current", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<SubscriptionCurrentResponse>(resp).await
+  }
+
+  /// Switches the current user to the plan. Downgrades keep the resources above the limits of
+  /// the new plan during a grace period.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn subscribe_plan(
+    &self,
+    params: &SubscribeRequest,
+  ) -> Result<SubscriptionCurrentResponse, AppResponseError> {
+    let url = format!("{}/api/subscription/subscribe", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<SubscriptionCurrentResponse>(resp).await
+  }
+
+  /// Cancels the subscription of the current user. Not to be confused with
+  /// [Client::cancel_subscription], which cancels a workspace subscription on the billing service.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn cancel_user_subscription(
+    &self,
+    params: &CancelSubscriptionRequest,
+  ) -> Result<UserSubscriptionRecord, AppResponseError> {
+    let url = format!("{}/api/subscription/cancel", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<UserSubscriptionRecord>(resp).await
+  }
+
+  /// Usage of the current user against the limits of their plan, including the usage covered by
+  /// addons.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_subscription_usage(
+    &self,
+    query: &SubscriptionUsageQuery,
+  ) -> Result<SubscriptionUsageResponse, AppResponseError> {
+    let url = format!("{}/api/subscription/usage", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<SubscriptionUsageResponse>(resp).await
+  }
+
+  /// Adds to the usage of the current user on the given day, today if none is given.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn record_subscription_usage(
+    &self,
+    params: &UsageRecordRequest,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/subscription/usage/record", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+}
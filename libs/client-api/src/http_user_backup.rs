@@ -0,0 +1,67 @@
+use app_error::AppError;
+use client_api_entity::{PutUserBackupQuery, UserBackup, UserBackupInfo};
+use reqwest::{header, Method, StatusCode};
+use shared_entity::response::AppResponseError;
+
+use crate::{process_response_data, process_response_error, Client};
+
+// User Backup API
+impl Client {
+  /// Stores `data` as the backup of the user. The data must be encrypted by the client, the
+  /// server keeps it as is. Pass the version last read as `expected_version` to fail instead of
+  /// overwriting a backup written by another device since, 0 when no backup was found.
+  pub async fn put_user_backup(
+    &self,
+    data: Vec<u8>,
+    expected_version: Option<i64>,
+  ) -> Result<UserBackupInfo, AppResponseError> {
+    let url = format!("{}/api/user/backup", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .query(&PutUserBackupQuery { expected_version })
+      .header(header::CONTENT_TYPE, "application/octet-stream")
+      .body(data)
+      .send()
+      .await?;
+    process_response_data::<UserBackupInfo>(resp).await
+  }
+
+  /// Reads the backup of the user. Returns `None` when `known_version` is still the latest
+  /// version, so that the backup is only downloaded when it changed.
+  pub async fn get_user_backup(
+    &self,
+    known_version: Option<i64>,
+  ) -> Result<Option<UserBackup>, AppResponseError> {
+    let url = format!("{}/api/user/backup", self.base_url);
+    let mut builder = self.http_client_with_auth(Method::GET, &url).await?;
+    if let Some(version) = known_version {
+      builder = builder.header(header::IF_NONE_MATCH, format!("\"{}\"", version));
+    }
+    let resp = builder.send().await?;
+    match resp.status() {
+      StatusCode::NOT_MODIFIED => Ok(None),
+      StatusCode::OK => {
+        let version = resp
+          .headers()
+          .get(header::ETAG)
+          .and_then(|value| value.to_str().ok())
+          .and_then(|value| value.trim_matches('"').parse::<i64>().ok())
+          .ok_or_else(|| {
+            AppResponseError::from(AppError::Unhandled(
+              "backup response has no version".to_string(),
+            ))
+          })?;
+        let data = resp.bytes().await?.to_vec();
+        Ok(Some(UserBackup { version, data }))
+      },
+      status => {
+        process_response_error(resp).await?;
+        Err(AppResponseError::from(AppError::Unhandled(format!(
+          "unexpected status code: {}",
+          status
+        ))))
+      },
+    }
+  }
+}
@@ -1,9 +1,11 @@
 use client_api_entity::workspace_dto::{
-  AddRecentPagesParams, AppendBlockToPageParams, CreateFolderViewParams,
-  CreatePageDatabaseViewParams, CreatePageParams, CreateSpaceParams, DuplicatePageParams,
-  FavoritePageParams, MovePageParams, Page, PageCollab, PublishPageParams, Space,
-  UpdatePageExtraParams, UpdatePageIconParams, UpdatePageNameParams, UpdatePageParams,
-  UpdateSpaceParams,
+  AddRecentPagesParams, AppendBlockToPageParams, ArchivedSpace, BatchGetPageViewParams,
+  CreateFolderViewParams, CreatePageDatabaseViewParams, CreatePageParams, CreateSpaceParams,
+  DocumentChunk, DocumentOutline, DuplicatePageParams, FavoritePageParams, MovePageParams, Page,
+  PageActivityFeed, PageCollab, PageReactionCount, PageReactionParams, PageViewBatchItem,
+  PageWatchStatus, PublishPageParams, QueryDocumentChunk, QueryPageActivity,
+  ReorderPageViewsParams, Space, UpdatePageExtraParams, UpdatePageIconParams, UpdatePageNameParams,
+  UpdatePageParams, UpdateSpaceParams,
 };
 use reqwest::Method;
 use serde_json::json;
@@ -84,6 +86,79 @@ impl Client {
     process_response_error(resp).await
   }
 
+  pub async fn reorder_workspace_page_views(
+    &self,
+    workspace_id: Uuid,
+    params: &ReorderPageViewsParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/reorder",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn get_workspace_page_reactions(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+  ) -> Result<Vec<PageReactionCount>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/reaction",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<PageReactionCount>>(resp).await
+  }
+
+  pub async fn add_workspace_page_reaction(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    params: &PageReactionParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/reaction",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn remove_workspace_page_reaction(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    params: &PageReactionParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/reaction",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
   pub async fn move_workspace_page_view_to_trash(
     &self,
     workspace_id: Uuid,
@@ -225,6 +300,136 @@ impl Client {
     process_response_data::<PageCollab>(resp).await
   }
 
+  /// Loads several views in one request. Views that fail to load carry an error instead of
+  /// failing the whole batch.
+  pub async fn batch_get_workspace_page_views(
+    &self,
+    workspace_id: Uuid,
+    view_ids: Vec<Uuid>,
+  ) -> Result<Vec<PageViewBatchItem>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-views/batch",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&BatchGetPageViewParams { view_ids })
+      .send()
+      .await?;
+    process_response_data::<Vec<PageViewBatchItem>>(resp).await
+  }
+
+  /// Follows the page to be notified about its edits and comments.
+  pub async fn watch_page(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+  ) -> Result<PageWatchStatus, AppResponseError> {
+    self
+      .send_page_watch(Method::PUT, workspace_id, view_id)
+      .await
+  }
+
+  pub async fn unwatch_page(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+  ) -> Result<PageWatchStatus, AppResponseError> {
+    self
+      .send_page_watch(Method::DELETE, workspace_id, view_id)
+      .await
+  }
+
+  pub async fn get_page_watch_status(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+  ) -> Result<PageWatchStatus, AppResponseError> {
+    self
+      .send_page_watch(Method::GET, workspace_id, view_id)
+      .await
+  }
+
+  async fn send_page_watch(
+    &self,
+    method: Method,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+  ) -> Result<PageWatchStatus, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/watch",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(method, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<PageWatchStatus>(resp).await
+  }
+
+  /// Loads the activity feed of a page, most recent first. Pass the time of the oldest activity
+  /// received as `before` to load the next page of the feed.
+  pub async fn get_workspace_page_activity(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    query: &QueryPageActivity,
+  ) -> Result<PageActivityFeed, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/activity",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<PageActivityFeed>(resp).await
+  }
+
+  /// Loads a block range of a document. Small documents are always returned in one chunk.
+  /// The blocks are meant for display only. To edit the document, open it through the realtime
+  /// connection, which syncs the whole document.
+  pub async fn get_workspace_page_view_chunk(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+    query: &QueryDocumentChunk,
+  ) -> Result<DocumentChunk, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/chunk",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<DocumentChunk>(resp).await
+  }
+
+  /// Loads the nested headings of a document, with an anchor for each heading.
+  pub async fn get_workspace_page_view_outline(
+    &self,
+    workspace_id: Uuid,
+    view_id: &Uuid,
+  ) -> Result<DocumentOutline, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/outline",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<DocumentOutline>(resp).await
+  }
+
   pub async fn publish_page(
     &self,
     workspace_id: Uuid,
@@ -296,6 +501,56 @@ impl Client {
     process_response_error(resp).await
   }
 
+  pub async fn archive_space(
+    &self,
+    workspace_id: Uuid,
+    space_id: &Uuid,
+  ) -> Result<ArchivedSpace, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/spaces/{}/archive",
+      self.base_url, workspace_id, space_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ArchivedSpace>(resp).await
+  }
+
+  pub async fn unarchive_space(
+    &self,
+    workspace_id: Uuid,
+    space_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/spaces/{}/archive",
+      self.base_url, workspace_id, space_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn list_archived_spaces(
+    &self,
+    workspace_id: Uuid,
+  ) -> Result<Vec<ArchivedSpace>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/archived-spaces",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<Vec<ArchivedSpace>>(resp).await
+  }
+
   pub async fn update_page_name(
     &self,
     workspace_id: Uuid,
@@ -0,0 +1,74 @@
+use client_api_entity::{ResolvedViewSlug, SetViewSlugParams, ViewSlug};
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+fn view_slug_url(base_url: &str, workspace_id: Uuid, view_id: Uuid) -> String {
+  format!("{base_url}/api/workspace/{workspace_id}/page-view/{view_id}/slug")
+}
+
+// View Slug API
+impl Client {
+  pub async fn get_view_slug(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+  ) -> Result<ViewSlug, AppResponseError> {
+    let url = view_slug_url(&self.base_url, workspace_id, view_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ViewSlug>(resp).await
+  }
+
+  pub async fn set_view_slug(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    params: &SetViewSlugParams,
+  ) -> Result<ViewSlug, AppResponseError> {
+    let url = view_slug_url(&self.base_url, workspace_id, view_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send()
+      .await?;
+    process_response_data::<ViewSlug>(resp).await
+  }
+
+  pub async fn delete_view_slug(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = view_slug_url(&self.base_url, workspace_id, view_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+
+  pub async fn resolve_view_slug(
+    &self,
+    workspace_id: Uuid,
+    slug: &str,
+  ) -> Result<ResolvedViewSlug, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/slug/{}",
+      self.base_url, workspace_id, slug
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send()
+      .await?;
+    process_response_data::<ResolvedViewSlug>(resp).await
+  }
+}
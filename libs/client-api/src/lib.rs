@@ -12,6 +12,8 @@ pub(crate) use yrs_impl as yrs;
 
 mod http;
 mod http_ai;
+mod http_api_key;
+mod http_device;
 mod http_billing;
 
 mod http_access_request;
@@ -22,9 +24,14 @@ mod http_member;
 mod http_person;
 pub mod http_publish;
 mod http_quick_note;
+mod http_scheduled_export;
 mod http_search;
+mod http_snippet;
+mod http_subscription;
 mod http_template;
+mod http_user_backup;
 mod http_view;
+mod http_view_slug;
 pub use http::*;
 
 pub mod collab_sync;
@@ -32,6 +39,7 @@ pub mod collab_sync;
 mod http_chat;
 mod http_file;
 mod http_settings;
+pub mod notifications;
 pub mod notify;
 mod ping;
 mod retry;
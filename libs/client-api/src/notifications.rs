@@ -0,0 +1,405 @@
+//! The notification inbox of the user, kept in sync with the server.
+//!
+//! [NotificationFeed] merges the notifications listed over HTTP with the ones pushed over the
+//! realtime connection. A notification delivered both ways is only reported once, and reading
+//! notifications on one device marks them as read on the other devices of the user.
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use appflowy_proto::WorkspaceNotification;
+use chrono::{DateTime, Utc};
+use client_api_entity::{
+  ListUserNotificationsQuery, NotificationsRead, UserNotification, UserNotifications,
+  NOTIFICATIONS_READ_NOTIFICATION, UNLISTED_NOTIFICATIONS,
+};
+use futures::stream::{self, Stream};
+use parking_lot::Mutex;
+use reqwest::Method;
+use shared_entity::response::AppResponseError;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{process_response_data, process_response_error, Client};
+
+impl Client {
+  pub async fn list_inbox_notifications(
+    &self,
+    query: &ListUserNotificationsQuery,
+  ) -> Result<UserNotifications, AppResponseError> {
+    let url = format!("{}/api/user/notifications/inbox", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send()
+      .await?;
+    process_response_data::<UserNotifications>(resp).await
+  }
+
+  /// Marks the notifications as read on all the devices of the user.
+  pub async fn mark_notifications_read(
+    &self,
+    notification_ids: Vec<Uuid>,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/notifications/read", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&NotificationsRead { notification_ids })
+      .send()
+      .await?;
+    process_response_error(resp).await
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationEvent {
+  /// A notification that was not in the feed yet
+  Received(UserNotification),
+  /// Notifications of the feed that were read, possibly on another device
+  Read { notification_ids: Vec<Uuid> },
+}
+
+#[derive(Clone)]
+pub struct NotificationFeed {
+  client: Client,
+  state: Arc<Mutex<FeedState>>,
+}
+
+impl NotificationFeed {
+  pub fn new(client: Client) -> Self {
+    Self {
+      client,
+      state: Default::default(),
+    }
+  }
+
+  /// The notifications of the feed, most recent first.
+  pub fn notifications(&self) -> Vec<UserNotification> {
+    self.state.lock().sorted()
+  }
+
+  pub fn unread_count(&self) -> usize {
+    self.state.lock().unread_count()
+  }
+
+  /// Loads the most recent notifications. Returns what changed in the feed.
+  pub async fn refresh(&self) -> Result<Vec<NotificationEvent>, AppResponseError> {
+    let page = self
+      .client
+      .list_inbox_notifications(&ListUserNotificationsQuery::default())
+      .await?;
+    Ok(self.state.lock().apply_page(page.notifications))
+  }
+
+  /// Loads the page of notifications older than the ones in the feed. Returns what changed in
+  /// the feed, and whether there are even older notifications.
+  pub async fn load_more(&self) -> Result<(Vec<NotificationEvent>, bool), AppResponseError> {
+    let before = self.state.lock().oldest_created_at();
+    let page = self
+      .client
+      .list_inbox_notifications(&ListUserNotificationsQuery {
+        before,
+        limit: None,
+      })
+      .await?;
+    let events = self.state.lock().apply_page(page.notifications);
+    Ok((events, page.has_more))
+  }
+
+  /// Marks the notifications as read, here and on the other devices of the user.
+  pub async fn mark_read(&self, notification_ids: Vec<Uuid>) -> Result<(), AppResponseError> {
+    self
+      .client
+      .mark_notifications_read(notification_ids.clone())
+      .await?;
+    self.state.lock().mark_read(&notification_ids);
+    Ok(())
+  }
+
+  pub async fn mark_all_read(&self) -> Result<(), AppResponseError> {
+    let notification_ids = self.state.lock().unread_ids();
+    if notification_ids.is_empty() {
+      return Ok(());
+    }
+    self.mark_read(notification_ids).await
+  }
+
+  /// Applies a notification pushed over the realtime connection.
+  pub fn apply_live(&self, notification: WorkspaceNotification) -> Option<NotificationEvent> {
+    match LiveUpdate::from_workspace_notification(notification)? {
+      LiveUpdate::Notification(notification) => self.state.lock().insert(notification),
+      LiveUpdate::Read(notification_ids) => self.state.lock().mark_read(&notification_ids),
+    }
+  }
+
+  /// The changes of the feed: first the most recent notifications, then the ones pushed over
+  /// `live`. The receiver should be subscribed before calling this, so that nothing pushed
+  /// while the notifications are loaded is missed. When the receiver lags behind, the most
+  /// recent notifications are loaded again. The stream ends when `live` is closed.
+  pub fn events(
+    &self,
+    live: broadcast::Receiver<WorkspaceNotification>,
+  ) -> impl Stream<Item = NotificationEvent> {
+    let feed = self.clone();
+    stream::unfold(
+      (feed, live, VecDeque::new(), true),
+      |(feed, mut live, mut pending, mut needs_refresh)| async move {
+        loop {
+          if let Some(event) = pending.pop_front() {
+            return Some((event, (feed, live, pending, needs_refresh)));
+          }
+          if needs_refresh {
+            needs_refresh = false;
+            match feed.refresh().await {
+              Ok(events) => pending.extend(events),
+              Err(err) => warn!("failed to load notifications: {}", err),
+            }
+            continue;
+          }
+          match live.recv().await {
+            Ok(notification) => pending.extend(feed.apply_live(notification)),
+            Err(RecvError::Lagged(skipped)) => {
+              warn!("notification feed skipped {} live notifications", skipped);
+              needs_refresh = true;
+            },
+            Err(RecvError::Closed) => return None,
+          }
+        }
+      },
+    )
+  }
+}
+
+enum LiveUpdate {
+  Notification(UserNotification),
+  Read(Vec<Uuid>),
+}
+
+impl LiveUpdate {
+  fn from_workspace_notification(notification: WorkspaceNotification) -> Option<Self> {
+    let WorkspaceNotification::SystemNotification {
+      id,
+      workspace_id,
+      notification_type,
+      title,
+      message,
+      payload_json,
+      created_at,
+      ..
+    } = notification
+    else {
+      return None;
+    };
+    if notification_type == NOTIFICATIONS_READ_NOTIFICATION {
+      let read = serde_json::from_str::<NotificationsRead>(&payload_json).ok()?;
+      return Some(LiveUpdate::Read(read.notification_ids));
+    }
+    if UNLISTED_NOTIFICATIONS.contains(&notification_type.as_str()) {
+      return None;
+    }
+    Some(LiveUpdate::Notification(UserNotification {
+      id: Uuid::parse_str(&id).ok()?,
+      workspace_id: Uuid::parse_str(&workspace_id).ok(),
+      notification_type,
+      title,
+      message,
+      payload: serde_json::from_str(&payload_json).unwrap_or_default(),
+      created_at: DateTime::from_timestamp(created_at, 0).unwrap_or_else(Utc::now),
+      read: false,
+    }))
+  }
+}
+
+#[derive(Default)]
+struct FeedState {
+  notifications: HashMap<Uuid, UserNotification>,
+  /// Notifications read on another device before they were loaded in the feed
+  read_ahead: HashSet<Uuid>,
+}
+
+impl FeedState {
+  fn insert(&mut self, mut notification: UserNotification) -> Option<NotificationEvent> {
+    if self.read_ahead.remove(&notification.id) {
+      notification.read = true;
+    }
+    match self.notifications.entry(notification.id) {
+      Entry::Occupied(mut entry) => {
+        if notification.read && !entry.get().read {
+          entry.get_mut().read = true;
+          Some(NotificationEvent::Read {
+            notification_ids: vec![notification.id],
+          })
+        } else {
+          None
+        }
+      },
+      Entry::Vacant(entry) => {
+        entry.insert(notification.clone());
+        Some(NotificationEvent::Received(notification))
+      },
+    }
+  }
+
+  /// Applies a page listed most recent first, reporting the new notifications oldest first.
+  fn apply_page(&mut self, notifications: Vec<UserNotification>) -> Vec<NotificationEvent> {
+    notifications
+      .into_iter()
+      .rev()
+      .filter_map(|notification| self.insert(notification))
+      .collect()
+  }
+
+  fn mark_read(&mut self, notification_ids: &[Uuid]) -> Option<NotificationEvent> {
+    let mut read_ids = vec![];
+    for id in notification_ids {
+      match self.notifications.get_mut(id) {
+        Some(notification) if !notification.read => {
+          notification.read = true;
+          read_ids.push(*id);
+        },
+        Some(_) => {},
+        None => {
+          self.read_ahead.insert(*id);
+        },
+      }
+    }
+    if read_ids.is_empty() {
+      None
+    } else {
+      Some(NotificationEvent::Read {
+        notification_ids: read_ids,
+      })
+    }
+  }
+
+  fn sorted(&self) -> Vec<UserNotification> {
+    let mut notifications: Vec<_> = self.notifications.values().cloned().collect();
+    notifications.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    notifications
+  }
+
+  fn unread_count(&self) -> usize {
+    self.notifications.values().filter(|n| !n.read).count()
+  }
+
+  fn unread_ids(&self) -> Vec<Uuid> {
+    self
+      .notifications
+      .values()
+      .filter(|n| !n.read)
+      .map(|n| n.id)
+      .collect()
+  }
+
+  fn oldest_created_at(&self) -> Option<DateTime<Utc>> {
+    self.notifications.values().map(|n| n.created_at).min()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn notification(created_at: i64) -> UserNotification {
+    UserNotification {
+      id: Uuid::new_v4(),
+      workspace_id: None,
+      notification_type: "mention".to_string(),
+      title: "title".to_string(),
+      message: "message".to_string(),
+      payload: serde_json::json!({}),
+      created_at: DateTime::from_timestamp(created_at, 0).unwrap(),
+      read: false,
+    }
+  }
+
+  fn live(notification: &UserNotification) -> WorkspaceNotification {
+    WorkspaceNotification::SystemNotification {
+      id: notification.id.to_string(),
+      workspace_id: String::new(),
+      notification_type: notification.notification_type.clone(),
+      title: notification.title.clone(),
+      message: notification.message.clone(),
+      payload_json: notification.payload.to_string(),
+      created_at: notification.created_at.timestamp(),
+      recipient_uid: 1,
+    }
+  }
+
+  #[test]
+  fn notifications_delivered_twice_are_reported_once() {
+    let mut state = FeedState::default();
+    let first = notification(1);
+    let second = notification(2);
+    let LiveUpdate::Notification(pushed) =
+      LiveUpdate::from_workspace_notification(live(&second)).unwrap()
+    else {
+      panic!("expected a notification");
+    };
+    assert!(state.insert(pushed).is_some());
+
+    let events = state.apply_page(vec![second.clone(), first.clone()]);
+    assert_eq!(events, vec![NotificationEvent::Received(first.clone())]);
+    assert_eq!(
+      state.sorted().iter().map(|n| n.id).collect::<Vec<_>>(),
+      vec![second.id, first.id]
+    );
+  }
+
+  #[test]
+  fn read_state_follows_other_devices() {
+    let mut state = FeedState::default();
+    let loaded = notification(1);
+    let not_loaded = notification(2);
+    state.insert(loaded.clone());
+
+    let read = WorkspaceNotification::SystemNotification {
+      id: Uuid::new_v4().to_string(),
+      workspace_id: String::new(),
+      notification_type: NOTIFICATIONS_READ_NOTIFICATION.to_string(),
+      title: String::new(),
+      message: String::new(),
+      payload_json: serde_json::to_string(&NotificationsRead {
+        notification_ids: vec![loaded.id, not_loaded.id],
+      })
+      .unwrap(),
+      created_at: 3,
+      recipient_uid: 1,
+    };
+    let Some(LiveUpdate::Read(ids)) = LiveUpdate::from_workspace_notification(read) else {
+      panic!("expected read notifications");
+    };
+    assert_eq!(
+      state.mark_read(&ids),
+      Some(NotificationEvent::Read {
+        notification_ids: vec![loaded.id],
+      })
+    );
+    assert_eq!(state.mark_read(&ids), None);
+    assert_eq!(state.unread_count(), 0);
+
+    // read before it was loaded
+    let Some(NotificationEvent::Received(received)) = state.insert(not_loaded) else {
+      panic!("expected a new notification");
+    };
+    assert!(received.read);
+    assert_eq!(state.unread_count(), 0);
+  }
+
+  #[test]
+  fn read_state_is_updated_on_refresh() {
+    let mut state = FeedState::default();
+    let mut notification = notification(1);
+    state.insert(notification.clone());
+    notification.read = true;
+    assert_eq!(
+      state.apply_page(vec![notification.clone()]),
+      vec![NotificationEvent::Read {
+        notification_ids: vec![notification.id],
+      }]
+    );
+  }
+}
@@ -714,7 +714,15 @@ impl WorkspaceControllerActor {
         // forces a reload from the server's authoritative state. For a downgrade
         // this is what prevents edits made during a stale-permission window from
         // being replayed to other collaborators once write access is restored.
-        if can_read {
+        if can_read && can_write {
+          // The access didn't change but an update was refused, e.g. for exceeding the
+          // structure limits of the server: the local state diverged from the server's.
+          tracing::warn!(
+            "update of {} rejected - reason: {}; resetting local collab state",
+            object_id,
+            reason
+          );
+        } else if can_read {
           tracing::warn!(
             "access downgraded to read-only for {} (can_write={}) - reason: {}; \
              resetting local collab state to discard un-synced edits",
@@ -34,7 +34,7 @@ impl CollabUpdateSink {
       &self.object_id,
       self.collab_type,
       &msg.sender,
-      msg.data.clone(),
+      &msg.data,
       msg.flags,
     )
     .query_async(&mut *lock)
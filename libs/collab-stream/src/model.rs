@@ -491,7 +491,7 @@ impl UpdateStreamMessage {
     object_id: &Uuid,
     collab_type: CollabType,
     sender: &CollabOrigin,
-    update: Vec<u8>,
+    update: &[u8],
     flag: UpdateFlags,
   ) -> Cmd {
     let mut cmd = cmd("XADD");
@@ -505,7 +505,7 @@ impl UpdateStreamMessage {
       .arg("sender")
       .arg(sender.to_string())
       .arg("data")
-      .arg(update.to_vec())
+      .arg(update)
       .arg("flags")
       .arg(flag);
     cmd
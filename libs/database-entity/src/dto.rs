@@ -718,6 +718,20 @@ pub struct AFWorkspaceSettings {
 
   #[serde(default)]
   pub only_owner_can_create_team_workspace: bool,
+
+  /// Mark mentions of trashed or deleted pages and removed members inside documents when
+  /// the workspace is scanned for dead references.
+  #[serde(default)]
+  pub auto_annotate_dead_references: bool,
+
+  /// Who may publish and unpublish pages of the workspace.
+  #[serde(default)]
+  pub publish_permission: PublishPermission,
+
+  /// Members allowed to publish when `publish_permission` is [PublishPermission::Allowlist].
+  /// Owners can always publish.
+  #[serde(default)]
+  pub publish_allowlist: Vec<i64>,
 }
 
 impl Default for AFWorkspaceSettings {
@@ -726,10 +740,24 @@ impl Default for AFWorkspaceSettings {
       disable_search_indexing: false,
       ai_model: "Auto".to_string(),
       only_owner_can_create_team_workspace: true,
+      auto_annotate_dead_references: false,
+      publish_permission: PublishPermission::default(),
+      publish_allowlist: vec![],
     }
   }
 }
 
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishPermission {
+  /// Every member of the workspace
+  #[default]
+  Members,
+  Owners,
+  /// Owners and the members of the allowlist
+  Allowlist,
+}
+
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct AFWorkspaceSettingsChange {
   #[serde(skip_serializing_if = "Option::is_none")]
@@ -738,6 +766,12 @@ pub struct AFWorkspaceSettingsChange {
   pub ai_model: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub only_owner_can_create_team_workspace: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub auto_annotate_dead_references: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publish_permission: Option<PublishPermission>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub publish_allowlist: Option<Vec<i64>>,
 }
 
 impl AFWorkspaceSettingsChange {
@@ -746,6 +780,9 @@ impl AFWorkspaceSettingsChange {
       disable_search_indexing: None,
       ai_model: None,
       only_owner_can_create_team_workspace: None,
+      auto_annotate_dead_references: None,
+      publish_permission: None,
+      publish_allowlist: None,
     }
   }
   pub fn disable_search_indexing(mut self, disable_search_indexing: bool) -> Self {
@@ -760,6 +797,18 @@ impl AFWorkspaceSettingsChange {
     self.only_owner_can_create_team_workspace = Some(only_owner_can_create_team_workspace);
     self
   }
+  pub fn auto_annotate_dead_references(mut self, auto_annotate_dead_references: bool) -> Self {
+    self.auto_annotate_dead_references = Some(auto_annotate_dead_references);
+    self
+  }
+  pub fn publish_permission(mut self, publish_permission: PublishPermission) -> Self {
+    self.publish_permission = Some(publish_permission);
+    self
+  }
+  pub fn publish_allowlist(mut self, publish_allowlist: Vec<i64>) -> Self {
+    self.publish_allowlist = Some(publish_allowlist);
+    self
+  }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -777,6 +826,17 @@ pub struct AFWorkspaceMember {
   pub role: AFRole,
   pub avatar_url: Option<String>,
   pub joined_at: Option<DateTime<Utc>>,
+  #[serde(default)]
+  pub status: Option<WorkspaceMemberStatus>,
+}
+
+/// Availability status of a workspace member, e.g. "🌴 On vacation" until a given time.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub struct WorkspaceMemberStatus {
+  pub emoji: Option<String>,
+  pub text: String,
+  /// The status is cleared automatically after this time. Kept until cleared when `None`.
+  pub until: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -899,6 +959,9 @@ pub struct PatchPublishedCollab {
   pub publish_name: Option<String>,
   pub comments_enabled: Option<bool>,
   pub duplicate_enabled: Option<bool>,
+  /// Whether the publisher receives the weekly stats summary of the view.
+  #[serde(default)]
+  pub stats_digest_enabled: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -943,6 +1006,20 @@ pub struct DeleteGlobalCommentParams {
   pub comment_id: Uuid,
 }
 
+/// A reply to a comment notification email, as forwarded by the inbound mail provider.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InboundCommentReplyMail {
+  /// Address the reply was sent to, which carries the signed reply token
+  pub recipient: String,
+  pub sender: String,
+  /// Whether the provider authenticated the domain of the sender, with DKIM or SPF aligned with
+  /// the sender address. Replies from senders that were not authenticated are rejected.
+  #[serde(default)]
+  pub sender_authenticated: bool,
+  /// Plain text body of the email, quoted text included
+  pub text: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Reactions {
   pub reactions: Vec<Reaction>,
@@ -1298,9 +1375,14 @@ pub struct QuickNotes {
   pub has_more: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct CreateQuickNoteParams {
   pub data: Option<serde_json::Value>,
+  /// When a note with the same text, ignoring whitespace, was created by the user shortly
+  /// before, typically by a retried request, that note is returned instead of creating another
+  /// one. Existing notes are never modified.
+  #[serde(default)]
+  pub dedupe: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1308,13 +1390,479 @@ pub struct UpdateQuickNoteParams {
   pub data: serde_json::Value,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ListQuickNotesQueryParams {
   pub search_term: Option<String>,
+  /// Only notes created at or after this time
+  pub created_after: Option<DateTime<Utc>>,
+  /// Only notes created before this time
+  pub created_before: Option<DateTime<Utc>>,
   pub offset: Option<i32>,
   pub limit: Option<i32>,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WorkspaceSnippet {
+  pub snippet_id: Uuid,
+  pub shortcut: String,
+  pub name: String,
+  pub description: Option<String>,
+  /// Blocks inserted by the snippet, in the same format as the data of a [QuickNote]
+  pub content: serde_json::Value,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub last_updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WorkspaceSnippets {
+  pub snippets: Vec<WorkspaceSnippet>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateWorkspaceSnippetParams {
+  pub shortcut: String,
+  pub name: String,
+  pub description: Option<String>,
+  pub content: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UpdateWorkspaceSnippetParams {
+  pub shortcut: Option<String>,
+  pub name: Option<String>,
+  pub description: Option<String>,
+  pub content: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListWorkspaceSnippetsQueryParams {
+  /// Only snippets whose shortcut starts with the prefix, as typed in the slash menu
+  pub prefix: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExpandWorkspaceSnippetParams {
+  pub shortcut: String,
+  /// Values of the `{{name}}` placeholders of the snippet, on top of the built-in `date`,
+  /// `user_name` and `workspace_name`
+  #[serde(default)]
+  pub variables: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExpandedWorkspaceSnippet {
+  pub snippet_id: Uuid,
+  pub shortcut: String,
+  pub content: serde_json::Value,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ViewSlug {
+  pub view_id: Uuid,
+  pub slug: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetViewSlugParams {
+  pub slug: String,
+}
+
+/// The view a slug points to. `redirected` is set when the slug is a previous slug of the view,
+/// in which case clients should replace it with the current `slug` in the URL.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ResolvedViewSlug {
+  pub view_id: Uuid,
+  pub slug: String,
+  pub redirected: bool,
+}
+
+/// How the documents of an export bundle are written.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+  /// One Markdown file per document
+  #[default]
+  Markdown,
+  /// One JSON file per document, holding its blocks, their children and their text. Code
+  /// blocks without a language get the one detected from their code.
+  Json,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct ExportSpaceParams {
+  #[serde(default)]
+  pub format: ExportFormat,
+}
+
+/// Storage receiving the bundles of a [ScheduledExport]. The credential of the destination is
+/// never part of it: it is only accepted when creating or updating the export.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledExportDestination {
+  /// Any S3 compatible bucket. The credential is the secret access key.
+  S3 {
+    /// Defaults to AWS when not set
+    endpoint: Option<String>,
+    region: String,
+    bucket: String,
+    /// Prepended to the object key of the bundles
+    #[serde(default)]
+    prefix: String,
+    access_key_id: String,
+  },
+  /// A WebDAV collection the bundles are uploaded into. The credential is the password.
+  WebDav { url: String, username: String },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScheduledExport {
+  pub export_id: Uuid,
+  pub destination: ScheduledExportDestination,
+  pub interval_days: i32,
+  pub enabled: bool,
+  pub next_run_at: DateTime<Utc>,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub last_updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScheduledExports {
+  pub exports: Vec<ScheduledExport>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateScheduledExportParams {
+  pub destination: ScheduledExportDestination,
+  pub secret: String,
+  /// Defaults to a weekly export
+  pub interval_days: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UpdateScheduledExportParams {
+  /// Replacing the destination requires the secret of the new destination as well
+  pub destination: Option<ScheduledExportDestination>,
+  pub secret: Option<String>,
+  pub interval_days: Option<i32>,
+  pub enabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(i16)]
+pub enum ScheduledExportRunStatus {
+  Running = 0,
+  Succeeded = 1,
+  Failed = 2,
+}
+
+impl From<i16> for ScheduledExportRunStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      0 => ScheduledExportRunStatus::Running,
+      1 => ScheduledExportRunStatus::Succeeded,
+      _ => ScheduledExportRunStatus::Failed,
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScheduledExportRun {
+  pub run_id: Uuid,
+  pub export_id: Uuid,
+  pub started_at: DateTime<Utc>,
+  pub finished_at: Option<DateTime<Utc>>,
+  pub status: ScheduledExportRunStatus,
+  pub documents: i32,
+  pub bytes: i64,
+  /// Key or path of the uploaded bundle in the destination
+  pub object_key: Option<String>,
+  pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScheduledExportRuns {
+  pub runs: Vec<ScheduledExportRun>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListScheduledExportRunsQueryParams {
+  pub limit: Option<i64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserApiKey {
+  pub key_id: Uuid,
+  pub name: String,
+  /// First characters of the key, to tell keys apart
+  pub key_prefix: String,
+  pub created_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserApiKeys {
+  pub api_keys: Vec<UserApiKey>,
+}
+
+/// A device of the user that opened a realtime connection.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserDevice {
+  pub device_id: String,
+  pub client_version: Option<String>,
+  pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserDevices {
+  pub devices: Vec<UserDevice>,
+}
+
+/// Metadata of the encrypted backup of a user. The backup itself is opaque to the server.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserBackupInfo {
+  /// Incremented on every write of the backup, starting at 1
+  pub version: i64,
+  pub size: i64,
+  /// Device that wrote the backup last, when it sent its device id
+  pub device_id: Option<String>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PutUserBackupQuery {
+  /// Version of the backup the client last read, 0 when it expects no backup. The write is
+  /// rejected when the backup was changed since. Without it, the backup is always replaced.
+  pub expected_version: Option<i64>,
+}
+
+/// Encrypted backup of a user, as read by a client.
+#[derive(Clone, Debug)]
+pub struct UserBackup {
+  pub version: i64,
+  pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[repr(i16)]
+pub enum DeviceHandoffKind {
+  Link = 0,
+  Text = 1,
+}
+
+impl From<i16> for DeviceHandoffKind {
+  fn from(value: i16) -> Self {
+    match value {
+      0 => DeviceHandoffKind::Link,
+      _ => DeviceHandoffKind::Text,
+    }
+  }
+}
+
+/// Content pushed from one device of the user to another. Without `target_device_id`, every
+/// other device of the user receives it.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DeviceHandoffParams {
+  pub target_device_id: Option<String>,
+  pub kind: DeviceHandoffKind,
+  pub content: String,
+  pub title: Option<String>,
+}
+
+/// Content handed off to a device, kept until it expires. The `device_handoff` notification only
+/// carries its `handoff_id`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DeviceHandoff {
+  pub handoff_id: Uuid,
+  pub source_device_id: Option<String>,
+  pub kind: DeviceHandoffKind,
+  pub content: String,
+  pub title: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+/// Handoffs the device has not processed yet.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct DeviceHandoffs {
+  pub handoffs: Vec<DeviceHandoff>,
+}
+
+/// The service delivering the push notifications of a device.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PushPlatform {
+  /// Apple Push Notification service, for iOS devices
+  Apns,
+  /// Firebase Cloud Messaging, for Android devices
+  Fcm,
+}
+
+impl PushPlatform {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PushPlatform::Apns => "apns",
+      PushPlatform::Fcm => "fcm",
+    }
+  }
+}
+
+impl std::str::FromStr for PushPlatform {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "apns" => Ok(PushPlatform::Apns),
+      "fcm" => Ok(PushPlatform::Fcm),
+      other => Err(format!("unknown push platform: {}", other)),
+    }
+  }
+}
+
+/// Registers the push token of the device sending the request, replacing its previous token.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RegisterPushTokenParams {
+  pub platform: PushPlatform,
+  pub token: String,
+}
+
+/// Push notifications to the device are held back between `start_minute` and `end_minute`,
+/// in minutes after midnight in `timezone`, and sent as a summary once the quiet hours end. The
+/// quiet hours span midnight when they end before they start.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+  pub device_id: String,
+  pub start_minute: i32,
+  pub end_minute: i32,
+  /// IANA name of the timezone, e.g. `Asia/Shanghai`
+  pub timezone: String,
+  /// Mentions are still pushed during the quiet hours
+  pub allow_mentions: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SetQuietHoursParams {
+  pub start_minute: i32,
+  pub end_minute: i32,
+  pub timezone: String,
+  #[serde(default)]
+  pub allow_mentions: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserQuietHours {
+  pub quiet_hours: Vec<QuietHours>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(i16)]
+pub enum CollabPermissionSyncStatus {
+  Running = 0,
+  Succeeded = 1,
+  Failed = 2,
+  /// The role of the member changed again before the sync finished
+  Superseded = 3,
+}
+
+impl From<i16> for CollabPermissionSyncStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      0 => CollabPermissionSyncStatus::Running,
+      1 => CollabPermissionSyncStatus::Succeeded,
+      3 => CollabPermissionSyncStatus::Superseded,
+      _ => CollabPermissionSyncStatus::Failed,
+    }
+  }
+}
+
+/// Progress of the recomputation of the collab-level permissions of a member after their
+/// workspace role changed.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CollabPermissionSync {
+  pub sync_id: Uuid,
+  pub uid: i64,
+  pub role: AFRole,
+  pub status: CollabPermissionSyncStatus,
+  pub total_collabs: i32,
+  pub synced_collabs: i32,
+  pub error: Option<String>,
+  pub started_at: DateTime<Utc>,
+  pub finished_at: Option<DateTime<Utc>>,
+}
+
+pub const DEVICE_HANDOFF_NOTIFICATION: &str = "device_handoff";
+/// Type of the notification sent to the other devices of a user when some of their notifications
+/// were read. Its payload is a [NotificationsRead].
+pub const NOTIFICATIONS_READ_NOTIFICATION: &str = "notifications_read";
+/// Notifications that are only delivered to the devices of the user, and never listed in their
+/// notification inbox.
+pub const UNLISTED_NOTIFICATIONS: [&str; 2] =
+  [DEVICE_HANDOFF_NOTIFICATION, NOTIFICATIONS_READ_NOTIFICATION];
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct UserNotification {
+  pub id: Uuid,
+  pub workspace_id: Option<Uuid>,
+  pub notification_type: String,
+  pub title: String,
+  pub message: String,
+  pub payload: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+  pub read: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UserNotifications {
+  pub notifications: Vec<UserNotification>,
+  pub has_more: bool,
+}
+
+/// Notifications are listed from the most recent. `before` is the `created_at` of the last
+/// notification of the previous page.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ListUserNotificationsQuery {
+  pub before: Option<DateTime<Utc>>,
+  pub limit: Option<u32>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NotificationsRead {
+  pub notification_ids: Vec<Uuid>,
+}
+
+/// An update the server did not apply to a collab, for example because the device lost write
+/// access while it was offline. `update_v1` can be applied to a copy of the collab to recover
+/// the content.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CollabConflict {
+  pub conflict_id: Uuid,
+  pub object_id: Uuid,
+  pub collab_type: CollabType,
+  pub device_id: Option<String>,
+  pub reason: String,
+  pub update_v1: Vec<u8>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CollabConflicts {
+  pub conflicts: Vec<CollabConflict>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateUserApiKeyParams {
+  pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreatedUserApiKey {
+  pub api_key: UserApiKey,
+  /// The key itself. It cannot be retrieved again.
+  pub secret: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct WorkspaceInviteCodeParams {
   pub validity_period_hours: Option<i64>,
@@ -1378,6 +1926,7 @@ impl From<MentionableWorkspaceMemberOrGuest> for MentionablePerson {
       custom_image_url: val.custom_image_url,
       description: val.description,
       invited: false,
+      status: None,
     }
   }
 }
@@ -1413,17 +1962,97 @@ impl From<MentionableWorkspaceMemberOrGuestWithLastMentionedTime>
       description: val.description,
       invited: false,
       last_mentioned_at: val.last_mentioned_at,
+      status: None,
     }
   }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct WorkspaceMemberProfile {
   pub name: String,
   pub avatar_url: Option<String>,
   pub cover_image_url: Option<String>,
   pub custom_image_url: Option<String>,
   pub description: Option<String>,
+  #[serde(default)]
+  pub title: Option<String>,
+  #[serde(default)]
+  pub department: Option<String>,
+  #[serde(default)]
+  pub pronouns: Option<String>,
+  #[serde(default)]
+  pub links: Vec<WorkspaceMemberProfileLink>,
+  /// Values of the custom member fields by field id, limited to the fields editable by members.
+  /// Fields that are left out keep their value and an empty value clears it. `None` leaves all
+  /// the custom fields unchanged.
+  #[serde(default)]
+  pub custom_fields: Option<HashMap<Uuid, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMemberProfileLink {
+  pub label: String,
+  pub url: String,
+}
+
+/// A custom attribute of the members of a workspace, defined by its owners.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkspaceMemberField {
+  pub field_id: Uuid,
+  pub name: String,
+  /// Whether members can set their own value, otherwise only owners can
+  pub editable_by_member: bool,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkspaceMemberFields {
+  pub fields: Vec<WorkspaceMemberField>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateWorkspaceMemberFieldParams {
+  pub name: String,
+  #[serde(default)]
+  pub editable_by_member: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UpdateWorkspaceMemberFieldParams {
+  pub name: Option<String>,
+  pub editable_by_member: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QueryWorkspaceDirectory {
+  /// Matches name, email, title and department, case insensitive
+  pub q: Option<String>,
+  pub offset: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkspaceDirectoryPerson {
+  pub uuid: Uuid,
+  pub name: String,
+  pub email: Option<String>,
+  pub role: AFRole,
+  pub avatar_url: Option<String>,
+  pub description: Option<String>,
+  pub title: Option<String>,
+  pub department: Option<String>,
+  pub pronouns: Option<String>,
+  pub links: Vec<WorkspaceMemberProfileLink>,
+  /// Values of the custom member fields by field id
+  #[serde(default)]
+  pub custom_fields: HashMap<Uuid, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkspaceDirectory {
+  pub people: Vec<WorkspaceDirectoryPerson>,
+  /// Number of people matching the query, regardless of offset and limit
+  pub total: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1437,6 +2066,8 @@ pub struct MentionablePerson {
   pub custom_image_url: Option<String>,
   pub description: Option<String>,
   pub invited: bool,
+  #[serde(default)]
+  pub status: Option<WorkspaceMemberStatus>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1451,6 +2082,8 @@ pub struct MentionablePersonWithLastMentionedTime {
   pub description: Option<String>,
   pub invited: bool,
   pub last_mentioned_at: Option<DateTime<Utc>>,
+  #[serde(default)]
+  pub status: Option<WorkspaceMemberStatus>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1510,6 +2143,47 @@ pub struct ProcessedPageMentionNotification {
   pub person_id: Uuid,
 }
 
+/// What happened to a page in its activity feed.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PageActivityKind {
+  Edited,
+  Commented,
+  Moved,
+  Published,
+  Unpublished,
+  PermissionChanged,
+}
+
+impl PageActivityKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PageActivityKind::Edited => "edited",
+      PageActivityKind::Commented => "commented",
+      PageActivityKind::Moved => "moved",
+      PageActivityKind::Published => "published",
+      PageActivityKind::Unpublished => "unpublished",
+      PageActivityKind::PermissionChanged => "permission_changed",
+    }
+  }
+}
+
+impl std::str::FromStr for PageActivityKind {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "edited" => Ok(PageActivityKind::Edited),
+      "commented" => Ok(PageActivityKind::Commented),
+      "moved" => Ok(PageActivityKind::Moved),
+      "published" => Ok(PageActivityKind::Published),
+      "unpublished" => Ok(PageActivityKind::Unpublished),
+      "permission_changed" => Ok(PageActivityKind::PermissionChanged),
+      other => Err(format!("unknown page activity kind: {}", other)),
+    }
+  }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EditCollabMemberParams {
   pub permission_id:i32,
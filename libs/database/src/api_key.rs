@@ -0,0 +1,99 @@
+use app_error::AppError;
+use database_entity::dto::UserApiKey;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFUserApiKeyRow;
+
+pub async fn insert_user_api_key<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  name: &str,
+  key_prefix: &str,
+  key_hash: &[u8],
+) -> Result<UserApiKey, AppError> {
+  let row = sqlx::query_as::<_, AFUserApiKeyRow>(
+    r#"
+      INSERT INTO af_user_api_key (uid, name, key_prefix, key_hash)
+      VALUES ($1, $2, $3, $4)
+      RETURNING key_id, name, key_prefix, created_at, last_used_at
+    "#,
+  )
+  .bind(uid)
+  .bind(name)
+  .bind(key_prefix)
+  .bind(key_hash)
+  .fetch_one(executor)
+  .await?;
+  Ok(row.into())
+}
+
+pub async fn select_user_api_keys<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<UserApiKey>, AppError> {
+  let rows = sqlx::query_as::<_, AFUserApiKeyRow>(
+    r#"
+      SELECT key_id, name, key_prefix, created_at, last_used_at
+      FROM af_user_api_key
+      WHERE uid = $1
+      ORDER BY created_at
+    "#,
+  )
+  .bind(uid)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(Into::into).collect())
+}
+
+pub async fn select_user_api_key_count<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM af_user_api_key WHERE uid = $1")
+    .bind(uid)
+    .fetch_one(executor)
+    .await?;
+  Ok(count)
+}
+
+/// Returns false when the key does not exist or belongs to another user.
+pub async fn delete_user_api_key<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  key_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query("DELETE FROM af_user_api_key WHERE uid = $1 AND key_id = $2")
+    .bind(uid)
+    .bind(key_id)
+    .execute(executor)
+    .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the uid and uuid of the owner of the key, and records that the key was used. The use
+/// is recorded at most once a minute, so that clients sending many requests don't write the key
+/// on each of them.
+pub async fn select_user_by_api_key_hash<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  key_hash: &[u8],
+) -> Result<Option<(i64, Uuid)>, AppError> {
+  let user = sqlx::query_as::<_, (i64, Uuid)>(
+    r#"
+      WITH used_key AS (
+        UPDATE af_user_api_key
+        SET last_used_at = NOW()
+        WHERE key_hash = $1
+          AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+      )
+      SELECT af_user.uid, af_user.uuid
+      FROM af_user_api_key
+      JOIN af_user ON af_user.uid = af_user_api_key.uid
+      WHERE af_user_api_key.key_hash = $1
+    "#,
+  )
+  .bind(key_hash)
+  .fetch_optional(executor)
+  .await?;
+  Ok(user)
+}
@@ -0,0 +1,242 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use sqlx::{Executor, Postgres, Transaction};
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+use crate::collab::partition_key_from_collab_type;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFArchivedSpaceRow {
+  pub space_id: Uuid,
+  pub archived_by: i64,
+  pub archived_at: DateTime<Utc>,
+}
+
+/// A collab archived with a space.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct AFArchivedObject {
+  pub object_id: Uuid,
+  /// Whether the page was locked before being archived
+  pub was_locked: bool,
+  /// The database of a row, rows being archived along with their database
+  pub database_id: Option<Uuid>,
+}
+
+/// Archives the space together with the collabs it contains. A collab already archived with
+/// another space stays with it.
+pub async fn insert_archived_space(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  space_id: &Uuid,
+  uid: i64,
+  objects: &[AFArchivedObject],
+) -> Result<AFArchivedSpaceRow, AppError> {
+  let row = sqlx::query_as::<_, AFArchivedSpaceRow>(
+    r#"
+      INSERT INTO af_archived_space (workspace_id, space_id, archived_by)
+      VALUES ($1, $2, $3)
+      RETURNING space_id, archived_by, archived_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(space_id)
+  .bind(uid)
+  .fetch_one(txn.deref_mut())
+  .await
+  .map_err(|err| {
+    if err
+      .as_database_error()
+      .is_some_and(|err| err.is_unique_violation())
+    {
+      AppError::RecordAlreadyExists(format!("space {} is already archived", space_id))
+    } else {
+      err.into()
+    }
+  })?;
+
+  insert_archived_objects(txn, workspace_id, space_id, objects).await?;
+  Ok(row)
+}
+
+/// Adds collabs to an archived space, such as the pages moved into it after it was archived.
+pub async fn insert_archived_objects(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  space_id: &Uuid,
+  objects: &[AFArchivedObject],
+) -> Result<(), AppError> {
+  if objects.is_empty() {
+    return Ok(());
+  }
+  let object_ids: Vec<Uuid> = objects.iter().map(|object| object.object_id).collect();
+  let was_locked: Vec<bool> = objects.iter().map(|object| object.was_locked).collect();
+  let database_ids: Vec<Option<Uuid>> = objects.iter().map(|object| object.database_id).collect();
+  sqlx::query(
+    r#"
+      INSERT INTO af_archived_view (workspace_id, space_id, object_id, was_locked, database_id)
+      SELECT $1, $2, t.object_id, t.was_locked, t.database_id
+      FROM UNNEST($3::uuid[], $4::boolean[], $5::uuid[]) AS t(object_id, was_locked, database_id)
+      ON CONFLICT (object_id) DO NOTHING
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(space_id)
+  .bind(&object_ids)
+  .bind(&was_locked)
+  .bind(&database_ids)
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Removes collabs from the archived spaces of the workspace, such as the pages moved out of
+/// them. The rows of the removed databases are removed along with them.
+pub async fn delete_archived_objects(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  object_ids: &[Uuid],
+) -> Result<(), AppError> {
+  if object_ids.is_empty() {
+    return Ok(());
+  }
+  sqlx::query(
+    r#"
+      DELETE FROM af_archived_view
+      WHERE workspace_id = $1 AND (object_id = ANY($2) OR database_id = ANY($2))
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(object_ids)
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Collabs archived with each archived space of the workspace, rows of databases excluded.
+pub async fn select_archived_space_objects<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(Uuid, AFArchivedObject)>, AppError> {
+  let rows = sqlx::query_as::<_, (Uuid, Uuid, bool)>(
+    r#"
+      SELECT space_id, object_id, was_locked FROM af_archived_view
+      WHERE workspace_id = $1 AND database_id IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(space_id, object_id, was_locked)| {
+        let object = AFArchivedObject {
+          object_id,
+          was_locked,
+          database_id: None,
+        };
+        (space_id, object)
+      })
+      .collect(),
+  )
+}
+
+/// Unarchives the space and returns the collabs that were archived with it. Returns `None` when
+/// the space is not archived.
+pub async fn delete_archived_space(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  space_id: &Uuid,
+) -> Result<Option<Vec<AFArchivedObject>>, AppError> {
+  let objects = sqlx::query_as::<_, AFArchivedObject>(
+    r#"
+      SELECT object_id, was_locked, database_id FROM af_archived_view
+      WHERE workspace_id = $1 AND space_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(space_id)
+  .fetch_all(txn.deref_mut())
+  .await?;
+
+  // Archived collabs are deleted along with the space
+  let res = sqlx::query("DELETE FROM af_archived_space WHERE workspace_id = $1 AND space_id = $2")
+    .bind(workspace_id)
+    .bind(space_id)
+    .execute(txn.deref_mut())
+    .await?;
+  Ok((res.rows_affected() > 0).then_some(objects))
+}
+
+pub async fn select_archived_spaces<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFArchivedSpaceRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFArchivedSpaceRow>(
+    r#"
+      SELECT space_id, archived_by, archived_at
+      FROM af_archived_space
+      WHERE workspace_id = $1
+      ORDER BY archived_at DESC
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Ids of the collabs of the archived spaces of the workspace.
+pub async fn select_archived_object_ids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+  let object_ids =
+    sqlx::query_scalar::<_, Uuid>("SELECT object_id FROM af_archived_view WHERE workspace_id = $1")
+      .bind(workspace_id)
+      .fetch_all(executor)
+      .await?;
+  Ok(object_ids)
+}
+
+/// Workspaces with archived spaces whose folder changed since their archived collabs were last
+/// compared with it.
+pub async fn select_workspaces_due_for_archived_space_sync<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT a.workspace_id
+      FROM af_archived_space a
+      JOIN af_collab c ON c.oid = a.workspace_id AND c.partition_key = $1
+      WHERE c.deleted_at IS NULL
+      GROUP BY a.workspace_id, c.updated_at
+      HAVING c.updated_at > MIN(a.objects_synced_at)
+      LIMIT $2
+    "#,
+  )
+  .bind(partition_key_from_collab_type(&CollabType::Folder))
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
+
+pub async fn update_archived_space_synced_at(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  synced_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+  sqlx::query("UPDATE af_archived_space SET objects_synced_at = $2 WHERE workspace_id = $1")
+    .bind(workspace_id)
+    .bind(synced_at)
+    .execute(txn.deref_mut())
+    .await?;
+  Ok(())
+}
@@ -606,6 +606,159 @@ pub async fn select_collab_workspace_id<'a, E: Executor<'a, Database = Postgres>
   .await
 }
 
+/// Returns the ids of all collabs of the given type in the workspace that are not deleted.
+pub async fn select_workspace_collab_oids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  collab_type: &CollabType,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+  let partition_key = partition_key_from_collab_type(collab_type);
+  sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT oid
+      FROM af_collab
+      WHERE workspace_id = $1
+        AND partition_key = $2
+        AND deleted_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(partition_key)
+  .fetch_all(executor)
+  .await
+}
+
+/// Returns the id and type of all collabs of the workspace that are not deleted.
+pub async fn select_workspace_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(Uuid, CollabType)>, sqlx::Error> {
+  let rows = sqlx::query_as::<_, (Uuid, i32)>(
+    r#"
+      SELECT oid, partition_key
+      FROM af_collab
+      WHERE workspace_id = $1
+        AND deleted_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(oid, partition_key)| (oid, CollabType::from(partition_key)))
+      .collect(),
+  )
+}
+
+/// Moves the collabs, along with their snapshots and conflicts, to another workspace.
+pub async fn move_collabs_to_workspace(
+  tx: &mut Transaction<'_, Postgres>,
+  from_workspace_id: &Uuid,
+  to_workspace_id: &Uuid,
+  oids: &[Uuid],
+) -> Result<(), AppError> {
+  let text_oids: Vec<String> = oids.iter().map(|oid| oid.to_string()).collect();
+  sqlx::query(
+    r#"
+      UPDATE af_collab SET workspace_id = $2
+      WHERE workspace_id = $1 AND oid = ANY($3)
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .bind(oids)
+  .execute(tx.deref_mut())
+  .await?;
+  for table in [
+    "af_collab_snapshot",
+    "af_snapshot_meta",
+    "af_snapshot_state",
+  ] {
+    sqlx::query(&format!(
+      "UPDATE {table} SET workspace_id = $2 WHERE workspace_id = $1 AND oid = ANY($3)"
+    ))
+    .bind(from_workspace_id)
+    .bind(to_workspace_id)
+    .bind(&text_oids)
+    .execute(tx.deref_mut())
+    .await?;
+  }
+  sqlx::query(
+    r#"
+      UPDATE af_collab_rejected_update SET workspace_id = $2
+      WHERE workspace_id = $1 AND object_id = ANY($3)
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .bind(oids)
+  .execute(tx.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Returns the subset of `oids` whose collab has been soft deleted.
+pub async fn select_deleted_collab_oids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oids: &[Uuid],
+) -> Result<Vec<Uuid>, sqlx::Error> {
+  sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT oid
+      FROM af_collab
+      WHERE oid = ANY($1)
+        AND deleted_at IS NOT NULL
+    "#,
+  )
+  .bind(oids)
+  .fetch_all(executor)
+  .await
+}
+
+/// Returns the subset of `oids` that exist in the workspace and are not deleted.
+pub async fn select_existing_collab_oids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oids: &[Uuid],
+) -> Result<Vec<Uuid>, sqlx::Error> {
+  sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT oid
+      FROM af_collab
+      WHERE workspace_id = $1
+        AND oid = ANY($2)
+        AND deleted_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(oids)
+  .fetch_all(executor)
+  .await
+}
+
+/// Stored size in bytes of the collabs of the workspace among `oids`, by object id.
+pub async fn select_collab_lengths<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oids: &[Uuid],
+) -> Result<HashMap<Uuid, i64>, sqlx::Error> {
+  let rows = sqlx::query_as::<_, (Uuid, i64)>(
+    r#"
+      SELECT oid, COALESCE(len, 0)::BIGINT
+      FROM af_collab
+      WHERE workspace_id = $1
+        AND oid = ANY($2)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(oids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().collect())
+}
+
 pub async fn select_workspace_database_oid<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   workspace_id: &Uuid,
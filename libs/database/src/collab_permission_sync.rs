@@ -0,0 +1,204 @@
+use app_error::AppError;
+use database_entity::dto::{
+  AFAccessLevel, AFRole, CollabPermissionSync, CollabPermissionSyncStatus,
+};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{
+  AFCollabPermissionSyncRow, AFMemberCollabPermissionRow, AFStalledCollabPermissionSyncRow,
+};
+
+const COLLAB_PERMISSION_SYNC_COLUMNS: &str = "sync_id, uid, role_id, status, total_collabs, \
+   synced_collabs, error, started_at, finished_at";
+
+/// Starts a sync for the new role of the member. Syncs still running for an earlier role of the
+/// member are marked as superseded, which stops them at their next progress update.
+pub async fn insert_collab_permission_sync<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  role: AFRole,
+) -> Result<Uuid, AppError> {
+  let sync_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      WITH superseded AS (
+        UPDATE af_collab_permission_sync
+        SET status = $4, finished_at = NOW()
+        WHERE workspace_id = $1 AND uid = $2 AND status = $5
+      )
+      INSERT INTO af_collab_permission_sync (workspace_id, uid, role_id, status)
+      VALUES ($1, $2, $3, $5)
+      RETURNING sync_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(role as i32)
+  .bind(CollabPermissionSyncStatus::Superseded as i16)
+  .bind(CollabPermissionSyncStatus::Running as i16)
+  .fetch_one(executor)
+  .await?;
+  Ok(sync_id)
+}
+
+/// Records the progress of a running sync. Returns false when the sync is no longer running,
+/// e.g. because a later role change superseded it.
+pub async fn update_collab_permission_sync_progress<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  sync_id: &Uuid,
+  total_collabs: i32,
+  synced_collabs: i32,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      UPDATE af_collab_permission_sync
+      SET total_collabs = $2, synced_collabs = $3, updated_at = NOW()
+      WHERE sync_id = $1 AND status = $4
+    "#,
+  )
+  .bind(sync_id)
+  .bind(total_collabs)
+  .bind(synced_collabs)
+  .bind(CollabPermissionSyncStatus::Running as i16)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn finish_collab_permission_sync<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  sync_id: &Uuid,
+  status: CollabPermissionSyncStatus,
+  error: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_collab_permission_sync
+      SET status = $2, error = $3, finished_at = NOW()
+      WHERE sync_id = $1 AND status = $4
+    "#,
+  )
+  .bind(sync_id)
+  .bind(status as i16)
+  .bind(error)
+  .bind(CollabPermissionSyncStatus::Running as i16)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Claims the running syncs without progress for `stalled_secs`, e.g. because the server running
+/// them restarted. Claiming counts as progress, so that a sync is resumed by one server only.
+pub async fn claim_stalled_collab_permission_syncs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  stalled_secs: i64,
+  limit: i64,
+) -> Result<Vec<AFStalledCollabPermissionSyncRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFStalledCollabPermissionSyncRow>(
+    r#"
+      UPDATE af_collab_permission_sync
+      SET updated_at = NOW()
+      WHERE sync_id IN (
+        SELECT sync_id
+        FROM af_collab_permission_sync
+        WHERE status = $1 AND updated_at < NOW() - make_interval(secs => $2)
+        ORDER BY updated_at
+        LIMIT $3
+        FOR UPDATE SKIP LOCKED
+      )
+      RETURNING sync_id, workspace_id, uid, role_id
+    "#,
+  )
+  .bind(CollabPermissionSyncStatus::Running as i16)
+  .bind(stalled_secs as f64)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_latest_collab_permission_sync<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Option<CollabPermissionSync>, AppError> {
+  let row = sqlx::query_as::<_, AFCollabPermissionSyncRow>(&format!(
+    r#"
+      SELECT {}
+      FROM af_collab_permission_sync
+      WHERE workspace_id = $1 AND uid = $2
+      ORDER BY started_at DESC
+      LIMIT 1
+    "#,
+    COLLAB_PERMISSION_SYNC_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(uid)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(Into::into))
+}
+
+/// The collab-level permissions of the member on the collabs of the workspace, including the
+/// workspace collab itself.
+pub async fn select_member_collab_permissions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<AFMemberCollabPermissionRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFMemberCollabPermissionRow>(
+    r#"
+      SELECT
+        cm.oid,
+        p.access_level,
+        EXISTS (
+          SELECT 1 FROM af_collab_member_invite i
+          WHERE i.oid = cm.oid AND i.received_uid = cm.uid
+        ) AS shared,
+        EXISTS (
+          SELECT 1 FROM af_collab c
+          WHERE c.oid::text = cm.oid AND c.owner_uid = cm.uid
+        ) AS owned
+      FROM af_collab_member cm
+      JOIN af_permissions p ON p.id = cm.permission_id
+      WHERE cm.uid = $2
+        AND (
+          cm.oid = $1::text
+          OR EXISTS (
+            SELECT 1 FROM af_collab c
+            WHERE c.oid::text = cm.oid AND c.workspace_id = $1
+          )
+        )
+      ORDER BY cm.oid
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn update_member_collab_permissions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  oids: &[String],
+  access_level: AFAccessLevel,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_collab_member
+      SET permission_id = (
+        SELECT id FROM af_permissions WHERE access_level = $3 ORDER BY id LIMIT 1
+      )
+      WHERE uid = $1 AND oid = ANY($2)
+    "#,
+  )
+  .bind(uid)
+  .bind(oids)
+  .bind(access_level as i32)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
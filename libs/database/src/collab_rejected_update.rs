@@ -0,0 +1,124 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::collab::partition_key_from_collab_type;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFCollabRejectedUpdateRow {
+  pub conflict_id: Uuid,
+  pub object_id: Uuid,
+  pub collab_type: i32,
+  pub uid: i64,
+  pub device_id: Option<String>,
+  pub reason: String,
+  pub update_v1: Vec<u8>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Keeps an update the server did not apply. Returns `None` when the same update of the user
+/// was already kept for the collab.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_collab_rejected_update<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+  collab_type: &CollabType,
+  uid: i64,
+  device_id: Option<&str>,
+  reason: &str,
+  update_v1: &[u8],
+) -> Result<Option<Uuid>, AppError> {
+  let conflict_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      INSERT INTO af_collab_rejected_update
+        (workspace_id, object_id, collab_type, uid, device_id, reason, update_v1, update_hash)
+      VALUES ($1, $2, $3, $4, $5, $6, $7, sha256($7))
+      ON CONFLICT (workspace_id, object_id, uid, update_hash) DO NOTHING
+      RETURNING conflict_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(object_id)
+  .bind(partition_key_from_collab_type(collab_type))
+  .bind(uid)
+  .bind(device_id)
+  .bind(reason)
+  .bind(update_v1)
+  .fetch_optional(executor)
+  .await?;
+  Ok(conflict_id)
+}
+
+/// Deletes the rejected updates of `uid` for the collab, except the `keep` most recent ones.
+pub async fn delete_excess_collab_rejected_updates<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+  uid: i64,
+  keep: i64,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    r#"
+      DELETE FROM af_collab_rejected_update
+      WHERE conflict_id IN (
+        SELECT conflict_id
+        FROM af_collab_rejected_update
+        WHERE workspace_id = $1 AND object_id = $2 AND uid = $3
+        ORDER BY created_at DESC
+        OFFSET $4
+      )
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(object_id)
+  .bind(uid)
+  .bind(keep)
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn delete_collab_rejected_updates_before<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  before: DateTime<Utc>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    r#"
+      DELETE FROM af_collab_rejected_update
+      WHERE created_at < $1
+    "#,
+  )
+  .bind(before)
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// Rejected updates `uid` sent to the collab, most recent first.
+pub async fn select_collab_rejected_updates<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+  uid: i64,
+  limit: i64,
+) -> Result<Vec<AFCollabRejectedUpdateRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFCollabRejectedUpdateRow>(
+    r#"
+      SELECT conflict_id, object_id, collab_type, uid, device_id, reason, update_v1, created_at
+      FROM af_collab_rejected_update
+      WHERE workspace_id = $1 AND object_id = $2 AND uid = $3
+      ORDER BY created_at DESC
+      LIMIT $4
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(object_id)
+  .bind(uid)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,95 @@
+use app_error::AppError;
+use collab_entity::CollabType;
+use shared_entity::dto::workspace_dto::DeadReferenceReport;
+use sqlx::types::Json;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::collab::partition_key_from_collab_type;
+
+pub async fn upsert_dead_reference_report<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  report: &DeadReferenceReport,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_dead_reference_report (workspace_id, report, scanned_at)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id) DO UPDATE SET
+        report = EXCLUDED.report,
+        scanned_at = EXCLUDED.scanned_at
+    "#,
+  )
+  .bind(report.workspace_id)
+  .bind(Json(report))
+  .bind(report.scanned_at)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_dead_reference_report<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<DeadReferenceReport>, AppError> {
+  let report = sqlx::query_scalar::<_, Json<DeadReferenceReport>>(
+    r#"
+      SELECT report FROM af_dead_reference_report WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(report.map(|report| report.0))
+}
+
+/// Workspaces whose documents changed since their last report, or that have never been
+/// scanned. Never scanned workspaces come first.
+pub async fn select_workspaces_due_for_dead_reference_scan<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT w.workspace_id
+      FROM af_workspace w
+      LEFT JOIN af_dead_reference_report r ON r.workspace_id = w.workspace_id
+      WHERE EXISTS (
+        SELECT 1 FROM af_collab c
+        WHERE c.workspace_id = w.workspace_id
+          AND c.partition_key = $1
+          AND c.deleted_at IS NULL
+          AND (r.scanned_at IS NULL OR c.updated_at > r.scanned_at)
+      )
+      ORDER BY r.scanned_at ASC NULLS FIRST
+      LIMIT $2
+    "#,
+  )
+  .bind(partition_key_from_collab_type(&CollabType::Document))
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
+
+/// Uuids of every member of the workspace, guests included.
+pub async fn select_workspace_member_uuids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+  let uuids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT u.uuid
+      FROM af_workspace_member m
+      JOIN af_user u ON u.uid = m.uid
+      WHERE m.workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(uuids)
+}
@@ -0,0 +1,83 @@
+use app_error::AppError;
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use sqlx::types::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::pg_row::AFEgressSource;
+
+/// Adds the given bytes to the daily aggregates of `usage_date`. Rows of workspaces that
+/// were deleted in the meantime are skipped.
+pub async fn upsert_workspace_egress_bulk(
+  pg_pool: &PgPool,
+  usage_date: NaiveDate,
+  items: &[(Uuid, AFEgressSource, i64)],
+) -> Result<(), AppError> {
+  let workspace_ids: Vec<Uuid> = items.iter().map(|(id, _, _)| *id).collect();
+  let sources: Vec<i16> = items.iter().map(|(_, source, _)| *source as i16).collect();
+  let bytes: Vec<i64> = items.iter().map(|(_, _, bytes)| *bytes).collect();
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_egress_daily (workspace_id, usage_date, source, bytes)
+      SELECT t.workspace_id, $2, t.source, t.bytes
+      FROM unnest($1::uuid[], $3::int2[], $4::int8[]) AS t(workspace_id, source, bytes)
+      WHERE EXISTS (SELECT 1 FROM af_workspace w WHERE w.workspace_id = t.workspace_id)
+      ON CONFLICT (workspace_id, usage_date, source) DO UPDATE SET
+        bytes = af_workspace_egress_daily.bytes + EXCLUDED.bytes
+    "#,
+  )
+  .bind(workspace_ids)
+  .bind(usage_date)
+  .bind(sources)
+  .bind(bytes)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Total bytes served for the workspace since `from` (inclusive).
+pub async fn select_workspace_egress_bytes_since(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  from: NaiveDate,
+) -> Result<i64, AppError> {
+  let total = sqlx::query_scalar::<_, Option<Decimal>>(
+    r#"
+      SELECT SUM(bytes) FROM af_workspace_egress_daily
+      WHERE workspace_id = $1 AND usage_date >= $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from)
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(total.and_then(|total| total.to_i64()).unwrap_or(0))
+}
+
+/// Daily aggregates of the workspace in `[from, to)`, ordered by date.
+pub async fn select_workspace_daily_egress(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  from: NaiveDate,
+  to: NaiveDate,
+) -> Result<Vec<(NaiveDate, AFEgressSource, i64)>, AppError> {
+  let rows = sqlx::query_as::<_, (NaiveDate, i16, i64)>(
+    r#"
+      SELECT usage_date, source, bytes FROM af_workspace_egress_daily
+      WHERE workspace_id = $1 AND usage_date >= $2 AND usage_date < $3
+      ORDER BY usage_date
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(from)
+  .bind(to)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(date, source, bytes)| (date, AFEgressSource::from(source), bytes))
+      .collect(),
+  )
+}
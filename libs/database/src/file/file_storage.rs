@@ -1,6 +1,7 @@
-use crate::pg_row::AFBlobMetadataRow;
+use crate::pg_row::{AFBlobMetadataRow, AFBlobStorageClass};
 use crate::resource_usage::{
   delete_blob_metadata, get_blob_metadata, insert_blob_metadata, is_blob_metadata_exists,
+  move_blob_metadata, touch_blob_metadata, update_blob_storage_class,
 };
 use app_error::AppError;
 use async_trait::async_trait;
@@ -62,6 +63,15 @@ pub trait BucketClient {
   async fn remove_dir(&self, dir: &str) -> Result<(), AppError>;
 
   async fn list_dir(&self, dir: &str, limit: usize) -> Result<Vec<String>, AppError>;
+
+  /// Lists the key of every object under the directory.
+  async fn list_all_in_dir(&self, dir: &str) -> Result<Vec<String>, AppError>;
+
+  /// Moves the object to another storage class in place.
+  async fn set_storage_class(&self, object_key: &str, storage_class: &str) -> Result<(), AppError>;
+
+  /// Copies the object to another key of the same bucket.
+  async fn copy_blob(&self, from_object_key: &str, to_object_key: &str) -> Result<(), AppError>;
 }
 
 pub trait BlobKey: Send + Sync {
@@ -71,6 +81,33 @@ pub trait BlobKey: Send + Sync {
   fn e_tag(&self) -> &str;
 }
 
+/// Length of the uuid `parent_dir` prefix of v1 blob metadata keys
+const PARENT_DIR_LEN: usize = 36;
+
+/// Returns the `parent_dir` of a v1 blob metadata key (`{parent_dir}_{file_id}`), or `None`
+/// for legacy blobs that are stored directly under the workspace.
+pub fn blob_metadata_key_parent_dir(metadata_key: &str) -> Option<Uuid> {
+  let parent_dir = metadata_key.get(..PARENT_DIR_LEN)?;
+  if metadata_key[PARENT_DIR_LEN..].starts_with('_') {
+    Uuid::parse_str(parent_dir).ok()
+  } else {
+    None
+  }
+}
+
+/// Rebuilds the object key of a blob from its metadata key.
+pub fn blob_object_key(workspace_id: &Uuid, metadata_key: &str) -> String {
+  match blob_metadata_key_parent_dir(metadata_key) {
+    Some(parent_dir) => format!(
+      "{}/{}/{}",
+      workspace_id,
+      parent_dir,
+      &metadata_key[PARENT_DIR_LEN + 1..]
+    ),
+    None => format!("{}/{}", workspace_id, metadata_key),
+  }
+}
+
 pub struct BucketStorage<C> {
   client: C,
   pg_pool: PgPool,
@@ -132,6 +169,17 @@ where
     Ok(())
   }
 
+  /// Object keys of every blob stored for the workspace.
+  pub async fn list_workspace_object_keys(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<Vec<String>, AppError> {
+    self
+      .client
+      .list_all_in_dir(&format!("{}/", workspace_id))
+      .await
+  }
+
   pub async fn get_blob_metadata(
     &self,
     workspace_id: &Uuid,
@@ -143,9 +191,59 @@ where
 
   pub async fn get_blob(&self, key: &impl BlobKey) -> Result<Vec<u8>, AppError> {
     let blob = self.client.get_blob(&key.object_key()).await?.to_blob();
+    self.record_blob_access(key).await;
     Ok(blob)
   }
 
+  /// Records the read for lifecycle tiering. Blobs that were moved to the infrequent access
+  /// storage class are moved back by the tiering task, so that reads never wait for a copy.
+  async fn record_blob_access(&self, key: &impl BlobKey) {
+    let metadata_key = key.blob_metadata_key();
+    if let Err(err) = touch_blob_metadata(&self.pg_pool, key.workspace_id(), &metadata_key).await {
+      warn!("failed to record access of blob {}: {}", metadata_key, err);
+    }
+  }
+
+  pub async fn set_blob_storage_class(
+    &self,
+    workspace_id: &Uuid,
+    metadata_key: &str,
+    storage_class: AFBlobStorageClass,
+  ) -> Result<(), AppError> {
+    self
+      .client
+      .set_storage_class(
+        &blob_object_key(workspace_id, metadata_key),
+        storage_class.as_s3_storage_class(),
+      )
+      .await?;
+    update_blob_storage_class(&self.pg_pool, workspace_id, metadata_key, storage_class).await
+  }
+
+  /// Moves a blob to another workspace under the same metadata key. The object in the source
+  /// workspace is left for the deletion of the workspace to remove.
+  pub async fn move_blob(
+    &self,
+    from_workspace_id: &Uuid,
+    to_workspace_id: &Uuid,
+    metadata_key: &str,
+  ) -> Result<(), AppError> {
+    self
+      .client
+      .copy_blob(
+        &blob_object_key(from_workspace_id, metadata_key),
+        &blob_object_key(to_workspace_id, metadata_key),
+      )
+      .await?;
+    move_blob_metadata(
+      &self.pg_pool,
+      from_workspace_id,
+      to_workspace_id,
+      metadata_key,
+    )
+    .await
+  }
+
   pub async fn create_upload(
     &self,
     key: impl BlobKey,
@@ -13,7 +13,10 @@ use aws_sdk_s3::operation::get_object::GetObjectError;
 
 use aws_sdk_s3::presigning::PresigningConfig;
 use aws_sdk_s3::primitives::ByteStream;
-use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier};
+use aws_sdk_s3::types::{
+  CompletedMultipartUpload, CompletedPart, Delete, MetadataDirective, ObjectIdentifier,
+  StorageClass,
+};
 use aws_sdk_s3::Client;
 use database_entity::file_dto::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
@@ -496,6 +499,93 @@ impl BucketClient for AwsS3BucketClientImpl {
         .collect(),
     )
   }
+
+  async fn list_all_in_dir(&self, dir: &str) -> Result<Vec<String>, AppError> {
+    let mut keys = vec![];
+    let mut continuation_token = None;
+    loop {
+      let list_objects = self
+        .client
+        .list_objects_v2()
+        .bucket(&self.bucket)
+        .prefix(dir)
+        .set_continuation_token(continuation_token)
+        .send()
+        .await
+        .map_err(|err| anyhow!("Failed to list object: {}", err))?;
+      keys.extend(
+        list_objects
+          .contents
+          .unwrap_or_default()
+          .into_iter()
+          .filter_map(|o| o.key),
+      );
+      if !list_objects.is_truncated.unwrap_or(false) {
+        break;
+      }
+      continuation_token = list_objects.next_continuation_token;
+    }
+    Ok(keys)
+  }
+
+  async fn set_storage_class(&self, object_key: &str, storage_class: &str) -> Result<(), AppError> {
+    // S3 has no api to change the storage class of an object, so the object is copied onto
+    // itself with the new storage class.
+    self
+      .client
+      .copy_object()
+      .bucket(&self.bucket)
+      .key(object_key)
+      .copy_source(format!("{}/{}", self.bucket, object_key))
+      .storage_class(StorageClass::from(storage_class))
+      .metadata_directive(MetadataDirective::Copy)
+      .send()
+      .await
+      .map_err(|err| match err {
+        SdkError::ServiceError(service_err) if service_err.raw().status().as_u16() == 404 => {
+          AppError::RecordNotFound(format!("blob not found for key:{object_key}"))
+        },
+        err => AppError::Internal(anyhow!(
+          "Failed to change storage class of {} to {}: {}",
+          object_key,
+          storage_class,
+          err
+        )),
+      })?;
+
+    trace!(
+      "changed storage class of {} to {}",
+      object_key,
+      storage_class
+    );
+    Ok(())
+  }
+
+  async fn copy_blob(&self, from_object_key: &str, to_object_key: &str) -> Result<(), AppError> {
+    self
+      .client
+      .copy_object()
+      .bucket(&self.bucket)
+      .key(to_object_key)
+      .copy_source(format!("{}/{}", self.bucket, from_object_key))
+      .metadata_directive(MetadataDirective::Copy)
+      .send()
+      .await
+      .map_err(|err| match err {
+        SdkError::ServiceError(service_err) if service_err.raw().status().as_u16() == 404 => {
+          AppError::RecordNotFound(format!("blob not found for key:{from_object_key}"))
+        },
+        err => AppError::Internal(anyhow!(
+          "Failed to copy {} to {}: {}",
+          from_object_key,
+          to_object_key,
+          err
+        )),
+      })?;
+
+    trace!("copied {} to {}", from_object_key, to_object_key);
+    Ok(())
+  }
 }
 
 #[derive(Debug)]
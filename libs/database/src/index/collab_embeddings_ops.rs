@@ -246,6 +246,32 @@ where
   Ok(())
 }
 
+/// Marks every collab of the given type in the workspace as not indexed, so that they are
+/// embedded again. Returns the ids of the collabs.
+pub async fn reset_workspace_collabs_indexed_at<'a, E>(
+  executor: E,
+  workspace_id: &Uuid,
+  collab_type: &CollabType,
+) -> Result<Vec<Uuid>, Error>
+where
+  E: Executor<'a, Database = Postgres>,
+{
+  let partition_key = partition_key_from_collab_type(collab_type);
+  let oids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      UPDATE af_collab
+      SET indexed_at = NULL
+      WHERE workspace_id = $1 AND partition_key = $2 AND deleted_at IS NULL
+      RETURNING oid
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(partition_key)
+  .fetch_all(executor)
+  .await?;
+  Ok(oids)
+}
+
 pub async fn get_collabs_indexed_at<'a, E>(
   executor: E,
   oids: Vec<Uuid>,
@@ -1,18 +1,39 @@
 pub mod access_request;
+pub mod api_key;
+pub mod archived_space;
+pub mod blob_integrity;
 pub mod ai_usage;
 pub mod chat;
 pub mod collab;
+pub mod collab_permission_sync;
+pub mod collab_rejected_update;
+pub mod dead_reference;
+pub mod egress;
 pub mod file;
 pub mod history;
 pub mod index;
 pub mod listener;
+pub mod member_field;
 pub mod notification;
+pub mod page_activity;
+pub mod page_reaction;
+pub mod page_watch;
 pub mod pg_row;
 pub mod publish;
+pub mod publish_integrity;
+pub mod publish_stats;
+pub mod push_token;
 pub mod subscription;
 pub mod quick_note;
 pub mod resource_usage;
+pub mod scheduled_export;
+pub mod snippet;
 pub mod template;
 pub mod user;
+pub mod user_backup;
+pub mod user_device;
+pub mod view_metadata;
+pub mod view_slug;
 pub mod workspace;
+pub mod workspace_health;
 pub mod integrations;
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use database_entity::dto::WorkspaceMemberField;
+use sqlx::{Executor, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceMemberFieldRow;
+
+const MEMBER_FIELD_COLUMNS: &str = "field_id, name, editable_by_member, created_at";
+
+pub async fn insert_workspace_member_field<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  name: &str,
+  editable_by_member: bool,
+) -> Result<WorkspaceMemberField, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceMemberFieldRow>(&format!(
+    r#"
+      INSERT INTO af_workspace_member_field (workspace_id, name, editable_by_member)
+      VALUES ($1, $2, $3)
+      RETURNING {}
+    "#,
+    MEMBER_FIELD_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(name)
+  .bind(editable_by_member)
+  .fetch_one(executor)
+  .await
+  .map_err(|err| member_field_error(err, name))?;
+  Ok(row.into())
+}
+
+/// Only the given attributes are changed. Returns `None` when the field does not exist in the
+/// workspace.
+pub async fn update_workspace_member_field<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  field_id: &Uuid,
+  name: Option<&str>,
+  editable_by_member: Option<bool>,
+) -> Result<Option<WorkspaceMemberField>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceMemberFieldRow>(&format!(
+    r#"
+      UPDATE af_workspace_member_field
+      SET name = COALESCE($3, name),
+          editable_by_member = COALESCE($4, editable_by_member)
+      WHERE workspace_id = $1 AND field_id = $2
+      RETURNING {}
+    "#,
+    MEMBER_FIELD_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(field_id)
+  .bind(name)
+  .bind(editable_by_member)
+  .fetch_optional(executor)
+  .await
+  .map_err(|err| member_field_error(err, name.unwrap_or_default()))?;
+  Ok(row.map(Into::into))
+}
+
+/// Deletes the field along with the values of the members. Returns whether the field existed in
+/// the workspace.
+pub async fn delete_workspace_member_field(
+  tx: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  field_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_workspace_member_field WHERE workspace_id = $1 AND field_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(field_id)
+  .execute(tx.as_mut())
+  .await?;
+  if res.rows_affected() == 0 {
+    return Ok(false);
+  }
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_member_profile
+      SET custom_fields = custom_fields - $2
+      WHERE workspace_id = $1 AND custom_fields ? $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(field_id.to_string())
+  .execute(tx.as_mut())
+  .await?;
+  Ok(true)
+}
+
+/// Fields of the workspace in the order they were created.
+pub async fn select_workspace_member_fields<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<WorkspaceMemberField>, AppError> {
+  let rows = sqlx::query_as::<_, AFWorkspaceMemberFieldRow>(&format!(
+    r#"
+      SELECT {} FROM af_workspace_member_field
+      WHERE workspace_id = $1
+      ORDER BY created_at, field_id
+    "#,
+    MEMBER_FIELD_COLUMNS
+  ))
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(Into::into).collect())
+}
+
+/// Merges the values into the custom fields of the member. A `None` value removes the field.
+pub async fn upsert_workspace_member_custom_fields<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  values: &HashMap<Uuid, Option<String>>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_member_profile (workspace_id, uid, custom_fields)
+      VALUES ($1, $2, jsonb_strip_nulls($3))
+      ON CONFLICT (workspace_id, uid) DO UPDATE
+      SET custom_fields = jsonb_strip_nulls(af_workspace_member_profile.custom_fields || $3)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(sqlx::types::Json(values))
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+fn member_field_error(err: sqlx::Error, name: &str) -> AppError {
+  if err
+    .as_database_error()
+    .is_some_and(|err| err.is_unique_violation())
+  {
+    return AppError::RecordAlreadyExists(format!("a member field named {} already exists", name));
+  }
+  err.into()
+}
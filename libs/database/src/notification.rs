@@ -1,8 +1,33 @@
 use std::time::Duration;
 
 use app_error::AppError;
+use chrono::{DateTime, Utc};
 use database_entity::dto::{PageMentionNotification, ProcessedPageMentionNotification};
 use sqlx::{postgres::types::PgInterval, Executor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::pg_row::{AFNotificationInboxRow, AFNotificationRow, AFSystemNotification};
+
+/// Channel the `af_notification` insert trigger notifies on.
+pub const AF_NOTIFICATION_CHANNEL: &str = "af_notification_channel";
+
+/// Notifications `$1` can see: the ones sent to them, and the ones without recipient that are
+/// either global or belong to a workspace they are a member of.
+const NOTIFICATION_VISIBLE_TO_UID: &str = r#"
+  (
+    n.recipient_uid = $1
+    OR (
+      n.recipient_uid IS NULL
+      AND (
+        n.workspace_id IS NULL
+        OR EXISTS (
+          SELECT 1 FROM af_workspace_member m
+          WHERE m.workspace_id = n.workspace_id AND m.uid = $1
+        )
+      )
+    )
+  )
+"#;
 
 pub async fn select_recent_page_mentions<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
@@ -66,3 +91,76 @@ pub async fn update_page_mention_notification_status<'a, E: Executor<'a, Databas
   builder.build().execute(executor).await?;
   Ok(())
 }
+
+/// The notifications of the user created before `before`, most recent first. Notifications of
+/// the `excluded_types` are only meant to be delivered and are left out.
+pub async fn select_inbox_notifications<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  before: Option<DateTime<Utc>>,
+  limit: i64,
+  excluded_types: &[&str],
+) -> Result<Vec<AFNotificationInboxRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFNotificationInboxRow>(&format!(
+    r#"
+      SELECT n.id, n.workspace_id, n.notification_type, n.payload, n.created_at, r.read_at
+      FROM af_notification n
+      LEFT JOIN af_notification_read r ON r.notification_id = n.id AND r.uid = $1
+      WHERE {}
+        AND ($2::TIMESTAMPTZ IS NULL OR n.created_at < $2)
+        AND n.notification_type <> ALL($3)
+      ORDER BY n.created_at DESC
+      LIMIT $4
+    "#,
+    NOTIFICATION_VISIBLE_TO_UID
+  ))
+  .bind(uid)
+  .bind(before)
+  .bind(excluded_types)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Marks the notifications as read by the user. Returns the ids of the notifications the user
+/// had not read yet.
+pub async fn update_notifications_read<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  notification_ids: &[Uuid],
+) -> Result<Vec<Uuid>, AppError> {
+  let ids = sqlx::query_scalar::<_, Uuid>(&format!(
+    r#"
+      INSERT INTO af_notification_read (notification_id, uid)
+      SELECT n.id, $1
+      FROM af_notification n
+      WHERE n.id = ANY($2) AND {}
+      ON CONFLICT DO NOTHING
+      RETURNING notification_id
+    "#,
+    NOTIFICATION_VISIBLE_TO_UID
+  ))
+  .bind(uid)
+  .bind(notification_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(ids)
+}
+
+/// Sends the notification to the listeners of [AF_NOTIFICATION_CHANNEL] without storing it, so
+/// only the clients connected right now receive it.
+pub async fn notify_notification<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  notification: AFNotificationRow,
+) -> Result<(), AppError> {
+  let payload = serde_json::to_string(&AFSystemNotification {
+    payload: Some(notification),
+  })?;
+  sqlx::query("SELECT pg_notify($1, $2)")
+    .bind(AF_NOTIFICATION_CHANNEL)
+    .bind(payload)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
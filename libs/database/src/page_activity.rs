@@ -0,0 +1,193 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::PageActivityKind;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+pub async fn insert_page_activity<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  actor_uid: i64,
+  kind: PageActivityKind,
+  details: serde_json::Value,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_page_activity (workspace_id, view_id, actor_uid, kind, details)
+      VALUES ($1, $2, $3, $4, $5)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(actor_uid)
+  .bind(kind.as_str())
+  .bind(details)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Records `updates` edits of the page by `actor_uid` made between `started_at` and `ended_at`.
+/// They are added to the latest edit activity of the actor when it ended less than `gap_secs`
+/// before `started_at`, so that an editing session shows as one activity.
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_page_edit_activity<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  actor_uid: i64,
+  updates: i64,
+  started_at: DateTime<Utc>,
+  ended_at: DateTime<Utc>,
+  gap_secs: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      WITH session AS (
+        SELECT activity_id
+        FROM af_page_activity
+        WHERE workspace_id = $1 AND view_id = $2 AND actor_uid = $3 AND kind = $4
+          AND created_at > $6 - make_interval(secs => $8)
+        ORDER BY created_at DESC
+        LIMIT 1
+      ),
+      extended AS (
+        UPDATE af_page_activity a
+        SET created_at = GREATEST(a.created_at, $7),
+            details = jsonb_set(
+              a.details,
+              '{updates}',
+              to_jsonb(COALESCE((a.details ->> 'updates')::BIGINT, 0) + $5)
+            )
+        FROM session
+        WHERE a.activity_id = session.activity_id
+        RETURNING a.activity_id
+      )
+      INSERT INTO af_page_activity (workspace_id, view_id, actor_uid, kind, details, created_at)
+      SELECT $1, $2, $3, $4, jsonb_build_object('updates', $5, 'started_at', $6), $7
+      WHERE NOT EXISTS (SELECT 1 FROM extended)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(actor_uid)
+  .bind(PageActivityKind::Edited.as_str())
+  .bind(updates)
+  .bind(started_at)
+  .bind(ended_at)
+  .bind(gap_secs as f64)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Records a move of the page found in the folder, unless the latest recorded move of the page
+/// already took it to `new_parent_view_id`, e.g. because it was moved through the HTTP API.
+pub async fn insert_page_move_activity_if_unrecorded<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  actor_uid: Option<i64>,
+  old_parent_view_id: Option<Uuid>,
+  new_parent_view_id: Option<Uuid>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_page_activity (workspace_id, view_id, actor_uid, kind, details)
+      SELECT
+        $1,
+        $2,
+        -- The folder may still name a user that was deleted since
+        (SELECT uid FROM af_user WHERE uid = $3),
+        $4,
+        jsonb_build_object('old_parent_view_id', $5::TEXT, 'new_parent_view_id', $6::TEXT)
+      WHERE (
+        SELECT details ->> 'new_parent_view_id'
+        FROM af_page_activity
+        WHERE workspace_id = $1 AND view_id = $2 AND kind = $4
+        ORDER BY created_at DESC, activity_id DESC
+        LIMIT 1
+      ) IS DISTINCT FROM $6::TEXT
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(actor_uid)
+  .bind(PageActivityKind::Moved.as_str())
+  .bind(old_parent_view_id.map(|id| id.to_string()))
+  .bind(new_parent_view_id.map(|id| id.to_string()))
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// An event recorded for a page, or a comment left on it.
+#[derive(Debug, sqlx::FromRow)]
+pub struct PageActivityRecord {
+  pub kind: String,
+  pub actor_uid: Option<i64>,
+  pub details: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Recorded events of the view that occurred before `before`, most recent first.
+pub async fn select_page_activities<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  before: Option<DateTime<Utc>>,
+  limit: i64,
+) -> Result<Vec<PageActivityRecord>, AppError> {
+  let records = sqlx::query_as::<_, PageActivityRecord>(
+    r#"
+      SELECT kind, actor_uid, details, created_at
+      FROM af_page_activity
+      WHERE workspace_id = $1 AND view_id = $2
+        AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+      ORDER BY created_at DESC, activity_id DESC
+      LIMIT $4
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(before)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(records)
+}
+
+/// Comments left on the view that were not deleted, as activities of the `commented` kind,
+/// most recent first.
+pub async fn select_page_comment_activities<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  before: Option<DateTime<Utc>>,
+  limit: i64,
+) -> Result<Vec<PageActivityRecord>, AppError> {
+  let records = sqlx::query_as::<_, PageActivityRecord>(
+    r#"
+      SELECT
+        $1::TEXT AS kind,
+        created_by AS actor_uid,
+        jsonb_build_object(
+          'comment_id', comment_id,
+          'reply_comment_id', reply_comment_id
+        ) AS details,
+        created_at
+      FROM af_published_view_comment
+      WHERE view_id = $2 AND NOT is_deleted
+        AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+      ORDER BY created_at DESC
+      LIMIT $4
+    "#,
+  )
+  .bind(PageActivityKind::Commented.as_str())
+  .bind(view_id)
+  .bind(before)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(records)
+}
@@ -0,0 +1,98 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Page reactions are stored in `af_published_view_reaction`, next to the reactions on comments
+/// of published views.
+const WORKSPACE_PAGE_SCOPE: &str = "workspace_page";
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFPageReactionCountRow {
+  pub view_id: Uuid,
+  pub reaction_type: String,
+  pub count: i64,
+  pub reacted_by_me: bool,
+}
+
+/// Adding a reaction the user already made is a no-op.
+pub async fn insert_page_reaction<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+  reaction_type: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_view_reaction
+        (object_scope, workspace_id, view_id, created_by, reaction_type)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (workspace_id, view_id, reaction_type, created_by)
+        WHERE object_scope = 'workspace_page'
+      DO NOTHING
+    "#,
+  )
+  .bind(WORKSPACE_PAGE_SCOPE)
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(uid)
+  .bind(reaction_type)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns whether the user had made the reaction.
+pub async fn delete_page_reaction<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+  reaction_type: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_published_view_reaction
+      WHERE object_scope = $1 AND workspace_id = $2 AND view_id = $3
+        AND created_by = $4 AND reaction_type = $5
+    "#,
+  )
+  .bind(WORKSPACE_PAGE_SCOPE)
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(uid)
+  .bind(reaction_type)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Number of reactions of each type on the pages, and whether `uid` made one of them.
+pub async fn select_page_reaction_counts<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  uid: i64,
+) -> Result<Vec<AFPageReactionCountRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPageReactionCountRow>(
+    r#"
+      SELECT
+        view_id,
+        reaction_type,
+        COUNT(*) AS count,
+        BOOL_OR(created_by = $4) AS reacted_by_me
+      FROM af_published_view_reaction
+      WHERE object_scope = $1 AND workspace_id = $2
+        AND view_id = ANY($3)
+      GROUP BY view_id, reaction_type
+      ORDER BY view_id, MIN(created_at)
+    "#,
+  )
+  .bind(WORKSPACE_PAGE_SCOPE)
+  .bind(workspace_id)
+  .bind(view_ids)
+  .bind(uid)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
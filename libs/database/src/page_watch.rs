@@ -0,0 +1,115 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+pub async fn upsert_page_watch<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_page_watch (workspace_id, view_id, uid)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (view_id, uid) DO NOTHING
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(uid)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_page_watch<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query("DELETE FROM af_page_watch WHERE view_id = $1 AND uid = $2")
+    .bind(view_id)
+    .bind(uid)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
+
+pub async fn is_page_watched<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let watched = sqlx::query_scalar::<_, bool>(
+    "SELECT EXISTS (SELECT 1 FROM af_page_watch WHERE view_id = $1 AND uid = $2)",
+  )
+  .bind(view_id)
+  .bind(uid)
+  .fetch_one(executor)
+  .await?;
+  Ok(watched)
+}
+
+/// Returns the workspace id and the uids of the users watching the view.
+pub async fn select_page_watchers<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let watchers = sqlx::query_as::<_, (Uuid, i64)>(
+    "SELECT workspace_id, uid FROM af_page_watch WHERE view_id = $1",
+  )
+  .bind(view_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(watchers)
+}
+
+/// A watched page that was edited by someone else since the watcher was notified last.
+#[derive(Debug, sqlx::FromRow)]
+pub struct EditedWatchedPage {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub uid: i64,
+  pub edited_at: DateTime<Utc>,
+}
+
+/// The last writer of a collab is recorded as `af_collab.owner_uid`, so edits of the watcher
+/// itself are skipped.
+pub async fn select_edited_watched_pages(
+  pg_pool: &PgPool,
+  limit: i64,
+) -> Result<Vec<EditedWatchedPage>, AppError> {
+  let pages = sqlx::query_as::<_, EditedWatchedPage>(
+    r#"
+      SELECT w.workspace_id, w.view_id, w.uid, c.updated_at AS edited_at
+      FROM af_page_watch w
+      JOIN af_collab c ON c.oid = w.view_id AND c.workspace_id = w.workspace_id
+      WHERE c.updated_at > w.last_notified_at
+        AND c.deleted_at IS NULL
+        AND c.owner_uid <> w.uid
+      ORDER BY c.updated_at ASC
+      LIMIT $1
+    "#,
+  )
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(pages)
+}
+
+pub async fn update_page_watch_notified_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  uid: i64,
+  notified_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+  sqlx::query("UPDATE af_page_watch SET last_notified_at = $3 WHERE view_id = $1 AND uid = $2")
+    .bind(view_id)
+    .bind(uid)
+    .bind(notified_at)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
@@ -1,13 +1,17 @@
+use std::collections::HashMap;
+
 use anyhow::anyhow;
 use app_error::AppError;
 use chrono::{DateTime, Utc};
 
 use database_entity::dto::{
-  AFAccessLevel, AFRole, AFUserProfile, AFWebUser, AFWebUserWithObfuscatedName,
-  AFWorkspace, AFWorkspaceInvitationStatus, AFWorkspaceMember, AccessRequestMinimal,
-  AccessRequestStatus, AccessRequestWithViewId, AccessRequesterInfo, AccountLink, GlobalComment,
-  QuickNote, Reaction, Template, TemplateCategory, TemplateCategoryMinimal, TemplateCategoryType,
-  TemplateCreator, TemplateCreatorMinimal, TemplateGroup, TemplateMinimal,
+  AFAccessLevel, AFRole, AFUserProfile, AFWebUser, AFWebUserWithObfuscatedName, AFWorkspace,
+  AFWorkspaceInvitationStatus, AFWorkspaceMember, AccessRequestMinimal, AccessRequestStatus,
+  AccessRequestWithViewId, AccessRequesterInfo, AccountLink, CollabPermissionSync, GlobalComment,
+  QuickNote, Reaction, ScheduledExport, ScheduledExportDestination, ScheduledExportRun, Template,
+  TemplateCategory, TemplateCategoryMinimal, TemplateCategoryType, TemplateCreator,
+  TemplateCreatorMinimal, TemplateGroup, TemplateMinimal, UserApiKey, WorkspaceDirectoryPerson,
+  WorkspaceMemberField, WorkspaceMemberProfileLink, WorkspaceSnippet,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -254,6 +258,79 @@ impl From<AFWorkspaceMemberRow> for AFWorkspaceMember {
       role: value.role.clone(),
       avatar_url: value.avatar_url.clone(),
       joined_at: value.created_at,
+      status: None,
+    }
+  }
+}
+
+/// A member of the workspace as listed in the membership export.
+#[derive(Debug, FromRow)]
+pub struct AFWorkspaceMemberExportRow {
+  pub name: String,
+  pub email: Option<String>,
+  pub role_id: i32,
+  pub joined_at: Option<DateTime<Utc>>,
+  /// The last time any device of the member connected, in any workspace
+  pub last_active_at: Option<DateTime<Utc>>,
+}
+
+/// A pending invitation to the workspace as listed in the membership export.
+#[derive(Debug, FromRow)]
+pub struct AFWorkspacePendingInvitationExportRow {
+  pub invitee_email: String,
+  pub role_id: i32,
+  pub inviter_name: Option<String>,
+  pub invited_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFWorkspaceDirectoryRow {
+  pub uuid: Uuid,
+  pub name: String,
+  pub email: Option<String>,
+  pub role_id: i32,
+  pub avatar_url: Option<String>,
+  pub description: Option<String>,
+  pub title: Option<String>,
+  pub department: Option<String>,
+  pub pronouns: Option<String>,
+  pub links: sqlx::types::Json<Vec<WorkspaceMemberProfileLink>>,
+  pub custom_fields: sqlx::types::Json<HashMap<Uuid, String>>,
+}
+
+impl From<AFWorkspaceDirectoryRow> for WorkspaceDirectoryPerson {
+  fn from(value: AFWorkspaceDirectoryRow) -> Self {
+    WorkspaceDirectoryPerson {
+      uuid: value.uuid,
+      name: value.name,
+      email: value.email,
+      role: AFRole::from(value.role_id),
+      avatar_url: value.avatar_url,
+      description: value.description,
+      title: value.title,
+      department: value.department,
+      pronouns: value.pronouns,
+      links: value.links.0,
+      custom_fields: value.custom_fields.0,
+    }
+  }
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFWorkspaceMemberFieldRow {
+  pub field_id: Uuid,
+  pub name: String,
+  pub editable_by_member: bool,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<AFWorkspaceMemberFieldRow> for WorkspaceMemberField {
+  fn from(value: AFWorkspaceMemberFieldRow) -> Self {
+    Self {
+      field_id: value.field_id,
+      name: value.name,
+      editable_by_member: value.editable_by_member,
+      created_at: value.created_at,
     }
   }
 }
@@ -320,6 +397,57 @@ impl From<i16> for AFBlobSource {
   }
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(i16)]
+pub enum AFBlobStorageClass {
+  Standard = 0,
+  InfrequentAccess = 1,
+}
+
+impl AFBlobStorageClass {
+  /// Name of the storage class in the S3 api
+  pub fn as_s3_storage_class(&self) -> &'static str {
+    match self {
+      AFBlobStorageClass::Standard => "STANDARD",
+      AFBlobStorageClass::InfrequentAccess => "STANDARD_IA",
+    }
+  }
+}
+
+impl From<i16> for AFBlobStorageClass {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => AFBlobStorageClass::InfrequentAccess,
+      _ => AFBlobStorageClass::Standard,
+    }
+  }
+}
+
+/// Per user section of the folder kept in the view metadata
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(i16)]
+pub enum AFViewSection {
+  Favorite = 0,
+  Recent = 1,
+}
+
+/// Endpoint family that served the bytes of an egress record
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Debug, Clone, Copy)]
+#[repr(i16)]
+pub enum AFEgressSource {
+  Blob = 0,
+  Publish = 1,
+}
+
+impl From<i16> for AFEgressSource {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => AFEgressSource::Publish,
+      _ => AFEgressSource::Blob,
+    }
+  }
+}
+
 #[derive(Debug, FromRow, Serialize, Deserialize)]
 pub struct AFBlobMetadataRow {
   pub workspace_id: Uuid,
@@ -333,6 +461,12 @@ pub struct AFBlobMetadataRow {
   pub source: i16,
   #[serde(default)]
   pub source_metadata: serde_json::Value,
+  #[serde(default)]
+  pub storage_class: i16,
+  #[serde(default)]
+  pub last_accessed_at: DateTime<Utc>,
+  #[serde(default)]
+  pub storage_class_changed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -358,6 +492,17 @@ pub struct AFSystemNotification {
   pub payload: Option<AFNotificationRow>,
 }
 
+/// A notification as listed in the notification inbox of its recipient.
+#[derive(FromRow, Debug, Clone)]
+pub struct AFNotificationInboxRow {
+  pub id: Uuid,
+  pub workspace_id: Option<Uuid>,
+  pub notification_type: String,
+  pub payload: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+  pub read_at: Option<DateTime<Utc>>,
+}
+
 #[derive(FromRow, Serialize, Deserialize, Debug, Clone)]
 pub struct AFPermissionRow {
   pub id: i32,
@@ -855,6 +1000,160 @@ impl From<AFQuickNoteRow> for QuickNote {
   }
 }
 
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceSnippetRow {
+  pub snippet_id: Uuid,
+  pub shortcut: String,
+  pub name: String,
+  pub description: Option<String>,
+  pub content: serde_json::Value,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl From<AFWorkspaceSnippetRow> for WorkspaceSnippet {
+  fn from(value: AFWorkspaceSnippetRow) -> Self {
+    Self {
+      snippet_id: value.snippet_id,
+      shortcut: value.shortcut,
+      name: value.name,
+      description: value.description,
+      content: value.content,
+      created_by: value.created_by,
+      created_at: value.created_at,
+      last_updated_at: value.updated_at,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFScheduledExportRow {
+  pub export_id: Uuid,
+  pub workspace_id: Uuid,
+  pub destination: sqlx::types::Json<ScheduledExportDestination>,
+  pub encrypted_secret: Vec<u8>,
+  pub interval_days: i32,
+  pub enabled: bool,
+  pub next_run_at: DateTime<Utc>,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl From<AFScheduledExportRow> for ScheduledExport {
+  fn from(value: AFScheduledExportRow) -> Self {
+    Self {
+      export_id: value.export_id,
+      destination: value.destination.0,
+      interval_days: value.interval_days,
+      enabled: value.enabled,
+      next_run_at: value.next_run_at,
+      created_by: value.created_by,
+      created_at: value.created_at,
+      last_updated_at: value.updated_at,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFScheduledExportRunRow {
+  pub run_id: Uuid,
+  pub export_id: Uuid,
+  pub started_at: DateTime<Utc>,
+  pub finished_at: Option<DateTime<Utc>>,
+  pub status: i16,
+  pub documents: i32,
+  pub bytes: i64,
+  pub object_key: Option<String>,
+  pub error: Option<String>,
+}
+
+impl From<AFScheduledExportRunRow> for ScheduledExportRun {
+  fn from(value: AFScheduledExportRunRow) -> Self {
+    Self {
+      run_id: value.run_id,
+      export_id: value.export_id,
+      started_at: value.started_at,
+      finished_at: value.finished_at,
+      status: value.status.into(),
+      documents: value.documents,
+      bytes: value.bytes,
+      object_key: value.object_key,
+      error: value.error,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCollabPermissionSyncRow {
+  pub sync_id: Uuid,
+  pub uid: i64,
+  pub role_id: i32,
+  pub status: i16,
+  pub total_collabs: i32,
+  pub synced_collabs: i32,
+  pub error: Option<String>,
+  pub started_at: DateTime<Utc>,
+  pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// A running sync whose server stopped making progress on it.
+#[derive(FromRow, Debug)]
+pub struct AFStalledCollabPermissionSyncRow {
+  pub sync_id: Uuid,
+  pub workspace_id: Uuid,
+  pub uid: i64,
+  pub role_id: i32,
+}
+
+impl From<AFCollabPermissionSyncRow> for CollabPermissionSync {
+  fn from(value: AFCollabPermissionSyncRow) -> Self {
+    Self {
+      sync_id: value.sync_id,
+      uid: value.uid,
+      role: AFRole::from(value.role_id),
+      status: value.status.into(),
+      total_collabs: value.total_collabs,
+      synced_collabs: value.synced_collabs,
+      error: value.error,
+      started_at: value.started_at,
+      finished_at: value.finished_at,
+    }
+  }
+}
+
+/// A collab-level permission of a member. `shared` tells whether the collab was shared with
+/// them, and `owned` whether they created it.
+#[derive(FromRow, Debug)]
+pub struct AFMemberCollabPermissionRow {
+  pub oid: String,
+  pub access_level: i32,
+  pub shared: bool,
+  pub owned: bool,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFUserApiKeyRow {
+  pub key_id: Uuid,
+  pub name: String,
+  pub key_prefix: String,
+  pub created_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<AFUserApiKeyRow> for UserApiKey {
+  fn from(value: AFUserApiKeyRow) -> Self {
+    Self {
+      key_id: value.key_id,
+      name: value.name,
+      key_prefix: value.key_prefix,
+      created_at: value.created_at,
+      last_used_at: value.last_used_at,
+    }
+  }
+}
+
 pub struct AFPublishViewWithPublishInfo {
   pub view_id: Uuid,
   pub publish_name: String,
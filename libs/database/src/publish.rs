@@ -3,6 +3,8 @@ use chrono::{DateTime, Utc};
 use database_entity::dto::{
   PatchPublishedCollab, PublishCollabItem, PublishCollabKey, PublishInfo, WorkspaceNamespace,
 };
+use shared_entity::dto::publish_dto::{PublishRowFilter, PublishTheme};
+use sqlx::types::Json;
 use sqlx::{Executor, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
@@ -90,18 +92,19 @@ pub async fn update_non_orginal_workspace_publish_namespace(
   old_namespace: &str,
   new_namespace: &str,
 ) -> Result<(), AppError> {
-  let res = sqlx::query!(
+  let res = sqlx::query(
     r#"
       UPDATE af_workspace_namespace
       SET namespace = $1
       WHERE workspace_id = $2
         AND namespace = $3
         AND is_original = FALSE
+        AND NOT is_alias
     "#,
-    new_namespace,
-    workspace_id,
-    old_namespace,
   )
+  .bind(new_namespace)
+  .bind(workspace_id)
+  .bind(old_namespace)
   .execute(pg_pool)
   .await?;
 
@@ -178,19 +181,19 @@ pub async fn select_workspace_publish_namespaces(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
 ) -> Result<Vec<WorkspaceNamespace>, AppError> {
-  let res = sqlx::query_as!(
-    WorkspaceNamespace,
+  // Aliases of merged workspaces are not namespaces of the workspace itself
+  let rows = sqlx::query_as::<_, (Uuid, String, bool)>(
     r#"
       SELECT workspace_id, namespace, is_original
       FROM af_workspace_namespace
-      WHERE workspace_id = $1
+      WHERE workspace_id = $1 AND NOT is_alias
     "#,
-    workspace_id,
   )
+  .bind(workspace_id)
   .fetch_all(pg_pool)
   .await?;
 
-  Ok(res)
+  Ok(rows.into_iter().map(to_workspace_namespace).collect())
 }
 
 #[inline]
@@ -199,21 +202,31 @@ pub async fn select_workspace_publish_namespace(
   workspace_id: &Uuid,
   namespace: &str,
 ) -> Result<WorkspaceNamespace, AppError> {
-  let res = sqlx::query_as!(
-    WorkspaceNamespace,
+  let row = sqlx::query_as::<_, (Uuid, String, bool)>(
     r#"
       SELECT workspace_id, namespace, is_original
       FROM af_workspace_namespace
       WHERE workspace_id = $1
         AND namespace = $2
+        AND NOT is_alias
     "#,
-    workspace_id,
-    namespace,
   )
+  .bind(workspace_id)
+  .bind(namespace)
   .fetch_one(pg_pool)
   .await?;
 
-  Ok(res)
+  Ok(to_workspace_namespace(row))
+}
+
+fn to_workspace_namespace(
+  (workspace_id, namespace, is_original): (Uuid, String, bool),
+) -> WorkspaceNamespace {
+  WorkspaceNamespace {
+    workspace_id,
+    namespace,
+    is_original,
+  }
 }
 
 async fn delete_published_collabs(
@@ -334,6 +347,229 @@ pub async fn select_publish_collab_meta<'a, E: Executor<'a, Database = Postgres>
   Ok(metadata)
 }
 
+/// The views among `view_ids` that are published, with their publisher and publish name.
+pub async fn select_published_views_with_publisher<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<(Uuid, i64, String)>, AppError> {
+  let rows = sqlx::query_as::<_, (Uuid, i64, String)>(
+    r#"
+      SELECT view_id, published_by, publish_name
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+        AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// The AI description of the published view, if it was generated from the content with the
+/// given hash.
+pub async fn select_published_view_preview_description<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+  content_hash: &str,
+) -> Result<Option<String>, AppError> {
+  let description = sqlx::query_scalar::<_, String>(
+    r#"
+      SELECT description FROM af_published_view_preview
+      WHERE view_id = $1 AND content_hash = $2
+    "#,
+  )
+  .bind(view_id)
+  .bind(content_hash)
+  .fetch_optional(executor)
+  .await?;
+  Ok(description)
+}
+
+pub async fn upsert_published_view_preview_description<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+  content_hash: &str,
+  description: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_view_preview (view_id, content_hash, description)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (view_id) DO UPDATE
+      SET content_hash = EXCLUDED.content_hash,
+          description = EXCLUDED.description,
+          created_at = NOW()
+    "#,
+  )
+  .bind(view_id)
+  .bind(content_hash)
+  .bind(description)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFPublishedViewVariantRow {
+  pub lang: String,
+  pub variant_view_id: Uuid,
+  pub publish_name: String,
+}
+
+pub async fn upsert_published_view_variant<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  lang: &str,
+  variant_view_id: &Uuid,
+  created_by: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_view_variant
+        (workspace_id, view_id, lang, variant_view_id, created_by)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (view_id, lang) DO UPDATE
+      SET variant_view_id = EXCLUDED.variant_view_id,
+          created_by = EXCLUDED.created_by,
+          created_at = NOW()
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(lang)
+  .bind(variant_view_id)
+  .bind(created_by)
+  .execute(executor)
+  .await
+  .map_err(|err| {
+    if err
+      .as_database_error()
+      .is_some_and(|err| err.is_unique_violation())
+    {
+      return AppError::RecordAlreadyExists(format!(
+        "view {} is already the variant of another view",
+        variant_view_id
+      ));
+    }
+    err.into()
+  })?;
+  Ok(())
+}
+
+/// Returns false if the view had no variant for the language.
+pub async fn delete_published_view_variant<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  lang: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_published_view_variant
+      WHERE workspace_id = $1 AND view_id = $2 AND lang = $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(lang)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Whether the view is a variant of another view, or has variants itself.
+pub async fn select_view_is_in_variant_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<(bool, bool), AppError> {
+  let res = sqlx::query_as::<_, (bool, bool)>(
+    r#"
+      SELECT
+        EXISTS(
+          SELECT 1 FROM af_published_view_variant
+          WHERE workspace_id = $1 AND variant_view_id = $2
+        ),
+        EXISTS(
+          SELECT 1 FROM af_published_view_variant
+          WHERE workspace_id = $1 AND view_id = $2
+        )
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(res)
+}
+
+/// Variants of the view that are currently published.
+pub async fn select_published_view_variants<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<AFPublishedViewVariantRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishedViewVariantRow>(
+    r#"
+      SELECT v.lang, v.variant_view_id, apc.publish_name
+      FROM af_published_view_variant v
+      JOIN af_published_collab apc
+        ON apc.workspace_id = v.workspace_id
+        AND apc.view_id = v.variant_view_id
+        AND apc.unpublished_at IS NULL
+      WHERE v.workspace_id = $1 AND v.view_id = $2
+      ORDER BY v.lang
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Published variants of the view published as `publish_namespace`/`publish_name`.
+pub async fn select_published_view_variants_by_publish_name<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<Vec<AFPublishedViewVariantRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPublishedViewVariantRow>(
+    r#"
+      SELECT v.lang, v.variant_view_id, variant.publish_name
+      FROM af_published_collab apc
+      JOIN af_published_view_variant v
+        ON v.workspace_id = apc.workspace_id AND v.view_id = apc.view_id
+      JOIN af_published_collab variant
+        ON variant.workspace_id = v.workspace_id
+        AND variant.view_id = v.variant_view_id
+        AND variant.unpublished_at IS NULL
+      WHERE apc.workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND apc.publish_name = $2
+        AND apc.unpublished_at IS NULL
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
 #[inline]
 pub async fn set_published_collabs_as_unpublished(
   pg_pool: &PgPool,
@@ -427,6 +663,14 @@ pub async fn update_published_collabs(
       query_builder.push(" duplicate_enabled = ");
       query_builder.push_bind(duplicate_enabled);
     }
+    if let Some(stats_digest_enabled) = patch.stats_digest_enabled {
+      if !first_set {
+        query_builder.push(",");
+      }
+      first_set = false;
+      query_builder.push(" stats_digest_enabled = ");
+      query_builder.push_bind(stats_digest_enabled);
+    }
     if let Some(publish_name) = &patch.publish_name {
       if !first_set {
         query_builder.push(",");
@@ -578,17 +822,18 @@ async fn select_most_recent_non_original_namespace(
   pg_pool: &PgPool,
   namespace: &str,
 ) -> Result<Option<String>, AppError> {
-  let res = sqlx::query_scalar!(
+  let res = sqlx::query_scalar::<_, String>(
     r#"
       SELECT namespace
       FROM af_workspace_namespace
       WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
         AND is_original = FALSE
+        AND NOT is_alias
       ORDER BY created_at DESC
       LIMIT 1
     "#,
-    namespace,
   )
+  .bind(namespace)
   .fetch_optional(pg_pool)
   .await?;
 
@@ -1095,3 +1340,207 @@ pub async fn select_published_collab_with_receivers(
     None => Ok(None),
   }
 }
+
+/// Sets or clears the theme of a publish namespace of the workspace. Returns false if the
+/// namespace does not belong to the workspace.
+pub async fn update_publish_namespace_theme<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  namespace: &str,
+  theme: Option<&PublishTheme>,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      UPDATE af_workspace_namespace
+      SET theme = $3
+      WHERE workspace_id = $1
+        AND namespace = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(namespace)
+  .bind(theme.map(Json))
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns `None` if the namespace does not belong to the workspace, and the theme otherwise,
+/// which is the default theme if none has been set.
+pub async fn select_publish_namespace_theme<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  namespace: &str,
+) -> Result<Option<PublishTheme>, AppError> {
+  let theme = sqlx::query_scalar::<_, Option<Json<PublishTheme>>>(
+    r#"
+      SELECT theme
+      FROM af_workspace_namespace
+      WHERE workspace_id = $1
+        AND namespace = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(namespace)
+  .fetch_optional(executor)
+  .await?;
+  Ok(theme.map(|theme| theme.map(|theme| theme.0).unwrap_or_default()))
+}
+
+/// Sets or clears the theme of a published view. Returns false if the view is not published.
+pub async fn update_published_collab_theme<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  theme: Option<&PublishTheme>,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      UPDATE af_published_collab
+      SET theme = $3
+      WHERE workspace_id = $1
+        AND view_id = $2
+        AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(theme.map(Json))
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn upsert_published_row_filter<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+  filter: &PublishRowFilter,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_row_filter (workspace_id, view_id, filter, updated_by)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (view_id) DO UPDATE SET
+        filter = EXCLUDED.filter,
+        updated_by = EXCLUDED.updated_by,
+        updated_at = NOW()
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(Json(filter))
+  .bind(uid)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_published_row_filter<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<PublishRowFilter>, AppError> {
+  let filter = sqlx::query_scalar::<_, Json<PublishRowFilter>>(
+    r#"
+      SELECT filter FROM af_published_row_filter WHERE workspace_id = $1 AND view_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(filter.map(|filter| filter.0))
+}
+
+pub async fn delete_published_row_filter<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res =
+    sqlx::query("DELETE FROM af_published_row_filter WHERE workspace_id = $1 AND view_id = $2")
+      .bind(workspace_id)
+      .bind(view_id)
+      .execute(executor)
+      .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Replaces the published data of a view, keeping its publish settings. Returns false if the
+/// view is not published.
+pub async fn update_published_collab_blob<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  blob: &[u8],
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      UPDATE af_published_collab
+      SET blob = $3
+      WHERE workspace_id = $1
+        AND view_id = $2
+        AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(blob)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns `None` if the view is not published, and the theme of the view otherwise, which
+/// is the default theme if none has been set.
+pub async fn select_published_collab_theme<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<PublishTheme>, AppError> {
+  let theme = sqlx::query_scalar::<_, Option<Json<PublishTheme>>>(
+    r#"
+      SELECT theme
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND view_id = $2
+        AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(theme.map(|theme| theme.map(|theme| theme.0).unwrap_or_default()))
+}
+
+/// Theme of the publish namespace and theme of the published view, for the publish renderer.
+/// Returns `None` if no view is published under this name.
+pub async fn select_published_view_themes<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<Option<(Option<PublishTheme>, Option<PublishTheme>)>, AppError> {
+  let themes = sqlx::query_as::<_, (Option<Json<PublishTheme>>, Option<Json<PublishTheme>>)>(
+    r#"
+      SELECT ns.theme, pc.theme
+      FROM af_workspace_namespace ns
+      JOIN af_published_collab pc ON pc.workspace_id = ns.workspace_id
+      WHERE ns.namespace = $1
+        AND pc.publish_name = $2
+        AND pc.unpublished_at IS NULL
+    "#,
+  )
+  .bind(publish_namespace)
+  .bind(publish_name)
+  .fetch_optional(executor)
+  .await?;
+  Ok(themes.map(|(namespace_theme, view_theme)| {
+    (
+      namespace_theme.map(|theme| theme.0),
+      view_theme.map(|theme| theme.0),
+    )
+  }))
+}
@@ -0,0 +1,94 @@
+use app_error::AppError;
+use shared_entity::dto::workspace_dto::PublishIntegrityReport;
+use sqlx::types::Json;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+pub async fn upsert_publish_integrity_report<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  report: &PublishIntegrityReport,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_publish_integrity_report (workspace_id, report, scanned_at)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id) DO UPDATE SET
+        report = EXCLUDED.report,
+        scanned_at = EXCLUDED.scanned_at
+    "#,
+  )
+  .bind(report.workspace_id)
+  .bind(Json(report))
+  .bind(report.scanned_at)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_publish_integrity_report<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<PublishIntegrityReport>, AppError> {
+  let report = sqlx::query_scalar::<_, Json<PublishIntegrityReport>>(
+    r#"
+      SELECT report FROM af_publish_integrity_report WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(report.map(|report| report.0))
+}
+
+/// Workspaces with at least one published view that have never been scanned, or whose last
+/// report is older than `stale_after_days`. Never scanned workspaces come first.
+pub async fn select_workspaces_due_for_publish_integrity_scan<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  stale_after_days: i32,
+  limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT w.workspace_id
+      FROM af_workspace w
+      LEFT JOIN af_publish_integrity_report r ON r.workspace_id = w.workspace_id
+      WHERE EXISTS (
+          SELECT 1 FROM af_published_collab p
+          WHERE p.workspace_id = w.workspace_id AND p.unpublished_at IS NULL
+        )
+        AND (r.scanned_at IS NULL OR r.scanned_at < NOW() - make_interval(days => $1))
+      ORDER BY r.scanned_at ASC NULLS FIRST
+      LIMIT $2
+    "#,
+  )
+  .bind(stale_after_days)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
+
+/// Published views of the workspace with the uid of their publisher.
+pub async fn select_workspace_published_views_with_publisher<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let rows = sqlx::query_as::<_, (Uuid, i64)>(
+    r#"
+      SELECT view_id, published_by
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,124 @@
+use app_error::AppError;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+/// Adds the given views to the counters of `stat_date`. Views are identified by their
+/// workspace and publish name, and names that are no longer published are skipped.
+pub async fn upsert_published_view_stats_bulk(
+  pg_pool: &PgPool,
+  stat_date: NaiveDate,
+  items: &[(Uuid, String, i64)],
+) -> Result<(), AppError> {
+  let workspace_ids: Vec<Uuid> = items.iter().map(|(id, _, _)| *id).collect();
+  let publish_names: Vec<String> = items.iter().map(|(_, name, _)| name.clone()).collect();
+  let views: Vec<i64> = items.iter().map(|(_, _, views)| *views).collect();
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_view_stats_daily (workspace_id, view_id, stat_date, views)
+      SELECT apc.workspace_id, apc.view_id, $2, SUM(t.views)
+      FROM unnest($1::uuid[], $3::text[], $4::int8[]) AS t(workspace_id, publish_name, views)
+      JOIN af_published_collab apc
+        ON apc.workspace_id = t.workspace_id AND apc.publish_name = t.publish_name
+      GROUP BY apc.workspace_id, apc.view_id
+      ON CONFLICT (view_id, stat_date) DO UPDATE SET
+        views = af_published_view_stats_daily.views + EXCLUDED.views
+    "#,
+  )
+  .bind(workspace_ids)
+  .bind(stat_date)
+  .bind(publish_names)
+  .bind(views)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct PublishedViewStatsDigest {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub publish_name: String,
+  pub published_by: i64,
+  pub since: DateTime<Utc>,
+  pub views: i64,
+  pub reactions: i64,
+  pub comments: i64,
+}
+
+/// Published views whose last digest, or publication, is older than `period_start`, with
+/// their views, reactions and new comments since then. Views that opted out of the digest
+/// and unpublished views are skipped.
+pub async fn select_published_view_stats_due<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  period_start: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<PublishedViewStatsDigest>, AppError> {
+  let rows = sqlx::query_as::<_, PublishedViewStatsDigest>(
+    r#"
+      WITH due AS (
+        SELECT
+          apc.workspace_id,
+          apc.view_id,
+          apc.publish_name,
+          apc.published_by,
+          COALESCE(d.sent_at, apc.created_at) AS since
+        FROM af_published_collab apc
+        LEFT JOIN af_published_view_stats_digest d ON d.view_id = apc.view_id
+        WHERE apc.unpublished_at IS NULL
+          AND apc.stats_digest_enabled
+          AND COALESCE(d.sent_at, apc.created_at) <= $1
+        ORDER BY since
+        LIMIT $2
+      )
+      SELECT
+        due.workspace_id,
+        due.view_id,
+        due.publish_name,
+        due.published_by,
+        due.since,
+        COALESCE((
+          SELECT SUM(s.views) FROM af_published_view_stats_daily s
+          WHERE s.view_id = due.view_id AND s.stat_date >= due.since::date
+        ), 0)::BIGINT AS views,
+        (
+          SELECT COUNT(*) FROM af_published_view_reaction r
+          WHERE r.view_id = due.view_id AND r.created_at > due.since
+        ) AS reactions,
+        (
+          SELECT COUNT(*) FROM af_published_view_comment c
+          WHERE c.view_id = due.view_id AND c.created_at > due.since AND NOT c.is_deleted
+        ) AS comments
+      FROM due
+    "#,
+  )
+  .bind(period_start)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn upsert_published_view_stats_digest_sent_at<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  sent_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_published_view_stats_digest (workspace_id, view_id, sent_at)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (view_id) DO UPDATE SET sent_at = EXCLUDED.sent_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(sent_at)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
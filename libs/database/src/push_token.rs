@@ -0,0 +1,312 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::{PushPlatform, QuietHours, SetQuietHoursParams};
+use sqlx::{Executor, Postgres, Transaction};
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+/// A push token, and the quiet hours of its device when it has some.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFPushTokenRow {
+  pub uid: i64,
+  pub device_id: String,
+  pub platform: String,
+  pub token: String,
+  pub quiet_start_minute: Option<i32>,
+  pub quiet_end_minute: Option<i32>,
+  pub quiet_timezone: Option<String>,
+  pub quiet_allow_mentions: Option<bool>,
+}
+
+/// A push notification held back by the quiet hours of a device.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFDeferredPush {
+  pub notification_id: Uuid,
+  pub notification_type: String,
+  pub workspace_id: Option<Uuid>,
+  pub title: String,
+  pub body: String,
+}
+
+const SELECT_PUSH_TOKEN_ROWS: &str = r#"
+  SELECT t.uid, t.device_id, t.platform, t.token,
+    q.start_minute AS quiet_start_minute,
+    q.end_minute AS quiet_end_minute,
+    q.timezone AS quiet_timezone,
+    q.allow_mentions AS quiet_allow_mentions
+  FROM af_push_token t
+  LEFT JOIN af_push_quiet_hours q ON q.uid = t.uid AND q.device_id = t.device_id
+"#;
+
+/// Sets the push token of the device. A token already registered for another device, for
+/// example after signing in with another account on the same phone, is moved to this one.
+pub async fn upsert_push_token(
+  txn: &mut Transaction<'_, Postgres>,
+  uid: i64,
+  device_id: &str,
+  platform: PushPlatform,
+  token: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      DELETE FROM af_push_token
+      WHERE platform = $1 AND token = $2 AND NOT (uid = $3 AND device_id = $4)
+    "#,
+  )
+  .bind(platform.as_str())
+  .bind(token)
+  .bind(uid)
+  .bind(device_id)
+  .execute(txn.deref_mut())
+  .await?;
+
+  sqlx::query(
+    r#"
+      INSERT INTO af_push_token (uid, device_id, platform, token, updated_at)
+      VALUES ($1, $2, $3, $4, NOW())
+      ON CONFLICT (uid, device_id) DO UPDATE SET
+        platform = EXCLUDED.platform,
+        token = EXCLUDED.token,
+        updated_at = EXCLUDED.updated_at
+    "#,
+  )
+  .bind(uid)
+  .bind(device_id)
+  .bind(platform.as_str())
+  .bind(token)
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_push_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query("DELETE FROM af_push_token WHERE uid = $1 AND device_id = $2")
+    .bind(uid)
+    .bind(device_id)
+    .execute(executor)
+    .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn select_push_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFPushTokenRow>, AppError> {
+  let rows =
+    sqlx::query_as::<_, AFPushTokenRow>(&format!("{} WHERE t.uid = $1", SELECT_PUSH_TOKEN_ROWS))
+      .bind(uid)
+      .fetch_all(executor)
+      .await?;
+  Ok(rows)
+}
+
+/// Deletes tokens the push service rejected as invalid or unregistered.
+pub async fn delete_push_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  platform: PushPlatform,
+  tokens: &[String],
+) -> Result<u64, AppError> {
+  let res = sqlx::query("DELETE FROM af_push_token WHERE platform = $1 AND token = ANY($2)")
+    .bind(platform.as_str())
+    .bind(tokens)
+    .execute(executor)
+    .await?;
+  Ok(res.rows_affected())
+}
+
+/// Deletes the tokens that were not refreshed since `stale_before`, unless their device opened a
+/// realtime connection since then.
+pub async fn delete_stale_push_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  stale_before: DateTime<Utc>,
+) -> Result<u64, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_push_token t
+      WHERE t.updated_at < $1
+        AND NOT EXISTS (
+          SELECT 1 FROM af_user_device d
+          WHERE d.uid = t.uid AND d.device_id = t.device_id AND d.last_seen_at >= $1
+        )
+    "#,
+  )
+  .bind(stale_before)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
+
+/// Claims the notification for push delivery. Returns false when another server already
+/// claimed it.
+pub async fn claim_notification_push<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  notification_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res =
+    sqlx::query("UPDATE af_notification SET pushed_at = NOW() WHERE id = $1 AND pushed_at IS NULL")
+      .bind(notification_id)
+      .execute(executor)
+      .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn upsert_quiet_hours<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+  params: &SetQuietHoursParams,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_push_quiet_hours
+        (uid, device_id, start_minute, end_minute, timezone, allow_mentions, updated_at)
+      VALUES ($1, $2, $3, $4, $5, $6, NOW())
+      ON CONFLICT (uid, device_id) DO UPDATE SET
+        start_minute = EXCLUDED.start_minute,
+        end_minute = EXCLUDED.end_minute,
+        timezone = EXCLUDED.timezone,
+        allow_mentions = EXCLUDED.allow_mentions,
+        updated_at = EXCLUDED.updated_at
+    "#,
+  )
+  .bind(uid)
+  .bind(device_id)
+  .bind(params.start_minute)
+  .bind(params.end_minute)
+  .bind(&params.timezone)
+  .bind(params.allow_mentions)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_quiet_hours<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<QuietHours>, AppError> {
+  let quiet_hours = sqlx::query_as::<_, (String, i32, i32, String, bool)>(
+    r#"
+      SELECT device_id, start_minute, end_minute, timezone, allow_mentions
+      FROM af_push_quiet_hours
+      WHERE uid = $1
+      ORDER BY device_id
+    "#,
+  )
+  .bind(uid)
+  .fetch_all(executor)
+  .await?
+  .into_iter()
+  .map(
+    |(device_id, start_minute, end_minute, timezone, allow_mentions)| QuietHours {
+      device_id,
+      start_minute,
+      end_minute,
+      timezone,
+      allow_mentions,
+    },
+  )
+  .collect();
+  Ok(quiet_hours)
+}
+
+pub async fn delete_quiet_hours<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query("DELETE FROM af_push_quiet_hours WHERE uid = $1 AND device_id = $2")
+    .bind(uid)
+    .bind(device_id)
+    .execute(executor)
+    .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn insert_deferred_push<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+  push: &AFDeferredPush,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_push_deferred
+        (uid, device_id, notification_id, notification_type, workspace_id, title, body)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#,
+  )
+  .bind(uid)
+  .bind(device_id)
+  .bind(push.notification_id)
+  .bind(&push.notification_type)
+  .bind(push.workspace_id)
+  .bind(&push.title)
+  .bind(&push.body)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Push tokens of the devices having deferred push notifications.
+pub async fn select_push_tokens_with_deferred_pushes<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<AFPushTokenRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFPushTokenRow>(&format!(
+    r#"
+      {}
+      WHERE EXISTS (
+        SELECT 1 FROM af_push_deferred d WHERE d.uid = t.uid AND d.device_id = t.device_id
+      )
+    "#,
+    SELECT_PUSH_TOKEN_ROWS
+  ))
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Removes and returns the deferred push notifications of the device, oldest first.
+pub async fn take_deferred_pushes<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<Vec<AFDeferredPush>, AppError> {
+  let pushes = sqlx::query_as::<_, AFDeferredPush>(
+    r#"
+      WITH taken AS (
+        DELETE FROM af_push_deferred
+        WHERE uid = $1 AND device_id = $2
+        RETURNING id, notification_id, notification_type, workspace_id, title, body
+      )
+      SELECT notification_id, notification_type, workspace_id, title, body
+      FROM taken
+      ORDER BY id
+    "#,
+  )
+  .bind(uid)
+  .bind(device_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(pushes)
+}
+
+/// Deletes the deferred push notifications of devices that no longer have a push token.
+pub async fn delete_orphan_deferred_pushes<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<u64, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_push_deferred d
+      WHERE NOT EXISTS (
+        SELECT 1 FROM af_push_token t WHERE t.uid = d.uid AND t.device_id = d.device_id
+      )
+    "#,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
@@ -1,6 +1,7 @@
 use app_error::AppError;
+use chrono::{DateTime, Utc};
 use database_entity::dto::QuickNote;
-use sqlx::{Executor, Postgres, QueryBuilder};
+use sqlx::{Executor, Postgres, QueryBuilder, Transaction};
 use uuid::Uuid;
 
 use crate::pg_row::AFQuickNoteRow;
@@ -26,6 +27,46 @@ pub async fn insert_new_quick_note<'a, E: Executor<'a, Database = Postgres>>(
   Ok(quick_note)
 }
 
+/// Serializes the creation of quick notes by the user in the workspace until the end of the
+/// transaction, so that concurrent requests see the notes created by each other.
+pub async fn lock_quick_note_creation(
+  tx: &mut Transaction<'_, Postgres>,
+  workspace_id: Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+    .bind(format!("af_quick_note:{}:{}", workspace_id, uid))
+    .execute(tx.as_mut())
+    .await?;
+  Ok(())
+}
+
+/// Quick notes of the user in the workspace created in the last `window_secs` seconds, most
+/// recent first.
+pub async fn select_recent_quick_notes<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: Uuid,
+  uid: i64,
+  window_secs: i64,
+) -> Result<Vec<QuickNote>, AppError> {
+  let rows = sqlx::query_as::<_, AFQuickNoteRow>(
+    r#"
+      SELECT quick_note_id, data, created_at, updated_at
+      FROM af_quick_note
+      WHERE workspace_id = $1 AND uid = $2
+        AND created_at > NOW() - make_interval(secs => $3)
+      ORDER BY created_at DESC
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(window_secs as f64)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(Into::into).collect())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn select_quick_notes_with_one_more_than_limit<
   'a,
   E: Executor<'a, Database = Postgres>,
@@ -34,6 +75,8 @@ pub async fn select_quick_notes_with_one_more_than_limit<
   workspace_id: Uuid,
   uid: i64,
   search_term: Option<String>,
+  created_after: Option<DateTime<Utc>>,
+  created_before: Option<DateTime<Utc>>,
   offset: Option<i32>,
   limit: Option<i32>,
 ) -> Result<Vec<QuickNote>, AppError> {
@@ -55,6 +98,14 @@ pub async fn select_quick_notes_with_one_more_than_limit<
     let json_path_query = format!("'$.**.insert ? (@ like_regex \".*{}.*\")'", search_term);
     query_builder.push(json_path_query);
   }
+  if let Some(created_after) = created_after {
+    query_builder.push(" AND created_at >= ");
+    query_builder.push_bind(created_after);
+  }
+  if let Some(created_before) = created_before {
+    query_builder.push(" AND created_at < ");
+    query_builder.push_bind(created_before);
+  }
   query_builder.push(" ORDER BY updated_at DESC");
   if let Some(limit) = limit {
     query_builder.push(" LIMIT ");
@@ -1,4 +1,4 @@
-use crate::pg_row::AFBlobMetadataRow;
+use crate::pg_row::{AFBlobMetadataRow, AFBlobStorageClass};
 use app_error::AppError;
 use rust_decimal::prelude::ToPrimitive;
 use sqlx::types::Decimal;
@@ -213,3 +213,185 @@ pub async fn get_workspace_usage_size(pool: &PgPool, workspace_id: &Uuid) -> Res
     None => Ok(0),
   }
 }
+
+/// Records a read of the blob. To keep reads cheap the row is only written when the last
+/// recorded access is older than a day, or on the first read of a blob since it was moved out
+/// of the standard storage class, which marks it for [select_blobs_read_since_tiering].
+#[instrument(level = "trace", skip_all, err)]
+pub async fn touch_blob_metadata(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  metadata_key: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_blob_metadata
+      SET last_accessed_at = NOW()
+      WHERE workspace_id = $1 AND file_id = $2
+        AND (
+          last_accessed_at < NOW() - INTERVAL '1 day'
+          OR (storage_class <> 0 AND last_accessed_at <= storage_class_changed_at)
+        )
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(metadata_key)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+#[instrument(level = "trace", skip_all, err)]
+pub async fn update_blob_storage_class(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  metadata_key: &str,
+  storage_class: AFBlobStorageClass,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_blob_metadata
+      SET storage_class = $3, storage_class_changed_at = NOW()
+      WHERE workspace_id = $1 AND file_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(metadata_key)
+  .bind(storage_class as i16)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Moves the metadata of a blob to another workspace. The metadata is left in place when the
+/// other workspace already has a blob with the same key.
+pub async fn move_blob_metadata(
+  pg_pool: &PgPool,
+  from_workspace_id: &Uuid,
+  to_workspace_id: &Uuid,
+  metadata_key: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_blob_metadata SET workspace_id = $2
+      WHERE workspace_id = $1 AND file_id = $3
+        AND NOT EXISTS (
+          SELECT 1 FROM af_blob_metadata WHERE workspace_id = $2 AND file_id = $3
+        )
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .bind(metadata_key)
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Workspaces, with their owner, that have standard blobs which have not been read for at
+/// least `idle_days`.
+pub async fn select_workspaces_with_idle_blobs(
+  pg_pool: &PgPool,
+  idle_days: i32,
+  min_file_size: i64,
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let workspaces = sqlx::query_as::<_, (Uuid, i64)>(
+    r#"
+      SELECT w.workspace_id, w.owner_uid
+      FROM af_workspace w
+      WHERE w.owner_uid IS NOT NULL
+        AND EXISTS (
+          SELECT 1 FROM af_blob_metadata b
+          WHERE b.workspace_id = w.workspace_id
+            AND b.storage_class = 0
+            AND b.file_size >= $2
+            AND b.last_accessed_at < NOW() - make_interval(days => $1)
+        )
+    "#,
+  )
+  .bind(idle_days)
+  .bind(min_file_size)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(workspaces)
+}
+
+/// Standard blobs of the workspace that have not been read for at least `idle_days`, least
+/// recently accessed first.
+pub async fn select_idle_blob_ids(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  idle_days: i32,
+  min_file_size: i64,
+  limit: i64,
+) -> Result<Vec<String>, AppError> {
+  let file_ids = sqlx::query_scalar::<_, String>(
+    r#"
+      SELECT file_id FROM af_blob_metadata
+      WHERE workspace_id = $1
+        AND storage_class = 0
+        AND file_size >= $3
+        AND last_accessed_at < NOW() - make_interval(days => $2)
+      ORDER BY last_accessed_at
+      LIMIT $4
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(idle_days)
+  .bind(min_file_size)
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(file_ids)
+}
+
+/// Blobs outside the standard storage class that were read after they were moved there,
+/// least recently moved first.
+pub async fn select_blobs_read_since_tiering(
+  pg_pool: &PgPool,
+  limit: i64,
+) -> Result<Vec<(Uuid, String)>, AppError> {
+  let blobs = sqlx::query_as::<_, (Uuid, String)>(
+    r#"
+      SELECT workspace_id, file_id FROM af_blob_metadata
+      WHERE storage_class <> 0
+        AND last_accessed_at > storage_class_changed_at
+      ORDER BY storage_class_changed_at
+      LIMIT $1
+    "#,
+  )
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(blobs)
+}
+
+/// Number of blobs and their total size per storage class.
+pub async fn select_blob_storage_class_usage(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<(AFBlobStorageClass, i64, i64)>, AppError> {
+  let rows = sqlx::query_as::<_, (i16, i64, Decimal)>(
+    r#"
+      SELECT storage_class, COUNT(*), COALESCE(SUM(file_size), 0)
+      FROM af_blob_metadata
+      WHERE workspace_id = $1
+      GROUP BY storage_class
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(storage_class, count, bytes)| {
+        (
+          AFBlobStorageClass::from(storage_class),
+          count,
+          bytes.to_i64().unwrap_or(0),
+        )
+      })
+      .collect(),
+  )
+}
@@ -0,0 +1,271 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::{ScheduledExport, ScheduledExportDestination, ScheduledExportRun};
+use sqlx::types::Json;
+use sqlx::{Executor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::pg_row::{AFScheduledExportRow, AFScheduledExportRunRow};
+
+const SCHEDULED_EXPORT_COLUMNS: &str = "export_id, workspace_id, destination, encrypted_secret, \
+   interval_days, enabled, next_run_at, created_by, created_at, updated_at";
+
+const SCHEDULED_EXPORT_RUN_COLUMNS: &str =
+  "run_id, export_id, started_at, finished_at, status, documents, bytes, object_key, error";
+
+pub async fn insert_scheduled_export<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  destination: &ScheduledExportDestination,
+  encrypted_secret: &[u8],
+  interval_days: i32,
+) -> Result<ScheduledExport, AppError> {
+  let row = sqlx::query_as::<_, AFScheduledExportRow>(&format!(
+    r#"
+      INSERT INTO af_scheduled_export
+        (workspace_id, destination, encrypted_secret, interval_days, created_by)
+      VALUES ($1, $2, $3, $4, $5)
+      RETURNING {}
+    "#,
+    SCHEDULED_EXPORT_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(Json(destination))
+  .bind(encrypted_secret)
+  .bind(interval_days)
+  .bind(uid)
+  .fetch_one(executor)
+  .await?;
+  Ok(row.into())
+}
+
+/// Only the given fields are changed. Returns `None` when the export does not exist in the
+/// workspace.
+pub async fn update_scheduled_export<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+  destination: Option<&ScheduledExportDestination>,
+  encrypted_secret: Option<&[u8]>,
+  interval_days: Option<i32>,
+  enabled: Option<bool>,
+) -> Result<Option<ScheduledExport>, AppError> {
+  let mut query_builder: QueryBuilder<Postgres> =
+    QueryBuilder::new("UPDATE af_scheduled_export SET updated_at = NOW()");
+  if let Some(destination) = destination {
+    query_builder.push(", destination = ");
+    query_builder.push_bind(Json(destination));
+  }
+  if let Some(encrypted_secret) = encrypted_secret {
+    query_builder.push(", encrypted_secret = ");
+    query_builder.push_bind(encrypted_secret);
+  }
+  if let Some(interval_days) = interval_days {
+    query_builder.push(", interval_days = ");
+    query_builder.push_bind(interval_days);
+  }
+  if let Some(enabled) = enabled {
+    query_builder.push(", enabled = ");
+    query_builder.push_bind(enabled);
+  }
+  query_builder.push(" WHERE workspace_id = ");
+  query_builder.push_bind(workspace_id);
+  query_builder.push(" AND export_id = ");
+  query_builder.push_bind(export_id);
+  query_builder.push(" RETURNING ");
+  query_builder.push(SCHEDULED_EXPORT_COLUMNS);
+  let row = query_builder
+    .build_query_as::<AFScheduledExportRow>()
+    .fetch_optional(executor)
+    .await?;
+  Ok(row.map(Into::into))
+}
+
+/// Returns the destination of the deleted export, or `None` when the export does not exist in
+/// the workspace.
+pub async fn delete_scheduled_export<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+) -> Result<Option<ScheduledExportDestination>, AppError> {
+  let destination = sqlx::query_scalar::<_, Json<ScheduledExportDestination>>(
+    r#"
+      DELETE FROM af_scheduled_export
+      WHERE workspace_id = $1 AND export_id = $2
+      RETURNING destination
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(export_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(destination.map(|destination| destination.0))
+}
+
+pub async fn insert_scheduled_export_audit<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+  action: i16,
+  uid: Option<i64>,
+  destination: &ScheduledExportDestination,
+  address: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_scheduled_export_audit
+        (workspace_id, export_id, action, uid, destination, address)
+      VALUES ($1, $2, $3, $4, $5, $6)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(export_id)
+  .bind(action)
+  .bind(uid)
+  .bind(Json(destination))
+  .bind(address)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_scheduled_exports<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<ScheduledExport>, AppError> {
+  let rows = sqlx::query_as::<_, AFScheduledExportRow>(&format!(
+    r#"
+      SELECT {}
+      FROM af_scheduled_export
+      WHERE workspace_id = $1
+      ORDER BY created_at
+    "#,
+    SCHEDULED_EXPORT_COLUMNS
+  ))
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(Into::into).collect())
+}
+
+pub async fn select_scheduled_export<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+) -> Result<Option<AFScheduledExportRow>, AppError> {
+  let row = sqlx::query_as::<_, AFScheduledExportRow>(&format!(
+    r#"
+      SELECT {}
+      FROM af_scheduled_export
+      WHERE workspace_id = $1 AND export_id = $2
+    "#,
+    SCHEDULED_EXPORT_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(export_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Claims the enabled exports that are due by moving their next run forward by their interval,
+/// so that concurrent servers do not run the same export twice.
+pub async fn claim_due_scheduled_exports<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  limit: i64,
+) -> Result<Vec<AFScheduledExportRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFScheduledExportRow>(&format!(
+    r#"
+      UPDATE af_scheduled_export
+      SET next_run_at = NOW() + make_interval(days => interval_days)
+      WHERE export_id IN (
+        SELECT export_id
+        FROM af_scheduled_export
+        WHERE enabled AND next_run_at <= NOW()
+        ORDER BY next_run_at
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+      )
+      RETURNING {}
+    "#,
+    SCHEDULED_EXPORT_COLUMNS
+  ))
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn insert_scheduled_export_run<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+) -> Result<Uuid, AppError> {
+  let run_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      INSERT INTO af_scheduled_export_run (export_id, workspace_id)
+      VALUES ($1, $2)
+      RETURNING run_id
+    "#,
+  )
+  .bind(export_id)
+  .bind(workspace_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(run_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn update_scheduled_export_run<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  run_id: &Uuid,
+  status: i16,
+  finished_at: DateTime<Utc>,
+  documents: i32,
+  bytes: i64,
+  object_key: Option<&str>,
+  error: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_scheduled_export_run
+      SET status = $2, finished_at = $3, documents = $4, bytes = $5, object_key = $6, error = $7
+      WHERE run_id = $1
+    "#,
+  )
+  .bind(run_id)
+  .bind(status)
+  .bind(finished_at)
+  .bind(documents)
+  .bind(bytes)
+  .bind(object_key)
+  .bind(error)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_scheduled_export_runs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+  limit: i64,
+) -> Result<Vec<ScheduledExportRun>, AppError> {
+  let rows = sqlx::query_as::<_, AFScheduledExportRunRow>(&format!(
+    r#"
+      SELECT {}
+      FROM af_scheduled_export_run
+      WHERE workspace_id = $1 AND export_id = $2
+      ORDER BY started_at DESC
+      LIMIT $3
+    "#,
+    SCHEDULED_EXPORT_RUN_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(export_id)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(Into::into).collect())
+}
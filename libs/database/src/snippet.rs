@@ -0,0 +1,175 @@
+use app_error::AppError;
+use database_entity::dto::WorkspaceSnippet;
+use sqlx::{Executor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceSnippetRow;
+
+const SNIPPET_COLUMNS: &str =
+  "snippet_id, shortcut, name, description, content, created_by, created_at, updated_at";
+
+pub async fn insert_workspace_snippet<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  shortcut: &str,
+  name: &str,
+  description: Option<&str>,
+  content: &serde_json::Value,
+) -> Result<WorkspaceSnippet, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceSnippetRow>(&format!(
+    r#"
+      INSERT INTO af_workspace_snippet
+        (workspace_id, shortcut, name, description, content, created_by)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING {}
+    "#,
+    SNIPPET_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(shortcut)
+  .bind(name)
+  .bind(description)
+  .bind(content)
+  .bind(uid)
+  .fetch_one(executor)
+  .await
+  .map_err(|err| snippet_error(err, shortcut))?;
+  Ok(row.into())
+}
+
+/// Only the given fields are changed. Returns `None` when the snippet does not exist in the
+/// workspace.
+pub async fn update_workspace_snippet<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  snippet_id: &Uuid,
+  shortcut: Option<&str>,
+  name: Option<&str>,
+  description: Option<&str>,
+  content: Option<&serde_json::Value>,
+) -> Result<Option<WorkspaceSnippet>, AppError> {
+  let mut query_builder: QueryBuilder<Postgres> =
+    QueryBuilder::new("UPDATE af_workspace_snippet SET updated_at = NOW()");
+  if let Some(shortcut) = shortcut {
+    query_builder.push(", shortcut = ");
+    query_builder.push_bind(shortcut);
+  }
+  if let Some(name) = name {
+    query_builder.push(", name = ");
+    query_builder.push_bind(name);
+  }
+  if let Some(description) = description {
+    // An empty description clears it
+    query_builder.push(", description = NULLIF(");
+    query_builder.push_bind(description);
+    query_builder.push(", '')");
+  }
+  if let Some(content) = content {
+    query_builder.push(", content = ");
+    query_builder.push_bind(content);
+  }
+  query_builder.push(" WHERE workspace_id = ");
+  query_builder.push_bind(workspace_id);
+  query_builder.push(" AND snippet_id = ");
+  query_builder.push_bind(snippet_id);
+  query_builder.push(" RETURNING ");
+  query_builder.push(SNIPPET_COLUMNS);
+  let row = query_builder
+    .build_query_as::<AFWorkspaceSnippetRow>()
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| snippet_error(err, shortcut.unwrap_or_default()))?;
+  Ok(row.map(Into::into))
+}
+
+/// Returns whether the snippet existed in the workspace.
+pub async fn delete_workspace_snippet<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  snippet_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query(
+    r#"
+      DELETE FROM af_workspace_snippet WHERE workspace_id = $1 AND snippet_id = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(snippet_id)
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Snippets of the workspace ordered by shortcut, optionally only those whose shortcut
+/// starts with `prefix`.
+pub async fn select_workspace_snippets<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  prefix: Option<&str>,
+) -> Result<Vec<WorkspaceSnippet>, AppError> {
+  let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new("SELECT ");
+  query_builder.push(SNIPPET_COLUMNS);
+  query_builder.push(" FROM af_workspace_snippet WHERE workspace_id = ");
+  query_builder.push_bind(workspace_id);
+  if let Some(prefix) = prefix {
+    query_builder.push(" AND starts_with(shortcut, ");
+    query_builder.push_bind(prefix);
+    query_builder.push(")");
+  }
+  query_builder.push(" ORDER BY shortcut");
+  let rows = query_builder
+    .build_query_as::<AFWorkspaceSnippetRow>()
+    .fetch_all(executor)
+    .await?;
+  Ok(rows.into_iter().map(Into::into).collect())
+}
+
+pub async fn select_workspace_snippet_by_shortcut<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  shortcut: &str,
+) -> Result<Option<WorkspaceSnippet>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceSnippetRow>(&format!(
+    r#"
+      SELECT {} FROM af_workspace_snippet WHERE workspace_id = $1 AND shortcut = $2
+    "#,
+    SNIPPET_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(shortcut)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(Into::into))
+}
+
+pub async fn select_workspace_snippet_by_id<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  snippet_id: &Uuid,
+) -> Result<Option<WorkspaceSnippet>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceSnippetRow>(&format!(
+    r#"
+      SELECT {} FROM af_workspace_snippet WHERE workspace_id = $1 AND snippet_id = $2
+    "#,
+    SNIPPET_COLUMNS
+  ))
+  .bind(workspace_id)
+  .bind(snippet_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(Into::into))
+}
+
+fn snippet_error(err: sqlx::Error, shortcut: &str) -> AppError {
+  if err
+    .as_database_error()
+    .is_some_and(|err| err.is_unique_violation())
+  {
+    return AppError::RecordAlreadyExists(format!(
+      "a snippet with the shortcut {} already exists",
+      shortcut
+    ));
+  }
+  err.into()
+}
@@ -753,6 +753,35 @@ pub async fn upsert_usage_record(
   Ok(())
 }
 
+/// 重新把用户未归属订阅的用量记录归到用量日期当天有效的订阅上（与
+/// 20260630000000_backfill_usage_subscription_id 的回填规则一致），返回更新的记录数。
+#[instrument(skip_all, err)]
+pub async fn backfill_user_usage_subscription_id(
+  pg_pool: &PgPool,
+  uid: i64,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    r#"
+    UPDATE af_user_subscription_usage u
+    SET subscription_id = (
+      SELECT s.id
+      FROM af_user_subscriptions s
+      WHERE s.uid = u.uid
+        AND u.usage_date >= s.start_date::date
+        AND u.usage_date <= s.end_date::date
+      ORDER BY (s.status = 'active') DESC, s.id DESC
+      LIMIT 1
+    )
+    WHERE u.uid = $1 AND u.subscription_id IS NULL
+    "#,
+  )
+  .bind(uid)
+  .execute(pg_pool)
+  .await?;
+
+  Ok(result.rows_affected())
+}
+
 #[instrument(skip_all, err)]
 pub async fn get_user_total_storage_usage(pg_pool: &PgPool, uid: i64) -> Result<i64, AppError> {
   let row: (Option<i64>,) = sqlx::query_as(
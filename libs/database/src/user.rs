@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use database_entity::dto::{AFUserWithAvatar, AFWebUser};
 use futures_util::stream::BoxStream;
 use sqlx::postgres::PgArguments;
@@ -387,6 +389,19 @@ pub async fn select_name_from_uid(pool: &PgPool, uid: i64) -> Result<String, App
   Ok(name)
 }
 
+/// Names of the users in `uids`. Users that don't exist are left out.
+pub async fn select_names_from_uids(
+  pool: &PgPool,
+  uids: &[i64],
+) -> Result<HashMap<i64, String>, AppError> {
+  let rows: Vec<(i64, String)> =
+    sqlx::query_as(r#"SELECT uid, name FROM af_user WHERE uid = ANY($1)"#)
+      .bind(uids)
+      .fetch_all(pool)
+      .await?;
+  Ok(rows.into_iter().collect())
+}
+
 #[inline]
 pub async fn select_name_from_uuid(pool: &PgPool, user_uuid: &Uuid) -> Result<String, AppError> {
   let email = sqlx::query_scalar!(
@@ -439,6 +454,38 @@ pub async fn select_web_user_from_uid(
   Ok(row)
 }
 
+/// Web users by uid. Uids of deleted users are left out.
+pub async fn select_web_users_from_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uids: &[i64],
+) -> Result<HashMap<i64, AFWebUser>, AppError> {
+  let rows = sqlx::query_as::<_, (i64, Uuid, String, Option<String>)>(
+    r#"
+      SELECT uid, uuid, name, metadata ->> 'icon_url' AS avatar_url
+      FROM af_user
+      WHERE uid = ANY($1)
+    "#,
+  )
+  .bind(uids)
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(uid, uuid, name, avatar_url)| {
+        (
+          uid,
+          AFWebUser {
+            uuid,
+            name,
+            avatar_url,
+          },
+        )
+      })
+      .collect(),
+  )
+}
+
 pub async fn select_user_with_avatar<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   uid: i64,
@@ -0,0 +1,87 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::UserBackupInfo;
+use sqlx::{Executor, FromRow, Postgres};
+
+#[derive(FromRow)]
+struct AFUserBackupInfoRow {
+  version: i64,
+  size: i32,
+  device_id: Option<String>,
+  updated_at: DateTime<Utc>,
+}
+
+impl From<AFUserBackupInfoRow> for UserBackupInfo {
+  fn from(row: AFUserBackupInfoRow) -> Self {
+    Self {
+      version: row.version,
+      size: row.size as i64,
+      device_id: row.device_id,
+      updated_at: row.updated_at,
+    }
+  }
+}
+
+const USER_BACKUP_INFO_COLUMNS: &str = "version, octet_length(data) AS size, device_id, updated_at";
+
+/// Writes the backup of the user, incrementing its version. With `expected_version`, the backup
+/// is only written when its current version is that version, 0 standing for no backup. Returns
+/// `None` when it was not written.
+pub async fn upsert_user_backup<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  data: &[u8],
+  device_id: Option<&str>,
+  expected_version: Option<i64>,
+) -> Result<Option<UserBackupInfo>, AppError> {
+  let row = sqlx::query_as::<_, AFUserBackupInfoRow>(&format!(
+    r#"
+      INSERT INTO af_user_backup (uid, data, device_id)
+      SELECT $1, $2, $3
+      -- Expecting an existing backup, while there is none, writes nothing
+      WHERE COALESCE($4::BIGINT, 0) = 0 OR EXISTS (SELECT 1 FROM af_user_backup WHERE uid = $1)
+      ON CONFLICT (uid) DO UPDATE SET
+        version = af_user_backup.version + 1,
+        data = EXCLUDED.data,
+        device_id = EXCLUDED.device_id,
+        updated_at = NOW()
+      WHERE $4::BIGINT IS NULL OR af_user_backup.version = $4
+      RETURNING {}
+    "#,
+    USER_BACKUP_INFO_COLUMNS
+  ))
+  .bind(uid)
+  .bind(data)
+  .bind(device_id)
+  .bind(expected_version)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(Into::into))
+}
+
+pub async fn select_user_backup_info<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Option<UserBackupInfo>, AppError> {
+  let row = sqlx::query_as::<_, AFUserBackupInfoRow>(&format!(
+    "SELECT {} FROM af_user_backup WHERE uid = $1",
+    USER_BACKUP_INFO_COLUMNS
+  ))
+  .bind(uid)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(Into::into))
+}
+
+/// Returns the version and the data of the backup of the user.
+pub async fn select_user_backup<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Option<(i64, Vec<u8>)>, AppError> {
+  let backup =
+    sqlx::query_as::<_, (i64, Vec<u8>)>("SELECT version, data FROM af_user_backup WHERE uid = $1")
+      .bind(uid)
+      .fetch_optional(executor)
+      .await?;
+  Ok(backup)
+}
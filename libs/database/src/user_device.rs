@@ -0,0 +1,199 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database_entity::dto::{DeviceHandoff, DeviceHandoffKind, UserDevice};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+pub async fn upsert_user_device<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+  client_version: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_user_device (uid, device_id, client_version, last_seen_at)
+      VALUES ($1, $2, $3, NOW())
+      ON CONFLICT (uid, device_id) DO UPDATE SET
+        client_version = COALESCE(EXCLUDED.client_version, af_user_device.client_version),
+        last_seen_at = EXCLUDED.last_seen_at
+    "#,
+  )
+  .bind(uid)
+  .bind(device_id)
+  .bind(client_version)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Devices of the user seen since `seen_since`, most recently seen first.
+pub async fn select_user_devices<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  seen_since: DateTime<Utc>,
+) -> Result<Vec<UserDevice>, AppError> {
+  let devices = sqlx::query_as::<_, (String, Option<String>, DateTime<Utc>)>(
+    r#"
+      SELECT device_id, client_version, last_seen_at
+      FROM af_user_device
+      WHERE uid = $1 AND last_seen_at >= $2
+      ORDER BY last_seen_at DESC
+    "#,
+  )
+  .bind(uid)
+  .bind(seen_since)
+  .fetch_all(executor)
+  .await?
+  .into_iter()
+  .map(|(device_id, client_version, last_seen_at)| UserDevice {
+    device_id,
+    client_version,
+    last_seen_at,
+  })
+  .collect();
+  Ok(devices)
+}
+
+pub async fn is_user_device<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar::<_, bool>(
+    "SELECT EXISTS(SELECT 1 FROM af_user_device WHERE uid = $1 AND device_id = $2)",
+  )
+  .bind(uid)
+  .bind(device_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(exists)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_device_handoff<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  source_device_id: Option<&str>,
+  target_device_id: Option<&str>,
+  kind: DeviceHandoffKind,
+  content: &str,
+  title: Option<&str>,
+  expires_at: DateTime<Utc>,
+) -> Result<Uuid, AppError> {
+  let handoff_id = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      INSERT INTO af_device_handoff
+        (uid, source_device_id, target_device_id, kind, content, title, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING handoff_id
+    "#,
+  )
+  .bind(uid)
+  .bind(source_device_id)
+  .bind(target_device_id)
+  .bind(kind as i16)
+  .bind(content)
+  .bind(title)
+  .bind(expires_at)
+  .fetch_one(executor)
+  .await?;
+  Ok(handoff_id)
+}
+
+/// Unexpired handoffs sent to the device, or to every device but their sender, that the device
+/// has not processed yet. Oldest first.
+pub async fn select_pending_device_handoffs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<Vec<DeviceHandoff>, AppError> {
+  let rows = sqlx::query_as::<
+    _,
+    (
+      Uuid,
+      Option<String>,
+      i16,
+      String,
+      Option<String>,
+      DateTime<Utc>,
+      DateTime<Utc>,
+    ),
+  >(
+    r#"
+      SELECT h.handoff_id, h.source_device_id, h.kind, h.content, h.title, h.created_at, h.expires_at
+      FROM af_device_handoff h
+      WHERE h.uid = $1
+        AND h.expires_at > NOW()
+        AND (
+          h.target_device_id = $2
+          OR (h.target_device_id IS NULL AND h.source_device_id IS DISTINCT FROM $2)
+        )
+        AND NOT EXISTS (
+          SELECT 1 FROM af_device_handoff_receipt r
+          WHERE r.handoff_id = h.handoff_id AND r.device_id = $2
+        )
+      ORDER BY h.created_at
+    "#,
+  )
+  .bind(uid)
+  .bind(device_id)
+  .fetch_all(executor)
+  .await?;
+  let handoffs = rows
+    .into_iter()
+    .map(
+      |(handoff_id, source_device_id, kind, content, title, created_at, expires_at)| {
+        DeviceHandoff {
+          handoff_id,
+          source_device_id,
+          kind: DeviceHandoffKind::from(kind),
+          content,
+          title,
+          created_at,
+          expires_at,
+        }
+      },
+    )
+    .collect();
+  Ok(handoffs)
+}
+
+/// Records that the device processed the handoff. Returns false when the user has no such
+/// handoff.
+pub async fn insert_device_handoff_receipt<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  handoff_id: &Uuid,
+  device_id: &str,
+) -> Result<bool, AppError> {
+  let found = sqlx::query_scalar::<_, bool>(
+    r#"
+      WITH handoff AS (
+        SELECT handoff_id FROM af_device_handoff WHERE handoff_id = $1 AND uid = $2
+      ),
+      receipt AS (
+        INSERT INTO af_device_handoff_receipt (handoff_id, device_id)
+        SELECT handoff_id, $3 FROM handoff
+        ON CONFLICT (handoff_id, device_id) DO NOTHING
+      )
+      SELECT EXISTS(SELECT 1 FROM handoff)
+    "#,
+  )
+  .bind(handoff_id)
+  .bind(uid)
+  .bind(device_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(found)
+}
+
+/// Deletes the expired handoffs with their receipts, so that their content is not kept.
+pub async fn delete_expired_device_handoffs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<u64, AppError> {
+  let res = sqlx::query("DELETE FROM af_device_handoff WHERE expires_at <= NOW()")
+    .execute(executor)
+    .await?;
+  Ok(res.rows_affected())
+}
@@ -0,0 +1,314 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use serde_json::Value;
+use sqlx::{Executor, Postgres, Transaction};
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+use crate::collab::partition_key_from_collab_type;
+use crate::pg_row::AFViewSection;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFViewMetadataRow {
+  pub view_id: Uuid,
+  pub parent_view_id: Option<Uuid>,
+  pub prev_view_id: Option<Uuid>,
+  pub name: String,
+  pub icon: Option<Value>,
+  pub layout: i16,
+  pub created_at: DateTime<Utc>,
+  pub created_by: Option<i64>,
+  pub last_edited_by: Option<i64>,
+  pub last_edited_time: DateTime<Utc>,
+  pub is_locked: Option<bool>,
+  pub extra: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AFViewSectionItemRow {
+  pub uid: i64,
+  pub section: AFViewSection,
+  pub view_id: Uuid,
+  pub timestamp: DateTime<Utc>,
+}
+
+/// Serializes the syncs of the view metadata of the workspace, so that a sync that read an
+/// older folder never overwrites a newer one.
+pub async fn lock_workspace_view_metadata(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+    .bind(format!("af_view_metadata:{}", workspace_id))
+    .execute(txn.deref_mut())
+    .await?;
+  Ok(())
+}
+
+/// Brings the view metadata and section items of the workspace in line with `views` and
+/// `section_items`, and records `synced_at` as the time of the folder state they were read
+/// from. Only the rows that changed are written. Section items are kept in the order given.
+pub async fn update_workspace_view_metadata(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  views: &[AFViewMetadataRow],
+  section_items: &[AFViewSectionItemRow],
+  synced_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+  let mut view_ids = Vec::with_capacity(views.len());
+  let mut parent_view_ids = Vec::with_capacity(views.len());
+  let mut prev_view_ids = Vec::with_capacity(views.len());
+  let mut names = Vec::with_capacity(views.len());
+  let mut icons = Vec::with_capacity(views.len());
+  let mut layouts = Vec::with_capacity(views.len());
+  let mut created_ats = Vec::with_capacity(views.len());
+  let mut created_bys = Vec::with_capacity(views.len());
+  let mut last_edited_bys = Vec::with_capacity(views.len());
+  let mut last_edited_times = Vec::with_capacity(views.len());
+  let mut is_lockeds = Vec::with_capacity(views.len());
+  let mut extras = Vec::with_capacity(views.len());
+  for view in views {
+    view_ids.push(view.view_id);
+    parent_view_ids.push(view.parent_view_id);
+    prev_view_ids.push(view.prev_view_id);
+    names.push(view.name.clone());
+    icons.push(view.icon.clone());
+    layouts.push(view.layout);
+    created_ats.push(view.created_at);
+    created_bys.push(view.created_by);
+    last_edited_bys.push(view.last_edited_by);
+    last_edited_times.push(view.last_edited_time);
+    is_lockeds.push(view.is_locked);
+    extras.push(view.extra.clone());
+  }
+  sqlx::query("DELETE FROM af_view_metadata WHERE workspace_id = $1 AND view_id <> ALL($2)")
+    .bind(workspace_id)
+    .bind(&view_ids)
+    .execute(txn.deref_mut())
+    .await?;
+  sqlx::query(
+    r#"
+      INSERT INTO af_view_metadata
+        (workspace_id, view_id, parent_view_id, prev_view_id, name, icon, layout, created_at,
+         created_by, last_edited_by, last_edited_time, is_locked, extra, updated_at)
+      SELECT $1, v.view_id, v.parent_view_id, v.prev_view_id, v.name, v.icon, v.layout,
+        v.created_at, v.created_by, v.last_edited_by, v.last_edited_time, v.is_locked, v.extra, $14
+      FROM UNNEST($2::uuid[], $3::uuid[], $4::uuid[], $5::text[], $6::jsonb[], $7::smallint[],
+        $8::timestamptz[], $9::bigint[], $10::bigint[], $11::timestamptz[], $12::boolean[],
+        $13::jsonb[])
+        AS v(view_id, parent_view_id, prev_view_id, name, icon, layout, created_at, created_by,
+          last_edited_by, last_edited_time, is_locked, extra)
+      ON CONFLICT (workspace_id, view_id) DO UPDATE SET
+        parent_view_id = EXCLUDED.parent_view_id,
+        prev_view_id = EXCLUDED.prev_view_id,
+        name = EXCLUDED.name,
+        icon = EXCLUDED.icon,
+        layout = EXCLUDED.layout,
+        created_at = EXCLUDED.created_at,
+        created_by = EXCLUDED.created_by,
+        last_edited_by = EXCLUDED.last_edited_by,
+        last_edited_time = EXCLUDED.last_edited_time,
+        is_locked = EXCLUDED.is_locked,
+        extra = EXCLUDED.extra,
+        updated_at = EXCLUDED.updated_at
+      WHERE (af_view_metadata.parent_view_id, af_view_metadata.prev_view_id,
+          af_view_metadata.name, af_view_metadata.icon, af_view_metadata.layout,
+          af_view_metadata.created_at, af_view_metadata.created_by,
+          af_view_metadata.last_edited_by, af_view_metadata.last_edited_time,
+          af_view_metadata.is_locked, af_view_metadata.extra)
+        IS DISTINCT FROM (EXCLUDED.parent_view_id, EXCLUDED.prev_view_id, EXCLUDED.name,
+          EXCLUDED.icon, EXCLUDED.layout, EXCLUDED.created_at, EXCLUDED.created_by,
+          EXCLUDED.last_edited_by, EXCLUDED.last_edited_time, EXCLUDED.is_locked,
+          EXCLUDED.extra)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(&view_ids)
+  .bind(parent_view_ids)
+  .bind(prev_view_ids)
+  .bind(names)
+  .bind(icons)
+  .bind(layouts)
+  .bind(created_ats)
+  .bind(created_bys)
+  .bind(last_edited_bys)
+  .bind(last_edited_times)
+  .bind(is_lockeds)
+  .bind(extras)
+  .bind(synced_at)
+  .execute(txn.deref_mut())
+  .await?;
+
+  let mut uids = Vec::with_capacity(section_items.len());
+  let mut sections = Vec::with_capacity(section_items.len());
+  let mut item_view_ids = Vec::with_capacity(section_items.len());
+  let mut positions = Vec::with_capacity(section_items.len());
+  let mut timestamps = Vec::with_capacity(section_items.len());
+  for (position, item) in section_items.iter().enumerate() {
+    uids.push(item.uid);
+    sections.push(item.section as i16);
+    item_view_ids.push(item.view_id);
+    positions.push(position as i32);
+    timestamps.push(item.timestamp);
+  }
+  sqlx::query(
+    r#"
+      DELETE FROM af_view_section_item s
+      WHERE s.workspace_id = $1
+        AND NOT EXISTS (
+          SELECT 1
+          FROM UNNEST($2::bigint[], $3::smallint[], $4::uuid[]) AS n(uid, section, view_id)
+          WHERE n.uid = s.uid AND n.section = s.section AND n.view_id = s.view_id
+        )
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(&uids)
+  .bind(&sections)
+  .bind(&item_view_ids)
+  .execute(txn.deref_mut())
+  .await?;
+  sqlx::query(
+    r#"
+      INSERT INTO af_view_section_item
+        (workspace_id, uid, section, view_id, position, item_timestamp)
+      SELECT $1, n.uid, n.section, n.view_id, n.position, n.item_timestamp
+      FROM UNNEST($2::bigint[], $3::smallint[], $4::uuid[], $5::int[], $6::timestamptz[])
+        AS n(uid, section, view_id, position, item_timestamp)
+      ON CONFLICT (workspace_id, uid, section, view_id) DO UPDATE SET
+        position = EXCLUDED.position,
+        item_timestamp = EXCLUDED.item_timestamp
+      WHERE (af_view_section_item.position, af_view_section_item.item_timestamp)
+        IS DISTINCT FROM (EXCLUDED.position, EXCLUDED.item_timestamp)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uids)
+  .bind(sections)
+  .bind(item_view_ids)
+  .bind(positions)
+  .bind(timestamps)
+  .execute(txn.deref_mut())
+  .await?;
+
+  sqlx::query(
+    r#"
+      INSERT INTO af_view_metadata_sync (workspace_id, synced_at)
+      VALUES ($1, $2)
+      ON CONFLICT (workspace_id) DO UPDATE SET synced_at = EXCLUDED.synced_at
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(synced_at)
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+pub async fn is_workspace_view_metadata_synced<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<bool, AppError> {
+  let synced = sqlx::query_scalar::<_, bool>(
+    r#"
+      SELECT EXISTS (SELECT 1 FROM af_view_metadata_sync WHERE workspace_id = $1)
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(synced)
+}
+
+/// Items of a section of the user, in section order, with the time each view was added to the
+/// section. Items whose view no longer exists are left out.
+pub async fn select_view_section_items<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  section: AFViewSection,
+) -> Result<Vec<(Uuid, DateTime<Utc>)>, AppError> {
+  let rows = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+    r#"
+      SELECT s.view_id, s.item_timestamp
+      FROM af_view_section_item s
+      JOIN af_view_metadata m ON m.workspace_id = s.workspace_id AND m.view_id = s.view_id
+      WHERE s.workspace_id = $1 AND s.uid = $2 AND s.section = $3
+      ORDER BY s.position
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(section as i16)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Parent of every view of the workspace as of the last sync.
+pub async fn select_workspace_view_parents<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(Uuid, Option<Uuid>)>, AppError> {
+  let rows = sqlx::query_as::<_, (Uuid, Option<Uuid>)>(
+    r#"
+      SELECT view_id, parent_view_id
+      FROM af_view_metadata
+      WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_view_metadata<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<Vec<AFViewMetadataRow>, AppError> {
+  let rows = sqlx::query_as::<_, AFViewMetadataRow>(
+    r#"
+      SELECT view_id, parent_view_id, prev_view_id, name, icon, layout, created_at, created_by,
+        last_edited_by, last_edited_time, is_locked, extra
+      FROM af_view_metadata
+      WHERE workspace_id = $1 AND view_id = ANY($2)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_ids)
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Workspaces whose folder changed since their view metadata was last rebuilt, or that have
+/// never been synced. Never synced workspaces come first.
+pub async fn select_workspaces_due_for_view_metadata_sync<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT c.workspace_id
+      FROM af_collab c
+      LEFT JOIN af_view_metadata_sync s ON s.workspace_id = c.workspace_id
+      WHERE c.oid = c.workspace_id
+        AND c.partition_key = $1
+        AND c.deleted_at IS NULL
+        AND (s.synced_at IS NULL OR c.updated_at > s.synced_at)
+      ORDER BY s.synced_at ASC NULLS FIRST
+      LIMIT $2
+    "#,
+  )
+  .bind(partition_key_from_collab_type(&CollabType::Folder))
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
@@ -0,0 +1,99 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres, Transaction};
+use std::ops::DerefMut;
+use uuid::Uuid;
+
+/// Makes `slug` the current slug of the view. The previous slug is kept so that it still
+/// resolves to the view. Fails when the slug, current or previous, belongs to another view.
+pub async fn upsert_current_view_slug(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  slug: &str,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_view_slug SET is_current = FALSE
+      WHERE workspace_id = $1 AND view_id = $2 AND is_current AND slug <> $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .bind(slug)
+  .execute(txn.deref_mut())
+  .await?;
+
+  let res = sqlx::query(
+    r#"
+      INSERT INTO af_view_slug (workspace_id, slug, view_id)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, slug) DO UPDATE SET is_current = TRUE, created_at = NOW()
+      WHERE af_view_slug.view_id = EXCLUDED.view_id
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(slug)
+  .bind(view_id)
+  .execute(txn.deref_mut())
+  .await?;
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "the slug {} is used by another page",
+      slug
+    )));
+  }
+  Ok(())
+}
+
+pub async fn select_current_view_slug<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Option<String>, AppError> {
+  let slug = sqlx::query_scalar::<_, String>(
+    r#"
+      SELECT slug FROM af_view_slug WHERE workspace_id = $1 AND view_id = $2 AND is_current
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(slug)
+}
+
+/// The view `slug` points to, with the current slug of that view.
+pub async fn select_view_by_slug<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  slug: &str,
+) -> Result<Option<(Uuid, String)>, AppError> {
+  let view = sqlx::query_as::<_, (Uuid, String)>(
+    r#"
+      SELECT s.view_id, c.slug
+      FROM af_view_slug s
+      JOIN af_view_slug c
+        ON c.workspace_id = s.workspace_id AND c.view_id = s.view_id AND c.is_current
+      WHERE s.workspace_id = $1 AND s.slug = $2
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(slug)
+  .fetch_optional(executor)
+  .await?;
+  Ok(view)
+}
+
+/// Removes the current and previous slugs of the view. Returns whether the view had any.
+pub async fn delete_view_slugs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query("DELETE FROM af_view_slug WHERE workspace_id = $1 AND view_id = $2")
+    .bind(workspace_id)
+    .bind(view_id)
+    .execute(executor)
+    .await?;
+  Ok(res.rows_affected() > 0)
+}
@@ -3,7 +3,7 @@ use database_entity::dto::{
   AFRole, AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceSettings, GlobalComment,
   InvitationCodeInfo, MentionableWorkspaceMemberOrGuest,
   MentionableWorkspaceMemberOrGuestWithLastMentionedTime, PageMentionUpdate, Reaction,
-  WorkspaceMemberProfile,
+  WorkspaceMemberProfile, WorkspaceMemberStatus,
 };
 use futures_util::stream::BoxStream;
 use sqlx::{types::uuid, Acquire, Executor, PgPool, Postgres, Transaction};
@@ -14,8 +14,9 @@ use uuid::Uuid;
 use crate::pg_row::{
   AFCollabMemberPermRow, AFExplicitCollabMemberRow, AFGlobalCommentRow, AFImportTask,
   AFPermissionRow, AFReactionRow, AFUserProfileRow, AFWebUserWithEmailColumn,
-  AFWorkspaceInvitationMinimal, AFWorkspaceMemberPermRow, AFWorkspaceMemberRow, AFWorkspaceRow,
-  AFWorkspaceRowWithMemberCountAndRole,
+  AFWorkspaceDirectoryRow, AFWorkspaceInvitationMinimal, AFWorkspaceMemberExportRow,
+  AFWorkspaceMemberPermRow, AFWorkspaceMemberRow, AFWorkspacePendingInvitationExportRow,
+  AFWorkspaceRow, AFWorkspaceRowWithMemberCountAndRole,
 };
 use crate::user::select_uid_from_email;
 use app_error::AppError;
@@ -698,6 +699,58 @@ pub async fn select_workspace_member_list(
   Ok(members)
 }
 
+/// Streams the members of the workspace, guests included, with the last time one of their
+/// devices connected.
+pub fn select_workspace_member_export_stream<'a>(
+  pg_pool: &'a PgPool,
+  workspace_id: &'a Uuid,
+) -> BoxStream<'a, sqlx::Result<AFWorkspaceMemberExportRow>> {
+  sqlx::query_as::<_, AFWorkspaceMemberExportRow>(
+    r#"
+    SELECT
+      af_user.name,
+      af_user.email,
+      af_workspace_member.role_id,
+      af_workspace_member.created_at AS joined_at,
+      (
+        SELECT MAX(af_user_device.last_seen_at)
+        FROM af_user_device
+        WHERE af_user_device.uid = af_user.uid
+      ) AS last_active_at
+    FROM public.af_workspace_member
+        JOIN public.af_user ON af_workspace_member.uid = af_user.uid
+    WHERE af_workspace_member.workspace_id = $1
+    ORDER BY af_workspace_member.created_at ASC
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch(pg_pool)
+}
+
+pub async fn select_workspace_pending_invitation_export_list(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspacePendingInvitationExportRow>, AppError> {
+  let invitations = sqlx::query_as::<_, AFWorkspacePendingInvitationExportRow>(
+    r#"
+    SELECT
+      af_workspace_invitation.invitee_email,
+      af_workspace_invitation.role_id,
+      af_user.name AS inviter_name,
+      af_workspace_invitation.created_at AS invited_at
+    FROM public.af_workspace_invitation
+        LEFT JOIN public.af_user ON af_workspace_invitation.inviter = af_user.uid
+    WHERE af_workspace_invitation.workspace_id = $1
+    AND af_workspace_invitation.status = 0
+    ORDER BY af_workspace_invitation.created_at ASC
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(invitations)
+}
+
 pub async fn select_workspace_member_list_exclude_guest(
   pg_pool: &PgPool,
   workspace_id: &uuid::Uuid,
@@ -1540,6 +1593,72 @@ pub async fn update_comment_deletion_status<'a, E: Executor<'a, Database = Postg
   Ok(())
 }
 
+/// A comment on a published view that is still visible, with its author and where the view
+/// is published.
+#[derive(Debug, sqlx::FromRow)]
+pub struct PublishedViewCommentContext {
+  pub comment_id: Uuid,
+  pub view_id: Uuid,
+  pub workspace_id: Uuid,
+  pub author_uid: i64,
+  pub author_name: String,
+  pub author_email: Option<String>,
+  pub publish_name: String,
+  pub namespace: String,
+  pub comments_enabled: bool,
+}
+
+/// Returns `None` when the comment was deleted, or when its view is no longer published.
+pub async fn select_published_view_comment_context<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+) -> Result<Option<PublishedViewCommentContext>, AppError> {
+  let context = sqlx::query_as::<_, PublishedViewCommentContext>(
+    r#"
+      SELECT
+        avc.comment_id,
+        avc.view_id,
+        apc.workspace_id,
+        au.uid AS author_uid,
+        au.name AS author_name,
+        au.email AS author_email,
+        apc.publish_name,
+        (
+          SELECT awn.namespace FROM af_workspace_namespace awn
+          WHERE awn.workspace_id = apc.workspace_id AND NOT awn.is_alias
+          ORDER BY awn.is_original, awn.created_at DESC
+          LIMIT 1
+        ) AS namespace,
+        apc.comments_enabled
+      FROM af_published_view_comment avc
+      JOIN af_user au ON au.uid = avc.created_by
+      JOIN af_published_collab apc ON apc.view_id = avc.view_id AND apc.unpublished_at IS NULL
+      WHERE avc.comment_id = $1 AND NOT avc.is_deleted
+    "#,
+  )
+  .bind(comment_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(context)
+}
+
+/// Whether the view accepts comments, or `None` when it is not published.
+pub async fn select_published_view_comments_enabled<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Option<bool>, AppError> {
+  let comments_enabled = sqlx::query_scalar::<_, bool>(
+    r#"
+      SELECT comments_enabled FROM af_published_collab
+      WHERE view_id = $1 AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(view_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(comments_enabled)
+}
+
 pub async fn select_reactions_for_published_view_ordered_by_reaction_type_creation_time<
   'a,
   E: Executor<'a, Database = Postgres>,
@@ -1551,12 +1670,12 @@ pub async fn select_reactions_for_published_view_ordered_by_reaction_type_creati
     AFReactionRow,
     r#"
       SELECT
-        avr.comment_id,
+        avr.comment_id AS "comment_id!",
         avr.reaction_type,
         ARRAY_AGG((au.uuid, au.name, au.email, au.metadata ->> 'icon_url')) AS "react_users!: Vec<AFWebUserWithEmailColumn>"
       FROM af_published_view_reaction avr
       INNER JOIN af_user au ON avr.created_by = au.uid
-      WHERE view_id = $1
+      WHERE view_id = $1 AND avr.object_scope = 'published_comment'
       GROUP BY comment_id, reaction_type
       ORDER BY MIN(avr.created_at)
     "#,
@@ -1582,7 +1701,7 @@ pub async fn select_reactions_for_comment_ordered_by_reaction_type_creation_time
       SELECT
         avr.reaction_type,
         ARRAY_AGG((au.uuid, au.name, au.email, au.metadata ->> 'icon_url')) AS "react_users!: Vec<AFWebUserWithEmailColumn>",
-        avr.comment_id
+        avr.comment_id AS "comment_id!"
       FROM af_published_view_reaction avr
       INNER JOIN af_user au ON avr.created_by = au.uid
       WHERE comment_id = $1
@@ -2155,14 +2274,21 @@ pub async fn upsert_workspace_member_profile<'a, E: Executor<'a, Database = Post
 ) -> Result<(), AppError> {
   sqlx::query!(
     r#"
-      INSERT INTO af_workspace_member_profile (workspace_id, uid, name, avatar_url, cover_image_url, custom_image_url, description)
-      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      INSERT INTO af_workspace_member_profile (
+        workspace_id, uid, name, avatar_url, cover_image_url, custom_image_url, description,
+        title, department, pronouns, links
+      )
+      VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
       ON CONFLICT (workspace_id, uid) DO UPDATE
       SET name = EXCLUDED.name,
           avatar_url = EXCLUDED.avatar_url,
           cover_image_url = EXCLUDED.cover_image_url,
           custom_image_url = EXCLUDED.custom_image_url,
-          description = EXCLUDED.description
+          description = EXCLUDED.description,
+          title = EXCLUDED.title,
+          department = EXCLUDED.department,
+          pronouns = EXCLUDED.pronouns,
+          links = EXCLUDED.links
     "#,
     workspace_id,
     uid,
@@ -2170,7 +2296,11 @@ pub async fn upsert_workspace_member_profile<'a, E: Executor<'a, Database = Post
     updated_profile.avatar_url,
     updated_profile.cover_image_url,
     updated_profile.custom_image_url,
-    updated_profile.description
+    updated_profile.description,
+    updated_profile.title,
+    updated_profile.department,
+    updated_profile.pronouns,
+    sqlx::types::Json(&updated_profile.links) as _,
   )
   .execute(executor)
   .await?;
@@ -2178,6 +2308,160 @@ pub async fn upsert_workspace_member_profile<'a, E: Executor<'a, Database = Post
   Ok(())
 }
 
+/// Sets the availability status of a member, or clears it when `status` is `None`.
+pub async fn upsert_workspace_member_status<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  status: Option<&WorkspaceMemberStatus>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_member_profile (workspace_id, uid, status_emoji, status_text, status_until)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (workspace_id, uid) DO UPDATE
+      SET status_emoji = EXCLUDED.status_emoji,
+          status_text = EXCLUDED.status_text,
+          status_until = EXCLUDED.status_until
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(uid)
+  .bind(status.and_then(|s| s.emoji.as_deref()))
+  .bind(status.map(|s| s.text.as_str()))
+  .bind(status.and_then(|s| s.until))
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the statuses of the workspace members that are not expired, with the uid and uuid
+/// of each member.
+pub async fn select_active_workspace_member_statuses<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(i64, Uuid, WorkspaceMemberStatus)>, AppError> {
+  let rows = sqlx::query_as::<_, (i64, Uuid, Option<String>, String, Option<DateTime<Utc>>)>(
+    r#"
+      SELECT awmp.uid, au.uuid, awmp.status_emoji, awmp.status_text, awmp.status_until
+      FROM af_workspace_member_profile awmp
+      JOIN af_user au ON awmp.uid = au.uid
+      WHERE awmp.workspace_id = $1
+        AND awmp.status_text IS NOT NULL
+        AND (awmp.status_until IS NULL OR awmp.status_until > NOW())
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(uid, uuid, emoji, text, until)| {
+        (uid, uuid, WorkspaceMemberStatus { emoji, text, until })
+      })
+      .collect(),
+  )
+}
+
+/// Returns the status of the member with the given uuid, unless it is expired.
+pub async fn select_active_workspace_member_status<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  member_uuid: &Uuid,
+) -> Result<Option<WorkspaceMemberStatus>, AppError> {
+  let row = sqlx::query_as::<_, (Option<String>, String, Option<DateTime<Utc>>)>(
+    r#"
+      SELECT awmp.status_emoji, awmp.status_text, awmp.status_until
+      FROM af_workspace_member_profile awmp
+      JOIN af_user au ON awmp.uid = au.uid
+      WHERE awmp.workspace_id = $1
+        AND au.uuid = $2
+        AND awmp.status_text IS NOT NULL
+        AND (awmp.status_until IS NULL OR awmp.status_until > NOW())
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(member_uuid)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(|(emoji, text, until)| WorkspaceMemberStatus { emoji, text, until }))
+}
+
+/// Returns one page of the workspace members, ordered by name, and the number of members that
+/// match `search`.
+pub async fn select_workspace_directory(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  search: Option<&str>,
+  offset: i64,
+  limit: i64,
+) -> Result<(Vec<AFWorkspaceDirectoryRow>, i64), AppError> {
+  let search = search
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .map(escape_like_pattern);
+  let filter = r#"
+      FROM af_workspace_member awm
+      JOIN af_user au ON awm.uid = au.uid
+      LEFT JOIN af_workspace_member_profile awmp ON (awm.uid = awmp.uid AND awm.workspace_id = awmp.workspace_id)
+      WHERE awm.workspace_id = $1
+        AND (
+          $2::TEXT IS NULL
+          OR COALESCE(awmp.name, au.name) ILIKE '%' || $2 || '%' ESCAPE '\'
+          OR au.email ILIKE '%' || $2 || '%' ESCAPE '\'
+          OR awmp.title ILIKE '%' || $2 || '%' ESCAPE '\'
+          OR awmp.department ILIKE '%' || $2 || '%' ESCAPE '\'
+        )
+  "#;
+  let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", filter))
+    .bind(workspace_id)
+    .bind(&search)
+    .fetch_one(pg_pool)
+    .await?;
+  let rows = sqlx::query_as::<_, AFWorkspaceDirectoryRow>(&format!(
+    r#"
+      SELECT
+        au.uuid,
+        COALESCE(awmp.name, au.name) AS name,
+        au.email,
+        awm.role_id,
+        COALESCE(awmp.avatar_url, au.metadata ->> 'icon_url') AS avatar_url,
+        awmp.description,
+        awmp.title,
+        awmp.department,
+        awmp.pronouns,
+        COALESCE(awmp.links, '[]'::jsonb) AS links,
+        COALESCE(awmp.custom_fields, '{{}}'::jsonb) AS custom_fields
+      {}
+      ORDER BY LOWER(COALESCE(awmp.name, au.name)), au.uid
+      OFFSET $3
+      LIMIT $4
+    "#,
+    filter
+  ))
+  .bind(workspace_id)
+  .bind(&search)
+  .bind(offset)
+  .bind(limit)
+  .fetch_all(pg_pool)
+  .await?;
+  Ok((rows, total))
+}
+
+/// Escapes the `LIKE` wildcards in `value`, so that it only matches itself. The pattern must
+/// be used with `ESCAPE '\'`.
+fn escape_like_pattern(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    if matches!(c, '%' | '_' | '\\') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
 pub async fn select_page_mentions_by_user<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   workspace_id: &Uuid,
@@ -2407,3 +2691,292 @@ pub async fn update_collab_member_invite_permission(
 
   Ok(())
 }
+
+/// Adds the members of `from_workspace_id` to `to_workspace_id`. Users that are members of
+/// both keep the highest of their two roles. Returns the role of every member of
+/// `from_workspace_id` in `to_workspace_id`.
+pub async fn merge_workspace_members(
+  tx: &mut Transaction<'_, Postgres>,
+  from_workspace_id: &Uuid,
+  to_workspace_id: &Uuid,
+) -> Result<Vec<(i64, AFRole)>, AppError> {
+  // Role ids are ordered from the highest role (owner) to the lowest (guest).
+  let rows = sqlx::query_as::<_, (i64, i32)>(
+    r#"
+      INSERT INTO af_workspace_member (workspace_id, uid, role_id)
+      SELECT $2, uid, role_id FROM af_workspace_member WHERE workspace_id = $1
+      ON CONFLICT (workspace_id, uid)
+      DO UPDATE SET role_id = LEAST(af_workspace_member.role_id, excluded.role_id)
+      RETURNING uid, role_id
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .fetch_all(tx.deref_mut())
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|(uid, role_id)| (uid, AFRole::from(role_id)))
+      .collect(),
+  )
+}
+
+/// Moves the per page data of the workspace to another workspace: view metadata, sections,
+/// mentions, watches, slugs, activity, reactions, archived spaces, published pages and the quick
+/// notes of its members. The publish namespaces of the workspace become aliases of the other
+/// workspace, so that links to its published pages keep working.
+///
+/// Slugs and publish names already used in the other workspace are left behind, and are
+/// deleted with the workspace. Returns how many published pages were left behind.
+pub async fn move_workspace_page_data(
+  tx: &mut Transaction<'_, Postgres>,
+  from_workspace_id: &Uuid,
+  to_workspace_id: &Uuid,
+) -> Result<i64, AppError> {
+  for table in [
+    "af_view_metadata",
+    "af_view_section_item",
+    "af_page_mention",
+    "af_page_watch",
+    "af_page_activity",
+    "af_quick_note",
+    "af_archived_space",
+    "af_archived_view",
+    "af_published_view_reaction",
+    "af_published_view_variant",
+    "af_published_row_filter",
+    "af_published_view_stats_daily",
+    "af_published_view_stats_digest",
+  ] {
+    sqlx::query(&format!(
+      "UPDATE {table} SET workspace_id = $2 WHERE workspace_id = $1"
+    ))
+    .bind(from_workspace_id)
+    .bind(to_workspace_id)
+    .execute(tx.deref_mut())
+    .await?;
+  }
+  sqlx::query(
+    r#"
+      UPDATE af_view_slug s SET workspace_id = $2
+      WHERE s.workspace_id = $1
+        AND NOT EXISTS (SELECT 1 FROM af_view_slug d WHERE d.workspace_id = $2 AND d.slug = s.slug)
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .execute(tx.deref_mut())
+  .await?;
+  sqlx::query(
+    r#"
+      UPDATE af_published_collab s SET workspace_id = $2
+      WHERE s.workspace_id = $1
+        AND NOT EXISTS (
+          SELECT 1 FROM af_published_collab d
+          WHERE d.workspace_id = $2 AND d.publish_name = s.publish_name
+        )
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .execute(tx.deref_mut())
+  .await?;
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_namespace
+      SET workspace_id = $2, is_original = FALSE, is_alias = TRUE
+      WHERE workspace_id = $1
+    "#,
+  )
+  .bind(from_workspace_id)
+  .bind(to_workspace_id)
+  .execute(tx.deref_mut())
+  .await?;
+  let left_behind = sqlx::query_scalar::<_, i64>(
+    r#"
+      SELECT COUNT(*) FROM af_published_collab WHERE workspace_id = $1 AND unpublished_at IS NULL
+    "#,
+  )
+  .bind(from_workspace_id)
+  .fetch_one(tx.deref_mut())
+  .await?;
+  Ok(left_behind)
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFWorkspaceMergeRow {
+  pub source_workspace_id: Uuid,
+  pub destination_workspace_id: Uuid,
+  pub space_id: Uuid,
+  pub merged_by: i64,
+  /// The last step of the merge that completed
+  pub step: i16,
+  pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Starts the merge of `source_workspace_id` into `destination_workspace_id`, or takes over the
+/// merge that was started before and did not complete. The merge is held for `lease_secs`.
+/// Returns `None` when another request runs the merge, or when the source is being merged into
+/// another workspace.
+pub async fn claim_workspace_merge<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  source_workspace_id: &Uuid,
+  destination_workspace_id: &Uuid,
+  space_id: &Uuid,
+  merged_by: i64,
+  lease_secs: i64,
+) -> Result<Option<AFWorkspaceMergeRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceMergeRow>(
+    r#"
+      INSERT INTO af_workspace_merge
+        (source_workspace_id, destination_workspace_id, space_id, merged_by, running_until)
+      VALUES ($1, $2, $3, $4, NOW() + make_interval(secs => $5))
+      ON CONFLICT (source_workspace_id) DO UPDATE
+      SET running_until = excluded.running_until,
+          merged_by = excluded.merged_by
+      WHERE af_workspace_merge.destination_workspace_id = excluded.destination_workspace_id
+        AND af_workspace_merge.completed_at IS NULL
+        AND (af_workspace_merge.running_until IS NULL OR af_workspace_merge.running_until < NOW())
+      RETURNING source_workspace_id, destination_workspace_id, space_id, merged_by, step,
+        completed_at
+    "#,
+  )
+  .bind(source_workspace_id)
+  .bind(destination_workspace_id)
+  .bind(space_id)
+  .bind(merged_by)
+  .bind(lease_secs as f64)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Records that the merge completed `step`, and extends the hold on it by `lease_secs`.
+pub async fn update_workspace_merge_step<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  source_workspace_id: &Uuid,
+  step: i16,
+  lease_secs: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_merge
+      SET step = $2, running_until = NOW() + make_interval(secs => $3)
+      WHERE source_workspace_id = $1
+    "#,
+  )
+  .bind(source_workspace_id)
+  .bind(step)
+  .bind(lease_secs as f64)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Releases the merge after a failure, so that it can be resumed right away.
+pub async fn release_workspace_merge<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  source_workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query("UPDATE af_workspace_merge SET running_until = NULL WHERE source_workspace_id = $1")
+    .bind(source_workspace_id)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
+
+pub async fn complete_workspace_merge<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  source_workspace_id: &Uuid,
+  step: i16,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      UPDATE af_workspace_merge
+      SET step = $2, completed_at = NOW(), merged_at = NOW(), running_until = NULL
+      WHERE source_workspace_id = $1
+    "#,
+  )
+  .bind(source_workspace_id)
+  .bind(step)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_workspace_merge<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  source_workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceMergeRow>, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceMergeRow>(
+    r#"
+      SELECT source_workspace_id, destination_workspace_id, space_id, merged_by, step,
+        completed_at
+      FROM af_workspace_merge
+      WHERE source_workspace_id = $1
+    "#,
+  )
+  .bind(source_workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Whether the workspace is being merged into another one.
+pub async fn is_workspace_merging<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<bool, AppError> {
+  let merging = sqlx::query_scalar::<_, bool>(
+    r#"
+      SELECT EXISTS (
+        SELECT 1 FROM af_workspace_merge
+        WHERE source_workspace_id = $1 AND completed_at IS NULL
+      )
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_one(executor)
+  .await?;
+  Ok(merging)
+}
+
+/// The workspace that `source_workspace_id` was merged into, once the merge completed.
+pub async fn select_workspace_merge_destination<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  source_workspace_id: &Uuid,
+) -> Result<Option<Uuid>, AppError> {
+  let destination = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT destination_workspace_id FROM af_workspace_merge
+      WHERE source_workspace_id = $1 AND completed_at IS NOT NULL
+    "#,
+  )
+  .bind(source_workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(destination)
+}
+
+/// Number of members, guests excluded, that the two workspaces would have once merged.
+pub async fn select_merged_workspace_member_count<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  other_workspace_id: &Uuid,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar::<_, i64>(
+    r#"
+      SELECT COUNT(DISTINCT uid)
+      FROM af_workspace_member
+      WHERE workspace_id IN ($1, $2)
+        AND role_id != $3
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(other_workspace_id)
+  .bind(AFRole::Guest as i32)
+  .fetch_one(executor)
+  .await?;
+  Ok(count)
+}
@@ -0,0 +1,134 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::WorkspaceHealthReport;
+use sqlx::types::Json;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::collab::partition_key_from_collab_type;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AFWorkspaceHealthCountsRow {
+  pub total_documents: i64,
+  pub stale_documents: i64,
+  pub unindexed_documents: i64,
+  pub unused_guests: i64,
+}
+
+pub async fn upsert_workspace_health_report<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  report: &WorkspaceHealthReport,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+      INSERT INTO af_workspace_health_report (workspace_id, report, computed_at)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id) DO UPDATE SET
+        report = EXCLUDED.report,
+        computed_at = EXCLUDED.computed_at
+    "#,
+  )
+  .bind(report.workspace_id)
+  .bind(Json(report))
+  .bind(report.computed_at)
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_workspace_health_report<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<WorkspaceHealthReport>, AppError> {
+  let report = sqlx::query_scalar::<_, Json<WorkspaceHealthReport>>(
+    r#"
+      SELECT report FROM af_workspace_health_report WHERE workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .fetch_optional(executor)
+  .await?;
+  Ok(report.map(|report| report.0))
+}
+
+/// Workspaces without a report, or whose report was computed before `computed_before`.
+/// Never computed workspaces come first.
+pub async fn select_workspaces_due_for_health_check<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  computed_before: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar::<_, Uuid>(
+    r#"
+      SELECT w.workspace_id
+      FROM af_workspace w
+      LEFT JOIN af_workspace_health_report r ON r.workspace_id = w.workspace_id
+      WHERE r.computed_at IS NULL OR r.computed_at < $1
+      ORDER BY r.computed_at ASC NULLS FIRST
+      LIMIT $2
+    "#,
+  )
+  .bind(computed_before)
+  .bind(limit)
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
+
+/// Documents of the workspace, and how many of them were last edited before `stale_before`
+/// or are not indexed for search. Guests count as unused when they joined before `stale_before`
+/// and have not been active since: no sign-in, which GoTrue records since the account was
+/// created, no realtime connection and no page activity in the workspace. The device registry
+/// and the page activity are too recent to be relied on alone.
+pub async fn select_workspace_health_counts<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  stale_before: DateTime<Utc>,
+) -> Result<AFWorkspaceHealthCountsRow, AppError> {
+  let row = sqlx::query_as::<_, AFWorkspaceHealthCountsRow>(
+    r#"
+      SELECT
+        COUNT(c.oid) AS total_documents,
+        COUNT(c.oid) FILTER (WHERE c.updated_at < $3) AS stale_documents,
+        COUNT(c.oid) FILTER (
+          WHERE c.indexed_at IS NULL
+            AND NOT COALESCE(w.settings['disable_search_indexing']::boolean, false)
+        ) AS unindexed_documents,
+        (
+          SELECT COUNT(*)
+          FROM af_workspace_member m
+          WHERE m.workspace_id = $1
+            AND m.role_id = $4
+            AND m.created_at < $3
+            AND NOT EXISTS (
+              SELECT 1 FROM af_user u
+              JOIN auth.users au ON au.id = u.uuid
+              WHERE u.uid = m.uid AND au.last_sign_in_at >= $3
+            )
+            AND NOT EXISTS (
+              SELECT 1 FROM af_user_device d
+              WHERE d.uid = m.uid AND d.last_seen_at >= $3
+            )
+            AND NOT EXISTS (
+              SELECT 1 FROM af_page_activity a
+              WHERE a.workspace_id = $1 AND a.actor_uid = m.uid AND a.created_at >= $3
+            )
+        ) AS unused_guests
+      FROM af_workspace w
+      LEFT JOIN af_collab c
+        ON c.workspace_id = w.workspace_id
+        AND c.partition_key = $2
+        AND c.deleted_at IS NULL
+      WHERE w.workspace_id = $1
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(partition_key_from_collab_type(&CollabType::Document))
+  .bind(stale_before)
+  .bind(AFRole::Guest as i32)
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
@@ -0,0 +1,305 @@
+use collab_document::blocks::{Block, DocumentData};
+use serde::{Deserialize, Serialize};
+
+const CODE_BLOCK_TYPE: &str = "code";
+const LANGUAGE_KEY: &str = "language";
+const AUTO_LANGUAGE: &str = "auto";
+
+/// Language metadata of a single code block inside a document.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CodeBlockLanguage {
+  pub block_id: String,
+  pub language: String,
+  /// `true` when the block did not carry an explicit language and the language was
+  /// inferred from the code itself.
+  pub detected: bool,
+}
+
+/// Collects the language of every code block in the document, in document order.
+/// Blocks without an explicit language are run through [detect_code_language]; blocks
+/// whose language cannot be determined are skipped.
+pub fn code_block_languages(data: &DocumentData) -> Vec<CodeBlockLanguage> {
+  let mut result = vec![];
+  let mut stack = vec![data.page_id.as_str()];
+  while let Some(block_id) = stack.pop() {
+    let block = match data.blocks.get(block_id) {
+      Some(block) => block,
+      None => continue,
+    };
+    if block.ty == CODE_BLOCK_TYPE {
+      if let Some(language) = code_block_language(data, block) {
+        result.push(language);
+      }
+    }
+    if let Some(children) = data.meta.children_map.get(&block.children) {
+      stack.extend(children.iter().rev().map(|id| id.as_str()));
+    }
+  }
+  result
+}
+
+fn code_block_language(data: &DocumentData, block: &Block) -> Option<CodeBlockLanguage> {
+  let explicit = block
+    .data
+    .get(LANGUAGE_KEY)
+    .and_then(|value| value.as_str())
+    .map(|language| language.trim())
+    .filter(|language| !language.is_empty() && !language.eq_ignore_ascii_case(AUTO_LANGUAGE));
+  if let Some(language) = explicit {
+    return Some(CodeBlockLanguage {
+      block_id: block.id.clone(),
+      language: language.to_lowercase(),
+      detected: false,
+    });
+  }
+
+  let code = block_plain_text(data, block)?;
+  detect_code_language(&code).map(|language| CodeBlockLanguage {
+    block_id: block.id.clone(),
+    language: language.to_string(),
+    detected: true,
+  })
+}
+
+/// Returns the concatenated text inserts of the block's delta.
+pub fn block_plain_text(data: &DocumentData, block: &Block) -> Option<String> {
+  let external_id = block.external_id.as_ref()?;
+  let delta = data.meta.text_map.as_ref()?.get(external_id)?;
+  let ops = serde_json::from_str::<Vec<serde_json::Value>>(delta).ok()?;
+  let text: String = ops
+    .iter()
+    .filter_map(|op| op.get("insert").and_then(|insert| insert.as_str()))
+    .collect();
+  Some(text)
+}
+
+/// Markers that strongly suggest a language. Every marker found in the code adds one
+/// point to its language and the language with the highest score wins.
+const LANGUAGE_MARKERS: &[(&str, &[&str])] = &[
+  (
+    "rust",
+    &[
+      "fn ",
+      "let mut ",
+      "impl ",
+      "pub fn ",
+      "use std::",
+      "-> Result<",
+      "::new(",
+      "&self",
+    ],
+  ),
+  (
+    "python",
+    &[
+      "def ", "import ", "elif ", "self.", "print(", "__init__", "None", "from ",
+    ],
+  ),
+  (
+    "javascript",
+    &[
+      "function ",
+      "const ",
+      "=> {",
+      "console.log",
+      "require(",
+      "document.",
+      "===",
+      "let ",
+    ],
+  ),
+  (
+    "typescript",
+    &[
+      "interface ",
+      ": string",
+      ": number",
+      "export type ",
+      "implements ",
+      "readonly ",
+    ],
+  ),
+  ("go", &["package main", "func ", ":= ", "fmt.", "go func"]),
+  (
+    "java",
+    &[
+      "public class ",
+      "public static void",
+      "System.out",
+      "private ",
+      "@Override",
+    ],
+  ),
+  ("c", &["#include <", "printf(", "int main(", "malloc("]),
+  (
+    "cpp",
+    &[
+      "std::",
+      "#include <iostream>",
+      "cout <<",
+      "template<",
+      "nullptr",
+    ],
+  ),
+  (
+    "sql",
+    &[
+      "SELECT ",
+      "INSERT INTO",
+      "CREATE TABLE",
+      "WHERE ",
+      "UPDATE ",
+      "JOIN ",
+    ],
+  ),
+  (
+    "bash",
+    &["#!/bin/bash", "#!/bin/sh", "echo ", "fi\n", "$(", "sudo "],
+  ),
+  ("html", &["<!DOCTYPE", "<html", "<div", "</", "<body"]),
+  (
+    "css",
+    &[
+      "{\n  color:",
+      "margin:",
+      "padding:",
+      "display: flex",
+      "@media",
+    ],
+  ),
+];
+
+/// Guesses the language of a code snippet from a small set of well known markers.
+/// Returns `None` if nothing matches, so callers can fall back to plain text rendering.
+pub fn detect_code_language(code: &str) -> Option<&'static str> {
+  let trimmed = code.trim();
+  if trimmed.is_empty() {
+    return None;
+  }
+  if (trimmed.starts_with('{') || trimmed.starts_with('['))
+    && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+  {
+    return Some("json");
+  }
+
+  LANGUAGE_MARKERS
+    .iter()
+    .map(|(language, markers)| {
+      let score = markers
+        .iter()
+        .filter(|marker| trimmed.contains(*marker))
+        .count();
+      (*language, score)
+    })
+    .filter(|(_, score)| *score > 0)
+    // max_by_key returns the last maximum, so reverse to prefer the earlier entry on ties.
+    .rev()
+    .max_by_key(|(_, score)| *score)
+    .map(|(language, _)| language)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use collab_document::blocks::DocumentMeta;
+  use serde_json::json;
+  use std::collections::HashMap;
+
+  fn code_block(id: &str, language: Option<&str>, external_id: &str) -> Block {
+    let mut data = HashMap::new();
+    if let Some(language) = language {
+      data.insert(LANGUAGE_KEY.to_string(), json!(language));
+    }
+    Block {
+      id: id.to_string(),
+      ty: CODE_BLOCK_TYPE.to_string(),
+      parent: "page".to_string(),
+      children: format!("{}_children", id),
+      external_id: Some(external_id.to_string()),
+      external_type: Some("text".to_string()),
+      data,
+    }
+  }
+
+  #[test]
+  fn detect_common_languages() {
+    assert_eq!(
+      detect_code_language("fn main() {\n  let mut x = 1;\n}"),
+      Some("rust")
+    );
+    assert_eq!(
+      detect_code_language("def hello():\n    print('hi')"),
+      Some("python")
+    );
+    assert_eq!(detect_code_language("{\"a\": [1, 2, 3]}"), Some("json"));
+    assert_eq!(
+      detect_code_language("SELECT * FROM af_user WHERE uid = 1"),
+      Some("sql")
+    );
+    assert_eq!(detect_code_language("hello world"), None);
+    assert_eq!(detect_code_language("   "), None);
+  }
+
+  #[test]
+  fn collect_explicit_and_detected_languages() {
+    let page = Block {
+      id: "page".to_string(),
+      ty: "page".to_string(),
+      parent: "".to_string(),
+      children: "page_children".to_string(),
+      external_id: None,
+      external_type: None,
+      data: HashMap::new(),
+    };
+    let blocks = HashMap::from([
+      ("page".to_string(), page),
+      (
+        "a".to_string(),
+        code_block("a", Some("TypeScript"), "text_a"),
+      ),
+      ("b".to_string(), code_block("b", Some("auto"), "text_b")),
+      ("c".to_string(), code_block("c", None, "text_c")),
+    ]);
+    let data = DocumentData {
+      page_id: "page".to_string(),
+      blocks,
+      meta: DocumentMeta {
+        children_map: HashMap::from([(
+          "page_children".to_string(),
+          vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        )]),
+        text_map: Some(HashMap::from([
+          (
+            "text_a".to_string(),
+            json!([{"insert": "let a = 1"}]).to_string(),
+          ),
+          (
+            "text_b".to_string(),
+            json!([{"insert": "package main\n"}, {"insert": "func main() {}"}]).to_string(),
+          ),
+          (
+            "text_c".to_string(),
+            json!([{"insert": "just words"}]).to_string(),
+          ),
+        ])),
+      },
+    };
+
+    let languages = code_block_languages(&data);
+    assert_eq!(
+      languages,
+      vec![
+        CodeBlockLanguage {
+          block_id: "a".to_string(),
+          language: "typescript".to_string(),
+          detected: false,
+        },
+        CodeBlockLanguage {
+          block_id: "b".to_string(),
+          language: "go".to_string(),
+          detected: true,
+        },
+      ]
+    );
+  }
+}
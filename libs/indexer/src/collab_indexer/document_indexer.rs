@@ -1,4 +1,4 @@
-use crate::collab_indexer::Indexer;
+use crate::collab_indexer::{code_block_languages, Indexer};
 use crate::vector::embedder::AFEmbedder;
 use crate::vector::open_ai::group_paragraphs_by_max_content_len;
 use anyhow::anyhow;
@@ -32,8 +32,26 @@ impl Indexer for DocumentIndexer {
       )
     })?;
 
+    let code_languages = {
+      let txn = collab.transact();
+      document
+        .get_document_data(&txn)
+        .map(|data| code_block_languages(&data))
+        .unwrap_or_default()
+    };
     let paragraphs = document.to_plain_text(collab.transact());
-    self.create_embedded_chunks_from_text(object_id, paragraphs, model)
+    let mut chunks = self.create_embedded_chunks_from_text(object_id, paragraphs, model)?;
+    if !code_languages.is_empty() {
+      // Keep the detected languages next to the chunks so that consumers of the
+      // embeddings can render code without guessing the language again.
+      let code_languages = json!(code_languages);
+      for chunk in chunks.iter_mut() {
+        if let Some(metadata) = chunk.metadata.as_object_mut() {
+          metadata.insert("code_languages".to_string(), code_languages.clone());
+        }
+      }
+    }
+    Ok(chunks)
   }
 
   fn create_embedded_chunks_from_text(
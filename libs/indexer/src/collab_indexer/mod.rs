@@ -1,5 +1,7 @@
+mod code_block;
 mod document_indexer;
 mod provider;
 
+pub use code_block::*;
 pub use document_indexer::*;
 pub use provider::*;
@@ -22,7 +22,7 @@ use sqlx::PgPool;
 use std::cmp::max;
 use std::collections::HashSet;
 use std::ops::DerefMut;
-use std::sync::{Arc, Weak};
+use std::sync::{Arc, RwLock, Weak};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TrySendError;
@@ -42,6 +42,8 @@ pub struct IndexerScheduler {
   write_embedding_tx: UnboundedSender<EmbeddingRecord>,
   generate_embedding_tx: mpsc::Sender<UnindexedCollabTask>,
   config: IndexerConfiguration,
+  /// The AI services used for embeddings, replaced when the keys are reloaded
+  ai_configs: RwLock<(Option<OpenAIConfig>, Option<AzureConfig>)>,
   redis_client: ConnectionManager,
 }
 
@@ -77,6 +79,10 @@ impl IndexerScheduler {
     let (generate_embedding_tx, generate_embedding_rx) =
       mpsc::channel::<UnindexedCollabTask>(config.embedding_buffer_size);
 
+    let ai_configs = RwLock::new((
+      config.open_ai_config.clone(),
+      config.azure_ai_config.clone(),
+    ));
     let this = Arc::new(Self {
       indexer_provider,
       pg_pool,
@@ -85,13 +91,15 @@ impl IndexerScheduler {
       write_embedding_tx,
       generate_embedding_tx,
       config,
+      ai_configs,
       redis_client,
     });
 
     info!("Indexer scheduler is enabled: {}", this.index_enabled(),);
 
+    // Started even without AI service, which can be configured later by reloading the keys
     let latest_write_embedding_err = Arc::new(TokioRwLock::new(None));
-    if this.index_enabled() {
+    if this.config.enable {
       tokio::spawn(generate_embeddings_loop(
         generate_embedding_rx,
         Arc::downgrade(&this),
@@ -111,8 +119,27 @@ impl IndexerScheduler {
   }
 
   fn index_enabled(&self) -> bool {
-    self.config.enable
-      && (self.config.open_ai_config.is_some() || self.config.azure_ai_config.is_some())
+    if !self.config.enable {
+      return false;
+    }
+    let ai_configs = self
+      .ai_configs
+      .read()
+      .unwrap_or_else(|err| err.into_inner());
+    ai_configs.0.is_some() || ai_configs.1.is_some()
+  }
+
+  /// Replaces the AI services used for embeddings, once their keys were reloaded.
+  pub fn set_ai_configs(
+    &self,
+    open_ai_config: Option<OpenAIConfig>,
+    azure_ai_config: Option<AzureConfig>,
+  ) {
+    *self
+      .ai_configs
+      .write()
+      .unwrap_or_else(|err| err.into_inner()) = (open_ai_config, azure_ai_config);
+    info!("Indexer scheduler is enabled: {}", self.index_enabled());
   }
 
   pub fn is_indexing_enabled(&self, collab_type: CollabType) -> bool {
@@ -120,13 +147,17 @@ impl IndexerScheduler {
   }
 
   pub(crate) fn create_embedder(&self) -> Result<AFEmbedder, AppError> {
-    if let Some(config) = &self.config.azure_ai_config {
+    let (open_ai_config, azure_ai_config) = &*self
+      .ai_configs
+      .read()
+      .unwrap_or_else(|err| err.into_inner());
+    if let Some(config) = azure_ai_config {
       return Ok(AFEmbedder::AzureOpenAI(open_ai::AzureOpenAIEmbedder::new(
         config.clone(),
       )));
     }
 
-    if let Some(config) = &self.config.open_ai_config {
+    if let Some(config) = open_ai_config {
       return Ok(AFEmbedder::OpenAI(open_ai::OpenAIEmbedder::new(
         config.clone(),
       )));
@@ -71,11 +71,30 @@ impl Mailer {
     param: T,
     subject: &str,
   ) -> Result<(), anyhow::Error>
+  where
+    T: serde::Serialize,
+  {
+    self
+      .send_email_template_with_reply_to(recipient_name, email, template_name, param, subject, None)
+      .await
+  }
+
+  /// Same as [Self::send_email_template], with replies to the email sent to `reply_to`
+  /// instead of the sender.
+  pub async fn send_email_template_with_reply_to<T>(
+    &self,
+    recipient_name: Option<String>,
+    email: &str,
+    template_name: &str,
+    param: T,
+    subject: &str,
+    reply_to: Option<&str>,
+  ) -> Result<(), anyhow::Error>
   where
     T: serde::Serialize,
   {
     let rendered = self.handlers.render(template_name, &param)?;
-    let email = Message::builder()
+    let mut builder = Message::builder()
       .from(lettre::message::Mailbox::new(
         Some("AppFlowy Notification".to_string()),
         self.smtp_email.parse::<Address>()?,
@@ -83,7 +102,11 @@ impl Mailer {
       .to(lettre::message::Mailbox::new(
         recipient_name,
         email.parse()?,
-      ))
+      ));
+    if let Some(reply_to) = reply_to {
+      builder = builder.reply_to(lettre::message::Mailbox::new(None, reply_to.parse()?));
+    }
+    let email = builder
       .subject(subject)
       .header(ContentType::TEXT_HTML)
       .body(rendered)?;
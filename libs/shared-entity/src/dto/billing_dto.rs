@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -146,6 +146,21 @@ pub struct WorkspaceUsageAndLimit {
 
   pub local_ai: bool,
   pub ai_responses_unlimited: bool,
+
+  /// Bytes served from the blob and publish endpoints this month
+  #[serde(default)]
+  pub egress_bytes: i64,
+  #[serde(default)]
+  pub egress_bytes_limit: i64,
+  #[serde(default)]
+  pub daily_egress: Vec<WorkspaceEgressDay>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkspaceEgressDay {
+  pub date: NaiveDate,
+  pub blob_bytes: i64,
+  pub publish_bytes: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
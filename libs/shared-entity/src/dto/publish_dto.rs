@@ -18,6 +18,18 @@ pub struct PublishViewMetaData {
   pub view: PublishViewInfo,
   pub child_views: Vec<PublishViewInfo>,
   pub ancestor_views: Vec<PublishViewInfo>,
+  /// Languages of the code blocks in a published document, so that the publish renderer
+  /// can highlight code without guessing. Always empty for databases.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub code_blocks: Vec<PublishCodeBlockMeta>,
+}
+
+#[derive(Default, Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct PublishCodeBlockMeta {
+  pub block_id: String,
+  pub language: String,
+  /// `true` if the language was inferred by the server rather than chosen by the author
+  pub detected: bool,
 }
 
 #[derive(Default, Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
@@ -108,3 +120,87 @@ impl From<PublishDatabaseDataWithNonUuidRelations> for PublishDatabaseData {
 pub struct DuplicatePublishedPageResponse {
   pub view_id: Uuid,
 }
+
+/// Appearance of published pages. A theme can be set on a publish namespace and on a single
+/// published view, in which case the fields set on the view take precedence.
+#[derive(Default, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct PublishTheme {
+  /// Hex color such as `#3366ff`
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub accent_color: Option<String>,
+  /// Multiplier applied to the base font size of the renderer
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub font_scale: Option<f32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub header_image_url: Option<String>,
+  /// Sanitized by the server, only basic formatting tags and http(s) links are kept
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub footer_html: Option<String>,
+}
+
+impl PublishTheme {
+  /// Fills the fields that are not set on this theme from `fallback`.
+  pub fn or(self, fallback: PublishTheme) -> PublishTheme {
+    PublishTheme {
+      accent_color: self.accent_color.or(fallback.accent_color),
+      font_scale: self.font_scale.or(fallback.font_scale),
+      header_image_url: self.header_image_url.or(fallback.header_image_url),
+      footer_html: self.footer_html.or(fallback.footer_html),
+    }
+  }
+}
+
+/// A published view served under the publish name of another view when its language is
+/// requested, either with `?lang=` or through the `Accept-Language` header.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PublishedViewVariant {
+  /// Lowercase language tag, such as `fr` or `pt-br`
+  pub lang: String,
+  pub view_id: Uuid,
+  pub publish_name: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SetPublishedViewVariant {
+  /// The published view to serve for the language
+  pub view_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct PublishLanguageQuery {
+  pub lang: Option<String>,
+}
+
+/// Which views of a published database are publicly visible.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PublishedDatabaseViews {
+  pub visible_database_view_ids: Vec<Uuid>,
+  /// All the views in the published data of the database, visible or not. Views added to the
+  /// database since it was published are only listed once it is published again.
+  pub database_view_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct UpdatePublishedDatabaseViews {
+  /// Must include the published view itself
+  pub visible_database_view_ids: Vec<Uuid>,
+}
+
+/// Only the rows of a published database matching the filter are copied to the public data.
+/// The filter is kept for the view and applied again each time it is published.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PublishRowFilter {
+  /// Name of the field, as shown in the database
+  pub field: String,
+  pub condition: PublishRowFilterCondition,
+  /// Compared to the text of the cell, ignoring case. For select fields, to the selected options.
+  pub value: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishRowFilterCondition {
+  Is,
+  IsNot,
+  Contains,
+}
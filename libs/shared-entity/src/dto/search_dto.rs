@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use uuid::Uuid;
 
+use crate::dto::workspace_dto::FolderViewMinimal;
+
 /// Parameters used to customize the collab vector search query.
 /// In response, a list of [SearchDocumentResponseItem] is returned.
 #[derive(Clone, Debug, Deserialize)]
@@ -18,6 +20,9 @@ pub struct SearchDocumentRequest {
 
   #[serde(default = "default_search_score_limit")]
   pub score: f64,
+  /// Also search the pages of archived spaces. Default: false.
+  #[serde(default)]
+  pub include_archived: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -89,6 +94,9 @@ pub struct SearchDocumentResponseItem {
   pub created_by: String,
   /// Date when the document was created.
   pub created_at: DateTime<Utc>,
+  /// Name, icon and layout of the page the result belongs to.
+  #[serde(default)]
+  pub view: Option<FolderViewMinimal>,
 }
 
 /// Type of the document content to be presented in the search results.
@@ -1,7 +1,11 @@
+use crate::dto::publish_dto::PublishRowFilter;
+use crate::response::AppResponseError;
 use app_error::AppError;
 use chrono::{DateTime, Utc};
 use collab_entity::{CollabType, EncodedCollab};
-use database_entity::dto::{AFRole, AFWebUser, AFWorkspaceInvitationStatus, PublishInfo};
+use database_entity::dto::{
+  AFRole, AFWebUser, AFWorkspaceInvitationStatus, PageActivityKind, PublishInfo,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -93,6 +97,10 @@ pub struct WorkspaceMemberChangeset {
   pub email: Option<String>, // 邮箱/手机联系方式，可选
   pub role: Option<AFRole>,
   pub name: Option<String>,
+  /// Values of the custom member fields by field id. Fields that are left out keep their value
+  /// and an empty value clears it.
+  #[serde(default)]
+  pub custom_fields: Option<HashMap<Uuid, String>>,
 }
 
 impl WorkspaceMemberChangeset {
@@ -103,6 +111,7 @@ impl WorkspaceMemberChangeset {
       email: None,
       role: None,
       name: None,
+      custom_fields: None,
     }
   }
 
@@ -113,6 +122,7 @@ impl WorkspaceMemberChangeset {
       email: Some(email),
       role: None,
       name: None,
+      custom_fields: None,
     }
   }
 
@@ -130,6 +140,10 @@ impl WorkspaceMemberChangeset {
     self.name = Some(name);
     self
   }
+  pub fn with_custom_fields(mut self, custom_fields: HashMap<Uuid, String>) -> Self {
+    self.custom_fields = Some(custom_fields);
+    self
+  }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -289,6 +303,26 @@ pub struct MovePageParams {
   pub prev_view_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PageReactionCount {
+  pub reaction_type: String,
+  pub count: i64,
+  pub reacted_by_me: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageReactionParams {
+  pub reaction_type: String,
+}
+
+/// The children of `parent_view_id` in their new order. Must list every current child exactly
+/// once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderPageViewsParams {
+  pub parent_view_id: String,
+  pub view_ids: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReorderFavoritePageParams {
   pub prev_view_id: Option<String>,
@@ -324,6 +358,315 @@ pub struct PageCollab {
   pub last_editor: Option<AFWebUser>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageWatchStatus {
+  pub view_id: Uuid,
+  pub watching: bool,
+}
+
+/// One entry of the activity feed of a page. `actor` is empty when the user who caused it was
+/// deleted. Consecutive edits by the same user are grouped into one entry, `details.updates`
+/// being the number of updates and `occurred_at` the time of the latest one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageActivity {
+  pub kind: PageActivityKind,
+  pub actor: Option<AFWebUser>,
+  pub occurred_at: DateTime<Utc>,
+  #[serde(default)]
+  pub details: Value,
+}
+
+/// Activities of a page, most recent first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageActivityFeed {
+  pub activities: Vec<PageActivity>,
+  pub has_more: bool,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPageActivity {
+  /// Only activities that occurred strictly before this time are returned, to load the next
+  /// page of the feed.
+  pub before: Option<DateTime<Utc>>,
+  pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabAccessCheckParams {
+  pub object_ids: Vec<Uuid>,
+}
+
+/// What the current user can do with a collab. All permissions are false when the collab
+/// does not exist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabAccessCheckItem {
+  pub object_id: Uuid,
+  pub exists: bool,
+  pub can_read: bool,
+  pub can_write: bool,
+  pub can_share: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchGetPageViewParams {
+  pub view_ids: Vec<Uuid>,
+}
+
+/// Result of one view of a batch page view request. Exactly one of `page_collab` and `error`
+/// is set, so a view that fails to load does not fail the whole batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageViewBatchItem {
+  pub view_id: Uuid,
+  pub page_collab: Option<PageCollab>,
+  pub error: Option<AppResponseError>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryDocumentChunk {
+  /// Index of the first block to return, in document order
+  pub offset: Option<u32>,
+  /// Maximum number of blocks to return
+  pub limit: Option<u32>,
+}
+
+/// A block of a document flattened in document order, carrying everything a client needs
+/// to render it without the rest of the document. Chunks are read-only: editing requires the
+/// whole document to be synced over the realtime connection.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DocumentChunkBlock {
+  pub id: String,
+  pub ty: String,
+  pub parent_id: String,
+  /// Nesting depth below the page block. Top level blocks have depth 0.
+  pub depth: u32,
+  pub data: HashMap<String, Value>,
+  /// The text delta of the block, if the block has text
+  pub delta: Option<Value>,
+  /// Ids of the direct children, in order. Children may live in a later chunk.
+  pub children: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DocumentTocItem {
+  pub block_id: String,
+  pub level: u32,
+  pub text: String,
+  /// Index of the heading block in document order. Use it as the `offset` of a chunk
+  /// request to jump to the heading.
+  pub block_index: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DocumentOutlineHeading {
+  pub block_id: String,
+  pub level: u32,
+  pub text: String,
+  /// Url fragment for the heading, unique within the document
+  pub anchor: String,
+  /// Index of the heading block in document order, see [DocumentTocItem::block_index]
+  pub block_index: u32,
+  pub children: Vec<DocumentOutlineHeading>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentOutline {
+  pub view_id: Uuid,
+  pub headings: Vec<DocumentOutlineHeading>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChunk {
+  pub view_id: Uuid,
+  pub page_id: String,
+  /// Size of the encoded document in bytes
+  pub doc_size: u64,
+  /// Documents below the size threshold are always returned in a single chunk
+  pub is_large: bool,
+  pub total_blocks: u32,
+  pub offset: u32,
+  pub blocks: Vec<DocumentChunkBlock>,
+  pub has_more: bool,
+  /// Only returned with the first chunk
+  pub toc: Vec<DocumentTocItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobIntegrityReport {
+  pub workspace_id: Uuid,
+  pub scanned_at: DateTime<Utc>,
+  pub scanned_documents: u32,
+  /// Blob links inside documents whose file no longer exists
+  pub missing_blobs: Vec<MissingBlobReference>,
+  /// Blobs that belong to a scanned or deleted document but are not referenced anymore
+  pub orphan_blobs: Vec<OrphanBlob>,
+  pub orphan_bytes: i64,
+  /// Blobs that are still recorded for the workspace but whose file is gone from storage
+  #[serde(default)]
+  pub lost_blobs: Vec<OrphanBlob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingBlobReference {
+  pub object_id: Uuid,
+  pub block_id: String,
+  pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanBlob {
+  pub file_id: String,
+  pub file_type: String,
+  pub file_size: i64,
+  pub modified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishIntegrityReport {
+  pub workspace_id: Uuid,
+  pub scanned_at: DateTime<Utc>,
+  pub scanned_views: u32,
+  /// Published views whose published data can't be read back
+  pub broken_views: Vec<BrokenPublishedView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokenPublishedView {
+  pub view_id: Uuid,
+  pub error: String,
+  /// Whether the published data was regenerated from the current state of the view
+  pub regenerated: bool,
+  /// Why the regeneration failed, if it was attempted
+  pub regenerate_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanPublishIntegrityParams {
+  /// Regenerate the published data of broken views
+  #[serde(default)]
+  pub regenerate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadReferenceReport {
+  pub workspace_id: Uuid,
+  pub scanned_at: DateTime<Utc>,
+  pub scanned_documents: u32,
+  pub dead_references: Vec<DeadReference>,
+  /// Number of dead references that were annotated in their document by this scan
+  pub annotated: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadReferenceKind {
+  /// Mention of a page that has been moved to the trash
+  TrashedPage,
+  /// Mention of a page that no longer exists in the workspace
+  DeletedPage,
+  /// Mention of a person who is no longer a member of the workspace
+  RemovedMember,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadReference {
+  /// The document containing the mention
+  pub object_id: Uuid,
+  pub block_id: String,
+  pub kind: DeadReferenceKind,
+  /// The mentioned page or person
+  pub target_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceHealthReport {
+  pub workspace_id: Uuid,
+  pub computed_at: DateTime<Utc>,
+  /// From 0 to 100, 100 meaning that there is no housekeeping to do
+  pub score: u8,
+  pub total_documents: i64,
+  /// Documents not edited for [WORKSPACE_HEALTH_STALE_AFTER_DAYS] days
+  pub stale_documents: i64,
+  /// Dead page and member mentions found by the latest dead reference scan
+  pub dead_references: i64,
+  /// Documents not indexed for search yet. Always 0 when search indexing is disabled.
+  pub unindexed_documents: i64,
+  /// Guests that have not signed in, connected or edited a page of the workspace for
+  /// [WORKSPACE_HEALTH_STALE_AFTER_DAYS] days
+  pub unused_guests: i64,
+  /// Storage used over the storage limit of the plan, `None` when storage is unlimited
+  pub storage_usage_ratio: Option<f64>,
+  /// Members over the member limit of the plan, `None` when members are unlimited
+  pub member_usage_ratio: Option<f64>,
+  pub recommendations: Vec<WorkspaceHealthRecommendation>,
+}
+
+pub const WORKSPACE_HEALTH_STALE_AFTER_DAYS: i64 = 180;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceHealthIssue {
+  StalePages,
+  DeadReferences,
+  UnindexedDocuments,
+  UnusedGuests,
+  NearStorageLimit,
+  NearMemberLimit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceHealthRecommendation {
+  pub issue: WorkspaceHealthIssue,
+  /// Points the issue removes from the score, recommendations are sorted by it
+  pub penalty: u8,
+  pub message: String,
+}
+
+/// A space whose pages are read-only, and hidden from search and recent pages by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSpace {
+  pub space_id: Uuid,
+  pub archived_by: i64,
+  pub archived_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobTieringSummary {
+  pub workspace_id: Uuid,
+  /// Blobs not read for this many days are moved to infrequent access storage. Depends on
+  /// the plan of the workspace owner.
+  pub infrequent_access_after_days: i32,
+  pub standard_blob_count: i64,
+  pub standard_bytes: i64,
+  pub infrequent_access_blob_count: i64,
+  pub infrequent_access_bytes: i64,
+  /// Estimated storage cost saved per month by the infrequent access blobs, in USD
+  pub estimated_monthly_savings: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeWorkspaceParams {
+  /// Workspace merged into the workspace of the request path. It is deleted once merged.
+  pub source_workspace_id: Uuid,
+  /// Name of the space holding the pages of the source workspace. Defaults to the name of the
+  /// source workspace.
+  #[serde(default)]
+  pub space_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedWorkspace {
+  /// Space of the destination workspace holding the pages of the source workspace
+  pub space_id: Uuid,
+  pub moved_view_count: usize,
+  pub moved_collab_count: usize,
+  pub moved_blob_count: usize,
+  pub merged_member_count: usize,
+  /// Published pages of the source workspace whose publish name is already used in the
+  /// destination. They are unpublished by the merge and have to be published again.
+  #[serde(default)]
+  pub unpublished_view_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublishedDuplicate {
   pub published_view_id: Uuid,
@@ -350,6 +693,16 @@ pub struct TrashFolderView {
   #[serde(flatten)]
   pub view: FolderView,
   pub deleted_at: DateTime<Utc>,
+  /// The user who moved the view to the trash
+  #[serde(default)]
+  pub deleted_by: Option<i64>,
+  /// Ancestors the view is restored under, from its space down to its parent. The path stops
+  /// at the first ancestor that no longer exists.
+  #[serde(default)]
+  pub parent_path: Vec<FolderViewMinimal>,
+  /// Stored size in bytes of the view and the views nested in it
+  #[serde(default)]
+  pub size: i64,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -365,6 +718,17 @@ pub struct FavoriteSectionItems {
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct TrashSectionItems {
   pub views: Vec<TrashFolderView>,
+  /// Number of views in the trash, regardless of offset and limit
+  #[serde(default)]
+  pub total: i64,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryTrashViews {
+  /// Number of views to skip, most recently deleted first
+  pub offset: Option<u32>,
+  /// Maximum number of views to return. Without a limit, every view in the trash is returned.
+  pub limit: Option<u32>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -387,6 +751,9 @@ pub struct FolderView {
   /// contains fields like `is_space`, and font information
   pub extra: Option<serde_json::Value>,
   pub children: Vec<FolderView>,
+  /// Reactions of workspace members on the page
+  #[serde(default)]
+  pub reactions: Vec<PageReactionCount>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -410,6 +777,10 @@ pub struct PublishPageParams {
   pub visible_database_view_ids: Option<Vec<Uuid>>,
   pub comments_enabled: Option<bool>,
   pub duplicate_enabled: Option<bool>,
+  /// Replaces the row filter of the database. When not set, the filter used when the database
+  /// was last published applies.
+  #[serde(default)]
+  pub row_filter: Option<PublishRowFilter>,
 }
 
 #[derive(Eq, PartialEq, Debug, Hash, Clone, Serialize_repr, Deserialize_repr)]
@@ -491,6 +862,13 @@ pub struct ListDatabaseRowDetailParam {
   pub with_doc: Option<bool>,
 }
 
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct GetDatabaseRowDetailParam {
+  // if set to true, document data will be fetched (if exist)
+  // as markdown
+  pub with_doc: Option<bool>,
+}
+
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct ListDatabaseRowUpdatedParam {
   pub after: Option<DateTime<Utc>>,
@@ -524,6 +902,12 @@ pub struct QueryWorkspaceFolder {
   pub root_view_id: Option<Uuid>,
 }
 
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct QueryWorkspaceFolderForest {
+  pub depth: Option<u32>,
+  pub root_view_ids: Vec<Uuid>,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct PublishedView {
   pub view_id: String,
@@ -0,0 +1,9 @@
+-- Latest attachment link integrity report of each workspace
+CREATE TABLE IF NOT EXISTS af_blob_integrity_report (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    report JSONB NOT NULL,
+    scanned_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_blob_integrity_report_scanned_at
+    ON af_blob_integrity_report (scanned_at);
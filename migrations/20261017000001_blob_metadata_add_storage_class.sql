@@ -0,0 +1,11 @@
+-- Blob lifecycle tiering: storage class of the object and the last time it was read.
+-- storage_class: 0 = standard, 1 = infrequent access
+ALTER TABLE af_blob_metadata
+ADD COLUMN IF NOT EXISTS storage_class SMALLINT NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS last_accessed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+ADD COLUMN IF NOT EXISTS storage_class_changed_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE af_blob_metadata SET last_accessed_at = modified_at;
+
+CREATE INDEX IF NOT EXISTS idx_af_blob_metadata_storage_class_last_accessed_at
+    ON af_blob_metadata (storage_class, last_accessed_at);
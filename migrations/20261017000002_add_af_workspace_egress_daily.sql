@@ -0,0 +1,9 @@
+-- Bytes served per workspace and day, split by the endpoint that served them.
+-- source: 0 = blob, 1 = publish
+CREATE TABLE IF NOT EXISTS af_workspace_egress_daily (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    usage_date DATE NOT NULL,
+    source SMALLINT NOT NULL,
+    bytes BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, usage_date, source)
+);
@@ -0,0 +1,6 @@
+-- Availability status of a member, e.g. out of office. The status is hidden once status_until
+-- has passed.
+ALTER TABLE af_workspace_member_profile
+  ADD COLUMN IF NOT EXISTS status_emoji TEXT,
+  ADD COLUMN IF NOT EXISTS status_text TEXT,
+  ADD COLUMN IF NOT EXISTS status_until TIMESTAMPTZ;
@@ -0,0 +1,12 @@
+-- Pages a user follows explicitly to be notified about edits and comments.
+-- last_notified_at: edits up to this time were already included in a notification.
+CREATE TABLE IF NOT EXISTS af_page_watch (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_notified_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (view_id, uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_page_watch_workspace_uid ON af_page_watch (workspace_id, uid);
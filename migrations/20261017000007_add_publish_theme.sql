@@ -0,0 +1,3 @@
+-- Theme of published pages, set per publish namespace and per published view
+ALTER TABLE af_workspace_namespace ADD COLUMN IF NOT EXISTS theme JSONB;
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS theme JSONB;
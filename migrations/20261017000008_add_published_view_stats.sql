@@ -0,0 +1,21 @@
+-- Daily view counters of published views, and the weekly stats digest sent to their publisher
+CREATE TABLE IF NOT EXISTS af_published_view_stats_daily (
+  workspace_id UUID   NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id      UUID   NOT NULL,
+  stat_date    DATE   NOT NULL,
+  views        BIGINT NOT NULL DEFAULT 0,
+
+  PRIMARY KEY (view_id, stat_date)
+);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_on_af_published_view_stats_daily
+  ON af_published_view_stats_daily (workspace_id);
+
+ALTER TABLE af_published_collab
+  ADD COLUMN IF NOT EXISTS stats_digest_enabled BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Kept apart from af_published_collab so that sending a digest does not bump its updated_at
+CREATE TABLE IF NOT EXISTS af_published_view_stats_digest (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id      UUID NOT NULL PRIMARY KEY,
+  sent_at      TIMESTAMP WITH TIME ZONE NOT NULL
+);
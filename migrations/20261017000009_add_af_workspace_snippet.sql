@@ -0,0 +1,14 @@
+-- Shared snippets of a workspace: a shortcut expanding into rich block content
+CREATE TABLE IF NOT EXISTS af_workspace_snippet (
+  snippet_id   UUID        NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  workspace_id UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  shortcut     TEXT        NOT NULL,
+  name         TEXT        NOT NULL,
+  description  TEXT,
+  content      JSONB       NOT NULL,
+  created_by   BIGINT      REFERENCES af_user(uid) ON DELETE SET NULL,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  UNIQUE (workspace_id, shortcut)
+);
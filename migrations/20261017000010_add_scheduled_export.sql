@@ -0,0 +1,38 @@
+-- Recurring Markdown exports of a workspace to storage owned by the workspace
+CREATE TABLE IF NOT EXISTS af_scheduled_export (
+  export_id        UUID        NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  workspace_id     UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  -- Where the bundles are uploaded to, without the credential
+  destination      JSONB       NOT NULL,
+  -- Credential of the destination, encrypted with AES-256-GCM (nonce || ciphertext)
+  encrypted_secret BYTEA       NOT NULL,
+  interval_days    INTEGER     NOT NULL DEFAULT 7 CHECK (interval_days > 0),
+  enabled          BOOLEAN     NOT NULL DEFAULT TRUE,
+  next_run_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  created_by       BIGINT      REFERENCES af_user(uid) ON DELETE SET NULL,
+  created_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_scheduled_export_workspace_id
+  ON af_scheduled_export (workspace_id);
+CREATE INDEX IF NOT EXISTS idx_af_scheduled_export_next_run_at
+  ON af_scheduled_export (next_run_at) WHERE enabled;
+
+-- One row per attempted export, kept as the history of the schedule
+CREATE TABLE IF NOT EXISTS af_scheduled_export_run (
+  run_id       UUID        NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  export_id    UUID        NOT NULL REFERENCES af_scheduled_export(export_id) ON DELETE CASCADE,
+  workspace_id UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  started_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  finished_at  TIMESTAMP WITH TIME ZONE,
+  -- 0: running, 1: succeeded, 2: failed
+  status       SMALLINT    NOT NULL DEFAULT 0,
+  documents    INTEGER     NOT NULL DEFAULT 0,
+  bytes        BIGINT      NOT NULL DEFAULT 0,
+  object_key   TEXT,
+  error        TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_scheduled_export_run_export_id
+  ON af_scheduled_export_run (export_id, started_at DESC);
@@ -0,0 +1,14 @@
+-- Personal API keys, used by clients that cannot sign in such as WebDAV file managers
+CREATE TABLE IF NOT EXISTS af_user_api_key (
+  key_id       UUID        NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  uid          BIGINT      NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  name         TEXT        NOT NULL,
+  -- First characters of the key, to tell keys apart
+  key_prefix   TEXT        NOT NULL,
+  -- SHA-256 of the key, the key itself is only shown once
+  key_hash     BYTEA       NOT NULL UNIQUE,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_user_api_key_uid ON af_user_api_key (uid);
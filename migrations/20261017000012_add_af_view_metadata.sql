@@ -0,0 +1,23 @@
+-- Denormalized view metadata, refreshed from the folder collab of the workspace, so that list
+-- endpoints can hydrate view names and icons without opening the folder
+CREATE TABLE IF NOT EXISTS af_view_metadata (
+  workspace_id     UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id          UUID        NOT NULL,
+  parent_view_id   UUID,
+  name             TEXT        NOT NULL,
+  icon             JSONB,
+  -- ViewLayout
+  layout           SMALLINT    NOT NULL,
+  last_edited_time TIMESTAMP WITH TIME ZONE NOT NULL,
+  updated_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (workspace_id, view_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_view_metadata_parent
+  ON af_view_metadata (workspace_id, parent_view_id);
+
+-- Last time the view metadata of a workspace was rebuilt from its folder
+CREATE TABLE IF NOT EXISTS af_view_metadata_sync (
+  workspace_id UUID NOT NULL PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  synced_at    TIMESTAMP WITH TIME ZONE NOT NULL
+);
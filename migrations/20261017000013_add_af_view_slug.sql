@@ -0,0 +1,13 @@
+-- Human readable slugs of views. Previous slugs of a view are kept with is_current = false so
+-- that links using them keep resolving to the view.
+CREATE TABLE IF NOT EXISTS af_view_slug (
+  workspace_id UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  slug         TEXT        NOT NULL,
+  view_id      UUID        NOT NULL,
+  is_current   BOOLEAN     NOT NULL DEFAULT TRUE,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (workspace_id, slug)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_view_slug_current
+  ON af_view_slug (workspace_id, view_id) WHERE is_current;
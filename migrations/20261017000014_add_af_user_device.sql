@@ -0,0 +1,8 @@
+-- Devices that opened a realtime connection, used to target device handoffs
+CREATE TABLE IF NOT EXISTS af_user_device (
+  uid            BIGINT      NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  device_id      TEXT        NOT NULL,
+  client_version TEXT,
+  last_seen_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, device_id)
+);
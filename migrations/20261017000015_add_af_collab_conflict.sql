@@ -0,0 +1,15 @@
+-- Updates the server refused to apply, kept so that their author can recover the content
+CREATE TABLE IF NOT EXISTS af_collab_conflict (
+  conflict_id  UUID        NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  workspace_id UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  object_id    UUID        NOT NULL,
+  collab_type  INTEGER     NOT NULL,
+  uid          BIGINT      NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  device_id    TEXT,
+  reason       TEXT        NOT NULL,
+  update_v1    BYTEA       NOT NULL,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_conflict_object
+  ON af_collab_conflict (workspace_id, object_id, created_at DESC);
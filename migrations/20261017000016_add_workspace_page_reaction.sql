@@ -0,0 +1,21 @@
+-- Members can react to workspace pages. Page reactions share the storage of reactions on
+-- published comments, told apart by object_scope.
+ALTER TABLE af_published_view_reaction
+  ADD COLUMN IF NOT EXISTS object_scope TEXT NOT NULL DEFAULT 'published_comment',
+  ADD COLUMN IF NOT EXISTS workspace_id UUID REFERENCES af_workspace(workspace_id) ON DELETE CASCADE;
+
+-- Page reactions have no comment, so the primary key on (comment_id, reaction_type, created_by)
+-- is replaced by a unique constraint on the same columns. NULL comment ids never conflict, and
+-- the scope check below keeps comment_id set for comment reactions.
+ALTER TABLE af_published_view_reaction
+  DROP CONSTRAINT IF EXISTS af_published_view_reaction_pkey,
+  ADD CONSTRAINT af_published_view_reaction_comment_key UNIQUE (comment_id, reaction_type, created_by),
+  ALTER COLUMN comment_id DROP NOT NULL;
+ALTER TABLE af_published_view_reaction ADD CONSTRAINT af_published_view_reaction_scope_check CHECK (
+  (object_scope = 'published_comment' AND comment_id IS NOT NULL)
+  OR (object_scope = 'workspace_page' AND comment_id IS NULL AND workspace_id IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_af_published_view_reaction_page
+  ON af_published_view_reaction (workspace_id, view_id, reaction_type, created_by)
+  WHERE object_scope = 'workspace_page';
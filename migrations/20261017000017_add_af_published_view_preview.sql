@@ -0,0 +1,8 @@
+-- Descriptions of published pages written by the AI, used when a publish link is unfurled.
+-- content_hash is the hash of the text the description was generated from.
+CREATE TABLE IF NOT EXISTS af_published_view_preview (
+  view_id      UUID NOT NULL PRIMARY KEY,
+  content_hash TEXT NOT NULL,
+  description  TEXT NOT NULL,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Language variants of a published view. Each variant is a published view of its own, served
+-- under the publish name of the primary view when its language is requested.
+CREATE TABLE IF NOT EXISTS af_published_view_variant (
+  workspace_id    UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id         UUID NOT NULL,
+  lang            TEXT NOT NULL,
+  variant_view_id UUID NOT NULL,
+  created_by      BIGINT NOT NULL,
+  created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (view_id, lang),
+  UNIQUE (variant_view_id),
+  CHECK (view_id <> variant_view_id)
+);
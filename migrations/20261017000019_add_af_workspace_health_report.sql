@@ -0,0 +1,9 @@
+-- Latest maintenance score of each workspace, with the recommendations shown to its owner
+CREATE TABLE IF NOT EXISTS af_workspace_health_report (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    report JSONB NOT NULL,
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_health_report_computed_at
+    ON af_workspace_health_report (computed_at);
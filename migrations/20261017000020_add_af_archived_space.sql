@@ -0,0 +1,20 @@
+-- Spaces archived by a workspace owner. Their pages are locked, hidden from search and recent
+-- lists, and the realtime server rejects edits on them until the space is unarchived.
+CREATE TABLE IF NOT EXISTS af_archived_space (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  space_id     UUID NOT NULL,
+  archived_by  BIGINT NOT NULL,
+  archived_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (space_id)
+);
+
+-- Collabs of an archived space at the time it was archived. `was_locked` keeps the lock state
+-- of the page so that unarchiving restores it.
+CREATE TABLE IF NOT EXISTS af_archived_view (
+  workspace_id UUID NOT NULL,
+  space_id     UUID NOT NULL REFERENCES af_archived_space(space_id) ON DELETE CASCADE,
+  object_id    UUID NOT NULL,
+  was_locked   BOOLEAN NOT NULL DEFAULT FALSE,
+  PRIMARY KEY (object_id)
+);
+CREATE INDEX IF NOT EXISTS idx_af_archived_view_workspace ON af_archived_view (workspace_id);
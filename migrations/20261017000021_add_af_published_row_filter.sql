@@ -0,0 +1,10 @@
+-- Filter applied to the rows of a published database each time it is published, so that only
+-- the matching rows are copied to the public data.
+CREATE TABLE IF NOT EXISTS af_published_row_filter (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id      UUID NOT NULL,
+  filter       JSONB NOT NULL,
+  updated_by   BIGINT NOT NULL,
+  updated_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (view_id)
+);
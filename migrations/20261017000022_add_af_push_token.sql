@@ -0,0 +1,13 @@
+-- Push notification tokens of the mobile devices of users. A token belongs to a single device.
+CREATE TABLE IF NOT EXISTS af_push_token (
+  uid        BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  device_id  TEXT   NOT NULL,
+  platform   TEXT   NOT NULL,
+  token      TEXT   NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, device_id)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_push_token_token ON af_push_token (platform, token);
+
+-- Set when a notification is claimed for push delivery, so that only one server pushes it
+ALTER TABLE af_notification ADD COLUMN IF NOT EXISTS pushed_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,25 @@
+-- Quiet hours of the devices of users, in minutes after midnight in the timezone of the device
+CREATE TABLE IF NOT EXISTS af_push_quiet_hours (
+  uid            BIGINT  NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  device_id      TEXT    NOT NULL,
+  start_minute   INTEGER NOT NULL,
+  end_minute     INTEGER NOT NULL,
+  timezone       TEXT    NOT NULL,
+  allow_mentions BOOLEAN NOT NULL DEFAULT FALSE,
+  updated_at     TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, device_id)
+);
+
+-- Push notifications held back during quiet hours, sent as a summary once they end
+CREATE TABLE IF NOT EXISTS af_push_deferred (
+  id                BIGSERIAL PRIMARY KEY,
+  uid               BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  device_id         TEXT   NOT NULL,
+  notification_id   UUID   NOT NULL,
+  notification_type TEXT   NOT NULL,
+  workspace_id      UUID,
+  title             TEXT   NOT NULL,
+  body              TEXT   NOT NULL,
+  created_at        TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_af_push_deferred_device ON af_push_deferred (uid, device_id);
@@ -0,0 +1,17 @@
+-- Recomputation of the collab-level permissions of a member after a change of their workspace role
+CREATE TABLE IF NOT EXISTS af_collab_permission_sync (
+  sync_id        UUID     NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  workspace_id   UUID     NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  uid            BIGINT   NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  role_id        INTEGER  NOT NULL,
+  -- 0: running, 1: succeeded, 2: failed, 3: superseded by a later role change
+  status         SMALLINT NOT NULL DEFAULT 0,
+  total_collabs  INTEGER  NOT NULL DEFAULT 0,
+  synced_collabs INTEGER  NOT NULL DEFAULT 0,
+  error          TEXT,
+  started_at     TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  finished_at    TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_permission_sync_member
+  ON af_collab_permission_sync (workspace_id, uid, started_at DESC);
@@ -0,0 +1,4 @@
+-- When the recipient read the notification, on any of their devices
+ALTER TABLE af_notification ADD COLUMN IF NOT EXISTS read_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_af_notification_recipient_created_at
+  ON af_notification (recipient_uid, created_at DESC);
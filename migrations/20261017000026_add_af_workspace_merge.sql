@@ -0,0 +1,11 @@
+-- Workspaces that were merged into another one. The source workspace is deleted after the
+-- merge, so its id is not a foreign key: the row is kept to redirect the links to its files.
+CREATE TABLE IF NOT EXISTS af_workspace_merge (
+  source_workspace_id      UUID   PRIMARY KEY,
+  destination_workspace_id UUID   NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  space_id                 UUID   NOT NULL,
+  merged_by                BIGINT NOT NULL,
+  merged_at                TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_af_workspace_merge_destination
+  ON af_workspace_merge (destination_workspace_id);
@@ -0,0 +1,14 @@
+-- Custom member attributes defined by the owners of a workspace, e.g. an employee ID. The values
+-- of a member are kept in af_workspace_member_profile.custom_fields, keyed by field id.
+CREATE TABLE IF NOT EXISTS af_workspace_member_field (
+  field_id           UUID    NOT NULL DEFAULT gen_random_uuid() PRIMARY KEY,
+  workspace_id       UUID    NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  name               TEXT    NOT NULL,
+  editable_by_member BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at         TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  UNIQUE (workspace_id, name)
+);
+
+ALTER TABLE af_workspace_member_profile
+  ADD COLUMN IF NOT EXISTS custom_fields JSONB NOT NULL DEFAULT '{}'::jsonb;
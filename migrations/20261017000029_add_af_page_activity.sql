@@ -0,0 +1,15 @@
+-- Events of a page shown in its activity feed: edits, moves, publishes and permission changes.
+-- Comments are read from the comments of the page instead.
+-- actor_uid: user who caused the event, kept empty once the user is deleted.
+CREATE TABLE IF NOT EXISTS af_page_activity (
+    activity_id BIGSERIAL PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    actor_uid BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    kind TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_page_activity_view_created_at
+    ON af_page_activity (workspace_id, view_id, created_at DESC);
@@ -0,0 +1,10 @@
+-- Encrypted backup of the local settings and keys of a user, opaque to the server.
+-- version: incremented on every write, so that clients don't overwrite a newer backup.
+-- device_id: device that wrote the backup last.
+CREATE TABLE IF NOT EXISTS af_user_backup (
+    uid BIGINT PRIMARY KEY REFERENCES af_user(uid) ON DELETE CASCADE,
+    version BIGINT NOT NULL DEFAULT 1,
+    data BYTEA NOT NULL,
+    device_id TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
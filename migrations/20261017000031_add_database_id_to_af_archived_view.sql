@@ -0,0 +1,10 @@
+-- Rows of the databases of an archived space are archived along with the database. They keep
+-- the id of their database so that they follow it when it leaves or enters an archived space.
+ALTER TABLE af_archived_view ADD COLUMN IF NOT EXISTS database_id UUID;
+CREATE INDEX IF NOT EXISTS idx_af_archived_view_database
+  ON af_archived_view (database_id) WHERE database_id IS NOT NULL;
+
+-- Last time the archived collabs of the space were compared with the folder, to follow the
+-- pages moved into or out of the space after it was archived
+ALTER TABLE af_archived_space
+  ADD COLUMN IF NOT EXISTS objects_synced_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
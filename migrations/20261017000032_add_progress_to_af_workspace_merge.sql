@@ -0,0 +1,9 @@
+-- Merges are recorded when they start, and their progress is kept so that a merge that failed
+-- halfway can be resumed. `step` is the last step that completed.
+ALTER TABLE af_workspace_merge ADD COLUMN IF NOT EXISTS step SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE af_workspace_merge ADD COLUMN IF NOT EXISTS completed_at TIMESTAMP WITH TIME ZONE;
+-- Set while a request runs the merge, so that the same merge is not run twice at once
+ALTER TABLE af_workspace_merge ADD COLUMN IF NOT EXISTS running_until TIMESTAMP WITH TIME ZONE;
+
+-- Merges recorded so far were recorded once complete
+UPDATE af_workspace_merge SET completed_at = merged_at, step = 5 WHERE completed_at IS NULL;
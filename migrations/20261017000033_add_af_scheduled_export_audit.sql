@@ -0,0 +1,19 @@
+-- Who pointed a scheduled export at which destination, and every upload made to it. Rows
+-- outlive the export they describe.
+CREATE TABLE IF NOT EXISTS af_scheduled_export_audit (
+  audit_id     BIGSERIAL   PRIMARY KEY,
+  workspace_id UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  export_id    UUID        NOT NULL,
+  -- 0: created, 1: destination changed, 2: deleted, 3: uploaded
+  action       SMALLINT    NOT NULL,
+  -- The member who made the change, or the creator of the export for uploads
+  uid          BIGINT,
+  -- Destination at the time of the action, without the credential
+  destination  JSONB       NOT NULL,
+  -- Address the upload connected to
+  address      TEXT,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_scheduled_export_audit_workspace_id
+  ON af_scheduled_export_audit (workspace_id, created_at DESC);
@@ -0,0 +1,6 @@
+-- Last progress of a sync, to find the running syncs whose server stopped before finishing them
+ALTER TABLE af_collab_permission_sync
+  ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_permission_sync_running
+  ON af_collab_permission_sync (updated_at) WHERE status = 0;
@@ -0,0 +1,27 @@
+-- af_collab_conflict is easily mistaken for af_collab_conflicts, which holds collabs
+ALTER TABLE af_collab_conflict RENAME TO af_collab_rejected_update;
+ALTER INDEX af_collab_conflict_pkey RENAME TO af_collab_rejected_update_pkey;
+ALTER INDEX idx_af_collab_conflict_object RENAME TO idx_af_collab_rejected_update_object;
+ALTER TABLE af_collab_rejected_update
+  RENAME CONSTRAINT af_collab_conflict_workspace_id_fkey TO af_collab_rejected_update_workspace_id_fkey;
+ALTER TABLE af_collab_rejected_update
+  RENAME CONSTRAINT af_collab_conflict_uid_fkey TO af_collab_rejected_update_uid_fkey;
+
+-- Clients resend the updates that were not acknowledged, so the same update is kept only once
+ALTER TABLE af_collab_rejected_update ADD COLUMN IF NOT EXISTS update_hash BYTEA;
+UPDATE af_collab_rejected_update SET update_hash = sha256(update_v1) WHERE update_hash IS NULL;
+DELETE FROM af_collab_rejected_update a
+  USING af_collab_rejected_update b
+  WHERE a.workspace_id = b.workspace_id
+    AND a.object_id = b.object_id
+    AND a.uid = b.uid
+    AND a.update_hash = b.update_hash
+    AND (a.created_at, a.conflict_id) > (b.created_at, b.conflict_id);
+ALTER TABLE af_collab_rejected_update ALTER COLUMN update_hash SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_collab_rejected_update_hash
+  ON af_collab_rejected_update (workspace_id, object_id, uid, update_hash);
+
+-- Rejected updates are removed once they expire
+CREATE INDEX IF NOT EXISTS idx_af_collab_rejected_update_created_at
+  ON af_collab_rejected_update (created_at);
@@ -0,0 +1,21 @@
+-- Read state of notifications, per reader. A notification without recipient goes to every user
+-- it concerns, and each of them reads it separately.
+CREATE TABLE IF NOT EXISTS af_notification_read (
+  notification_id UUID NOT NULL REFERENCES af_notification(id) ON DELETE CASCADE,
+  uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  read_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (notification_id, uid)
+);
+
+INSERT INTO af_notification_read (notification_id, uid, read_at)
+SELECT n.id, n.recipient_uid, n.read_at
+FROM af_notification n
+JOIN af_user u ON u.uid = n.recipient_uid
+WHERE n.read_at IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE af_notification DROP COLUMN IF EXISTS read_at;
+
+CREATE INDEX IF NOT EXISTS idx_af_notification_broadcast_created_at
+  ON af_notification (created_at DESC)
+  WHERE recipient_uid IS NULL;
@@ -0,0 +1,26 @@
+-- Everything the recent and favorite lists show of a view, so that they are served from the
+-- view metadata instead of the folder
+ALTER TABLE af_view_metadata
+  ADD COLUMN IF NOT EXISTS prev_view_id   UUID,
+  ADD COLUMN IF NOT EXISTS created_at     TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT to_timestamp(0),
+  ADD COLUMN IF NOT EXISTS created_by     BIGINT,
+  ADD COLUMN IF NOT EXISTS last_edited_by BIGINT,
+  ADD COLUMN IF NOT EXISTS is_locked      BOOLEAN,
+  ADD COLUMN IF NOT EXISTS extra          JSONB;
+
+-- Favorite and recent sections of the members, read from the folder together with the view
+-- metadata. Views in the trash of the member are left out.
+CREATE TABLE IF NOT EXISTS af_view_section_item (
+  workspace_id   UUID        NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  uid            BIGINT      NOT NULL,
+  -- AFViewSection
+  section        SMALLINT    NOT NULL,
+  view_id        UUID        NOT NULL,
+  -- Order of the item in the section
+  position       INT         NOT NULL,
+  item_timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (workspace_id, uid, section, view_id)
+);
+
+-- Rebuild every workspace, so that the new columns and the sections are filled
+DELETE FROM af_view_metadata_sync;
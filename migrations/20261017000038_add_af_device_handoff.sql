@@ -0,0 +1,27 @@
+-- Content handed off between the devices of a user. The handoff notification only carries the
+-- id, so that the content is not kept with the notifications, and the content is deleted once
+-- it expires.
+CREATE TABLE IF NOT EXISTS af_device_handoff (
+  handoff_id       UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+  uid              BIGINT      NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  source_device_id TEXT,
+  -- NULL when the content is handed off to every other device of the user
+  target_device_id TEXT,
+  kind             SMALLINT    NOT NULL,
+  content          TEXT        NOT NULL,
+  title            TEXT,
+  created_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  expires_at       TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_device_handoff_uid ON af_device_handoff (uid, created_at);
+CREATE INDEX IF NOT EXISTS idx_af_device_handoff_expires_at ON af_device_handoff (expires_at);
+
+-- Devices that processed a handoff. A handoff sent to every other device stays pending on each
+-- device until that device processes it.
+CREATE TABLE IF NOT EXISTS af_device_handoff_receipt (
+  handoff_id   UUID        NOT NULL REFERENCES af_device_handoff(handoff_id) ON DELETE CASCADE,
+  device_id    TEXT        NOT NULL,
+  processed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (handoff_id, device_id)
+);
@@ -0,0 +1,3 @@
+-- Namespaces of workspaces merged into the workspace. They keep resolving to the pages
+-- published under them, but are never shown or renamed as the namespace of the workspace.
+ALTER TABLE af_workspace_namespace ADD COLUMN IF NOT EXISTS is_alias BOOLEAN NOT NULL DEFAULT FALSE;
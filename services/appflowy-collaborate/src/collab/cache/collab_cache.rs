@@ -50,6 +50,7 @@ impl CollabCache {
     thread_pool: Arc<ThreadPoolNoAbort>,
     redis_conn_manager: redis::aio::ConnectionManager,
     pg_pool: PgPool,
+    persistence_pg_pool: PgPool,
     s3: AwsS3BucketClientImpl,
     metrics: Arc<CollabMetrics>,
     s3_collab_threshold: usize,
@@ -62,6 +63,7 @@ impl CollabCache {
     let disk_cache = CollabDiskCache::new(
       thread_pool.clone(),
       pg_pool.clone(),
+      persistence_pg_pool,
       s3,
       s3_collab_threshold,
       metrics.clone(),
@@ -305,9 +307,22 @@ impl CollabCache {
       };
     }
 
-    let updates = self
+    // Pending updates live in redis. Without redis the snapshot is served as it is, which
+    // can lag behind the latest edits until they are persisted.
+    let updates = match self
       .get_workspace_updates(workspace_id, Some(&object_id), Some(rid), None)
-      .await?;
+      .await
+    {
+      Ok(updates) => updates,
+      Err(err) if encoded_collab.is_some() => {
+        warn!(
+          "failed to get pending updates of collab {}, serving its snapshot: {}",
+          object_id, err
+        );
+        vec![]
+      },
+      Err(err) => return Err(err),
+    };
 
     let size = encoded_collab
       .as_ref()
@@ -32,6 +32,9 @@ use uuid::Uuid;
 pub struct CollabDiskCache {
   thread_pool: Arc<ThreadPoolNoAbort>,
   pg_pool: PgPool,
+  /// Runs the transactions writing collabs, so that long writes cannot take every connection
+  /// of `pg_pool`. It can be the same pool.
+  persistence_pg_pool: PgPool,
   s3: AwsS3BucketClientImpl,
   s3_collab_threshold: usize,
   metrics: Arc<CollabMetrics>,
@@ -41,6 +44,7 @@ impl CollabDiskCache {
   pub fn new(
     thread_pool: Arc<ThreadPoolNoAbort>,
     pg_pool: PgPool,
+    persistence_pg_pool: PgPool,
     s3: AwsS3BucketClientImpl,
     s3_collab_threshold: usize,
     metrics: Arc<CollabMetrics>,
@@ -48,6 +52,7 @@ impl CollabDiskCache {
     Self {
       thread_pool,
       pg_pool,
+      persistence_pg_pool,
       s3,
       s3_collab_threshold,
       metrics,
@@ -73,7 +78,7 @@ impl CollabDiskCache {
   ) -> AppResult<()> {
     // Start a database transaction
     let mut transaction = self
-      .pg_pool
+      .persistence_pg_pool
       .begin()
       .await
       .context("Failed to acquire transaction for writing pending collaboration data")
@@ -352,7 +357,7 @@ impl CollabDiskCache {
     let s3_count = blobs.len() as u64;
     let pg_count = delete_from_s3.len() as u64;
 
-    let mut transaction = self.pg_pool.begin().await?;
+    let mut transaction = self.persistence_pg_pool.begin().await?;
     let start = Instant::now();
     insert_into_af_collab_bulk_for_user(&mut transaction, uid, workspace_id, &params_list).await?;
     transaction.commit().await?;
@@ -383,7 +388,7 @@ impl CollabDiskCache {
     let s3 = self.s3.clone();
     // Start a database transaction
     let mut transaction = self
-      .pg_pool
+      .persistence_pg_pool
       .begin()
       .await
       .context("Failed to acquire transaction for writing pending collaboration data")
@@ -1,5 +1,9 @@
 use crate::collab::cache::mem_cache::MillisSeconds;
 use crate::collab::cache::CollabCache;
+use crate::collab::page_edit::PageEditRecorder;
+use crate::collab::rejected_update::RejectedUpdateRecorder;
+use crate::collab::structure_limit::CollabStructureGuard;
+use crate::collab::write_guard::{CollabWriteGuard, WriteBlock};
 use access_control::act::Action;
 use access_control::collab::CollabAccessControl;
 use access_control::workspace::WorkspaceAccessControl;
@@ -50,6 +54,10 @@ pub struct CollabManager {
   connection_manager: ConnectionManager,
   indexer_scheduler: Arc<IndexerScheduler>,
   snapshot_thread_pool: Arc<ThreadPoolNoAbort>,
+  structure_guard: Arc<CollabStructureGuard>,
+  write_guard: Arc<CollabWriteGuard>,
+  rejected_updates: Arc<RejectedUpdateRecorder>,
+  page_edits: Arc<PageEditRecorder>,
 }
 
 impl CollabManager {
@@ -63,6 +71,10 @@ impl CollabManager {
     update_streams: Arc<StreamRouter>,
     awareness_broadcast: Arc<AwarenessGossip>,
     indexer_scheduler: Arc<IndexerScheduler>,
+    structure_guard: Arc<CollabStructureGuard>,
+    write_guard: Arc<CollabWriteGuard>,
+    rejected_updates: Arc<RejectedUpdateRecorder>,
+    page_edits: Arc<PageEditRecorder>,
   ) -> Arc<Self> {
     Arc::new(Self {
       access_control,
@@ -73,6 +85,10 @@ impl CollabManager {
       connection_manager,
       indexer_scheduler,
       snapshot_thread_pool: thread_pool,
+      structure_guard,
+      write_guard,
+      rejected_updates,
+      page_edits,
     })
   }
 
@@ -155,6 +171,15 @@ impl CollabManager {
       .await
   }
 
+  /// Why writes to the collab are refused for everyone, regardless of their permissions.
+  pub async fn write_block(
+    &self,
+    workspace_id: &WorkspaceId,
+    object_id: &ObjectId,
+  ) -> Option<WriteBlock> {
+    self.write_guard.write_block(workspace_id, object_id).await
+  }
+
   pub async fn enforce_write_collab(
     &self,
     workspace_id: &WorkspaceId,
@@ -247,6 +272,22 @@ impl CollabManager {
       .await
   }
 
+  /// Checks that applying `update` keeps the collab within the structure limits, see
+  /// [CollabStructureGuard].
+  pub async fn enforce_structure_limits(
+    &self,
+    workspace_id: WorkspaceId,
+    object_id: ObjectId,
+    collab_type: CollabType,
+    uid: i64,
+    update: &[u8],
+  ) -> AppResult<()> {
+    self
+      .structure_guard
+      .enforce(workspace_id, object_id, collab_type, uid, update)
+      .await
+  }
+
   #[instrument(level = "trace", skip_all, err)]
   pub async fn publish_update(
     &self,
@@ -254,14 +295,14 @@ impl CollabManager {
     object_id: ObjectId,
     collab_type: CollabType,
     sender: &CollabOrigin,
-    update: Vec<u8>,
+    update: &[u8],
   ) -> anyhow::Result<Rid> {
     trace!(
       "publish_update({:?}, {}/{}, update: {:#?})",
       workspace_id,
       object_id,
       collab_type,
-      yrs::Update::decode_v1(&update)
+      yrs::Update::decode_v1(update)
     );
 
     let key = UpdateStreamMessage::stream_key(&workspace_id);
@@ -280,6 +321,9 @@ impl CollabManager {
     self
       .collab_cache
       .mark_as_dirty(object_id, MillisSeconds::from(rid.timestamp));
+    self
+      .page_edits
+      .record(workspace_id, object_id, collab_type, sender);
     trace!(
       "published update to '{}' (object id: {}), rid:{}",
       key,
@@ -289,6 +333,30 @@ impl CollabManager {
     Ok(rid)
   }
 
+  /// Keeps an update of `uid` that was not applied to the collab, so that the content can be
+  /// recovered. The update is written in the background, see [RejectedUpdateRecorder].
+  #[allow(clippy::too_many_arguments)]
+  pub fn record_rejected_update(
+    &self,
+    workspace_id: WorkspaceId,
+    object_id: ObjectId,
+    collab_type: CollabType,
+    uid: i64,
+    sender: &CollabOrigin,
+    reason: &str,
+    update: &[u8],
+  ) {
+    self.rejected_updates.record(
+      workspace_id,
+      object_id,
+      collab_type,
+      uid,
+      sender,
+      reason,
+      update,
+    );
+  }
+
   pub fn mark_as_dirty(&self, object_id: ObjectId, millis_secs: u64) {
     self
       .collab_cache
@@ -812,7 +880,7 @@ fn count_live_whiteboard_elements<T: yrs::ReadTxn>(root: &MapRef, txn: &T) -> Op
 
 /// 【数据丢失安全网】仅从前序快照字节重建一个只读用途的 collab（不应用任何增量 update）。
 /// 用于破坏性清空被拦截时回滚到清空前的状态。
-fn build_collab_from_snapshot(
+pub(crate) fn build_collab_from_snapshot(
   client_id: ClientID,
   object_id: ObjectId,
   update_snapshot: &Bytes,
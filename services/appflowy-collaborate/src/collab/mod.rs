@@ -1,4 +1,8 @@
 pub mod cache;
 pub mod collab_manager;
 pub mod collab_store;
+pub mod page_edit;
+pub mod rejected_update;
 pub mod snapshot_scheduler;
+pub mod structure_limit;
+pub mod write_guard;
@@ -0,0 +1,125 @@
+use appflowy_proto::{ObjectId, WorkspaceId};
+use chrono::{DateTime, Utc};
+use collab::core::origin::CollabOrigin;
+use collab_entity::CollabType;
+use database::page_activity::upsert_page_edit_activity;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Consecutive edits of a page by the same user less than this apart are shown as one activity.
+const PAGE_EDIT_GROUP_GAP_SECS: i64 = 10 * 60;
+/// Edits are counted in memory and written to the activity feed this often.
+const PAGE_EDIT_FLUSH_INTERVAL_SECS: u64 = 30;
+/// Edits waiting to be counted. Edits made while the queue is full are left out of the feed.
+const PAGE_EDIT_QUEUE_SIZE: usize = 10_000;
+
+struct PageEdit {
+  workspace_id: WorkspaceId,
+  object_id: ObjectId,
+  uid: i64,
+  edited_at: DateTime<Utc>,
+}
+
+struct PendingEdits {
+  updates: i64,
+  started_at: DateTime<Utc>,
+  ended_at: DateTime<Utc>,
+}
+
+/// Records the edits of documents in the activity feed of their page. Edits are counted per
+/// user and document, and written by a background task, so that applying an update never waits
+/// for the database.
+pub struct PageEditRecorder {
+  queue: mpsc::Sender<PageEdit>,
+}
+
+impl PageEditRecorder {
+  pub fn new(pg_pool: PgPool) -> Arc<Self> {
+    let (queue, rx) = mpsc::channel(PAGE_EDIT_QUEUE_SIZE);
+    tokio::spawn(write_page_edits(pg_pool, rx));
+    Arc::new(Self { queue })
+  }
+
+  pub fn record(
+    &self,
+    workspace_id: WorkspaceId,
+    object_id: ObjectId,
+    collab_type: CollabType,
+    sender: &CollabOrigin,
+  ) {
+    // The object id of a document is the id of its page
+    if collab_type != CollabType::Document {
+      return;
+    }
+    let uid = match sender {
+      CollabOrigin::Client(client) => client.uid,
+      _ => return,
+    };
+    let edit = PageEdit {
+      workspace_id,
+      object_id,
+      uid,
+      edited_at: Utc::now(),
+    };
+    if let Err(err) = self.queue.try_send(edit) {
+      warn!("dropped edit of {} by {}: {}", object_id, uid, err);
+    }
+  }
+}
+
+async fn write_page_edits(pg_pool: PgPool, mut rx: mpsc::Receiver<PageEdit>) {
+  let mut pending: HashMap<(WorkspaceId, ObjectId, i64), PendingEdits> = HashMap::new();
+  let mut timer = tokio::time::interval(Duration::from_secs(PAGE_EDIT_FLUSH_INTERVAL_SECS));
+  loop {
+    tokio::select! {
+      edit = rx.recv() => {
+        let edit = match edit {
+          Some(edit) => edit,
+          None => break,
+        };
+        pending
+          .entry((edit.workspace_id, edit.object_id, edit.uid))
+          .and_modify(|edits| {
+            edits.updates += 1;
+            edits.ended_at = edit.edited_at;
+          })
+          .or_insert(PendingEdits {
+            updates: 1,
+            started_at: edit.edited_at,
+            ended_at: edit.edited_at,
+          });
+      },
+      _ = timer.tick() => flush_page_edits(&pg_pool, &mut pending).await,
+    }
+  }
+  flush_page_edits(&pg_pool, &mut pending).await;
+}
+
+async fn flush_page_edits(
+  pg_pool: &PgPool,
+  pending: &mut HashMap<(WorkspaceId, ObjectId, i64), PendingEdits>,
+) {
+  for ((workspace_id, object_id, uid), edits) in pending.drain() {
+    if let Err(err) = upsert_page_edit_activity(
+      pg_pool,
+      &workspace_id,
+      &object_id,
+      uid,
+      edits.updates,
+      edits.started_at,
+      edits.ended_at,
+      PAGE_EDIT_GROUP_GAP_SECS,
+    )
+    .await
+    {
+      warn!(
+        "failed to record edits of {} by {}: {}",
+        object_id, uid, err
+      );
+    }
+  }
+}
@@ -0,0 +1,126 @@
+use appflowy_proto::{ObjectId, WorkspaceId};
+use collab::core::origin::CollabOrigin;
+use collab_entity::CollabType;
+use database::collab_rejected_update::{
+  delete_excess_collab_rejected_updates, insert_collab_rejected_update,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{trace, warn};
+
+/// Rejected updates below this size are not kept: they can't hold significant content.
+pub const MIN_CONFLICT_UPDATE_LEN: usize = 64;
+/// Most recent rejected updates kept per user and collab.
+const MAX_REJECTED_UPDATES_PER_COLLAB: i64 = 50;
+/// Rejected updates waiting to be written. Updates rejected while the queue is full are dropped.
+const REJECTED_UPDATE_QUEUE_SIZE: usize = 1_000;
+
+pub const CONFLICT_REASON_PERMISSION_DENIED: &str = "permission_denied";
+
+struct RejectedUpdate {
+  workspace_id: WorkspaceId,
+  object_id: ObjectId,
+  collab_type: CollabType,
+  uid: i64,
+  device_id: Option<String>,
+  reason: String,
+  update: Vec<u8>,
+}
+
+/// Keeps the updates the server did not apply, so that their author can recover the content
+/// from `GET /api/workspace/{workspace_id}/collab/{object_id}/conflicts`. The updates are
+/// written by a background task, so that rejecting an update never waits for the database.
+pub struct RejectedUpdateRecorder {
+  queue: mpsc::Sender<RejectedUpdate>,
+}
+
+impl RejectedUpdateRecorder {
+  pub fn new(pg_pool: PgPool) -> Arc<Self> {
+    let (queue, rx) = mpsc::channel(REJECTED_UPDATE_QUEUE_SIZE);
+    tokio::spawn(write_rejected_updates(pg_pool, rx));
+    Arc::new(Self { queue })
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  pub fn record(
+    &self,
+    workspace_id: WorkspaceId,
+    object_id: ObjectId,
+    collab_type: CollabType,
+    uid: i64,
+    sender: &CollabOrigin,
+    reason: &str,
+    update: &[u8],
+  ) {
+    if update.len() < MIN_CONFLICT_UPDATE_LEN {
+      return;
+    }
+    let device_id = match sender {
+      CollabOrigin::Client(client) => Some(client.device_id.clone()),
+      _ => None,
+    };
+    let rejected = RejectedUpdate {
+      workspace_id,
+      object_id,
+      collab_type,
+      uid,
+      device_id,
+      reason: reason.to_string(),
+      update: update.to_vec(),
+    };
+    if let Err(err) = self.queue.try_send(rejected) {
+      warn!(
+        "dropped rejected update of {} by {}: {}",
+        object_id, uid, err
+      );
+    }
+  }
+}
+
+async fn write_rejected_updates(pg_pool: PgPool, mut rx: mpsc::Receiver<RejectedUpdate>) {
+  while let Some(rejected) = rx.recv().await {
+    let result = insert_collab_rejected_update(
+      &pg_pool,
+      &rejected.workspace_id,
+      &rejected.object_id,
+      &rejected.collab_type,
+      rejected.uid,
+      rejected.device_id.as_deref(),
+      &rejected.reason,
+      &rejected.update,
+    )
+    .await;
+    match result {
+      Ok(Some(conflict_id)) => {
+        trace!(
+          "recorded conflict {} for rejected update of {} by {}: {}",
+          conflict_id,
+          rejected.object_id,
+          rejected.uid,
+          rejected.reason
+        );
+        if let Err(err) = delete_excess_collab_rejected_updates(
+          &pg_pool,
+          &rejected.workspace_id,
+          &rejected.object_id,
+          rejected.uid,
+          MAX_REJECTED_UPDATES_PER_COLLAB,
+        )
+        .await
+        {
+          warn!(
+            "failed to trim rejected updates of {} by {}: {}",
+            rejected.object_id, rejected.uid, err
+          );
+        }
+      },
+      // The client sent the same update again
+      Ok(None) => {},
+      Err(err) => warn!(
+        "failed to record rejected update of {} by {}: {}",
+        rejected.object_id, rejected.uid, err
+      ),
+    }
+  }
+}
@@ -0,0 +1,340 @@
+use crate::collab::cache::CollabCache;
+use crate::collab::collab_manager::{build_collab_from_snapshot, decode_update};
+use app_error::AppError;
+use appflowy_proto::{ObjectId, WorkspaceId};
+use collab::core::collab::default_client_id;
+use collab::core::origin::CollabOrigin;
+use collab::entity::EncoderVersion;
+use collab::preclude::{Collab, Map, MapRef, Out};
+use collab_entity::CollabType;
+use collab_folder::Folder;
+use database::collab::AppResult;
+use database_entity::dto::QueryCollab;
+use lru::LruCache;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::instrument;
+use yrs::ReadTxn;
+
+/// Decoded collabs are kept for this long, after which they are loaded again so that changes
+/// that didn't go through the guard, e.g. made on other servers, are taken into account.
+const CHECKED_COLLAB_TTL: Duration = Duration::from_secs(60);
+
+/// Hard limits on the structure of collabs, checked before an update is accepted. A limit of 0
+/// disables the check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollabStructureLimits {
+  /// Maximum nesting depth of the views of a folder. Spaces are at depth 1.
+  pub max_view_depth: usize,
+  /// Maximum number of blocks of a document.
+  pub max_document_blocks: usize,
+}
+
+impl Default for CollabStructureLimits {
+  fn default() -> Self {
+    Self {
+      max_view_depth: 64,
+      max_document_blocks: 50_000,
+    }
+  }
+}
+
+impl CollabStructureLimits {
+  /// Rejects a folder whose view depth goes from `before` to `after`. Folders that were already
+  /// above the limit can still be edited, as long as the edit doesn't make them deeper.
+  pub fn check_view_depth(&self, before: usize, after: usize) -> Result<(), AppError> {
+    if exceeds_limit(self.max_view_depth, before, after) {
+      return Err(AppError::CollabStructureLimitExceeded(format!(
+        "views can't be nested more than {} levels deep",
+        self.max_view_depth
+      )));
+    }
+    Ok(())
+  }
+
+  /// Rejects a document whose block count goes from `before` to `after`. Documents that were
+  /// already above the limit can still be edited, as long as the edit doesn't add blocks.
+  pub fn check_document_blocks(&self, before: usize, after: usize) -> Result<(), AppError> {
+    if exceeds_limit(self.max_document_blocks, before, after) {
+      return Err(AppError::CollabStructureLimitExceeded(format!(
+        "a document can't have more than {} blocks",
+        self.max_document_blocks
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// Checks the updates made by users against the [CollabStructureLimits]: the view depth for the
+/// workspace folder and the block count for documents. Other collab types are not checked.
+///
+/// Checking an update means measuring the collab before and after it, so the collabs recently
+/// checked are kept decoded, with the accepted updates applied, and consecutive updates of the
+/// same collab don't decode it again. Once the cache is full, the least recently checked collab
+/// is dropped.
+pub struct CollabStructureGuard {
+  limits: CollabStructureLimits,
+  collab_cache: Arc<CollabCache>,
+  checked_collabs: Mutex<LruCache<ObjectId, CheckedCollab>>,
+}
+
+struct CheckedCollab {
+  collab: DecodedCollab,
+  loaded_at: Instant,
+}
+
+enum DecodedCollab {
+  Document(Collab),
+  Folder(Box<Folder>),
+}
+
+struct CheckOutcome {
+  result: AppResult<()>,
+  /// The update depends on changes the decoded collab hasn't seen, so it was measured on an
+  /// incomplete collab.
+  stale: bool,
+  checked: CheckedCollab,
+}
+
+impl CollabStructureGuard {
+  /// Keeps up to `max_checked_collabs` collabs decoded, at least one.
+  pub fn new(
+    limits: CollabStructureLimits,
+    max_checked_collabs: usize,
+    collab_cache: Arc<CollabCache>,
+  ) -> Arc<Self> {
+    let capacity = NonZeroUsize::new(max_checked_collabs).unwrap_or(NonZeroUsize::MIN);
+    Arc::new(Self {
+      limits,
+      collab_cache,
+      checked_collabs: Mutex::new(LruCache::new(capacity)),
+    })
+  }
+
+  /// Fails with [AppError::CollabStructureLimitExceeded] when applying `update`, made by the
+  /// user `uid`, makes the collab exceed the limits.
+  #[instrument(level = "trace", skip_all, err)]
+  pub async fn enforce(
+    &self,
+    workspace_id: WorkspaceId,
+    object_id: ObjectId,
+    collab_type: CollabType,
+    uid: i64,
+    update: &[u8],
+  ) -> AppResult<()> {
+    match collab_type {
+      CollabType::Document if self.limits.max_document_blocks > 0 => {},
+      CollabType::Folder if self.limits.max_view_depth > 0 && object_id == workspace_id => {},
+      _ => return Ok(()),
+    }
+    // Taken out while checking, so that concurrent updates of the same collab load their own
+    // copy instead of waiting
+    let checked = self
+      .checked_collabs
+      .lock()
+      .ok()
+      .and_then(|mut checked_collabs| checked_collabs.pop(&object_id))
+      .filter(|checked| checked.loaded_at.elapsed() < CHECKED_COLLAB_TTL);
+    if let Some(checked) = checked {
+      let outcome = self.check(checked, workspace_id, uid, update).await?;
+      if !outcome.stale {
+        return self.keep_if_accepted(object_id, outcome);
+      }
+    }
+    let checked = match self.load(workspace_id, object_id, collab_type).await? {
+      Some(checked) => checked,
+      // The folder is being created: there is nothing to compare the update to yet.
+      None => return Ok(()),
+    };
+    let outcome = self.check(checked, workspace_id, uid, update).await?;
+    self.keep_if_accepted(object_id, outcome)
+  }
+
+  /// The checked copy has the update applied, so it is only kept when the update is accepted and
+  /// the copy is complete.
+  fn keep_if_accepted(&self, object_id: ObjectId, outcome: CheckOutcome) -> AppResult<()> {
+    if outcome.result.is_ok() && !outcome.stale {
+      if let Ok(mut checked_collabs) = self.checked_collabs.lock() {
+        checked_collabs.put(object_id, outcome.checked);
+      }
+    }
+    outcome.result
+  }
+
+  async fn load(
+    &self,
+    workspace_id: WorkspaceId,
+    object_id: ObjectId,
+    collab_type: CollabType,
+  ) -> AppResult<Option<CheckedCollab>> {
+    let encoded_collab = self
+      .collab_cache
+      .get_full_collab(
+        &workspace_id,
+        QueryCollab::new(object_id, collab_type),
+        None,
+        EncoderVersion::V1,
+      )
+      .await?
+      .encoded_collab;
+    tokio::task::spawn_blocking(move || {
+      let collab = if collab_type == CollabType::Folder {
+        match Folder::from_collab_doc_state(
+          CollabOrigin::Server,
+          encoded_collab.into(),
+          &workspace_id.to_string(),
+          default_client_id(),
+        ) {
+          Ok(folder) => DecodedCollab::Folder(Box::new(folder)),
+          Err(_) => return Ok(None),
+        }
+      } else {
+        DecodedCollab::Document(build_collab_from_snapshot(
+          default_client_id(),
+          object_id,
+          &encoded_collab.doc_state,
+        )?)
+      };
+      Ok(Some(CheckedCollab {
+        collab,
+        loaded_at: Instant::now(),
+      }))
+    })
+    .await?
+  }
+
+  /// Applies the update to the decoded collab and compares its structure before and after.
+  async fn check(
+    &self,
+    mut checked: CheckedCollab,
+    workspace_id: WorkspaceId,
+    uid: i64,
+    update: &[u8],
+  ) -> AppResult<CheckOutcome> {
+    let limits = self.limits;
+    let update = decode_update(update)?;
+    tokio::task::spawn_blocking(move || {
+      let (result, stale) = match &mut checked.collab {
+        DecodedCollab::Document(collab) => {
+          let before = document_block_count(&collab.data, &collab.transact());
+          let mut txn = collab.transact_mut();
+          txn
+            .apply_update(update)
+            .map_err(|err| AppError::DecodeUpdateError(err.to_string()))?;
+          let stale = txn.has_missing_updates();
+          drop(txn);
+          let after = document_block_count(&collab.data, &collab.transact());
+          (limits.check_document_blocks(before, after), stale)
+        },
+        DecodedCollab::Folder(folder) => {
+          let workspace_id = workspace_id.to_string();
+          let before = folder_view_depth(folder, &workspace_id, uid);
+          let mut txn = folder.collab.transact_mut();
+          txn
+            .apply_update(update)
+            .map_err(|err| AppError::DecodeUpdateError(err.to_string()))?;
+          let stale = txn.has_missing_updates();
+          drop(txn);
+          let after = folder_view_depth(folder, &workspace_id, uid);
+          (limits.check_view_depth(before, after), stale)
+        },
+      };
+      Ok(CheckOutcome {
+        result,
+        stale,
+        checked,
+      })
+    })
+    .await?
+  }
+}
+
+fn exceeds_limit(limit: usize, before: usize, after: usize) -> bool {
+  limit > 0 && after > limit && after > before
+}
+
+/// Number of blocks of a document collab, read from the length of its blocks map so that the
+/// blocks don't need to be deserialized. Returns 0 when the collab is not a document.
+pub fn document_block_count<T: yrs::ReadTxn>(root: &MapRef, txn: &T) -> usize {
+  // Same keys as the document schema of collab_document.
+  const DOCUMENT_KEY: &str = "document";
+  const BLOCKS_KEY: &str = "blocks";
+
+  let document = match root.get(txn, DOCUMENT_KEY) {
+    Some(Out::YMap(map)) => map,
+    _ => return 0,
+  };
+  match document.get(txn, BLOCKS_KEY) {
+    Some(Out::YMap(blocks)) => blocks.len(txn) as usize,
+    _ => 0,
+  }
+}
+
+/// Depth of the deepest view reachable from the workspace. Views that are not reachable from the
+/// workspace, such as orphaned views, are not counted.
+pub fn folder_view_depth(folder: &Folder, workspace_id: &str, uid: i64) -> usize {
+  let mut visited = HashSet::from([workspace_id.to_string()]);
+  let mut level = vec![workspace_id.to_string()];
+  let mut depth = 0;
+  while !level.is_empty() {
+    let mut next_level = Vec::new();
+    for parent_id in &level {
+      for view in folder.get_views_belong_to(parent_id, uid) {
+        if visited.insert(view.id.clone()) {
+          next_level.push(view.id.clone());
+        }
+      }
+    }
+    if next_level.is_empty() {
+      break;
+    }
+    depth += 1;
+    level = next_level;
+  }
+  depth
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn growing_past_the_limit_is_rejected() {
+    let limits = CollabStructureLimits {
+      max_view_depth: 3,
+      max_document_blocks: 10,
+    };
+    assert!(limits.check_view_depth(3, 3).is_ok());
+    assert!(limits.check_view_depth(3, 4).is_err());
+    assert!(limits.check_document_blocks(2, 10).is_ok());
+    assert!(matches!(
+      limits.check_document_blocks(10, 11),
+      Err(AppError::CollabStructureLimitExceeded(_))
+    ));
+  }
+
+  #[test]
+  fn collabs_already_past_the_limit_can_shrink_or_stay() {
+    let limits = CollabStructureLimits {
+      max_view_depth: 3,
+      max_document_blocks: 10,
+    };
+    assert!(limits.check_view_depth(5, 5).is_ok());
+    assert!(limits.check_view_depth(5, 4).is_ok());
+    assert!(limits.check_view_depth(5, 6).is_err());
+    assert!(limits.check_document_blocks(20, 15).is_ok());
+    assert!(limits.check_document_blocks(20, 21).is_err());
+  }
+
+  #[test]
+  fn zero_disables_the_limit() {
+    let limits = CollabStructureLimits {
+      max_view_depth: 0,
+      max_document_blocks: 0,
+    };
+    assert!(limits.check_view_depth(0, 1_000).is_ok());
+    assert!(limits.check_document_blocks(0, 1_000_000).is_ok());
+  }
+}
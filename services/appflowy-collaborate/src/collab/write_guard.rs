@@ -0,0 +1,165 @@
+use app_error::AppError;
+use appflowy_proto::{AccessChangedReason, ObjectId, WorkspaceId};
+use dashmap::DashMap;
+use database::archived_space::select_archived_object_ids;
+use database::workspace::is_workspace_merging;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How long the write blocks of a workspace are cached. Changes made through this server drop
+/// the cached state right away, other servers pick them up once it expires.
+pub const WRITE_BLOCKS_TTL: Duration = Duration::from_secs(15);
+/// Expired entries are swept once the cache holds more workspaces than this. Workspaces being
+/// merged are kept.
+const MAX_CACHED_WORKSPACES: usize = 10_000;
+
+/// Why a collab can't be written to, whatever the permissions of the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteBlock {
+  /// The collab belongs to an archived space.
+  SpaceArchived,
+  /// The workspace of the collab is being merged into another one.
+  WorkspaceMerging,
+}
+
+impl WriteBlock {
+  pub fn reason(&self) -> &'static str {
+    match self {
+      WriteBlock::SpaceArchived => "space_archived",
+      WriteBlock::WorkspaceMerging => "workspace_merging",
+    }
+  }
+}
+
+impl From<WriteBlock> for AccessChangedReason {
+  fn from(block: WriteBlock) -> Self {
+    match block {
+      WriteBlock::SpaceArchived => AccessChangedReason::SpaceArchived,
+      WriteBlock::WorkspaceMerging => AccessChangedReason::WorkspaceMerging,
+    }
+  }
+}
+
+impl From<WriteBlock> for AppError {
+  fn from(block: WriteBlock) -> Self {
+    match block {
+      WriteBlock::SpaceArchived => AppError::NotEnoughPermissions,
+      WriteBlock::WorkspaceMerging => AppError::ServiceTemporaryUnavailable(
+        "the workspace is being merged into another workspace".to_string(),
+      ),
+    }
+  }
+}
+
+#[derive(Default)]
+struct WorkspaceWriteBlocks {
+  /// Collabs of the archived spaces, including the databases and rows they contain.
+  archived_object_ids: HashSet<ObjectId>,
+  /// Set while the workspace is merged into another one: all its collabs are read-only.
+  merging: bool,
+}
+
+/// Refuses writes to collabs that are read-only for everyone, such as the pages of archived
+/// spaces or the collabs of a workspace being merged. It is checked on every write path: the
+/// realtime protocols and the HTTP updates.
+pub struct CollabWriteGuard {
+  pg_pool: PgPool,
+  /// Write blocks of each workspace, with the time they were loaded. An invalidated entry has no
+  /// load time, it is reloaded on the next check but still used when that fails.
+  workspaces: DashMap<WorkspaceId, (Option<Instant>, Arc<WorkspaceWriteBlocks>)>,
+}
+
+impl CollabWriteGuard {
+  pub fn new(pg_pool: PgPool) -> Arc<Self> {
+    Arc::new(Self {
+      pg_pool,
+      workspaces: DashMap::new(),
+    })
+  }
+
+  /// Returns why writes to the collab are refused, if they are. When the state of the workspace
+  /// can't be read, the last known state is used, so a workspace being merged stays read-only.
+  /// Writes are only let through when nothing is known about the workspace.
+  pub async fn write_block(
+    &self,
+    workspace_id: &WorkspaceId,
+    object_id: &ObjectId,
+  ) -> Option<WriteBlock> {
+    let blocks = self.workspace_blocks(workspace_id).await?;
+    if blocks.merging {
+      return Some(WriteBlock::WorkspaceMerging);
+    }
+    if blocks.archived_object_ids.contains(object_id) {
+      return Some(WriteBlock::SpaceArchived);
+    }
+    None
+  }
+
+  /// Expires the cached state of the workspace, once spaces were archived or unarchived, or a
+  /// merge started or ended.
+  pub fn invalidate(&self, workspace_id: &WorkspaceId) {
+    if let Some(mut entry) = self.workspaces.get_mut(workspace_id) {
+      entry.value_mut().0 = None;
+    }
+  }
+
+  fn last_known_blocks(&self, workspace_id: &WorkspaceId) -> Option<Arc<WorkspaceWriteBlocks>> {
+    self
+      .workspaces
+      .get(workspace_id)
+      .map(|entry| entry.value().1.clone())
+  }
+
+  async fn workspace_blocks(
+    &self,
+    workspace_id: &WorkspaceId,
+  ) -> Option<Arc<WorkspaceWriteBlocks>> {
+    if let Some(entry) = self.workspaces.get(workspace_id) {
+      let (loaded_at, blocks) = entry.value();
+      if is_fresh(loaded_at) {
+        return Some(blocks.clone());
+      }
+    }
+
+    let archived_object_ids = match select_archived_object_ids(&self.pg_pool, workspace_id).await {
+      Ok(object_ids) => object_ids.into_iter().collect(),
+      Err(err) => {
+        warn!(
+          "failed to load the archived collabs of workspace {}: {}",
+          workspace_id, err
+        );
+        return self.last_known_blocks(workspace_id);
+      },
+    };
+    let merging = match is_workspace_merging(&self.pg_pool, workspace_id).await {
+      Ok(merging) => merging,
+      Err(err) => {
+        warn!(
+          "failed to check whether workspace {} is being merged: {}",
+          workspace_id, err
+        );
+        return self.last_known_blocks(workspace_id);
+      },
+    };
+    let blocks = Arc::new(WorkspaceWriteBlocks {
+      archived_object_ids,
+      merging,
+    });
+    if self.workspaces.len() >= MAX_CACHED_WORKSPACES {
+      self
+        .workspaces
+        .retain(|_, (loaded_at, blocks)| blocks.merging || is_fresh(loaded_at));
+    }
+    self
+      .workspaces
+      .insert(*workspace_id, (Some(Instant::now()), blocks.clone()));
+    Some(blocks)
+  }
+}
+
+fn is_fresh(loaded_at: &Option<Instant>) -> bool {
+  loaded_at.is_some_and(|loaded_at| loaded_at.elapsed() < WRITE_BLOCKS_TTL)
+}
@@ -1,3 +1,5 @@
+use crate::collab::structure_limit::CollabStructureGuard;
+use crate::collab::write_guard::CollabWriteGuard;
 use crate::error::RealtimeError;
 use anyhow::anyhow;
 use app_error::AppError;
@@ -68,6 +70,8 @@ struct CollabGroupState {
   seq_no: AtomicU32,
   /// The most recent state vector from a redis update.
   state_vector: RwLock<StateVector>,
+  write_guard: Arc<CollabWriteGuard>,
+  structure_guard: Arc<CollabStructureGuard>,
 }
 
 impl Drop for CollabGroup {
@@ -90,6 +94,8 @@ impl CollabGroup {
     persistence_interval: Duration,
     state_vector: StateVector,
     indexer_scheduler: Arc<IndexerScheduler>,
+    write_guard: Arc<CollabWriteGuard>,
+    structure_guard: Arc<CollabStructureGuard>,
   ) -> Result<Self, StreamError> {
     let is_new_collab = state_vector.is_empty();
     let persister = CollabPersister::new(
@@ -115,6 +121,8 @@ impl CollabGroup {
       last_activity: ArcSwap::new(Instant::now().into()),
       seq_no: AtomicU32::new(0),
       state_vector: state_vector.into(),
+      write_guard,
+      structure_guard,
     });
 
     /*
@@ -743,6 +751,32 @@ impl CollabGroup {
     origin: &CollabOrigin,
     update: Vec<u8>,
   ) -> Result<Option<Vec<u8>>, RTProtocolError> {
+    // Collabs that are read-only for everyone, such as the pages of archived spaces
+    if let Some(uid) = origin.client_user_id() {
+      if let Some(block) = state
+        .write_guard
+        .write_block(&state.workspace_id, &state.object_id)
+        .await
+      {
+        return Err(RTProtocolError::PermissionDenied {
+          reason: block.reason().to_string(),
+        });
+      }
+      // Updates that would make the folder too deep or a document too large
+      if let Err(AppError::CollabStructureLimitExceeded(reason)) = state
+        .structure_guard
+        .enforce(
+          state.workspace_id,
+          state.object_id,
+          state.collab_type,
+          uid,
+          &update,
+        )
+        .await
+      {
+        return Err(RTProtocolError::PermissionDenied { reason });
+      }
+    }
     state.metrics.collab_size.observe(update.len() as f64);
 
     let start = tokio::time::Instant::now();
@@ -868,6 +902,7 @@ impl CollabGroup {
       RTProtocolError::YrsApplyUpdate(_) => AckCode::CannotApplyUpdate,
       RTProtocolError::YrsEncodeState(_) => AckCode::EncodeStateAsUpdateFail,
       RTProtocolError::MissUpdates { .. } => AckCode::MissUpdate,
+      RTProtocolError::PermissionDenied { .. } => AckCode::PermissionDenied,
       _ => AckCode::Internal,
     }
   }
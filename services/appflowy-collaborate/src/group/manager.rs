@@ -15,6 +15,8 @@ use uuid::Uuid;
 use yrs::{ReadTxn, StateVector};
 
 use crate::client::client_msg_router::ClientMessageRouter;
+use crate::collab::structure_limit::CollabStructureGuard;
+use crate::collab::write_guard::CollabWriteGuard;
 use crate::error::RealtimeError;
 use crate::group::group_init::CollabGroup;
 use crate::group::state::GroupManagementState;
@@ -29,6 +31,8 @@ pub struct GroupManager {
   collab_redis_stream: Arc<CollabRedisStream>,
   persistence_interval: Duration,
   indexer_scheduler: Arc<IndexerScheduler>,
+  write_guard: Arc<CollabWriteGuard>,
+  structure_guard: Arc<CollabStructureGuard>,
 }
 
 impl GroupManager {
@@ -40,6 +44,8 @@ impl GroupManager {
     collab_stream: CollabRedisStream,
     persistence_interval: Duration,
     indexer_scheduler: Arc<IndexerScheduler>,
+    write_guard: Arc<CollabWriteGuard>,
+    structure_guard: Arc<CollabStructureGuard>,
   ) -> Result<Self, RealtimeError> {
     let collab_stream = Arc::new(collab_stream);
     Ok(Self {
@@ -50,6 +56,8 @@ impl GroupManager {
       collab_redis_stream: collab_stream,
       persistence_interval,
       indexer_scheduler,
+      write_guard,
+      structure_guard,
     })
   }
 
@@ -152,6 +160,8 @@ impl GroupManager {
       self.persistence_interval,
       state_vector,
       self.indexer_scheduler.clone(),
+      self.write_guard.clone(),
+      self.structure_guard.clone(),
     )
     .await?;
     self.state.insert_group(object_id, group);
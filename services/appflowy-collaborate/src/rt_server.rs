@@ -3,6 +3,8 @@ use std::time::Duration;
 
 use crate::actix_ws::entities::{ClientGenerateEmbeddingMessage, ClientHttpUpdateMessage};
 use crate::client::client_msg_router::ClientMessageRouter;
+use crate::collab::structure_limit::CollabStructureGuard;
+use crate::collab::write_guard::CollabWriteGuard;
 use crate::connect_state::ConnectState;
 use crate::error::{CreateGroupFailedReason, RealtimeError};
 use crate::group::cmd::{GroupCommand, GroupCommandRunner, GroupCommandSender};
@@ -49,6 +51,8 @@ impl CollaborationServer {
     redis_connection_manager: ConnectionManager,
     group_persistence_interval: Duration,
     indexer_scheduler: Arc<IndexerScheduler>,
+    write_guard: Arc<CollabWriteGuard>,
+    structure_guard: Arc<CollabStructureGuard>,
   ) -> Result<Self, RealtimeError> {
     let connect_state = ConnectState::new();
     let collab_stream = CollabRedisStream::new_with_connection_manager(
@@ -64,6 +68,8 @@ impl CollaborationServer {
         collab_stream,
         group_persistence_interval,
         indexer_scheduler.clone(),
+        write_guard,
+        structure_guard,
       )
       .await?,
     );
@@ -1,6 +1,7 @@
 use super::server::{Join, Leave, WsOutput};
 use super::session::{InputMessage, WsInput, WsSession};
 use crate::collab::collab_manager::CollabManager;
+use crate::collab::rejected_update::CONFLICT_REASON_PERMISSION_DENIED;
 use crate::collab::snapshot_scheduler::SnapshotScheduler;
 use crate::ws2::{
   BroadcastPermissionChanges, PublishUpdate, RefreshWorkspaceUserPermissions,
@@ -29,6 +30,9 @@ use yrs::block::ClientID;
 use yrs::updates::encoder::Encode;
 use yrs::{StateVector, Update};
 
+const CONFLICT_REASON_PUBLISH_FAILED: &str = "publish_failed";
+const CONFLICT_REASON_STRUCTURE_LIMIT: &str = "structure_limit_exceeded";
+
 pub struct Workspace {
   server: Recipient<Terminate>,
   workspace_id: WorkspaceId,
@@ -74,13 +78,33 @@ impl Workspace {
     workspace_id: WorkspaceId,
     msg: PublishUpdate,
   ) {
+    // Only the edits made on behalf of a user are limited, not the updates of the server itself.
+    if let Some(uid) = msg.sender.client_user_id() {
+      if let Some(block) = store.write_block(&workspace_id, &msg.object_id).await {
+        let _ = msg.ack.send(Err(AppError::from(block).into()));
+        return;
+      }
+      if let Err(err @ AppError::CollabStructureLimitExceeded(_)) = store
+        .enforce_structure_limits(
+          workspace_id,
+          msg.object_id,
+          msg.collab_type,
+          uid,
+          &msg.update_v1,
+        )
+        .await
+      {
+        let _ = msg.ack.send(Err(err.into()));
+        return;
+      }
+    }
     let result = store
       .publish_update(
         workspace_id,
         msg.object_id,
         msg.collab_type,
         &msg.sender,
-        msg.update_v1,
+        &msg.update_v1,
       )
       .await;
     let _ = msg.ack.send(result);
@@ -181,6 +205,36 @@ impl Workspace {
           return;
         }
 
+        // Collabs that are read-only for everyone, such as the pages of archived spaces: tell the
+        // client why its update is rejected, so that it stops editing instead of retrying.
+        if let Some(block) = store.write_block(&msg.workspace_id, &msg.object_id).await {
+          tracing::trace!(
+            "user {} cannot write to collab {}: {}",
+            sender.uid,
+            msg.object_id,
+            block.reason(),
+          );
+          sender.conn.do_send(WsOutput {
+            message: ServerMessage::AccessChanges {
+              object_id: msg.object_id,
+              collab_type,
+              can_read: true,
+              can_write: false,
+              reason: block.into(),
+            },
+          });
+          store.record_rejected_update(
+            msg.workspace_id,
+            msg.object_id,
+            collab_type,
+            sender.uid,
+            &msg.sender,
+            block.reason(),
+            &update,
+          );
+          return;
+        }
+
         // Check if the user has permission to write to the collab.
         let can_write = sender
           .can_write_collab(&store, &msg.object_id)
@@ -192,20 +246,88 @@ impl Workspace {
             sender.uid,
             msg.object_id,
           );
+          // Typically edits a device made offline after losing write access: the client resets
+          // the collab to the server state, so keep them for the user to recover.
+          store.record_rejected_update(
+            msg.workspace_id,
+            msg.object_id,
+            collab_type,
+            sender.uid,
+            &msg.sender,
+            CONFLICT_REASON_PERMISSION_DENIED,
+            &update,
+          );
           return;
         }
 
+        // Updates that would make the folder too deep or a document too large are rejected, and
+        // the client resets the collab to the server state.
+        match store
+          .enforce_structure_limits(
+            msg.workspace_id,
+            msg.object_id,
+            collab_type,
+            sender.uid,
+            &update,
+          )
+          .await
+        {
+          Ok(()) => {},
+          Err(AppError::CollabStructureLimitExceeded(reason)) => {
+            tracing::debug!(
+              "rejected update of user {} to collab {}: {}",
+              sender.uid,
+              msg.object_id,
+              reason
+            );
+            sender.conn.do_send(WsOutput {
+              message: ServerMessage::AccessChanges {
+                object_id: msg.object_id,
+                collab_type,
+                can_read: true,
+                can_write: true,
+                reason: AccessChangedReason::StructureLimitExceeded,
+              },
+            });
+            store.record_rejected_update(
+              msg.workspace_id,
+              msg.object_id,
+              collab_type,
+              sender.uid,
+              &msg.sender,
+              CONFLICT_REASON_STRUCTURE_LIMIT,
+              &update,
+            );
+            return;
+          },
+          // Failing to check the limits must not stop the sync.
+          Err(err) => tracing::warn!(
+            "failed to check structure limits of collab {}: {}",
+            msg.object_id,
+            err
+          ),
+        }
+
         if let Err(err) = store
           .publish_update(
             msg.workspace_id,
             msg.object_id,
             collab_type,
             &msg.sender,
-            update,
+            &update,
           )
           .await
         {
           tracing::error!("failed to publish update: {:?}", err);
+          store.record_rejected_update(
+            msg.workspace_id,
+            msg.object_id,
+            collab_type,
+            sender.uid,
+            &msg.sender,
+            CONFLICT_REASON_PUBLISH_FAILED,
+            &update,
+          );
         }
       },
       InputMessage::AwarenessUpdate(update) => {
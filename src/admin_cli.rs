@@ -0,0 +1,253 @@
+//! Operational commands run with the server binary, `appflowy_cloud admin <command>`, so that
+//! common maintenance does not need ad-hoc SQL against the production database. The commands
+//! use the same configuration as the server.
+
+use std::io::BufRead;
+use std::sync::Arc;
+
+use access_control::noops::collab::CollabAccessControlImpl as NoOpsCollabAccessControlImpl;
+use access_control::noops::workspace::WorkspaceAccessControlImpl as NoOpsWorkspaceAccessControlImpl;
+use anyhow::{anyhow, bail, Context};
+use appflowy_collaborate::collab::cache::CollabCache;
+use appflowy_collaborate::collab::collab_store::CollabStoreImpl;
+use collab::core::collab::default_client_id;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database::collab::{CollabStore, GetCollabOrigin};
+use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::index::reset_workspace_collabs_indexed_at;
+use database::subscription::{
+  aggregate_user_usage, backfill_user_usage_subscription_id, get_user_active_subscription,
+  get_user_total_usage_bytes,
+};
+use database::user::select_uid_from_email;
+use gotrue::params::{AdminUserParams, GenerateLinkParams, GenerateLinkType};
+use indexer::queue::add_background_embed_task;
+use indexer::scheduler::{UnindexedCollabTask, UnindexedData};
+use infra::thread_pool::ThreadPoolNoAbortBuilder;
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::application::{
+  get_admin_client, get_aws_s3_client, get_connection_pool, get_gotrue_client,
+};
+use crate::biz::collab::utils::{batch_get_latest_collab_encoded, collab_from_doc_state};
+use crate::config::config::Config;
+use crate::state::AppMetrics;
+
+const REINDEX_BATCH_SIZE: usize = 50;
+const GOTRUE_ADMIN_ROLE: &str = "supabase_admin";
+/// Password of the user created by `create-admin-user`, read from stdin when unset. It is never
+/// taken from the arguments, which other users of the host can see.
+const ADMIN_PASSWORD_ENV: &str = "APPFLOWY_ADMIN_PASSWORD";
+
+const USAGE: &str = "Usage: appflowy_cloud admin <command>
+
+Commands:
+  create-admin-user <email>          Create a confirmed user with the admin role. The password
+                                     is read from APPFLOWY_ADMIN_PASSWORD, or from stdin
+  password-reset-link <email>        Print a password recovery link for the user
+  reindex-workspace <workspace_id>   Queue every document of the workspace for search indexing
+  recompute-usage <email>            Attribute usage records to subscriptions and print usage
+  help                               Print this message";
+
+/// Returns true when the arguments of the process ask for an admin command.
+pub fn is_admin_command(args: &[String]) -> bool {
+  args.get(1).map(String::as_str) == Some("admin")
+}
+
+/// Runs the admin command given after `admin` in the arguments of the process.
+pub async fn run_admin_command(config: &Config, args: &[String]) -> anyhow::Result<()> {
+  let args: Vec<&str> = args.iter().skip(2).map(String::as_str).collect();
+  match args.as_slice() {
+    ["create-admin-user", email] => {
+      let password = read_admin_password()?;
+      create_admin_user(config, email, &password).await
+    },
+    ["password-reset-link", email] => password_reset_link(config, email).await,
+    ["reindex-workspace", workspace_id] => {
+      let workspace_id = Uuid::parse_str(workspace_id).context("invalid workspace id")?;
+      reindex_workspace(config, workspace_id).await
+    },
+    ["recompute-usage", email] => recompute_usage(config, email).await,
+    [] | ["help"] => {
+      println!("{}", USAGE);
+      Ok(())
+    },
+    _ => bail!("unknown admin command\n\n{}", USAGE),
+  }
+}
+
+async fn create_admin_user(config: &Config, email: &str, password: &str) -> anyhow::Result<()> {
+  let gotrue_client = get_gotrue_client(&config.gotrue).await?;
+  let gotrue_admin = get_admin_client(gotrue_client.clone(), &config.gotrue);
+  let token = gotrue_admin.token().await?;
+  let user = gotrue_client
+    .admin_add_user(
+      &token,
+      &AdminUserParams {
+        email: email.to_string(),
+        password: Some(password.to_string()),
+        email_confirm: true,
+        role: GOTRUE_ADMIN_ROLE.to_string(),
+        ..Default::default()
+      },
+    )
+    .await
+    .map_err(|err| anyhow!("failed to create the admin user: {}", err))?;
+  println!("created admin user {} ({})", user.email, user.id);
+  Ok(())
+}
+
+fn read_admin_password() -> anyhow::Result<String> {
+  let password = match std::env::var(ADMIN_PASSWORD_ENV) {
+    Ok(password) => password,
+    Err(_) => {
+      eprintln!("password (or set {}):", ADMIN_PASSWORD_ENV);
+      let mut line = String::new();
+      std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("failed to read the password")?;
+      line.trim_end_matches(['\r', '\n']).to_string()
+    },
+  };
+  if password.is_empty() {
+    bail!("the password must not be empty");
+  }
+  Ok(password)
+}
+
+async fn password_reset_link(config: &Config, email: &str) -> anyhow::Result<()> {
+  let gotrue_client = get_gotrue_client(&config.gotrue).await?;
+  let gotrue_admin = get_admin_client(gotrue_client.clone(), &config.gotrue);
+  let token = gotrue_admin.token().await?;
+  let link = gotrue_client
+    .admin_generate_link(
+      &token,
+      &GenerateLinkParams {
+        type_: GenerateLinkType::Recovery,
+        email: email.to_string(),
+        redirect_to: config.appflowy_web_url.clone(),
+        ..Default::default()
+      },
+    )
+    .await
+    .map_err(|err| anyhow!("failed to generate the recovery link: {}", err))?;
+  println!("{}", link.action_link);
+  Ok(())
+}
+
+async fn reindex_workspace(config: &Config, workspace_id: Uuid) -> anyhow::Result<()> {
+  let pg_pool = get_connection_pool(&config.db_settings).await?;
+  let redis_conn_manager = redis::Client::open(config.redis_uri.expose_secret().as_str())
+    .context("failed to connect to redis")?
+    .get_connection_manager()
+    .await
+    .context("failed to get the connection manager")?;
+  let collab_storage = collab_storage(config, &pg_pool, redis_conn_manager.clone()).await?;
+
+  let oids =
+    reset_workspace_collabs_indexed_at(&pg_pool, &workspace_id, &CollabType::Document).await?;
+  let mut queued = 0;
+  for batch in oids.chunks(REINDEX_BATCH_SIZE) {
+    let encoded_collabs = batch_get_latest_collab_encoded(
+      &collab_storage,
+      GetCollabOrigin::Server,
+      workspace_id,
+      batch,
+      CollabType::Document,
+    )
+    .await?;
+    let tasks: Vec<UnindexedCollabTask> = encoded_collabs
+      .into_iter()
+      .filter_map(|(object_id, encoded_collab)| {
+        let collab = collab_from_doc_state(
+          encoded_collab.doc_state.to_vec(),
+          &object_id,
+          default_client_id(),
+        )
+        .ok()?;
+        let paragraphs = Document::open(collab).ok()?.paragraphs();
+        Some(UnindexedCollabTask::new(
+          workspace_id,
+          object_id,
+          CollabType::Document,
+          UnindexedData::Paragraphs(paragraphs),
+        ))
+      })
+      .collect();
+    queued += tasks.len();
+    add_background_embed_task(redis_conn_manager.clone(), tasks).await?;
+  }
+  println!(
+    "queued {} of {} documents of workspace {} for indexing",
+    queued,
+    oids.len(),
+    workspace_id
+  );
+  Ok(())
+}
+
+async fn recompute_usage(config: &Config, email: &str) -> anyhow::Result<()> {
+  let pg_pool = get_connection_pool(&config.db_settings).await?;
+  let uid = select_uid_from_email(&pg_pool, email).await?;
+  let attributed = backfill_user_usage_subscription_id(&pg_pool, uid).await?;
+  println!("attributed {} usage records to subscriptions", attributed);
+
+  let storage_bytes = get_user_total_usage_bytes(&pg_pool, uid).await?;
+  println!("storage: {} bytes", storage_bytes);
+  match get_user_active_subscription(&pg_pool, uid).await? {
+    Some(subscription) => {
+      let usage = aggregate_user_usage(
+        &pg_pool,
+        uid,
+        subscription.start_date.date_naive(),
+        subscription.end_date.date_naive(),
+        Some(subscription.id),
+      )
+      .await?;
+      for usage in usage {
+        println!("{}: {}", usage.usage_type, usage.total);
+      }
+    },
+    None => println!("no active subscription"),
+  }
+  Ok(())
+}
+
+/// Reads collabs the way the server does, without access control.
+async fn collab_storage(
+  config: &Config,
+  pg_pool: &PgPool,
+  redis_conn_manager: redis::aio::ConnectionManager,
+) -> anyhow::Result<Arc<dyn CollabStore>> {
+  let s3_client = AwsS3BucketClientImpl::new(
+    get_aws_s3_client(&config.s3).await?,
+    config.s3.bucket.clone(),
+    config.s3.minio_url.clone(),
+    config.s3.presigned_url_endpoint.clone(),
+  );
+  let thread_pool = Arc::new(
+    ThreadPoolNoAbortBuilder::new()
+      .thread_name(|idx| format!("af-admin-worker-{}", idx))
+      .num_threads(2)
+      .build()
+      .context("failed to create the thread pool")?,
+  );
+  let collab_cache = CollabCache::new(
+    thread_pool,
+    redis_conn_manager,
+    pg_pool.clone(),
+    pg_pool.clone(),
+    s3_client,
+    AppMetrics::new().collab_metrics,
+    config.collab.s3_collab_threshold as usize,
+  );
+  Ok(Arc::new(CollabStoreImpl::new(
+    collab_cache,
+    Arc::new(NoOpsCollabAccessControlImpl::new()),
+    Arc::new(NoOpsWorkspaceAccessControlImpl::new()),
+  )))
+}
@@ -3,6 +3,7 @@ use crate::biz::authentication::jwt::UserUuid;
 use crate::biz::subscription::ops::{fetch_current_subscription, get_user_resource_limit_status, record_usage};
 use database::subscription::get_user_total_usage_bytes;
 use crate::biz::workspace::subscription_plan_limits::PlanLimits;
+use crate::middleware::deadline::within_deadline;
 use crate::state::AppState;
 use shared_entity::dto::subscription_dto::UsageRecordRequest;
 use shared_entity::dto::subscription_dto::UsageType;
@@ -364,7 +365,7 @@ async fn summarize_row_handler(
 
       state.metrics.ai_metrics.record_total_summary_row_count(1);
       let ai_model = ai_model_from_header(&req);
-      let result = state.ai_client.summarize_row(&content, ai_model).await;
+      let result = within_deadline(state.ai_client.summarize_row(&content, ai_model)).await?;
       let resp = match result {
         Ok(resp) => SummarizeRowResponse { text: resp.text },
         Err(err) => {
@@ -389,7 +390,7 @@ async fn translate_row_handler(
   let params = payload.into_inner();
   let ai_model = ai_model_from_header(&req);
   state.metrics.ai_metrics.record_total_translate_row_count(1);
-  match state.ai_client.translate_row(params.data, ai_model).await {
+  match within_deadline(state.ai_client.translate_row(params.data, ai_model)).await? {
     Ok(resp) => Ok(AppResponse::Ok().with_data(resp).into()),
     Err(err) => {
       error!("Failed to translate row: {:?}", err);
@@ -107,6 +107,7 @@ async fn create_import_handler(
     0,
     Some(presigned_url),
     &state.redis_connection_manager,
+    &state.redis_health,
     &state.pg_pool,
   )
   .await?;
@@ -251,6 +252,7 @@ async fn import_data_handler(
     file.size,
     None,
     &state.redis_connection_manager,
+    &state.redis_health,
     &state.pg_pool,
   )
   .await?;
@@ -2,7 +2,7 @@ use access_control::act::Action;
 use actix_http::body::BoxBody;
 use actix_web::http::header::{
   ContentLength, ContentType, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE,
-  LAST_MODIFIED,
+  LAST_MODIFIED, LOCATION,
 };
 use actix_web::web::{Json, Payload};
 use actix_web::{
@@ -15,6 +15,7 @@ use app_error::AppError;
 use chrono::DateTime;
 use database::file::BlobKey;
 use database::resource_usage::{get_all_workspace_blob_metadata, get_workspace_usage_size};
+use database::workspace::select_workspace_merge_destination;
 use database_entity::file_dto::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartData,
   UploadPartResponse,
@@ -30,7 +31,7 @@ use anyhow::anyhow;
 use appflowy_ai_client::client::AppFlowyAIClient;
 use aws_sdk_s3::primitives::ByteStream;
 use collab_importer::util::FileId;
-use database::pg_row::{AFBlobSource, AFBlobStatus};
+use database::pg_row::{AFBlobSource, AFBlobStatus, AFEgressSource};
 use serde::Deserialize;
 use shared_entity::dto::billing_dto::SubscriptionPlan;
 use shared_entity::dto::file_dto::PutFileResponse;
@@ -443,6 +444,39 @@ async fn delete_blob_v1_handler(
   Ok(AppResponse::Ok().into())
 }
 
+/// Blobs of a workspace that was merged into another one are served by the other workspace.
+/// Redirects to the same path with the id of that workspace.
+async fn merged_workspace_redirect(
+  state: &AppState,
+  workspace_id: &Uuid,
+  req: &HttpRequest,
+) -> Option<HttpResponse> {
+  let destination_workspace_id =
+    match select_workspace_merge_destination(&state.pg_pool, workspace_id).await {
+      Ok(destination) => destination?,
+      Err(err) => {
+        error!(
+          "failed to look up merge of workspace {}: {}",
+          workspace_id, err
+        );
+        return None;
+      },
+    };
+  let mut location = req.path().replacen(
+    &workspace_id.to_string(),
+    &destination_workspace_id.to_string(),
+    1,
+  );
+  if !req.query_string().is_empty() {
+    location = format!("{}?{}", location, req.query_string());
+  }
+  Some(
+    HttpResponse::PermanentRedirect()
+      .insert_header((LOCATION, location))
+      .finish(),
+  )
+}
+
 async fn get_blob_by_object_key(
   state: Data<AppState>,
   key: &impl BlobKey,
@@ -456,7 +490,11 @@ async fn get_blob_by_object_key(
 
   if let Err(err) = result.as_ref() {
     return if err.is_record_not_found() {
-      Ok(HttpResponse::NotFound().finish())
+      Ok(
+        merged_workspace_redirect(&state, key.workspace_id(), &req)
+          .await
+          .unwrap_or_else(|| HttpResponse::NotFound().finish()),
+      )
     } else {
       Ok(HttpResponse::InternalServerError().finish())
     };
@@ -509,6 +547,10 @@ async fn get_blob_by_object_key(
     }
   }
 
+  if let Err(err) = state.egress_meter.check_limit(key.workspace_id()) {
+    return Ok(AppResponseError::from(err).error_response());
+  }
+
   trace!("Get blob data from storage: {:?}", key.object_key());
 
   // 优先从七牛云获取，失败则回退到MinIO（兼容历史数据）
@@ -530,6 +572,9 @@ async fn get_blob_by_object_key(
 
   match blob_result {
     Ok(blob) => {
+      state
+        .egress_meter
+        .record(*key.workspace_id(), AFEgressSource::Blob, blob.len());
       let response = HttpResponse::Ok()
           .append_header((ETAG, key.e_tag()))
           .append_header((CONTENT_TYPE, metadata.file_type))
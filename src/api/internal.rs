@@ -8,6 +8,8 @@
 // 端点：
 //   POST /internal/migrate-user-data   - 执行用户数据迁移
 //   POST /internal/delete-secondary-user - 软删除 secondary 用户（由 cloud 调用 Gotrue）
+//   POST /internal/reload-config       - 重新加载运行时配置（与向进程发送 SIGHUP 等效）
+//   GET  /internal/slow-endpoints      - 慢查询耗时最多的接口排行
 //
 // 注意：这些 API 不需要用户认证（通过 service role JWT 保护），
 // 应在网络层限制只允许 GoTrue 服务访问。
@@ -17,6 +19,8 @@ use crate::biz::user::user_merge::{
   GetUserWorkspacesRequest, GetUserWorkspacesResponse,
   MigrateUserDataRequest, MigrateUserDataResponse,
 };
+use crate::config::runtime::reload_runtime_config;
+use crate::middleware::slow_query::{top_slow_endpoints, SlowEndpoint};
 use crate::state::AppState;
 use actix_web::{web, Scope};
 use app_error::AppError;
@@ -39,6 +43,14 @@ pub fn internal_scope() -> Scope {
       web::resource("/get-user-workspaces")
         .route(web::post().to(get_user_workspaces_handler)),
     )
+    .service(
+      web::resource("/reload-config")
+        .route(web::post().to(reload_config_handler)),
+    )
+    .service(
+      web::resource("/slow-endpoints")
+        .route(web::get().to(slow_endpoints_handler)),
+    )
 }
 
 /// POST /internal/migrate-user-data
@@ -176,3 +188,37 @@ async fn delete_secondary_user_handler(
 
   Ok(AppResponse::Ok().into())
 }
+
+/// POST /internal/reload-config
+///
+/// 重新加载运行时配置（限流、AI 服务密钥、功能开关、请求体大小限制），无需重启服务，
+/// 已建立的 WebSocket 连接不受影响。新配置无效时保留当前配置并返回错误。
+async fn reload_config_handler(
+  state: web::Data<AppState>,
+) -> Result<JsonAppResponse<()>, AppResponseError> {
+  info!("[InternalAPI] Received reload-config request");
+  reload_runtime_config(&state.runtime_config, &state.indexer_scheduler).map_err(|e| {
+    tracing::error!("[InternalAPI] Failed to reload runtime config: {:?}", e);
+    AppResponseError::from(AppError::InvalidRequest(format!(
+      "Failed to reload runtime config: {}",
+      e
+    )))
+  })?;
+  Ok(AppResponse::Ok().into())
+}
+
+#[derive(serde::Deserialize)]
+struct SlowEndpointsQuery {
+  limit: Option<usize>,
+}
+
+/// GET /internal/slow-endpoints?limit=20
+///
+/// 返回自服务启动以来慢查询总耗时最多的接口（按路由统计），
+/// 慢查询阈值由 APPFLOWY_DATABASE_SLOW_QUERY_THRESHOLD_MS 配置。
+async fn slow_endpoints_handler(
+  query: web::Query<SlowEndpointsQuery>,
+) -> Result<JsonAppResponse<Vec<SlowEndpoint>>, AppResponseError> {
+  let endpoints = top_slow_endpoints(query.limit.unwrap_or(20));
+  Ok(AppResponse::Ok().with_data(endpoints).into())
+}
@@ -290,3 +290,132 @@ impl AppFlowyWebMetrics {
     self.apply_update_timeout_count.inc_by(count);
   }
 }
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct PoolLabel {
+  pub pool: String,
+}
+
+fn acquire_millis_histogram() -> Histogram {
+  Histogram::new([1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0, 10000.0].into_iter())
+}
+
+/// Usage of the Postgres connection pools and latency of Redis, sampled periodically. sqlx does
+/// not expose the number of tasks waiting for a connection, so the time taken to acquire a
+/// connection is measured instead: it grows as soon as requests queue for the pool.
+pub struct PoolMetrics {
+  pg_connections: Family<PoolLabel, Gauge>,
+  pg_idle_connections: Family<PoolLabel, Gauge>,
+  pg_in_use_connections: Family<PoolLabel, Gauge>,
+  pg_max_connections: Family<PoolLabel, Gauge>,
+  pg_acquire_millis: Family<PoolLabel, Histogram, fn() -> Histogram>,
+  pg_acquire_failure_count: Family<PoolLabel, Counter>,
+  redis_ping_millis: Histogram,
+  redis_ping_failure_count: Counter,
+}
+
+impl PoolMetrics {
+  fn init() -> Self {
+    Self {
+      pg_connections: Default::default(),
+      pg_idle_connections: Default::default(),
+      pg_in_use_connections: Default::default(),
+      pg_max_connections: Default::default(),
+      pg_acquire_millis: Family::new_with_constructor(acquire_millis_histogram),
+      pg_acquire_failure_count: Default::default(),
+      redis_ping_millis: acquire_millis_histogram(),
+      redis_ping_failure_count: Default::default(),
+    }
+  }
+
+  pub fn register(registry: &mut Registry) -> Self {
+    let metrics = Self::init();
+    let pool_registry = registry.sub_registry_with_prefix("pool");
+    pool_registry.register(
+      "pg_connections",
+      "Number of open connections of the Postgres pool",
+      metrics.pg_connections.clone(),
+    );
+    pool_registry.register(
+      "pg_idle_connections",
+      "Number of idle connections of the Postgres pool",
+      metrics.pg_idle_connections.clone(),
+    );
+    pool_registry.register(
+      "pg_in_use_connections",
+      "Number of connections of the Postgres pool in use",
+      metrics.pg_in_use_connections.clone(),
+    );
+    pool_registry.register(
+      "pg_max_connections",
+      "Maximum number of connections of the Postgres pool",
+      metrics.pg_max_connections.clone(),
+    );
+    pool_registry.register(
+      "pg_acquire_millis",
+      "Time taken to acquire a connection from the Postgres pool",
+      metrics.pg_acquire_millis.clone(),
+    );
+    pool_registry.register(
+      "pg_acquire_failure_count",
+      "Number of connections that could not be acquired from the Postgres pool",
+      metrics.pg_acquire_failure_count.clone(),
+    );
+    pool_registry.register(
+      "redis_ping_millis",
+      "Round trip time of a Redis PING",
+      metrics.redis_ping_millis.clone(),
+    );
+    pool_registry.register(
+      "redis_ping_failure_count",
+      "Number of Redis PINGs that failed or timed out",
+      metrics.redis_ping_failure_count.clone(),
+    );
+    metrics
+  }
+
+  pub fn record_pg_pool_usage(&self, pool: &str, size: u32, idle: usize, max: u32) {
+    let label = PoolLabel {
+      pool: pool.to_string(),
+    };
+    self.pg_connections.get_or_create(&label).set(size as i64);
+    self
+      .pg_idle_connections
+      .get_or_create(&label)
+      .set(idle as i64);
+    self
+      .pg_in_use_connections
+      .get_or_create(&label)
+      .set(size as i64 - idle as i64);
+    self
+      .pg_max_connections
+      .get_or_create(&label)
+      .set(max as i64);
+  }
+
+  pub fn observe_pg_acquire(&self, pool: &str, millis: u64) {
+    self
+      .pg_acquire_millis
+      .get_or_create(&PoolLabel {
+        pool: pool.to_string(),
+      })
+      .observe(millis as f64);
+  }
+
+  pub fn incr_pg_acquire_failure_count(&self, pool: &str) {
+    self
+      .pg_acquire_failure_count
+      .get_or_create(&PoolLabel {
+        pool: pool.to_string(),
+      })
+      .inc();
+  }
+
+  pub fn observe_redis_ping(&self, millis: u64) {
+    self.redis_ping_millis.observe(millis as f64);
+  }
+
+  pub fn incr_redis_ping_failure_count(&self) {
+    self.redis_ping_failure_count.inc();
+  }
+}
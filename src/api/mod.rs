@@ -14,6 +14,7 @@ pub mod subscription;
 pub mod template;
 pub mod user;
 pub mod util;
+pub mod webdav;
 pub mod workspace;
 pub mod ws;
 pub mod integrations;
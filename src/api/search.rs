@@ -67,7 +67,11 @@ async fn summary_search_results_handler(
     .enforce_action(&uid, &workspace_id, Action::Read)
     .await?;
 
-  let ai_tool = create_ai_tool(&state.config.azure_ai_config, &state.config.open_ai_config);
+  let runtime_config = state.runtime_config.load_full();
+  let ai_tool = create_ai_tool(
+    &runtime_config.azure_ai_config,
+    &runtime_config.open_ai_config,
+  );
   let result = summarize_search_results(ai_tool, request).await?;
   Ok(AppResponse::Ok().with_data(result).into())
 }
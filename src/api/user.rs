@@ -1,7 +1,21 @@
-use crate::api::util::client_version_from_headers;
+use crate::api::util::{client_version_from_headers, device_id_from_headers};
 use crate::biz::authentication::jwt::{Authorization, UserUuid};
+use crate::biz::notification::ops::{list_inbox_notifications, mark_notifications_read};
+use crate::biz::notification::push::{register_push_token, unregister_push_token};
+use crate::biz::notification::quiet_hours::{
+  list_quiet_hours, remove_quiet_hours, set_quiet_hours,
+};
+use crate::biz::subscription::ops::check_user_storage_limit;
+use crate::biz::user::device_handoff::{
+  list_pending_device_handoffs, list_user_devices, mark_device_handoff_processed,
+  send_device_handoff,
+};
 use crate::biz::user::image_asset::{get_user_image_asset, upload_user_image_asset};
 use crate::biz::user::otp_rate_limit::{check_email_otp_rate_limit, check_phone_otp_rate_limit};
+use crate::biz::user::user_api_key::{
+  create_user_api_key, list_user_api_keys, revoke_user_api_key,
+};
+use crate::biz::user::user_backup::{get_user_backup, put_user_backup, MAX_USER_BACKUP_SIZE};
 use crate::biz::user::user_delete::delete_user;
 use crate::biz::user::user_info::{get_profile, get_user_workspace_info, update_user};
 use crate::biz::user::user_search::{get_uid_by_email_or_phone, search_users_by_email};
@@ -9,7 +23,6 @@ use crate::biz::user::user_verify::{
   check_email_registered, send_email_change_otp, send_phone_otp, send_phone_reauth_otp,
   verify_and_bind_email, verify_and_bind_phone, verify_phone_reauthentication, verify_token,
 };
-use crate::biz::subscription::ops::check_user_storage_limit;
 use crate::state::AppState;
 use actix_http::StatusCode;
 use actix_multipart::form::bytes::Bytes;
@@ -18,8 +31,14 @@ use actix_web::web::{Data, Json};
 use actix_web::{web, HttpResponse, Scope};
 use actix_web::{HttpRequest, Result};
 use app_error::AppError;
-use database_entity::dto::{AFUserProfile, AFUserWorkspaceInfo, UserImageAssetSource};
+use database_entity::dto::{
+  AFUserProfile, AFUserWorkspaceInfo, CreateUserApiKeyParams, CreatedUserApiKey,
+  DeviceHandoffParams, DeviceHandoffs, ListUserNotificationsQuery, NotificationsRead,
+  PutUserBackupQuery, QuietHours, RegisterPushTokenParams, SetQuietHoursParams, UserApiKeys,
+  UserBackupInfo, UserDevices, UserImageAssetSource, UserNotifications, UserQuietHours,
+};
 use semver::Version;
+use serde_json::json;
 use shared_entity::dto::auth_dto::{
   BindPhoneResponse, CheckEmailParams, DeleteUserQuery, GetUidByEmailOrPhoneQuery,
   GetUidByEmailOrPhoneResponse, SearchUserQuery, SearchUserResponse, SendEmailChangeOtpParams,
@@ -30,19 +49,30 @@ use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 use tracing::event;
 use uuid::Uuid;
-use serde_json::json;
 
 pub fn user_scope() -> Scope {
   web::scope("/api/user")
     .service(web::resource("/verify/{access_token}").route(web::get().to(verify_user_handler)))
     .service(web::resource("/update").route(web::post().to(update_user_handler)))
     .service(web::resource("/send-phone-otp").route(web::post().to(send_phone_otp_handler)))
-    .service(web::resource("/send-phone-reauth-otp").route(web::post().to(send_phone_reauth_otp_handler)))
-    .service(web::resource("/send-email-change-otp").route(web::post().to(send_email_change_otp_handler)))
+    .service(
+      web::resource("/send-phone-reauth-otp")
+        .route(web::post().to(send_phone_reauth_otp_handler)),
+    )
+    .service(
+      web::resource("/send-email-change-otp")
+        .route(web::post().to(send_email_change_otp_handler)),
+    )
     .service(web::resource("/verify-phone").route(web::post().to(verify_and_bind_phone_handler)))
-    .service(web::resource("/verify-phone-reauthentication").route(web::post().to(verify_phone_reauthentication_handler)))
+    .service(
+      web::resource("/verify-phone-reauthentication")
+        .route(web::post().to(verify_phone_reauthentication_handler)),
+    )
     .service(web::resource("/verify-email").route(web::post().to(verify_and_bind_email_handler)))
-    .service(web::resource("/check-email-registered").route(web::post().to(check_email_registered_handler)))
+    .service(
+      web::resource("/check-email-registered")
+        .route(web::post().to(check_email_registered_handler)),
+    )
     .service(web::resource("/profile").route(web::get().to(get_user_profile_handler)))
     .service(web::resource("/workspace").route(web::get().to(get_user_workspace_info_handler)))
     .service(web::resource("/asset/image").route(web::post().to(post_user_image_asset_handler)))
@@ -58,6 +88,51 @@ pub fn user_scope() -> Scope {
         .route(web::get().to(get_notification_preferences_handler))
         .route(web::post().to(post_notification_preferences_handler)),
     )
+    .service(
+      web::resource("/notification-preferences/quiet-hours")
+        .route(web::get().to(list_quiet_hours_handler)),
+    )
+    .service(
+      web::resource("/notification-preferences/quiet-hours/{device_id}")
+        .route(web::put().to(put_quiet_hours_handler))
+        .route(web::delete().to(delete_quiet_hours_handler)),
+    )
+    .service(
+      web::resource("/api-keys")
+        .route(web::get().to(list_user_api_keys_handler))
+        .route(web::post().to(post_user_api_key_handler)),
+    )
+    .service(
+      web::resource("/api-keys/{key_id}")
+        .route(web::delete().to(delete_user_api_key_handler)),
+    )
+    .service(web::resource("/devices").route(web::get().to(list_user_devices_handler)))
+    .service(
+      web::resource("/backup")
+        .app_data(web::PayloadConfig::new(MAX_USER_BACKUP_SIZE + 1))
+        .route(web::get().to(get_user_backup_handler))
+        .route(web::put().to(put_user_backup_handler)),
+    )
+    .service(
+      web::resource("/handoff")
+        .route(web::get().to(list_device_handoffs_handler))
+        .route(web::post().to(post_device_handoff_handler)),
+    )
+    .service(
+      web::resource("/handoff/{handoff_id}/processed")
+        .route(web::post().to(post_device_handoff_processed_handler)),
+    )
+    .service(
+      web::resource("/push-token")
+        .route(web::post().to(post_push_token_handler))
+        .route(web::delete().to(delete_push_token_handler)),
+    )
+    .service(
+      web::resource("/notifications/inbox").route(web::get().to(list_inbox_notifications_handler)),
+    )
+    .service(
+      web::resource("/notifications/read").route(web::post().to(post_notifications_read_handler)),
+    )
     // 诊断接口：查询当前用户的所有通知（含已处理）
     .service(web::resource("/notifications").route(web::get().to(list_user_notifications_handler)))
 }
@@ -103,6 +178,202 @@ async fn get_user_workspace_info_handler(
   Ok(AppResponse::Ok().with_data(info).into())
 }
 
+async fn list_user_api_keys_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<UserApiKeys>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let api_keys = list_user_api_keys(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(api_keys).into())
+}
+
+async fn post_user_api_key_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  data: Json<CreateUserApiKeyParams>,
+) -> Result<JsonAppResponse<CreatedUserApiKey>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let api_key = create_user_api_key(&state.pg_pool, uid, data.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(api_key).into())
+}
+
+async fn delete_user_api_key_handler(
+  uuid: UserUuid,
+  key_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  revoke_user_api_key(&state.pg_pool, uid, &key_id).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn list_user_devices_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<UserDevices>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let devices = list_user_devices(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(devices).into())
+}
+
+async fn put_user_backup_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  query: web::Query<PutUserBackupQuery>,
+  payload: web::Bytes,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<UserBackupInfo>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let device_id = device_id_from_headers(req.headers()).ok();
+  let info = put_user_backup(
+    &state.pg_pool,
+    uid,
+    &payload,
+    device_id,
+    query.expected_version,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(info).into())
+}
+
+/// Returns the backup as is, its version being the ETag of the response. A client that already
+/// has the latest version gets a 304 response by sending it in `If-None-Match`.
+async fn get_user_backup_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<HttpResponse> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let (version, data) = get_user_backup(&state.pg_pool, uid).await?;
+  let etag = format!("\"{}\"", version);
+  let not_modified = req
+    .headers()
+    .get(actix_web::http::header::IF_NONE_MATCH)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+  if not_modified {
+    return Ok(
+      HttpResponse::NotModified()
+        .insert_header((actix_web::http::header::ETAG, etag))
+        .finish(),
+    );
+  }
+  Ok(
+    HttpResponse::Ok()
+      .insert_header((actix_web::http::header::ETAG, etag))
+      .content_type("application/octet-stream")
+      .body(data),
+  )
+}
+
+async fn post_device_handoff_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  data: Json<DeviceHandoffParams>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let source_device_id = device_id_from_headers(req.headers()).ok();
+  send_device_handoff(&state.pg_pool, uid, source_device_id, data.into_inner()).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn list_device_handoffs_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<DeviceHandoffs>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let device_id = device_id_from_headers(req.headers())?;
+  let handoffs = list_pending_device_handoffs(&state.pg_pool, uid, device_id).await?;
+  Ok(AppResponse::Ok().with_data(handoffs).into())
+}
+
+async fn post_device_handoff_processed_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  handoff_id: web::Path<Uuid>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let device_id = device_id_from_headers(req.headers())?;
+  mark_device_handoff_processed(&state.pg_pool, uid, &handoff_id.into_inner(), device_id).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn post_push_token_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  data: Json<RegisterPushTokenParams>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let device_id = device_id_from_headers(req.headers())?;
+  register_push_token(&state.pg_pool, uid, device_id, data.into_inner()).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn delete_push_token_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let device_id = device_id_from_headers(req.headers())?;
+  unregister_push_token(&state.pg_pool, uid, device_id).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn list_inbox_notifications_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  query: web::Query<ListUserNotificationsQuery>,
+) -> Result<JsonAppResponse<UserNotifications>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let notifications = list_inbox_notifications(&state.pg_pool, uid, query.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(notifications).into())
+}
+
+async fn post_notifications_read_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+  data: Json<NotificationsRead>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  mark_notifications_read(&state.pg_pool, uid, data.into_inner()).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn list_quiet_hours_handler(
+  uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<UserQuietHours>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let quiet_hours = list_quiet_hours(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(quiet_hours).into())
+}
+
+async fn put_quiet_hours_handler(
+  uuid: UserUuid,
+  device_id: web::Path<String>,
+  state: Data<AppState>,
+  data: Json<SetQuietHoursParams>,
+) -> Result<JsonAppResponse<QuietHours>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  let quiet_hours = set_quiet_hours(&state.pg_pool, uid, &device_id, data.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(quiet_hours).into())
+}
+
+async fn delete_quiet_hours_handler(
+  uuid: UserUuid,
+  device_id: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&uuid).await?;
+  remove_quiet_hours(&state.pg_pool, uid, &device_id).await?;
+  Ok(AppResponse::Ok().into())
+}
+
 #[tracing::instrument(skip(state, auth, payload), err)]
 async fn update_user_handler(
   auth: Authorization,
@@ -123,7 +394,8 @@ async fn verify_and_bind_phone_handler(
   let user_uuid = auth.uuid()?;
   let params = payload.into_inner();
 
-  let result = verify_and_bind_phone(&user_uuid, &params.phone, &params.otp, state.as_ref()).await?;
+  let result =
+    verify_and_bind_phone(&user_uuid, &params.phone, &params.otp, state.as_ref()).await?;
 
   Ok(AppResponse::Ok().with_data(result).into())
 }
@@ -227,9 +499,12 @@ async fn post_user_image_asset_handler(
   database::user::update_user(
     &state.pg_pool,
     &user_uuid,
-    None, None, None,
+    None,
+    None,
+    None,
     Some(json!({"avatar_file_size": avatar_file_size})),
-  ).await?;
+  )
+  .await?;
   Ok(
     AppResponse::Ok()
       .with_data(UserImageAssetSource { file_id })
@@ -246,7 +521,12 @@ async fn send_phone_otp_handler(
   let params = payload.into_inner();
   let user_uuid = auth.uuid()?;
 
-  check_phone_otp_rate_limit(&params.phone, &mut state.redis_connection_manager.clone()).await?;
+  check_phone_otp_rate_limit(
+    &params.phone,
+    &mut state.redis_connection_manager.clone(),
+    &state.runtime_config.load_full().otp_rate_limit,
+  )
+  .await?;
 
   event!(
     tracing::Level::INFO,
@@ -268,7 +548,12 @@ async fn send_phone_reauth_otp_handler(
   let params = payload.into_inner();
   let _user_uuid = auth.uuid()?;
 
-  check_phone_otp_rate_limit(&params.phone, &mut state.redis_connection_manager.clone()).await?;
+  check_phone_otp_rate_limit(
+    &params.phone,
+    &mut state.redis_connection_manager.clone(),
+    &state.runtime_config.load_full().otp_rate_limit,
+  )
+  .await?;
 
   event!(
     tracing::Level::INFO,
@@ -293,7 +578,12 @@ async fn send_email_change_otp_handler(
   let params = payload.into_inner();
   let _user_uuid = auth.uuid()?;
 
-  check_email_otp_rate_limit(&params.email, &mut state.redis_connection_manager.clone()).await?;
+  check_email_otp_rate_limit(
+    &params.email,
+    &mut state.redis_connection_manager.clone(),
+    &state.runtime_config.load_full().otp_rate_limit,
+  )
+  .await?;
 
   event!(
     tracing::Level::INFO,
@@ -325,14 +615,16 @@ async fn check_email_registered_handler(
 
   let result = check_email_registered(&user_uuid, &params.email, state.as_ref()).await?;
 
-  Ok(AppResponse::Ok()
-    .with_data(CheckEmailRegisteredResponse {
-      email_exists: result.email_exists,
-      is_own_email: result.is_own_email,
-      existing_uid: result.existing_uid,
-      message: result.message,
-    })
-    .into())
+  Ok(
+    AppResponse::Ok()
+      .with_data(CheckEmailRegisteredResponse {
+        email_exists: result.email_exists,
+        is_own_email: result.is_own_email,
+        existing_uid: result.existing_uid,
+        message: result.message,
+      })
+      .into(),
+  )
 }
 
 #[tracing::instrument(skip(state, auth), err)]
@@ -351,13 +643,15 @@ async fn get_notification_preferences_handler(
     .as_ref()
     .and_then(|p| p.metadata.as_ref())
     .and_then(|m| m.get("notification_settings").cloned())
-    .unwrap_or_else(|| json!({
-      "notify_at_me": true,
-      "notify_pending": true,
-      "notify_permission_change": true,
-      "notify_join_team": true,
-      "notify_clip": true
-    }));
+    .unwrap_or_else(|| {
+      json!({
+        "notify_at_me": true,
+        "notify_pending": true,
+        "notify_permission_change": true,
+        "notify_join_team": true,
+        "notify_clip": true
+      })
+    });
 
   Ok(AppResponse::Ok().with_data(notification_settings).into())
 }
@@ -381,18 +675,35 @@ async fn post_notification_preferences_handler(
   let prefs = payload.into_inner();
 
   let mut obj = serde_json::Map::new();
-  if let Some(v) = prefs.notify_at_me { obj.insert("notify_at_me".to_string(), serde_json::json!(v)); }
-  if let Some(v) = prefs.notify_pending { obj.insert("notify_pending".to_string(), serde_json::json!(v)); }
-  if let Some(v) = prefs.notify_permission_change { obj.insert("notify_permission_change".to_string(), serde_json::json!(v)); }
-  if let Some(v) = prefs.notify_join_team { obj.insert("notify_join_team".to_string(), serde_json::json!(v)); }
-  if let Some(v) = prefs.notify_clip { obj.insert("notify_clip".to_string(), serde_json::json!(v)); }
+  if let Some(v) = prefs.notify_at_me {
+    obj.insert("notify_at_me".to_string(), serde_json::json!(v));
+  }
+  if let Some(v) = prefs.notify_pending {
+    obj.insert("notify_pending".to_string(), serde_json::json!(v));
+  }
+  if let Some(v) = prefs.notify_permission_change {
+    obj.insert("notify_permission_change".to_string(), serde_json::json!(v));
+  }
+  if let Some(v) = prefs.notify_join_team {
+    obj.insert("notify_join_team".to_string(), serde_json::json!(v));
+  }
+  if let Some(v) = prefs.notify_clip {
+    obj.insert("notify_clip".to_string(), serde_json::json!(v));
+  }
 
   let metadata = json!({ "notification_settings": serde_json::Value::Object(obj) });
 
   // merge into user metadata
-  database::user::update_user(&state.pg_pool, &user_uuid, None, None, None, Some(metadata.into()))
-    .await
-    .map_err(AppResponseError::from)?;
+  database::user::update_user(
+    &state.pg_pool,
+    &user_uuid,
+    None,
+    None,
+    None,
+    Some(metadata.into()),
+  )
+  .await
+  .map_err(AppResponseError::from)?;
 
   Ok(AppResponse::Ok().into())
 }
@@ -434,14 +745,10 @@ async fn get_uid_by_email_or_phone_handler(
   let identifier = query.identifier.trim();
 
   if identifier.is_empty() {
-    return Err(AppError::InvalidRequest(
-      "identifier parameter is required".to_string(),
-    )
-    .into());
+    return Err(AppError::InvalidRequest("identifier parameter is required".to_string()).into());
   }
 
-  let (uid, identifier_type) =
-    get_uid_by_email_or_phone(&state.pg_pool, identifier).await?;
+  let (uid, identifier_type) = get_uid_by_email_or_phone(&state.pg_pool, identifier).await?;
 
   let response = GetUidByEmailOrPhoneResponse {
     uid,
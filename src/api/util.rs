@@ -88,6 +88,15 @@ pub fn device_id_from_headers(headers: &HeaderMap) -> Result<&str, AppError> {
   )
 }
 
+/// Retrieve the workspace id of a request from its path, given the matched route pattern
+pub fn workspace_id_from_path(pattern: &str, path: &str) -> Option<Uuid> {
+  pattern
+    .split('/')
+    .zip(path.split('/'))
+    .find(|(pattern_segment, _)| *pattern_segment == "{workspace_id}")
+    .and_then(|(_, segment)| Uuid::parse_str(segment).ok())
+}
+
 /// Create new realtime user for requests from appflowy web
 pub fn realtime_user_for_web_request(
   headers: &HeaderMap,
@@ -328,4 +337,20 @@ mod tests {
     // Should return the first matching key's value
     assert_eq!(result.unwrap(), "value1");
   }
+
+  #[test]
+  fn test_workspace_id_from_path() {
+    let workspace_id = Uuid::new_v4();
+    assert_eq!(
+      workspace_id_from_path(
+        "/api/workspace/{workspace_id}/collab/{object_id}",
+        &format!("/api/workspace/{}/collab/{}", workspace_id, Uuid::new_v4()),
+      ),
+      Some(workspace_id)
+    );
+    assert_eq!(
+      workspace_id_from_path("/api/user/profile", "/api/user/profile"),
+      None
+    );
+  }
 }
@@ -0,0 +1,188 @@
+use actix_http::header::{
+  HeaderMap, ALLOW, AUTHORIZATION, CONTENT_TYPE, LAST_MODIFIED, WWW_AUTHENTICATE,
+};
+use actix_http::StatusCode;
+use actix_web::web::Data;
+use actix_web::{web, HttpRequest, HttpResponse, Result, Scope};
+use app_error::AppError;
+use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use database::collab::GetCollabOrigin;
+use database::workspace::select_all_user_workspaces;
+use database_entity::dto::AFRole;
+use uuid::Uuid;
+
+use crate::biz::user::user_api_key::authenticate_api_key;
+use crate::biz::workspace::markdown_export::{markdown_files, render_markdown_documents};
+use crate::biz::workspace::webdav::{
+  encode_webdav_path, http_date, resolve_webdav_path, webdav_children, MultiStatus, WebDavNode,
+};
+use crate::state::AppState;
+
+const WEBDAV_ROOT: &str = "/webdav";
+const ALLOWED_METHODS: &str = "OPTIONS, PROPFIND, GET, HEAD";
+
+/// Read-only WebDAV view of the workspaces of the user: every workspace is a directory, every
+/// document an exported Markdown file. Clients authenticate with an API key, sent as the
+/// password of basic authentication or as a bearer token.
+pub fn webdav_scope() -> Scope {
+  web::scope(WEBDAV_ROOT)
+    .service(web::resource("").route(web::route().to(webdav_handler)))
+    .service(web::resource("/{tail:.*}").route(web::route().to(webdav_handler)))
+}
+
+async fn webdav_handler(req: HttpRequest, state: Data<AppState>) -> Result<HttpResponse> {
+  let method = req.method().as_str();
+  if method == "OPTIONS" {
+    return Ok(
+      HttpResponse::Ok()
+        .insert_header(("DAV", "1"))
+        .insert_header((ALLOW, ALLOWED_METHODS))
+        .finish(),
+    );
+  }
+  if !matches!(method, "PROPFIND" | "GET" | "HEAD") {
+    return Ok(method_not_allowed());
+  }
+  let api_key = match api_key_from_headers(req.headers()) {
+    Some(api_key) => api_key,
+    None => return Ok(unauthorized()),
+  };
+  let (uid, user_uuid) = match authenticate_api_key(&state.pg_pool, &api_key).await {
+    Ok(user) => user,
+    Err(AppError::UserUnAuthorized(_)) => return Ok(unauthorized()),
+    Err(err) => return Err(err.into()),
+  };
+
+  let tail = req.match_info().query("tail").trim_matches('/');
+  let (workspace, path) = tail.split_once('/').unwrap_or((tail, ""));
+  if workspace.is_empty() {
+    if method != "PROPFIND" {
+      return Ok(method_not_allowed());
+    }
+    let mut multi_status = MultiStatus::default();
+    multi_status.add_directory(&format!("{}/", WEBDAV_ROOT), "", 0);
+    if depth(&req) != 0 {
+      for workspace in select_all_user_workspaces(&state.pg_pool, &user_uuid).await? {
+        multi_status.add_directory(
+          &format!("{}/{}/", WEBDAV_ROOT, workspace.workspace_id),
+          workspace.workspace_name.as_deref().unwrap_or_default(),
+          workspace.created_at.map(|t| t.timestamp()).unwrap_or(0),
+        );
+      }
+    }
+    return Ok(multi_status_response(multi_status));
+  }
+
+  let workspace_id = match Uuid::parse_str(workspace) {
+    Ok(workspace_id) => workspace_id,
+    Err(_) => return Ok(HttpResponse::NotFound().finish()),
+  };
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let folder = state.ws_server.get_folder(workspace_id).await?;
+  let files = markdown_files(&folder, &workspace_id, uid)?;
+  drop(folder);
+  let node = match resolve_webdav_path(&files, path) {
+    Some(node) => node,
+    None => return Ok(HttpResponse::NotFound().finish()),
+  };
+
+  if method != "PROPFIND" {
+    // GET and HEAD only make sense for files
+    let file = match node {
+      WebDavNode::File(file) => file,
+      WebDavNode::Directory { .. } => return Ok(method_not_allowed()),
+    };
+    let mut documents = render_markdown_documents(
+      &state.collab_storage,
+      GetCollabOrigin::User { uid },
+      workspace_id,
+      &[file.view_id],
+    )
+    .await?;
+    let markdown = documents.remove(&file.view_id).unwrap_or_default();
+    return Ok(
+      HttpResponse::Ok()
+        .insert_header((CONTENT_TYPE, "text/markdown; charset=utf-8"))
+        .insert_header((LAST_MODIFIED, http_date(file.last_edited_time)))
+        .body(markdown),
+    );
+  }
+
+  let mut listed = vec![node.clone()];
+  if let (WebDavNode::Directory { path, .. }, true) = (&node, depth(&req) != 0) {
+    listed.extend(webdav_children(&files, path));
+  }
+  let workspace_href = format!("{}/{}", WEBDAV_ROOT, workspace_id);
+  let mut multi_status = MultiStatus::default();
+  for node in listed {
+    match node {
+      WebDavNode::Directory {
+        path,
+        last_modified,
+      } => {
+        let href = if path.is_empty() {
+          format!("{}/", workspace_href)
+        } else {
+          format!("{}/{}/", workspace_href, encode_webdav_path(&path))
+        };
+        multi_status.add_directory(&href, display_name(&path), last_modified);
+      },
+      WebDavNode::File(file) => {
+        multi_status.add_file(
+          &format!("{}/{}", workspace_href, encode_webdav_path(&file.path)),
+          display_name(&file.path),
+          file.last_edited_time,
+        );
+      },
+    }
+  }
+  Ok(multi_status_response(multi_status))
+}
+
+/// The API key is accepted as the password of basic authentication, which is what most file
+/// managers offer, or as a bearer token.
+fn api_key_from_headers(headers: &HeaderMap) -> Option<String> {
+  let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+  if let Some(token) = value.strip_prefix("Bearer ") {
+    return Some(token.trim().to_string());
+  }
+  let credentials = STANDARD.decode(value.strip_prefix("Basic ")?.trim()).ok()?;
+  let credentials = String::from_utf8(credentials).ok()?;
+  let (_, password) = credentials.split_once(':')?;
+  Some(password.to_string())
+}
+
+/// Depth of a PROPFIND request. `infinity` is answered as a depth of one.
+fn depth(req: &HttpRequest) -> u8 {
+  match req.headers().get("Depth").and_then(|v| v.to_str().ok()) {
+    Some("0") => 0,
+    _ => 1,
+  }
+}
+
+fn display_name(path: &str) -> &str {
+  path.rsplit('/').next().unwrap_or(path)
+}
+
+fn multi_status_response(multi_status: MultiStatus) -> HttpResponse {
+  HttpResponse::build(StatusCode::MULTI_STATUS)
+    .insert_header((CONTENT_TYPE, "application/xml; charset=utf-8"))
+    .body(multi_status.into_xml())
+}
+
+fn method_not_allowed() -> HttpResponse {
+  HttpResponse::MethodNotAllowed()
+    .insert_header((ALLOW, ALLOWED_METHODS))
+    .finish()
+}
+
+fn unauthorized() -> HttpResponse {
+  HttpResponse::Unauthorized()
+    .insert_header((WWW_AUTHENTICATE, "Basic realm=\"PonyNotes\""))
+    .finish()
+}
@@ -3,11 +3,13 @@ use crate::api::util::{compress_type_from_header_value, device_id_from_headers};
 use crate::api::ws::RealtimeServerAddr;
 use crate::biz;
 use crate::biz::authentication::jwt::{Authorization, OptionalUserUuid, UserUuid};
+use crate::biz::collab::conflict::get_collab_conflicts;
 use crate::biz::collab::database::check_if_row_document_collab_exists;
 use crate::biz::collab::ops::{
   get_user_favorite_folder_views, get_user_recent_folder_views, get_user_trash_folder_views,
 };
 use crate::biz::collab::utils::{collab_from_doc_state, DUMMY_UID};
+use crate::biz::notification::comment_reply::post_comment_reply_from_email;
 use crate::biz::workspace;
 use crate::biz::workspace::duplicate::duplicate_view_tree_and_collab;
 use crate::biz::workspace::invite::{
@@ -25,11 +27,27 @@ use crate::biz::workspace::ops::{
 use crate::biz::workspace::page_view::{
   add_recent_pages, append_block_at_the_end_of_page, create_database_view, create_folder_view,
   create_orphaned_view, create_page, create_space, delete_all_pages_from_trash, delete_trash,
-  favorite_page, get_page_view_collab, move_page, move_page_to_trash, publish_page,
-  reorder_favorite_page, restore_all_pages_from_trash, restore_page_from_trash, unpublish_page,
-  update_page, update_page_collab_data, update_page_extra, update_page_icon, update_page_name,
-  update_space,
+  favorite_page, get_page_view_collab, get_page_view_collabs, get_page_view_document_chunk,
+  get_page_view_document_outline, move_page, move_page_to_trash, publish_page,
+  reorder_favorite_page, reorder_page_views, restore_all_pages_from_trash, restore_page_from_trash,
+  unpublish_page, update_page, update_page_collab_data, update_page_extra, update_page_icon,
+  update_page_name, update_space,
 };
+use crate::biz::workspace::collab_permission_sync::get_collab_permission_sync;
+use crate::biz::workspace::member_export::export_workspace_members_csv;
+use crate::biz::workspace::member_field::{
+  create_workspace_member_field, delete_workspace_member_field_by_id, list_workspace_member_fields,
+  update_workspace_member_field_by_id,
+};
+use crate::biz::workspace::member_status::{
+  attach_statuses_to_members, set_workspace_member_status,
+};
+use crate::biz::workspace::page_activity::{get_page_activity_feed, record_page_activity};
+use crate::biz::workspace::page_reaction::{
+  add_page_reaction, get_page_reactions, remove_page_reaction,
+};
+use crate::biz::workspace::page_watch::{get_page_watch_status, unwatch_page, watch_page};
+use crate::biz::workspace::publish::check_user_can_publish;
 use crate::biz::workspace::publish::get_workspace_default_publish_view_info_meta;
 use crate::biz::workspace::publish::list_collab_publish_info;
 use database::publish::{
@@ -39,11 +57,26 @@ use database::publish::{
 use crate::biz::workspace::quick_note::{
   create_quick_note, delete_quick_note, list_quick_notes, update_quick_note,
 };
+use crate::biz::workspace::scheduled_export::{
+  create_scheduled_export, delete_workspace_scheduled_export, list_scheduled_export_runs,
+  list_scheduled_exports, run_scheduled_export_now, update_workspace_scheduled_export,
+  ScheduledExportContext,
+};
+use crate::biz::workspace::view_slug::{
+  get_view_slug, remove_view_slug, resolve_view_slug, set_view_slug,
+};
+use crate::biz::workspace::snippet::{
+  create_workspace_snippet, delete_workspace_snippet_by_id, expand_workspace_snippet,
+  list_workspace_snippets, update_workspace_snippet_by_id,
+};
 use crate::domain::compression::{
   blocking_decompress, decompress, CompressionType, X_COMPRESSION_TYPE,
 };
+use crate::middleware::deadline::apply_statement_timeout;
 use crate::state::AppState;
 use access_control::act::Action;
+use access_control::collab::RealtimeAccessControl;
+use actix_web::middleware::DefaultHeaders;
 use actix_web::web::{Bytes, Path, Payload};
 use actix_web::web::{Data, Json, PayloadConfig};
 use actix_web::{web, HttpResponse, ResponseError, Scope};
@@ -53,16 +86,19 @@ use app_error::{AppError, ErrorCode};
 use appflowy_collaborate::actix_ws::entities::{
   ClientGenerateEmbeddingMessage, ClientHttpStreamMessage, ClientHttpUpdateMessage,
 };
+use appflowy_collaborate::collab::rejected_update::CONFLICT_REASON_PERMISSION_DENIED;
+use appflowy_collaborate::collab::structure_limit::document_block_count;
 use appflowy_collaborate::ws2::{
-  PermissionType, PermissionUpdate, RefreshWorkspaceUserPermissions, UpdateUserPermissions,
-  WorkspaceCollabInstanceCache,
+  PermissionType, PermissionUpdate, UpdateUserPermissions, WorkspaceCollabInstanceCache,
 };
+use database::pg_row::AFEgressSource;
 use database::publish::select_all_published_collab_info_global;
+use database::publish::select_workspace_id_for_publish_namespace;
 
 use bytes::BytesMut;
 use chrono::{DateTime, Duration, Utc};
 use collab::core::collab::{default_client_id, CollabOptions, DataSource};
-use collab::core::origin::CollabOrigin;
+use collab::core::origin::{CollabClient, CollabOrigin};
 use collab::entity::EncodedCollab;
 use collab::preclude::Collab;
 use collab_database::entity::FieldType;
@@ -91,11 +127,19 @@ use crate::biz::workspace::collab_member::{
 };
 use crate::biz::workspace::ops::get_collab_owner;
 use database::pg_row::AFCollabMemberInvite;
+use database::blob_integrity::select_blob_integrity_report;
+use database::publish_integrity::select_publish_integrity_report;
+use database::dead_reference::select_dead_reference_report;
+use database::workspace_health::select_workspace_health_report;
 use database::subscription::get_user_total_usage_bytes;
 use database::workspace::{select_collab_owner, update_collab_member_permission};
 use semver::Version;
 use sha2::{Digest, Sha256};
-use shared_entity::dto::publish_dto::DuplicatePublishedPageResponse;
+use shared_entity::dto::publish_dto::{
+  DuplicatePublishedPageResponse, PublishLanguageQuery, PublishRowFilter, PublishTheme,
+  PublishedDatabaseViews, PublishedViewVariant, SetPublishedViewVariant,
+  UpdatePublishedDatabaseViews,
+};
 use shared_entity::dto::workspace_dto::{
   AllPublishedCollabItem, ListAllPublishedCollabResponse, ReceivePublishedCollabRequest,
   ReceivePublishedCollabResponse,
@@ -105,10 +149,11 @@ use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 use sqlx::types::uuid;
 use std::io::Cursor;
+use std::ops::DerefMut;
 use std::time::Instant;
 use tokio_stream::StreamExt;
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{error, event, instrument, trace};
+use tracing::{error, event, instrument, trace, warn};
 use uuid::Uuid;
 use validator::Validate;
 use workspace_template::document::parser::SerdeBlock;
@@ -124,6 +169,8 @@ pub const V1_COLLAB_PATTERN: &str = "/api/workspace/v1/{workspace_id}/collab/{ob
 pub const WORKSPACE_PUBLISH_PATTERN: &str = "/api/workspace/{workspace_id}/publish";
 pub const WORKSPACE_PUBLISH_NAMESPACE_PATTERN: &str =
   "/api/workspace/{workspace_id}/publish-namespace";
+/// Hex HMAC-SHA256 of the body of an inbound comment reply email, under the webhook secret.
+const COMMENT_REPLY_SIGNATURE_HEADER: &str = "X-Comment-Reply-Signature";
 
 pub fn workspace_scope() -> Scope {
   web::scope("/api/workspace")
@@ -165,10 +212,33 @@ pub fn workspace_scope() -> Scope {
                 .route(web::put().to(update_workspace_member_handler))
                 .route(web::delete().to(remove_workspace_member_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/member/export")
+                .route(web::get().to(export_workspace_members_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/mentionable-person")
                 .route(web::get().to(list_workspace_mentionable_person_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/member-status")
+                .route(web::put().to(put_workspace_member_status_handler))
+                .route(web::delete().to(delete_workspace_member_status_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/directory")
+                .route(web::get().to(get_workspace_directory_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/member-field")
+                .route(web::get().to(list_workspace_member_fields_handler))
+                .route(web::post().to(post_workspace_member_field_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/member-field/{field_id}")
+                .route(web::put().to(update_workspace_member_field_handler))
+                .route(web::delete().to(delete_workspace_member_field_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/mentionable-person/{contact_id}")
                 .route(web::get().to(get_workspace_mentionable_person_handler)),
@@ -186,6 +256,15 @@ pub fn workspace_scope() -> Scope {
             web::resource("v1/{workspace_id}/member/user/{user_id}")
                 .route(web::get().to(get_workspace_member_v1_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/member/user/{user_id}/permission-sync")
+                .route(web::get().to(get_member_collab_permission_sync_handler)),
+        )
+        // Registered before `/collab/{object_id}`, which would match this path as well
+        .service(
+            web::resource("/{workspace_id}/collab/access-check")
+                .route(web::post().to(collab_access_check_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/collab/{object_id}")
                 .app_data(
@@ -200,6 +279,10 @@ pub fn workspace_scope() -> Scope {
             web::resource("/{workspace_id}/collab/{object_id}/members")
                 .route(web::get().to(get_collab_members_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/collab/{object_id}/conflicts")
+                .route(web::get().to(get_collab_conflicts_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/collab/{object_id}/permission")
                 .route(web::get().to(get_current_collab_permission_handler)),
@@ -253,6 +336,23 @@ pub fn workspace_scope() -> Scope {
         .service(
             web::resource("/{workspace_id}/space/{view_id}").route(web::patch().to(update_space_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/spaces/{space_id}/export")
+                .route(web::post().to(export_space_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/spaces/{space_id}/archive")
+                .route(web::post().to(archive_space_handler))
+                .route(web::delete().to(unarchive_space_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/archived-spaces")
+                .route(web::get().to(list_archived_spaces_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/merge")
+                .route(web::post().to(merge_workspace_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/spaces/{space_id}/join-requests")
                 .route(web::post().to(post_join_request_handler))
@@ -269,11 +369,52 @@ pub fn workspace_scope() -> Scope {
         .service(
             web::resource("/{workspace_id}/page-view").route(web::post().to(post_page_view_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/page-view/reorder")
+                .route(web::post().to(reorder_page_views_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/page-view/{view_id}")
                 .route(web::get().to(get_page_view_handler))
                 .route(web::patch().to(update_page_view_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/page-views/batch")
+                .route(web::post().to(batch_get_page_view_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/watch")
+                .route(web::get().to(get_page_watch_handler))
+                .route(web::put().to(watch_page_handler))
+                .route(web::delete().to(unwatch_page_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/activity")
+                .route(web::get().to(get_page_activity_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/chunk")
+                .route(web::get().to(get_page_view_chunk_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/outline")
+                .route(web::get().to(get_page_view_outline_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/publish-theme")
+                .route(web::get().to(get_published_view_theme_handler))
+                .route(web::put().to(put_published_view_theme_handler))
+                .route(web::delete().to(delete_published_view_theme_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/publish-variants")
+                .route(web::get().to(list_published_view_variants_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/publish-variants/{lang}")
+                .route(web::put().to(put_published_view_variant_handler))
+                .route(web::delete().to(delete_published_view_variant_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/page-view/{view_id}/mentionable-person-with-access")
                 .route(web::get().to(list_page_mentionable_person_with_access_handler))
@@ -310,6 +451,22 @@ pub fn workspace_scope() -> Scope {
             web::resource("/{workspace_id}/page-view/{view_id}/move")
                 .route(web::post().to(move_page_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/slug")
+                .route(web::get().to(get_view_slug_handler))
+                .route(web::put().to(put_view_slug_handler))
+                .route(web::delete().to(delete_view_slug_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/page-view/{view_id}/reaction")
+                .route(web::get().to(get_page_reactions_handler))
+                .route(web::post().to(post_page_reaction_handler))
+                .route(web::delete().to(delete_page_reaction_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/slug/{slug}")
+                .route(web::get().to(resolve_view_slug_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/page-view/{view_id}/reorder-favorite")
                 .route(web::post().to(reorder_favorite_page_handler)),
@@ -365,6 +522,30 @@ pub fn workspace_scope() -> Scope {
             web::resource("/{workspace_id}/usage-and-limit")
                 .route(web::get().to(get_workspace_usage_and_limit_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/blob-integrity")
+                .route(web::get().to(get_blob_integrity_report_handler))
+                .route(web::post().to(scan_blob_integrity_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/publish-integrity")
+                .route(web::get().to(get_publish_integrity_report_handler))
+                .route(web::post().to(scan_publish_integrity_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/dead-references")
+                .route(web::get().to(get_dead_reference_report_handler))
+                .route(web::post().to(scan_dead_references_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/health")
+                .route(web::get().to(get_workspace_health_handler))
+                .route(web::post().to(compute_workspace_health_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/blob-tiering")
+                .route(web::get().to(get_blob_tiering_summary_handler)),
+        )
         // 接收发布的文档 API（静态路径必须在动态路径 /published/{publish_namespace} 之前注册，避免 405 冲突）
         .service(
             web::resource("/published/receive")
@@ -381,12 +562,23 @@ pub fn workspace_scope() -> Scope {
         )
         .service(
             web::resource("/v1/published/{publish_namespace}/{publish_name}")
+                .wrap(vary_on_accept_language())
                 .route(web::get().to(get_v1_published_collab_handler)),
         )
         .service(
             web::resource("/published/{publish_namespace}/{publish_name}/blob")
+                .wrap(vary_on_accept_language())
                 .route(web::get().to(get_published_collab_blob_handler)),
         )
+        .service(
+            web::resource("/published/{publish_namespace}/{publish_name}/theme")
+                .route(web::get().to(get_published_collab_theme_handler)),
+        )
+        .service(
+            web::resource("/published/{publish_namespace}/{publish_name}/head")
+                .wrap(vary_on_accept_language())
+                .route(web::get().to(get_published_collab_head_handler)),
+        )
         .service(
             web::resource("{workspace_id}/published-duplicate")
                 .route(web::post().to(post_published_duplicate_handler)),
@@ -400,6 +592,10 @@ pub fn workspace_scope() -> Scope {
             web::resource("/published-info/all")
                 .route(web::get().to(list_all_published_collab_info_handler)),
         )
+        .service(
+            web::resource("/published-info/comment-reply-mail")
+                .route(web::post().to(post_comment_reply_mail_handler)),
+        )
         .service(
             // deprecated since 0.7.4
             web::resource("/published-info/{view_id}")
@@ -426,6 +622,12 @@ pub fn workspace_scope() -> Scope {
                 .route(web::put().to(put_publish_namespace_handler))
                 .route(web::get().to(get_publish_namespace_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/publish-namespace/{namespace}/theme")
+                .route(web::get().to(get_publish_namespace_theme_handler))
+                .route(web::put().to(put_publish_namespace_theme_handler))
+                .route(web::delete().to(delete_publish_namespace_theme_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/publish-default")
                 .route(web::put().to(put_workspace_default_published_view_handler))
@@ -438,9 +640,23 @@ pub fn workspace_scope() -> Scope {
                 .route(web::delete().to(delete_published_collabs_handler))
                 .route(web::patch().to(patch_published_collabs_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/publish/{view_id}/database-views")
+                .route(web::get().to(get_published_database_views_handler))
+                .route(web::patch().to(patch_published_database_views_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/publish/{view_id}/row-filter")
+                .route(web::get().to(get_published_row_filter_handler))
+                .route(web::delete().to(delete_published_row_filter_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/folder").route(web::get().to(get_workspace_folder_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/folder/forest")
+                .route(web::post().to(get_workspace_folder_forest_handler)),
+        )
         .service(web::resource("/{workspace_id}/recent").route(web::get().to(get_recent_views_handler)))
         .service(
             web::resource("/{workspace_id}/favorite").route(web::get().to(get_favorite_views_handler)),
@@ -484,6 +700,10 @@ pub fn workspace_scope() -> Scope {
             web::resource("/{workspace_id}/database/{database_id}/row/detail")
                 .route(web::get().to(list_database_row_details_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/database/{database_id}/row/{row_id}")
+                .route(web::get().to(get_database_row_detail_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/quick-note")
                 .route(web::get().to(list_quick_notes_handler))
@@ -494,6 +714,38 @@ pub fn workspace_scope() -> Scope {
                 .route(web::put().to(update_quick_note_handler))
                 .route(web::delete().to(delete_quick_note_handler)),
         )
+        .service(
+            web::resource("/{workspace_id}/snippets")
+                .route(web::get().to(list_workspace_snippets_handler))
+                .route(web::post().to(post_workspace_snippet_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/snippets/expand")
+                .route(web::post().to(expand_workspace_snippet_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/snippets/{snippet_id}")
+                .route(web::put().to(update_workspace_snippet_handler))
+                .route(web::delete().to(delete_workspace_snippet_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/scheduled-exports")
+                .route(web::get().to(list_scheduled_exports_handler))
+                .route(web::post().to(post_scheduled_export_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/scheduled-exports/{export_id}")
+                .route(web::put().to(update_scheduled_export_handler))
+                .route(web::delete().to(delete_scheduled_export_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/scheduled-exports/{export_id}/runs")
+                .route(web::get().to(list_scheduled_export_runs_handler)),
+        )
+        .service(
+            web::resource("/{workspace_id}/scheduled-exports/{export_id}/run")
+                .route(web::post().to(run_scheduled_export_handler)),
+        )
         .service(
             web::resource("/{workspace_id}/invite-code")
                 .route(web::get().to(get_workspace_invite_code_handler))
@@ -662,13 +914,11 @@ async fn post_workspace_invite_handler(
   let invitations = payload.into_inner().into_vec();
 
   workspace::ops::invite_workspace_members(
-    &state.mailer,
     &state.pg_pool,
-    &state.workspace_access_control,
+    &state.workspace_events,
     &user_uuid,
     &workspace_id,
     invitations,
-    &state.config.appflowy_web_url,
   )
   .await?;
   Ok(AppResponse::Ok().into())
@@ -708,7 +958,7 @@ async fn post_accept_workspace_invite_handler(
   let invite_id = invite_id.into_inner();
   workspace::ops::accept_workspace_invite(
     &state.pg_pool,
-    state.workspace_access_control.clone(),
+    &state.workspace_events,
     user_uid,
     &user_uuid,
     &invite_id,
@@ -769,6 +1019,13 @@ async fn post_workspace_settings_handler(
     .workspace_access_control
     .enforce_action(&uid, &workspace_id, Action::Write)
     .await?;
+  // Who may publish is decided by the owners only
+  if data.publish_permission.is_some() || data.publish_allowlist.is_some() {
+    state
+      .workspace_access_control
+      .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+      .await?;
+  }
   let settings =
     workspace::ops::update_workspace_settings(&state.pg_pool, &workspace_id, data).await?;
   Ok(AppResponse::Ok().with_data(settings).into())
@@ -790,7 +1047,7 @@ async fn get_workspace_members_handler(
     .await?;
   let requester_member_info =
     workspace::ops::get_workspace_member(uid, &state.pg_pool, &workspace_id).await?;
-  let members: Vec<AFWorkspaceMember> = if requester_member_info.role == AFRole::Guest {
+  let mut members: Vec<AFWorkspaceMember> = if requester_member_info.role == AFRole::Guest {
     // Guest 只能看到自己和 owner
     let owner = get_workspace_owner(&state.pg_pool, &workspace_id).await?;
     vec![requester_member_info.into(), owner.into()]
@@ -809,10 +1066,62 @@ async fn get_workspace_members_handler(
       .map(|member| member.into())
       .collect()
   };
+  attach_statuses_to_members(&state.pg_pool, &workspace_id, &mut members).await?;
 
   Ok(AppResponse::Ok().with_data(members).into())
 }
 
+/// Members and pending invitations of the workspace as CSV, for access reviews by the owner.
+async fn export_workspace_members_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/csv; charset=utf-8")
+      .insert_header((
+        actix_web::http::header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"members-{}.csv\"", workspace_id),
+      ))
+      .streaming(export_workspace_members_csv(
+        state.pg_pool.clone(),
+        workspace_id,
+      )),
+  )
+}
+
+#[instrument(skip_all, err)]
+async fn get_collab_conflicts_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+) -> Result<JsonAppResponse<CollabConflicts>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let (workspace_id, object_id) = path.into_inner();
+  let has_workspace_access = state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await
+    .is_ok();
+  let has_collab_access = state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await
+    .is_ok();
+  if !has_workspace_access && !has_collab_access {
+    return Err(AppError::NotEnoughPermissions.into());
+  }
+  let conflicts = get_collab_conflicts(&state.pg_pool, &workspace_id, &object_id, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(conflicts)))
+}
+
 #[instrument(skip_all, err)]
 async fn get_collab_members_handler(
   user_uuid: UserUuid,
@@ -1035,7 +1344,7 @@ async fn remove_workspace_member_handler(
     &state.pg_pool,
     &workspace_id,
     &member_emails,
-    state.workspace_access_control.clone(),
+    &state.workspace_events,
     Some(uid),
   )
   .await?;
@@ -1138,6 +1447,132 @@ async fn get_workspace_mentionable_person_handler(
   Ok(AppResponse::Ok().with_data(person).into())
 }
 
+async fn get_workspace_directory_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+  query: web::Query<QueryWorkspaceDirectory>,
+) -> Result<JsonAppResponse<WorkspaceDirectory>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  // Unlike the mentionable persons, the directory exposes the profiles of all members, so
+  // guests can not browse it
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let directory =
+    workspace::ops::get_workspace_directory(&state.pg_pool, &workspace_id, query.into_inner())
+      .await?;
+  Ok(AppResponse::Ok().with_data(directory).into())
+}
+
+async fn list_workspace_member_fields_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+) -> Result<JsonAppResponse<WorkspaceMemberFields>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let fields = list_workspace_member_fields(&state.pg_pool, &workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(fields).into())
+}
+
+async fn post_workspace_member_field_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<Uuid>,
+  payload: Json<CreateWorkspaceMemberFieldParams>,
+) -> Result<JsonAppResponse<WorkspaceMemberField>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let field =
+    create_workspace_member_field(&state.pg_pool, &workspace_id, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(field).into())
+}
+
+async fn update_workspace_member_field_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateWorkspaceMemberFieldParams>,
+) -> Result<JsonAppResponse<WorkspaceMemberField>> {
+  let (workspace_id, field_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let field = update_workspace_member_field_by_id(
+    &state.pg_pool,
+    &workspace_id,
+    &field_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(field).into())
+}
+
+async fn delete_workspace_member_field_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, Uuid)>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, field_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  delete_workspace_member_field_by_id(&state.pg_pool, &workspace_id, &field_id).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn put_workspace_member_status_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  payload: Json<WorkspaceMemberStatus>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  set_workspace_member_status(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    Some(payload.into_inner()),
+  )
+  .await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn delete_workspace_member_status_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let workspace_id = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  set_workspace_member_status(&state.pg_pool, &workspace_id, uid, None).await?;
+  Ok(AppResponse::Ok().into())
+}
+
 async fn put_workspace_member_profile_handler(
   user_uuid: UserUuid,
   path: web::Path<Uuid>,
@@ -1179,7 +1614,13 @@ async fn get_workspace_member_handler(
         ),
       )
     })?;
-  let member: AFWorkspaceMember = member_row.into();
+  let mut member: AFWorkspaceMember = member_row.into();
+  attach_statuses_to_members(
+    &state.pg_pool,
+    &workspace_id,
+    std::slice::from_mut(&mut member),
+  )
+  .await?;
 
   Ok(AppResponse::Ok().with_data(member).into())
 }
@@ -1210,7 +1651,13 @@ async fn get_workspace_member_v1_handler(
           ),
         )
       })?;
-  let member: AFWorkspaceMember = member_row.into();
+  let mut member: AFWorkspaceMember = member_row.into();
+  attach_statuses_to_members(
+    &state.pg_pool,
+    &workspace_id,
+    std::slice::from_mut(&mut member),
+  )
+  .await?;
 
   Ok(AppResponse::Ok().with_data(member).into())
 }
@@ -1243,7 +1690,7 @@ async fn leave_workspace_handler(
     &state.pg_pool,
     &workspace_id,
     &user_uuid,
-    state.workspace_access_control.clone(),
+    &state.workspace_events,
   )
   .await?;
   Ok(AppResponse::Ok().into())
@@ -1271,19 +1718,31 @@ async fn update_workspace_member_handler(
     &state.pg_pool,
     &workspace_id,
     &changeset,
-    state.workspace_access_control.clone(),
+    &state.workspace_events,
     uid,
   )
   .await?;
 
-  state.ws_server.do_send(RefreshWorkspaceUserPermissions {
-    workspace_id,
-    uid: changeset.uid,
-  });
-
   Ok(AppResponse::Ok().into())
 }
 
+/// Progress of the recomputation of the collab-level permissions of the member after their
+/// latest role change.
+async fn get_member_collab_permission_sync_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, i64)>,
+) -> Result<JsonAppResponse<CollabPermissionSync>> {
+  let (workspace_id, member_uid) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let sync = get_collab_permission_sync(&state.pg_pool, &workspace_id, member_uid).await?;
+  Ok(AppResponse::Ok().with_data(sync).into())
+}
+
 #[instrument(skip(state, payload))]
 async fn create_collab_handler(
   user_uuid: UserUuid,
@@ -1356,6 +1815,15 @@ async fn create_collab_handler(
     );
   }
 
+  if params.collab_type == CollabType::Document {
+    let block_count = document_block_count(&collab.data, &collab.transact());
+    state
+      .config
+      .collab
+      .structure_limits()
+      .check_document_blocks(0, block_count)?;
+  }
+
   if state
     .indexer_scheduler
     .can_index_workspace(&workspace_id)
@@ -1380,6 +1848,7 @@ async fn create_collab_handler(
     .await
     .context("acquire transaction to upsert collab")
     .map_err(AppError::from)?;
+  apply_statement_timeout(transaction.deref_mut()).await?;
   let start = Instant::now();
 
   let action = format!("Create new collab: {}", params);
@@ -1876,7 +2345,29 @@ async fn move_page_handler(
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn reorder_favorite_page_handler(
+async fn reorder_page_views_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<ReorderPageViewsParams>,
+  state: Data<AppState>,
+
+  req: HttpRequest,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_uuid = workspace_id.into_inner();
+  let user = realtime_user_for_web_request(req.headers(), uid)?;
+  reorder_page_views(
+    &state,
+    user,
+    workspace_uuid,
+    &payload.parent_view_id,
+    &payload.view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn reorder_favorite_page_handler(
   user_uuid: UserUuid,
   path: web::Path<(Uuid, String)>,
   payload: Json<ReorderFavoritePageParams>,
@@ -2033,11 +2524,13 @@ async fn publish_page_handler(
     .workspace_access_control
     .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
     .await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
   let PublishPageParams {
     publish_name,
     visible_database_view_ids,
     comments_enabled,
     duplicate_enabled,
+    row_filter,
   } = payload.into_inner();
   publish_page(
     &state,
@@ -2049,6 +2542,7 @@ async fn publish_page_handler(
     publish_name,
     comments_enabled.unwrap_or(true),
     duplicate_enabled.unwrap_or(true),
+    row_filter,
   )
   .await?;
   Ok(Json(AppResponse::Ok()))
@@ -2069,6 +2563,7 @@ async fn unpublish_page_handler(
     .workspace_access_control
     .enforce_role_weak(&uid, &workspace_uuid, AFRole::Member)
     .await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_uuid).await?;
   unpublish_page(
     state.published_collab_store.as_ref(),
     workspace_uuid,
@@ -2076,6 +2571,15 @@ async fn unpublish_page_handler(
     view_uuid,
   )
   .await?;
+  record_page_activity(
+    &state.pg_pool,
+    &workspace_uuid,
+    &view_uuid,
+    uid,
+    PageActivityKind::Unpublished,
+    serde_json::json!({}),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -2205,7 +2709,7 @@ async fn get_page_view_handler(
     .await
     .map_err(AppResponseError::from)?;
 
-  let page_collab = get_page_view_collab(
+  let mut page_collab = get_page_view_collab(
     &state.pg_pool,
     &state.collab_storage,
     &state.ws_server,
@@ -2214,9 +2718,265 @@ async fn get_page_view_handler(
     view_id,
   )
   .await?;
+  // Reactions are not essential to the page, it is still returned when they can't be read
+  page_collab.view.reactions =
+    match get_page_reactions(&state.pg_pool, &workspace_uuid, &view_id, uid).await {
+      Ok(reactions) => reactions,
+      Err(err) => {
+        warn!("failed to get the reactions of page {}: {}", view_id, err);
+        vec![]
+      },
+    };
   Ok(Json(AppResponse::Ok().with_data(page_collab)))
 }
 
+async fn batch_get_page_view_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<BatchGetPageViewParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PageViewBatchItem>>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+
+  let items = get_page_view_collabs(
+    &state.pg_pool,
+    &state.collab_storage,
+    &state.ws_server,
+    uid,
+    workspace_id,
+    payload.into_inner().view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(items)))
+}
+
+async fn get_page_watch_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageWatchStatus>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  let status = get_page_watch_status(&state.pg_pool, &view_id, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(status)))
+}
+
+async fn watch_page_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageWatchStatus>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &view_id, Action::Read)
+    .await?;
+  let status = watch_page(&state.pg_pool, &workspace_id, &view_id, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(status)))
+}
+
+async fn unwatch_page_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageWatchStatus>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  let status = unwatch_page(&state.pg_pool, &view_id, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(status)))
+}
+
+async fn get_page_activity_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  query: web::Query<QueryPageActivity>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageActivityFeed>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &view_id, Action::Read)
+    .await?;
+  let feed =
+    get_page_activity_feed(&state.pg_pool, &workspace_id, &view_id, query.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(feed)))
+}
+
+async fn get_page_view_chunk_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  query: web::Query<QueryDocumentChunk>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DocumentChunk>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  let QueryDocumentChunk { offset, limit } = query.into_inner();
+  let chunk = get_page_view_document_chunk(
+    &state.collab_storage,
+    uid,
+    workspace_uuid,
+    view_id,
+    offset,
+    limit,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(chunk)))
+}
+
+async fn get_page_view_outline_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DocumentOutline>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  let outline =
+    get_page_view_document_outline(&state.collab_storage, uid, workspace_uuid, view_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(outline)))
+}
+
+async fn get_published_view_theme_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishTheme>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let theme = biz::workspace::publish_theme::get_published_view_theme(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(theme)))
+}
+
+async fn put_published_view_theme_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<PublishTheme>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishTheme>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let theme = biz::workspace::publish_theme::set_published_view_theme(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(theme)))
+}
+
+async fn delete_published_view_theme_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  biz::workspace::publish_theme::remove_published_view_theme(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_published_view_variants_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedViewVariant>>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let variants = biz::workspace::publish_variant::list_published_view_variants(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(variants)))
+}
+
+async fn put_published_view_variant_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, String)>,
+  payload: Json<SetPublishedViewVariant>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedViewVariant>>> {
+  let (workspace_id, view_id, lang) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
+  let variant = biz::workspace::publish_variant::set_published_view_variant(
+    &state.pg_pool,
+    &user_uuid,
+    uid,
+    &workspace_id,
+    &view_id,
+    &lang,
+    &payload.view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(variant)))
+}
+
+async fn delete_published_view_variant_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id, lang) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
+  biz::workspace::publish_variant::remove_published_view_variant(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+    &lang,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn favorite_page_view_handler(
   user_uuid: UserUuid,
   path: web::Path<(Uuid, String)>,
@@ -2424,6 +3184,68 @@ async fn put_publish_namespace_handler(
   Ok(Json(AppResponse::Ok()))
 }
 
+async fn get_publish_namespace_theme_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishTheme>>> {
+  let (workspace_id, namespace) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let theme = biz::workspace::publish_theme::get_publish_namespace_theme(
+    &state.pg_pool,
+    &workspace_id,
+    &namespace,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(theme)))
+}
+
+async fn put_publish_namespace_theme_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  payload: Json<PublishTheme>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishTheme>>> {
+  let (workspace_id, namespace) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let theme = biz::workspace::publish_theme::set_publish_namespace_theme(
+    &state.pg_pool,
+    &workspace_id,
+    &namespace,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(theme)))
+}
+
+async fn delete_publish_namespace_theme_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, namespace) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  biz::workspace::publish_theme::remove_publish_namespace_theme(
+    &state.pg_pool,
+    &workspace_id,
+    &namespace,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn get_publish_namespace_handler(
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
@@ -2448,9 +3270,14 @@ async fn get_default_published_collab_info_meta_handler(
 
 async fn get_v1_published_collab_handler(
   path_param: web::Path<(String, String)>,
+  query: web::Query<PublishLanguageQuery>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<Json<AppResponse<serde_json::Value>>> {
   let (workspace_namespace, publish_name) = path_param.into_inner();
+  let publish_name =
+    negotiate_published_language(&state, &workspace_namespace, &publish_name, &query, &req)
+      .await?;
   let metadata = state
     .published_collab_store
     .get_collab_metadata(&workspace_namespace, &publish_name)
@@ -2460,24 +3287,106 @@ async fn get_v1_published_collab_handler(
 
 async fn get_published_collab_blob_handler(
   path_param: web::Path<(String, String)>,
+  query: web::Query<PublishLanguageQuery>,
   state: Data<AppState>,
+  req: HttpRequest,
 ) -> Result<Vec<u8>> {
   let (publish_namespace, publish_name) = path_param.into_inner();
+  let publish_name =
+    negotiate_published_language(&state, &publish_namespace, &publish_name, &query, &req).await?;
+  let workspace_id =
+    select_workspace_id_for_publish_namespace(&state.pg_pool, &publish_namespace).await?;
+  state.egress_meter.check_limit(&workspace_id)?;
   let collab_data = state
     .published_collab_store
     .get_collab_blob_by_publish_namespace(&publish_namespace, &publish_name)
     .await?;
+  state
+    .egress_meter
+    .record(workspace_id, AFEgressSource::Publish, collab_data.len());
+  state
+    .publish_view_counter
+    .record(workspace_id, &publish_name);
   Ok(collab_data)
 }
 
-async fn post_published_duplicate_handler(
-  user_uuid: UserUuid,
-  workspace_id: web::Path<Uuid>,
-  state: Data<AppState>,
-  params: Json<PublishedDuplicate>,
-) -> Result<Json<AppResponse<DuplicatePublishedPageResponse>>> {
-  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  let workspace_id = workspace_id.into_inner();
+/// Publish name of the language variant to serve instead of `publish_name`, if any.
+async fn negotiate_published_language(
+  state: &AppState,
+  publish_namespace: &str,
+  publish_name: &str,
+  query: &PublishLanguageQuery,
+  req: &HttpRequest,
+) -> Result<String, AppError> {
+  let accept_language = req
+    .headers()
+    .get(actix_web::http::header::ACCEPT_LANGUAGE)
+    .and_then(|value| value.to_str().ok());
+  biz::workspace::publish_variant::negotiate_publish_name(
+    &state.pg_pool,
+    publish_namespace,
+    publish_name,
+    query.lang.as_deref(),
+    accept_language,
+  )
+  .await
+}
+
+/// The page served depends on the language negotiated by [negotiate_published_language], so
+/// caches must not share a response between languages.
+fn vary_on_accept_language() -> DefaultHeaders {
+  DefaultHeaders::new().add((
+    actix_web::http::header::VARY,
+    actix_web::http::header::ACCEPT_LANGUAGE.as_str(),
+  ))
+}
+
+async fn get_published_collab_theme_handler(
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishTheme>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let theme = biz::workspace::publish_theme::get_effective_publish_theme(
+    &state.pg_pool,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(theme)))
+}
+
+/// Open Graph and Twitter card tags of the published page, so shared links unfurl in chat apps.
+async fn get_published_collab_head_handler(
+  path_param: web::Path<(String, String)>,
+  query: web::Query<PublishLanguageQuery>,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<HttpResponse> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let publish_name =
+    negotiate_published_language(&state, &publish_namespace, &publish_name, &query, &req).await?;
+  let preview = biz::workspace::publish_preview::get_published_page_preview(
+    &state,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
+  let head = biz::workspace::publish_preview::render_preview_head(&preview);
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/html; charset=utf-8")
+      .body(head),
+  )
+}
+
+async fn post_published_duplicate_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  params: Json<PublishedDuplicate>,
+) -> Result<Json<AppResponse<DuplicatePublishedPageResponse>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
   state
     .workspace_access_control
     .enforce_action(&uid, &workspace_id, Action::Write)
@@ -2831,7 +3740,7 @@ async fn post_published_collab_comment_handler(
 ) -> Result<JsonAppResponse<()>> {
   let view_id = view_id.into_inner();
   create_comment_on_published_view(
-    &state.pg_pool,
+    &state,
     &view_id,
     &data.reply_comment_id,
     &data.content,
@@ -2841,6 +3750,22 @@ async fn post_published_collab_comment_handler(
   Ok(Json(AppResponse::Ok()))
 }
 
+/// Called by the inbound mail provider with replies to comment notification emails. The body is
+/// signed by the provider, see [post_comment_reply_from_email].
+async fn post_comment_reply_mail_handler(
+  state: Data<AppState>,
+  req: HttpRequest,
+  body: Bytes,
+) -> Result<JsonAppResponse<()>> {
+  let signature = req
+    .headers()
+    .get(COMMENT_REPLY_SIGNATURE_HEADER)
+    .and_then(|value| value.to_str().ok());
+  let view_id = post_comment_reply_from_email(&state, &body, signature).await?;
+  tracing::info!("posted comment reply by email on {}", view_id);
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn delete_published_collab_comment_handler(
   user_uuid: UserUuid,
   view_id: web::Path<Uuid>,
@@ -2908,6 +3833,8 @@ async fn post_publish_collabs_handler(
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
   let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
 
   let mut accumulator = Vec::<PublishCollabItem<serde_json::Value, Vec<u8>>>::new();
   let mut payload_reader: PayloadReader = PayloadReader::new(payload);
@@ -2956,7 +3883,6 @@ async fn post_publish_collabs_handler(
   }
 
   // 云空间容量检查
-  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let total_data_size: i64 = accumulator.iter().map(|item| item.data.len() as i64).sum();
   check_user_storage_limit(&state.pg_pool, uid, total_data_size).await?;
 
@@ -2965,11 +3891,28 @@ async fn post_publish_collabs_handler(
     .iter()
     .map(|item| item.meta.view_id)
     .collect();
+  let publish_names: Vec<(Uuid, String)> = accumulator
+    .iter()
+    .map(|item| (item.meta.view_id, item.meta.publish_name.clone()))
+    .collect();
 
+  let documents = biz::workspace::publish_preview::published_documents(&accumulator);
   state
     .published_collab_store
     .publish_collabs(accumulator, &workspace_id, &user_uuid)
     .await?;
+  biz::workspace::publish_preview::describe_published_documents(&state, workspace_id, documents);
+  for (view_id, publish_name) in publish_names {
+    record_page_activity(
+      &state.pg_pool,
+      &workspace_id,
+      &view_id,
+      uid,
+      PageActivityKind::Published,
+      serde_json::json!({ "publish_name": publish_name }),
+    )
+    .await;
+  }
 
   // 重新发布时，删除所有用户已接收的旧副本记录
   // 这样其他用户下次通过链接打开时会生成新的只读副本
@@ -2985,6 +3928,84 @@ async fn post_publish_collabs_handler(
   Ok(Json(AppResponse::Ok()))
 }
 
+async fn get_published_database_views_handler(
+  path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseViews>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let views = biz::workspace::publish_database_views::get_published_database_views(
+    &state,
+    workspace_id,
+    view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(views)))
+}
+
+async fn patch_published_database_views_handler(
+  path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  payload: Json<UpdatePublishedDatabaseViews>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseViews>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
+  let views = biz::workspace::publish_database_views::update_published_database_views(
+    &state,
+    &user_uuid,
+    workspace_id,
+    view_id,
+    payload.into_inner().visible_database_view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(views)))
+}
+
+async fn get_published_row_filter_handler(
+  path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishRowFilter>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let filter = biz::workspace::publish_row_filter::get_published_row_filter(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(filter)))
+}
+
+async fn delete_published_row_filter_handler(
+  path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
+  biz::workspace::publish_row_filter::remove_published_row_filter(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn patch_published_collabs_handler(
   workspace_id: web::Path<Uuid>,
   user_uuid: UserUuid,
@@ -2995,6 +4016,8 @@ async fn patch_published_collabs_handler(
   if patches.is_empty() {
     return Err(AppError::InvalidRequest("No patches provided".to_string()).into());
   }
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
   state
     .published_collab_store
     .patch_collabs(&workspace_id, &user_uuid, &patches)
@@ -3013,10 +4036,23 @@ async fn delete_published_collabs_handler(
   if view_ids.is_empty() {
     return Err(AppError::InvalidRequest("No view_ids provided".to_string()).into());
   }
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  check_user_can_publish(&state.pg_pool, &user_uuid, uid, &workspace_id).await?;
   state
     .published_collab_store
     .unpublish_collabs(&workspace_id, &view_ids, &user_uuid)
     .await?;
+  for view_id in &view_ids {
+    record_page_activity(
+      &state.pg_pool,
+      &workspace_id,
+      view_id,
+      uid,
+      PageActivityKind::Unpublished,
+      serde_json::json!({}),
+    )
+    .await;
+  }
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -3097,6 +4133,191 @@ async fn get_workspace_usage_and_limit_handler(
   Ok(Json(AppResponse::Ok().with_data(res)))
 }
 
+async fn get_blob_integrity_report_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<BlobIntegrityReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let report = select_blob_integrity_report(&state.pg_pool, &workspace_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "workspace {} has not been scanned for broken attachments yet",
+        workspace_id
+      ))
+    })?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn scan_blob_integrity_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<BlobIntegrityReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  // Same lookup order as reads: the upload storage first, then the default storage
+  let mut storages: Vec<_> = state.qiniu_bucket_storage.iter().cloned().collect();
+  storages.push(state.bucket_storage.clone());
+  let report = biz::workspace::blob_integrity::scan_workspace_blob_integrity(
+    &state.pg_pool,
+    &state.collab_storage,
+    &storages,
+    workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn get_publish_integrity_report_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishIntegrityReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let report = select_publish_integrity_report(&state.pg_pool, &workspace_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "workspace {} has not been scanned for broken published pages yet",
+        workspace_id
+      ))
+    })?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn scan_publish_integrity_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<ScanPublishIntegrityParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishIntegrityReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let report = biz::workspace::publish_integrity::scan_workspace_publish_integrity(
+    &state.pg_pool,
+    &state.collab_storage,
+    &state.published_collab_store,
+    workspace_id,
+    query.regenerate,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn get_dead_reference_report_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DeadReferenceReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let report = select_dead_reference_report(&state.pg_pool, &workspace_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "workspace {} has not been scanned for dead references yet",
+        workspace_id
+      ))
+    })?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn scan_dead_references_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DeadReferenceReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let report = biz::workspace::dead_reference::scan_workspace_dead_references(
+    &state.pg_pool,
+    &state.collab_storage,
+    &state.ws_server,
+    &state.ws_server,
+    workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn get_workspace_health_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceHealthReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let report = match select_workspace_health_report(&state.pg_pool, &workspace_id).await? {
+    Some(report) => report,
+    None => biz::workspace::health::compute_workspace_health(&state.pg_pool, workspace_id).await?,
+  };
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn compute_workspace_health_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceHealthReport>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let report =
+    biz::workspace::health::compute_workspace_health(&state.pg_pool, workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(report)))
+}
+
+async fn get_blob_tiering_summary_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<BlobTieringSummary>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let summary =
+    biz::workspace::blob_tiering::get_blob_tiering_summary(&state.pg_pool, workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(summary)))
+}
+
 async fn get_workspace_folder_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -3127,6 +4348,62 @@ async fn get_workspace_folder_handler(
   Ok(Json(AppResponse::Ok().with_data(folder_view)))
 }
 
+async fn get_workspace_folder_forest_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<QueryWorkspaceFolderForest>,
+  req: HttpRequest,
+) -> Result<Json<AppResponse<Vec<FolderView>>>> {
+  let payload = payload.into_inner();
+  let depth = payload.depth.unwrap_or(1);
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let user = realtime_user_for_web_request(req.headers(), uid)?;
+  let workspace_id = workspace_id.into_inner();
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let forest = biz::collab::ops::get_user_workspace_forest(
+    &state,
+    user,
+    workspace_id,
+    depth,
+    &payload.root_view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(forest)))
+}
+
+async fn collab_access_check_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CollabAccessCheckParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<CollabAccessCheckItem>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Read)
+    .await?;
+  let is_workspace_member = state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await
+    .is_ok();
+  let items = biz::collab::ops::check_collab_access(
+    &state.pg_pool,
+    &state.collab_access_control,
+    uid,
+    workspace_id,
+    is_workspace_member,
+    payload.into_inner().object_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(items)))
+}
+
 async fn get_recent_views_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -3169,6 +4446,7 @@ async fn get_trash_views_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
+  query: web::Query<QueryTrashViews>,
 ) -> Result<Json<AppResponse<TrashSectionItems>>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let workspace_id = workspace_id.into_inner();
@@ -3176,10 +4454,14 @@ async fn get_trash_views_handler(
     .workspace_access_control
     .enforce_action(&uid, &workspace_id, Action::Read)
     .await?;
-  let folder_views = get_user_trash_folder_views(&state.ws_server, uid, workspace_id).await?;
-  let section_items = TrashSectionItems {
-    views: folder_views,
-  };
+  let section_items = get_user_trash_folder_views(
+    &state.ws_server,
+    &state.pg_pool,
+    uid,
+    workspace_id,
+    query.into_inner(),
+  )
+  .await?;
   Ok(Json(AppResponse::Ok().with_data(section_items)))
 }
 
@@ -3434,6 +4716,64 @@ async fn list_database_row_details_handler(
   Ok(Json(AppResponse::Ok().with_data(db_rows)))
 }
 
+/// Details of a single database row. The response carries an ETag of the row details, and a
+/// request whose `If-None-Match` matches it gets `304 Not Modified`.
+async fn get_database_row_detail_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+  param: web::Query<GetDatabaseRowDetailParam>,
+  req: HttpRequest,
+) -> Result<HttpResponse> {
+  let (workspace_id, db_id, row_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let with_doc = param.into_inner().with_doc.unwrap_or_default();
+
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id, Action::Read)
+    .await?;
+
+  static UNSUPPORTED_FIELD_TYPES: &[FieldType] = &[FieldType::Relation];
+
+  let db_row = biz::collab::ops::get_database_row_detail(
+    &state.collab_storage,
+    uid,
+    workspace_id,
+    db_id,
+    row_id,
+    UNSUPPORTED_FIELD_TYPES,
+    with_doc,
+  )
+  .await?;
+  let body = serde_json::to_vec(&AppResponse::Ok().with_data(db_row)).map_err(AppError::from)?;
+  let etag = format!("\"{:x}\"", md5::compute(&body));
+  let not_modified = req
+    .headers()
+    .get(actix_web::http::header::IF_NONE_MATCH)
+    .and_then(|h| h.to_str().ok())
+    .map(|value| {
+      value
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+    })
+    .unwrap_or(false);
+  if not_modified {
+    return Ok(
+      HttpResponse::NotModified()
+        .insert_header((actix_web::http::header::ETAG, etag))
+        .finish(),
+    );
+  }
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/json")
+      .insert_header((actix_web::http::header::ETAG, etag))
+      .body(body),
+  )
+}
+
 #[inline]
 async fn parser_realtime_msg(
   payload: Bytes,
@@ -3593,6 +4933,25 @@ async fn collab_full_sync_handler(
   // 云空间容量检查
   check_user_storage_limit(&state.pg_pool, uid, doc_state.len() as i64).await?;
 
+  // The realtime server drops the doc state of users who can't write, and only replies with the
+  // changes they are missing: keep it for them to recover what they wrote.
+  let can_write = state
+    .realtime_access_control
+    .can_write_collab(&workspace_id, &uid, &object_id)
+    .await
+    .unwrap_or(false);
+  if !can_write {
+    state.rejected_updates.record(
+      workspace_id,
+      object_id,
+      collab_type,
+      uid,
+      &CollabOrigin::Client(CollabClient::new(uid, device_id.clone())),
+      CONFLICT_REASON_PERMISSION_DENIED,
+      &doc_state,
+    );
+  }
+
   let user = RealtimeUser {
     uid,
     device_id,
@@ -3612,103 +4971,442 @@ async fn collab_full_sync_handler(
     return_tx: Some(tx),
   };
 
-  server
-    .try_send(message)
-    .map_err(|err| AppError::Internal(anyhow!("Failed to send message to server: {}", err)))?;
+  server
+    .try_send(message)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to send message to server: {}", err)))?;
+
+  match rx
+    .await
+    .map_err(|err| AppError::Internal(anyhow!("Failed to receive message from server: {}", err)))?
+  {
+    Ok(Some(data)) => {
+      let encoded = tokio::task::spawn_blocking(move || zstd::encode_all(Cursor::new(data), 3))
+        .await
+        .map_err(|err| AppError::Internal(anyhow!("Failed to compress data: {}", err)))??;
+
+      Ok(HttpResponse::Ok().body(encoded))
+    },
+    Ok(None) => Ok(HttpResponse::InternalServerError().finish()),
+    Err(err) => Ok(err.error_response()),
+  }
+}
+
+async fn post_quick_note_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CreateQuickNoteParams>,
+) -> Result<JsonAppResponse<QuickNote>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let data = data.into_inner();
+  let quick_note = create_quick_note(
+    &state.pg_pool,
+    uid,
+    workspace_id,
+    data.data.as_ref(),
+    data.dedupe,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(quick_note)))
+}
+
+async fn list_quick_notes_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  query: web::Query<ListQuickNotesQueryParams>,
+) -> Result<JsonAppResponse<QuickNotes>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let ListQuickNotesQueryParams {
+    search_term,
+    created_after,
+    created_before,
+    offset,
+    limit,
+  } = query.into_inner();
+  let quick_notes = list_quick_notes(
+    &state.pg_pool,
+    uid,
+    workspace_id,
+    search_term,
+    created_after,
+    created_before,
+    offset,
+    limit,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(quick_notes)))
+}
+
+async fn update_quick_note_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  data: Json<UpdateQuickNoteParams>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, quick_note_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  update_quick_note(&state.pg_pool, quick_note_id, &data.data).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn delete_quick_note_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, quick_note_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  delete_quick_note(&state.pg_pool, quick_note_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_view_slug_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ViewSlug>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  let slug = get_view_slug(&state.pg_pool, &workspace_id, &view_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(slug)))
+}
+
+async fn put_view_slug_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  data: Json<SetViewSlugParams>,
+) -> Result<JsonAppResponse<ViewSlug>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let slug = set_view_slug(&state.pg_pool, &workspace_id, &view_id, &data.slug).await?;
+  Ok(Json(AppResponse::Ok().with_data(slug)))
+}
+
+async fn delete_view_slug_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  remove_view_slug(&state.pg_pool, &workspace_id, &view_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_page_reactions_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<PageReactionCount>>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  let reactions = get_page_reactions(&state.pg_pool, &workspace_id, &view_id, uid).await?;
+  Ok(Json(AppResponse::Ok().with_data(reactions)))
+}
+
+async fn post_page_reaction_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  data: Json<PageReactionParams>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  add_page_reaction(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+    uid,
+    &data.reaction_type,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
 
-  match rx
-    .await
-    .map_err(|err| AppError::Internal(anyhow!("Failed to receive message from server: {}", err)))?
-  {
-    Ok(Some(data)) => {
-      let encoded = tokio::task::spawn_blocking(move || zstd::encode_all(Cursor::new(data), 3))
-        .await
-        .map_err(|err| AppError::Internal(anyhow!("Failed to compress data: {}", err)))??;
+async fn delete_page_reaction_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  data: Json<PageReactionParams>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  remove_page_reaction(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+    uid,
+    &data.reaction_type,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
 
-      Ok(HttpResponse::Ok().body(encoded))
-    },
-    Ok(None) => Ok(HttpResponse::InternalServerError().finish()),
-    Err(err) => Ok(err.error_response()),
-  }
+async fn resolve_view_slug_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ResolvedViewSlug>> {
+  let (workspace_id, slug) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Guest)
+    .await?;
+  let resolved = resolve_view_slug(&state.pg_pool, &workspace_id, &slug).await?;
+  Ok(Json(AppResponse::Ok().with_data(resolved)))
 }
 
-async fn post_quick_note_handler(
+async fn list_workspace_snippets_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
-  data: Json<CreateQuickNoteParams>,
-) -> Result<JsonAppResponse<QuickNote>> {
+  query: web::Query<ListWorkspaceSnippetsQueryParams>,
+) -> Result<JsonAppResponse<WorkspaceSnippets>> {
   let workspace_id = workspace_id.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
-    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
     .await?;
-  let data = data.into_inner();
-  let quick_note = create_quick_note(&state.pg_pool, uid, workspace_id, data.data.as_ref()).await?;
-  Ok(Json(AppResponse::Ok().with_data(quick_note)))
+  let snippets =
+    list_workspace_snippets(&state.pg_pool, &workspace_id, query.prefix.as_deref()).await?;
+  Ok(Json(AppResponse::Ok().with_data(snippets)))
 }
 
-async fn list_quick_notes_handler(
+async fn post_workspace_snippet_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
   state: Data<AppState>,
-  query: web::Query<ListQuickNotesQueryParams>,
-) -> Result<JsonAppResponse<QuickNotes>> {
+  data: Json<CreateWorkspaceSnippetParams>,
+) -> Result<JsonAppResponse<WorkspaceSnippet>> {
   let workspace_id = workspace_id.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
     .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
     .await?;
-  let ListQuickNotesQueryParams {
-    search_term,
-    offset,
-    limit,
-  } = query.into_inner();
-  let quick_notes = list_quick_notes(
+  let snippet =
+    create_workspace_snippet(&state.pg_pool, uid, &workspace_id, data.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(snippet)))
+}
+
+async fn update_workspace_snippet_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  data: Json<UpdateWorkspaceSnippetParams>,
+) -> Result<JsonAppResponse<WorkspaceSnippet>> {
+  let (workspace_id, snippet_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let snippet = update_workspace_snippet_by_id(
     &state.pg_pool,
+    &user_uuid,
     uid,
-    workspace_id,
-    search_term,
-    offset,
-    limit,
+    &workspace_id,
+    &snippet_id,
+    data.into_inner(),
   )
   .await?;
-  Ok(Json(AppResponse::Ok().with_data(quick_notes)))
+  Ok(Json(AppResponse::Ok().with_data(snippet)))
 }
 
-async fn update_quick_note_handler(
+async fn delete_workspace_snippet_handler(
   user_uuid: UserUuid,
   path_param: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
-  data: Json<UpdateQuickNoteParams>,
 ) -> Result<JsonAppResponse<()>> {
-  let (workspace_id, quick_note_id) = path_param.into_inner();
+  let (workspace_id, snippet_id) = path_param.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
     .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
     .await?;
-  update_quick_note(&state.pg_pool, quick_note_id, &data.data).await?;
+  delete_workspace_snippet_by_id(&state.pg_pool, &user_uuid, uid, &workspace_id, &snippet_id)
+    .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn delete_quick_note_handler(
+async fn expand_workspace_snippet_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<ExpandWorkspaceSnippetParams>,
+) -> Result<JsonAppResponse<ExpandedWorkspaceSnippet>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let expanded =
+    expand_workspace_snippet(&state.pg_pool, uid, &workspace_id, data.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(expanded)))
+}
+
+async fn list_scheduled_exports_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ScheduledExports>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let exports = list_scheduled_exports(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(exports)))
+}
+
+async fn post_scheduled_export_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CreateScheduledExportParams>,
+) -> Result<JsonAppResponse<ScheduledExport>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let export = create_scheduled_export(
+    &state.pg_pool,
+    &state.config.scheduled_export.secret_key,
+    uid,
+    &workspace_id,
+    data.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(export)))
+}
+
+async fn update_scheduled_export_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  data: Json<UpdateScheduledExportParams>,
+) -> Result<JsonAppResponse<ScheduledExport>> {
+  let (workspace_id, export_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let export = update_workspace_scheduled_export(
+    &state.pg_pool,
+    &state.config.scheduled_export.secret_key,
+    uid,
+    &workspace_id,
+    &export_id,
+    data.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(export)))
+}
+
+async fn delete_scheduled_export_handler(
   user_uuid: UserUuid,
   path_param: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
-  let (workspace_id, quick_note_id) = path_param.into_inner();
+  let (workspace_id, export_id) = path_param.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
-    .enforce_role_strong(&uid, &workspace_id, AFRole::Member)
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
     .await?;
-  delete_quick_note(&state.pg_pool, quick_note_id).await?;
+  delete_workspace_scheduled_export(&state.pg_pool, uid, &workspace_id, &export_id).await?;
   Ok(Json(AppResponse::Ok()))
 }
 
+async fn list_scheduled_export_runs_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  query: web::Query<ListScheduledExportRunsQueryParams>,
+) -> Result<JsonAppResponse<ScheduledExportRuns>> {
+  let (workspace_id, export_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let runs =
+    list_scheduled_export_runs(&state.pg_pool, &workspace_id, &export_id, query.limit).await?;
+  Ok(Json(AppResponse::Ok().with_data(runs)))
+}
+
+async fn run_scheduled_export_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<ScheduledExportRun>> {
+  let (workspace_id, export_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let context = ScheduledExportContext {
+    pg_pool: state.pg_pool.clone(),
+    collab_storage: state.collab_storage.clone(),
+    collab_instance_cache: state.ws_server.clone(),
+    secret_key: state.config.scheduled_export.secret_key.clone(),
+  };
+  let run = run_scheduled_export_now(context, &workspace_id, &export_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(run)))
+}
+
 async fn delete_workspace_invite_code_handler(
   user_uuid: UserUuid,
   path_param: web::Path<Uuid>,
@@ -4453,6 +6151,111 @@ async fn post_join_request_handler(
   Ok(Json(AppResponse::Ok().with_data(join_request)))
 }
 
+/// Zip archive of the documents of a space, as Markdown or JSON files
+async fn export_space_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<ExportSpaceParams>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (workspace_id, space_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let bundle = biz::workspace::markdown_export::export_space(
+    &state.collab_storage,
+    &state.ws_server,
+    uid,
+    workspace_id,
+    space_id,
+    payload.format,
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/zip")
+      .insert_header((
+        actix_web::http::header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}.zip\"", space_id),
+      ))
+      .body(bundle.bytes),
+  )
+}
+
+async fn archive_space_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<ArchivedSpace>> {
+  let (workspace_id, space_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let user = realtime_user_for_web_request(req.headers(), uid)?;
+  let archived_space =
+    biz::workspace::space_archive::archive_space(&state, user, workspace_id, space_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(archived_space)))
+}
+
+async fn unarchive_space_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, space_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let user = realtime_user_for_web_request(req.headers(), uid)?;
+  biz::workspace::space_archive::unarchive_space(&state, user, workspace_id, space_id).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_archived_spaces_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<ArchivedSpace>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_weak(&uid, &workspace_id, AFRole::Member)
+    .await?;
+  let archived_spaces =
+    biz::workspace::space_archive::list_archived_spaces(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(archived_spaces)))
+}
+
+/// Merges another workspace of the owner into this one. The other workspace is deleted.
+async fn merge_workspace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<MergeWorkspaceParams>,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<MergedWorkspace>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role_strong(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let user = realtime_user_for_web_request(req.headers(), uid)?;
+  let merged =
+    biz::workspace::merge::merge_workspaces(&state, user, workspace_id, payload.into_inner())
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(merged)))
+}
+
 /// List join requests for a space (space owner only)
 async fn get_join_requests_handler(
   user_uuid: UserUuid,
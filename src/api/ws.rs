@@ -3,7 +3,11 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 use crate::biz::authentication::jwt::{authorization_from_token, UserUuid};
-use crate::biz::notification::ops::{get_pending_notifications, mark_notifications_processed};
+use crate::biz::notification::ops::{
+  extract_message_from_payload, extract_title_from_payload, get_pending_notifications,
+  mark_notifications_processed,
+};
+use crate::biz::user::device_handoff::{is_notification_for_device, record_user_device};
 use crate::state::AppState;
 use actix::Addr;
 use actix_http::header::AUTHORIZATION;
@@ -120,6 +124,8 @@ pub async fn establish_ws_connection_v2(
   let auth = authorization_from_token(params.access_token.as_str(), &jwt_secret)?;
   let user_uuid = UserUuid::from_auth(auth)?;
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let device_id = params.device_id.clone();
+  record_user_device(&state.pg_pool, uid, &device_id, None).await;
   let info = SessionInfo::new(
     params.client_id,
     uid,
@@ -169,6 +175,13 @@ pub async fn establish_ws_connection_v2(
         );
         let mut delivered_ids = Vec::with_capacity(pending.len());
         for notification in pending {
+          if !is_notification_for_device(
+            &notification.notification_type,
+            &notification.payload,
+            &device_id,
+          ) {
+            continue;
+          }
           let msg = ServerMessage::Notification {
             notification: WorkspaceNotification::SystemNotification {
               id: notification.id.to_string(),
@@ -199,6 +212,13 @@ pub async fn establish_ws_connection_v2(
 
     // 持续监听新到达的通知
     while let Some(notification) = system_notification_recv.recv().await {
+      if !is_notification_for_device(
+        &notification.notification_type,
+        &notification.payload,
+        &device_id,
+      ) {
+        continue;
+      }
       debug!(
         "[ws_v2] Pushing system notification to uid={}: type={}, id={}",
         uid,
@@ -242,24 +262,6 @@ pub async fn establish_ws_connection_v2(
   .start()
 }
 
-/// 从通知 payload 中提取标题
-fn extract_title_from_payload(payload: &serde_json::Value) -> String {
-  payload
-    .get("title")
-    .and_then(|v| v.as_str())
-    .unwrap_or("系统通知")
-    .to_string()
-}
-
-/// 从通知 payload 中提取消息内容
-fn extract_message_from_payload(payload: &serde_json::Value) -> String {
-  payload
-    .get("message")
-    .and_then(|v| v.as_str())
-    .unwrap_or("")
-    .to_string()
-}
-
 #[allow(clippy::too_many_arguments)]
 #[inline]
 async fn start_connect(
@@ -284,10 +286,17 @@ async fn start_connect(
         uid, device_id, client_app_version
       );
 
+      record_user_device(
+        &state.pg_pool,
+        uid,
+        &device_id,
+        Some(&client_app_version.to_string()),
+      )
+      .await;
       let session_id = uuid::Uuid::new_v4().to_string();
       let realtime_user = RealtimeUser::new(
         uid,
-        device_id,
+        device_id.clone(),
         session_id,
         connect_at,
         client_app_version.to_string(),
@@ -308,7 +317,7 @@ async fn start_connect(
 
       // Receive system notifications and send them to the client.
       // Also delivers any pending (unprocessed) notifications that arrived while the client was offline.
-      listen_on_system_notification(state, uid, tx);
+      listen_on_system_notification(state, uid, device_id, tx);
 
       match ws::WsResponseBuilder::new(client, request, payload)
         .frame_size(MAX_FRAME_SIZE * 2)
@@ -361,7 +370,12 @@ fn listen_on_user_change(state: &Data<AppState>, uid: i64, tx: Sender<RealtimeMe
 
 /// 监听系统通知并发送给客户端（v1 版本 WebSocket）
 /// 连接建立时先补发离线期间未处理的通知，再持续监听新通知
-fn listen_on_system_notification(state: &Data<AppState>, uid: i64, tx: Sender<RealtimeMessage>) {
+fn listen_on_system_notification(
+  state: &Data<AppState>,
+  uid: i64,
+  device_id: String,
+  tx: Sender<RealtimeMessage>,
+) {
   let mut notification_recv = state.pg_listeners.subscribe_system_notification(uid);
   let pg_pool = state.pg_pool.clone();
   actix::spawn(async move {
@@ -375,6 +389,13 @@ fn listen_on_system_notification(state: &Data<AppState>, uid: i64, tx: Sender<Re
         );
         let mut delivered_ids = Vec::with_capacity(pending.len());
         for notification in pending {
+          if !is_notification_for_device(
+            &notification.notification_type,
+            &notification.payload,
+            &device_id,
+          ) {
+            continue;
+          }
           let msg = UserMessage::SystemNotification(AFSystemNotification {
             id: notification.id.to_string(),
             workspace_id: notification
@@ -406,6 +427,13 @@ fn listen_on_system_notification(state: &Data<AppState>, uid: i64, tx: Sender<Re
 
     // 持续监听新到达的通知
     while let Some(notification) = notification_recv.recv().await {
+      if !is_notification_for_device(
+        &notification.notification_type,
+        &notification.payload,
+        &device_id,
+      ) {
+        continue;
+      }
       debug!(
         "[ws_v1] Pushing system notification to uid={}: type={}, id={}",
         uid,
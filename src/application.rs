@@ -23,6 +23,7 @@ use actix_web::middleware::NormalizePath;
 use actix_web::web::JsonConfig;
 use actix_web::{dev::Server, web, web::Data, App, HttpResponse, HttpServer, Responder};
 use anyhow::{Context, Error};
+use arc_swap::ArcSwap;
 use aws_sdk_s3::config::{Credentials, Region, SharedCredentialsProvider};
 use aws_sdk_s3::operation::create_bucket::CreateBucketError;
 use aws_sdk_s3::types::{
@@ -38,15 +39,19 @@ use appflowy_ai_client::client::AppFlowyAIClient;
 use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
 use appflowy_collaborate::collab::cache::CollabCache;
 use appflowy_collaborate::collab::collab_store::CollabStoreImpl;
+use appflowy_collaborate::collab::page_edit::PageEditRecorder;
+use appflowy_collaborate::collab::rejected_update::RejectedUpdateRecorder;
+use appflowy_collaborate::collab::structure_limit::CollabStructureGuard;
+use appflowy_collaborate::collab::write_guard::CollabWriteGuard;
 use appflowy_collaborate::ws2::{CollabManager, WsServer};
 use appflowy_collaborate::CollaborationServer;
 use collab_stream::awareness_gossip::AwarenessGossip;
 use collab_stream::metrics::CollabStreamMetrics;
 use collab_stream::stream_router::{StreamRouter, StreamRouterOptions};
+use database::collab::CollabStore;
 use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
 use indexer::collab_indexer::IndexerProvider;
 use indexer::scheduler::{IndexerConfiguration, IndexerScheduler};
-use indexer::vector::embedder::get_open_ai_config;
 use infra::env_util::get_env_var;
 use infra::thread_pool::ThreadPoolNoAbortBuilder;
 use mailer::sender::Mailer;
@@ -69,21 +74,50 @@ use crate::api::server_info::server_info_scope;
 use crate::api::subscription::subscription_scope;
 use crate::api::template::template_scope;
 use crate::api::user::user_scope;
+use crate::api::webdav::webdav_scope;
 use crate::api::workspace::{collab_scope, collab_share_scope, workspace_scope};
 use crate::api::ws::ws_scope;
+use crate::biz::collab::conflict::start_collab_conflict_cleanup_task;
 use crate::biz::notification::email::EmailNotificationWorker;
+use crate::biz::notification::push::{start_push_token_cleanup_task, PushNotificationDispatcher};
 use crate::biz::subscription::subscription_expiry_task::start_subscription_expiry_task;
 use crate::biz::subscription::resource_cleanup_task::start_resource_cleanup_task;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::pool_metrics::start_pool_metrics_task;
+use crate::biz::redis_health::{start_redis_health_task, RedisHealth};
+use crate::biz::workspace::blob_integrity::start_blob_integrity_task;
+use crate::biz::workspace::publish_integrity::start_publish_integrity_task;
+use crate::biz::workspace::blob_tiering::start_blob_tiering_task;
+use crate::biz::workspace::dead_reference::start_dead_reference_task;
+use crate::biz::workspace::health::start_workspace_health_task;
+use crate::biz::user::device_handoff::start_device_handoff_cleanup_task;
+use crate::biz::workspace::space_archive::start_archived_space_sync_task;
+use crate::biz::workspace::collab_permission_sync::start_collab_permission_sync_recovery_task;
+use crate::biz::workspace::events::WorkspaceEvents;
+use crate::biz::workspace::view_metadata::start_view_metadata_sync_task;
+use crate::biz::workspace::egress::{start_egress_flush_task, EgressMeter};
+use crate::biz::workspace::page_watch::start_page_watch_digest_task;
+use crate::biz::workspace::publish_stats::{
+  start_publish_view_counter_flush_task, start_published_view_stats_digest_task, PublishViewCounter,
+};
 use crate::biz::workspace::publish::{
   PublishedCollabPostgresStore, PublishedCollabS3StoreWithPostgresFallback, PublishedCollabStore,
 };
+use crate::biz::workspace::scheduled_export::{
+  start_scheduled_export_task, ScheduledExportContext,
+};
 use crate::config::config::{
   Config, DatabaseSetting, GoTrueSetting, PublishedCollabStorageBackend, QiniuSetting, S3Setting,
 };
+use crate::config::runtime::{
+  start_runtime_config_reload_task, RuntimeConfig, RuntimeSetting, MAX_JSON_PAYLOAD_LIMIT,
+};
 use crate::mailer::AFCloudMailer;
+use crate::middleware::deadline::DeadlineMiddleware;
 use crate::middleware::metrics_mw::MetricsMiddleware;
+use crate::middleware::payload_limit::PayloadLimitMiddleware;
 use crate::middleware::request_id::RequestIdMiddleware;
+use crate::middleware::slow_query::SlowQueryMiddleware;
 use crate::state::{AppMetrics, AppState, GoTrueAdmin, UserCache};
 
 pub struct Application {
@@ -127,6 +161,7 @@ pub async fn run_actix_server(
     })?;
 
   let storage = state.collab_storage.clone();
+  let max_request_deadline = Duration::from_millis(config.application.max_request_deadline_ms);
 
   // Initialize metrics that which are registered in the registry.
   let realtime_server = CollaborationServer::new(
@@ -138,6 +173,8 @@ pub async fn run_actix_server(
     state.redis_connection_manager.clone(),
     Duration::from_secs(config.collab.group_persistence_interval_secs),
     state.indexer_scheduler.clone(),
+    state.collab_write_guard.clone(),
+    state.collab_structure_guard.clone(),
   )
   .await
   .unwrap();
@@ -153,7 +190,10 @@ pub async fn run_actix_server(
         SessionMiddleware::builder(redis_store.clone(), Key::generate())
           .build(),
       )
-      .wrap(RequestIdMiddleware);
+      .wrap(RequestIdMiddleware)
+      .wrap(SlowQueryMiddleware)
+      .wrap(DeadlineMiddleware::new(max_request_deadline))
+      .wrap(PayloadLimitMiddleware::new(state.runtime_config.clone()));
 
     #[cfg(feature = "use_actix_cors")]
     let app = app.wrap(actix_cors_scope());
@@ -183,6 +223,7 @@ pub async fn run_actix_server(
       .service(data_import_scope())
       .service(access_request_scope())
       .service(sharing_scope())
+      .service(webdav_scope())
       .route("/health", web::get().to(health_check))
       .app_data(Data::new(state.metrics.registry.clone()))
       .app_data(Data::new(state.metrics.request_metrics.clone()))
@@ -193,8 +234,8 @@ pub async fn run_actix_server(
       .app_data(Data::new(state.clone()))
       .app_data(Data::new(storage.clone()))
       .app_data(Data::new(state.published_collab_store.clone()))
-      // 设置JSON body大小限制为20MB，支持AI图片上传
-      .app_data(JsonConfig::default().limit(20 * 1024 * 1024))
+      // 设置JSON body大小限制为20MB，支持AI图片上传；PayloadLimitMiddleware 按运行时配置进一步收紧
+      .app_data(JsonConfig::default().limit(MAX_JSON_PAYLOAD_LIMIT))
   });
 
   server = server.listen(listener)?;
@@ -207,10 +248,21 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
 
   let metrics = AppMetrics::new();
 
+  info!("Loading runtime configuration...");
+  let runtime_config: RuntimeConfig = Arc::new(ArcSwap::from_pointee(RuntimeSetting::load()?));
+
   // Postgres
   info!("Preparing to run database migrations...");
   let pg_pool = get_connection_pool(&config.db_settings).await?;
   migrate(&pg_pool).await?;
+  let mut pg_pools = vec![("main", pg_pool.clone())];
+  let collab_persistence_pg_pool = match get_collab_persistence_pool(&config.db_settings).await? {
+    Some(pool) => {
+      pg_pools.push(("collab_persistence", pool.clone()));
+      pool
+    },
+    None => pg_pool.clone(),
+  };
 
   // Bucket storage
   info!("Setting up S3 bucket...");
@@ -259,6 +311,16 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     metrics.collab_stream_metrics.clone(),
   )
   .await?;
+  let redis_health = Arc::new(RedisHealth::default());
+  tokio::spawn(start_redis_health_task(
+    redis_health.clone(),
+    redis_conn_manager.clone(),
+  ));
+  tokio::spawn(start_pool_metrics_task(
+    metrics.pool_metrics.clone(),
+    pg_pools,
+    redis_conn_manager.clone(),
+  ));
 
   info!("Setup AppFlowy AI: {}", config.appflowy_ai.url());
   let appflowy_ai_client = AppFlowyAIClient::new(&config.appflowy_ai.url());
@@ -322,6 +384,7 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     thread_pool.clone(),
     redis_conn_manager.clone(),
     pg_pool.clone(),
+    collab_persistence_pg_pool,
     s3_client.clone(),
     metrics.collab_metrics.clone(),
     config.collab.s3_collab_threshold as usize,
@@ -334,24 +397,34 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
   ));
 
   let mailer = get_mailer(&config.mailer).await?;
-  if config.notification.enable_email_notification {
-    info!("Setting up background notification worker...");
-    let email_notification_interval = config.notification.email_notification_interval_secs;
-    let email_notification_grace_period = config.notification.email_notification_grace_period_secs;
-    let task_appflowy_web_url = config.appflowy_web_url.clone();
-    let task_mailer = mailer.clone();
-    let task_pg_pool = pg_pool.clone();
-    tokio::spawn(async move {
-      let email_notification_worker = EmailNotificationWorker::new(
-        task_pg_pool,
-        task_mailer,
-        email_notification_interval,
-        email_notification_grace_period,
-        &task_appflowy_web_url,
-      );
-      email_notification_worker.start_task().await;
-    });
+  info!("Setting up background notification worker...");
+  let email_notification_interval = config.notification.email_notification_interval_secs;
+  let email_notification_grace_period = config.notification.email_notification_grace_period_secs;
+  let task_appflowy_web_url = config.appflowy_web_url.clone();
+  let task_mailer = mailer.clone();
+  let task_pg_pool = pg_pool.clone();
+  let task_runtime_config = runtime_config.clone();
+  tokio::spawn(async move {
+    let email_notification_worker = EmailNotificationWorker::new(
+      task_pg_pool,
+      task_mailer,
+      task_runtime_config,
+      email_notification_interval,
+      email_notification_grace_period,
+      &task_appflowy_web_url,
+    );
+    email_notification_worker.start_task().await;
+  });
+
+  let push_dispatcher = PushNotificationDispatcher::new(pg_pool.clone(), &config.push_notification);
+  if push_dispatcher.is_enabled() {
+    info!("Setting up push notification dispatcher...");
+    tokio::spawn(push_dispatcher.run(pg_listeners.subscribe_notifications()));
   }
+  tokio::spawn(start_push_token_cleanup_task(
+    pg_pool.clone(),
+    config.push_notification.stale_token_days,
+  ));
 
   // 启动订阅过期检查定时任务（每小时）
   info!("Setting up subscription expiry check task...");
@@ -368,8 +441,29 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     start_resource_cleanup_task(cleanup_pg_pool, cleanup_s3_client).await;
   });
 
+  info!("Setting up publish integrity check task...");
+  let publish_integrity_pg_pool = pg_pool.clone();
+  let publish_integrity_collab_storage: Arc<dyn CollabStore> =
+    collab_access_control_storage.clone();
+  let publish_integrity_store = published_collab_store.clone();
+  tokio::spawn(async move {
+    start_publish_integrity_task(
+      publish_integrity_pg_pool,
+      publish_integrity_collab_storage,
+      publish_integrity_store,
+    )
+    .await;
+  });
+
   info!("Setting up Indexer scheduler...");
-  let (open_ai_config, azure_ai_config) = get_open_ai_config();
+  // The AI keys come from the runtime settings, which hand them again to the indexer on reload
+  let (open_ai_config, azure_ai_config) = {
+    let runtime_setting = runtime_config.load();
+    (
+      runtime_setting.open_ai_config.clone(),
+      runtime_setting.azure_ai_config.clone(),
+    )
+  };
   let embedder_config = IndexerConfiguration {
     enable: get_env_var("APPFLOWY_INDEXER_ENABLED", "true")
       .parse::<bool>()
@@ -391,6 +485,18 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     embedder_config,
     redis_conn_manager.clone(),
   );
+  tokio::spawn(start_runtime_config_reload_task(
+    runtime_config.clone(),
+    indexer_scheduler.clone(),
+  ));
+  let collab_write_guard = CollabWriteGuard::new(pg_pool.clone());
+  let collab_structure_guard = CollabStructureGuard::new(
+    config.collab.structure_limits(),
+    config.collab.structure_check_cache_size,
+    collab_cache.clone(),
+  );
+  let rejected_updates = RejectedUpdateRecorder::new(pg_pool.clone());
+  let page_edits = PageEditRecorder::new(pg_pool.clone());
   let manager = CollabManager::new(
     thread_pool.clone(),
     collab_access_control.clone(),
@@ -400,8 +506,14 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     redis_stream_router.clone(),
     awareness_gossip.clone(),
     indexer_scheduler.clone(),
+    collab_structure_guard.clone(),
+    collab_write_guard.clone(),
+    rejected_updates.clone(),
+    page_edits,
   );
   let ws_server = WsServer::new(manager, pg_pool.clone()).start();
+  info!("Setting up collab conflict cleanup task...");
+  tokio::spawn(start_collab_conflict_cleanup_task(pg_pool.clone()));
 
   // Initialize ChatClient for third-party AI providers
   let chat_client = Arc::new(
@@ -478,6 +590,110 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     None
   };
 
+  info!("Setting up blob tiering task...");
+  let (tiering_primary_storage, tiering_fallback_storage) = match &qiniu_bucket_storage {
+    Some(qiniu_storage) => (qiniu_storage.clone(), Some(bucket_storage.clone())),
+    None => (bucket_storage.clone(), None),
+  };
+  let blob_integrity_primary_storage = tiering_primary_storage.clone();
+  let blob_integrity_fallback_storage = tiering_fallback_storage.clone();
+  let tiering_pg_pool = pg_pool.clone();
+  tokio::spawn(async move {
+    start_blob_tiering_task(
+      tiering_pg_pool,
+      tiering_primary_storage,
+      tiering_fallback_storage,
+    )
+    .await;
+  });
+
+  info!("Setting up blob integrity check task...");
+  let blob_integrity_pg_pool = pg_pool.clone();
+  let blob_integrity_collab_storage: Arc<dyn CollabStore> = collab_access_control_storage.clone();
+  tokio::spawn(async move {
+    start_blob_integrity_task(
+      blob_integrity_pg_pool,
+      blob_integrity_collab_storage,
+      blob_integrity_primary_storage,
+      blob_integrity_fallback_storage,
+    )
+    .await;
+  });
+
+  info!("Setting up egress metering...");
+  let egress_meter = Arc::new(EgressMeter::default());
+  tokio::spawn(start_egress_flush_task(
+    egress_meter.clone(),
+    pg_pool.clone(),
+    config.egress.flush_interval_secs,
+    runtime_config.clone(),
+  ));
+
+  info!("Setting up page watch digest task...");
+  tokio::spawn(start_page_watch_digest_task(pg_pool.clone()));
+
+  info!("Setting up dead reference scan task...");
+  tokio::spawn(start_dead_reference_task(
+    pg_pool.clone(),
+    collab_access_control_storage.clone(),
+    ws_server.clone(),
+  ));
+
+  info!("Setting up device handoff cleanup task...");
+  tokio::spawn(start_device_handoff_cleanup_task(pg_pool.clone()));
+
+  info!("Setting up workspace health task...");
+  tokio::spawn(start_workspace_health_task(pg_pool.clone()));
+
+  info!("Setting up view metadata sync task...");
+  tokio::spawn(start_view_metadata_sync_task(
+    pg_pool.clone(),
+    ws_server.clone(),
+  ));
+
+  info!("Setting up archived space sync task...");
+  tokio::spawn(start_archived_space_sync_task(
+    pg_pool.clone(),
+    collab_access_control_storage.clone(),
+    ws_server.clone(),
+    collab_write_guard.clone(),
+  ));
+
+  info!("Setting up collab permission sync recovery task...");
+  tokio::spawn(start_collab_permission_sync_recovery_task(
+    pg_pool.clone(),
+    collab_access_control.clone(),
+    ws_server.clone(),
+  ));
+
+  info!("Setting up published view stats...");
+  let publish_view_counter = Arc::new(PublishViewCounter::default());
+  tokio::spawn(start_publish_view_counter_flush_task(
+    publish_view_counter.clone(),
+    pg_pool.clone(),
+  ));
+  tokio::spawn(start_published_view_stats_digest_task(pg_pool.clone()));
+
+  info!("Setting up scheduled export task...");
+  tokio::spawn(start_scheduled_export_task(
+    ScheduledExportContext {
+      pg_pool: pg_pool.clone(),
+      collab_storage: collab_access_control_storage.clone(),
+      collab_instance_cache: ws_server.clone(),
+      secret_key: config.scheduled_export.secret_key.clone(),
+    },
+    config.scheduled_export.check_interval_secs,
+  ));
+
+  let workspace_events = Arc::new(WorkspaceEvents::new(
+    pg_pool.clone(),
+    workspace_access_control.clone(),
+    collab_access_control.clone(),
+    ws_server.clone(),
+    mailer.clone(),
+    config.appflowy_web_url.clone(),
+  ));
+
   info!("Application state initialized");
   Ok(AppState {
     pg_pool,
@@ -488,7 +704,11 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     redis_stream_router,
     awareness_gossip,
     redis_connection_manager: redis_conn_manager,
+    redis_health,
     collab_cache,
+    collab_write_guard,
+    collab_structure_guard,
+    rejected_updates,
     collab_storage: collab_access_control_storage,
     collab_access_control,
     workspace_access_control,
@@ -504,12 +724,16 @@ pub async fn init_state(config: &Config) -> Result<AppState, Error> {
     chat_client,
     indexer_scheduler,
     ws_server,
+    workspace_events,
     qiniu_client,
     qiniu_bucket_storage,
+    egress_meter,
+    publish_view_counter,
+    runtime_config,
   })
 }
 
-fn get_admin_client(
+pub fn get_admin_client(
   gotrue_client: gotrue::api::Client,
   gotrue_setting: &GoTrueSetting,
 ) -> GoTrueAdmin {
@@ -674,10 +898,28 @@ async fn get_mailer(mailer: &MailerSetting) -> Result<AFCloudMailer, Error> {
   AFCloudMailer::new(mailer).await
 }
 
-async fn get_connection_pool(setting: &DatabaseSetting) -> Result<PgPool, Error> {
+pub async fn get_connection_pool(setting: &DatabaseSetting) -> Result<PgPool, Error> {
   info!("Connecting to postgres database with setting: {}", setting);
+  connect_pool(setting, setting.max_connections).await
+}
+
+/// Pool dedicated to the transactions persisting collabs, when one is configured.
+async fn get_collab_persistence_pool(setting: &DatabaseSetting) -> Result<Option<PgPool>, Error> {
+  match setting.collab_persistence_max_connections {
+    Some(max_connections) => {
+      info!(
+        "Connecting to postgres database for collab persistence with {} connections",
+        max_connections
+      );
+      Ok(Some(connect_pool(setting, max_connections).await?))
+    },
+    None => Ok(None),
+  }
+}
+
+async fn connect_pool(setting: &DatabaseSetting, max_connections: u32) -> Result<PgPool, Error> {
   PgPoolOptions::new()
-    .max_connections(setting.max_connections)
+    .max_connections(max_connections)
     .acquire_timeout(Duration::from_secs(10))
     .max_lifetime(Duration::from_secs(30 * 60))
     .idle_timeout(Duration::from_secs(30))
@@ -694,7 +936,7 @@ async fn migrate(pool: &PgPool) -> Result<(), Error> {
     .map_err(|e| anyhow::anyhow!("Failed to run migrations: {}", e))
 }
 
-async fn get_gotrue_client(setting: &GoTrueSetting) -> Result<gotrue::api::Client, Error> {
+pub async fn get_gotrue_client(setting: &GoTrueSetting) -> Result<gotrue::api::Client, Error> {
   info!("Connecting to GoTrue with setting: {:?}", setting);
   let gotrue_client = gotrue::api::Client::new(reqwest::Client::new(), &setting.base_url);
   let _ = gotrue_client
@@ -704,8 +946,14 @@ async fn get_gotrue_client(setting: &GoTrueSetting) -> Result<gotrue::api::Clien
   Ok(gotrue_client)
 }
 
-async fn health_check() -> impl Responder {
-  HttpResponse::Ok().body("OK")
+/// Answers 200 as long as the server can serve requests. A degraded status means some
+/// dependencies are unavailable and the features relying on them fail soft.
+async fn health_check(state: Data<AppState>) -> impl Responder {
+  let redis_available = !state.redis_health.is_degraded();
+  HttpResponse::Ok().json(serde_json::json!({
+    "status": if redis_available { "ok" } else { "degraded" },
+    "redis": redis_available,
+  }))
 }
 
 #[cfg(feature = "use_actix_cors")]
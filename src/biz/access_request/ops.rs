@@ -2,6 +2,7 @@ use std::ops::DerefMut;
 use std::sync::Arc;
 
 use crate::mailer::AFCloudMailer;
+use crate::middleware::deadline::apply_statement_timeout;
 use crate::{
   biz::collab::folder_view::{to_dto_view_icon, to_dto_view_layout},
   mailer::{WorkspaceAccessRequestApprovedMailerParam, WorkspaceAccessRequestMailerParam},
@@ -121,6 +122,7 @@ pub async fn approve_or_reject_access_request(
     .await?;
 
   let mut txn = pg_pool.begin().await.context("approving request")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let role = AFRole::Member;
   if is_approved {
     upsert_workspace_member_with_txn(
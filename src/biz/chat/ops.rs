@@ -1,5 +1,6 @@
-use anyhow::anyhow;
+use std::ops::DerefMut;
 
+use anyhow::anyhow;
 use app_error::AppError;
 use appflowy_ai_client::client::AppFlowyAIClient;
 use database::chat;
@@ -20,6 +21,8 @@ use tracing::{info, trace};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::middleware::deadline::apply_statement_timeout;
+
 pub(crate) async fn create_chat(
   pg_pool: &PgPool,
   params: CreateChatParams,
@@ -34,6 +37,7 @@ pub(crate) async fn create_chat(
 
 pub(crate) async fn delete_chat(pg_pool: &PgPool, chat_id: &str) -> Result<(), AppError> {
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   chat::chat_ops::delete_chat(&mut txn, chat_id).await?;
   txn.commit().await?;
   Ok(())
@@ -47,6 +51,7 @@ pub async fn update_chat_message(
   ai_model: &str,
 ) -> Result<(), AppError> {
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   delete_answer_message_by_question_message_id(&mut txn, params.message_id).await?;
   chat::chat_ops::update_chat_message_content(&mut txn, &params).await?;
   txn.commit().await.map_err(|err| {
@@ -105,6 +110,7 @@ pub async fn generate_chat_message_answer(
   info!("new_answer: {:?}", new_answer);
   // Save the answer to the database
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let message = insert_answer_message_with_transaction(
     &mut txn,
     ChatAuthor::ai(),
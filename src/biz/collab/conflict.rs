@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use collab_entity::CollabType;
+use database::collab_rejected_update::{
+  delete_collab_rejected_updates_before, select_collab_rejected_updates,
+};
+use database_entity::dto::{CollabConflict, CollabConflicts};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+const MAX_CONFLICTS_PER_REQUEST: i64 = 50;
+/// Rejected updates are kept this long for their authors to recover them.
+const CONFLICT_RETENTION_DAYS: i64 = 30;
+const CONFLICT_CLEANUP_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Updates of the user that the server rejected for the collab, most recent first. Users only
+/// see their own rejected updates, which may hold content they were not allowed to write.
+pub async fn get_collab_conflicts(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  object_id: &Uuid,
+  uid: i64,
+) -> Result<CollabConflicts, AppError> {
+  let rows = select_collab_rejected_updates(
+    pg_pool,
+    workspace_id,
+    object_id,
+    uid,
+    MAX_CONFLICTS_PER_REQUEST,
+  )
+  .await?;
+  let conflicts = rows
+    .into_iter()
+    .map(|row| CollabConflict {
+      conflict_id: row.conflict_id,
+      object_id: row.object_id,
+      collab_type: CollabType::from(row.collab_type),
+      device_id: row.device_id,
+      reason: row.reason,
+      update_v1: row.update_v1,
+      created_at: row.created_at,
+    })
+    .collect();
+  Ok(CollabConflicts { conflicts })
+}
+
+/// Deletes the rejected updates older than [CONFLICT_RETENTION_DAYS].
+pub async fn start_collab_conflict_cleanup_task(pg_pool: PgPool) {
+  let mut timer = tokio::time::interval(Duration::from_secs(CONFLICT_CLEANUP_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    let expired_before = Utc::now() - chrono::Duration::days(CONFLICT_RETENTION_DAYS);
+    match delete_collab_rejected_updates_before(&pg_pool, expired_before).await {
+      Ok(0) => {},
+      Ok(count) => info!("deleted {} expired collab conflicts", count),
+      Err(err) => error!("failed to delete expired collab conflicts: {:?}", err),
+    }
+  }
+}
@@ -0,0 +1,584 @@
+use collab_document::blocks::{Block, DocumentData};
+use fancy_regex::Regex;
+use indexer::collab_indexer::{block_plain_text, code_block_languages};
+use shared_entity::dto::workspace_dto::{
+  DocumentChunkBlock, DocumentOutlineHeading, DocumentTocItem,
+};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+const HEADING_BLOCK_TYPE: &str = "heading";
+const HEADING_LEVEL_KEY: &str = "level";
+
+/// Matches both `/api/file_storage/{workspace_id}/v1/blob/{parent_dir}/{file_id}` and the
+/// legacy `/api/file_storage/{workspace_id}/blob/{file_id}` urls.
+static BLOB_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r#"/api/file_storage/([0-9a-fA-F-]{36})/(v1/)?blob/([^\s"'?#)\\]+)"#).unwrap()
+});
+
+/// Returns every block below the page block in document order, together with its
+/// nesting depth (0 for top level blocks).
+pub fn flatten_document_blocks(data: &DocumentData) -> Vec<(u32, &Block)> {
+  let mut blocks = vec![];
+  let mut stack: Vec<(u32, &str)> = child_ids(data, &data.page_id)
+    .iter()
+    .rev()
+    .map(|id| (0, id.as_str()))
+    .collect();
+  while let Some((depth, block_id)) = stack.pop() {
+    let block = match data.blocks.get(block_id) {
+      Some(block) => block,
+      None => continue,
+    };
+    blocks.push((depth, block));
+    stack.extend(
+      child_ids(data, block_id)
+        .iter()
+        .rev()
+        .map(|id| (depth + 1, id.as_str())),
+    );
+  }
+  blocks
+}
+
+fn child_ids<'a>(data: &'a DocumentData, block_id: &str) -> &'a [String] {
+  data
+    .blocks
+    .get(block_id)
+    .and_then(|block| data.meta.children_map.get(&block.children))
+    .map(|children| children.as_slice())
+    .unwrap_or_default()
+}
+
+pub fn to_document_chunk_block(
+  data: &DocumentData,
+  depth: u32,
+  block: &Block,
+) -> DocumentChunkBlock {
+  let delta = block
+    .external_id
+    .as_ref()
+    .and_then(|external_id| data.meta.text_map.as_ref()?.get(external_id))
+    .and_then(|delta| serde_json::from_str(delta).ok());
+  DocumentChunkBlock {
+    id: block.id.clone(),
+    ty: block.ty.clone(),
+    parent_id: block.parent.clone(),
+    depth,
+    data: block.data.clone(),
+    delta,
+    children: child_ids(data, &block.id).to_vec(),
+  }
+}
+
+/// Headings of the document in document order. `block_index` refers to the position
+/// of the heading in [flatten_document_blocks].
+pub fn document_headings(data: &DocumentData) -> Vec<DocumentTocItem> {
+  flatten_document_blocks(data)
+    .into_iter()
+    .enumerate()
+    .filter(|(_, (_, block))| block.ty == HEADING_BLOCK_TYPE)
+    .map(|(index, (_, block))| DocumentTocItem {
+      block_id: block.id.clone(),
+      level: block
+        .data
+        .get(HEADING_LEVEL_KEY)
+        .and_then(|level| level.as_u64())
+        .unwrap_or(1) as u32,
+      text: block_plain_text(data, block).unwrap_or_default(),
+      block_index: index as u32,
+    })
+    .collect()
+}
+
+/// Nests the headings of the document: a heading is a child of the closest preceding heading
+/// with a lower level. Every heading gets an anchor derived from its text that is unique
+/// within the document.
+pub fn document_outline(data: &DocumentData) -> Vec<DocumentOutlineHeading> {
+  build_outline(document_headings(data))
+}
+
+fn build_outline(headings: Vec<DocumentTocItem>) -> Vec<DocumentOutlineHeading> {
+  let mut used_anchors = HashMap::new();
+  let mut roots: Vec<DocumentOutlineHeading> = vec![];
+  // Headings whose children are not complete yet, from the outermost to the innermost
+  let mut open: Vec<DocumentOutlineHeading> = vec![];
+  for item in headings {
+    let heading = DocumentOutlineHeading {
+      anchor: unique_anchor(&mut used_anchors, &item.text),
+      block_id: item.block_id,
+      level: item.level,
+      text: item.text,
+      block_index: item.block_index,
+      children: vec![],
+    };
+    while open.last().is_some_and(|last| last.level >= heading.level) {
+      close_outline_heading(&mut open, &mut roots);
+    }
+    open.push(heading);
+  }
+  while !open.is_empty() {
+    close_outline_heading(&mut open, &mut roots);
+  }
+  roots
+}
+
+fn close_outline_heading(
+  open: &mut Vec<DocumentOutlineHeading>,
+  roots: &mut Vec<DocumentOutlineHeading>,
+) {
+  if let Some(heading) = open.pop() {
+    match open.last_mut() {
+      Some(parent) => parent.children.push(heading),
+      None => roots.push(heading),
+    }
+  }
+}
+
+/// Lowercases the text and joins its words with `-`. Repeated anchors get a `-1`, `-2`, ...
+/// suffix, and headings without any letter or digit fall back to `section`.
+fn unique_anchor(used_anchors: &mut HashMap<String, usize>, text: &str) -> String {
+  let slug = text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(|word| word.to_lowercase())
+    .collect::<Vec<_>>()
+    .join("-");
+  let slug = if slug.is_empty() {
+    "section".to_string()
+  } else {
+    slug
+  };
+  let count = used_anchors.entry(slug.clone()).or_insert(0);
+  let anchor = if *count == 0 {
+    slug
+  } else {
+    format!("{}-{}", slug, count)
+  };
+  *count += 1;
+  anchor
+}
+
+/// A link from a document block to a file uploaded through the file storage api.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobReference {
+  pub block_id: String,
+  pub url: String,
+  pub workspace_id: Uuid,
+  /// The `file_id` under which the blob is recorded in `af_blob_metadata`
+  pub metadata_key: String,
+}
+
+/// Collects the blob links of every block in document order. Both the block data (images,
+/// files, covers) and the text delta (inline links) are searched.
+pub fn document_blob_references(data: &DocumentData) -> Vec<BlobReference> {
+  let mut references = vec![];
+  for (_, block) in flatten_document_blocks(data) {
+    let mut sources = vec![serde_json::to_string(&block.data).unwrap_or_default()];
+    if let Some(delta) = block
+      .external_id
+      .as_ref()
+      .and_then(|external_id| data.meta.text_map.as_ref()?.get(external_id))
+    {
+      sources.push(delta.clone());
+    }
+    for source in sources {
+      references.extend(parse_blob_urls(&source).into_iter().map(
+        |(url, workspace_id, metadata_key)| BlobReference {
+          block_id: block.id.clone(),
+          url,
+          workspace_id,
+          metadata_key,
+        },
+      ));
+    }
+  }
+  references
+}
+
+fn parse_blob_urls(text: &str) -> Vec<(String, Uuid, String)> {
+  BLOB_URL_REGEX
+    .captures_iter(text)
+    .filter_map(|captures| {
+      let captures = captures.ok()?;
+      let url = captures.get(0)?.as_str().to_string();
+      let workspace_id = Uuid::parse_str(captures.get(1)?.as_str()).ok()?;
+      let path = captures.get(3)?.as_str();
+      let metadata_key = if captures.get(2).is_some() {
+        let (parent_dir, file_id) = path.split_once('/')?;
+        format!("{}_{}", parent_dir, file_id)
+      } else {
+        path.to_string()
+      };
+      Some((url, workspace_id, metadata_key))
+    })
+    .collect()
+}
+
+/// Text attribute that marks a mention whose target no longer exists, see [DocumentMention].
+pub const DEAD_REFERENCE_ATTRIBUTE: &str = "dead_reference";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MentionTarget {
+  Page,
+  Person,
+}
+
+/// A page or person mention inside the text of a document block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentMention {
+  pub block_id: String,
+  /// Id of the text the mention belongs to
+  pub text_id: String,
+  pub target: MentionTarget,
+  pub target_id: Uuid,
+  /// Position and length of the mention in the text, in UTF-16 code units
+  pub offset: u32,
+  pub len: u32,
+  /// Whether the mention already carries the [DEAD_REFERENCE_ATTRIBUTE]
+  pub annotated: bool,
+}
+
+/// Collects the page and person mentions of every block in document order.
+pub fn document_mentions(data: &DocumentData) -> Vec<DocumentMention> {
+  let mut mentions = vec![];
+  for (_, block) in flatten_document_blocks(data) {
+    let (text_id, delta) = match block.external_id.as_ref().and_then(|external_id| {
+      let delta = data.meta.text_map.as_ref()?.get(external_id)?;
+      Some((external_id, delta))
+    }) {
+      Some(text) => text,
+      None => continue,
+    };
+    mentions.extend(parse_delta_mentions(delta).into_iter().map(
+      |(target, target_id, offset, len, annotated)| DocumentMention {
+        block_id: block.id.clone(),
+        text_id: text_id.clone(),
+        target,
+        target_id,
+        offset,
+        len,
+        annotated,
+      },
+    ));
+  }
+  mentions
+}
+
+fn parse_delta_mentions(delta: &str) -> Vec<(MentionTarget, Uuid, u32, u32, bool)> {
+  let ops = match serde_json::from_str::<Vec<serde_json::Value>>(delta) {
+    Ok(ops) => ops,
+    Err(_) => return vec![],
+  };
+  let mut mentions = vec![];
+  let mut offset = 0;
+  for op in ops {
+    let len = match op.get("insert") {
+      Some(serde_json::Value::String(text)) => text.encode_utf16().count() as u32,
+      Some(_) => 1,
+      None => continue,
+    };
+    let attributes = op.get("attributes");
+    let mention = attributes.and_then(|attributes| attributes.get("mention"));
+    let target = mention.and_then(|mention| {
+      let (target, id_key) = match mention.get("type")?.as_str()? {
+        "page" => (MentionTarget::Page, "page_id"),
+        "person" => (MentionTarget::Person, "person_id"),
+        _ => return None,
+      };
+      let target_id = Uuid::parse_str(mention.get(id_key)?.as_str()?).ok()?;
+      Some((target, target_id))
+    });
+    if let Some((target, target_id)) = target {
+      let annotated = attributes
+        .and_then(|attributes| attributes.get(DEAD_REFERENCE_ATTRIBUTE))
+        .is_some_and(|value| !value.is_null());
+      mentions.push((target, target_id, offset, len, annotated));
+    }
+    offset += len;
+  }
+  mentions
+}
+
+/// Sets the detected language on the code blocks whose author did not choose one, so that
+/// exported documents can be highlighted without guessing the language again.
+pub fn fill_code_block_languages(data: &mut DocumentData) {
+  for code_block in code_block_languages(data) {
+    if !code_block.detected {
+      continue;
+    }
+    if let Some(block) = data.blocks.get_mut(&code_block.block_id) {
+      block.data.insert(
+        "language".to_string(),
+        serde_json::Value::String(code_block.language),
+      );
+    }
+  }
+}
+
+/// Renders the document as Markdown. Blocks without a Markdown equivalent keep their text,
+/// and page mentions become `[[page_id]]` links. Code blocks are fenced with their language,
+/// detected from the code when the author did not choose one.
+pub fn document_to_markdown(data: &DocumentData) -> String {
+  let code_languages: HashMap<String, String> = code_block_languages(data)
+    .into_iter()
+    .map(|code_block| (code_block.block_id, code_block.language))
+    .collect();
+  let mut markdown = String::new();
+  let mut previous_was_list_item = false;
+  let mut numbered_list_index: HashMap<u32, u32> = HashMap::new();
+  for (depth, block) in flatten_document_blocks(data) {
+    let indent = "  ".repeat(depth as usize);
+    let text = block
+      .external_id
+      .as_ref()
+      .and_then(|external_id| data.meta.text_map.as_ref()?.get(external_id))
+      .map(|delta| delta_to_markdown(delta))
+      .unwrap_or_default();
+    let data_str = |key: &str| {
+      block
+        .data
+        .get(key)
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+    };
+    // Numbering restarts in every list, including the nested lists of the next item
+    numbered_list_index.retain(|list_depth, _| *list_depth <= depth);
+    if block.ty != "numbered_list" {
+      numbered_list_index.remove(&depth);
+    }
+    let is_list_item = matches!(
+      block.ty.as_str(),
+      "bulleted_list" | "numbered_list" | "todo_list"
+    );
+    let line = match block.ty.as_str() {
+      HEADING_BLOCK_TYPE => {
+        let level = block
+          .data
+          .get(HEADING_LEVEL_KEY)
+          .and_then(|level| level.as_u64())
+          .unwrap_or(1)
+          .clamp(1, 6) as usize;
+        format!("{} {}", "#".repeat(level), text)
+      },
+      "bulleted_list" => format!("{}- {}", indent, text),
+      "numbered_list" => {
+        let index = numbered_list_index.entry(depth).or_insert(0);
+        *index += 1;
+        format!("{}{}. {}", indent, index, text)
+      },
+      "todo_list" => {
+        let checked = block
+          .data
+          .get("checked")
+          .and_then(|checked| checked.as_bool())
+          .unwrap_or(false);
+        format!("{}- [{}] {}", indent, if checked { "x" } else { " " }, text)
+      },
+      "quote" | "callout" => format!("{}> {}", indent, text),
+      "code" => {
+        let plain_text = block_plain_text(data, block).unwrap_or_default();
+        let language = code_languages
+          .get(&block.id)
+          .map(String::as_str)
+          .unwrap_or_default();
+        format!("```{}\n{}\n```", language, plain_text)
+      },
+      "math_equation" => format!("$$\n{}\n$$", data_str("formula")),
+      "divider" => "---".to_string(),
+      "image" => format!("![]({})", data_str("url")),
+      _ => format!("{}{}", indent, text),
+    };
+    if !markdown.is_empty() {
+      markdown.push_str(if previous_was_list_item && is_list_item {
+        "\n"
+      } else {
+        "\n\n"
+      });
+    }
+    markdown.push_str(&line);
+    previous_was_list_item = is_list_item;
+  }
+  markdown.push('\n');
+  markdown
+}
+
+fn delta_to_markdown(delta: &str) -> String {
+  let ops = match serde_json::from_str::<Vec<serde_json::Value>>(delta) {
+    Ok(ops) => ops,
+    Err(_) => return String::new(),
+  };
+  let mut markdown = String::new();
+  for op in ops {
+    let text = match op.get("insert").and_then(|insert| insert.as_str()) {
+      Some(text) => text,
+      None => continue,
+    };
+    let attributes = op.get("attributes");
+    let attribute = |key: &str| attributes.and_then(|attributes| attributes.get(key));
+    if let Some(mention) = attribute("mention") {
+      let id = |key: &str| {
+        mention
+          .get(key)
+          .and_then(|id| id.as_str())
+          .unwrap_or_default()
+      };
+      match mention.get("type").and_then(|ty| ty.as_str()) {
+        Some("page") => markdown.push_str(&format!("[[{}]]", id("page_id"))),
+        Some("person") => markdown.push_str(&format!("@{}", id("person_name"))),
+        Some("date") => markdown.push_str(id("date")),
+        _ => {},
+      }
+      continue;
+    }
+    let is_set = |key: &str| attribute(key).is_some_and(|value| value.as_bool() == Some(true));
+    let mut formatted = if is_set("code") {
+      format!("`{}`", text)
+    } else {
+      text.to_string()
+    };
+    if is_set("bold") {
+      formatted = format!("**{}**", formatted);
+    }
+    if is_set("italic") {
+      formatted = format!("_{}_", formatted);
+    }
+    if is_set("strikethrough") {
+      formatted = format!("~~{}~~", formatted);
+    }
+    if let Some(href) = attribute("href").and_then(|href| href.as_str()) {
+      formatted = format!("[{}]({})", formatted, href);
+    }
+    markdown.push_str(&formatted);
+  }
+  markdown
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn render_delta_as_markdown() {
+    let page_id = Uuid::new_v4();
+    let delta = format!(
+      r#"[{{"insert":"Read "}},{{"insert":"the docs","attributes":{{"bold":true,"href":"https://example.com"}}}},{{"insert":" and "}},{{"insert":"run","attributes":{{"code":true}}}},{{"insert":"$","attributes":{{"mention":{{"type":"page","page_id":"{page_id}"}}}}}}]"#
+    );
+    assert_eq!(
+      delta_to_markdown(&delta),
+      format!(
+        "Read [**the docs**](https://example.com) and `run`[[{}]]",
+        page_id
+      )
+    );
+    assert_eq!(delta_to_markdown("not a delta"), "");
+  }
+
+  #[test]
+  fn parse_v0_and_v1_blob_urls() {
+    let workspace_id = Uuid::new_v4();
+    let parent_dir = Uuid::new_v4();
+    let text = format!(
+      r#"{{"url":"https://host/api/file_storage/{workspace_id}/v1/blob/{parent_dir}/abc.png"}} [{{"insert":"x","attributes":{{"href":"/api/file_storage/{workspace_id}/blob/def.pdf?x=1"}}}}]"#
+    );
+    let urls = parse_blob_urls(&text);
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[0].1, workspace_id);
+    assert_eq!(urls[0].2, format!("{}_abc.png", parent_dir));
+    assert_eq!(urls[1].2, "def.pdf");
+    assert!(parse_blob_urls("https://example.com/image.png").is_empty());
+  }
+
+  #[test]
+  fn nest_headings_with_unique_anchors() {
+    let heading = |index: u32, level: u32, text: &str| DocumentTocItem {
+      block_id: format!("block-{}", index),
+      level,
+      text: text.to_string(),
+      block_index: index,
+    };
+    let outline = build_outline(vec![
+      heading(0, 2, "Intro"),
+      heading(1, 1, "Setup & Install"),
+      heading(2, 2, "Linux"),
+      heading(3, 3, "Intro"),
+      heading(4, 2, "macOS"),
+      heading(5, 1, "!!!"),
+    ]);
+    let anchors: Vec<&str> = outline.iter().map(|h| h.anchor.as_str()).collect();
+    assert_eq!(anchors, vec!["intro", "setup-install", "section"]);
+    let setup = &outline[1];
+    assert_eq!(setup.children.len(), 2);
+    assert_eq!(setup.children[0].text, "Linux");
+    assert_eq!(setup.children[0].children[0].anchor, "intro-1");
+    assert_eq!(setup.children[1].anchor, "macos");
+  }
+
+  #[test]
+  fn parse_page_and_person_mentions() {
+    let page_id = Uuid::new_v4();
+    let person_id = Uuid::new_v4();
+    let delta = format!(
+      r#"[{{"insert":"日本 "}},{{"insert":"$","attributes":{{"mention":{{"type":"page","page_id":"{page_id}"}}}}}},{{"insert":" and "}},{{"insert":"$","attributes":{{"mention":{{"type":"person","person_id":"{person_id}"}},"dead_reference":"removed_member"}}}},{{"insert":"$","attributes":{{"mention":{{"type":"date","date":"2024-01-01"}}}}}}]"#
+    );
+    let mentions = parse_delta_mentions(&delta);
+    assert_eq!(
+      mentions,
+      vec![
+        (MentionTarget::Page, page_id, 3, 1, false),
+        (MentionTarget::Person, person_id, 9, 1, true),
+      ]
+    );
+    assert!(parse_delta_mentions("not a delta").is_empty());
+  }
+
+  #[test]
+  fn fence_code_blocks_with_their_language() {
+    use collab_document::blocks::DocumentMeta;
+
+    let block = |id: &str, ty: &str, language: Option<&str>| Block {
+      id: id.to_string(),
+      ty: ty.to_string(),
+      parent: "page".to_string(),
+      children: format!("{}_children", id),
+      external_id: Some(format!("text_{}", id)),
+      external_type: Some("text".to_string()),
+      data: language
+        .map(|language| HashMap::from([("language".to_string(), serde_json::json!(language))]))
+        .unwrap_or_default(),
+    };
+    let mut data = DocumentData {
+      page_id: "page".to_string(),
+      blocks: HashMap::from([
+        ("page".to_string(), block("page", "page", None)),
+        ("a".to_string(), block("a", "code", Some("Python"))),
+        ("b".to_string(), block("b", "code", None)),
+      ]),
+      meta: DocumentMeta {
+        children_map: HashMap::from([(
+          "page_children".to_string(),
+          vec!["a".to_string(), "b".to_string()],
+        )]),
+        text_map: Some(HashMap::from([
+          (
+            "text_a".to_string(),
+            r#"[{"insert":"print(1)"}]"#.to_string(),
+          ),
+          (
+            "text_b".to_string(),
+            r#"[{"insert":"SELECT * FROM af_user WHERE uid = 1"}]"#.to_string(),
+          ),
+        ])),
+      },
+    };
+    assert_eq!(
+      document_to_markdown(&data),
+      "```python\nprint(1)\n```\n\n```sql\nSELECT * FROM af_user WHERE uid = 1\n```\n"
+    );
+
+    fill_code_block_languages(&mut data);
+    assert_eq!(data.blocks["a"].data["language"], "Python");
+    assert_eq!(data.blocks["b"].data["language"], "sql");
+  }
+}
@@ -6,8 +6,7 @@ use collab_folder::{
   Folder, SectionItem, SpacePermission, View, ViewLayout as CollabFolderViewLayout,
 };
 use shared_entity::dto::workspace_dto::{
-  self, FavoriteFolderView, FolderView, FolderViewMinimal, RecentFolderView, TrashFolderView,
-  ViewLayout,
+  self, FolderView, FolderViewMinimal, TrashFolderView, ViewLayout,
 };
 use uuid::Uuid;
 
@@ -207,64 +206,16 @@ fn to_folder_view(
     is_locked: view.is_locked,
     extra,
     children,
+    reactions: vec![],
   })
 }
 
-pub fn section_items_to_favorite_folder_view(
-  section_items: &[SectionItem],
-  folder: &Folder,
-  published_view_ids: &HashSet<String>,
-  uid: i64,
-) -> Vec<FavoriteFolderView> {
-  section_items
-    .iter()
-    .filter_map(|section_item| {
-      let view = folder.get_view(&section_item.id, uid);
-      view.map(|v| {
-        let extra = v.extra.as_ref().map(|e| parse_extra_field_as_json(e));
-        let is_pinned = match extra.as_ref() {
-          Some(extra) => extra
-            .get("is_pinned")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false),
-          None => false,
-        };
-        let view_id = v.id.parse().unwrap();
-        let folder_view = FolderView {
-          view_id,
-          parent_view_id: v.parent_view_id.parse().ok(),
-          prev_view_id: get_prev_view_id(folder, &view_id, uid),
-          name: v.name.clone(),
-          icon: v.icon.as_ref().map(|icon| to_dto_view_icon(icon.clone())),
-          is_space: false,
-          is_private: false,
-          is_favorite: v.is_favorite,
-          is_published: published_view_ids.contains(&v.id),
-          created_at: DateTime::from_timestamp(v.created_at, 0).unwrap_or_default(),
-          created_by: v.created_by,
-          last_edited_by: v.last_edited_by,
-          last_edited_time: DateTime::from_timestamp(v.last_edited_time, 0).unwrap_or_default(),
-          layout: to_dto_view_layout(&v.layout),
-          is_locked: v.is_locked,
-          extra,
-          children: vec![],
-        };
-        FavoriteFolderView {
-          view: folder_view,
-          favorited_at: DateTime::from_timestamp(section_item.timestamp, 0).unwrap_or_default(),
-          is_pinned,
-        }
-      })
-    })
-    .collect()
-}
-
-pub fn section_items_to_recent_folder_view(
+pub fn section_items_to_trash_folder_view(
   section_items: &[SectionItem],
   folder: &Folder,
-  published_view_ids: &HashSet<String>,
+  workspace_id: &str,
   uid: i64,
-) -> Vec<RecentFolderView> {
+) -> Vec<TrashFolderView> {
   section_items
     .iter()
     .filter_map(|section_item| {
@@ -279,8 +230,8 @@ pub fn section_items_to_recent_folder_view(
           icon: v.icon.as_ref().map(|icon| to_dto_view_icon(icon.clone())),
           is_space: false,
           is_private: false,
+          is_published: false,
           is_favorite: v.is_favorite,
-          is_published: published_view_ids.contains(&v.id),
           created_at: DateTime::from_timestamp(v.created_at, 0).unwrap_or_default(),
           created_by: v.created_by,
           last_edited_by: v.last_edited_by,
@@ -289,52 +240,33 @@ pub fn section_items_to_recent_folder_view(
           is_locked: v.is_locked,
           extra: v.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
           children: vec![],
+          reactions: vec![],
         };
-        RecentFolderView {
+        // The trash section only holds the views the user moved to the trash
+        TrashFolderView {
           view: folder_view,
-          last_viewed_at: DateTime::from_timestamp(section_item.timestamp, 0).unwrap_or_default(),
+          deleted_at: DateTime::from_timestamp(section_item.timestamp, 0).unwrap_or_default(),
+          deleted_by: Some(uid),
+          parent_path: trash_view_parent_path(folder, &v.parent_view_id, workspace_id, uid),
+          size: 0,
         }
       })
     })
     .collect()
 }
 
-pub fn section_items_to_trash_folder_view(
-  section_items: &[SectionItem],
+fn trash_view_parent_path(
   folder: &Folder,
+  parent_view_id: &str,
+  workspace_id: &str,
   uid: i64,
-) -> Vec<TrashFolderView> {
-  section_items
+) -> Vec<FolderViewMinimal> {
+  get_self_and_ancestor_views(folder, parent_view_id, uid)
+    .unwrap_or_default()
     .iter()
-    .filter_map(|section_item| {
-      let view = folder.get_view(&section_item.id, uid);
-      view.map(|v| {
-        let view_id = v.id.parse().unwrap();
-        let folder_view = FolderView {
-          view_id,
-          parent_view_id: v.parent_view_id.parse().ok(),
-          prev_view_id: get_prev_view_id(folder, &view_id, uid),
-          name: v.name.clone(),
-          icon: v.icon.as_ref().map(|icon| to_dto_view_icon(icon.clone())),
-          is_space: false,
-          is_private: false,
-          is_published: false,
-          is_favorite: v.is_favorite,
-          created_at: DateTime::from_timestamp(v.created_at, 0).unwrap_or_default(),
-          created_by: v.created_by,
-          last_edited_by: v.last_edited_by,
-          last_edited_time: DateTime::from_timestamp(v.last_edited_time, 0).unwrap_or_default(),
-          layout: to_dto_view_layout(&v.layout),
-          is_locked: v.is_locked,
-          extra: v.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
-          children: vec![],
-        };
-        TrashFolderView {
-          view: folder_view,
-          deleted_at: DateTime::from_timestamp(section_item.timestamp, 0).unwrap_or_default(),
-        }
-      })
-    })
+    .rev()
+    .filter(|view| view.id != workspace_id)
+    .map(to_dto_folder_view_miminal)
     .collect()
 }
 
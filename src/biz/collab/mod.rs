@@ -1,4 +1,6 @@
+pub mod conflict;
 pub mod database;
+pub mod document;
 pub mod folder_view;
 pub mod ops;
 pub mod publish_outline;
@@ -1,5 +1,8 @@
 use std::collections::HashMap;
+use std::ops::DerefMut;
 
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
 use app_error::AppError;
 use chrono::DateTime;
 use chrono::Utc;
@@ -23,36 +26,40 @@ use collab_entity::CollabType;
 use collab_entity::EncodedCollab;
 use collab_folder::hierarchy_builder::NestedChildViewBuilder;
 use collab_folder::Folder;
-use collab_folder::SectionItem;
 use collab_folder::{CollabOrigin, SpaceInfo};
 use collab_rt_entity::user::RealtimeUser;
+use database::collab::select_collab_lengths;
+use database::collab::select_existing_collab_oids;
 use database::collab::select_last_updated_database_row_ids;
 use database::collab::select_workspace_database_oid;
 use database::collab::{CollabStore, GetCollabOrigin};
+use database::pg_row::AFViewSection;
 use database::publish::select_published_view_ids_for_workspace;
 use database::publish::select_published_view_ids_with_publish_info_for_workspace;
 use database::publish::select_workspace_id_for_publish_namespace;
+use database::view_metadata::select_view_section_items;
 use database_entity::dto::CollabParams;
 use database_entity::dto::QueryCollab;
 use database_entity::dto::QueryCollabResult;
+use itertools::Itertools;
 
 use shared_entity::dto::workspace_dto::AFDatabase;
 use shared_entity::dto::workspace_dto::AFDatabaseField;
 use shared_entity::dto::workspace_dto::AFDatabaseRow;
 use shared_entity::dto::workspace_dto::AFDatabaseRowDetail;
 use shared_entity::dto::workspace_dto::AFInsertDatabaseField;
+use shared_entity::dto::workspace_dto::CollabAccessCheckItem;
 use shared_entity::dto::workspace_dto::DatabaseRowUpdatedItem;
 use shared_entity::dto::workspace_dto::FavoriteFolderView;
 use shared_entity::dto::workspace_dto::FolderViewMinimal;
 use shared_entity::dto::workspace_dto::PublishedViewInfo;
+use shared_entity::dto::workspace_dto::QueryTrashViews;
 use shared_entity::dto::workspace_dto::RecentFolderView;
-use shared_entity::dto::workspace_dto::TrashFolderView;
+use shared_entity::dto::workspace_dto::TrashSectionItems;
 use sqlx::PgPool;
 use yrs::Map;
 
 use super::folder_view::collab_folder_to_folder_view;
-use super::folder_view::section_items_to_favorite_folder_view;
-use super::folder_view::section_items_to_recent_folder_view;
 use super::folder_view::section_items_to_trash_folder_view;
 use super::folder_view::to_dto_folder_view_miminal;
 use super::publish_outline::collab_folder_to_published_outline;
@@ -73,7 +80,11 @@ use super::utils::DEFAULT_SPACE_ICON_COLOR;
 use crate::api::metrics::AppFlowyWebMetrics;
 use crate::biz::collab::folder_view::check_if_view_is_space;
 use crate::biz::collab::utils::get_database_row_doc_changes;
-use crate::biz::workspace::page_view::update_workspace_folder_data;
+use crate::biz::workspace::page_reaction::attach_page_reactions;
+use crate::biz::workspace::page_view::{update_workspace_folder_data, view_and_descendant_ids};
+use crate::biz::workspace::space_archive::get_archived_object_ids;
+use crate::biz::workspace::view_metadata::{get_section_views, to_folder_view};
+use crate::middleware::deadline::apply_statement_timeout;
 use crate::state::AppState;
 use appflowy_collaborate::ws2::{CollabUpdatePublisher, WorkspaceCollabInstanceCache};
 use collab::core::collab::{default_client_id, CollabOptions};
@@ -83,78 +94,127 @@ use std::collections::HashSet;
 use std::sync::Arc;
 use yrs::block::ClientID;
 
+/// Favorite views of the user, read from the view metadata.
 pub async fn get_user_favorite_folder_views(
   collab_instance_cache: &impl WorkspaceCollabInstanceCache,
   pg_pool: &PgPool,
   uid: i64,
   workspace_id: Uuid,
 ) -> Result<Vec<FavoriteFolderView>, AppError> {
-  let folder = collab_instance_cache.get_folder(workspace_id).await?;
-  let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
-  let publish_view_ids: HashSet<String> = publish_view_ids
-    .into_iter()
-    .map(|id| id.to_string())
-    .collect();
-  let deleted_section_item_ids: Vec<String> = folder
-    .get_my_trash_sections(uid)
-    .iter()
-    .map(|s| s.id.clone())
-    .collect();
-  let favorite_section_items: Vec<SectionItem> = folder
-    .get_my_favorite_sections(uid)
-    .into_iter()
-    .filter(|s| !deleted_section_item_ids.contains(&s.id))
-    .collect();
-  Ok(section_items_to_favorite_folder_view(
-    &favorite_section_items,
-    &folder,
-    &publish_view_ids,
+  let favorite_views = get_section_views(
+    pg_pool,
+    collab_instance_cache,
+    workspace_id,
     uid,
-  ))
+    AFViewSection::Favorite,
+  )
+  .await?;
+  let publish_view_ids: HashSet<Uuid> =
+    select_published_view_ids_for_workspace(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .collect();
+  Ok(
+    favorite_views
+      .into_iter()
+      .map(|(row, favorited_at)| {
+        let is_published = publish_view_ids.contains(&row.view_id);
+        let view = to_folder_view(row, true, is_published);
+        let is_pinned = view
+          .extra
+          .as_ref()
+          .and_then(|extra| extra.get("is_pinned"))
+          .and_then(|is_pinned| is_pinned.as_bool())
+          .unwrap_or(false);
+        FavoriteFolderView {
+          view,
+          favorited_at,
+          is_pinned,
+        }
+      })
+      .collect(),
+  )
 }
 
+/// Recently viewed views of the user, read from the view metadata.
 pub async fn get_user_recent_folder_views(
   collab_instance_cache: &impl WorkspaceCollabInstanceCache,
   pg_pool: &PgPool,
   uid: i64,
   workspace_id: Uuid,
 ) -> Result<Vec<RecentFolderView>, AppError> {
-  let folder = collab_instance_cache.get_folder(workspace_id).await?;
-  let deleted_section_item_ids: Vec<String> = folder
-    .get_my_trash_sections(uid)
-    .iter()
-    .map(|s| s.id.clone())
-    .collect();
-  let recent_section_items: Vec<SectionItem> = folder
-    .get_my_recent_sections(uid)
-    .into_iter()
-    .filter(|s| !deleted_section_item_ids.contains(&s.id))
-    .collect();
-  let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
-  let publish_view_ids: HashSet<String> = publish_view_ids
-    .into_iter()
-    .map(|id| id.to_string())
-    .collect();
-  Ok(section_items_to_recent_folder_view(
-    &recent_section_items,
-    &folder,
-    &publish_view_ids,
+  let recent_views = get_section_views(
+    pg_pool,
+    collab_instance_cache,
+    workspace_id,
     uid,
-  ))
+    AFViewSection::Recent,
+  )
+  .await?;
+  let favorite_view_ids: HashSet<Uuid> =
+    select_view_section_items(pg_pool, &workspace_id, uid, AFViewSection::Favorite)
+      .await?
+      .into_iter()
+      .map(|(view_id, _)| view_id)
+      .collect();
+  let archived_object_ids = get_archived_object_ids(pg_pool, &workspace_id).await?;
+  let publish_view_ids: HashSet<Uuid> =
+    select_published_view_ids_for_workspace(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .collect();
+  Ok(
+    recent_views
+      .into_iter()
+      .filter(|(row, _)| !archived_object_ids.contains(&row.view_id))
+      .map(|(row, last_viewed_at)| {
+        let is_favorite = favorite_view_ids.contains(&row.view_id);
+        let is_published = publish_view_ids.contains(&row.view_id);
+        RecentFolderView {
+          view: to_folder_view(row, is_favorite, is_published),
+          last_viewed_at,
+        }
+      })
+      .collect(),
+  )
 }
 
+const MAX_TRASH_PAGE_SIZE: u32 = 500;
+
+/// Views the user moved to the trash, most recently deleted first.
 pub async fn get_user_trash_folder_views(
   collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  pg_pool: &PgPool,
   uid: i64,
   workspace_id: Uuid,
-) -> Result<Vec<TrashFolderView>, AppError> {
+  query: QueryTrashViews,
+) -> Result<TrashSectionItems, AppError> {
   let folder = collab_instance_cache.get_folder(workspace_id).await?;
-  let section_items = folder.get_my_trash_sections(uid);
-  Ok(section_items_to_trash_folder_view(
-    &section_items,
-    &folder,
-    uid,
-  ))
+  let mut section_items = folder.get_my_trash_sections(uid);
+  section_items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.id.cmp(&b.id)));
+  let total = section_items.len() as i64;
+  let limit = query
+    .limit
+    .map(|limit| limit.clamp(1, MAX_TRASH_PAGE_SIZE) as usize)
+    .unwrap_or(usize::MAX);
+  let section_items: Vec<_> = section_items
+    .into_iter()
+    .skip(query.offset.unwrap_or(0) as usize)
+    .take(limit)
+    .collect();
+  let mut views =
+    section_items_to_trash_folder_view(&section_items, &folder, &workspace_id.to_string(), uid);
+
+  let view_ids: Vec<Vec<Uuid>> = views
+    .iter()
+    .map(|view| view_and_descendant_ids(&folder, &view.view.view_id.to_string(), uid))
+    .collect();
+  let oids: Vec<Uuid> = view_ids.iter().flatten().copied().unique().collect();
+  let lengths = select_collab_lengths(pg_pool, &workspace_id, &oids).await?;
+  for (view, view_ids) in views.iter_mut().zip(view_ids) {
+    view.size = view_ids.iter().filter_map(|id| lengths.get(id)).sum();
+  }
+  Ok(TrashSectionItems { views, total })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -256,7 +316,6 @@ pub async fn get_user_workspace_structure(
   depth: u32,
   root_view_id: &Uuid,
 ) -> Result<FolderView, AppError> {
-  let appflowy_web_metrics = &state.metrics.appflowy_web_metrics;
   let depth_limit = 10;
   if depth > depth_limit {
     return Err(AppError::InvalidRequest(format!(
@@ -264,11 +323,84 @@ pub async fn get_user_workspace_structure(
       depth, depth_limit
     )));
   }
+  let uid = user.uid;
+  let (folder, publish_view_ids) =
+    get_patched_folder_and_publish_view_ids(state, user, workspace_id).await?;
+  let mut folder_view = collab_folder_to_folder_view(
+    workspace_id,
+    root_view_id,
+    &folder,
+    depth,
+    &publish_view_ids,
+    uid,
+  )?;
+  attach_page_reactions(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    std::slice::from_mut(&mut folder_view),
+  )
+  .await?;
+  Ok(folder_view)
+}
+
+/// Same as [get_user_workspace_structure], but builds one tree per root view, in the order of
+/// `root_view_ids`. Roots that are missing, trashed or private to another member are left out,
+/// so a sidebar still loads when one of its spaces was deleted meanwhile.
+pub async fn get_user_workspace_forest(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+  depth: u32,
+  root_view_ids: &[Uuid],
+) -> Result<Vec<FolderView>, AppError> {
+  let depth_limit = 10;
+  if depth > depth_limit {
+    return Err(AppError::InvalidRequest(format!(
+      "Depth {} is too large (limit: {})",
+      depth, depth_limit
+    )));
+  }
+  let root_limit = 50;
+  if root_view_ids.len() > root_limit {
+    return Err(AppError::InvalidRequest(format!(
+      "Too many root views: {} (limit: {})",
+      root_view_ids.len(),
+      root_limit
+    )));
+  }
+  let uid = user.uid;
+  let (folder, publish_view_ids) =
+    get_patched_folder_and_publish_view_ids(state, user, workspace_id).await?;
+  let mut forest: Vec<FolderView> = root_view_ids
+    .iter()
+    .unique()
+    .filter_map(|root_view_id| {
+      collab_folder_to_folder_view(
+        workspace_id,
+        root_view_id,
+        &folder,
+        depth,
+        &publish_view_ids,
+        uid,
+      )
+      .ok()
+    })
+    .collect();
+  attach_page_reactions(&state.pg_pool, &workspace_id, uid, &mut forest).await?;
+  Ok(forest)
+}
+
+async fn get_patched_folder_and_publish_view_ids(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+) -> Result<(Folder, HashSet<Uuid>), AppError> {
   let folder = state.ws_server.get_folder(workspace_id).await?;
   let patched_folder = fix_old_workspace_folder(
-    appflowy_web_metrics,
+    &state.metrics.appflowy_web_metrics,
     &state.ws_server,
-    user.clone(),
+    user,
     folder,
     workspace_id,
   )
@@ -276,15 +408,68 @@ pub async fn get_user_workspace_structure(
 
   let publish_view_ids =
     select_published_view_ids_for_workspace(&state.pg_pool, workspace_id).await?;
-  let publish_view_ids: HashSet<_> = publish_view_ids.into_iter().collect();
-  collab_folder_to_folder_view(
-    workspace_id,
-    root_view_id,
-    &patched_folder,
-    depth,
-    &publish_view_ids,
-    user.uid,
-  )
+  Ok((patched_folder, publish_view_ids.into_iter().collect()))
+}
+
+/// Checks existence and permissions of several collabs at once. A collab can be shared by
+/// workspace members that can write to it; guests can never share.
+pub async fn check_collab_access(
+  pg_pool: &PgPool,
+  collab_access_control: &Arc<dyn CollabAccessControl>,
+  uid: i64,
+  workspace_id: Uuid,
+  is_workspace_member: bool,
+  object_ids: Vec<Uuid>,
+) -> Result<Vec<CollabAccessCheckItem>, AppError> {
+  let max_object_ids = 100;
+  if object_ids.len() > max_object_ids {
+    return Err(AppError::InvalidRequest(format!(
+      "Too many object ids: {} (limit: {})",
+      object_ids.len(),
+      max_object_ids
+    )));
+  }
+  let object_ids: Vec<Uuid> = object_ids.into_iter().unique().collect();
+  let existing: HashSet<Uuid> = select_existing_collab_oids(pg_pool, &workspace_id, &object_ids)
+    .await?
+    .into_iter()
+    .collect();
+
+  let mut items = Vec::with_capacity(object_ids.len());
+  for object_id in object_ids {
+    let mut item = CollabAccessCheckItem {
+      object_id,
+      exists: existing.contains(&object_id),
+      can_read: false,
+      can_write: false,
+      can_share: false,
+    };
+    if item.exists {
+      item.can_read = is_permitted(
+        collab_access_control
+          .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+          .await,
+      )?;
+      if item.can_read {
+        item.can_write = is_permitted(
+          collab_access_control
+            .enforce_action(&workspace_id, &uid, &object_id, Action::Write)
+            .await,
+        )?;
+        item.can_share = item.can_write && is_workspace_member;
+      }
+    }
+    items.push(item);
+  }
+  Ok(items)
+}
+
+fn is_permitted(result: Result<(), AppError>) -> Result<bool, AppError> {
+  match result {
+    Ok(()) => Ok(true),
+    Err(err) if err.is_not_enough_permissions() => Ok(false),
+    Err(err) => Err(err),
+  }
 }
 
 pub async fn get_latest_workspace_database(
@@ -542,6 +727,7 @@ pub async fn insert_database_row(
 
   let collab_storage = state.collab_storage.clone();
   let mut db_txn = state.pg_pool.begin().await?;
+  apply_statement_timeout(db_txn.deref_mut()).await?;
   // handle row document (if provided)
   if let Some((doc_id, created_doc)) = new_row_doc_creation {
     state
@@ -669,6 +855,7 @@ pub async fn upsert_database_row(
   let db_row_ec_v1 = collab_to_bin(db_row_collab, CollabType::DatabaseRow).await?;
   // write to disk and broadcast changes
   let mut db_txn = state.pg_pool.begin().await?;
+  apply_statement_timeout(db_txn.deref_mut()).await?;
   collab_storage
     .upsert_new_collab_with_transaction(
       workspace_uuid,
@@ -972,6 +1159,53 @@ pub async fn list_database_row_details(
   Ok(db_row_details)
 }
 
+/// Details of a single row of the database. The row collab is read on behalf of the user, and
+/// rows that don't belong to the database are reported as not found.
+pub async fn get_database_row_detail(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_uuid: Uuid,
+  database_uuid: Uuid,
+  row_id: Uuid,
+  unsupported_field_types: &[FieldType],
+  with_doc: bool,
+) -> Result<AFDatabaseRowDetail, AppError> {
+  let row_collab = get_latest_collab(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    workspace_uuid,
+    row_id,
+    CollabType::DatabaseRow,
+    default_client_id(),
+  )
+  .await?;
+  let row_database_id = RowDetail::from_collab(&row_collab).map(|detail| detail.row.database_id);
+  if row_database_id != Some(database_uuid.to_string()) {
+    return Err(AppError::RecordNotFound(format!(
+      "row {} not found in database {}",
+      row_id, database_uuid
+    )));
+  }
+
+  list_database_row_details(
+    collab_storage,
+    uid,
+    workspace_uuid,
+    database_uuid,
+    &[row_id],
+    unsupported_field_types,
+    with_doc,
+  )
+  .await?
+  .pop()
+  .ok_or_else(|| {
+    AppError::RecordNotFound(format!(
+      "row {} not found in database {}",
+      row_id, database_uuid
+    ))
+  })
+}
+
 fn fill_in_db_row_doc(
   client_id: ClientID,
   row_detail: &mut AFDatabaseRowDetail,
@@ -20,7 +20,8 @@ use collab_database::rows::RowId;
 use collab_database::rows::RowMetaKey;
 use collab_database::template::timestamp_parse::TimestampCellData;
 use collab_database::workspace_database::WorkspaceDatabaseBody;
-use collab_document::document::Document;
+use collab_document::blocks::DocumentData;
+use collab_document::document::{Document, DocumentBody};
 use collab_document::importer::md_importer::MDImporter;
 use collab_entity::CollabType;
 use collab_entity::EncodedCollab;
@@ -416,6 +417,23 @@ pub fn collab_from_doc_state(
   Ok(collab)
 }
 
+/// Reads the blocks of a document collab without opening it as a [Document].
+pub fn document_data_from_collab(collab: &Collab) -> Result<DocumentData, AppError> {
+  let document_body = DocumentBody::from_collab(collab)
+    .ok_or_else(|| AppError::Internal(anyhow::anyhow!("invalid document collab")))?;
+  document_body
+    .get_document_data(&collab.transact())
+    .map_err(|err| AppError::Internal(anyhow::anyhow!(err.to_string())))
+}
+
+pub fn document_data_from_doc_state(
+  doc_state: Vec<u8>,
+  object_id: &Uuid,
+) -> Result<DocumentData, AppError> {
+  let collab = collab_from_doc_state(doc_state, object_id, default_client_id())?;
+  document_data_from_collab(&collab)
+}
+
 /// Base on values given by [cell_value_by_id], write to fields of DatabaseRowBody.
 /// Returns encoded collab updates to the database row
 #[instrument(level = "debug", skip_all)]
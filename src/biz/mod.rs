@@ -5,6 +5,8 @@ pub mod collab;
 pub mod data_import;
 pub mod notification;
 pub mod pg_listener;
+pub mod pool_metrics;
+pub mod redis_health;
 pub mod search;
 pub mod subscription;
 pub mod template;
@@ -0,0 +1,412 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database::user::{select_name_from_uuid, select_uid_from_email, select_uuid_from_uid};
+use database::workspace::select_published_view_comment_context;
+use database_entity::dto::InboundCommentReplyMail;
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret;
+use sha2::Sha256;
+use sqlx::PgPool;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::biz::workspace::ops::create_comment_on_published_view;
+use crate::config::config::NotificationSetting;
+use crate::mailer::{AFCloudMailer, CommentReplyNotificationMailerParam};
+use crate::state::AppState;
+
+const REPLY_ADDRESS_PREFIX: &str = "reply+";
+/// Reply addresses older than this are rejected.
+const REPLY_TOKEN_TTL_DAYS: i64 = 30;
+/// HMAC truncated to 128 bits, which keeps the local part of the address within 64 characters.
+const REPLY_SIGNATURE_LEN: usize = 16;
+/// Comment id, issue day and signature.
+const REPLY_TOKEN_LEN: usize = 16 + 4 + REPLY_SIGNATURE_LEN;
+/// Lowercase RFC 4648 base32 alphabet.
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const DEFAULT_REPLIER_AVATAR_URL: &str =
+  "https://cdn.pixabay.com/photo/2015/10/05/22/37/blank-profile-picture-973460_1280.png";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Emails the author of `reply_comment_id` about a reply to their comment. The email can be
+/// answered directly when reply-by-mail is configured, see [post_comment_reply_from_email].
+pub async fn notify_comment_reply_by_email(
+  pg_pool: &PgPool,
+  mailer: &AFCloudMailer,
+  setting: &NotificationSetting,
+  appflowy_web_url: &str,
+  reply_comment_id: &Uuid,
+  replier_uuid: &Uuid,
+  content: &str,
+) -> Result<(), AppError> {
+  let comment = match select_published_view_comment_context(pg_pool, reply_comment_id).await? {
+    Some(comment) => comment,
+    None => return Ok(()),
+  };
+  let recipient_email = match comment.author_email {
+    Some(email) if !email.is_empty() => email,
+    _ => return Ok(()),
+  };
+  if select_uuid_from_uid(pg_pool, comment.author_uid).await? == *replier_uuid {
+    return Ok(());
+  }
+
+  let now = Utc::now();
+  let reply_to = reply_address(setting, reply_comment_id, comment.author_uid, now);
+  let param = CommentReplyNotificationMailerParam {
+    page_name: comment.publish_name.clone(),
+    page_url: format!(
+      "{}/{}/{}",
+      appflowy_web_url, comment.namespace, comment.publish_name
+    ),
+    replier_icon_url: DEFAULT_REPLIER_AVATAR_URL.to_string(),
+    replier_name: select_name_from_uuid(pg_pool, replier_uuid).await?,
+    reply_content: content.to_string(),
+    replied_at: now.format("%b %d, %Y, %-I:%M %p (UTC)").to_string(),
+    reply_by_mail: reply_to.is_some() && comment.comments_enabled,
+  };
+  mailer
+    .send_comment_reply_notification(
+      &comment.author_name,
+      &recipient_email,
+      &param,
+      reply_to.as_deref().filter(|_| param.reply_by_mail),
+    )
+    .await?;
+  debug!(
+    "Sent comment reply notification email to {}",
+    recipient_email
+  );
+  Ok(())
+}
+
+/// Posts the reply to a comment notification email as a reply in the comment thread. `body` is
+/// the [InboundCommentReplyMail] forwarded by the inbound mail provider, and `signature` the
+/// hex HMAC-SHA256 of it under the webhook secret. The provider must have authenticated the
+/// sender, and the email must be sent to a reply address that is signed for that sender and not
+/// expired. Returns the published view the reply was posted on.
+pub async fn post_comment_reply_from_email(
+  state: &AppState,
+  body: &[u8],
+  signature: Option<&str>,
+) -> Result<Uuid, AppError> {
+  let pg_pool = &state.pg_pool;
+  let setting = &state.config.notification;
+  let (domain, secret) = reply_by_mail_setting(setting)
+    .ok_or_else(|| AppError::InvalidRequest("replying by email is not enabled".to_string()))?;
+  if !verify_webhook_signature(setting, body, signature) {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  let mail: InboundCommentReplyMail = serde_json::from_slice(body)
+    .map_err(|err| AppError::InvalidRequest(format!("invalid reply email: {}", err)))?;
+  if !mail.sender_authenticated {
+    warn!(
+      "rejected comment reply email from unauthenticated sender {}",
+      mail.sender
+    );
+    return Err(AppError::NotEnoughPermissions);
+  }
+  let (local_part, recipient_domain) = mail_address(&mail.recipient)
+    .rsplit_once('@')
+    .ok_or_else(|| AppError::InvalidRequest("invalid recipient address".to_string()))?;
+  if !recipient_domain.eq_ignore_ascii_case(domain) {
+    return Err(AppError::InvalidRequest(format!(
+      "recipient domain {} does not receive comment replies",
+      recipient_domain
+    )));
+  }
+  let token = parse_reply_token(local_part)
+    .ok_or_else(|| AppError::InvalidRequest("invalid reply address".to_string()))?;
+
+  let sender = mail_address(&mail.sender).to_lowercase();
+  let uid = select_uid_from_email(pg_pool, &sender)
+    .await
+    .map_err(|_| AppError::NotEnoughPermissions)?;
+  if !token.verify(secret, uid, Utc::now()) {
+    warn!(
+      "rejected comment reply email from {} to {}",
+      sender, mail.recipient
+    );
+    return Err(AppError::NotEnoughPermissions);
+  }
+
+  let comment = select_published_view_comment_context(pg_pool, &token.comment_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "comment {} no longer accepts replies",
+        token.comment_id
+      ))
+    })?;
+  let content = strip_quoted_reply(&mail.text);
+  if content.is_empty() {
+    return Err(AppError::InvalidRequest("the reply is empty".to_string()));
+  }
+  let user_uuid = select_uuid_from_uid(pg_pool, uid).await?;
+  create_comment_on_published_view(
+    state,
+    &comment.view_id,
+    &Some(comment.comment_id),
+    &content,
+    &user_uuid,
+  )
+  .await?;
+  Ok(comment.view_id)
+}
+
+/// The domain and the signing secret of the reply addresses. Replies are only offered when the
+/// replies forwarded by the inbound mail provider can be verified as well.
+fn reply_by_mail_setting(setting: &NotificationSetting) -> Option<(&str, &str)> {
+  let domain = setting.comment_reply_domain.as_deref()?;
+  let secret = setting.comment_reply_secret.expose_secret();
+  let webhook_secret = setting.comment_reply_webhook_secret.expose_secret();
+  (!secret.is_empty() && !webhook_secret.is_empty()).then_some((domain, secret.as_str()))
+}
+
+fn verify_webhook_signature(
+  setting: &NotificationSetting,
+  body: &[u8],
+  signature: Option<&str>,
+) -> bool {
+  let webhook_secret = setting.comment_reply_webhook_secret.expose_secret();
+  let signature = match signature.and_then(|signature| hex::decode(signature.trim()).ok()) {
+    Some(signature) => signature,
+    None => return false,
+  };
+  let mut mac =
+    HmacSha256::new_from_slice(webhook_secret.as_bytes()).expect("HMAC accepts keys of any size");
+  mac.update(body);
+  mac.verify_slice(&signature).is_ok()
+}
+
+/// `reply+<token>@<domain>`, or `None` when reply-by-mail is not configured.
+fn reply_address(
+  setting: &NotificationSetting,
+  comment_id: &Uuid,
+  uid: i64,
+  now: DateTime<Utc>,
+) -> Option<String> {
+  let (domain, secret) = reply_by_mail_setting(setting)?;
+  let token = ReplyToken::new(secret, *comment_id, uid, now);
+  Some(format!("{}{}@{}", REPLY_ADDRESS_PREFIX, token, domain))
+}
+
+/// Identifies the comment to reply to. The token is bound to the user it was sent to by its
+/// signature only, so that it fits in the local part of an email address; the user is found
+/// again from the sender of the reply. Base32 keeps the token intact when mail servers change
+/// the case of addresses.
+struct ReplyToken {
+  comment_id: Uuid,
+  issued_day: u32,
+  signature: [u8; REPLY_SIGNATURE_LEN],
+}
+
+impl ReplyToken {
+  fn new(secret: &str, comment_id: Uuid, uid: i64, now: DateTime<Utc>) -> Self {
+    let issued_day = day_number(now);
+    Self {
+      comment_id,
+      issued_day,
+      signature: sign(secret, &comment_id, uid, issued_day),
+    }
+  }
+
+  fn verify(&self, secret: &str, uid: i64, now: DateTime<Utc>) -> bool {
+    let age_days = i64::from(day_number(now)) - i64::from(self.issued_day);
+    (0..=REPLY_TOKEN_TTL_DAYS).contains(&age_days)
+      && reply_mac(secret, &self.comment_id, uid, self.issued_day)
+        .verify_truncated_left(&self.signature)
+        .is_ok()
+  }
+}
+
+impl std::fmt::Display for ReplyToken {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut bytes = Vec::with_capacity(REPLY_TOKEN_LEN);
+    bytes.extend_from_slice(self.comment_id.as_bytes());
+    bytes.extend_from_slice(&self.issued_day.to_be_bytes());
+    bytes.extend_from_slice(&self.signature);
+    f.write_str(&base32_encode(&bytes))
+  }
+}
+
+fn parse_reply_token(local_part: &str) -> Option<ReplyToken> {
+  let local_part = local_part.to_ascii_lowercase();
+  let token = local_part.strip_prefix(REPLY_ADDRESS_PREFIX)?;
+  let bytes = base32_decode(token)?;
+  if bytes.len() != REPLY_TOKEN_LEN {
+    return None;
+  }
+  let comment_id = Uuid::from_slice(&bytes[..16]).ok()?;
+  let issued_day = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+  let signature = bytes[20..].try_into().ok()?;
+  Some(ReplyToken {
+    comment_id,
+    issued_day,
+    signature,
+  })
+}
+
+/// Unpadded base32, in lowercase.
+fn base32_encode(bytes: &[u8]) -> String {
+  let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+  let (mut buffer, mut bits) = (0u32, 0u32);
+  for byte in bytes {
+    buffer = (buffer << 8) | u32::from(*byte);
+    bits += 8;
+    while bits >= 5 {
+      bits -= 5;
+      encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+    }
+  }
+  if bits > 0 {
+    encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+  }
+  encoded
+}
+
+/// Decodes [base32_encode]. Returns `None` when `encoded` is not lowercase base32.
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+  let mut bytes = Vec::with_capacity(encoded.len() * 5 / 8);
+  let (mut buffer, mut bits) = (0u32, 0u32);
+  for c in encoded.bytes() {
+    let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u32;
+    buffer = (buffer << 5) | value;
+    bits += 5;
+    if bits >= 8 {
+      bits -= 8;
+      bytes.push((buffer >> bits) as u8);
+    }
+  }
+  // Leftover bits are padding, and must be zero
+  (buffer & ((1 << bits) - 1) == 0).then_some(bytes)
+}
+
+fn day_number(time: DateTime<Utc>) -> u32 {
+  (time.timestamp().max(0) / 86_400) as u32
+}
+
+fn reply_mac(secret: &str, comment_id: &Uuid, uid: i64, issued_day: u32) -> HmacSha256 {
+  let mut mac =
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+  mac.update(comment_id.as_bytes());
+  mac.update(&uid.to_be_bytes());
+  mac.update(&issued_day.to_be_bytes());
+  mac
+}
+
+fn sign(secret: &str, comment_id: &Uuid, uid: i64, issued_day: u32) -> [u8; REPLY_SIGNATURE_LEN] {
+  let digest = reply_mac(secret, comment_id, uid, issued_day)
+    .finalize()
+    .into_bytes();
+  let mut signature = [0u8; REPLY_SIGNATURE_LEN];
+  signature.copy_from_slice(&digest[..REPLY_SIGNATURE_LEN]);
+  signature
+}
+
+/// `Name <user@example.com>` becomes `user@example.com`.
+fn mail_address(address: &str) -> &str {
+  let address = address.trim();
+  match (address.rfind('<'), address.rfind('>')) {
+    (Some(start), Some(end)) if start < end => address[start + 1..end].trim(),
+    _ => address,
+  }
+}
+
+/// Keeps what the user wrote: the quoted original email, from its attribution line such as
+/// `On <date>, <name> wrote:`, and the signature are removed.
+fn strip_quoted_reply(text: &str) -> String {
+  let mut lines = vec![];
+  for line in text.lines() {
+    let trimmed = line.trim();
+    let is_attribution = trimmed.ends_with("wrote:") && trimmed.starts_with("On ");
+    if trimmed.starts_with('>')
+      || is_attribution
+      || trimmed == "--"
+      || trimmed.starts_with("-----Original Message-----")
+      || trimmed.starts_with("________________________________")
+    {
+      break;
+    }
+    lines.push(line.trim_end());
+  }
+  lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SECRET: &str = "comment-reply-secret";
+
+  #[test]
+  fn reply_token_round_trip() {
+    let now = Utc::now();
+    let comment_id = Uuid::new_v4();
+    let token = ReplyToken::new(SECRET, comment_id, 42, now).to_string();
+    let local_part = format!("{}{}", REPLY_ADDRESS_PREFIX, token);
+    assert!(local_part.len() <= 64);
+
+    let parsed = parse_reply_token(&local_part.to_uppercase()).unwrap();
+    assert_eq!(parsed.comment_id, comment_id);
+    assert!(parsed.verify(SECRET, 42, now));
+    assert!(!parsed.verify(SECRET, 43, now));
+    assert!(!parsed.verify("another-secret", 42, now));
+    assert!(!parsed.verify(
+      SECRET,
+      42,
+      now + chrono::Duration::days(REPLY_TOKEN_TTL_DAYS + 1)
+    ));
+
+    let mut tampered = local_part.clone();
+    tampered.replace_range(6..7, if &local_part[6..7] == "a" { "b" } else { "a" });
+    assert!(!parse_reply_token(&tampered)
+      .unwrap()
+      .verify(SECRET, 42, now));
+    assert!(parse_reply_token("reply+not-a-token").is_none());
+  }
+
+  #[test]
+  fn base32_round_trip() {
+    for len in 0..12 {
+      let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 11) as u8).collect();
+      assert_eq!(base32_decode(&base32_encode(&bytes)).unwrap(), bytes);
+    }
+    assert_eq!(base32_encode(b"foobar"), "mzxw6ytboi");
+    assert!(base32_decode("mzxw6ytbo1").is_none());
+  }
+
+  #[test]
+  fn webhook_signature() {
+    let setting = NotificationSetting {
+      email_notification_interval_secs: 0,
+      email_notification_grace_period_secs: 0,
+      comment_reply_domain: Some("reply.example.com".to_string()),
+      comment_reply_secret: SECRET.to_string().into(),
+      comment_reply_webhook_secret: "webhook-secret".to_string().into(),
+    };
+    let body = br#"{"recipient":"reply+x@reply.example.com"}"#;
+    let mut mac = HmacSha256::new_from_slice(b"webhook-secret").unwrap();
+    mac.update(body);
+    let signature = hex::encode(mac.finalize().into_bytes());
+    assert!(verify_webhook_signature(&setting, body, Some(&signature)));
+    assert!(!verify_webhook_signature(&setting, b"{}", Some(&signature)));
+    assert!(!verify_webhook_signature(&setting, body, Some("00")));
+    assert!(!verify_webhook_signature(&setting, body, None));
+  }
+
+  #[test]
+  fn strip_quoted_text_from_reply() {
+    let text = "Sounds good,\nlet's ship it.\n\nOn Mon, Oct 12, 2026 at 9:00 AM AppFlowy <notify@appflowy.io> wrote:\n> Alice replied to your comment\n";
+    assert_eq!(strip_quoted_reply(text), "Sounds good,\nlet's ship it.");
+    assert_eq!(strip_quoted_reply("Thanks!\n-- \nBob\n"), "Thanks!");
+    assert_eq!(strip_quoted_reply("> only quoted"), "");
+  }
+
+  #[test]
+  fn extract_mail_address() {
+    assert_eq!(mail_address("Bob <Bob@Example.com>"), "Bob@Example.com");
+    assert_eq!(mail_address(" bob@example.com "), "bob@example.com");
+  }
+}
@@ -7,11 +7,14 @@ use database_entity::dto::ProcessedPageMentionNotification;
 use sqlx::PgPool;
 use tokio::time::interval;
 
+use crate::config::runtime::RuntimeConfig;
 use crate::mailer::{AFCloudMailer, PageMentionNotificationMailerParam};
 
 pub struct EmailNotificationWorker {
   pub pg_pool: PgPool,
   pub mailer: AFCloudMailer,
+  /// Emails are only sent while the runtime settings enable them
+  pub runtime_config: RuntimeConfig,
   pub notification_interval_seconds: u64,
   pub notification_grace_period_seconds: u64,
   pub appflowy_web_url: String,
@@ -21,6 +24,7 @@ impl EmailNotificationWorker {
   pub fn new(
     pg_pool: PgPool,
     mailer: AFCloudMailer,
+    runtime_config: RuntimeConfig,
     notification_interval_seconds: u64,
     notification_grace_period_seconds: u64,
     appflowy_web_url: &str,
@@ -28,6 +32,7 @@ impl EmailNotificationWorker {
     Self {
       pg_pool,
       mailer,
+      runtime_config,
       notification_interval_seconds,
       notification_grace_period_seconds,
       appflowy_web_url: appflowy_web_url.to_string(),
@@ -39,7 +44,9 @@ impl EmailNotificationWorker {
 
     loop {
       interval.tick().await;
-      self.send_page_notification_emails().await;
+      if self.runtime_config.load().enable_email_notification {
+        self.send_page_notification_emails().await;
+      }
     }
   }
 
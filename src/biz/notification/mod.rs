@@ -1,2 +1,5 @@
+pub mod comment_reply;
 pub mod email;
 pub mod ops;
+pub mod push;
+pub mod quiet_hours;
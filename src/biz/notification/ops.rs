@@ -1,9 +1,24 @@
 use anyhow::Context;
 use app_error::AppError;
-use database::pg_row::AFNotificationRow;
+use chrono::Utc;
+use database::notification::{
+  notify_notification, select_inbox_notifications, update_notifications_read,
+};
+use database::pg_row::{AFNotificationInboxRow, AFNotificationRow};
+use database_entity::dto::{
+  ListUserNotificationsQuery, NotificationsRead, UserNotification, UserNotifications,
+  NOTIFICATIONS_READ_NOTIFICATION, UNLISTED_NOTIFICATIONS,
+};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+const DEFAULT_INBOX_PAGE_SIZE: u32 = 50;
+const MAX_INBOX_PAGE_SIZE: u32 = 200;
+const MAX_READ_NOTIFICATIONS: usize = 500;
+/// Read notifications announced by one [NOTIFICATIONS_READ_NOTIFICATION], which keeps its
+/// payload well below the 8000 bytes limit of `pg_notify`.
+const READ_NOTIFICATIONS_PER_ANNOUNCEMENT: usize = 100;
+
 pub async fn create_workspace_notification(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
@@ -15,7 +30,8 @@ pub async fn create_workspace_notification(
     "[notification] inserting: type={}, workspace={}, recipient={:?}",
     notification_type, workspace_id, recipient_uid
   );
-  // Insert a notification row; the DB trigger will emit a pg_notify so realtime workers / listeners can pick it up.
+  // Insert a notification row; the DB trigger will emit a pg_notify so realtime workers / listeners
+  // and the push notification dispatcher can pick it up.
   sqlx::query!(
     r#"
     INSERT INTO af_notification (workspace_id, notification_type, payload, recipient_uid)
@@ -37,6 +53,84 @@ pub async fn create_workspace_notification(
   Ok(())
 }
 
+/// Inserts the same notification for every member of the workspace but `except_uid`, in a
+/// single statement.
+pub async fn create_workspace_member_notifications(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  notification_type: &str,
+  payload_json: &serde_json::Value,
+  except_uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+    INSERT INTO af_notification (workspace_id, notification_type, payload, recipient_uid)
+    SELECT workspace_id, $2, $3, uid
+    FROM af_workspace_member
+    WHERE workspace_id = $1 AND uid <> $4
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(notification_type)
+  .bind(payload_json)
+  .bind(except_uid)
+  .execute(pg_pool)
+  .await
+  .context("Insert workspace member notification rows")?;
+  Ok(())
+}
+
+/// Inserts one notification per recipient, each with its own payload, in a single statement.
+pub async fn create_workspace_notifications(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  notification_type: &str,
+  notifications: Vec<(i64, serde_json::Value)>,
+) -> Result<(), AppError> {
+  if notifications.is_empty() {
+    return Ok(());
+  }
+  let (recipient_uids, payloads): (Vec<i64>, Vec<serde_json::Value>) =
+    notifications.into_iter().unzip();
+  sqlx::query(
+    r#"
+    INSERT INTO af_notification (workspace_id, notification_type, payload, recipient_uid)
+    SELECT $1, $2, payload, recipient_uid
+    FROM UNNEST($3::jsonb[], $4::bigint[]) AS n(payload, recipient_uid)
+    "#,
+  )
+  .bind(workspace_id)
+  .bind(notification_type)
+  .bind(payloads)
+  .bind(recipient_uids)
+  .execute(pg_pool)
+  .await
+  .context("Insert notification rows")?;
+  Ok(())
+}
+
+/// Notification that doesn't belong to a workspace, e.g. a device handoff.
+pub async fn create_user_notification(
+  pg_pool: &PgPool,
+  notification_type: &str,
+  payload_json: &serde_json::Value,
+  recipient_uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    r#"
+    INSERT INTO af_notification (workspace_id, notification_type, payload, recipient_uid)
+    VALUES (NULL, $1, $2, $3)
+    "#,
+  )
+  .bind(notification_type)
+  .bind(payload_json)
+  .bind(recipient_uid)
+  .execute(pg_pool)
+  .await
+  .context("Insert user notification row")?;
+  Ok(())
+}
+
 /// 查询指定用户的未处理通知（在 WebSocket 重连时补发）
 pub async fn get_pending_notifications(
   pg_pool: &PgPool,
@@ -82,4 +176,96 @@ pub async fn mark_notifications_processed(
   Ok(())
 }
 
+/// 从通知 payload 中提取标题
+pub fn extract_title_from_payload(payload: &serde_json::Value) -> String {
+  payload
+    .get("title")
+    .and_then(|v| v.as_str())
+    .unwrap_or("系统通知")
+    .to_string()
+}
+
+/// 从通知 payload 中提取消息内容
+pub fn extract_message_from_payload(payload: &serde_json::Value) -> String {
+  payload
+    .get("message")
+    .and_then(|v| v.as_str())
+    .unwrap_or("")
+    .to_string()
+}
+
+pub async fn list_inbox_notifications(
+  pg_pool: &PgPool,
+  uid: i64,
+  query: ListUserNotificationsQuery,
+) -> Result<UserNotifications, AppError> {
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_INBOX_PAGE_SIZE)
+    .clamp(1, MAX_INBOX_PAGE_SIZE) as usize;
+  // One more row than requested tells whether there is another page
+  let mut rows = select_inbox_notifications(
+    pg_pool,
+    uid,
+    query.before,
+    limit as i64 + 1,
+    &UNLISTED_NOTIFICATIONS,
+  )
+  .await?;
+  let has_more = rows.len() > limit;
+  rows.truncate(limit);
+  Ok(UserNotifications {
+    notifications: rows.into_iter().map(to_user_notification).collect(),
+    has_more,
+  })
+}
 
+/// Marks the notifications as read, and lets the other connected devices of the user know
+/// through a [NOTIFICATIONS_READ_NOTIFICATION]. It is not stored: devices that are offline get
+/// the read state with the inbox. It has neither title nor message, so it is never pushed.
+pub async fn mark_notifications_read(
+  pg_pool: &PgPool,
+  uid: i64,
+  params: NotificationsRead,
+) -> Result<(), AppError> {
+  if params.notification_ids.len() > MAX_READ_NOTIFICATIONS {
+    return Err(AppError::InvalidRequest(format!(
+      "at most {} notifications can be marked as read at once",
+      MAX_READ_NOTIFICATIONS
+    )));
+  }
+  let notification_ids = update_notifications_read(pg_pool, uid, &params.notification_ids).await?;
+  for notification_ids in notification_ids.chunks(READ_NOTIFICATIONS_PER_ANNOUNCEMENT) {
+    let payload = serde_json::to_value(NotificationsRead {
+      notification_ids: notification_ids.to_vec(),
+    })
+    .context("Serialize read notifications")?;
+    notify_notification(
+      pg_pool,
+      AFNotificationRow {
+        id: Uuid::new_v4(),
+        workspace_id: None,
+        notification_type: NOTIFICATIONS_READ_NOTIFICATION.to_string(),
+        payload,
+        recipient_uid: Some(uid),
+        created_at: Utc::now(),
+        processed: true,
+      },
+    )
+    .await?;
+  }
+  Ok(())
+}
+
+fn to_user_notification(row: AFNotificationInboxRow) -> UserNotification {
+  UserNotification {
+    id: row.id,
+    workspace_id: row.workspace_id,
+    title: extract_title_from_payload(&row.payload),
+    message: extract_message_from_payload(&row.payload),
+    notification_type: row.notification_type,
+    payload: row.payload,
+    created_at: row.created_at,
+    read: row.read_at.is_some(),
+  }
+}
@@ -0,0 +1,655 @@
+use std::ops::DerefMut;
+use std::time::{Duration, Instant};
+
+use app_error::AppError;
+use chrono::Utc;
+use database::pg_row::{AFNotificationRow, AFSystemNotification};
+use database::push_token::{
+  claim_notification_push, delete_orphan_deferred_pushes, delete_push_token, delete_push_tokens,
+  delete_stale_push_tokens, insert_deferred_push, select_push_tokens,
+  select_push_tokens_with_deferred_pushes, take_deferred_pushes, upsert_push_token, AFDeferredPush,
+  AFPushTokenRow,
+};
+use database_entity::dto::{PushPlatform, RegisterPushTokenParams};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use secrecy::ExposeSecret;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::quiet_hours::{summarize_deferred_pushes, QuietWindow};
+use crate::biz::user::device_handoff::is_notification_for_device;
+use crate::config::config::{ApnsSetting, FcmSetting, PushNotificationSetting};
+use crate::middleware::deadline::apply_statement_timeout;
+
+const MAX_PUSH_TOKEN_LENGTH: usize = 4096;
+const PUSH_TOKEN_CLEANUP_INTERVAL_SECS: u64 = 24 * 60 * 60;
+/// How often the notifications held back by quiet hours that ended are sent
+const DEFERRED_PUSH_INTERVAL_SECS: u64 = 60;
+const DEFAULT_PUSH_TITLE: &str = "PonyNotes";
+/// Apple rejects provider tokens older than an hour, and refreshing them more than once every
+/// 20 minutes.
+const APNS_AUTH_TOKEN_TTL: Duration = Duration::from_secs(50 * 60);
+const FCM_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+pub async fn register_push_token(
+  pg_pool: &PgPool,
+  uid: i64,
+  device_id: &str,
+  params: RegisterPushTokenParams,
+) -> Result<(), AppError> {
+  let token = params.token.trim();
+  if token.is_empty() || token.len() > MAX_PUSH_TOKEN_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "push token must be between 1 and {} characters",
+      MAX_PUSH_TOKEN_LENGTH
+    )));
+  }
+  let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  upsert_push_token(&mut txn, uid, device_id, params.platform, token).await?;
+  txn.commit().await?;
+  Ok(())
+}
+
+/// Stops push notifications to the device, for example when the user signs out of it.
+pub async fn unregister_push_token(
+  pg_pool: &PgPool,
+  uid: i64,
+  device_id: &str,
+) -> Result<(), AppError> {
+  delete_push_token(pg_pool, uid, device_id).await?;
+  Ok(())
+}
+
+pub async fn start_push_token_cleanup_task(pg_pool: PgPool, stale_token_days: i64) {
+  let mut timer = tokio::time::interval(Duration::from_secs(PUSH_TOKEN_CLEANUP_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    let stale_before = Utc::now() - chrono::Duration::days(stale_token_days);
+    match delete_stale_push_tokens(&pg_pool, stale_before).await {
+      Ok(0) => {},
+      Ok(count) => info!("deleted {} stale push tokens", count),
+      Err(err) => error!("failed to delete stale push tokens: {:?}", err),
+    }
+    if let Err(err) = delete_orphan_deferred_pushes(&pg_pool).await {
+      error!(
+        "failed to delete deferred pushes of removed devices: {:?}",
+        err
+      );
+    }
+  }
+}
+
+/// What a push service answered for one token.
+#[derive(Debug, PartialEq, Eq)]
+enum PushOutcome {
+  Delivered,
+  /// The token is not valid anymore, usually because the app was uninstalled
+  InvalidToken,
+  Failed,
+}
+
+#[derive(Debug, PartialEq)]
+struct PushMessage {
+  title: String,
+  body: String,
+  notification_id: Uuid,
+  notification_type: String,
+  workspace_id: Option<Uuid>,
+}
+
+impl PushMessage {
+  /// Notifications are shown with the `title` and `message` of their payload. Notifications
+  /// with neither are only meant for the apps and are not pushed.
+  fn from_notification(notification: &AFNotificationRow) -> Option<Self> {
+    let text = |key: &str| {
+      notification
+        .payload
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+    };
+    let (title, body) = match (text("title"), text("message")) {
+      (None, None) => return None,
+      (title, body) => (
+        title.unwrap_or_else(|| DEFAULT_PUSH_TITLE.to_string()),
+        body.unwrap_or_default(),
+      ),
+    };
+    Some(Self {
+      title,
+      body,
+      notification_id: notification.id,
+      notification_type: notification.notification_type.clone(),
+      workspace_id: notification.workspace_id,
+    })
+  }
+
+  fn to_deferred(&self) -> AFDeferredPush {
+    AFDeferredPush {
+      notification_id: self.notification_id,
+      notification_type: self.notification_type.clone(),
+      workspace_id: self.workspace_id,
+      title: self.title.clone(),
+      body: self.body.clone(),
+    }
+  }
+}
+
+impl From<AFDeferredPush> for PushMessage {
+  fn from(push: AFDeferredPush) -> Self {
+    Self {
+      title: push.title,
+      body: push.body,
+      notification_id: push.notification_id,
+      notification_type: push.notification_type,
+      workspace_id: push.workspace_id,
+    }
+  }
+}
+
+/// Pushes the notifications created with
+/// [create_workspace_notification](super::ops::create_workspace_notification) to the mobile
+/// devices of their recipient. Notifications without a recipient are only delivered in-app.
+/// During the quiet hours of a device, its notifications are held back and sent as a summary
+/// once the quiet hours end.
+pub struct PushNotificationDispatcher {
+  pg_pool: PgPool,
+  apns: Option<ApnsSender>,
+  fcm: Option<FcmSender>,
+}
+
+impl PushNotificationDispatcher {
+  pub fn new(pg_pool: PgPool, setting: &PushNotificationSetting) -> Self {
+    let apns = setting
+      .apns
+      .as_ref()
+      .and_then(|setting| match ApnsSender::new(setting) {
+        Ok(sender) => Some(sender),
+        Err(err) => {
+          error!("APNs push notifications are disabled: {}", err);
+          None
+        },
+      });
+    let fcm = setting
+      .fcm
+      .as_ref()
+      .and_then(|setting| match FcmSender::new(setting) {
+        Ok(sender) => Some(sender),
+        Err(err) => {
+          error!("FCM push notifications are disabled: {}", err);
+          None
+        },
+      });
+    Self { pg_pool, apns, fcm }
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.apns.is_some() || self.fcm.is_some()
+  }
+
+  pub async fn run(self, mut notifications: broadcast::Receiver<AFSystemNotification>) {
+    let mut deferred_timer =
+      tokio::time::interval(Duration::from_secs(DEFERRED_PUSH_INTERVAL_SECS));
+    loop {
+      let notification = tokio::select! {
+        notification = notifications.recv() => notification,
+        _ = deferred_timer.tick() => {
+          if let Err(err) = self.send_deferred_pushes().await {
+            error!("failed to send deferred push notifications: {:?}", err);
+          }
+          continue;
+        },
+      };
+      let notification = match notification {
+        Ok(notification) => notification,
+        Err(broadcast::error::RecvError::Lagged(n)) => {
+          warn!(
+            "push notification dispatcher lagged, skipped {} notifications",
+            n
+          );
+          continue;
+        },
+        Err(broadcast::error::RecvError::Closed) => break,
+      };
+      if let Some(row) = notification.payload.as_ref() {
+        if let Err(err) = self.dispatch(row).await {
+          error!("failed to push notification {}: {:?}", row.id, err);
+        }
+      }
+    }
+  }
+
+  async fn dispatch(&self, notification: &AFNotificationRow) -> Result<(), AppError> {
+    let uid = match notification.recipient_uid {
+      Some(uid) => uid,
+      None => return Ok(()),
+    };
+    let message = match PushMessage::from_notification(notification) {
+      Some(message) => message,
+      None => return Ok(()),
+    };
+    let tokens: Vec<_> = select_push_tokens(&self.pg_pool, uid)
+      .await?
+      .into_iter()
+      .filter(|token| {
+        is_notification_for_device(
+          &notification.notification_type,
+          &notification.payload,
+          &token.device_id,
+        )
+      })
+      .collect();
+    if tokens.is_empty() || !claim_notification_push(&self.pg_pool, &notification.id).await? {
+      return Ok(());
+    }
+
+    let now = Utc::now();
+    let mut invalid_tokens: Vec<(PushPlatform, String)> = vec![];
+    for token in tokens {
+      if QuietWindow::of_device(&token)
+        .is_some_and(|quiet| quiet.holds_back(&notification.notification_type, now))
+      {
+        insert_deferred_push(&self.pg_pool, uid, &token.device_id, &message.to_deferred()).await?;
+        continue;
+      }
+      if let Some(invalid_token) = self.send(&token, &message).await {
+        invalid_tokens.push(invalid_token);
+      }
+    }
+    self.prune_tokens(invalid_tokens).await
+  }
+
+  /// Sends the notifications held back on the devices whose quiet hours ended.
+  async fn send_deferred_pushes(&self) -> Result<(), AppError> {
+    let now = Utc::now();
+    let mut invalid_tokens: Vec<(PushPlatform, String)> = vec![];
+    for token in select_push_tokens_with_deferred_pushes(&self.pg_pool).await? {
+      if QuietWindow::of_device(&token).is_some_and(|quiet| quiet.is_quiet(now)) {
+        continue;
+      }
+      let pushes = take_deferred_pushes(&self.pg_pool, token.uid, &token.device_id).await?;
+      if let Some(summary) = summarize_deferred_pushes(pushes) {
+        if let Some(invalid_token) = self.send(&token, &summary.into()).await {
+          invalid_tokens.push(invalid_token);
+        }
+      }
+    }
+    self.prune_tokens(invalid_tokens).await
+  }
+
+  /// Returns the token when the push service reports it as invalid.
+  async fn send(
+    &self,
+    token: &AFPushTokenRow,
+    message: &PushMessage,
+  ) -> Option<(PushPlatform, String)> {
+    let platform = match token.platform.parse::<PushPlatform>() {
+      Ok(platform) => platform,
+      Err(err) => {
+        warn!("skipping push token of device {}: {}", token.device_id, err);
+        return None;
+      },
+    };
+    let outcome = match (platform, &self.apns, &self.fcm) {
+      (PushPlatform::Apns, Some(apns), _) => apns.send(&token.token, message).await,
+      (PushPlatform::Fcm, _, Some(fcm)) => fcm.send(&token.token, message).await,
+      _ => return None,
+    };
+    (outcome == PushOutcome::InvalidToken).then(|| (platform, token.token.clone()))
+  }
+
+  async fn prune_tokens(
+    &self,
+    invalid_tokens: Vec<(PushPlatform, String)>,
+  ) -> Result<(), AppError> {
+    for platform in [PushPlatform::Apns, PushPlatform::Fcm] {
+      let tokens: Vec<String> = invalid_tokens
+        .iter()
+        .filter(|(p, _)| *p == platform)
+        .map(|(_, token)| token.clone())
+        .collect();
+      if !tokens.is_empty() {
+        let count = delete_push_tokens(&self.pg_pool, platform, &tokens).await?;
+        info!("pruned {} invalid {} push tokens", count, platform.as_str());
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Serialize)]
+struct ApnsClaims<'a> {
+  iss: &'a str,
+  iat: i64,
+}
+
+struct ApnsSender {
+  client: reqwest::Client,
+  base_url: &'static str,
+  team_id: String,
+  key_id: String,
+  topic: String,
+  key: EncodingKey,
+  auth_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl ApnsSender {
+  fn new(setting: &ApnsSetting) -> Result<Self, AppError> {
+    let key = EncodingKey::from_ec_pem(setting.private_key.expose_secret().as_bytes())
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("invalid APNs key: {}", err)))?;
+    // APNs only speaks HTTP/2
+    let client = reqwest::Client::builder().http2_prior_knowledge().build()?;
+    Ok(Self {
+      client,
+      base_url: if setting.sandbox {
+        "https://api.sandbox.push.apple.com"
+      } else {
+        "https://api.push.apple.com"
+      },
+      team_id: setting.team_id.clone(),
+      key_id: setting.key_id.clone(),
+      topic: setting.topic.clone(),
+      key,
+      auth_token: Mutex::new(None),
+    })
+  }
+
+  async fn auth_token(&self) -> Result<String, AppError> {
+    let mut auth_token = self.auth_token.lock().await;
+    if let Some((token, issued_at)) = auth_token.as_ref() {
+      if issued_at.elapsed() < APNS_AUTH_TOKEN_TTL {
+        return Ok(token.clone());
+      }
+    }
+    let mut header = Header::new(Algorithm::ES256);
+    header.kid = Some(self.key_id.clone());
+    let claims = ApnsClaims {
+      iss: &self.team_id,
+      iat: Utc::now().timestamp(),
+    };
+    let token = encode(&header, &claims, &self.key)
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("failed to sign APNs token: {}", err)))?;
+    *auth_token = Some((token.clone(), Instant::now()));
+    Ok(token)
+  }
+
+  async fn send(&self, device_token: &str, message: &PushMessage) -> PushOutcome {
+    let auth_token = match self.auth_token().await {
+      Ok(token) => token,
+      Err(err) => {
+        error!("{}", err);
+        return PushOutcome::Failed;
+      },
+    };
+    let body = json!({
+      "aps": {
+        "alert": { "title": message.title, "body": message.body },
+        "sound": "default",
+      },
+      "notification_id": message.notification_id,
+      "notification_type": message.notification_type,
+      "workspace_id": message.workspace_id,
+    });
+    let resp = self
+      .client
+      .post(format!("{}/3/device/{}", self.base_url, device_token))
+      .bearer_auth(auth_token)
+      .header("apns-topic", &self.topic)
+      .header("apns-push-type", "alert")
+      .header("apns-priority", "10")
+      .json(&body)
+      .send()
+      .await;
+    match resp {
+      Ok(resp) => {
+        let status = resp.status().as_u16();
+        let reason = resp
+          .json::<Value>()
+          .await
+          .ok()
+          .and_then(|body| body.get("reason")?.as_str().map(str::to_string));
+        let outcome = apns_outcome(status, reason.as_deref());
+        if outcome == PushOutcome::Failed {
+          warn!(
+            "APNs rejected notification {}: {} {:?}",
+            message.notification_id, status, reason
+          );
+        }
+        outcome
+      },
+      Err(err) => {
+        warn!("failed to reach APNs: {}", err);
+        PushOutcome::Failed
+      },
+    }
+  }
+}
+
+fn apns_outcome(status: u16, reason: Option<&str>) -> PushOutcome {
+  match (status, reason) {
+    (200, _) => PushOutcome::Delivered,
+    (410, _) | (400, Some("BadDeviceToken" | "DeviceTokenNotForTopic")) => {
+      PushOutcome::InvalidToken
+    },
+    _ => PushOutcome::Failed,
+  }
+}
+
+#[derive(Serialize)]
+struct GoogleClaims<'a> {
+  iss: &'a str,
+  scope: &'a str,
+  aud: &'a str,
+  iat: i64,
+  exp: i64,
+}
+
+struct FcmSender {
+  client: reqwest::Client,
+  project_id: String,
+  client_email: String,
+  key: EncodingKey,
+  access_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl FcmSender {
+  fn new(setting: &FcmSetting) -> Result<Self, AppError> {
+    let key = EncodingKey::from_rsa_pem(setting.private_key.expose_secret().as_bytes())
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("invalid FCM key: {}", err)))?;
+    Ok(Self {
+      client: reqwest::Client::new(),
+      project_id: setting.project_id.clone(),
+      client_email: setting.client_email.clone(),
+      key,
+      access_token: Mutex::new(None),
+    })
+  }
+
+  /// OAuth access token of the service account, refreshed a minute before it expires.
+  async fn access_token(&self) -> Result<String, AppError> {
+    let mut access_token = self.access_token.lock().await;
+    if let Some((token, expires_at)) = access_token.as_ref() {
+      if Instant::now() < *expires_at {
+        return Ok(token.clone());
+      }
+    }
+    let iat = Utc::now().timestamp();
+    let claims = GoogleClaims {
+      iss: &self.client_email,
+      scope: FCM_SCOPE,
+      aud: GOOGLE_TOKEN_URL,
+      iat,
+      exp: iat + 3600,
+    };
+    let assertion = encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+      .map_err(|err| AppError::Internal(anyhow::anyhow!("failed to sign FCM token: {}", err)))?;
+    let resp = self
+      .client
+      .post(GOOGLE_TOKEN_URL)
+      .form(&[
+        ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+        ("assertion", assertion.as_str()),
+      ])
+      .send()
+      .await?
+      .error_for_status()?
+      .json::<Value>()
+      .await?;
+    let token = resp
+      .get("access_token")
+      .and_then(Value::as_str)
+      .ok_or_else(|| AppError::Internal(anyhow::anyhow!("no FCM access token in response")))?
+      .to_string();
+    let expires_in = resp
+      .get("expires_in")
+      .and_then(Value::as_u64)
+      .unwrap_or(3600);
+    *access_token = Some((
+      token.clone(),
+      Instant::now() + Duration::from_secs(expires_in.saturating_sub(60)),
+    ));
+    Ok(token)
+  }
+
+  async fn send(&self, device_token: &str, message: &PushMessage) -> PushOutcome {
+    let access_token = match self.access_token().await {
+      Ok(token) => token,
+      Err(err) => {
+        error!("failed to get FCM access token: {}", err);
+        return PushOutcome::Failed;
+      },
+    };
+    let body = json!({
+      "message": {
+        "token": device_token,
+        "notification": { "title": message.title, "body": message.body },
+        "data": {
+          "notification_id": message.notification_id.to_string(),
+          "notification_type": message.notification_type,
+          "workspace_id": message.workspace_id.map(|id| id.to_string()).unwrap_or_default(),
+        },
+      }
+    });
+    let resp = self
+      .client
+      .post(format!(
+        "https://fcm.googleapis.com/v1/projects/{}/messages:send",
+        self.project_id
+      ))
+      .bearer_auth(access_token)
+      .json(&body)
+      .send()
+      .await;
+    match resp {
+      Ok(resp) => {
+        let status = resp.status().as_u16();
+        let error = resp.json::<Value>().await.ok();
+        let error_code = error.as_ref().and_then(fcm_error_code);
+        let outcome = fcm_outcome(status, error_code);
+        if outcome == PushOutcome::Failed {
+          warn!(
+            "FCM rejected notification {}: {} {:?}",
+            message.notification_id, status, error
+          );
+        }
+        outcome
+      },
+      Err(err) => {
+        warn!("failed to reach FCM: {}", err);
+        PushOutcome::Failed
+      },
+    }
+  }
+}
+
+/// The `errorCode` of the FCM error details, e.g. `UNREGISTERED`.
+fn fcm_error_code(body: &Value) -> Option<&str> {
+  body
+    .pointer("/error/details")?
+    .as_array()?
+    .iter()
+    .find_map(|detail| detail.get("errorCode")?.as_str())
+}
+
+fn fcm_outcome(status: u16, error_code: Option<&str>) -> PushOutcome {
+  match (status, error_code) {
+    (200, _) => PushOutcome::Delivered,
+    (404, _) | (_, Some("UNREGISTERED")) => PushOutcome::InvalidToken,
+    _ => PushOutcome::Failed,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  fn notification(payload: Value) -> AFNotificationRow {
+    AFNotificationRow {
+      id: Uuid::new_v4(),
+      workspace_id: None,
+      notification_type: "workspace_member_joined".to_string(),
+      payload,
+      recipient_uid: Some(1),
+      created_at: Utc::now(),
+      processed: false,
+    }
+  }
+
+  #[test]
+  fn push_message_from_notification_payload() {
+    let message =
+      PushMessage::from_notification(&notification(json!({ "title": "Hi", "message": "Body" })))
+        .unwrap();
+    assert_eq!(
+      (message.title.as_str(), message.body.as_str()),
+      ("Hi", "Body")
+    );
+
+    let message =
+      PushMessage::from_notification(&notification(json!({ "message": "Body" }))).unwrap();
+    assert_eq!(message.title, DEFAULT_PUSH_TITLE);
+
+    assert!(PushMessage::from_notification(&notification(json!({ "view_id": "1" }))).is_none());
+  }
+
+  #[test]
+  fn invalid_tokens_are_recognized() {
+    assert_eq!(apns_outcome(200, None), PushOutcome::Delivered);
+    assert_eq!(
+      apns_outcome(410, Some("Unregistered")),
+      PushOutcome::InvalidToken
+    );
+    assert_eq!(
+      apns_outcome(400, Some("BadDeviceToken")),
+      PushOutcome::InvalidToken
+    );
+    assert_eq!(apns_outcome(400, Some("PayloadEmpty")), PushOutcome::Failed);
+    assert_eq!(apns_outcome(429, None), PushOutcome::Failed);
+
+    let unregistered = json!({
+      "error": {
+        "status": "NOT_FOUND",
+        "details": [{
+          "@type": "type.googleapis.com/google.firebase.fcm.v1.FcmError",
+          "errorCode": "UNREGISTERED",
+        }]
+      }
+    });
+    assert_eq!(fcm_error_code(&unregistered), Some("UNREGISTERED"));
+    assert_eq!(fcm_outcome(200, None), PushOutcome::Delivered);
+    assert_eq!(
+      fcm_outcome(404, fcm_error_code(&unregistered)),
+      PushOutcome::InvalidToken
+    );
+    assert_eq!(fcm_outcome(503, None), PushOutcome::Failed);
+  }
+}
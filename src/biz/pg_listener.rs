@@ -1,5 +1,6 @@
 use anyhow::Error;
 use database::listener::PostgresDBListener;
+use database::notification::AF_NOTIFICATION_CHANNEL;
 use database::pg_row::{AFNotificationRow, AFSystemNotification, AFUserNotification};
 use sqlx::PgPool;
 use tracing::{info, trace, warn};
@@ -12,8 +13,7 @@ pub struct PgListeners {
 impl PgListeners {
   pub async fn new(pg_pool: &PgPool) -> Result<Self, Error> {
     let user_listener = UserListener::new(pg_pool, "af_user_channel").await?;
-    let notification_listener =
-      NotificationListener::new(pg_pool, "af_notification_channel").await?;
+    let notification_listener = NotificationListener::new(pg_pool, AF_NOTIFICATION_CHANNEL).await?;
     Ok(Self {
       user_listener,
       notification_listener,
@@ -46,6 +46,11 @@ impl PgListeners {
     rx
   }
 
+  /// Every notification, whatever its recipient
+  pub fn subscribe_notifications(&self) -> tokio::sync::broadcast::Receiver<AFSystemNotification> {
+    self.notification_listener.notify.subscribe()
+  }
+
   /// 订阅系统通知
   /// 仅推送给指定接收者（recipient_uid）或广播通知（recipient_uid 为 None）
   pub fn subscribe_system_notification(
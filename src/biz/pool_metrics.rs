@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::api::metrics::PoolMetrics;
+use crate::state::RedisConnectionManager;
+
+const POOL_METRICS_INTERVAL_SECS: u64 = 15;
+const PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// Samples the usage of every Postgres pool and the latency of Redis. Acquiring a connection
+/// goes through the same queue as the requests, so its duration shows how long they wait.
+pub async fn start_pool_metrics_task(
+  metrics: Arc<PoolMetrics>,
+  pg_pools: Vec<(&'static str, PgPool)>,
+  redis: RedisConnectionManager,
+) {
+  let mut interval = tokio::time::interval(Duration::from_secs(POOL_METRICS_INTERVAL_SECS));
+  loop {
+    interval.tick().await;
+    for (name, pool) in &pg_pools {
+      metrics.record_pg_pool_usage(
+        name,
+        pool.size(),
+        pool.num_idle(),
+        pool.options().get_max_connections(),
+      );
+      let start = Instant::now();
+      match tokio::time::timeout(Duration::from_secs(PROBE_TIMEOUT_SECS), pool.acquire()).await {
+        Ok(Ok(conn)) => {
+          metrics.observe_pg_acquire(name, start.elapsed().as_millis() as u64);
+          drop(conn);
+        },
+        Ok(Err(err)) => {
+          warn!(
+            "failed to acquire a connection from the {} pool: {}",
+            name, err
+          );
+          metrics.incr_pg_acquire_failure_count(name);
+        },
+        Err(_) => {
+          warn!("timed out acquiring a connection from the {} pool", name);
+          metrics.incr_pg_acquire_failure_count(name);
+        },
+      }
+    }
+
+    let start = Instant::now();
+    let ping = tokio::time::timeout(
+      Duration::from_secs(PROBE_TIMEOUT_SECS),
+      redis::cmd("PING").query_async::<String>(&mut redis.clone()),
+    )
+    .await;
+    match ping {
+      Ok(Ok(_)) => metrics.observe_redis_ping(start.elapsed().as_millis() as u64),
+      _ => metrics.incr_redis_ping_failure_count(),
+    }
+  }
+}
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use redis::AsyncCommands;
+use tracing::{info, warn};
+
+use crate::state::RedisConnectionManager;
+
+const REDIS_HEALTH_CHECK_INTERVAL_SECS: u64 = 10;
+const REDIS_PING_TIMEOUT_SECS: u64 = 3;
+pub const IMPORT_TASK_STREAM: &str = "import_task_stream";
+
+/// Tracks whether Redis is reachable. While it is not, the server runs in a degraded mode:
+/// features that only use Redis as a cache or a queue log a warning instead of failing the
+/// request, and work that could not be queued is kept here until Redis is back.
+#[derive(Default)]
+pub struct RedisHealth {
+  degraded: AtomicBool,
+  deferred_import_tasks: Mutex<Vec<String>>,
+}
+
+impl RedisHealth {
+  pub fn is_degraded(&self) -> bool {
+    self.degraded.load(Ordering::Relaxed)
+  }
+
+  fn warn_failure(&self, context: &str, err: &redis::RedisError) {
+    warn!("redis unavailable, {}: {}", context, err);
+    self.mark_degraded();
+  }
+
+  fn mark_degraded(&self) {
+    if !self.degraded.swap(true, Ordering::Relaxed) {
+      warn!("redis is unavailable, running in degraded mode");
+    }
+  }
+
+  /// Pushes the import task to the stream read by the import worker. The task is kept in
+  /// memory and pushed again once Redis is back when the stream cannot be reached.
+  pub async fn push_import_task(&self, redis: &RedisConnectionManager, task: String) {
+    if let Err(err) = xadd_import_task(redis, &task).await {
+      self.warn_failure("import task queued until redis is back", &err);
+      self.deferred_import_tasks().push(task);
+    }
+  }
+
+  async fn check(&self, redis: &RedisConnectionManager) {
+    let ping = tokio::time::timeout(
+      Duration::from_secs(REDIS_PING_TIMEOUT_SECS),
+      redis::cmd("PING").query_async::<String>(&mut redis.clone()),
+    )
+    .await;
+    match ping {
+      Ok(Ok(_)) => {
+        if self.degraded.swap(false, Ordering::Relaxed) {
+          info!("redis is available again, leaving degraded mode");
+        }
+        self.push_deferred_import_tasks(redis).await;
+      },
+      Ok(Err(err)) => self.warn_failure("health check failed", &err),
+      Err(_) => {
+        warn!("redis unavailable, health check timed out");
+        self.mark_degraded();
+      },
+    }
+  }
+
+  async fn push_deferred_import_tasks(&self, redis: &RedisConnectionManager) {
+    let tasks = std::mem::take(&mut *self.deferred_import_tasks());
+    let mut remaining = tasks.into_iter();
+    while let Some(task) = remaining.next() {
+      if let Err(err) = xadd_import_task(redis, &task).await {
+        self.warn_failure("failed to push deferred import tasks", &err);
+        let mut deferred = self.deferred_import_tasks();
+        deferred.push(task);
+        deferred.extend(remaining);
+        return;
+      }
+    }
+  }
+
+  fn deferred_import_tasks(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+    self
+      .deferred_import_tasks
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+  }
+}
+
+async fn xadd_import_task(
+  redis: &RedisConnectionManager,
+  task: &str,
+) -> Result<(), redis::RedisError> {
+  redis
+    .clone()
+    .xadd(IMPORT_TASK_STREAM, "*", &[("task", task)])
+    .await
+}
+
+pub async fn start_redis_health_task(health: Arc<RedisHealth>, redis: RedisConnectionManager) {
+  let mut interval = tokio::time::interval(Duration::from_secs(REDIS_HEALTH_CHECK_INTERVAL_SECS));
+  loop {
+    interval.tick().await;
+    health.check(&redis).await;
+  }
+}
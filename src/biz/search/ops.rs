@@ -1,4 +1,7 @@
 use crate::biz::collab::folder_view::PrivateSpaceAndTrashViews;
+use crate::biz::workspace::space_archive::get_archived_object_ids;
+use crate::biz::workspace::view_metadata::get_view_metadata;
+use crate::middleware::deadline::{check_deadline, within_deadline};
 use crate::{
   api::metrics::RequestMetrics, biz::collab::folder_view::private_space_and_trash_view_ids,
 };
@@ -94,9 +97,8 @@ pub async fn search_document(
     .map_err(|err| AppError::Unhandled(err.to_string()))?;
 
   // Create embeddings using the indexer scheduler.
-  let mut embeddings_resp = indexer_scheduler
-    .create_search_embeddings(embeddings_request)
-    .await?;
+  let mut embeddings_resp =
+    within_deadline(indexer_scheduler.create_search_embeddings(embeddings_request)).await??;
   let total_tokens = embeddings_resp.usage.total_tokens;
   metrics.record_search_tokens_used(&workspace_uuid, total_tokens);
   tracing::info!(
@@ -125,6 +127,10 @@ pub async fn search_document(
     MAX_SEARCH_DEPTH,
     uid,
   );
+  if !request.include_archived {
+    let archived_object_ids = get_archived_object_ids(pg_pool, &workspace_uuid).await?;
+    searchable_view_ids.retain(|view_id| !archived_object_ids.contains(view_id));
+  }
 
   // Set default preview size and search parameters.
   let preview_size = request.preview_size.unwrap_or(500) as i32;
@@ -147,6 +153,7 @@ pub async fn search_document(
   );
 
   // Perform document search.
+  check_deadline()?;
   let results = search_documents(pg_pool, params, total_tokens).await?;
   trace!(
     "[Search] query:{}, got {} results",
@@ -154,10 +161,15 @@ pub async fn search_document(
     results.len(),
   );
 
+  // Hydrate the results from the view metadata, the folder only knows the searchable ids.
+  let object_ids: Vec<Uuid> = results.iter().map(|item| item.object_id).collect();
+  let mut views = get_view_metadata(pg_pool, &workspace_uuid, &object_ids).await?;
+
   // Build and return the search result, mapping each document to its response item.
   let items = results
     .into_iter()
     .map(|item| SearchDocumentResponseItem {
+      view: views.remove(&item.object_id),
       object_id: item.object_id,
       workspace_id: item.workspace_id,
       score: item.score,
@@ -206,9 +218,8 @@ pub async fn summarize_search_results(
     model_name,
     llm_docs,
   );
-  match ai_tool
-    .summarize_documents(&query, &model_name, llm_docs, only_context)
-    .await
+  match within_deadline(ai_tool.summarize_documents(&query, &model_name, llm_docs, only_context))
+    .await?
   {
     Ok(resp) => {
       trace!("AI summary search document response: {:?}", resp);
@@ -22,6 +22,8 @@ use shared_entity::response::AppResponseError;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::middleware::deadline::apply_statement_timeout;
+
 pub async fn create_new_template_category(
   pg_pool: &PgPool,
   name: &str,
@@ -116,6 +118,7 @@ pub async fn update_template_creator(
     .begin()
     .await
     .context("Begin transaction to update template creator")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   delete_template_creator_account_links(txn.deref_mut(), creator_id).await?;
   let updated_template_creator =
     update_template_creator_by_id(txn.deref_mut(), creator_id, name, avatar_url, account_links)
@@ -170,6 +173,7 @@ pub async fn create_new_template(
     .begin()
     .await
     .context("Begin transaction to create template creator")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   insert_template_view(
     txn.deref_mut(),
     view_id,
@@ -210,6 +214,7 @@ pub async fn update_template(
     .begin()
     .await
     .context("Begin transaction to update template")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   delete_template_view_template_categories(txn.deref_mut(), view_id).await?;
   delete_related_templates(txn.deref_mut(), view_id).await?;
   update_template_view(
@@ -0,0 +1,224 @@
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::user_device::{
+  delete_expired_device_handoffs, insert_device_handoff, insert_device_handoff_receipt,
+  is_user_device, select_pending_device_handoffs, select_user_devices, upsert_user_device,
+};
+use database_entity::dto::{DeviceHandoffKind, DeviceHandoffParams, DeviceHandoffs, UserDevices};
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::biz::notification::ops::create_user_notification;
+
+pub use database_entity::dto::DEVICE_HANDOFF_NOTIFICATION;
+
+const MAX_HANDOFF_CONTENT_LENGTH: usize = 10_000;
+const MAX_HANDOFF_TITLE_LENGTH: usize = 200;
+/// Devices that haven't connected for longer are not offered as handoff targets
+const DEVICE_LIST_RETENTION_DAYS: i64 = 90;
+/// Handoffs are meant to be picked up right away. Their content, e.g. a copied password, is
+/// deleted after this long.
+const HANDOFF_TTL_SECS: i64 = 10 * 60;
+const HANDOFF_CLEANUP_INTERVAL_SECS: u64 = 60;
+
+/// Records that the device opened a realtime connection. Failures are only logged: the
+/// registry is best effort and must not prevent the connection.
+pub async fn record_user_device(
+  pg_pool: &PgPool,
+  uid: i64,
+  device_id: &str,
+  client_version: Option<&str>,
+) {
+  if device_id.is_empty() {
+    return;
+  }
+  if let Err(err) = upsert_user_device(pg_pool, uid, device_id, client_version).await {
+    warn!(
+      "failed to record device {} of uid={}: {:?}",
+      device_id, uid, err
+    );
+  }
+}
+
+pub async fn list_user_devices(pg_pool: &PgPool, uid: i64) -> Result<UserDevices, AppError> {
+  let seen_since = Utc::now() - Duration::days(DEVICE_LIST_RETENTION_DAYS);
+  let devices = select_user_devices(pg_pool, uid, seen_since).await?;
+  Ok(UserDevices { devices })
+}
+
+/// Hands the content off to the other devices of the user. The notification sent to them only
+/// carries the id of the handoff, the devices fetch the content with [list_pending_device_handoffs]
+/// before it expires. The notification is only delivered to the targeted device, see
+/// [is_notification_for_device].
+pub async fn send_device_handoff(
+  pg_pool: &PgPool,
+  uid: i64,
+  source_device_id: Option<&str>,
+  params: DeviceHandoffParams,
+) -> Result<(), AppError> {
+  let content = params.content.trim();
+  if content.is_empty() || content.chars().count() > MAX_HANDOFF_CONTENT_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "handoff content must be between 1 and {} characters",
+      MAX_HANDOFF_CONTENT_LENGTH
+    )));
+  }
+  if params.kind == DeviceHandoffKind::Link
+    && !(content.starts_with("https://") || content.starts_with("http://"))
+  {
+    return Err(AppError::InvalidRequest(
+      "a handoff link must be an http(s) url".to_string(),
+    ));
+  }
+  let title = params
+    .title
+    .as_deref()
+    .map(str::trim)
+    .filter(|title| !title.is_empty());
+  if title.is_some_and(|title| title.chars().count() > MAX_HANDOFF_TITLE_LENGTH) {
+    return Err(AppError::InvalidRequest(format!(
+      "handoff title must be at most {} characters",
+      MAX_HANDOFF_TITLE_LENGTH
+    )));
+  }
+  if let Some(target_device_id) = params.target_device_id.as_deref() {
+    if Some(target_device_id) == source_device_id {
+      return Err(AppError::InvalidRequest(
+        "cannot hand off to the sending device".to_string(),
+      ));
+    }
+    if !is_user_device(pg_pool, uid, target_device_id).await? {
+      return Err(AppError::RecordNotFound(format!(
+        "device {} not found",
+        target_device_id
+      )));
+    }
+  }
+
+  let message = match params.kind {
+    DeviceHandoffKind::Link => "你在另一台设备上发送了一个链接",
+    DeviceHandoffKind::Text => "你在另一台设备上发送了一段文字",
+  };
+  let now = Utc::now();
+  let expires_at = now + Duration::seconds(HANDOFF_TTL_SECS);
+  let handoff_id = insert_device_handoff(
+    pg_pool,
+    uid,
+    source_device_id,
+    params.target_device_id.as_deref(),
+    params.kind,
+    content,
+    title,
+    expires_at,
+  )
+  .await?;
+  let payload = json!({
+    "title": title.unwrap_or("来自其他设备"),
+    "message": message,
+    "kind": params.kind,
+    "handoff_id": handoff_id,
+    "source_device_id": source_device_id,
+    "target_device_id": params.target_device_id,
+    "sent_at": now.timestamp(),
+    "expires_at": expires_at.timestamp(),
+  });
+  create_user_notification(pg_pool, DEVICE_HANDOFF_NOTIFICATION, &payload, uid).await
+}
+
+/// Handoffs sent to the device that it has not processed yet. Each device keeps its own
+/// processed state, so that a handoff sent to every device is not lost on one device when
+/// another one processed it.
+pub async fn list_pending_device_handoffs(
+  pg_pool: &PgPool,
+  uid: i64,
+  device_id: &str,
+) -> Result<DeviceHandoffs, AppError> {
+  let handoffs = select_pending_device_handoffs(pg_pool, uid, device_id).await?;
+  Ok(DeviceHandoffs { handoffs })
+}
+
+pub async fn mark_device_handoff_processed(
+  pg_pool: &PgPool,
+  uid: i64,
+  handoff_id: &Uuid,
+  device_id: &str,
+) -> Result<(), AppError> {
+  if !insert_device_handoff_receipt(pg_pool, uid, handoff_id, device_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "handoff {} not found",
+      handoff_id
+    )));
+  }
+  Ok(())
+}
+
+pub async fn start_device_handoff_cleanup_task(pg_pool: PgPool) {
+  let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+    HANDOFF_CLEANUP_INTERVAL_SECS,
+  ));
+  loop {
+    interval.tick().await;
+    match delete_expired_device_handoffs(&pg_pool).await {
+      Ok(0) => {},
+      Ok(deleted) => info!("deleted {} expired device handoffs", deleted),
+      Err(err) => error!("failed to delete expired device handoffs: {:?}", err),
+    }
+  }
+}
+
+/// Whether a notification should be pushed to the realtime connection of `device_id`. Device
+/// handoffs only go to their target device, or to every device but the sender when they have no
+/// target. Other notifications go to every device.
+pub fn is_notification_for_device(
+  notification_type: &str,
+  payload: &serde_json::Value,
+  device_id: &str,
+) -> bool {
+  if notification_type != DEVICE_HANDOFF_NOTIFICATION {
+    return true;
+  }
+  match payload.get("target_device_id").and_then(|v| v.as_str()) {
+    Some(target_device_id) => target_device_id == device_id,
+    None => payload.get("source_device_id").and_then(|v| v.as_str()) != Some(device_id),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn handoffs_are_only_pushed_to_their_target_device() {
+    let targeted = json!({ "source_device_id": "phone", "target_device_id": "laptop" });
+    assert!(is_notification_for_device(
+      DEVICE_HANDOFF_NOTIFICATION,
+      &targeted,
+      "laptop"
+    ));
+    assert!(!is_notification_for_device(
+      DEVICE_HANDOFF_NOTIFICATION,
+      &targeted,
+      "tablet"
+    ));
+
+    let broadcast = json!({ "source_device_id": "phone", "target_device_id": null });
+    assert!(is_notification_for_device(
+      DEVICE_HANDOFF_NOTIFICATION,
+      &broadcast,
+      "laptop"
+    ));
+    assert!(!is_notification_for_device(
+      DEVICE_HANDOFF_NOTIFICATION,
+      &broadcast,
+      "phone"
+    ));
+
+    assert!(is_notification_for_device(
+      "workspace_member_invite",
+      &targeted,
+      "tablet"
+    ));
+  }
+}
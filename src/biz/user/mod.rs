@@ -1,5 +1,8 @@
+pub mod device_handoff;
 pub mod image_asset;
 pub mod otp_rate_limit;
+pub mod user_api_key;
+pub mod user_backup;
 pub mod user_delete;
 pub mod user_info;
 pub mod user_init;
@@ -1,26 +1,27 @@
 use app_error::AppError;
 use redis::AsyncCommands;
 
+use crate::config::runtime::OtpRateLimitSetting;
 use crate::state::RedisConnectionManager;
 
-// 同一手机或邮箱，60 秒内最多发送 1 次 OTP
-const OTP_WINDOW_SECS: i64 = 60;
-const OTP_MAX_PER_WINDOW: u64 = 1;
+// 同一手机或邮箱，在时间窗口内最多发送的 OTP 次数由运行时配置决定（默认 60 秒内 1 次）
 
 pub async fn check_phone_otp_rate_limit(
   phone: &str,
   redis: &mut RedisConnectionManager,
+  setting: &OtpRateLimitSetting,
 ) -> Result<(), AppError> {
   let key = format!("otp:phone:{}", phone);
-  check_otp_rate_limit(&key, redis, OTP_MAX_PER_WINDOW, OTP_WINDOW_SECS).await
+  check_otp_rate_limit(&key, redis, setting.max_per_window, setting.window_secs).await
 }
 
 pub async fn check_email_otp_rate_limit(
   email: &str,
   redis: &mut RedisConnectionManager,
+  setting: &OtpRateLimitSetting,
 ) -> Result<(), AppError> {
   let key = format!("otp:email:{}", email);
-  check_otp_rate_limit(&key, redis, OTP_MAX_PER_WINDOW, OTP_WINDOW_SECS).await
+  check_otp_rate_limit(&key, redis, setting.max_per_window, setting.window_secs).await
 }
 
 async fn check_otp_rate_limit(
@@ -43,9 +44,10 @@ async fn check_otp_rate_limit(
   }
 
   if count > max_count {
-    return Err(AppError::TooManyRequests(
-      "发送过于频繁，请 60 秒后再试".to_string(),
-    ));
+    return Err(AppError::TooManyRequests(format!(
+      "发送过于频繁，请 {} 秒后再试",
+      window_secs
+    )));
   }
 
   Ok(())
@@ -0,0 +1,89 @@
+use app_error::AppError;
+use database::api_key::{
+  delete_user_api_key, insert_user_api_key, select_user_api_key_count, select_user_api_keys,
+  select_user_by_api_key_hash,
+};
+use database_entity::dto::{CreateUserApiKeyParams, CreatedUserApiKey, UserApiKeys};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const API_KEY_PREFIX: &str = "pnk_";
+const API_KEY_RANDOM_LENGTH: usize = 40;
+/// Characters of the key kept in clear to tell keys apart, prefix included
+const API_KEY_DISPLAY_LENGTH: usize = 10;
+const MAX_API_KEY_NAME_LEN: usize = 100;
+const MAX_API_KEYS_PER_USER: i64 = 20;
+
+pub async fn create_user_api_key(
+  pg_pool: &PgPool,
+  uid: i64,
+  params: CreateUserApiKeyParams,
+) -> Result<CreatedUserApiKey, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "api key name must be between 1 and {} characters",
+      MAX_API_KEY_NAME_LEN
+    )));
+  }
+  if select_user_api_key_count(pg_pool, uid).await? >= MAX_API_KEYS_PER_USER {
+    return Err(AppError::InvalidRequest(format!(
+      "a user can have at most {} api keys",
+      MAX_API_KEYS_PER_USER
+    )));
+  }
+  let secret = generate_api_key();
+  let api_key = insert_user_api_key(
+    pg_pool,
+    uid,
+    name,
+    &secret[..API_KEY_DISPLAY_LENGTH],
+    &hash_api_key(&secret),
+  )
+  .await?;
+  Ok(CreatedUserApiKey { api_key, secret })
+}
+
+pub async fn list_user_api_keys(pg_pool: &PgPool, uid: i64) -> Result<UserApiKeys, AppError> {
+  let api_keys = select_user_api_keys(pg_pool, uid).await?;
+  Ok(UserApiKeys { api_keys })
+}
+
+pub async fn revoke_user_api_key(
+  pg_pool: &PgPool,
+  uid: i64,
+  key_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_user_api_key(pg_pool, uid, key_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "api key {} not found",
+      key_id
+    )));
+  }
+  Ok(())
+}
+
+/// Returns the uid and uuid of the user owning the key.
+pub async fn authenticate_api_key(pg_pool: &PgPool, key: &str) -> Result<(i64, Uuid), AppError> {
+  if !key.starts_with(API_KEY_PREFIX) {
+    return Err(AppError::UserUnAuthorized("invalid api key".to_string()));
+  }
+  select_user_by_api_key_hash(pg_pool, &hash_api_key(key))
+    .await?
+    .ok_or_else(|| AppError::UserUnAuthorized("invalid api key".to_string()))
+}
+
+fn generate_api_key() -> String {
+  let random: String = rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(API_KEY_RANDOM_LENGTH)
+    .map(char::from)
+    .collect();
+  format!("{}{}", API_KEY_PREFIX, random)
+}
+
+fn hash_api_key(key: &str) -> Vec<u8> {
+  Sha256::digest(key.as_bytes()).to_vec()
+}
@@ -0,0 +1,46 @@
+use app_error::AppError;
+use database::user_backup::{select_user_backup, select_user_backup_info, upsert_user_backup};
+use database_entity::dto::UserBackupInfo;
+use sqlx::PgPool;
+
+/// Backups hold encrypted settings and keys, not documents.
+pub const MAX_USER_BACKUP_SIZE: usize = 1024 * 1024;
+
+/// Replaces the backup of the user with `data`, which the server stores without reading it.
+pub async fn put_user_backup(
+  pg_pool: &PgPool,
+  uid: i64,
+  data: &[u8],
+  device_id: Option<&str>,
+  expected_version: Option<i64>,
+) -> Result<UserBackupInfo, AppError> {
+  if data.is_empty() {
+    return Err(AppError::InvalidRequest("backup is empty".to_string()));
+  }
+  if data.len() > MAX_USER_BACKUP_SIZE {
+    return Err(AppError::PayloadTooLarge(format!(
+      "backup is {} bytes, at most {} bytes are allowed",
+      data.len(),
+      MAX_USER_BACKUP_SIZE
+    )));
+  }
+  if let Some(info) = upsert_user_backup(pg_pool, uid, data, device_id, expected_version).await? {
+    return Ok(info);
+  }
+  let current_version = select_user_backup_info(pg_pool, uid)
+    .await?
+    .map(|info| info.version)
+    .unwrap_or_default();
+  Err(AppError::OverrideWithIncorrectData(format!(
+    "backup version is {}, not the expected version {}",
+    current_version,
+    expected_version.unwrap_or_default()
+  )))
+}
+
+/// Returns the version and the data of the backup of the user.
+pub async fn get_user_backup(pg_pool: &PgPool, uid: i64) -> Result<(i64, Vec<u8>), AppError> {
+  select_user_backup(pg_pool, uid)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound("user has no backup".to_string()))
+}
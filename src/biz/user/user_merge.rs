@@ -24,6 +24,7 @@ use crate::biz::subscription::ops::{get_user_resource_limit_status};
 use crate::biz::user::user_init::{
   create_user_awareness, create_workspace_collab, create_workspace_database_collab,
 };
+use crate::middleware::deadline::apply_statement_timeout;
 use database::subscription::get_user_owned_workspace_count;
 use access_control::workspace::WorkspaceAccessControl;
 use anyhow::anyhow;
@@ -41,6 +42,7 @@ use serde::{Deserialize, Serialize};
 use shared_entity::response::AppResponseError;
 use sqlx::PgPool;
 use std::collections::HashMap;
+use std::ops::DerefMut;
 use std::sync::Arc;
 use tracing::{error, info, instrument, warn};
 use uuid::Uuid;
@@ -705,6 +707,7 @@ async fn create_workspace_for_user(
 
   // 为新工作区创建初始 collab 结构（Folder + WorkspaceDatabase + UserAwareness）
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
 
   // 创建 Folder collab
   create_workspace_collab(
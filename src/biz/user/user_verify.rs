@@ -12,6 +12,7 @@ use workspace_template::document::getting_started::GettingStartedTemplate;
 
 use crate::biz::user::user_init::initialize_workspace_for_user;
 use crate::biz::user::user_search::get_uid_by_email_or_phone;
+use crate::middleware::deadline::apply_statement_timeout;
 use crate::state::AppState;
 
 /// Verify the token from the gotrue server and create the user if it is a new user
@@ -29,6 +30,7 @@ pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool,
     .begin()
     .await
     .context("acquire transaction to verify token")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
 
   let is_new = !is_user_exist(txn.deref_mut(), &user_uuid).await?;
   if is_new {
@@ -90,6 +92,7 @@ pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool,
 
     // Create a workspace with the GetStarted template
     let mut txn2 = state.pg_pool.begin().await?;
+    apply_statement_timeout(txn2.deref_mut()).await?;
     let start = Instant::now();
     initialize_workspace_for_user(
       new_uid,
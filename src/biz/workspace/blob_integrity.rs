@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use collab_entity::{CollabType, EncodedCollab};
+use database::blob_integrity::{
+  select_workspaces_due_for_blob_integrity_scan, upsert_blob_integrity_report,
+};
+use database::collab::{
+  select_deleted_collab_oids, select_workspace_collab_oids, CollabStore, GetCollabOrigin,
+};
+use database::file::s3_client_impl::S3BucketStorage;
+use database::file::{blob_metadata_key_parent_dir, blob_object_key};
+use database::pg_row::AFBlobMetadataRow;
+use database::resource_usage::get_all_workspace_blob_metadata;
+use shared_entity::dto::workspace_dto::{BlobIntegrityReport, MissingBlobReference, OrphanBlob};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::biz::collab::document::{document_blob_references, BlobReference};
+use crate::biz::collab::utils::{batch_get_latest_collab_encoded, document_data_from_doc_state};
+
+const BLOB_INTEGRITY_SCAN_INTERVAL_SECS: u64 = 86400;
+const BLOB_INTEGRITY_STALE_AFTER_DAYS: i32 = 7;
+const BLOB_INTEGRITY_WORKSPACES_PER_RUN: i64 = 50;
+const DOCUMENT_SCAN_BATCH_SIZE: usize = 100;
+
+/// Periodically scans the workspaces whose last report is stale. Blobs are looked up in the
+/// same order as they are read: `primary_storage` first, then `fallback_storage`.
+pub async fn start_blob_integrity_task(
+  pg_pool: PgPool,
+  collab_storage: Arc<dyn CollabStore>,
+  primary_storage: Arc<S3BucketStorage>,
+  fallback_storage: Option<Arc<S3BucketStorage>>,
+) {
+  let mut storages = vec![primary_storage];
+  storages.extend(fallback_storage);
+  let mut timer = tokio::time::interval(Duration::from_secs(BLOB_INTEGRITY_SCAN_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    if let Err(err) = run_blob_integrity_task(&pg_pool, &collab_storage, &storages).await {
+      error!("blob integrity task failed: {:?}", err);
+    }
+  }
+}
+
+async fn run_blob_integrity_task(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  storages: &[Arc<S3BucketStorage>],
+) -> Result<(), AppError> {
+  let workspace_ids = select_workspaces_due_for_blob_integrity_scan(
+    pg_pool,
+    BLOB_INTEGRITY_STALE_AFTER_DAYS,
+    BLOB_INTEGRITY_WORKSPACES_PER_RUN,
+  )
+  .await?;
+  for workspace_id in workspace_ids {
+    match scan_workspace_blob_integrity(pg_pool, collab_storage, storages, workspace_id).await {
+      Ok(report) => info!(
+        "blob integrity scan of workspace {}: {} missing, {} orphan, {} lost blobs",
+        workspace_id,
+        report.missing_blobs.len(),
+        report.orphan_blobs.len(),
+        report.lost_blobs.len()
+      ),
+      Err(err) => warn!(
+        "failed to scan blob integrity of workspace {}: {:?}",
+        workspace_id, err
+      ),
+    }
+  }
+  Ok(())
+}
+
+/// Scans every document of the workspace for links to blobs that no longer exist, and for
+/// blobs that were uploaded to a document but are not referenced by it anymore. Only blobs
+/// whose parent document was scanned or has been deleted are reported as orphans, since
+/// files uploaded elsewhere (databases, chats, covers) are not visible to this scan.
+/// A blob only exists if its file is found in one of the `storages`; recorded blobs whose
+/// file is gone are reported as lost.
+/// The report is stored as the latest report of the workspace.
+#[instrument(level = "debug", skip(pg_pool, collab_storage, storages), err)]
+pub async fn scan_workspace_blob_integrity(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  storages: &[Arc<S3BucketStorage>],
+  workspace_id: Uuid,
+) -> Result<BlobIntegrityReport, AppError> {
+  let document_ids =
+    select_workspace_collab_oids(pg_pool, &workspace_id, &CollabType::Document).await?;
+  let blobs = get_all_workspace_blob_metadata(pg_pool, &workspace_id).await?;
+  let mut object_keys = HashSet::new();
+  for storage in storages {
+    object_keys.extend(storage.list_workspace_object_keys(&workspace_id).await?);
+  }
+  let (stored_blobs, lost_blobs): (Vec<_>, Vec<_>) = blobs
+    .iter()
+    .partition(|blob| object_keys.contains(&blob_object_key(&workspace_id, &blob.file_id)));
+  let blob_keys: HashSet<&str> = stored_blobs
+    .iter()
+    .map(|blob| blob.file_id.as_str())
+    .collect();
+
+  let mut scanned_documents = 0;
+  let mut referenced_keys = HashSet::new();
+  let mut missing_blobs = vec![];
+  for batch in document_ids.chunks(DOCUMENT_SCAN_BATCH_SIZE) {
+    let encoded_collabs = batch_get_latest_collab_encoded(
+      collab_storage,
+      GetCollabOrigin::Server,
+      workspace_id,
+      batch,
+      CollabType::Document,
+    )
+    .await?;
+    scanned_documents += encoded_collabs.len() as u32;
+    let references =
+      tokio::task::spawn_blocking(move || collect_blob_references(encoded_collabs)).await?;
+    for (object_id, reference) in references {
+      // Links to another workspace are checked when that workspace is scanned.
+      if reference.workspace_id != workspace_id {
+        continue;
+      }
+      if !blob_keys.contains(reference.metadata_key.as_str()) {
+        missing_blobs.push(MissingBlobReference {
+          object_id,
+          block_id: reference.block_id,
+          url: reference.url,
+        });
+      }
+      referenced_keys.insert(reference.metadata_key);
+    }
+  }
+
+  let mut parent_dirs: HashMap<Uuid, Vec<usize>> = HashMap::new();
+  for (index, blob) in stored_blobs.iter().enumerate() {
+    if referenced_keys.contains(&blob.file_id) {
+      continue;
+    }
+    if let Some(parent_dir) = blob_metadata_key_parent_dir(&blob.file_id) {
+      parent_dirs.entry(parent_dir).or_default().push(index);
+    }
+  }
+  let scanned_ids: HashSet<Uuid> = document_ids.iter().copied().collect();
+  let candidate_ids: Vec<Uuid> = parent_dirs.keys().copied().collect();
+  let deleted_ids: HashSet<Uuid> = select_deleted_collab_oids(pg_pool, &candidate_ids)
+    .await?
+    .into_iter()
+    .collect();
+  let mut orphan_blobs: Vec<OrphanBlob> = parent_dirs
+    .into_iter()
+    .filter(|(parent_dir, _)| scanned_ids.contains(parent_dir) || deleted_ids.contains(parent_dir))
+    .flat_map(|(_, indexes)| indexes)
+    .map(|index| to_orphan_blob(stored_blobs[index]))
+    .collect();
+  orphan_blobs.sort_by(|a, b| b.file_size.cmp(&a.file_size));
+  let lost_blobs = lost_blobs.into_iter().map(to_orphan_blob).collect();
+
+  let report = BlobIntegrityReport {
+    workspace_id,
+    scanned_at: Utc::now(),
+    scanned_documents,
+    orphan_bytes: orphan_blobs.iter().map(|blob| blob.file_size).sum(),
+    missing_blobs,
+    orphan_blobs,
+    lost_blobs,
+  };
+  upsert_blob_integrity_report(pg_pool, &report).await?;
+  Ok(report)
+}
+
+fn to_orphan_blob(blob: &AFBlobMetadataRow) -> OrphanBlob {
+  OrphanBlob {
+    file_id: blob.file_id.clone(),
+    file_type: blob.file_type.clone(),
+    file_size: blob.file_size,
+    modified_at: blob.modified_at,
+  }
+}
+
+fn collect_blob_references(
+  encoded_collabs: HashMap<Uuid, EncodedCollab>,
+) -> Vec<(Uuid, BlobReference)> {
+  let mut references = vec![];
+  for (object_id, encoded_collab) in encoded_collabs {
+    match document_references(object_id, encoded_collab) {
+      Ok(document_references) => references.extend(
+        document_references
+          .into_iter()
+          .map(|reference| (object_id, reference)),
+      ),
+      Err(err) => warn!(
+        "failed to read blob references of document {}: {}",
+        object_id, err
+      ),
+    }
+  }
+  references
+}
+
+fn document_references(
+  object_id: Uuid,
+  encoded_collab: EncodedCollab,
+) -> Result<Vec<BlobReference>, AppError> {
+  let document_data = document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &object_id)?;
+  Ok(document_blob_references(&document_data))
+}
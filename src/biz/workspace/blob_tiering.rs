@@ -0,0 +1,216 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use database::file::s3_client_impl::S3BucketStorage;
+use database::pg_row::AFBlobStorageClass;
+use database::resource_usage::{
+  select_blob_storage_class_usage, select_blobs_read_since_tiering, select_idle_blob_ids,
+  select_workspaces_with_idle_blobs,
+};
+use database::workspace::select_workspace;
+use shared_entity::dto::billing_dto::SubscriptionPlan;
+use shared_entity::dto::workspace_dto::BlobTieringSummary;
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::biz::subscription::ops::get_user_resource_limit_status;
+use crate::biz::workspace::subscription_plan_limits::PlanLimits;
+
+const BLOB_TIERING_INTERVAL_SECS: u64 = 86400;
+/// Blobs read since they were tiered are moved back to standard storage this often.
+const BLOB_RESTORE_INTERVAL_SECS: u64 = 600;
+const MAX_RESTORED_BLOBS_PER_RUN: i64 = 1000;
+/// Infrequent access storage bills every object as at least 128KB, so smaller blobs would
+/// cost more after the transition.
+const MIN_TIERING_FILE_SIZE: i64 = 128 * 1024;
+const MAX_TIERED_BLOBS_PER_WORKSPACE: i64 = 500;
+/// Monthly price per GB of the standard and infrequent access storage classes, in USD
+const STANDARD_PRICE_PER_GB: f64 = 0.023;
+const INFREQUENT_ACCESS_PRICE_PER_GB: f64 = 0.0125;
+
+/// Periodically moves blobs that have not been read for the idle period of the owner's plan
+/// to infrequent access storage, and moves blobs that were read again back to standard
+/// storage. Blobs are looked up in the same order as they are read: `primary_storage` first,
+/// then `fallback_storage`.
+pub async fn start_blob_tiering_task(
+  pg_pool: PgPool,
+  primary_storage: Arc<S3BucketStorage>,
+  fallback_storage: Option<Arc<S3BucketStorage>>,
+) {
+  let mut storages = vec![primary_storage];
+  storages.extend(fallback_storage);
+  let mut tiering_timer = tokio::time::interval(Duration::from_secs(BLOB_TIERING_INTERVAL_SECS));
+  let mut restore_timer = tokio::time::interval(Duration::from_secs(BLOB_RESTORE_INTERVAL_SECS));
+  loop {
+    tokio::select! {
+      _ = tiering_timer.tick() => {
+        if let Err(err) = run_blob_tiering_task(&pg_pool, &storages).await {
+          error!("blob tiering task failed: {:?}", err);
+        }
+      },
+      _ = restore_timer.tick() => {
+        if let Err(err) = restore_read_blobs(&pg_pool, &storages).await {
+          error!("blob restore task failed: {:?}", err);
+        }
+      },
+    }
+  }
+}
+
+/// Moves blobs that were read after they were tiered back to standard storage, since they
+/// are in use again.
+async fn restore_read_blobs(
+  pg_pool: &PgPool,
+  storages: &[Arc<S3BucketStorage>],
+) -> Result<(), AppError> {
+  let blobs = select_blobs_read_since_tiering(pg_pool, MAX_RESTORED_BLOBS_PER_RUN).await?;
+  let mut restored = 0;
+  for (workspace_id, file_id) in blobs {
+    match set_storage_class(
+      storages,
+      &workspace_id,
+      &file_id,
+      AFBlobStorageClass::Standard,
+    )
+    .await
+    {
+      Ok(()) => restored += 1,
+      Err(err) => warn!(
+        "failed to move blob {} of workspace {} back to standard storage: {}",
+        file_id, workspace_id, err
+      ),
+    }
+  }
+  if restored > 0 {
+    info!("moved {} blobs back to standard storage", restored);
+  }
+  Ok(())
+}
+
+async fn run_blob_tiering_task(
+  pg_pool: &PgPool,
+  storages: &[Arc<S3BucketStorage>],
+) -> Result<(), AppError> {
+  // The free plan has the shortest idle period, so no workspace with idle blobs is missed.
+  let shortest_idle_days =
+    PlanLimits::from_plan(&SubscriptionPlan::Free).blob_infrequent_access_after_days;
+  let workspaces =
+    select_workspaces_with_idle_blobs(pg_pool, shortest_idle_days, MIN_TIERING_FILE_SIZE).await?;
+  for (workspace_id, owner_uid) in workspaces {
+    if let Err(err) = tier_workspace_blobs(pg_pool, storages, workspace_id, owner_uid).await {
+      warn!(
+        "failed to tier blobs of workspace {}: {:?}",
+        workspace_id, err
+      );
+    }
+  }
+  Ok(())
+}
+
+async fn tier_workspace_blobs(
+  pg_pool: &PgPool,
+  storages: &[Arc<S3BucketStorage>],
+  workspace_id: Uuid,
+  owner_uid: i64,
+) -> Result<(), AppError> {
+  let idle_days = infrequent_access_after_days(pg_pool, owner_uid).await?;
+  let file_ids = select_idle_blob_ids(
+    pg_pool,
+    &workspace_id,
+    idle_days,
+    MIN_TIERING_FILE_SIZE,
+    MAX_TIERED_BLOBS_PER_WORKSPACE,
+  )
+  .await?;
+  let mut tiered = 0;
+  for file_id in file_ids {
+    match set_storage_class(
+      storages,
+      &workspace_id,
+      &file_id,
+      AFBlobStorageClass::InfrequentAccess,
+    )
+    .await
+    {
+      Ok(()) => tiered += 1,
+      Err(err) => warn!(
+        "failed to move blob {} of workspace {} to infrequent access storage: {}",
+        file_id, workspace_id, err
+      ),
+    }
+  }
+  if tiered > 0 {
+    info!(
+      "moved {} blobs of workspace {} to infrequent access storage",
+      tiered, workspace_id
+    );
+  }
+  Ok(())
+}
+
+async fn set_storage_class(
+  storages: &[Arc<S3BucketStorage>],
+  workspace_id: &Uuid,
+  file_id: &str,
+  storage_class: AFBlobStorageClass,
+) -> Result<(), AppError> {
+  for storage in storages {
+    match storage
+      .set_blob_storage_class(workspace_id, file_id, storage_class)
+      .await
+    {
+      Err(err) if err.is_record_not_found() => continue,
+      result => return result,
+    }
+  }
+  Err(AppError::RecordNotFound(format!(
+    "blob {} not found in any storage",
+    file_id
+  )))
+}
+
+async fn infrequent_access_after_days(pg_pool: &PgPool, owner_uid: i64) -> Result<i32, AppError> {
+  let resource_status = get_user_resource_limit_status(pg_pool, owner_uid).await?;
+  Ok(PlanLimits::from_plan_code(&resource_status.plan_code).blob_infrequent_access_after_days)
+}
+
+#[instrument(level = "debug", skip(pg_pool), err)]
+pub async fn get_blob_tiering_summary(
+  pg_pool: &PgPool,
+  workspace_id: Uuid,
+) -> Result<BlobTieringSummary, AppError> {
+  let workspace = select_workspace(pg_pool, &workspace_id).await?;
+  let owner_uid = workspace
+    .owner_uid
+    .ok_or_else(|| AppError::Internal(anyhow!("Workspace owner_uid is missing")))?;
+  let mut summary = BlobTieringSummary {
+    workspace_id,
+    infrequent_access_after_days: infrequent_access_after_days(pg_pool, owner_uid).await?,
+    standard_blob_count: 0,
+    standard_bytes: 0,
+    infrequent_access_blob_count: 0,
+    infrequent_access_bytes: 0,
+    estimated_monthly_savings: 0.0,
+  };
+  for (storage_class, count, bytes) in
+    select_blob_storage_class_usage(pg_pool, &workspace_id).await?
+  {
+    match storage_class {
+      AFBlobStorageClass::Standard => {
+        summary.standard_blob_count = count;
+        summary.standard_bytes = bytes;
+      },
+      AFBlobStorageClass::InfrequentAccess => {
+        summary.infrequent_access_blob_count = count;
+        summary.infrequent_access_bytes = bytes;
+      },
+    }
+  }
+  let infrequent_access_gb = summary.infrequent_access_bytes as f64 / (1024 * 1024 * 1024) as f64;
+  summary.estimated_monthly_savings =
+    infrequent_access_gb * (STANDARD_PRICE_PER_GB - INFREQUENT_ACCESS_PRICE_PER_GB);
+  Ok(summary)
+}
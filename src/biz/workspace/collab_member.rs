@@ -4,13 +4,15 @@ use database::workspace::{
   insert_collab_member, select_collab_owner, select_permission, update_collab_member_permission,
   update_collab_member_invite_permission,
 };
-use database_entity::dto::AFAccessLevel;
+use database_entity::dto::{AFAccessLevel, PageActivityKind};
 use sqlx::PgPool;
 use std::ops::DerefMut;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::biz::notification::ops::create_workspace_notification;
+use crate::biz::workspace::page_activity::record_page_activity;
+use crate::middleware::deadline::apply_statement_timeout;
 use database::collab::{delete_collab_member, delete_collab_member_invite};
 
 fn permission_name(permission_id: i32) -> &'static str {
@@ -99,6 +101,31 @@ async fn notify_collab_permission_changed(
   }
 }
 
+/// Records the change of the permission of a member of the view in its activity feed.
+async fn record_collab_permission_activity(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  operator_uid: i64,
+  member_uid: i64,
+  old_permission_id: Option<i32>,
+  new_permission_id: Option<i32>,
+) {
+  record_page_activity(
+    pg_pool,
+    workspace_id,
+    view_id,
+    operator_uid,
+    PageActivityKind::PermissionChanged,
+    serde_json::json!({
+      "member_uid": member_uid,
+      "old_permission": old_permission_id.map(permission_name),
+      "new_permission": new_permission_id.map(permission_name),
+    }),
+  )
+  .await;
+}
+
 pub async fn add_collab_member(
   pg_pool: &PgPool,
   access_control: Arc<dyn CollabAccessControl>,
@@ -110,6 +137,7 @@ pub async fn add_collab_member(
   permission_id: i32,
 ) -> Result<(), AppError> {
   let mut tx = pg_pool.begin().await?;
+  apply_statement_timeout(tx.deref_mut()).await?;
 
   let owner_id = select_collab_owner(tx.deref_mut(), workspace_id, view_id).await?;
 
@@ -144,6 +172,17 @@ pub async fn add_collab_member(
     .await?;
   tx.commit().await?;
 
+  record_collab_permission_activity(
+    pg_pool,
+    workspace_id,
+    view_id,
+    send_uid,
+    received_uid,
+    None,
+    Some(permission_id),
+  )
+  .await;
+
   // 权限名称
   let perm_name = permission_name(permission_id);
 
@@ -252,6 +291,17 @@ pub async fn edit_collab_member_permission(
     .update_access_level_policy(&uid, &view_id, permission.access_level)
     .await?;
 
+  record_collab_permission_activity(
+    pg_pool,
+    workspace_id,
+    view_id,
+    owner_uid,
+    uid,
+    old_permission_id,
+    Some(new_permission_id),
+  )
+  .await;
+
   // 发送权限变更通知给被修改权限的用户
   let view_name = select_shared_view_name(pg_pool, view_id, uid).await;
   let doc_name = view_name.as_deref().unwrap_or("未知文章");
@@ -321,6 +371,17 @@ pub async fn remove_collab_member(
     .remove_access_level(&uid, view_id)
     .await?;
 
+  record_collab_permission_activity(
+    pg_pool,
+    workspace_id,
+    view_id,
+    owner_uid,
+    uid,
+    old_permission_id,
+    None,
+  )
+  .await;
+
   // Marks a full access revocation (vs. a mere permission downgrade). The
   // client uses this to drop the view from the recipient's shared list and
   // close its open tab, while leaving other users' lists untouched.
@@ -0,0 +1,287 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use access_control::collab::CollabAccessControl;
+use actix::Addr;
+use app_error::AppError;
+use appflowy_collaborate::ws2::{RefreshWorkspaceUserPermissions, WsServer};
+use database::collab_permission_sync::{
+  claim_stalled_collab_permission_syncs, finish_collab_permission_sync,
+  insert_collab_permission_sync, select_latest_collab_permission_sync,
+  select_member_collab_permissions, update_collab_permission_sync_progress,
+  update_member_collab_permissions,
+};
+use database_entity::dto::{
+  AFAccessLevel, AFRole, CollabPermissionSync, CollabPermissionSyncStatus,
+};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Collabs whose permissions are synced between two progress updates
+const SYNC_BATCH_SIZE: usize = 100;
+const RECOVERY_INTERVAL_SECS: u64 = 60;
+/// Running syncs without progress for this long are resumed by another server
+const STALLED_SYNC_SECS: i64 = 600;
+const STALLED_SYNCS_PER_RUN: i64 = 10;
+
+/// Recomputes the collab-level permissions of the member for their new workspace role in the
+/// background. Until then, collab-level permissions granted for the previous role, such as the
+/// full access to the pages they created, would keep overriding the new role.
+pub async fn start_collab_permission_sync(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  ws_server: Addr<WsServer>,
+  workspace_id: Uuid,
+  uid: i64,
+  role: AFRole,
+) -> Result<Uuid, AppError> {
+  let sync_id = insert_collab_permission_sync(pg_pool, &workspace_id, uid, role.clone()).await?;
+  tokio::spawn(run_collab_permission_sync(
+    pg_pool.clone(),
+    collab_access_control,
+    ws_server,
+    sync_id,
+    workspace_id,
+    uid,
+    role,
+  ));
+  Ok(sync_id)
+}
+
+/// Resumes the syncs left running by servers that stopped before finishing them. The sync of a
+/// member is idempotent, so a resumed sync starts over.
+pub async fn start_collab_permission_sync_recovery_task(
+  pg_pool: PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  ws_server: Addr<WsServer>,
+) {
+  let mut interval = tokio::time::interval(Duration::from_secs(RECOVERY_INTERVAL_SECS));
+  loop {
+    interval.tick().await;
+    let syncs = match claim_stalled_collab_permission_syncs(
+      &pg_pool,
+      STALLED_SYNC_SECS,
+      STALLED_SYNCS_PER_RUN,
+    )
+    .await
+    {
+      Ok(syncs) => syncs,
+      Err(err) => {
+        error!("failed to claim stalled collab permission syncs: {:?}", err);
+        continue;
+      },
+    };
+    for sync in syncs {
+      info!(
+        "resuming collab permission sync {} of uid={}",
+        sync.sync_id, sync.uid
+      );
+      run_collab_permission_sync(
+        pg_pool.clone(),
+        collab_access_control.clone(),
+        ws_server.clone(),
+        sync.sync_id,
+        sync.workspace_id,
+        sync.uid,
+        AFRole::from(sync.role_id),
+      )
+      .await;
+    }
+  }
+}
+
+async fn run_collab_permission_sync(
+  pg_pool: PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  ws_server: Addr<WsServer>,
+  sync_id: Uuid,
+  workspace_id: Uuid,
+  uid: i64,
+  role: AFRole,
+) {
+  let result = sync_collab_permissions(
+    &pg_pool,
+    collab_access_control.as_ref(),
+    &sync_id,
+    &workspace_id,
+    uid,
+    &role,
+  )
+  .await;
+  let (status, error) = match result {
+    Ok(true) => (CollabPermissionSyncStatus::Succeeded, None),
+    Ok(false) => {
+      info!(
+        "collab permission sync {} of uid={} was superseded",
+        sync_id, uid
+      );
+      return;
+    },
+    Err(err) => {
+      error!(
+        "collab permission sync {} of uid={} failed: {:?}",
+        sync_id, uid, err
+      );
+      (CollabPermissionSyncStatus::Failed, Some(err.to_string()))
+    },
+  };
+  if let Err(err) =
+    finish_collab_permission_sync(&pg_pool, &sync_id, status, error.as_deref()).await
+  {
+    error!(
+      "failed to record the end of collab permission sync {}: {:?}",
+      sync_id, err
+    );
+  }
+  // Open sessions of the member re-check their collabs against the synced policies
+  ws_server.do_send(RefreshWorkspaceUserPermissions { workspace_id, uid });
+}
+
+pub async fn get_collab_permission_sync(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<CollabPermissionSync, AppError> {
+  select_latest_collab_permission_sync(pg_pool, workspace_id, uid)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "no collab permission sync for uid {} in workspace {}",
+        uid, workspace_id
+      ))
+    })
+}
+
+/// Returns false when a later role change superseded the sync.
+async fn sync_collab_permissions(
+  pg_pool: &PgPool,
+  collab_access_control: &dyn CollabAccessControl,
+  sync_id: &Uuid,
+  workspace_id: &Uuid,
+  uid: i64,
+  role: &AFRole,
+) -> Result<bool, AppError> {
+  let permissions = select_member_collab_permissions(pg_pool, workspace_id, uid).await?;
+  let total_collabs = permissions.len() as i32;
+  if !update_collab_permission_sync_progress(pg_pool, sync_id, total_collabs, 0).await? {
+    return Ok(false);
+  }
+
+  let mut synced_collabs = 0;
+  for batch in permissions.chunks(SYNC_BATCH_SIZE) {
+    let access_levels: Vec<AFAccessLevel> = batch
+      .iter()
+      .map(|permission| {
+        recomputed_access_level(
+          role,
+          AFAccessLevel::from(permission.access_level),
+          permission.shared,
+          permission.owned,
+        )
+      })
+      .collect();
+    // One update per access level the collabs of the batch move to
+    let mut changed_oids: Vec<(AFAccessLevel, Vec<String>)> = vec![];
+    for (permission, access_level) in batch.iter().zip(&access_levels) {
+      if *access_level == AFAccessLevel::from(permission.access_level) {
+        continue;
+      }
+      match changed_oids
+        .iter_mut()
+        .find(|(level, _)| level == access_level)
+      {
+        Some((_, oids)) => oids.push(permission.oid.clone()),
+        None => changed_oids.push((*access_level, vec![permission.oid.clone()])),
+      }
+    }
+    for (access_level, oids) in &changed_oids {
+      update_member_collab_permissions(pg_pool, uid, oids, *access_level).await?;
+    }
+
+    for (permission, access_level) in batch.iter().zip(access_levels) {
+      // The policy is reloaded even when unchanged, in case it drifted from the database
+      match Uuid::parse_str(&permission.oid) {
+        Ok(oid) => {
+          collab_access_control
+            .update_access_level_policy(&uid, &oid, access_level)
+            .await?;
+        },
+        Err(_) => warn!(
+          "skip collab permission of uid={} on invalid object id {}",
+          uid, permission.oid
+        ),
+      }
+    }
+    synced_collabs += batch.len() as i32;
+    if !update_collab_permission_sync_progress(pg_pool, sync_id, total_collabs, synced_collabs)
+      .await?
+    {
+      return Ok(false);
+    }
+  }
+  Ok(true)
+}
+
+/// The collab-level access of a member with the given role. Collabs shared with the member keep
+/// the access they were shared with. Creators keep full access to their collabs as long as their
+/// role allows creating collabs, and any other collab-level access follows the role.
+fn recomputed_access_level(
+  role: &AFRole,
+  current: AFAccessLevel,
+  shared: bool,
+  owned: bool,
+) -> AFAccessLevel {
+  if shared {
+    current
+  } else if owned && role.can_create_collab() {
+    AFAccessLevel::FullAccess
+  } else {
+    AFAccessLevel::from(role)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shared_collabs_keep_their_access() {
+    for role in [AFRole::Owner, AFRole::Member, AFRole::Guest] {
+      assert_eq!(
+        recomputed_access_level(&role, AFAccessLevel::ReadAndComment, true, false),
+        AFAccessLevel::ReadAndComment
+      );
+    }
+  }
+
+  #[test]
+  fn created_collabs_follow_the_role() {
+    let level = AFAccessLevel::FullAccess;
+    assert_eq!(
+      recomputed_access_level(&AFRole::Member, level, false, true),
+      AFAccessLevel::FullAccess
+    );
+    assert_eq!(
+      recomputed_access_level(&AFRole::Guest, level, false, true),
+      AFAccessLevel::ReadOnly
+    );
+    assert_eq!(
+      recomputed_access_level(&AFRole::Member, AFAccessLevel::ReadOnly, false, true),
+      AFAccessLevel::FullAccess
+    );
+  }
+
+  #[test]
+  fn workspace_collab_follows_the_role() {
+    let level = AFAccessLevel::ReadAndWrite;
+    assert_eq!(
+      recomputed_access_level(&AFRole::Owner, level, false, false),
+      AFAccessLevel::FullAccess
+    );
+    assert_eq!(
+      recomputed_access_level(&AFRole::Guest, level, false, false),
+      AFAccessLevel::ReadOnly
+    );
+  }
+}
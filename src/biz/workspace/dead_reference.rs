@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::ws2::{CollabUpdatePublisher, WorkspaceCollabInstanceCache};
+use chrono::Utc;
+use collab::core::collab::{default_client_id, Collab};
+use collab_document::document::DocumentBody;
+use collab_entity::{CollabType, EncodedCollab};
+use collab_folder::CollabOrigin;
+use database::collab::{select_workspace_collab_oids, CollabStore, GetCollabOrigin};
+use database::dead_reference::{
+  select_workspace_member_uuids, select_workspaces_due_for_dead_reference_scan,
+  upsert_dead_reference_report,
+};
+use database::workspace::select_workspace_settings;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{DeadReference, DeadReferenceKind, DeadReferenceReport};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::biz::collab::document::{
+  document_mentions, DocumentMention, MentionTarget, DEAD_REFERENCE_ATTRIBUTE,
+};
+use crate::biz::collab::utils::{
+  batch_get_latest_collab_encoded, collab_from_doc_state, document_data_from_collab,
+  document_data_from_doc_state, DUMMY_UID,
+};
+
+const DEAD_REFERENCE_SCAN_INTERVAL_SECS: u64 = 3600;
+const DEAD_REFERENCE_WORKSPACES_PER_RUN: i64 = 50;
+const DOCUMENT_SCAN_BATCH_SIZE: usize = 100;
+
+pub async fn start_dead_reference_task<C>(
+  pg_pool: PgPool,
+  collab_storage: Arc<dyn CollabStore>,
+  collab_server: C,
+) where
+  C: WorkspaceCollabInstanceCache + CollabUpdatePublisher,
+{
+  let mut timer = tokio::time::interval(Duration::from_secs(DEAD_REFERENCE_SCAN_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    if let Err(err) = run_dead_reference_task(&pg_pool, &collab_storage, &collab_server).await {
+      error!("dead reference task failed: {:?}", err);
+    }
+  }
+}
+
+async fn run_dead_reference_task<C>(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_server: &C,
+) -> Result<(), AppError>
+where
+  C: WorkspaceCollabInstanceCache + CollabUpdatePublisher,
+{
+  let workspace_ids =
+    select_workspaces_due_for_dead_reference_scan(pg_pool, DEAD_REFERENCE_WORKSPACES_PER_RUN)
+      .await?;
+  for workspace_id in workspace_ids {
+    match scan_workspace_dead_references(
+      pg_pool,
+      collab_storage,
+      collab_server,
+      collab_server,
+      workspace_id,
+    )
+    .await
+    {
+      Ok(report) => info!(
+        "dead reference scan of workspace {}: {} dead references, {} annotated",
+        workspace_id,
+        report.dead_references.len(),
+        report.annotated
+      ),
+      Err(err) => warn!(
+        "failed to scan dead references of workspace {}: {:?}",
+        workspace_id, err
+      ),
+    }
+  }
+  Ok(())
+}
+
+/// Scans every document of the workspace for mentions of pages that were moved to the trash
+/// or deleted, and of people who are no longer members of the workspace. When the workspace
+/// enabled [auto_annotate_dead_references](database_entity::dto::AFWorkspaceSettings), the
+/// dead mentions are marked in their document so that clients can render them as such.
+/// The report is stored as the latest report of the workspace.
+#[instrument(
+  level = "debug",
+  skip(pg_pool, collab_storage, collab_instance_cache, update_publisher),
+  err
+)]
+pub async fn scan_workspace_dead_references(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  update_publisher: &impl CollabUpdatePublisher,
+  workspace_id: Uuid,
+) -> Result<DeadReferenceReport, AppError> {
+  let auto_annotate = select_workspace_settings(pg_pool, &workspace_id)
+    .await?
+    .unwrap_or_default()
+    .auto_annotate_dead_references;
+  let document_ids =
+    select_workspace_collab_oids(pg_pool, &workspace_id, &CollabType::Document).await?;
+
+  let mut scanned_documents = 0;
+  let mut mentions = vec![];
+  for batch in document_ids.chunks(DOCUMENT_SCAN_BATCH_SIZE) {
+    let encoded_collabs = batch_get_latest_collab_encoded(
+      collab_storage,
+      GetCollabOrigin::Server,
+      workspace_id,
+      batch,
+      CollabType::Document,
+    )
+    .await?;
+    scanned_documents += encoded_collabs.len() as u32;
+    mentions.extend(tokio::task::spawn_blocking(move || collect_mentions(encoded_collabs)).await?);
+  }
+
+  let dead_targets =
+    find_dead_targets(pg_pool, collab_instance_cache, workspace_id, &mentions).await?;
+  let mut dead_references = vec![];
+  let mut documents_to_annotate = HashSet::new();
+  for (object_id, mention) in mentions {
+    if let Some(kind) = dead_targets.get(&mention.target_id) {
+      if !mention.annotated {
+        documents_to_annotate.insert(object_id);
+      }
+      dead_references.push(DeadReference {
+        object_id,
+        block_id: mention.block_id,
+        kind: *kind,
+        target_id: mention.target_id,
+      });
+    }
+  }
+
+  let mut annotated = 0;
+  if auto_annotate {
+    for object_id in documents_to_annotate {
+      match annotate_document(
+        collab_storage,
+        update_publisher,
+        workspace_id,
+        object_id,
+        &dead_targets,
+      )
+      .await
+      {
+        Ok(count) => annotated += count,
+        Err(err) => warn!(
+          "failed to annotate dead references of document {}: {:?}",
+          object_id, err
+        ),
+      }
+    }
+  }
+
+  let report = DeadReferenceReport {
+    workspace_id,
+    scanned_at: Utc::now(),
+    scanned_documents,
+    dead_references,
+    annotated,
+  };
+  upsert_dead_reference_report(pg_pool, &report).await?;
+  Ok(report)
+}
+
+/// Classifies the mentioned pages and people, returning only the targets that are dead.
+async fn find_dead_targets(
+  pg_pool: &PgPool,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  workspace_id: Uuid,
+  mentions: &[(Uuid, DocumentMention)],
+) -> Result<HashMap<Uuid, DeadReferenceKind>, AppError> {
+  let mut page_ids = HashSet::new();
+  let mut person_ids = HashSet::new();
+  for (_, mention) in mentions {
+    match mention.target {
+      MentionTarget::Page => page_ids.insert(mention.target_id),
+      MentionTarget::Person => person_ids.insert(mention.target_id),
+    };
+  }
+
+  let mut dead_targets = HashMap::new();
+  if !person_ids.is_empty() {
+    let member_uuids: HashSet<Uuid> = select_workspace_member_uuids(pg_pool, &workspace_id)
+      .await?
+      .into_iter()
+      .collect();
+    for person_id in person_ids.difference(&member_uuids) {
+      dead_targets.insert(*person_id, DeadReferenceKind::RemovedMember);
+    }
+  }
+  if !page_ids.is_empty() {
+    let folder = collab_instance_cache.get_folder(workspace_id).await?;
+    let trash: HashSet<String> = folder
+      .get_all_trash_sections(DUMMY_UID)
+      .into_iter()
+      .map(|section| section.id)
+      .collect();
+    for page_id in page_ids {
+      let view_id = page_id.to_string();
+      if trash.contains(&view_id) {
+        dead_targets.insert(page_id, DeadReferenceKind::TrashedPage);
+      } else if folder.get_view(&view_id, DUMMY_UID).is_none() {
+        dead_targets.insert(page_id, DeadReferenceKind::DeletedPage);
+      }
+    }
+  }
+  Ok(dead_targets)
+}
+
+fn collect_mentions(encoded_collabs: HashMap<Uuid, EncodedCollab>) -> Vec<(Uuid, DocumentMention)> {
+  let mut mentions = vec![];
+  for (object_id, encoded_collab) in encoded_collabs {
+    let document_mentions =
+      document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &object_id)
+        .map(|document_data| document_mentions(&document_data));
+    match document_mentions {
+      Ok(document_mentions) => mentions.extend(
+        document_mentions
+          .into_iter()
+          .map(|mention| (object_id, mention)),
+      ),
+      Err(err) => warn!("failed to read mentions of document {}: {}", object_id, err),
+    }
+  }
+  mentions
+}
+
+fn read_document_mentions(collab: &Collab) -> Result<Vec<DocumentMention>, AppError> {
+  Ok(document_mentions(&document_data_from_collab(collab)?))
+}
+
+/// Adds the [DEAD_REFERENCE_ATTRIBUTE] to the dead mentions of the latest version of the
+/// document that are not annotated yet, and returns how many mentions were annotated.
+async fn annotate_document(
+  collab_storage: &Arc<dyn CollabStore>,
+  update_publisher: &impl CollabUpdatePublisher,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  dead_targets: &HashMap<Uuid, DeadReferenceKind>,
+) -> Result<u32, AppError> {
+  let encoded_collab = collab_storage
+    .get_full_encode_collab(
+      GetCollabOrigin::Server,
+      &workspace_id,
+      &object_id,
+      CollabType::Document,
+    )
+    .await
+    .map(|v| v.encoded_collab)?;
+  let dead_targets = dead_targets.clone();
+  let (count, update) = tokio::task::spawn_blocking(move || {
+    let collab = collab_from_doc_state(
+      encoded_collab.doc_state.to_vec(),
+      &object_id,
+      default_client_id(),
+    )?;
+    annotate_dead_mentions(collab, &dead_targets)
+  })
+  .await??;
+  if count > 0 {
+    update_publisher
+      .publish_update(
+        workspace_id,
+        object_id,
+        CollabType::Document,
+        &CollabOrigin::Server,
+        update,
+      )
+      .await?;
+  }
+  Ok(count)
+}
+
+fn annotate_dead_mentions(
+  mut collab: Collab,
+  dead_targets: &HashMap<Uuid, DeadReferenceKind>,
+) -> Result<(u32, Vec<u8>), AppError> {
+  let mentions = read_document_mentions(&collab)?;
+  let document_body = DocumentBody::from_collab(&collab)
+    .ok_or_else(|| AppError::Internal(anyhow!("invalid document collab")))?;
+  let mut count = 0;
+  let mut txn = collab.transact_mut();
+  for mention in mentions.into_iter().filter(|mention| !mention.annotated) {
+    let kind = match dead_targets.get(&mention.target_id) {
+      Some(kind) => kind,
+      None => continue,
+    };
+    // Formatting keeps the mention itself and only adds the attribute next to it
+    let delta = serde_json::from_value(json!([
+      { "retain": mention.offset },
+      { "retain": mention.len, "attributes": { DEAD_REFERENCE_ATTRIBUTE: kind } },
+    ]))
+    .map_err(|err| AppError::Internal(anyhow!(err)))?;
+    document_body
+      .text_operation
+      .apply_delta(&mut txn, &mention.text_id, delta);
+    count += 1;
+  }
+  Ok((count, txn.encode_update_v1()))
+}
@@ -1,4 +1,4 @@
-use super::page_view::{update_workspace_database_data, update_workspace_folder_data};
+use super::page_view::{update_workspace_database_data, update_workspace_folder};
 use crate::biz::collab::utils::get_latest_collab;
 use crate::state::AppState;
 use crate::{
@@ -109,14 +109,7 @@ pub async fn duplicate_view_tree_and_collab(
     }
     txn.encode_update_v1()
   };
-  update_workspace_folder_data(
-    appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    encoded_folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, encoded_folder_update).await?;
   Ok(())
 }
 
@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use chrono::{Datelike, NaiveDate, Utc};
+use dashmap::{DashMap, DashSet};
+use database::egress::{
+  select_workspace_daily_egress, select_workspace_egress_bytes_since, upsert_workspace_egress_bulk,
+};
+use database::pg_row::AFEgressSource;
+use database::workspace::select_workspace;
+use shared_entity::dto::billing_dto::WorkspaceEgressDay;
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::biz::subscription::ops::get_user_resource_limit_status;
+use crate::biz::workspace::subscription_plan_limits::PlanLimits;
+use crate::config::runtime::RuntimeConfig;
+
+/// Counts the bytes served per workspace in memory and periodically adds them to the daily
+/// aggregates in `af_workspace_egress_daily`, so serving a file costs no database write.
+#[derive(Default)]
+pub struct EgressMeter {
+  pending: DashMap<(Uuid, AFEgressSource), i64>,
+  /// Workspaces that used up the monthly egress of their plan. Only filled when the limit
+  /// is enforced.
+  exceeded: DashSet<Uuid>,
+}
+
+impl EgressMeter {
+  pub fn record(&self, workspace_id: Uuid, source: AFEgressSource, bytes: usize) {
+    *self.pending.entry((workspace_id, source)).or_default() += bytes as i64;
+  }
+
+  pub fn check_limit(&self, workspace_id: &Uuid) -> Result<(), AppError> {
+    if self.exceeded.contains(workspace_id) {
+      return Err(AppError::PlanLimitExceeded(format!(
+        "workspace {} has used up the monthly download bandwidth of its plan",
+        workspace_id
+      )));
+    }
+    Ok(())
+  }
+
+  async fn flush(&self, pg_pool: &PgPool, enforce_limit: bool) -> Result<(), AppError> {
+    let keys: Vec<(Uuid, AFEgressSource)> = self.pending.iter().map(|entry| *entry.key()).collect();
+    let items: Vec<(Uuid, AFEgressSource, i64)> = keys
+      .into_iter()
+      .filter_map(|key| self.pending.remove(&key))
+      .map(|((workspace_id, source), bytes)| (workspace_id, source, bytes))
+      .collect();
+
+    if !items.is_empty() {
+      if let Err(err) = upsert_workspace_egress_bulk(pg_pool, Utc::now().date_naive(), &items).await
+      {
+        // Keep the bytes for the next flush instead of losing them.
+        for (workspace_id, source, bytes) in items {
+          *self.pending.entry((workspace_id, source)).or_default() += bytes;
+        }
+        return Err(err);
+      }
+    }
+
+    if enforce_limit {
+      // Exceeded workspaces are rechecked as well, since the limit resets every month.
+      let mut workspace_ids: HashSet<Uuid> = items.iter().map(|(id, _, _)| *id).collect();
+      workspace_ids.extend(self.exceeded.iter().map(|id| *id));
+      for workspace_id in workspace_ids {
+        match get_workspace_egress_and_limit(pg_pool, &workspace_id).await {
+          Ok((egress_bytes, limits)) if !limits.can_serve_egress(egress_bytes) => {
+            self.exceeded.insert(workspace_id);
+          },
+          Ok(_) => {
+            self.exceeded.remove(&workspace_id);
+          },
+          Err(err) => warn!(
+            "failed to check egress limit of workspace {}: {}",
+            workspace_id, err
+          ),
+        }
+      }
+    } else {
+      // The limit may have been turned off since the last flush.
+      self.exceeded.clear();
+    }
+    Ok(())
+  }
+}
+
+pub async fn start_egress_flush_task(
+  meter: Arc<EgressMeter>,
+  pg_pool: PgPool,
+  flush_interval_secs: u64,
+  runtime_config: RuntimeConfig,
+) {
+  let mut timer = tokio::time::interval(Duration::from_secs(flush_interval_secs));
+  loop {
+    timer.tick().await;
+    let enforce_limit = runtime_config.load().egress_enforce_limit;
+    if let Err(err) = meter.flush(&pg_pool, enforce_limit).await {
+      error!("failed to flush egress usage: {:?}", err);
+    }
+  }
+}
+
+fn first_day_of_month(date: NaiveDate) -> NaiveDate {
+  date.with_day(1).unwrap_or(date)
+}
+
+/// Bytes served for the workspace this month, and the limits of the owner's plan.
+async fn get_workspace_egress_and_limit(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(i64, PlanLimits), AppError> {
+  let workspace = select_workspace(pg_pool, workspace_id).await?;
+  let owner_uid = workspace
+    .owner_uid
+    .ok_or_else(|| AppError::Internal(anyhow!("Workspace owner_uid is missing")))?;
+  let resource_status = get_user_resource_limit_status(pg_pool, owner_uid).await?;
+  let month_start = first_day_of_month(Utc::now().date_naive());
+  let egress_bytes =
+    select_workspace_egress_bytes_since(pg_pool, workspace_id, month_start).await?;
+  Ok((
+    egress_bytes,
+    PlanLimits::from_plan_code(&resource_status.plan_code),
+  ))
+}
+
+/// Bytes served this month and the per day breakdown, oldest day first.
+pub async fn get_workspace_monthly_egress(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(i64, Vec<WorkspaceEgressDay>), AppError> {
+  let today = Utc::now().date_naive();
+  let month_start = first_day_of_month(today);
+  let rows = select_workspace_daily_egress(
+    pg_pool,
+    workspace_id,
+    month_start,
+    today + chrono::Duration::days(1),
+  )
+  .await?;
+  let mut days: BTreeMap<NaiveDate, WorkspaceEgressDay> = BTreeMap::new();
+  for (date, source, bytes) in rows {
+    let day = days.entry(date).or_insert_with(|| WorkspaceEgressDay {
+      date,
+      blob_bytes: 0,
+      publish_bytes: 0,
+    });
+    match source {
+      AFEgressSource::Blob => day.blob_bytes += bytes,
+      AFEgressSource::Publish => day.publish_bytes += bytes,
+    }
+  }
+  let total = days
+    .values()
+    .map(|day| day.blob_bytes + day.publish_bytes)
+    .sum();
+  Ok((total, days.into_values().collect()))
+}
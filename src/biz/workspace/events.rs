@@ -0,0 +1,478 @@
+//! Domain events emitted by workspace membership operations.
+//!
+//! Ops in [crate::biz::workspace::ops] perform the state change in the database and then hand a
+//! [WorkspaceEvent] to [WorkspaceEvents::publish]. Everything that follows from the change
+//! (access control policies, permission syncs, invitation mails, in-app notifications; webhooks,
+//! audit log or event export later) is a handler here, so new integrations hook a single place
+//! instead of editing each op.
+//!
+//! Handlers run after the owning transaction has committed. Handlers that keep the server state
+//! in line with the database (access control, permission syncs) fail the operation, as requests
+//! would otherwise be authorized against the previous role. The others only log their errors.
+
+use std::sync::Arc;
+
+use access_control::collab::CollabAccessControl;
+use access_control::workspace::WorkspaceAccessControl;
+use actix::Addr;
+use app_error::AppError;
+use appflowy_collaborate::ws2::{RefreshWorkspaceUserPermissions, WsServer};
+use database_entity::dto::AFRole;
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::biz::notification::ops::{
+  create_workspace_notification, create_workspace_notifications,
+};
+use crate::biz::workspace::collab_permission_sync::start_collab_permission_sync;
+use crate::mailer::{AFCloudMailer, WorkspaceInviteMailerParam};
+
+// use default icons until we have workspace icons
+const WORKSPACE_ICON_URL: &str =
+  "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png";
+const USER_ICON_URL: &str =
+  "https://cdn.pixabay.com/photo/2015/10/05/22/37/blank-profile-picture-973460_1280.png";
+
+#[derive(Debug, Clone)]
+pub enum WorkspaceEvent {
+  /// An invitee accepted a pending workspace invitation.
+  InvitationAccepted {
+    workspace_id: Uuid,
+    inviter_uid: i64,
+    invitee_uid: i64,
+    role: AFRole,
+  },
+  /// One or more users were invited to a workspace. Registered invitees are added to the
+  /// workspace right away, so `invitees` only lists users that already have a uid.
+  MembersInvited {
+    workspace_id: Uuid,
+    workspace_name: String,
+    workspace_member_count: i64,
+    inviter_uid: i64,
+    inviter_name: String,
+    invitees: Vec<(i64, AFRole)>,
+    mails: Vec<InvitationMail>,
+  },
+  /// Members were removed from a workspace, either by an admin or by leaving.
+  MembersRemoved {
+    workspace_id: Uuid,
+    workspace_name: String,
+    uids: Vec<i64>,
+    operator_uid: Option<i64>,
+  },
+  /// The role of a workspace member was changed.
+  MemberRoleChanged {
+    workspace_id: Uuid,
+    uid: i64,
+    role: AFRole,
+    operator_uid: i64,
+  },
+  /// The members of a merged workspace joined the workspace it was merged into.
+  MembersMerged {
+    workspace_id: Uuid,
+    members: Vec<(i64, AFRole)>,
+  },
+}
+
+/// Invitation mail to send once the invitation is committed, so that its link always works.
+#[derive(Debug, Clone)]
+pub struct InvitationMail {
+  pub email: String,
+  pub invite_id: Uuid,
+  /// The inviter waits for the mail to be sent and is told when it fails.
+  pub wait: bool,
+}
+
+impl WorkspaceEvent {
+  pub fn workspace_id(&self) -> &Uuid {
+    match self {
+      WorkspaceEvent::InvitationAccepted { workspace_id, .. }
+      | WorkspaceEvent::MembersInvited { workspace_id, .. }
+      | WorkspaceEvent::MembersRemoved { workspace_id, .. }
+      | WorkspaceEvent::MemberRoleChanged { workspace_id, .. }
+      | WorkspaceEvent::MembersMerged { workspace_id, .. } => workspace_id,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      WorkspaceEvent::InvitationAccepted { .. } => "invitation_accepted",
+      WorkspaceEvent::MembersInvited { .. } => "members_invited",
+      WorkspaceEvent::MembersRemoved { .. } => "members_removed",
+      WorkspaceEvent::MemberRoleChanged { .. } => "member_role_changed",
+      WorkspaceEvent::MembersMerged { .. } => "members_merged",
+    }
+  }
+}
+
+/// Dispatches [WorkspaceEvent]s to their handlers.
+pub struct WorkspaceEvents {
+  pg_pool: PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  ws_server: Addr<WsServer>,
+  mailer: AFCloudMailer,
+  appflowy_web_url: String,
+}
+
+impl WorkspaceEvents {
+  pub fn new(
+    pg_pool: PgPool,
+    workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+    collab_access_control: Arc<dyn CollabAccessControl>,
+    ws_server: Addr<WsServer>,
+    mailer: AFCloudMailer,
+    appflowy_web_url: String,
+  ) -> Self {
+    Self {
+      pg_pool,
+      workspace_access_control,
+      collab_access_control,
+      ws_server,
+      mailer,
+      appflowy_web_url,
+    }
+  }
+
+  /// Dispatch `event` to every handler. Must be called after the transaction that produced the
+  /// event has committed: clients react to notifications by re-querying the server and would
+  /// otherwise observe the old state.
+  pub async fn publish(&self, event: WorkspaceEvent) -> Result<(), AppError> {
+    info!(
+      "[workspace event] {} in workspace {}",
+      event.name(),
+      event.workspace_id()
+    );
+    self.update_access_control(&event).await?;
+    self.sync_member_permissions(&event).await?;
+    let mails = self.send_invitation_mails(&event).await;
+    if let Err(err) = self.notify_members(&event).await {
+      warn!(
+        "Failed to send notifications for workspace event {}: {:?}",
+        event.name(),
+        err
+      );
+    }
+    mails
+  }
+
+  async fn update_access_control(&self, event: &WorkspaceEvent) -> Result<(), AppError> {
+    match event {
+      WorkspaceEvent::InvitationAccepted {
+        workspace_id,
+        invitee_uid,
+        role,
+        ..
+      } => {
+        self
+          .workspace_access_control
+          .insert_role(invitee_uid, workspace_id, role.clone())
+          .await?;
+      },
+      WorkspaceEvent::MembersInvited {
+        workspace_id,
+        invitees: members,
+        ..
+      }
+      | WorkspaceEvent::MembersMerged {
+        workspace_id,
+        members,
+        ..
+      } => {
+        for (uid, role) in members {
+          self
+            .workspace_access_control
+            .insert_role(uid, workspace_id, role.clone())
+            .await?;
+        }
+      },
+      WorkspaceEvent::MembersRemoved {
+        workspace_id, uids, ..
+      } => {
+        for uid in uids {
+          self
+            .workspace_access_control
+            .remove_user_from_workspace(uid, workspace_id)
+            .await?;
+        }
+      },
+      WorkspaceEvent::MemberRoleChanged {
+        workspace_id,
+        uid,
+        role,
+        ..
+      } => {
+        self
+          .workspace_access_control
+          .insert_role(uid, workspace_id, role.clone())
+          .await?;
+      },
+    }
+    Ok(())
+  }
+
+  /// Refreshes the permissions of the member's open connections and recomputes their
+  /// collab-level permissions for the new role.
+  async fn sync_member_permissions(&self, event: &WorkspaceEvent) -> Result<(), AppError> {
+    if let WorkspaceEvent::MemberRoleChanged {
+      workspace_id,
+      uid,
+      role,
+      ..
+    } = event
+    {
+      self.ws_server.do_send(RefreshWorkspaceUserPermissions {
+        workspace_id: *workspace_id,
+        uid: *uid,
+      });
+      start_collab_permission_sync(
+        &self.pg_pool,
+        self.collab_access_control.clone(),
+        self.ws_server.clone(),
+        *workspace_id,
+        *uid,
+        role.clone(),
+      )
+      .await?;
+    }
+    Ok(())
+  }
+
+  /// Sends the invitation mails. Only the failures of the mails the inviter waits for are
+  /// returned.
+  async fn send_invitation_mails(&self, event: &WorkspaceEvent) -> Result<(), AppError> {
+    let WorkspaceEvent::MembersInvited {
+      workspace_name,
+      workspace_member_count,
+      inviter_name,
+      mails,
+      ..
+    } = event
+    else {
+      return Ok(());
+    };
+
+    let mut waited_mails = vec![];
+    for mail in mails {
+      let mailer = self.mailer.clone();
+      let email = mail.email.clone();
+      let param = WorkspaceInviteMailerParam {
+        user_icon_url: USER_ICON_URL.to_string(),
+        username: inviter_name.clone(),
+        workspace_name: workspace_name.clone(),
+        workspace_icon_url: WORKSPACE_ICON_URL.to_string(),
+        workspace_member_count: workspace_member_count.to_string(),
+        // Generate a link such that when clicked, the user is added to the workspace.
+        accept_url: format!(
+          "{}/accept-invitation?invited_id={}",
+          self.appflowy_web_url, mail.invite_id
+        ),
+      };
+      let sending = tokio::spawn(async move { mailer.send_workspace_invite(&email, param).await });
+      if mail.wait {
+        waited_mails.push(sending);
+      }
+    }
+    for sending in waited_mails {
+      sending.await??;
+    }
+    Ok(())
+  }
+
+  /// In-app notification handler. Recipients of an event are notified in one insert.
+  async fn notify_members(&self, event: &WorkspaceEvent) -> Result<(), AppError> {
+    let pg_pool = &self.pg_pool;
+    match event {
+      WorkspaceEvent::InvitationAccepted {
+        workspace_id,
+        inviter_uid,
+        invitee_uid,
+        role,
+      } => {
+        let role_str = format!("{:?}", role);
+        let invitee_name = select_user_name(pg_pool, *invitee_uid, "新成员".to_string()).await;
+        let workspace_name = select_workspace_name(pg_pool, workspace_id, "工作区").await;
+
+        // 通知邀请者：被邀请人已接受邀请
+        let payload = json!({
+          "title": "邀请已被接受",
+          "message": format!(
+            "【{}】已接受你的邀请，以「{}」身份加入了工作区「{}」",
+            invitee_name, role_str, workspace_name
+          ),
+          "invitee_uid": invitee_uid,
+          "invitee_name": invitee_name,
+          "inviter_uid": inviter_uid,
+          "role": role_str,
+          "workspace_name": workspace_name,
+          "accepted_at": chrono::Utc::now().timestamp(),
+        });
+        create_workspace_notification(
+          pg_pool,
+          workspace_id,
+          "workspace_invitation_accepted",
+          &payload,
+          Some(*inviter_uid),
+        )
+        .await?;
+      },
+      WorkspaceEvent::MembersInvited {
+        workspace_id,
+        workspace_name,
+        inviter_uid,
+        inviter_name,
+        invitees,
+        ..
+      } => {
+        if invitees.is_empty() {
+          return Ok(());
+        }
+
+        // 通知被邀请者：收到邀请
+        let invitee_notifications = invitees
+          .iter()
+          .map(|(invitee_uid, role)| {
+            let role_name = role_display_name(role);
+            let payload = json!({
+              "workspace_id": workspace_id.to_string(),
+              "inviter_name": inviter_name,
+              "workspace_name": workspace_name,
+              "role": role_name,
+              "title": "你收到了工作区邀请",
+              "message": format!("【{}】邀请你加入工作区「{}」，你的角色是：{}", inviter_name, workspace_name, role_name),
+            });
+            (*invitee_uid, payload)
+          })
+          .collect();
+        if let Err(err) = create_workspace_notifications(
+          pg_pool,
+          workspace_id,
+          "workspace_member_invite",
+          invitee_notifications,
+        )
+        .await
+        {
+          warn!(
+            "Failed to send invite notifications in workspace {}: {:?}",
+            workspace_id, err
+          );
+        }
+
+        // 通知邀请者：邀请发送成功确认
+        let invitee_uids: Vec<i64> = invitees.iter().map(|(uid, _)| *uid).collect();
+        let names = database::user::select_names_from_uids(pg_pool, &invitee_uids)
+          .await
+          .unwrap_or_default();
+        let invited_names: Vec<String> = invitee_uids
+          .iter()
+          .map(|uid| match names.get(uid) {
+            Some(name) => format!("【{}】", name),
+            None => format!("【用户{}】", uid),
+          })
+          .collect();
+        let payload = json!({
+          "workspace_id": workspace_id.to_string(),
+          "workspace_name": workspace_name,
+          "invited_count": invitees.len(),
+          "title": "工作区邀请已发送",
+          "message": format!("你已成功邀请 {} 加入工作区「{}」", invited_names.join("、"), workspace_name),
+        });
+        create_workspace_notification(
+          pg_pool,
+          workspace_id,
+          "workspace_invite_sent",
+          &payload,
+          Some(*inviter_uid),
+        )
+        .await?;
+      },
+      WorkspaceEvent::MembersRemoved {
+        workspace_id,
+        workspace_name,
+        uids,
+        operator_uid,
+      } => {
+        let operator_name = match operator_uid {
+          Some(op_uid) => select_user_name(pg_pool, *op_uid, "管理员".to_string()).await,
+          None => "管理员".to_string(),
+        };
+        let notifications = uids
+          .iter()
+          .map(|uid| {
+            let payload = json!({
+              "workspace_id": workspace_id.to_string(),
+              "removed_member_uid": uid,
+              "title": "工作区成员已移除",
+              "message": format!("{}用户已经移除了您所在工作区：{}", operator_name, workspace_name),
+            });
+            (*uid, payload)
+          })
+          .collect();
+        create_workspace_notifications(
+          pg_pool,
+          workspace_id,
+          "workspace_member_removed",
+          notifications,
+        )
+        .await?;
+        info!(
+          "Sent workspace_member_removed notifications to {:?} for workspace={}",
+          uids, workspace_id
+        );
+      },
+      WorkspaceEvent::MemberRoleChanged {
+        workspace_id,
+        uid,
+        role,
+        operator_uid,
+      } => {
+        let workspace_name = select_workspace_name(pg_pool, workspace_id, "").await;
+        let operator_name = select_user_name(pg_pool, *operator_uid, "管理员".to_string()).await;
+        let payload = json!({
+          "workspace_id": workspace_id.to_string(),
+          "title": "工作区角色已变更",
+          "message": format!(
+            "{}用户已经修改了您在工作区：{}的角色，新角色为：{}",
+            operator_name,
+            workspace_name,
+            role_display_name(role)
+          ),
+        });
+        create_workspace_notification(
+          pg_pool,
+          workspace_id,
+          "workspace_member_role_changed",
+          &payload,
+          Some(*uid),
+        )
+        .await?;
+      },
+      WorkspaceEvent::MembersMerged { .. } => {},
+    }
+    Ok(())
+  }
+}
+
+fn role_display_name(role: &AFRole) -> &'static str {
+  match role {
+    AFRole::Owner => "所有者",
+    AFRole::Member => "成员",
+    AFRole::Guest => "访客",
+  }
+}
+
+async fn select_user_name(pg_pool: &PgPool, uid: i64, fallback: String) -> String {
+  database::user::select_name_from_uid(pg_pool, uid)
+    .await
+    .unwrap_or(fallback)
+}
+
+async fn select_workspace_name(pg_pool: &PgPool, workspace_id: &Uuid, fallback: &str) -> String {
+  database::workspace::select_workspace_name_from_workspace_id(pg_pool, workspace_id)
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| fallback.to_string())
+}
@@ -0,0 +1,270 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use database::dead_reference::select_dead_reference_report;
+use database::workspace_health::{
+  select_workspace_health_counts, select_workspaces_due_for_health_check,
+  upsert_workspace_health_report, AFWorkspaceHealthCountsRow,
+};
+use shared_entity::dto::workspace_dto::{
+  WorkspaceHealthIssue, WorkspaceHealthRecommendation, WorkspaceHealthReport,
+  WORKSPACE_HEALTH_STALE_AFTER_DAYS,
+};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use super::ops::get_workspace_usage_and_limit;
+
+const WORKSPACE_HEALTH_INTERVAL_SECS: u64 = 3600;
+const WORKSPACE_HEALTH_WORKSPACES_PER_RUN: i64 = 100;
+/// Reports older than this are recomputed by the periodic task
+const WORKSPACE_HEALTH_REPORT_TTL_HOURS: i64 = 24;
+
+/// Below these ratios, stale and unindexed documents are considered normal
+const STALE_DOCUMENTS_RATIO_THRESHOLD: f64 = 0.3;
+const UNINDEXED_DOCUMENTS_RATIO_THRESHOLD: f64 = 0.1;
+const NEAR_LIMIT_RATIO: f64 = 0.8;
+
+pub async fn start_workspace_health_task(pg_pool: PgPool) {
+  let mut timer = tokio::time::interval(Duration::from_secs(WORKSPACE_HEALTH_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    if let Err(err) = run_workspace_health_task(&pg_pool).await {
+      error!("workspace health task failed: {:?}", err);
+    }
+  }
+}
+
+async fn run_workspace_health_task(pg_pool: &PgPool) -> Result<(), AppError> {
+  let computed_before = Utc::now() - chrono::Duration::hours(WORKSPACE_HEALTH_REPORT_TTL_HOURS);
+  let workspace_ids = select_workspaces_due_for_health_check(
+    pg_pool,
+    computed_before,
+    WORKSPACE_HEALTH_WORKSPACES_PER_RUN,
+  )
+  .await?;
+  for workspace_id in workspace_ids {
+    match compute_workspace_health(pg_pool, workspace_id).await {
+      Ok(report) => info!(
+        "health score of workspace {}: {}",
+        workspace_id, report.score
+      ),
+      Err(err) => warn!(
+        "failed to compute the health of workspace {}: {:?}",
+        workspace_id, err
+      ),
+    }
+  }
+  Ok(())
+}
+
+/// Computes the maintenance score of the workspace and stores it as its latest report.
+/// Dead references are taken from the latest dead reference scan rather than scanned again.
+#[instrument(level = "debug", skip(pg_pool), err)]
+pub async fn compute_workspace_health(
+  pg_pool: &PgPool,
+  workspace_id: Uuid,
+) -> Result<WorkspaceHealthReport, AppError> {
+  let computed_at = Utc::now();
+  let stale_before = computed_at - chrono::Duration::days(WORKSPACE_HEALTH_STALE_AFTER_DAYS);
+  let AFWorkspaceHealthCountsRow {
+    total_documents,
+    stale_documents,
+    unindexed_documents,
+    unused_guests,
+  } = select_workspace_health_counts(pg_pool, &workspace_id, stale_before).await?;
+  let dead_references = select_dead_reference_report(pg_pool, &workspace_id)
+    .await?
+    .map(|report| report.dead_references.len() as i64)
+    .unwrap_or(0);
+  let (storage_usage_ratio, member_usage_ratio) =
+    match get_workspace_usage_and_limit(pg_pool, &workspace_id).await {
+      Ok(usage) => (
+        (!usage.storage_bytes_unlimited)
+          .then(|| usage_ratio(usage.storage_bytes, usage.storage_bytes_limit))
+          .flatten(),
+        (usage.member_count_limit != i64::MAX)
+          .then(|| usage_ratio(usage.member_count, usage.member_count_limit))
+          .flatten(),
+      ),
+      Err(err) => {
+        warn!(
+          "failed to get the usage of workspace {} for its health: {:?}",
+          workspace_id, err
+        );
+        (None, None)
+      },
+    };
+
+  let mut report = WorkspaceHealthReport {
+    workspace_id,
+    computed_at,
+    score: 100,
+    total_documents,
+    stale_documents,
+    dead_references,
+    unindexed_documents,
+    unused_guests,
+    storage_usage_ratio,
+    member_usage_ratio,
+    recommendations: vec![],
+  };
+  report.recommendations = recommendations(&report);
+  let penalty: u32 = report
+    .recommendations
+    .iter()
+    .map(|recommendation| recommendation.penalty as u32)
+    .sum();
+  report.score = 100u32.saturating_sub(penalty) as u8;
+  upsert_workspace_health_report(pg_pool, &report).await?;
+  Ok(report)
+}
+
+fn usage_ratio(used: i64, limit: i64) -> Option<f64> {
+  (limit > 0).then(|| used as f64 / limit as f64)
+}
+
+/// Penalty proportional to `value`, reaching `max` when `value` reaches `full_at`.
+fn scaled_penalty(value: f64, full_at: f64, max: u8) -> u8 {
+  ((value / full_at).min(1.0) * max as f64).ceil() as u8
+}
+
+/// The housekeeping tasks of the workspace, most impactful first.
+fn recommendations(report: &WorkspaceHealthReport) -> Vec<WorkspaceHealthRecommendation> {
+  let mut recommendations = vec![];
+  let mut recommend = |issue, penalty, message: String| {
+    recommendations.push(WorkspaceHealthRecommendation {
+      issue,
+      penalty,
+      message,
+    })
+  };
+
+  if report.total_documents > 0 {
+    let stale_ratio = report.stale_documents as f64 / report.total_documents as f64;
+    if stale_ratio >= STALE_DOCUMENTS_RATIO_THRESHOLD {
+      recommend(
+        WorkspaceHealthIssue::StalePages,
+        scaled_penalty(stale_ratio, 1.0, 20),
+        format!(
+          "{} of {} pages have not been edited for {} days. Archive or delete the ones that are no longer needed.",
+          report.stale_documents, report.total_documents, WORKSPACE_HEALTH_STALE_AFTER_DAYS
+        ),
+      );
+    }
+    let unindexed_ratio = report.unindexed_documents as f64 / report.total_documents as f64;
+    if unindexed_ratio >= UNINDEXED_DOCUMENTS_RATIO_THRESHOLD {
+      recommend(
+        WorkspaceHealthIssue::UnindexedDocuments,
+        scaled_penalty(unindexed_ratio, 0.5, 15),
+        format!(
+          "{} pages are not indexed and cannot be found by search or AI. Reindex the workspace from its search settings.",
+          report.unindexed_documents
+        ),
+      );
+    }
+  }
+  if report.dead_references > 0 {
+    recommend(
+      WorkspaceHealthIssue::DeadReferences,
+      scaled_penalty(report.dead_references as f64, 10.0, 20),
+      format!(
+        "{} mentions point to deleted pages or removed members. Review the dead reference report to fix them.",
+        report.dead_references
+      ),
+    );
+  }
+  if report.unused_guests > 0 {
+    recommend(
+      WorkspaceHealthIssue::UnusedGuests,
+      scaled_penalty(report.unused_guests as f64, 5.0, 15),
+      format!(
+        "{} guests have not been active for {} days. Remove the guests who no longer need access.",
+        report.unused_guests, WORKSPACE_HEALTH_STALE_AFTER_DAYS
+      ),
+    );
+  }
+  if let Some(ratio) = report
+    .storage_usage_ratio
+    .filter(|ratio| *ratio >= NEAR_LIMIT_RATIO)
+  {
+    recommend(
+      WorkspaceHealthIssue::NearStorageLimit,
+      if ratio >= 1.0 { 20 } else { 10 },
+      format!(
+        "{:.0}% of the storage of the plan is used. Delete unused files or upgrade the plan.",
+        ratio * 100.0
+      ),
+    );
+  }
+  if let Some(ratio) = report
+    .member_usage_ratio
+    .filter(|ratio| *ratio >= NEAR_LIMIT_RATIO)
+  {
+    recommend(
+      WorkspaceHealthIssue::NearMemberLimit,
+      if ratio >= 1.0 { 10 } else { 5 },
+      format!(
+        "{:.0}% of the member seats of the plan are used. Remove inactive members or upgrade the plan.",
+        ratio * 100.0
+      ),
+    );
+  }
+
+  recommendations.sort_by(|a, b| b.penalty.cmp(&a.penalty));
+  recommendations
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn report() -> WorkspaceHealthReport {
+    WorkspaceHealthReport {
+      workspace_id: Uuid::new_v4(),
+      computed_at: Utc::now(),
+      score: 100,
+      total_documents: 10,
+      stale_documents: 0,
+      dead_references: 0,
+      unindexed_documents: 0,
+      unused_guests: 0,
+      storage_usage_ratio: Some(0.1),
+      member_usage_ratio: None,
+      recommendations: vec![],
+    }
+  }
+
+  #[test]
+  fn healthy_workspace_has_no_recommendation() {
+    let mut report = report();
+    report.stale_documents = 2;
+    report.storage_usage_ratio = Some(0.79);
+    assert!(recommendations(&report).is_empty());
+  }
+
+  #[test]
+  fn recommendations_are_sorted_by_penalty() {
+    let mut report = report();
+    report.stale_documents = 5;
+    report.dead_references = 30;
+    report.unused_guests = 1;
+    report.storage_usage_ratio = Some(1.2);
+    let recommendations = recommendations(&report);
+    let issues: Vec<_> = recommendations
+      .iter()
+      .map(|r| (r.issue, r.penalty))
+      .collect();
+    assert_eq!(
+      issues,
+      vec![
+        (WorkspaceHealthIssue::DeadReferences, 20),
+        (WorkspaceHealthIssue::NearStorageLimit, 20),
+        (WorkspaceHealthIssue::StalePages, 10),
+        (WorkspaceHealthIssue::UnusedGuests, 3),
+      ]
+    );
+  }
+}
@@ -0,0 +1,303 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use collab_entity::CollabType;
+use collab_folder::{Folder, ViewLayout};
+use database::collab::{CollabStore, GetCollabOrigin};
+use database_entity::dto::ExportFormat;
+use futures_lite::AsyncWrite;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::biz::collab::document::{document_to_markdown, fill_code_block_languages};
+use crate::biz::collab::folder_view::{
+  check_if_view_is_space, get_view_and_children, private_space_and_trash_view_ids, ViewTree,
+};
+use crate::biz::collab::utils::{batch_get_latest_collab_encoded, document_data_from_doc_state};
+
+const MARKDOWN_BATCH_SIZE: usize = 100;
+const UNTITLED_VIEW_NAME: &str = "Untitled";
+
+/// A document of the workspace exported as a Markdown file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownFile {
+  pub view_id: Uuid,
+  /// `/` separated path of the file, e.g. `General/Getting started.md`. Every view with
+  /// children is a directory named after the view.
+  pub path: String,
+  pub last_edited_time: i64,
+}
+
+/// Lays out the documents the user can see as files, following the folder tree. Views in the
+/// trash and in private spaces of other members are left out.
+pub fn markdown_files(
+  folder: &Folder,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<MarkdownFile>, AppError> {
+  markdown_files_under(folder, workspace_id, uid)
+}
+
+/// Like [markdown_files], for the subtree of `root_view_id` only. Paths are relative to the
+/// root view, which is not part of them.
+pub fn markdown_files_under(
+  folder: &Folder,
+  root_view_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<MarkdownFile>, AppError> {
+  let hidden = private_space_and_trash_view_ids(uid, folder)?;
+  let root = match get_view_and_children(folder, &root_view_id.to_string(), uid)? {
+    Some(root) => root,
+    None => return Ok(vec![]),
+  };
+  let mut files = vec![];
+  let mut stack: Vec<(String, &ViewTree)> = vec![];
+  push_children(&mut stack, "", &root, &hidden.other_private_space_ids);
+  while let Some((path, tree)) = stack.pop() {
+    if matches!(tree.view.layout, ViewLayout::Document) {
+      files.push(MarkdownFile {
+        view_id: Uuid::parse_str(&tree.view.id)?,
+        path: format!("{}.md", path),
+        last_edited_time: tree.view.last_edited_time,
+      });
+    }
+    push_children(&mut stack, &path, tree, &hidden.other_private_space_ids);
+  }
+  files.sort_by(|a, b| a.path.cmp(&b.path));
+  Ok(files)
+}
+
+fn push_children<'a>(
+  stack: &mut Vec<(String, &'a ViewTree)>,
+  parent_path: &str,
+  tree: &'a ViewTree,
+  excluded: &HashSet<Uuid>,
+) {
+  let mut used_names = HashSet::new();
+  for child in &tree.children {
+    if Uuid::parse_str(&child.view.id).is_ok_and(|id| excluded.contains(&id)) {
+      continue;
+    }
+    let name = unique_file_name(&mut used_names, &child.view.name);
+    let path = if parent_path.is_empty() {
+      name
+    } else {
+      format!("{}/{}", parent_path, name)
+    };
+    stack.push((path, child));
+  }
+}
+
+/// Views are named freely, so separators and control characters are replaced and siblings
+/// with the same name are numbered.
+fn unique_file_name(used_names: &mut HashSet<String>, view_name: &str) -> String {
+  let name: String = view_name
+    .trim()
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+      c if c.is_control() => ' ',
+      c => c,
+    })
+    .collect();
+  let name = match name.trim().trim_matches('.') {
+    "" => UNTITLED_VIEW_NAME.to_string(),
+    name => name.to_string(),
+  };
+  let mut unique_name = name.clone();
+  let mut index = 1;
+  while !used_names.insert(unique_name.to_lowercase()) {
+    index += 1;
+    unique_name = format!("{} ({})", name, index);
+  }
+  unique_name
+}
+
+/// Renders the documents as Markdown. Documents that cannot be read are skipped.
+pub async fn render_markdown_documents(
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_origin: GetCollabOrigin,
+  workspace_id: Uuid,
+  view_ids: &[Uuid],
+) -> Result<HashMap<Uuid, String>, AppError> {
+  render_documents(
+    collab_storage,
+    collab_origin,
+    workspace_id,
+    view_ids,
+    ExportFormat::Markdown,
+  )
+  .await
+}
+
+/// A zip archive of exported documents.
+pub struct ExportBundle {
+  pub documents: i32,
+  pub bytes: Vec<u8>,
+}
+
+/// Writes the files in a zip archive, in the given format. Documents that cannot be read are
+/// left out of the archive.
+pub async fn build_export_bundle(
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_origin: GetCollabOrigin,
+  workspace_id: Uuid,
+  files: Vec<MarkdownFile>,
+  format: ExportFormat,
+) -> Result<ExportBundle, AppError> {
+  let (bytes, documents) = write_export_bundle(
+    Vec::new(),
+    collab_storage,
+    collab_origin,
+    workspace_id,
+    files,
+    format,
+  )
+  .await?;
+  Ok(ExportBundle { documents, bytes })
+}
+
+/// Like [build_export_bundle], writing the archive to `writer` as the documents are rendered,
+/// so that at most one batch of documents is held in memory. Returns the writer and the number
+/// of documents written.
+pub async fn write_export_bundle<W>(
+  writer: W,
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_origin: GetCollabOrigin,
+  workspace_id: Uuid,
+  files: Vec<MarkdownFile>,
+  format: ExportFormat,
+) -> Result<(W, i32), AppError>
+where
+  W: AsyncWrite + Unpin,
+{
+  let mut writer = ZipFileWriter::new(writer);
+  let mut count = 0;
+  for batch in files.chunks(MARKDOWN_BATCH_SIZE) {
+    let view_ids: Vec<Uuid> = batch.iter().map(|file| file.view_id).collect();
+    let mut documents = render_documents(
+      collab_storage,
+      collab_origin.clone(),
+      workspace_id,
+      &view_ids,
+      format,
+    )
+    .await?;
+    for file in batch {
+      if let Some(content) = documents.remove(&file.view_id) {
+        let path = match format {
+          ExportFormat::Markdown => file.path.clone(),
+          ExportFormat::Json => format!("{}.json", file.path.trim_end_matches(".md")),
+        };
+        let builder = ZipEntryBuilder::new(path.into(), Compression::Deflate);
+        writer
+          .write_entry_whole(builder, content.as_bytes())
+          .await
+          .map_err(|err| AppError::Internal(err.into()))?;
+        count += 1;
+      }
+    }
+  }
+  let writer = writer
+    .close()
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?;
+  Ok((writer, count))
+}
+
+/// Exports the documents of a space the user can see, laid out as in [markdown_files_under].
+pub async fn export_space(
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  uid: i64,
+  workspace_id: Uuid,
+  space_id: Uuid,
+  format: ExportFormat,
+) -> Result<ExportBundle, AppError> {
+  let folder = collab_instance_cache.get_folder(workspace_id).await?;
+  let space_not_found = || AppError::RecordNotFound(format!("space {} not found", space_id));
+  let space = folder
+    .get_view(&space_id.to_string(), uid)
+    .ok_or_else(space_not_found)?;
+  if !check_if_view_is_space(&space) || space.parent_view_id != workspace_id.to_string() {
+    return Err(space_not_found());
+  }
+  let hidden = private_space_and_trash_view_ids(uid, &folder)?;
+  if hidden.other_private_space_ids.contains(&space_id) {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  let files = markdown_files_under(&folder, &space_id, uid)?;
+  drop(folder);
+  build_export_bundle(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    workspace_id,
+    files,
+    format,
+  )
+  .await
+}
+
+async fn render_documents(
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_origin: GetCollabOrigin,
+  workspace_id: Uuid,
+  view_ids: &[Uuid],
+  format: ExportFormat,
+) -> Result<HashMap<Uuid, String>, AppError> {
+  let mut documents = HashMap::with_capacity(view_ids.len());
+  for batch in view_ids.chunks(MARKDOWN_BATCH_SIZE) {
+    let encoded_collabs = batch_get_latest_collab_encoded(
+      collab_storage,
+      collab_origin.clone(),
+      workspace_id,
+      batch,
+      CollabType::Document,
+    )
+    .await?;
+    let rendered = tokio::task::spawn_blocking(move || {
+      encoded_collabs
+        .into_iter()
+        .filter_map(|(view_id, encoded_collab)| {
+          let rendered = document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &view_id)
+            .and_then(|data| match format {
+              ExportFormat::Markdown => Ok(document_to_markdown(&data)),
+              ExportFormat::Json => {
+                let mut data = data;
+                fill_code_block_languages(&mut data);
+                serde_json::to_string_pretty(&data).map_err(AppError::from)
+              },
+            });
+          match rendered {
+            Ok(content) => Some((view_id, content)),
+            Err(err) => {
+              warn!("failed to render document {} as {:?}: {}", view_id, format, err);
+              None
+            },
+          }
+        })
+        .collect::<Vec<_>>()
+    })
+    .await?;
+    documents.extend(rendered);
+  }
+  Ok(documents)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unique_markdown_file_names() {
+    let mut used_names = HashSet::new();
+    assert_eq!(unique_file_name(&mut used_names, "Notes"), "Notes");
+    assert_eq!(unique_file_name(&mut used_names, "notes"), "notes (2)");
+    assert_eq!(unique_file_name(&mut used_names, "a/b: c?"), "a-b- c-");
+    assert_eq!(unique_file_name(&mut used_names, " .. "), "Untitled");
+  }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::ops::DerefMut;
+
+use app_error::AppError;
+use database::member_field::{
+  delete_workspace_member_field, insert_workspace_member_field, select_workspace_member_fields,
+  update_workspace_member_field, upsert_workspace_member_custom_fields,
+};
+use database_entity::dto::{
+  CreateWorkspaceMemberFieldParams, UpdateWorkspaceMemberFieldParams, WorkspaceMemberField,
+  WorkspaceMemberFields,
+};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::middleware::deadline::apply_statement_timeout;
+
+const MAX_MEMBER_FIELDS: usize = 50;
+const MAX_MEMBER_FIELD_NAME_LENGTH: usize = 100;
+const MAX_MEMBER_FIELD_VALUE_LENGTH: usize = 256;
+
+pub async fn list_workspace_member_fields(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceMemberFields, AppError> {
+  let fields = select_workspace_member_fields(pg_pool, workspace_id).await?;
+  Ok(WorkspaceMemberFields { fields })
+}
+
+pub async fn create_workspace_member_field(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreateWorkspaceMemberFieldParams,
+) -> Result<WorkspaceMemberField, AppError> {
+  let name = validate_name(&params.name)?;
+  if select_workspace_member_fields(pg_pool, workspace_id)
+    .await?
+    .len()
+    >= MAX_MEMBER_FIELDS
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "a workspace can have at most {} member fields",
+      MAX_MEMBER_FIELDS
+    )));
+  }
+  insert_workspace_member_field(pg_pool, workspace_id, name, params.editable_by_member).await
+}
+
+pub async fn update_workspace_member_field_by_id(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  field_id: &Uuid,
+  params: UpdateWorkspaceMemberFieldParams,
+) -> Result<WorkspaceMemberField, AppError> {
+  let name = params.name.as_deref().map(validate_name).transpose()?;
+  update_workspace_member_field(
+    pg_pool,
+    workspace_id,
+    field_id,
+    name,
+    params.editable_by_member,
+  )
+  .await?
+  .ok_or_else(|| member_field_not_found(field_id))
+}
+
+/// Deletes the field and the values members have for it.
+pub async fn delete_workspace_member_field_by_id(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  field_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut tx = pg_pool.begin().await?;
+  apply_statement_timeout(tx.deref_mut()).await?;
+  if !delete_workspace_member_field(&mut tx, workspace_id, field_id).await? {
+    return Err(member_field_not_found(field_id));
+  }
+  tx.commit().await?;
+  Ok(())
+}
+
+/// Checks that the values are for fields of the workspace, editable by members unless they
+/// are set by an owner. Returns the values to store, empty values clearing the field.
+pub async fn validate_member_custom_fields<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  values: &HashMap<Uuid, String>,
+  set_by_owner: bool,
+) -> Result<HashMap<Uuid, Option<String>>, AppError> {
+  if values.is_empty() {
+    return Ok(HashMap::new());
+  }
+  let fields: HashMap<Uuid, WorkspaceMemberField> =
+    select_workspace_member_fields(executor, workspace_id)
+      .await?
+      .into_iter()
+      .map(|field| (field.field_id, field))
+      .collect();
+  let mut validated = HashMap::with_capacity(values.len());
+  for (field_id, value) in values {
+    let field = fields
+      .get(field_id)
+      .ok_or_else(|| member_field_not_found(field_id))?;
+    if !set_by_owner && !field.editable_by_member {
+      return Err(AppError::NotEnoughPermissions);
+    }
+    let value = value.trim();
+    if value.chars().count() > MAX_MEMBER_FIELD_VALUE_LENGTH {
+      return Err(AppError::InvalidRequest(format!(
+        "the value of the member field {} has at most {} characters",
+        field.name, MAX_MEMBER_FIELD_VALUE_LENGTH
+      )));
+    }
+    let value = (!value.is_empty()).then(|| value.to_string());
+    validated.insert(*field_id, value);
+  }
+  Ok(validated)
+}
+
+/// Sets the custom fields of a member on behalf of an owner, who can set every field.
+pub async fn set_member_custom_fields_by_owner(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+  values: &HashMap<Uuid, String>,
+) -> Result<(), AppError> {
+  let values = validate_member_custom_fields(pg_pool, workspace_id, values, true).await?;
+  if !values.is_empty() {
+    upsert_workspace_member_custom_fields(pg_pool, workspace_id, uid, &values).await?;
+  }
+  Ok(())
+}
+
+fn member_field_not_found(field_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("member field {} not found", field_id))
+}
+
+fn validate_name(name: &str) -> Result<&str, AppError> {
+  let name = name.trim();
+  if name.is_empty() || name.chars().count() > MAX_MEMBER_FIELD_NAME_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "a member field name has 1 to {} characters",
+      MAX_MEMBER_FIELD_NAME_LENGTH
+    )));
+  }
+  Ok(name)
+}
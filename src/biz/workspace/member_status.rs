@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use chrono::Utc;
+use database::workspace::{
+  select_active_workspace_member_status, select_active_workspace_member_statuses,
+  upsert_workspace_member_status,
+};
+use database_entity::dto::{
+  AFWorkspaceMember, MentionablePersonWithLastMentionedTime, WorkspaceMemberStatus,
+};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::biz::notification::ops::create_workspace_member_notifications;
+
+const MAX_STATUS_TEXT_LENGTH: usize = 100;
+const MEMBER_STATUS_CHANGED_NOTIFICATION: &str = "workspace_member_status_changed";
+
+/// Sets or clears (`None`) the status of the member, and notifies the other members so their
+/// clients can update mention pickers and member lists without polling.
+pub async fn set_workspace_member_status(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+  status: Option<WorkspaceMemberStatus>,
+) -> Result<(), AppError> {
+  if let Some(status) = &status {
+    validate_member_status(status)?;
+  }
+  upsert_workspace_member_status(pg_pool, workspace_id, uid, status.as_ref()).await?;
+
+  let payload = serde_json::json!({
+    "uid": uid,
+    "status": status,
+  });
+  if let Err(err) = create_workspace_member_notifications(
+    pg_pool,
+    workspace_id,
+    MEMBER_STATUS_CHANGED_NOTIFICATION,
+    &payload,
+    uid,
+  )
+  .await
+  {
+    warn!(
+      "failed to notify the members of workspace {} about the status change of uid={}: {:?}",
+      workspace_id, uid, err
+    );
+  }
+  Ok(())
+}
+
+fn validate_member_status(status: &WorkspaceMemberStatus) -> Result<(), AppError> {
+  if status.text.trim().is_empty() {
+    return Err(AppError::InvalidRequest(
+      "Status text must not be empty".to_string(),
+    ));
+  }
+  if status.text.chars().count() > MAX_STATUS_TEXT_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "Status text can have at most {} characters",
+      MAX_STATUS_TEXT_LENGTH
+    )));
+  }
+  if matches!(status.until, Some(until) if until <= Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "Status expiry must be in the future".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+pub async fn attach_statuses_to_members(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  members: &mut [AFWorkspaceMember],
+) -> Result<(), AppError> {
+  let mut statuses: HashMap<i64, WorkspaceMemberStatus> =
+    select_active_workspace_member_statuses(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .map(|(uid, _, status)| (uid, status))
+      .collect();
+  for member in members {
+    member.status = statuses.remove(&member.uid);
+  }
+  Ok(())
+}
+
+pub async fn attach_statuses_to_mentionable_persons(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  persons: &mut [MentionablePersonWithLastMentionedTime],
+) -> Result<(), AppError> {
+  let mut statuses: HashMap<Uuid, WorkspaceMemberStatus> =
+    select_active_workspace_member_statuses(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .map(|(_, uuid, status)| (uuid, status))
+      .collect();
+  for person in persons {
+    person.status = statuses.remove(&person.uuid);
+  }
+  Ok(())
+}
+
+pub async fn get_member_status_by_uuid(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  person_id: &Uuid,
+) -> Result<Option<WorkspaceMemberStatus>, AppError> {
+  select_active_workspace_member_status(pg_pool, workspace_id, person_id).await
+}
@@ -0,0 +1,698 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::write_guard::WRITE_BLOCKS_TTL;
+use collab_database::workspace_database::DatabaseMeta;
+use collab_entity::CollabType;
+use collab_folder::{Folder, Section, SectionItem, View};
+use collab_rt_entity::user::RealtimeUser;
+use database::collab::{move_collabs_to_workspace, select_workspace_collabs, GetCollabOrigin};
+use database::file::s3_client_impl::S3BucketStorage;
+use database::resource_usage::get_all_workspace_blob_metadata;
+use database::workspace::{
+  claim_workspace_merge, complete_workspace_merge, merge_workspace_members,
+  move_workspace_page_data, release_workspace_merge, select_merged_workspace_member_count,
+  select_workspace, select_workspace_member_uids, select_workspace_merge,
+  update_workspace_merge_step, AFWorkspaceMergeRow,
+};
+use database_entity::dto::{CollabParams, QueryCollab, QueryCollabResult};
+use shared_entity::dto::workspace_dto::{MergeWorkspaceParams, MergedWorkspace, SpacePermission};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::events::WorkspaceEvent;
+use super::ops::delete_workspace_for_user;
+use super::page_view::{create_space, update_workspace_database_data, update_workspace_folder};
+use crate::biz::collab::folder_view::check_if_view_is_space;
+use crate::biz::collab::ops::get_latest_workspace_database;
+use crate::biz::subscription::ops::get_user_resource_limit_status;
+use crate::state::AppState;
+
+/// Collabs are moved in chunks, each in its own transaction, to keep transactions short.
+const MOVE_COLLAB_CHUNK_SIZE: usize = 100;
+const MERGED_SPACE_ICON: &str = "space_icon_1";
+const MERGED_SPACE_ICON_COLOR: &str = "0xFFA34AFD";
+/// How long a request holds the merge it runs. The hold is extended after every step.
+const MERGE_LEASE_SECS: i64 = 1800;
+
+/// Steps of a merge, in the order they run. The merge records the last step that completed, so
+/// that a merge that failed resumes after it when it is requested again. Every step can run again
+/// after it failed halfway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(i16)]
+enum MergeStep {
+  Started = 0,
+  CollabsMoved = 1,
+  ViewsInserted = 2,
+  MembersMerged = 3,
+  BlobsMoved = 4,
+  Completed = 5,
+}
+
+impl From<i16> for MergeStep {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => MergeStep::CollabsMoved,
+      2 => MergeStep::ViewsInserted,
+      3 => MergeStep::MembersMerged,
+      4 => MergeStep::BlobsMoved,
+      5 => MergeStep::Completed,
+      _ => MergeStep::Started,
+    }
+  }
+}
+
+/// Merges the source workspace into `destination_workspace_id`. The pages of the source become
+/// a new space of the destination, and its collabs, databases, members, page data and blobs are
+/// moved along. Archived spaces stay archived, and published pages stay published, with the
+/// namespaces of the source kept as aliases of the destination. The source workspace is deleted afterwards, and blob links that
+/// still point to it are redirected to the destination.
+///
+/// The source workspace is read-only while it is merged. A merge that failed keeps it so, and
+/// resumes where it stopped when it is requested again. The counts returned by a resumed merge
+/// only cover what it moved itself.
+pub async fn merge_workspaces(
+  state: &AppState,
+  user: RealtimeUser,
+  destination_workspace_id: Uuid,
+  params: MergeWorkspaceParams,
+) -> Result<MergedWorkspace, AppError> {
+  let uid = user.uid;
+  let source_workspace_id = params.source_workspace_id;
+  if source_workspace_id == destination_workspace_id {
+    return Err(AppError::InvalidRequest(
+      "a workspace can't be merged into itself".to_string(),
+    ));
+  }
+  let destination = select_workspace(&state.pg_pool, &destination_workspace_id).await?;
+  if destination.owner_uid != Some(uid) {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  if let Some(merge) = select_workspace_merge(&state.pg_pool, &destination_workspace_id).await? {
+    if merge.completed_at.is_none() {
+      return Err(AppError::InvalidRequest(format!(
+        "workspace {} is being merged into another workspace",
+        destination_workspace_id
+      )));
+    }
+  }
+  let source = match select_workspace(&state.pg_pool, &source_workspace_id).await {
+    Ok(source) => source,
+    Err(err) if err.is_record_not_found() => {
+      return complete_deleted_source_merge(state, uid, &source_workspace_id, err).await;
+    },
+    Err(err) => return Err(err),
+  };
+  if source.owner_uid != Some(uid) {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  check_merged_member_limit(state, uid, &source_workspace_id, &destination_workspace_id).await?;
+
+  let merge = claim_workspace_merge(
+    &state.pg_pool,
+    &source_workspace_id,
+    &destination_workspace_id,
+    &Uuid::new_v4(),
+    uid,
+    MERGE_LEASE_SECS,
+  )
+  .await?
+  .ok_or_else(|| {
+    AppError::RecordAlreadyExists(format!(
+      "workspace {} is already being merged",
+      source_workspace_id
+    ))
+  })?;
+  if MergeStep::from(merge.step) == MergeStep::Started {
+    // Other servers see the source workspace as read-only once their cached state expires
+    state.collab_write_guard.invalidate(&source_workspace_id);
+    tokio::time::sleep(WRITE_BLOCKS_TTL).await;
+  }
+
+  let space_name = params
+    .space_name
+    .filter(|name| !name.trim().is_empty())
+    .unwrap_or_else(|| source.workspace_name.clone().unwrap_or_default());
+  let result = run_merge(state, user, &merge, &space_name).await;
+  if let Err(err) = &result {
+    warn!(
+      "merge of workspace {} into {} stopped: {}",
+      source_workspace_id, destination_workspace_id, err
+    );
+    if let Err(err) = release_workspace_merge(&state.pg_pool, &source_workspace_id).await {
+      error!(
+        "failed to release the merge of workspace {}: {}",
+        source_workspace_id, err
+      );
+    }
+  }
+  result
+}
+
+async fn run_merge(
+  state: &AppState,
+  user: RealtimeUser,
+  merge: &AFWorkspaceMergeRow,
+  space_name: &str,
+) -> Result<MergedWorkspace, AppError> {
+  let uid = user.uid;
+  let source_workspace_id = merge.source_workspace_id;
+  let destination_workspace_id = merge.destination_workspace_id;
+  let mut step = MergeStep::from(merge.step);
+
+  let source_folder = state.ws_server.get_folder(source_workspace_id).await?;
+  let member_uids = select_workspace_member_uids(&state.pg_pool, &source_workspace_id).await?;
+  let views = source_views(&source_folder, &source_workspace_id, uid);
+  let private_view_ids = private_view_ids(&source_folder, &views, &member_uids);
+  let section_items = section_items(&source_folder, &views, &member_uids);
+  let (source_database_oid, source_workspace_database) = get_latest_workspace_database(
+    &state.collab_storage,
+    &state.pg_pool,
+    GetCollabOrigin::Server,
+    source_workspace_id,
+  )
+  .await?;
+
+  let mut moved_collab_count = 0;
+  if step < MergeStep::CollabsMoved {
+    moved_collab_count = move_collabs(
+      state,
+      uid,
+      &source_workspace_id,
+      &destination_workspace_id,
+      &source_database_oid,
+    )
+    .await?;
+    step = complete_step(state, &source_workspace_id, MergeStep::CollabsMoved).await?;
+  }
+
+  let mut moved_view_count = 0;
+  if step < MergeStep::ViewsInserted {
+    let database_metas = source_workspace_database
+      .body
+      .get_all_meta(&source_workspace_database.collab.transact());
+    insert_merged_space(
+      state,
+      user.clone(),
+      merge,
+      space_name,
+      &views,
+      &private_view_ids,
+      &section_items,
+      database_metas,
+    )
+    .await?;
+    moved_view_count = views.len();
+    step = complete_step(state, &source_workspace_id, MergeStep::ViewsInserted).await?;
+  }
+
+  let mut merged_member_count = 0;
+  let mut unpublished_view_count = 0;
+  if step < MergeStep::MembersMerged {
+    let mut txn = state.pg_pool.begin().await?;
+    let members =
+      merge_workspace_members(&mut txn, &source_workspace_id, &destination_workspace_id).await?;
+    let left_behind =
+      move_workspace_page_data(&mut txn, &source_workspace_id, &destination_workspace_id).await?;
+    txn.commit().await?;
+    // The archived spaces of the source are now archived spaces of the destination
+    state
+      .collab_write_guard
+      .invalidate(&destination_workspace_id);
+    merged_member_count = members.len();
+    unpublished_view_count = left_behind as usize;
+    if unpublished_view_count > 0 {
+      warn!(
+        "{} published pages of workspace {} were unpublished by its merge into {}, their publish name is taken",
+        unpublished_view_count, source_workspace_id, destination_workspace_id
+      );
+    }
+    state
+      .workspace_events
+      .publish(WorkspaceEvent::MembersMerged {
+        workspace_id: destination_workspace_id,
+        members,
+      })
+      .await?;
+    step = complete_step(state, &source_workspace_id, MergeStep::MembersMerged).await?;
+  }
+
+  let mut moved_blob_count = 0;
+  if step < MergeStep::BlobsMoved {
+    moved_blob_count = move_blobs(state, &source_workspace_id, &destination_workspace_id).await?;
+    complete_step(state, &source_workspace_id, MergeStep::BlobsMoved).await?;
+  }
+
+  delete_workspace_for_user(
+    state.pg_pool.clone(),
+    state.redis_connection_manager.clone(),
+    source_workspace_id,
+    state.bucket_storage.clone(),
+  )
+  .await?;
+  complete_workspace_merge(
+    &state.pg_pool,
+    &source_workspace_id,
+    MergeStep::Completed as i16,
+  )
+  .await?;
+  state.collab_write_guard.invalidate(&source_workspace_id);
+
+  info!(
+    "merged workspace {} into {}: {} views, {} collabs, {} blobs, {} members",
+    source_workspace_id,
+    destination_workspace_id,
+    moved_view_count,
+    moved_collab_count,
+    moved_blob_count,
+    merged_member_count
+  );
+  Ok(MergedWorkspace {
+    space_id: merge.space_id,
+    moved_view_count,
+    moved_collab_count,
+    moved_blob_count,
+    merged_member_count,
+    unpublished_view_count,
+  })
+}
+
+async fn complete_step(
+  state: &AppState,
+  source_workspace_id: &Uuid,
+  step: MergeStep,
+) -> Result<MergeStep, AppError> {
+  update_workspace_merge_step(
+    &state.pg_pool,
+    source_workspace_id,
+    step as i16,
+    MERGE_LEASE_SECS,
+  )
+  .await?;
+  Ok(step)
+}
+
+/// The source workspace is deleted once everything was moved out of it. A merge that stopped
+/// right after the deletion only has to be recorded as complete.
+async fn complete_deleted_source_merge(
+  state: &AppState,
+  uid: i64,
+  source_workspace_id: &Uuid,
+  not_found: AppError,
+) -> Result<MergedWorkspace, AppError> {
+  let merge = match select_workspace_merge(&state.pg_pool, source_workspace_id).await? {
+    Some(merge)
+      if merge.completed_at.is_none()
+        && merge.merged_by == uid
+        && MergeStep::from(merge.step) >= MergeStep::BlobsMoved =>
+    {
+      merge
+    },
+    _ => return Err(not_found),
+  };
+  complete_workspace_merge(
+    &state.pg_pool,
+    source_workspace_id,
+    MergeStep::Completed as i16,
+  )
+  .await?;
+  state.collab_write_guard.invalidate(source_workspace_id);
+  Ok(MergedWorkspace {
+    space_id: merge.space_id,
+    moved_view_count: 0,
+    moved_collab_count: 0,
+    moved_blob_count: 0,
+    merged_member_count: 0,
+    unpublished_view_count: 0,
+  })
+}
+
+/// Creates the space of the merge in the destination, unless a previous attempt did, and inserts
+/// the source views and databases in it. Inserting a view or a database again leaves it as is.
+async fn insert_merged_space(
+  state: &AppState,
+  user: RealtimeUser,
+  merge: &AFWorkspaceMergeRow,
+  space_name: &str,
+  views: &[View],
+  private_view_ids: &[(String, i64)],
+  section_items: &[MemberSectionItems],
+  database_metas: Vec<DatabaseMeta>,
+) -> Result<(), AppError> {
+  let uid = user.uid;
+  let destination_workspace_id = merge.destination_workspace_id;
+  let destination_folder = state.ws_server.get_folder(destination_workspace_id).await?;
+  if destination_folder
+    .get_view(&merge.space_id.to_string(), uid)
+    .is_none()
+  {
+    create_space(
+      state,
+      user.clone(),
+      destination_workspace_id,
+      &SpacePermission::PublicToAll,
+      space_name,
+      MERGED_SPACE_ICON,
+      MERGED_SPACE_ICON_COLOR,
+      Some(merge.space_id),
+    )
+    .await?;
+  }
+  let mut destination_folder = state.ws_server.get_folder(destination_workspace_id).await?;
+  let folder_update = insert_source_views(
+    &mut destination_folder,
+    &merge.source_workspace_id,
+    &merge.space_id,
+    views,
+    private_view_ids,
+    section_items,
+    uid,
+  );
+  update_workspace_folder(state, user.clone(), destination_workspace_id, folder_update).await?;
+
+  if database_metas.is_empty() {
+    return Ok(());
+  }
+  let (destination_database_oid, mut destination_workspace_database) =
+    get_latest_workspace_database(
+      &state.collab_storage,
+      &state.pg_pool,
+      GetCollabOrigin::Server,
+      destination_workspace_id,
+    )
+    .await?;
+  let database_update = {
+    let mut txn = destination_workspace_database.collab.transact_mut();
+    for meta in database_metas {
+      destination_workspace_database.body.add_database(
+        &mut txn,
+        &meta.database_id,
+        meta.linked_views,
+      );
+    }
+    txn.encode_update_v1()
+  };
+  update_workspace_database_data(
+    &state.metrics.appflowy_web_metrics,
+    &state.ws_server,
+    user,
+    destination_workspace_id,
+    destination_database_oid,
+    database_update,
+  )
+  .await
+}
+
+async fn check_merged_member_limit(
+  state: &AppState,
+  uid: i64,
+  source_workspace_id: &Uuid,
+  destination_workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let member_count = select_merged_workspace_member_count(
+    &state.pg_pool,
+    source_workspace_id,
+    destination_workspace_id,
+  )
+  .await?;
+  let resource_status = get_user_resource_limit_status(&state.pg_pool, uid).await?;
+  if member_count > resource_status.member_limit {
+    return Err(AppError::WorkspaceMemberLimitExceeded(format!(
+      "Member limit exceeded. Plan: {}, Merged: {}, Limit: {}. Please upgrade your subscription.",
+      resource_status.plan_code, member_count, resource_status.member_limit
+    )));
+  }
+  Ok(())
+}
+
+/// Views of the source workspace, parents before their children. Views in the trash, and
+/// their children, are left out.
+fn source_views(folder: &Folder, workspace_id: &Uuid, uid: i64) -> Vec<View> {
+  let trash_ids: HashSet<String> = folder
+    .get_all_trash_sections(uid)
+    .into_iter()
+    .map(|section| section.id)
+    .collect();
+  let mut visited = HashSet::from([workspace_id.to_string()]);
+  let mut views = vec![];
+  let mut level = vec![workspace_id.to_string()];
+  while !level.is_empty() {
+    let mut next_level = vec![];
+    for parent_id in &level {
+      for view in folder.get_views_belong_to(parent_id, uid) {
+        if trash_ids.contains(&view.id) || !visited.insert(view.id.clone()) {
+          continue;
+        }
+        next_level.push(view.id.clone());
+        views.push(view.as_ref().clone());
+      }
+    }
+    level = next_level;
+  }
+  views
+}
+
+/// Views of the source workspace that are private, with the member they are private to.
+fn private_view_ids(folder: &Folder, views: &[View], member_uids: &[i64]) -> Vec<(String, i64)> {
+  let view_ids: HashSet<&str> = views.iter().map(|view| view.id.as_str()).collect();
+  let mut private_view_ids = vec![];
+  for member_uid in member_uids {
+    for section in folder.get_my_private_sections(*member_uid) {
+      if view_ids.contains(section.id.as_str()) {
+        private_view_ids.push((section.id, *member_uid));
+      }
+    }
+  }
+  private_view_ids
+}
+
+/// Favorite and recent items of a member of the source workspace.
+struct MemberSectionItems {
+  uid: i64,
+  favorites: Vec<SectionItem>,
+  recents: Vec<SectionItem>,
+}
+
+/// Favorite and recent items of the members of the source workspace, limited to the views that
+/// are moved.
+fn section_items(folder: &Folder, views: &[View], member_uids: &[i64]) -> Vec<MemberSectionItems> {
+  let view_ids: HashSet<&str> = views.iter().map(|view| view.id.as_str()).collect();
+  let moved = |items: Vec<SectionItem>| -> Vec<SectionItem> {
+    items
+      .into_iter()
+      .filter(|item| view_ids.contains(item.id.as_str()))
+      .collect()
+  };
+  member_uids
+    .iter()
+    .map(|uid| MemberSectionItems {
+      uid: *uid,
+      favorites: moved(folder.get_my_favorite_sections(*uid)),
+      recents: moved(folder.get_my_recent_sections(*uid)),
+    })
+    .filter(|items| !items.favorites.is_empty() || !items.recents.is_empty())
+    .collect()
+}
+
+/// Moves all the collabs of the source workspace, except the ones that belong to the workspace
+/// itself: its folder, its workspace database and the user awareness of its members.
+async fn move_collabs(
+  state: &AppState,
+  uid: i64,
+  source_workspace_id: &Uuid,
+  destination_workspace_id: &Uuid,
+  source_database_oid: &Uuid,
+) -> Result<usize, AppError> {
+  let collabs: Vec<(Uuid, CollabType)> =
+    select_workspace_collabs(&state.pg_pool, source_workspace_id)
+      .await?
+      .into_iter()
+      .filter(|(oid, collab_type)| {
+        oid != source_workspace_id
+          && oid != source_database_oid
+          && !matches!(
+            collab_type,
+            CollabType::Folder | CollabType::WorkspaceDatabase | CollabType::UserAwareness
+          )
+      })
+      .collect();
+  let mut moved = 0;
+  for chunk in collabs.chunks(MOVE_COLLAB_CHUNK_SIZE) {
+    // Read through the collab storage so that updates not yet flushed to the database are
+    // carried over.
+    let queries = chunk
+      .iter()
+      .map(|(oid, collab_type)| QueryCollab::new(*oid, *collab_type))
+      .collect();
+    let mut results = state
+      .collab_storage
+      .batch_get_collab(&uid, *source_workspace_id, queries)
+      .await;
+    let oids: Vec<Uuid> = chunk.iter().map(|(oid, _)| *oid).collect();
+    let mut txn = state.pg_pool.begin().await?;
+    move_collabs_to_workspace(
+      &mut txn,
+      source_workspace_id,
+      destination_workspace_id,
+      &oids,
+    )
+    .await?;
+    for (oid, collab_type) in chunk {
+      let encoded_collab_v1 = match results.remove(oid) {
+        Some(QueryCollabResult::Success { encode_collab_v1 }) => encode_collab_v1,
+        Some(QueryCollabResult::Failed { error }) => {
+          warn!("failed to read collab {} for the merge: {}", oid, error);
+          continue;
+        },
+        None => continue,
+      };
+      let params = CollabParams {
+        object_id: *oid,
+        collab_type: *collab_type,
+        encoded_collab_v1: encoded_collab_v1.into(),
+        updated_at: None,
+      };
+      state
+        .collab_storage
+        .upsert_new_collab_with_transaction(
+          *destination_workspace_id,
+          &uid,
+          params,
+          &mut txn,
+          "merge workspace collab",
+        )
+        .await?;
+    }
+    txn.commit().await?;
+    moved += chunk.len();
+  }
+  Ok(moved)
+}
+
+/// Inserts the source views in the destination folder, with the favorite and recent items of
+/// the members. Top level views of the source, spaces included, become children of `space_id`.
+fn insert_source_views(
+  folder: &mut Folder,
+  source_workspace_id: &Uuid,
+  space_id: &Uuid,
+  views: &[View],
+  private_view_ids: &[(String, i64)],
+  section_items: &[MemberSectionItems],
+  uid: i64,
+) -> Vec<u8> {
+  let source_workspace_id = source_workspace_id.to_string();
+  let view_ids: HashSet<&str> = views.iter().map(|view| view.id.as_str()).collect();
+  let mut txn = folder.collab.transact_mut();
+  for view in views {
+    let mut view = view.clone();
+    if view.parent_view_id == source_workspace_id {
+      view.parent_view_id = space_id.to_string();
+      if check_if_view_is_space(&view) {
+        view.extra = None;
+      }
+    }
+    view
+      .children
+      .items
+      .retain(|child| view_ids.contains(child.id.as_str()));
+    folder.body.views.insert(&mut txn, view, None, uid);
+  }
+  for (view_id, member_uid) in private_view_ids {
+    folder.body.views.update_view(
+      &mut txn,
+      view_id,
+      |update| update.set_private(true).done(),
+      *member_uid,
+    );
+  }
+  for member_items in section_items {
+    for (section, items) in [
+      (Section::Favorite, &member_items.favorites),
+      (Section::Recent, &member_items.recents),
+    ] {
+      if items.is_empty() {
+        continue;
+      }
+      if let Some(op) = folder
+        .body
+        .section
+        .section_op(&txn, section, member_items.uid)
+      {
+        op.add_sections_item(&mut txn, items.clone());
+      }
+    }
+  }
+  txn.encode_update_v1()
+}
+
+/// Moves the blobs of the source workspace, looking them up in the upload storage first. Fails
+/// when a blob could not be moved, so that the source workspace, and the blobs left in it, are
+/// not deleted. Moved blobs are no longer listed in the source, a retry moves the others.
+async fn move_blobs(
+  state: &AppState,
+  source_workspace_id: &Uuid,
+  destination_workspace_id: &Uuid,
+) -> Result<usize, AppError> {
+  let mut storages: Vec<Arc<S3BucketStorage>> = vec![];
+  storages.extend(state.qiniu_bucket_storage.clone());
+  storages.push(state.bucket_storage.clone());
+
+  let blobs = get_all_workspace_blob_metadata(&state.pg_pool, source_workspace_id).await?;
+  let mut moved = 0;
+  let mut failed = 0;
+  for blob in blobs {
+    match move_blob(
+      &storages,
+      source_workspace_id,
+      destination_workspace_id,
+      &blob.file_id,
+    )
+    .await
+    {
+      Ok(()) => moved += 1,
+      // Missing from every storage: there is nothing left to move
+      Err(err) if err.is_record_not_found() => warn!(
+        "blob {} of workspace {} is missing from the storage: {}",
+        blob.file_id, source_workspace_id, err
+      ),
+      Err(err) => {
+        warn!(
+          "failed to move blob {} of workspace {}: {}",
+          blob.file_id, source_workspace_id, err
+        );
+        failed += 1;
+      },
+    }
+  }
+  if failed > 0 {
+    return Err(AppError::Internal(anyhow!(
+      "failed to move {} blobs of workspace {}",
+      failed,
+      source_workspace_id
+    )));
+  }
+  Ok(moved)
+}
+
+async fn move_blob(
+  storages: &[Arc<S3BucketStorage>],
+  source_workspace_id: &Uuid,
+  destination_workspace_id: &Uuid,
+  file_id: &str,
+) -> Result<(), AppError> {
+  for storage in storages {
+    match storage
+      .move_blob(source_workspace_id, destination_workspace_id, file_id)
+      .await
+    {
+      Err(err) if err.is_record_not_found() => continue,
+      result => return result,
+    }
+  }
+  Err(AppError::RecordNotFound(format!(
+    "blob {} not found in any storage",
+    file_id
+  )))
+}
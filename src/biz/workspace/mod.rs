@@ -1,11 +1,39 @@
+pub mod blob_integrity;
+pub mod blob_tiering;
+pub mod collab_permission_sync;
+pub mod dead_reference;
 pub mod duplicate;
+pub mod egress;
+pub mod events;
+pub mod health;
 pub mod invite;
 pub mod join_request;
+pub mod markdown_export;
+pub mod member_export;
+pub mod member_field;
+pub mod member_status;
+pub mod merge;
 pub mod ops;
+pub mod page_activity;
+pub mod page_reaction;
 pub mod page_view;
+pub mod page_watch;
 pub mod publish;
+pub mod publish_database_views;
 pub mod publish_dup;
+pub mod publish_integrity;
+pub mod publish_preview;
+pub mod publish_row_filter;
+pub mod publish_stats;
+pub mod publish_theme;
+pub mod publish_variant;
 pub mod quick_note;
+pub mod scheduled_export;
+pub mod snippet;
+pub mod space_archive;
 pub mod subscription_plan_limits;
+pub mod view_metadata;
+pub mod view_slug;
+pub mod webdav;
 
 pub mod collab_member;
\ No newline at end of file
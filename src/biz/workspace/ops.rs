@@ -3,7 +3,7 @@ use database_entity::dto::{
 };
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Context};
+use anyhow::Context;
 use redis::AsyncCommands;
 use serde_json::json;
 use sqlx::{types::uuid, PgPool};
@@ -22,28 +22,40 @@ use database::file::s3_client_impl::S3BucketStorage;
 use database::pg_row::AFWorkspaceMemberRow;
 use database::pg_row::AFExplicitCollabMemberRow;
 use database::user::{select_uid_from_email, select_uid_from_email_or_phone};
+use database::member_field::upsert_workspace_member_custom_fields;
 use database::workspace::*;
 use database::subscription::{
   aggregate_user_usage, get_user_active_subscription, get_user_owned_workspace_count,
 };
 use database_entity::dto::{
   AFRole, AFWorkspace, AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceSettings,
-  GlobalComment, Reaction, WorkspaceMemberProfile, WorkspaceUsage,
+  GlobalComment, QueryWorkspaceDirectory, Reaction, WorkspaceDirectory, WorkspaceMemberProfile,
+  WorkspaceUsage,
 };
 
-use crate::biz::notification::ops::create_workspace_notification;
 use shared_entity::dto::billing_dto::{SubscriptionPlan, WorkspaceUsageAndLimit};
+use crate::biz::workspace::egress::get_workspace_monthly_egress;
+use crate::biz::workspace::events::{InvitationMail, WorkspaceEvent, WorkspaceEvents};
+use crate::biz::workspace::member_field::{
+  set_member_custom_fields_by_owner, validate_member_custom_fields,
+};
+use crate::biz::workspace::member_status::{
+  attach_statuses_to_mentionable_persons, get_member_status_by_uuid,
+};
+use crate::biz::workspace::page_watch::notify_page_watchers_of_comment;
 use crate::biz::workspace::subscription_plan_limits::PlanLimits;
 use crate::biz::subscription::ops::{fetch_current_subscription, get_user_resource_limit_status};
 use chrono::{Datelike, Utc};
 
 use crate::biz::authentication::jwt::OptionalUserUuid;
+use crate::biz::notification::comment_reply::notify_comment_reply_by_email;
+use crate::biz::redis_health::RedisHealth;
 use crate::biz::user::user_init::{
   create_user_awareness, create_workspace_collab, create_workspace_database_collab,
   initialize_workspace_for_user,
 };
-use crate::mailer::{AFCloudMailer, WorkspaceInviteMailerParam};
-use crate::state::RedisConnectionManager;
+use crate::middleware::deadline::apply_statement_timeout;
+use crate::state::{AppState, RedisConnectionManager};
 use shared_entity::dto::workspace_dto::{
   CreateWorkspaceMember, WorkspaceMemberChangeset, WorkspaceMemberInvitation,
 };
@@ -65,10 +77,17 @@ pub async fn delete_workspace_for_user(
 
   // remove from postgres
   delete_from_workspace(&pg_pool, &workspace_id).await?;
-  let _: redis::Value = connection_manager
-    .del(UpdateStreamMessage::stream_key(&workspace_id))
+  // The update stream only matters to connected clients, so the workspace stays deleted when
+  // redis is unavailable.
+  if let Err(err) = connection_manager
+    .del::<_, redis::Value>(UpdateStreamMessage::stream_key(&workspace_id))
     .await
-    .map_err(|err| AppResponseError::new(ErrorCode::Internal, err.to_string()))?;
+  {
+    warn!(
+      "failed to remove update stream of deleted workspace {}: {}",
+      workspace_id, err
+    );
+  }
 
   Ok(())
 }
@@ -100,6 +119,7 @@ pub async fn create_empty_workspace(
 
   // create CollabType::Folder
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let start = Instant::now();
   create_workspace_collab(
     user_uid,
@@ -158,6 +178,7 @@ pub async fn create_workspace_for_user(
 
   // add create initial collab for user
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let start = Instant::now();
   initialize_workspace_for_user(
     user_uid,
@@ -182,6 +203,7 @@ pub async fn patch_workspace(
   workspace_icon: Option<&str>,
 ) -> Result<(), AppResponseError> {
   let mut tx = pg_pool.begin().await?;
+  apply_statement_timeout(tx.deref_mut()).await?;
   if let Some(workspace_name) = workspace_name {
     rename_workspace(&mut tx, workspace_id, workspace_name).await?;
   }
@@ -208,19 +230,57 @@ pub async fn get_comments_on_published_view(
   Ok(comments)
 }
 
+/// Posts a comment, whether from the web or from a reply to a notification email, and notifies
+/// the watchers of the page and the author of the comment replied to.
 pub async fn create_comment_on_published_view(
-  pg_pool: &PgPool,
+  state: &AppState,
   view_id: &Uuid,
   reply_comment_id: &Option<Uuid>,
   content: &str,
   user_uuid: &Uuid,
 ) -> Result<(), AppError> {
+  let pg_pool = &state.pg_pool;
   if content.len() > MAX_COMMENT_LENGTH {
     return Err(AppError::StringLengthLimitReached(
       "comment content exceed limit".to_string(),
     ));
   }
+  check_if_published_view_accepts_comments(pg_pool, view_id).await?;
   insert_comment_to_published_view(pg_pool, view_id, user_uuid, content, reply_comment_id).await?;
+
+  let author_uid = database::user::select_uid_from_uuid(pg_pool, user_uuid)
+    .await
+    .ok();
+  if let Err(err) =
+    notify_page_watchers_of_comment(pg_pool, view_id, author_uid, reply_comment_id).await
+  {
+    warn!("failed to notify watchers of {}: {:?}", view_id, err);
+  }
+  if let Some(reply_comment_id) = *reply_comment_id {
+    if state.runtime_config.load().enable_email_notification {
+      let state = state.clone();
+      let user_uuid = *user_uuid;
+      let content = content.to_string();
+      tokio::spawn(async move {
+        if let Err(err) = notify_comment_reply_by_email(
+          &state.pg_pool,
+          &state.mailer,
+          &state.config.notification,
+          &state.config.appflowy_web_url,
+          &reply_comment_id,
+          &user_uuid,
+          &content,
+        )
+        .await
+        {
+          warn!(
+            "failed to email the reply to comment {}: {:?}",
+            reply_comment_id, err
+          );
+        }
+      });
+    }
+  }
   Ok(())
 }
 
@@ -260,6 +320,7 @@ pub async fn create_reaction_on_comment(
   reaction_type: &str,
   user_uuid: &Uuid,
 ) -> Result<(), AppError> {
+  check_if_published_view_accepts_comments(pg_pool, view_id).await?;
   insert_reaction_on_comment(pg_pool, comment_id, view_id, user_uuid, reaction_type).await?;
   Ok(())
 }
@@ -337,6 +398,7 @@ pub async fn open_workspace(
     .begin()
     .await
     .context("Begin transaction to open workspace")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let row = select_workspace_with_count_and_role(txn.deref_mut(), workspace_id, user_uid).await?;
   update_updated_at_of_workspace(txn.deref_mut(), user_uuid, workspace_id).await?;
   txn
@@ -350,12 +412,13 @@ pub async fn open_workspace(
 
 pub async fn accept_workspace_invite(
   pg_pool: &PgPool,
-  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  events: &WorkspaceEvents,
   user_uid: i64,
   user_uuid: &Uuid,
   invite_id: &Uuid,
 ) -> Result<(), AppError> {
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let inv = get_invitation_by_id(&mut txn, invite_id).await?;
   if let Some(invitee_uid) = inv.invitee_uid {
     if invitee_uid != user_uid {
@@ -369,70 +432,31 @@ pub async fn accept_workspace_invite(
   let invited_uid = inv
     .invitee_uid
     .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Invitee uid is missing for {:?}", inv)))?;
-  // 保存 role 用于后续通知创建（因为 insert_role 会移动 inv.role）
-  let role_str = format!("{:?}", inv.role);
-  workspace_access_control
-    .insert_role(&invited_uid, &inv.workspace_id, inv.role)
-    .await?;
   txn.commit().await?;
 
-  // 获取新成员名称和工作区名称，用于构建更友好的通知消息
-  let invitee_name = database::user::select_name_from_uid(pg_pool, invited_uid)
+  events
+    .publish(WorkspaceEvent::InvitationAccepted {
+      workspace_id: inv.workspace_id,
+      inviter_uid: inv.inviter_uid,
+      invitee_uid: invited_uid,
+      role: inv.role,
+    })
     .await
-    .unwrap_or_else(|_| "新成员".to_string());
-  let workspace_name =
-    database::workspace::select_workspace_name_from_workspace_id(pg_pool, &inv.workspace_id)
-      .await
-      .unwrap_or(None)
-      .unwrap_or_else(|| "工作区".to_string());
-
-  // 通知邀请者（A）：被邀请人已接受邀请
-  let notification_payload = json!({
-    "title": "邀请已被接受",
-    "message": format!(
-      "【{}】已接受你的邀请，以「{}」身份加入了工作区「{}」",
-      invitee_name, role_str, workspace_name
-    ),
-    "invitee_uid": invited_uid,
-    "invitee_name": invitee_name,
-    "inviter_uid": inv.inviter_uid,
-    "role": role_str,
-    "workspace_name": workspace_name,
-    "accepted_at": chrono::Utc::now().timestamp(),
-  });
-  if let Err(err) = create_workspace_notification(
-    pg_pool,
-    &inv.workspace_id,
-    "workspace_invitation_accepted",
-    &notification_payload,
-    Some(inv.inviter_uid), // 只通知邀请者（A）
-  )
-  .await
-  {
-    tracing::warn!(
-      "Failed to create workspace invitation accepted notification: {:?}",
-      err
-    );
-  }
-
-  Ok(())
 }
 
 #[instrument(level = "debug", skip_all, err)]
-#[allow(clippy::too_many_arguments)]
 pub async fn invite_workspace_members(
-  mailer: &AFCloudMailer,
   pg_pool: &PgPool,
-  workspace_access_control: &Arc<dyn WorkspaceAccessControl>,
+  events: &WorkspaceEvents,
   inviter: &Uuid,
   workspace_id: &Uuid,
   invitations: Vec<WorkspaceMemberInvitation>,
-  appflowy_web_url: &str,
 ) -> Result<(), AppError> {
   let mut txn = pg_pool
     .begin()
     .await
     .context("Begin transaction to invite workspace members")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   let inviter_name = database::user::select_name_from_uuid(pg_pool, inviter).await?;
   let workspace_name =
     database::workspace::select_workspace_name_from_workspace_id(pg_pool, workspace_id)
@@ -473,21 +497,11 @@ pub async fn invite_workspace_members(
     }
   }
 
-  // 收集需要事务提交后发送的通知 (invitee_uid, role_name)
-  let mut pending_invite_notifications: Vec<(i64, String)> = Vec::new();
+  // 收集需要事务提交后发送的通知 (invitee_uid, role)
+  let mut invited_members: Vec<(i64, AFRole)> = Vec::new();
+  let mut mails = Vec::new();
 
   for invitation in invitations {
-    let inviter_name = inviter_name.clone();
-    let workspace_name = workspace_name.clone();
-    let workspace_member_count = workspace_member_count.to_string();
-
-    // use default icon until we have workspace icon
-    let workspace_icon_url =
-      "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png".to_string();
-    let user_icon_url =
-      "https://cdn.pixabay.com/photo/2015/10/05/22/37/blank-profile-picture-973460_1280.png"
-        .to_string();
-
     // 检查被邀请的用户是否已注册（支持邮箱或手机号）
     let invitee_uid_result = select_uid_from_email_or_phone(txn.deref_mut(), &invitation.email).await;
     
@@ -512,11 +526,6 @@ pub async fn invite_workspace_members(
           )
           .await?;
 
-          // 同步casbin权限策略
-          workspace_access_control
-            .insert_role(&invitee_uid, workspace_id, invitation.role.clone())
-            .await?;
-          
           // 创建邀请记录（先创建pending状态）
           insert_workspace_invitation(
             &mut txn,
@@ -541,12 +550,7 @@ pub async fn invite_workspace_members(
           .await?;
 
           // 收集通知信息，事务提交后再发送
-          let role_name = match invitation.role {
-            AFRole::Owner => "所有者",
-            AFRole::Member => "成员",
-            AFRole::Guest => "访客",
-          };
-          pending_invite_notifications.push((invitee_uid, role_name.to_string()));
+          invited_members.push((invitee_uid, invitation.role.clone()));
         } else {
           // 用户未注册，创建pending邀请记录
           insert_workspace_invitation(
@@ -594,13 +598,6 @@ pub async fn invite_workspace_members(
             )
             .await?;
 
-            // 同步casbin权限策略
-            if let Ok(invitee_uid) = invitee_uid_result {
-              workspace_access_control
-                .insert_role(&invitee_uid, workspace_id, invitation.role.clone())
-                .await?;
-            }
-            
             // 更新邀请状态为已接受
             sqlx::query(
               r#"
@@ -615,12 +612,7 @@ pub async fn invite_workspace_members(
 
             // 收集通知信息，事务提交后再发送
             if let Ok(invitee_uid) = invitee_uid_result {
-              let role_name = match invitation.role {
-                AFRole::Owner => "所有者",
-                AFRole::Member => "成员",
-                AFRole::Guest => "访客",
-              };
-              pending_invite_notifications.push((invitee_uid, role_name.to_string()));
+              invited_members.push((invitee_uid, invitation.role.clone()));
             }
           }
         }
@@ -629,32 +621,12 @@ pub async fn invite_workspace_members(
       },
     };
 
-    // Generate a link such that when clicked, the user is added to the workspace.
-    let accept_url = format!(
-      "{}/accept-invitation?invited_id={}",
-      appflowy_web_url, invite_id
-    );
-
     if !invitation.skip_email_send {
-      let cloned_mailer = mailer.clone();
-      let email_sending = tokio::spawn(async move {
-        cloned_mailer
-          .send_workspace_invite(
-            &invitation.email,
-            WorkspaceInviteMailerParam {
-              user_icon_url,
-              username: inviter_name,
-              workspace_name,
-              workspace_icon_url,
-              workspace_member_count,
-              accept_url,
-            },
-          )
-          .await
+      mails.push(InvitationMail {
+        email: invitation.email,
+        invite_id,
+        wait: invitation.wait_email_send,
       });
-      if invitation.wait_email_send {
-        email_sending.await??;
-      }
     } else {
       tracing::info!(
         "Skipping email send for workspace invite to {}",
@@ -668,61 +640,18 @@ pub async fn invite_workspace_members(
     .await
     .context("Commit transaction to invite workspace members")?;
 
-  // 事务提交后发送通知，此时成员记录已入库，不会有竞态问题
-  let invited_count = pending_invite_notifications.len();
-  for (invitee_uid, role_name) in &pending_invite_notifications {
-    // 通知被邀请者（B）：收到邀请
-    let payload_b = json!({
-      "workspace_id": workspace_id.to_string(),
-      "inviter_name": inviter_name,
-      "workspace_name": workspace_name,
-      "role": role_name,
-      "title": "你收到了工作区邀请",
-      "message": format!("【{}】邀请你加入工作区「{}」，你的角色是：{}", inviter_name, workspace_name, role_name),
-    });
-    if let Err(err) = create_workspace_notification(
-      pg_pool,
-      workspace_id,
-      "workspace_member_invite",
-      &payload_b,
-      Some(*invitee_uid),
-    ).await {
-      tracing::warn!("Failed to send invite notification to uid={}: {:?}", invitee_uid, err);
-    }
-  }
-
-  // 通知邀请者（A）：邀请发送成功确认
-  if invited_count > 0 {
-    let invited_names: Vec<String> = {
-      let mut names = Vec::new();
-      for (invitee_uid, _) in &pending_invite_notifications {
-        let name = database::user::select_name_from_uid(pg_pool, *invitee_uid)
-          .await
-          .unwrap_or_else(|_| format!("用户{}", invitee_uid));
-        names.push(format!("【{}】", name));
-      }
-      names
-    };
-    let names_str = invited_names.join("、");
-    let payload_a = json!({
-      "workspace_id": workspace_id.to_string(),
-      "workspace_name": workspace_name,
-      "invited_count": invited_count,
-      "title": "工作区邀请已发送",
-      "message": format!("你已成功邀请 {} 加入工作区「{}」", names_str, workspace_name),
-    });
-    if let Err(err) = create_workspace_notification(
-      pg_pool,
-      workspace_id,
-      "workspace_invite_sent",
-      &payload_a,
-      Some(inviter_uid),
-    ).await {
-      tracing::warn!("Failed to send invite sent confirmation to inviter uid={}: {:?}", inviter_uid, err);
-    }
-  }
-
-  Ok(())
+  // 事务提交后再授权、发送邮件和通知，此时成员记录已入库，不会有竞态问题
+  events
+    .publish(WorkspaceEvent::MembersInvited {
+      workspace_id: *workspace_id,
+      workspace_name,
+      workspace_member_count,
+      inviter_uid,
+      inviter_name,
+      invitees: invited_members,
+      mails,
+    })
+    .await
 }
 
 #[instrument(level = "debug", skip_all, err)]
@@ -763,6 +692,7 @@ pub async fn add_workspace_members_db_only(
     .begin()
     .await
     .context("Begin transaction to insert workspace members")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
 
   for member in members.into_iter() {
     upsert_workspace_member_with_txn(&mut txn, workspace_id, &member.email, member.role.clone())
@@ -781,11 +711,11 @@ pub async fn leave_workspace(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   user_uuid: &Uuid,
-  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  events: &WorkspaceEvents,
 ) -> Result<(), AppResponseError> {
   let email = database::user::select_email_from_user_uuid(pg_pool, user_uuid).await?;
   if let Some(email) = email {
-    remove_workspace_members(pg_pool, workspace_id, &[email], workspace_access_control, None).await
+    remove_workspace_members(pg_pool, workspace_id, &[email], events, None).await
   } else {
     // User has no email, cannot remove by email
     Ok(())
@@ -796,19 +726,20 @@ pub async fn remove_workspace_members(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   member_identifiers: &[String],
-  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  events: &WorkspaceEvents,
   operator_uid: Option<i64>,
 ) -> Result<(), AppResponseError> {
   let mut txn = pg_pool
     .begin()
     .await
     .context("Begin transaction to delete workspace members")?;
+  apply_statement_timeout(txn.deref_mut()).await?;
 
   // 收集需要通知的成员信息，在事务提交后再发送通知。
   // 必须先提交事务再发通知：PostgreSQL 的 pg_notify 在事务提交后才真正投递，
   // 若通知在事务提交前发出，被踢出的用户客户端收到通知后立即查询服务端时，
   // 事务尚未提交，服务端仍会返回该工作区，导致客户端检测不到变化，无法自动切换工作区。
-  let mut to_notify: Vec<(i64, String)> = Vec::new();
+  let mut removed_uids: Vec<i64> = Vec::new();
 
   for identifier in member_identifiers {
     // Skip empty identifiers
@@ -828,17 +759,7 @@ pub async fn remove_workspace_members(
         // 删除工作区内所有文档的成员邀请记录
         database::workspace::delete_collab_member_invites_by_workspace(&mut txn, workspace_id, uid).await?;
 
-        workspace_access_control
-          .remove_user_from_workspace(&uid, workspace_id)
-          .await?;
-
-        // 获取工作区名称（使用 pg_pool 查询）
-        let workspace_name = match database::workspace::select_workspace_name_from_workspace_id(pg_pool, workspace_id).await {
-            Ok(Some(name)) => name,
-            _ => "未知工作区".to_string(),
-        };
-
-        to_notify.push((uid, workspace_name));
+        removed_uids.push(uid);
       },
       Err(e) => {
         tracing::warn!(
@@ -857,45 +778,28 @@ pub async fn remove_workspace_members(
     .await
     .context("Commit transaction to delete workspace members")?;
 
-  // 事务提交后再发送 WebSocket 通知。
-  // 此时被踢出的用户客户端收到通知并立即查询服务端，能够得到不含该工作区的最新列表，
-  // 从而触发本地 DidUpdateUserWorkspaces 通知，Flutter 层会立即切换到自己的工作区。
-  let operator_name = if let Some(op_uid) = operator_uid {
-    database::user::select_name_from_uid(pg_pool, op_uid)
+  if removed_uids.is_empty() {
+    return Ok(());
+  }
+  let workspace_name =
+    match database::workspace::select_workspace_name_from_workspace_id(pg_pool, workspace_id)
       .await
-      .unwrap_or_else(|_| "管理员".to_string())
-  } else {
-    "管理员".to_string()
-  };
-  for (uid, workspace_name) in to_notify {
-    let notification_payload = json!({
-      "workspace_id": workspace_id.to_string(),
-      "removed_member_uid": uid,
-      "title": "工作区成员已移除",
-      "message": format!("{}用户已经移除了您所在工作区：{}", operator_name, workspace_name),
-    });
-    if let Err(err) = create_workspace_notification(
-      pg_pool,
-      workspace_id,
-      "workspace_member_removed",
-      &notification_payload,
-      Some(uid),
-    )
-    .await
     {
-      tracing::warn!(
-        "Failed to send workspace_member_removed notification to uid={}: {:?}",
-        uid,
-        err
-      );
-    } else {
-      tracing::info!(
-        "Sent workspace_member_removed notification to uid={} for workspace={}",
-        uid,
-        workspace_id
-      );
-    }
-  }
+      Ok(Some(name)) => name,
+      _ => "未知工作区".to_string(),
+    };
+
+  // 事务提交后再移除权限并发送 WebSocket 通知。
+  // 此时被踢出的用户客户端收到通知并立即查询服务端，能够得到不含该工作区的最新列表，
+  // 从而触发本地 DidUpdateUserWorkspaces 通知，Flutter 层会立即切换到自己的工作区。
+  events
+    .publish(WorkspaceEvent::MembersRemoved {
+      workspace_id: *workspace_id,
+      workspace_name,
+      uids: removed_uids,
+      operator_uid,
+    })
+    .await?;
 
   Ok(())
 }
@@ -976,9 +880,13 @@ pub async fn update_workspace_member(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   changeset: &WorkspaceMemberChangeset,
-  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  events: &WorkspaceEvents,
   operator_uid: i64,
 ) -> Result<(), AppError> {
+  if let Some(custom_fields) = &changeset.custom_fields {
+    set_member_custom_fields_by_owner(pg_pool, workspace_id, *uid, custom_fields).await?;
+  }
+
   if let Some(role) = &changeset.role {
     // 使用已解析的 uid 直接更新成员角色，不再依赖 email
     let role_id: i32 = role.clone().into();
@@ -992,32 +900,14 @@ pub async fn update_workspace_member(
     .await
     .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update workspace member role: {}", e)))?;
 
-    workspace_access_control
-      .insert_role(uid, workspace_id, role.clone())
+    events
+      .publish(WorkspaceEvent::MemberRoleChanged {
+        workspace_id: *workspace_id,
+        uid: *uid,
+        role: role.clone(),
+        operator_uid,
+      })
       .await?;
-
-    // 发通知给被修改角色的成员
-    let role_name = match role {
-      AFRole::Owner => "所有者",
-      AFRole::Member => "成员",
-      AFRole::Guest => "访客",
-    };
-    let workspace_name = database::workspace::select_workspace_name_from_workspace_id(pg_pool, workspace_id)
-      .await
-      .ok()
-      .flatten()
-      .unwrap_or_default();
-    let operator_name = database::user::select_name_from_uid(pg_pool, operator_uid)
-      .await
-      .unwrap_or_else(|_| "管理员".to_string());
-    let payload = json!({
-      "workspace_id": workspace_id.to_string(),
-      "title": "工作区角色已变更",
-      "message": format!("{}用户已经修改了您在工作区：{}的角色，新角色为：{}", operator_name, workspace_name, role_name),
-    });
-    if let Err(err) = create_workspace_notification(pg_pool, workspace_id, "workspace_member_role_changed", &payload, Some(*uid)).await {
-      tracing::warn!("Failed to send role change notification to uid={}: {:?}", uid, err);
-    }
   }
 
   Ok(())
@@ -1126,6 +1016,8 @@ pub async fn get_workspace_usage_and_limit(
       }
     };
   
+  let (egress_bytes, daily_egress) = get_workspace_monthly_egress(pg_pool, workspace_id).await?;
+
   // 使用用户真实订阅的存储限制（MB → bytes）
   let storage_limit_mb = resource_status.storage_limit_mb;
   let actual_storage_bytes_limit = (storage_limit_mb * 1024.0 * 1024.0) as i64;
@@ -1145,6 +1037,9 @@ pub async fn get_workspace_usage_and_limit(
     ai_image_responses_count_limit,
     local_ai: matches!(workspace_plan, SubscriptionPlan::AiLocal),
     ai_responses_unlimited,
+    egress_bytes,
+    egress_bytes_limit: limits.egress_bytes_limit,
+    daily_egress,
   })
 }
 
@@ -1162,6 +1057,7 @@ pub async fn update_workspace_settings(
   change: AFWorkspaceSettingsChange,
 ) -> Result<AFWorkspaceSettings, AppResponseError> {
   let mut tx = pg_pool.begin().await?;
+  apply_statement_timeout(tx.deref_mut()).await?;
   let mut setting = select_workspace_settings(tx.deref_mut(), workspace_id)
     .await?
     .unwrap_or_default();
@@ -1173,12 +1069,43 @@ pub async fn update_workspace_settings(
     setting.ai_model = ai_model;
   }
 
+  if let Some(auto_annotate_dead_references) = change.auto_annotate_dead_references {
+    setting.auto_annotate_dead_references = auto_annotate_dead_references;
+  }
+
+  if let Some(publish_permission) = change.publish_permission {
+    setting.publish_permission = publish_permission;
+  }
+
+  if let Some(mut publish_allowlist) = change.publish_allowlist {
+    publish_allowlist.sort_unstable();
+    publish_allowlist.dedup();
+    setting.publish_allowlist = publish_allowlist;
+  }
+
   // Update the workspace settings in the database
   upsert_workspace_settings(&mut tx, workspace_id, &setting).await?;
   tx.commit().await?;
   Ok(setting)
 }
 
+/// Comments and reactions can only be added while the view is published with comments enabled.
+pub(crate) async fn check_if_published_view_accepts_comments(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  match select_published_view_comments_enabled(pg_pool, view_id).await? {
+    Some(true) => Ok(()),
+    Some(false) => Err(AppError::InvalidRequest(
+      "comments are disabled on this page".to_string(),
+    )),
+    None => Err(AppError::RecordNotFound(format!(
+      "view {} is not published",
+      view_id
+    ))),
+  }
+}
+
 async fn check_if_user_is_allowed_to_delete_comment(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
@@ -1205,6 +1132,7 @@ pub async fn create_upload_task(
   file_size: usize,
   presigned_url: Option<String>,
   redis_client: &RedisConnectionManager,
+  redis_health: &RedisHealth,
   pg_pool: &PgPool,
 ) -> Result<(), AppError> {
   // Insert the task into the database
@@ -1220,12 +1148,9 @@ pub async fn create_upload_task(
   )
   .await?;
 
-  let _: () = redis_client
-    .clone()
-    .xadd("import_task_stream", "*", &[("task", task.to_string())])
-    .await
-    .map_err(|err| AppError::Internal(anyhow!("Failed to push task to Redis stream: {}", err)))?;
-
+  redis_health
+    .push_import_task(redis_client, task.to_string())
+    .await;
   Ok(())
 }
 
@@ -1285,6 +1210,7 @@ pub async fn list_workspace_mentionable_persons_with_last_mentioned_time(
       .into_iter()
       .map(|row| row.into()),
   );
+  attach_statuses_to_mentionable_persons(pg_pool, workspace_id, &mut persons).await?;
   Ok(persons)
 }
 
@@ -1296,7 +1222,9 @@ pub async fn get_workspace_mentionable_person(
   let mentionable_workspace_members_or_guests =
     select_workspace_mentionable_member_or_guest_by_uuid(pg_pool, workspace_id, person_id).await?;
   if let Some(mentionable) = mentionable_workspace_members_or_guests {
-    Ok(mentionable.into())
+    let mut person: MentionablePerson = mentionable.into();
+    person.status = get_member_status_by_uuid(pg_pool, workspace_id, person_id).await?;
+    Ok(person)
   } else {
     Err(AppError::RecordNotFound(format!(
       "person with ID {} is neither a workspace member nor contact",
@@ -1305,12 +1233,78 @@ pub async fn get_workspace_mentionable_person(
   }
 }
 
+const MAX_PROFILE_LINKS: usize = 10;
+const MAX_PROFILE_FIELD_LENGTH: usize = 256;
+const DEFAULT_DIRECTORY_PAGE_SIZE: i64 = 50;
+const MAX_DIRECTORY_PAGE_SIZE: i64 = 200;
+
 pub async fn update_workspace_member_profile(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   uid: i64,
   updated_profile: &WorkspaceMemberProfile,
 ) -> Result<(), AppError> {
-  upsert_workspace_member_profile(pg_pool, workspace_id, uid, updated_profile).await?;
+  validate_workspace_member_profile(updated_profile)?;
+  let custom_fields = match &updated_profile.custom_fields {
+    Some(values) => validate_member_custom_fields(pg_pool, workspace_id, values, false).await?,
+    None => HashMap::new(),
+  };
+  let mut tx = pg_pool.begin().await?;
+  apply_statement_timeout(tx.deref_mut()).await?;
+  upsert_workspace_member_profile(tx.deref_mut(), workspace_id, uid, updated_profile).await?;
+  if !custom_fields.is_empty() {
+    upsert_workspace_member_custom_fields(tx.deref_mut(), workspace_id, uid, &custom_fields)
+      .await?;
+  }
+  tx.commit().await?;
+  Ok(())
+}
+
+fn validate_workspace_member_profile(profile: &WorkspaceMemberProfile) -> Result<(), AppError> {
+  let fields = [&profile.title, &profile.department, &profile.pronouns];
+  if fields
+    .iter()
+    .any(|field| field.as_ref().map(|v| v.chars().count()).unwrap_or(0) > MAX_PROFILE_FIELD_LENGTH)
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "Profile fields can have at most {} characters",
+      MAX_PROFILE_FIELD_LENGTH
+    )));
+  }
+  if profile.links.len() > MAX_PROFILE_LINKS {
+    return Err(AppError::InvalidRequest(format!(
+      "A profile can have at most {} links",
+      MAX_PROFILE_LINKS
+    )));
+  }
+  for link in &profile.links {
+    let is_web_url = reqwest::Url::parse(&link.url)
+      .map(|url| matches!(url.scheme(), "http" | "https"))
+      .unwrap_or(false);
+    if !is_web_url {
+      return Err(AppError::InvalidRequest(format!(
+        "Profile link {} is not a http(s) url",
+        link.url
+      )));
+    }
+  }
   Ok(())
 }
+
+pub async fn get_workspace_directory(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  query: QueryWorkspaceDirectory,
+) -> Result<WorkspaceDirectory, AppError> {
+  let offset = query.offset.unwrap_or(0).max(0);
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_DIRECTORY_PAGE_SIZE)
+    .clamp(1, MAX_DIRECTORY_PAGE_SIZE);
+  let (rows, total) =
+    select_workspace_directory(pg_pool, workspace_id, query.q.as_deref(), offset, limit).await?;
+  Ok(WorkspaceDirectory {
+    people: rows.into_iter().map(Into::into).collect(),
+    total,
+  })
+}
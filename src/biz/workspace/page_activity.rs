@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+
+use app_error::AppError;
+use database::page_activity::{
+  insert_page_activity, select_page_activities, select_page_comment_activities,
+};
+use database::user::select_web_users_from_uids;
+use database_entity::dto::PageActivityKind;
+use shared_entity::dto::workspace_dto::{PageActivity, PageActivityFeed, QueryPageActivity};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+const DEFAULT_PAGE_ACTIVITY_LIMIT: u32 = 50;
+const MAX_PAGE_ACTIVITY_LIMIT: u32 = 200;
+
+/// Records an event of the page for its activity feed. The activity feed is informational, so
+/// a failure is logged without failing the operation that caused the event.
+pub async fn record_page_activity(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  actor_uid: i64,
+  kind: PageActivityKind,
+  details: serde_json::Value,
+) {
+  if let Err(err) =
+    insert_page_activity(pg_pool, workspace_id, view_id, actor_uid, kind, details).await
+  {
+    warn!(
+      "failed to record {} activity of view {}: {}",
+      kind.as_str(),
+      view_id,
+      err
+    );
+  }
+}
+
+/// Merges the recorded events of the page, its edits included, and the comments left on it into
+/// one feed, most recent first.
+pub async fn get_page_activity_feed(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  query: QueryPageActivity,
+) -> Result<PageActivityFeed, AppError> {
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_PAGE_ACTIVITY_LIMIT)
+    .clamp(1, MAX_PAGE_ACTIVITY_LIMIT) as usize;
+  // One more than the limit is read from each source to know whether there are more
+  let fetch_limit = limit as i64 + 1;
+  let mut records =
+    select_page_activities(pg_pool, workspace_id, view_id, query.before, fetch_limit).await?;
+  records
+    .extend(select_page_comment_activities(pg_pool, view_id, query.before, fetch_limit).await?);
+  records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+  let has_more = records.len() > limit;
+  records.truncate(limit);
+
+  let actor_uids: Vec<i64> = records
+    .iter()
+    .filter_map(|record| record.actor_uid)
+    .collect::<HashSet<_>>()
+    .into_iter()
+    .collect();
+  let actors = select_web_users_from_uids(pg_pool, &actor_uids).await?;
+  let activities = records
+    .into_iter()
+    .filter_map(|record| {
+      let kind = record
+        .kind
+        .parse::<PageActivityKind>()
+        .map_err(|err| warn!("skipping activity of view {}: {}", view_id, err))
+        .ok()?;
+      let actor = record.actor_uid.and_then(|uid| actors.get(&uid).cloned());
+      Some(PageActivity {
+        kind,
+        actor,
+        occurred_at: record.created_at,
+        details: record.details,
+      })
+    })
+    .collect();
+  Ok(PageActivityFeed {
+    activities,
+    has_more,
+  })
+}
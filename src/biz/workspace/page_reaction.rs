@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use database::page_reaction::{
+  delete_page_reaction, insert_page_reaction, select_page_reaction_counts,
+};
+use shared_entity::dto::workspace_dto::{FolderView, PageReactionCount};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_REACTION_TYPE_LENGTH: usize = 32;
+
+pub async fn add_page_reaction(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+  reaction_type: &str,
+) -> Result<(), AppError> {
+  let reaction_type = reaction_type.trim();
+  if reaction_type.is_empty() || reaction_type.chars().count() > MAX_REACTION_TYPE_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "reaction type must be between 1 and {} characters",
+      MAX_REACTION_TYPE_LENGTH
+    )));
+  }
+  insert_page_reaction(pg_pool, workspace_id, view_id, uid, reaction_type).await
+}
+
+pub async fn remove_page_reaction(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+  reaction_type: &str,
+) -> Result<(), AppError> {
+  let reaction_type = reaction_type.trim();
+  if !delete_page_reaction(pg_pool, workspace_id, view_id, uid, reaction_type).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "no {} reaction on view {}",
+      reaction_type, view_id
+    )));
+  }
+  Ok(())
+}
+
+pub async fn get_page_reactions(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<PageReactionCount>, AppError> {
+  let mut reactions = get_reactions_by_view(pg_pool, workspace_id, &[*view_id], uid).await?;
+  Ok(reactions.remove(view_id).unwrap_or_default())
+}
+
+/// Fills the reaction counts of every view of the trees. Only the reactions on the views of the
+/// trees are loaded.
+pub async fn attach_page_reactions(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uid: i64,
+  views: &mut [FolderView],
+) -> Result<(), AppError> {
+  let mut view_ids = vec![];
+  let mut stack: Vec<&FolderView> = views.iter().collect();
+  while let Some(view) = stack.pop() {
+    view_ids.push(view.view_id);
+    stack.extend(view.children.iter());
+  }
+  if view_ids.is_empty() {
+    return Ok(());
+  }
+  let mut reactions = get_reactions_by_view(pg_pool, workspace_id, &view_ids, uid).await?;
+  if reactions.is_empty() {
+    return Ok(());
+  }
+  let mut stack: Vec<&mut FolderView> = views.iter_mut().collect();
+  while let Some(view) = stack.pop() {
+    if let Some(view_reactions) = reactions.remove(&view.view_id) {
+      view.reactions = view_reactions;
+    }
+    stack.extend(view.children.iter_mut());
+  }
+  Ok(())
+}
+
+async fn get_reactions_by_view(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  uid: i64,
+) -> Result<HashMap<Uuid, Vec<PageReactionCount>>, AppError> {
+  let rows = select_page_reaction_counts(pg_pool, workspace_id, view_ids, uid).await?;
+  let mut reactions: HashMap<Uuid, Vec<PageReactionCount>> = HashMap::new();
+  for row in rows {
+    reactions
+      .entry(row.view_id)
+      .or_default()
+      .push(PageReactionCount {
+        reaction_type: row.reaction_type,
+        count: row.count,
+        reacted_by_me: row.reacted_by_me,
+      });
+  }
+  Ok(reactions)
+}
@@ -1,19 +1,28 @@
+use super::page_activity::record_page_activity;
 use super::publish::PublishedCollabStore;
+use super::publish_preview::{describe_published_documents, published_documents};
+use super::publish_row_filter::{filter_published_rows, validate_row_filter};
+use super::view_metadata::refresh_workspace_view_metadata;
 use crate::api::metrics::AppFlowyWebMetrics;
 use crate::biz::chat::ops::create_chat;
 use crate::biz::collab::database::{
   resolve_dependencies_when_create_database_linked_view, LinkedViewDependencies,
 };
+use crate::biz::collab::document::{
+  document_headings, document_outline, flatten_document_blocks, to_document_chunk_block,
+};
 use crate::biz::collab::folder_view::{
   check_if_space_is_private, check_if_view_is_space, get_prev_view_id,
   get_space_view_for_current_view, parse_extra_field_as_json, to_dto_view_icon, to_dto_view_layout,
   to_folder_view_icon, to_folder_view_layout, to_space_permission,
 };
 use crate::biz::collab::ops::get_latest_workspace_database;
+use crate::biz::notification::ops::create_workspace_notification;
 use crate::biz::collab::utils::{
-  batch_get_latest_collab_encoded, collab_to_doc_state, get_latest_collab,
-  get_latest_collab_database_body, DUMMY_UID,
+  batch_get_latest_collab_encoded, collab_to_doc_state, document_data_from_doc_state,
+  get_latest_collab, get_latest_collab_database_body, DUMMY_UID,
 };
+use crate::middleware::deadline::apply_statement_timeout;
 use crate::state::AppState;
 use anyhow::anyhow;
 use app_error::AppError;
@@ -49,27 +58,37 @@ use collab_rt_entity::user::RealtimeUser;
 use database::collab::{
   select_collab_meta_from_af_collab, select_workspace_database_oid, CollabStore, GetCollabOrigin,
 };
-use database::publish::select_published_view_ids_for_workspace;
+use database::publish::{
+  select_published_row_filter, select_published_view_ids_for_workspace,
+  select_published_views_with_publisher, upsert_published_row_filter,
+};
 use database::user::{select_uuid_from_uid, select_web_user_from_uid};
 use database::workspace::{
   select_workspace_member_uuid_exclude_guest, select_workspace_mentionable_members_or_guests,
   upsert_page_mention,
 };
 use database_entity::dto::{
-  CollabParams, MentionablePerson, MentionablePersonWithAccess, PageMentionUpdate,
-  PublishCollabItem, PublishCollabMetadata, QueryCollab, QueryCollabResult,
+  CollabParams, MentionablePerson, MentionablePersonWithAccess, PageActivityKind,
+  PageMentionUpdate, PublishCollabItem, PublishCollabMetadata, QueryCollab, QueryCollabResult,
 };
 use fancy_regex::Regex;
+use indexer::collab_indexer::code_block_languages;
 use itertools::Itertools;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde_json::json;
 use shared_entity::dto::chat_dto::CreateChatParams;
-use shared_entity::dto::publish_dto::{PublishDatabaseData, PublishViewInfo, PublishViewMetaData};
+use shared_entity::dto::publish_dto::{
+  PublishCodeBlockMeta, PublishDatabaseData, PublishRowFilter, PublishViewInfo,
+  PublishViewMetaData,
+};
 use shared_entity::dto::workspace_dto::{
-  FolderView, Page, PageCollab, PageCollabData, Space, SpacePermission, ViewIcon, ViewLayout,
+  DocumentChunk, DocumentOutline, FolderView, Page, PageCollab, PageCollabData, PageViewBatchItem,
+  Space, SpacePermission, ViewIcon, ViewLayout,
 };
+use shared_entity::response::AppResponseError;
 use sqlx::PgPool;
 use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
 use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 use tokio::time::timeout_at;
@@ -100,14 +119,7 @@ pub async fn update_space(
     user.uid,
   )
   .await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   Ok(())
 }
 
@@ -139,6 +151,7 @@ pub async fn create_space(
   )
   .await?;
   let mut transaction = state.pg_pool.begin().await?;
+  apply_statement_timeout(transaction.deref_mut()).await?;
   let start = Instant::now();
   let action = format!("Create new space: {}", view_id);
   state
@@ -151,14 +164,7 @@ pub async fn create_space(
       &action,
     )
     .await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   transaction.commit().await?;
   state.metrics.collab_metrics.observe_pg_tx(start.elapsed());
   Ok(Space { view_id })
@@ -207,14 +213,7 @@ pub async fn create_folder_view(
     (None, None)
   };
 
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user.clone(),
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user.clone(), workspace_id, folder_update).await?;
 
   if let (Some(workspace_database_id), Some(workspace_database_update)) =
     (workspace_database_id, workspace_database_update)
@@ -334,6 +333,7 @@ pub async fn create_orphaned_view(
   let default_document_collab_params =
     prepare_default_document_collab_param(default_client_id(), document_id).await?;
   let mut transaction = pg_pool.begin().await?;
+  apply_statement_timeout(transaction.deref_mut()).await?;
   let action = format!("Create new orphaned view: {}", document_id);
   collab_storage
     .upsert_new_collab_with_transaction(
@@ -943,6 +943,45 @@ async fn move_view(
   Ok(encoded_update)
 }
 
+fn reorder_views_in_parent(
+  parent_view_id: &str,
+  view_ids: &[String],
+  folder: &mut Folder,
+  uid: i64,
+) -> Result<Vec<u8>, AppError> {
+  let parent = folder
+    .get_view(parent_view_id, uid)
+    .ok_or_else(|| AppError::InvalidFolderView(format!("parent view {} not found", parent_view_id)))?;
+  let current_ids: Vec<String> = parent.children.iter().map(|child| child.id.clone()).collect();
+  let requested_ids: HashSet<&String> = view_ids.iter().collect();
+  if requested_ids.len() != view_ids.len()
+    || current_ids.len() != view_ids.len()
+    || current_ids.iter().any(|id| !requested_ids.contains(id))
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "view ids must list each child of {} exactly once",
+      parent_view_id
+    )));
+  }
+
+  let encoded_update = {
+    let mut txn = folder.collab.transact_mut();
+    let mut prev_view_id: Option<String> = None;
+    for view_id in view_ids {
+      folder.body.move_nested_view(
+        &mut txn,
+        view_id,
+        parent_view_id,
+        prev_view_id.clone(),
+        uid,
+      );
+      prev_view_id = Some(view_id.clone());
+    }
+    txn.encode_update_v1()
+  };
+  Ok(encoded_update)
+}
+
 async fn move_view_to_trash(
   view_id: &str,
   folder: &mut Folder,
@@ -1263,6 +1302,7 @@ async fn create_document_page(
   )
   .await?;
   let mut transaction = state.pg_pool.begin().await?;
+  apply_statement_timeout(transaction.deref_mut()).await?;
   let start = Instant::now();
   let action = format!("Create new collab: {}", view_id);
   state
@@ -1277,14 +1317,7 @@ async fn create_document_page(
     .await?;
   transaction.commit().await?;
 
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   state.metrics.collab_metrics.observe_pg_tx(start.elapsed());
   Ok(Page { view_id })
 }
@@ -1422,6 +1455,7 @@ async fn create_database_page(
     .collect();
 
   let mut transaction = state.pg_pool.begin().await?;
+  apply_statement_timeout(transaction.deref_mut()).await?;
   let start = Instant::now();
   let action = format!("Create new database collab: {}", database_id);
   state
@@ -1442,14 +1476,7 @@ async fn create_database_page(
   // the collab object is persisted even if the subsequent Redis stream updates fail.
   transaction.commit().await?;
 
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user.clone(),
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user.clone(), workspace_id, folder_update).await?;
   update_workspace_database_data(
     &state.metrics.appflowy_web_metrics,
     &state.ws_server,
@@ -1515,14 +1542,7 @@ async fn create_chat_page(
     collab_folder::ViewLayout::Chat,
   )
   .await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user.clone(),
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user.clone(), workspace_id, folder_update).await?;
   Ok(Page { view_id })
 }
 
@@ -1536,6 +1556,9 @@ pub async fn move_page(
   prev_view_id: Option<String>,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let old_parent_view_id = folder
+    .get_view(view_id, user.uid)
+    .map(|view| view.parent_view_id.clone());
   let folder_update = move_view(
     view_id,
     new_parent_view_id,
@@ -1544,14 +1567,39 @@ pub async fn move_page(
     user.uid,
   )
   .await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  let uid = user.uid;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
+  if old_parent_view_id.as_deref() != Some(new_parent_view_id) {
+    if let Ok(view_uuid) = Uuid::parse_str(view_id) {
+      record_page_activity(
+        &state.pg_pool,
+        &workspace_id,
+        &view_uuid,
+        uid,
+        PageActivityKind::Moved,
+        json!({
+          "old_parent_view_id": old_parent_view_id,
+          "new_parent_view_id": new_parent_view_id,
+        }),
+      )
+      .await;
+    }
+  }
+  Ok(())
+}
+
+/// Applies the whole order in one folder transaction, so that concurrent reorders don't
+/// interleave the way a sequence of [move_page] calls does.
+pub async fn reorder_page_views(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+  parent_view_id: &str,
+  view_ids: &[String],
+) -> Result<(), AppError> {
+  let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let folder_update = reorder_views_in_parent(parent_view_id, view_ids, &mut folder, user.uid)?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   Ok(())
 }
 
@@ -1566,14 +1614,7 @@ pub async fn reorder_favorite_page(
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update =
     reorder_favorite_section(view_id, prev_view_id, &mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   Ok(())
 }
 
@@ -1588,15 +1629,11 @@ pub async fn move_page_to_trash(
   if trash_info.into_iter().any(|info| info.id == view_id) {
     return Ok(());
   }
+  let removed_view_ids = view_and_descendant_ids(&folder, view_id, user.uid);
   let folder_update = move_view_to_trash(view_id, &mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  let uid = user.uid;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
+  unpublish_removed_views(state, workspace_id, &removed_view_ids, uid, "trashed").await;
   Ok(())
 }
 
@@ -1608,14 +1645,7 @@ pub async fn restore_page_from_trash(
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = move_view_out_from_trash(view_id, &mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   Ok(())
 }
 
@@ -1627,14 +1657,7 @@ pub async fn add_recent_pages(
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = extend_recent_views(&recent_view_ids, &mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   Ok(())
 }
 
@@ -1645,14 +1668,7 @@ pub async fn restore_all_pages_from_trash(
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = move_all_views_out_from_trash(&mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
   Ok(())
 }
 
@@ -1663,15 +1679,11 @@ pub async fn delete_trash(
   view_id: &str,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let removed_view_ids = view_and_descendant_ids(&folder, view_id, user.uid);
   let update = delete_view_from_trash(view_id, &mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    update,
-  )
-  .await?;
+  let uid = user.uid;
+  update_workspace_folder(state, user, workspace_id, update).await?;
+  unpublish_removed_views(state, workspace_id, &removed_view_ids, uid, "deleted").await;
   Ok(())
 }
 
@@ -1681,18 +1693,120 @@ pub async fn delete_all_pages_from_trash(
   workspace_id: Uuid,
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let removed_view_ids = folder
+    .get_all_trash_sections(user.uid)
+    .iter()
+    .flat_map(|section| view_and_descendant_ids(&folder, &section.id, user.uid))
+    .collect_vec();
   let update = delete_all_views_from_trash(&mut folder, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    update,
-  )
-  .await?;
+  let uid = user.uid;
+  update_workspace_folder(state, user, workspace_id, update).await?;
+  unpublish_removed_views(state, workspace_id, &removed_view_ids, uid, "deleted").await;
   Ok(())
 }
 
+const PUBLISHED_PAGE_REMOVED_NOTIFICATION: &str = "published_page_removed";
+
+/// The view and every view nested in it.
+pub(crate) fn view_and_descendant_ids(folder: &Folder, view_id: &str, uid: i64) -> Vec<Uuid> {
+  let mut view_ids = vec![];
+  let mut visited = HashSet::new();
+  let mut stack = vec![view_id.to_string()];
+  while let Some(view_id) = stack.pop() {
+    if !visited.insert(view_id.clone()) {
+      continue;
+    }
+    if let Some(view) = folder.get_view(&view_id, uid) {
+      stack.extend(view.children.iter().map(|child| child.id.clone()));
+    }
+    if let Ok(view_id) = Uuid::parse_str(&view_id) {
+      view_ids.push(view_id);
+    }
+  }
+  view_ids
+}
+
+/// Unpublishes the views that were moved to the trash or deleted, so that their public URL stops
+/// serving them, and tells their publishers. Failures are only logged, as the views are already
+/// removed from the folder.
+async fn unpublish_removed_views(
+  state: &AppState,
+  workspace_id: Uuid,
+  view_ids: &[Uuid],
+  operator_uid: i64,
+  reason: &str,
+) {
+  if view_ids.is_empty() {
+    return;
+  }
+  let published_views =
+    match select_published_views_with_publisher(&state.pg_pool, &workspace_id, view_ids).await {
+      Ok(published_views) => published_views,
+      Err(err) => {
+        tracing::warn!(
+          "failed to look up published views removed from workspace {}: {:?}",
+          workspace_id,
+          err
+        );
+        return;
+      },
+    };
+  if published_views.is_empty() {
+    return;
+  }
+  let published_view_ids = published_views
+    .iter()
+    .map(|(view_id, _, _)| *view_id)
+    .collect_vec();
+  if let Err(err) = state
+    .published_collab_store
+    .unpublish_removed_collabs(&workspace_id, &published_view_ids)
+    .await
+  {
+    tracing::error!(
+      "failed to unpublish views {:?} removed from workspace {}: {:?}",
+      published_view_ids,
+      workspace_id,
+      err
+    );
+    return;
+  }
+
+  let removal = if reason == "trashed" {
+    "移到回收站"
+  } else {
+    "删除"
+  };
+  for (view_id, published_by, publish_name) in published_views {
+    if published_by == operator_uid {
+      continue;
+    }
+    let payload = json!({
+      "view_id": view_id.to_string(),
+      "publish_name": publish_name,
+      "reason": reason,
+      "title": "你发布的页面已取消发布",
+      "message": format!("页面「{}」已被{}，因此已自动取消发布", publish_name, removal),
+    });
+    if let Err(err) = create_workspace_notification(
+      &state.pg_pool,
+      &workspace_id,
+      PUBLISHED_PAGE_REMOVED_NOTIFICATION,
+      &payload,
+      Some(published_by),
+    )
+    .await
+    {
+      tracing::warn!(
+        "failed to notify uid={} of the unpublished view {}: {:?}",
+        published_by,
+        view_id,
+        err
+      );
+    }
+  }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn update_page(
   state: &AppState,
@@ -1707,14 +1821,7 @@ pub async fn update_page(
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update =
     update_view_properties(view_id, &mut folder, name, icon, is_locked, extra, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
 
   Ok(())
 }
@@ -1728,14 +1835,7 @@ pub async fn update_page_name(
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = update_view_name(view_id, &mut folder, name, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
 
   Ok(())
 }
@@ -1749,14 +1849,7 @@ pub async fn update_page_icon(
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = update_view_icon(view_id, &mut folder, icon, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
 
   Ok(())
 }
@@ -1770,14 +1863,7 @@ pub async fn update_page_extra(
 ) -> Result<(), AppError> {
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update = update_view_extra(view_id, &mut folder, extra, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
 
   Ok(())
 }
@@ -1794,14 +1880,7 @@ pub async fn favorite_page(
   let mut folder = state.ws_server.get_folder(workspace_id).await?;
   let folder_update =
     update_favorite_view(view_id, &mut folder, is_favorite, is_pinned, user.uid).await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
 
   Ok(())
 }
@@ -1839,7 +1918,11 @@ pub async fn publish_page(
   publish_name: Option<impl ToString>,
   comments_enabled: bool,
   duplicate_enabled: bool,
+  row_filter: Option<PublishRowFilter>,
 ) -> Result<(), AppError> {
+  if let Some(row_filter) = &row_filter {
+    validate_row_filter(row_filter)?;
+  }
   let folder = state.ws_server.get_folder(workspace_id).await?;
   let view = folder
     .get_view(&view_id.to_string(), uid)
@@ -1847,11 +1930,16 @@ pub async fn publish_page(
       "View {} not found",
       view_id
     )))?;
+  if row_filter.is_some() && !view.layout.is_database() {
+    return Err(AppError::InvalidRequest(
+      "row filters only apply to databases".to_string(),
+    ));
+  }
   let icon = view
     .icon
     .as_ref()
     .map(|icon| to_dto_view_icon(icon.clone()));
-  let metadata = PublishViewMetaData {
+  let mut metadata = PublishViewMetaData {
     view: PublishViewInfo {
       view_id: view_id.to_string(),
       name: view.name.clone(),
@@ -1868,6 +1956,7 @@ pub async fn publish_page(
     // appflowy web as there is now endpoint to obtain published outline.
     child_views: vec![],
     ancestor_views: vec![],
+    code_blocks: vec![],
   };
 
   let publish_data = match view.layout {
@@ -1877,6 +1966,10 @@ pub async fn publish_page(
     collab_folder::ViewLayout::Grid
     | collab_folder::ViewLayout::Board
     | collab_folder::ViewLayout::Calendar => {
+      let row_filter = match &row_filter {
+        Some(row_filter) => Some(row_filter.clone()),
+        None => select_published_row_filter(&state.pg_pool, &workspace_id, &view_id).await?,
+      };
       generate_publish_data_for_database(
         &state.pg_pool,
         &state.collab_storage,
@@ -1884,6 +1977,7 @@ pub async fn publish_page(
         workspace_id,
         view_id,
         visible_database_view_ids,
+        row_filter.as_ref(),
       )
       .await
     },
@@ -1891,29 +1985,53 @@ pub async fn publish_page(
       "AI Chat cannot be published".to_string(),
     )),
   }?;
+  if matches!(view.layout, collab_folder::ViewLayout::Document) {
+    metadata.code_blocks = publish_code_block_metadata(view_id, publish_data.clone())
+      .await
+      .unwrap_or_else(|err| {
+        tracing::warn!(
+          "failed to collect code block metadata for published view {}: {}",
+          view_id,
+          err
+        );
+        vec![]
+      });
+  }
+  let publish_name = publish_name
+    .map(|name| name.to_string())
+    .unwrap_or_else(|| generate_publish_name(&view.id, &view.name));
+  let items = vec![PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.clone(),
+      metadata: serde_json::value::to_value(metadata).unwrap(),
+    },
+    data: publish_data,
+    comments_enabled,
+    duplicate_enabled,
+  }];
+  let documents = published_documents(&items);
   state
     .published_collab_store
-    .publish_collabs(
-      vec![PublishCollabItem {
-        meta: PublishCollabMetadata {
-          view_id,
-          publish_name: publish_name
-            .map(|name| name.to_string())
-            .unwrap_or_else(|| generate_publish_name(&view.id, &view.name)),
-          metadata: serde_json::value::to_value(metadata).unwrap(),
-        },
-        data: publish_data,
-        comments_enabled,
-        duplicate_enabled,
-      }],
-      &workspace_id,
-      &user_uuid,
-    )
+    .publish_collabs(items, &workspace_id, &user_uuid)
     .await?;
+  describe_published_documents(state, workspace_id, documents);
+  if let Some(row_filter) = &row_filter {
+    upsert_published_row_filter(&state.pg_pool, &workspace_id, &view_id, uid, row_filter).await?;
+  }
+  record_page_activity(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+    uid,
+    PageActivityKind::Published,
+    json!({ "publish_name": publish_name }),
+  )
+  .await;
   Ok(())
 }
 
-async fn generate_publish_data_for_document(
+pub(crate) async fn generate_publish_data_for_document(
   collab_storage: &Arc<dyn CollabStore>,
   uid: i64,
   workspace_id: Uuid,
@@ -1931,13 +2049,33 @@ async fn generate_publish_data_for_document(
   Ok(collab.doc_state.to_vec())
 }
 
-async fn generate_publish_data_for_database(
+async fn publish_code_block_metadata(
+  view_id: Uuid,
+  doc_state: Vec<u8>,
+) -> Result<Vec<PublishCodeBlockMeta>, AppError> {
+  tokio::task::spawn_blocking(move || {
+    let document_data = document_data_from_doc_state(doc_state, &view_id)?;
+    let code_blocks = code_block_languages(&document_data)
+      .into_iter()
+      .map(|code_block| PublishCodeBlockMeta {
+        block_id: code_block.block_id,
+        language: code_block.language,
+        detected: code_block.detected,
+      })
+      .collect();
+    Ok(code_blocks)
+  })
+  .await?
+}
+
+pub(crate) async fn generate_publish_data_for_database(
   pg_pool: &PgPool,
   collab_storage: &Arc<dyn CollabStore>,
   uid: i64,
   workspace_id: Uuid,
   view_id: Uuid,
   visible_database_view_ids: Option<Vec<Uuid>>,
+  row_filter: Option<&PublishRowFilter>,
 ) -> Result<Vec<u8>, AppError> {
   let (_, ws_db) = get_latest_workspace_database(
     collab_storage,
@@ -1971,6 +2109,13 @@ async fn generate_publish_data_for_database(
       .flat_map(|ro| Uuid::parse_str(&ro.id))
       .collect()
   };
+  let row_ids = match row_filter {
+    Some(row_filter) => {
+      filter_published_rows(collab_storage, uid, workspace_id, db_oid, &row_ids, row_filter)
+        .await?
+    },
+    None => row_ids,
+  };
   let encoded_rows = batch_get_latest_collab_encoded(
     collab_storage,
     GetCollabOrigin::User { uid },
@@ -2054,6 +2199,53 @@ pub async fn get_page_view_collab(
   }
 }
 
+/// Upper bound of views per batch page view request
+pub const MAX_PAGE_VIEW_BATCH_SIZE: usize = 50;
+
+/// Loads several views at once, e.g. all open tabs on session restore. Errors are reported per
+/// view, so a deleted or inaccessible view does not fail the rest of the batch.
+pub async fn get_page_view_collabs(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  uid: i64,
+  workspace_id: Uuid,
+  view_ids: Vec<Uuid>,
+) -> Result<Vec<PageViewBatchItem>, AppError> {
+  if view_ids.len() > MAX_PAGE_VIEW_BATCH_SIZE {
+    return Err(AppError::InvalidRequest(format!(
+      "At most {} views can be requested at once",
+      MAX_PAGE_VIEW_BATCH_SIZE
+    )));
+  }
+  let mut items = Vec::with_capacity(view_ids.len());
+  for view_id in view_ids.into_iter().unique() {
+    let result = get_page_view_collab(
+      pg_pool,
+      collab_storage,
+      collab_instance_cache,
+      uid,
+      workspace_id,
+      view_id,
+    )
+    .await;
+    let item = match result {
+      Ok(page_collab) => PageViewBatchItem {
+        view_id,
+        page_collab: Some(page_collab),
+        error: None,
+      },
+      Err(err) => PageViewBatchItem {
+        view_id,
+        page_collab: None,
+        error: Some(AppResponseError::from(err)),
+      },
+    };
+    items.push(item);
+  }
+  Ok(items)
+}
+
 async fn get_page_view_collab_for_orphaned_view(
   pg_pool: &PgPool,
   collab_storage: &Arc<dyn CollabStore>,
@@ -2121,6 +2313,7 @@ async fn get_page_view_collab_for_orphaned_view(
         is_locked: Some(false),
         extra: None,
         children: vec![],
+        reactions: vec![],
       },
       data,
       owner,
@@ -2162,6 +2355,7 @@ async fn get_page_view_collab_for_orphaned_view(
       is_locked: Some(false),
       extra: None,
       children: vec![],
+      reactions: vec![],
     },
     data,
     owner: owner.clone(),
@@ -2218,6 +2412,7 @@ async fn get_page_view_collab_for_view_with_parent(
     is_locked: view.is_locked,
     extra: view.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
     children: vec![],
+    reactions: vec![],
   };
   let page_collab_data = match view.layout {
     collab_folder::ViewLayout::Document => {
@@ -2387,6 +2582,103 @@ async fn get_page_collab_data_for_document(
   })
 }
 
+/// Documents whose encoded state is larger than this are served in block-range chunks.
+pub const LARGE_DOCUMENT_SIZE_THRESHOLD: usize = 1024 * 1024;
+const DEFAULT_DOCUMENT_CHUNK_BLOCKS: u32 = 200;
+const MAX_DOCUMENT_CHUNK_BLOCKS: u32 = 2000;
+
+/// Returns a range of blocks of a document view. Documents below [LARGE_DOCUMENT_SIZE_THRESHOLD]
+/// are always returned as a single chunk, regardless of the requested range. The first chunk
+/// also carries the table of contents, so clients can jump to a heading by its block index.
+///
+/// Chunks are a read-only rendering of the document. The realtime protocol has no partial
+/// sync: a yrs document can't be applied in parts, so a client that starts editing syncs the
+/// whole document through the usual manifest, and stops using the chunks.
+pub async fn get_page_view_document_chunk(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  offset: Option<u32>,
+  limit: Option<u32>,
+) -> Result<DocumentChunk, AppError> {
+  let encoded_collab = collab_storage
+    .get_full_encode_collab(
+      GetCollabOrigin::User { uid },
+      &workspace_id,
+      &view_id,
+      CollabType::Document,
+    )
+    .await
+    .map(|v| v.encoded_collab)?;
+  let doc_size = encoded_collab.doc_state.len();
+  tokio::task::spawn_blocking(move || {
+    let document_data = document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &view_id)?;
+    let blocks = flatten_document_blocks(&document_data);
+    let total_blocks = blocks.len() as u32;
+    let is_large = doc_size > LARGE_DOCUMENT_SIZE_THRESHOLD;
+    let (offset, limit) = if is_large {
+      (
+        offset.unwrap_or(0).min(total_blocks),
+        limit
+          .unwrap_or(DEFAULT_DOCUMENT_CHUNK_BLOCKS)
+          .clamp(1, MAX_DOCUMENT_CHUNK_BLOCKS),
+      )
+    } else {
+      (0, total_blocks)
+    };
+    let end = offset.saturating_add(limit).min(total_blocks);
+    let chunk_blocks = blocks[offset as usize..end as usize]
+      .iter()
+      .map(|(depth, block)| to_document_chunk_block(&document_data, *depth, block))
+      .collect();
+    let toc = if offset == 0 {
+      document_headings(&document_data)
+    } else {
+      vec![]
+    };
+    Ok(DocumentChunk {
+      view_id,
+      page_id: document_data.page_id.clone(),
+      doc_size: doc_size as u64,
+      is_large,
+      total_blocks,
+      offset,
+      blocks: chunk_blocks,
+      has_more: end < total_blocks,
+      toc,
+    })
+  })
+  .await?
+}
+
+/// Returns the nested headings of a document view, so clients can render a table of contents
+/// without downloading the whole document.
+pub async fn get_page_view_document_outline(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: Uuid,
+) -> Result<DocumentOutline, AppError> {
+  let encoded_collab = collab_storage
+    .get_full_encode_collab(
+      GetCollabOrigin::User { uid },
+      &workspace_id,
+      &view_id,
+      CollabType::Document,
+    )
+    .await
+    .map(|v| v.encoded_collab)?;
+  tokio::task::spawn_blocking(move || {
+    let document_data = document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &view_id)?;
+    Ok(DocumentOutline {
+      view_id,
+      headings: document_outline(&document_data),
+    })
+  })
+  .await?
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn create_database_view(
   state: &AppState,
@@ -2529,14 +2821,7 @@ pub async fn create_database_view(
     workspace_database_update,
   )
   .await?;
-  update_workspace_folder_data(
-    &state.metrics.appflowy_web_metrics,
-    &state.ws_server,
-    user,
-    workspace_id,
-    folder_update,
-  )
-  .await?;
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
 
   Ok(())
 }
@@ -2576,6 +2861,16 @@ async fn update_collab_data_with_timeout(
     Ok(_) => Ok(()),
     Err(err) => {
       appflowy_web_metrics.incr_apply_update_failure_count(1);
+      let err = match err.downcast::<AppError>() {
+        // The update was refused rather than failed: tell the caller why.
+        Ok(
+          err @ (AppError::CollabStructureLimitExceeded(_)
+          | AppError::NotEnoughPermissions
+          | AppError::ServiceTemporaryUnavailable(_)),
+        ) => return Err(err),
+        Ok(err) => anyhow::Error::from(err),
+        Err(err) => err,
+      };
       Err(AppError::Internal(anyhow!(
         "Failed to apply {} update: {}",
         error_context,
@@ -2602,11 +2897,32 @@ pub async fn update_page_collab_data(
   state
     .ws_server
     .publish_update(workspace_id, object_id, collab_type, &origin, doc_state)
-    .await?;
+    .await
+    .map_err(|err| err.downcast::<AppError>().unwrap_or_else(AppError::Internal))?;
 
   Ok(())
 }
 
+/// Applies the update to the workspace folder, and syncs the view metadata of the workspace so
+/// that the lists served from it see the change.
+pub async fn update_workspace_folder(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+  update: Vec<u8>,
+) -> Result<(), AppError> {
+  update_workspace_folder_data(
+    &state.metrics.appflowy_web_metrics,
+    &state.ws_server,
+    user,
+    workspace_id,
+    update,
+  )
+  .await?;
+  refresh_workspace_view_metadata(&state.pg_pool, &state.ws_server, workspace_id).await;
+  Ok(())
+}
+
 #[instrument(level = "debug", skip_all)]
 pub async fn update_workspace_folder_data(
   appflowy_web_metrics: &AppFlowyWebMetrics,
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use database::page_watch::{
+  delete_page_watch, is_page_watched, select_edited_watched_pages, select_page_watchers,
+  update_page_watch_notified_at, upsert_page_watch,
+};
+use shared_entity::dto::workspace_dto::PageWatchStatus;
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::biz::notification::ops::create_workspace_notification;
+
+const PAGE_WATCH_DIGEST_INTERVAL_SECS: u64 = 15 * 60;
+const MAX_DIGEST_PAGES_PER_RUN: i64 = 1000;
+const PAGE_EDITED_NOTIFICATION: &str = "page_watch_edited";
+const PAGE_COMMENTED_NOTIFICATION: &str = "page_watch_commented";
+
+pub async fn watch_page(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<PageWatchStatus, AppError> {
+  upsert_page_watch(pg_pool, workspace_id, view_id, uid).await?;
+  Ok(PageWatchStatus {
+    view_id: *view_id,
+    watching: true,
+  })
+}
+
+pub async fn unwatch_page(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<PageWatchStatus, AppError> {
+  delete_page_watch(pg_pool, view_id, uid).await?;
+  Ok(PageWatchStatus {
+    view_id: *view_id,
+    watching: false,
+  })
+}
+
+pub async fn get_page_watch_status(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<PageWatchStatus, AppError> {
+  Ok(PageWatchStatus {
+    view_id: *view_id,
+    watching: is_page_watched(pg_pool, view_id, uid).await?,
+  })
+}
+
+/// Notifies everyone watching the view about a new comment, except its author.
+pub async fn notify_page_watchers_of_comment(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  author_uid: Option<i64>,
+  reply_comment_id: &Option<Uuid>,
+) -> Result<(), AppError> {
+  let payload = serde_json::json!({
+    "view_id": view_id,
+    "commented_by": author_uid,
+    "reply_comment_id": reply_comment_id,
+  });
+  for (workspace_id, uid) in select_page_watchers(pg_pool, view_id).await? {
+    if Some(uid) == author_uid {
+      continue;
+    }
+    if let Err(err) = create_workspace_notification(
+      pg_pool,
+      &workspace_id,
+      PAGE_COMMENTED_NOTIFICATION,
+      &payload,
+      Some(uid),
+    )
+    .await
+    {
+      warn!(
+        "failed to notify uid={} about a comment on {}: {:?}",
+        uid, view_id, err
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Edits are reported as a digest: at most one notification per watched page and interval,
+/// however many updates the page received. Only pages backed by their own collab, e.g.
+/// documents, are tracked.
+pub async fn start_page_watch_digest_task(pg_pool: PgPool) {
+  let mut timer = tokio::time::interval(Duration::from_secs(PAGE_WATCH_DIGEST_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    if let Err(err) = send_page_edit_digest(&pg_pool).await {
+      error!("failed to send page watch digest: {:?}", err);
+    }
+  }
+}
+
+async fn send_page_edit_digest(pg_pool: &PgPool) -> Result<(), AppError> {
+  for page in select_edited_watched_pages(pg_pool, MAX_DIGEST_PAGES_PER_RUN).await? {
+    let payload = serde_json::json!({
+      "view_id": page.view_id,
+      "edited_at": page.edited_at.timestamp(),
+    });
+    create_workspace_notification(
+      pg_pool,
+      &page.workspace_id,
+      PAGE_EDITED_NOTIFICATION,
+      &payload,
+      Some(page.uid),
+    )
+    .await?;
+    update_page_watch_notified_at(pg_pool, &page.view_id, page.uid, page.edited_at).await?;
+  }
+  Ok(())
+}
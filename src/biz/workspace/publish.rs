@@ -3,18 +3,19 @@ use database::{
     insert_non_orginal_workspace_publish_namespace, select_all_published_collab_info,
     select_default_published_view_id, select_default_published_view_id_for_namespace,
     select_workspace_publish_namespace, select_workspace_publish_namespaces,
-    update_published_collabs, update_workspace_default_publish_view,
+    update_published_collab_blob, update_published_collabs, update_workspace_default_publish_view,
     update_workspace_default_publish_view_set_null,
   },
   workspace::{select_publish_name_exists, select_view_id_from_publish_name},
 };
 use database_entity::dto::PatchPublishedCollab;
+use std::ops::DerefMut;
 use std::sync::Arc;
 
 use app_error::AppError;
 use async_trait::async_trait;
 use aws_sdk_s3::primitives::ByteStream;
-use database_entity::dto::{PublishCollabItem, PublishInfo};
+use database_entity::dto::{PublishCollabItem, PublishInfo, PublishPermission};
 use shared_entity::dto::{
   publish_dto::PublishViewMetaData,
   workspace_dto::{FolderViewMinimal, PublishInfoView},
@@ -32,16 +33,17 @@ use database::{
     select_user_is_collab_publisher_for_all_views, select_workspace_publish_namespace_exists,
     set_published_collabs_as_unpublished, update_non_orginal_workspace_publish_namespace,
   },
-  workspace::select_user_is_workspace_owner,
+  workspace::{select_user_is_workspace_owner, select_workspace_settings},
 };
 
+use crate::middleware::deadline::apply_statement_timeout;
 use crate::{
   api::metrics::PublishedCollabMetrics, biz::collab::folder_view::to_dto_folder_view_miminal,
 };
 
 use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
 
-async fn check_workspace_owner_or_publisher(
+pub(crate) async fn check_workspace_owner_or_publisher(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
   workspace_id: &Uuid,
@@ -61,6 +63,30 @@ async fn check_workspace_owner_or_publisher(
   Ok(())
 }
 
+/// Enforces the [PublishPermission] of the workspace. Callers are expected to have checked
+/// that the user is a member of the workspace.
+pub(crate) async fn check_user_can_publish(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let settings = select_workspace_settings(pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default();
+  let is_allowed = match settings.publish_permission {
+    PublishPermission::Members => true,
+    PublishPermission::Allowlist if settings.publish_allowlist.contains(&uid) => true,
+    PublishPermission::Owners | PublishPermission::Allowlist => {
+      select_user_is_workspace_owner(pg_pool, user_uuid, workspace_id).await?
+    },
+  };
+  if !is_allowed {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  Ok(())
+}
+
 fn check_collab_publish_name(publish_name: &str) -> Result<(), AppError> {
   const MAX_PUBLISH_NAME_LENGTH: usize = 128;
 
@@ -290,12 +316,29 @@ pub trait PublishedCollabStore: Sync + Send + 'static {
     user_uuid: &Uuid,
   ) -> Result<(), AppError>;
 
+  /// Unpublishes views that were moved to the trash or deleted. Unlike [Self::unpublish_collabs],
+  /// the user removing the views doesn't need to be their publisher.
+  async fn unpublish_removed_collabs(
+    &self,
+    workspace_id: &Uuid,
+    view_ids: &[Uuid],
+  ) -> Result<(), AppError>;
+
   async fn patch_collabs(
     &self,
     workspace_id: &Uuid,
     user_uuid: &Uuid,
     patches: &[PatchPublishedCollab],
   ) -> Result<(), AppError>;
+
+  /// Replaces the published data of a view without republishing it: its publish name, publisher
+  /// and settings are kept.
+  async fn update_collab_blob(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    blob: Vec<u8>,
+  ) -> Result<(), AppError>;
 }
 
 pub struct PublishedCollabPostgresStore {
@@ -404,6 +447,16 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
     user_uuid: &Uuid,
   ) -> Result<(), AppError> {
     check_workspace_owner_or_publisher(&self.pg_pool, user_uuid, workspace_id, view_ids).await?;
+    self
+      .unpublish_removed_collabs(workspace_id, view_ids)
+      .await
+  }
+
+  async fn unpublish_removed_collabs(
+    &self,
+    workspace_id: &Uuid,
+    view_ids: &[Uuid],
+  ) -> Result<(), AppError> {
     // 同时删除 af_published_collab 和 af_received_published_collab 中的记录
     set_published_collabs_as_unpublished(&self.pg_pool, workspace_id, view_ids).await?;
     Ok(())
@@ -417,6 +470,22 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
   ) -> Result<(), AppError> {
     patch_collabs(&self.pg_pool, workspace_id, user_uuid, patches).await
   }
+
+  async fn update_collab_blob(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    blob: Vec<u8>,
+  ) -> Result<(), AppError> {
+    if !update_published_collab_blob(&self.pg_pool, workspace_id, view_id, &blob).await? {
+      return Err(AppError::RecordNotFound(format!(
+        "view {} is not published",
+        view_id
+      )));
+    }
+    self.metrics.incr_success_write_count(1);
+    Ok(())
+  }
 }
 
 pub struct PublishedCollabS3StoreWithPostgresFallback {
@@ -591,6 +660,16 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
     user_uuid: &Uuid,
   ) -> Result<(), AppError> {
     check_workspace_owner_or_publisher(&self.pg_pool, user_uuid, workspace_id, view_ids).await?;
+    self
+      .unpublish_removed_collabs(workspace_id, view_ids)
+      .await
+  }
+
+  async fn unpublish_removed_collabs(
+    &self,
+    workspace_id: &Uuid,
+    view_ids: &[Uuid],
+  ) -> Result<(), AppError> {
     let object_keys = view_ids
       .iter()
       .map(|view_id| get_collab_s3_key(workspace_id, view_id))
@@ -609,6 +688,32 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
   ) -> Result<(), AppError> {
     patch_collabs(&self.pg_pool, workspace_id, user_uuid, patches).await
   }
+
+  async fn update_collab_blob(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    blob: Vec<u8>,
+  ) -> Result<(), AppError> {
+    // The Postgres copy is updated first, it is the fallback when the S3 object is missing
+    if !update_published_collab_blob(&self.pg_pool, workspace_id, view_id, &blob).await? {
+      return Err(AppError::RecordNotFound(format!(
+        "view {} is not published",
+        view_id
+      )));
+    }
+    let object_key = get_collab_s3_key(workspace_id, view_id);
+    let result = self
+      .bucket_client
+      .put_blob(&object_key, ByteStream::from(blob), None)
+      .await;
+    if let Err(err) = result {
+      self.metrics.incr_failure_write_count(1);
+      return Err(err);
+    }
+    self.metrics.incr_success_write_count(1);
+    Ok(())
+  }
 }
 
 async fn patch_collabs(
@@ -630,6 +735,7 @@ async fn patch_collabs(
   check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &view_ids).await?;
 
   let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
   update_published_collabs(&mut txn, workspace_id, patches).await?;
   txn.commit().await?;
   Ok(())
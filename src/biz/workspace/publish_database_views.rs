@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use app_error::AppError;
+use collab::core::collab::default_client_id;
+use collab_database::database::DatabaseBody;
+use collab_database::database_trait::NoPersistenceDatabaseCollabService;
+use database::publish::select_published_metadata_for_view_id;
+use shared_entity::dto::publish_dto::{
+  PublishDatabaseData, PublishDatabaseDataWithNonUuidRelations, PublishedDatabaseViews,
+};
+use shared_entity::dto::workspace_dto::ViewLayout;
+use uuid::Uuid;
+
+use super::publish::check_workspace_owner_or_publisher;
+use crate::biz::collab::utils::collab_from_doc_state;
+use crate::state::AppState;
+
+pub async fn get_published_database_views(
+  state: &AppState,
+  workspace_id: Uuid,
+  view_id: Uuid,
+) -> Result<PublishedDatabaseViews, AppError> {
+  let (data, database_view_ids) = get_published_database_data(state, workspace_id, view_id).await?;
+  Ok(PublishedDatabaseViews {
+    visible_database_view_ids: data.visible_database_view_ids,
+    database_view_ids,
+  })
+}
+
+/// Changes which views of a published database are visible, without republishing it. The
+/// published view itself stays visible, and only views in the published data of the database can
+/// be made visible: views added to the database since it was published are missing from it until
+/// the database is published again.
+pub async fn update_published_database_views(
+  state: &AppState,
+  user_uuid: &Uuid,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  visible_database_view_ids: Vec<Uuid>,
+) -> Result<PublishedDatabaseViews, AppError> {
+  check_workspace_owner_or_publisher(&state.pg_pool, user_uuid, &workspace_id, &[view_id]).await?;
+  let (mut data, database_view_ids) =
+    get_published_database_data(state, workspace_id, view_id).await?;
+  if !visible_database_view_ids.contains(&view_id) {
+    return Err(AppError::InvalidRequest(format!(
+      "the published view {} must stay visible",
+      view_id
+    )));
+  }
+  if let Some(unknown_view_id) = visible_database_view_ids
+    .iter()
+    .find(|id| !database_view_ids.contains(id))
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "view {} is not in the published data of the database, publish the database again to show it",
+      unknown_view_id
+    )));
+  }
+
+  let mut visible = Vec::with_capacity(visible_database_view_ids.len());
+  for id in visible_database_view_ids {
+    if !visible.contains(&id) {
+      visible.push(id);
+    }
+  }
+  data.visible_database_view_ids = visible;
+  state
+    .published_collab_store
+    .update_collab_blob(&workspace_id, &view_id, serde_json::to_vec(&data)?)
+    .await?;
+  Ok(PublishedDatabaseViews {
+    visible_database_view_ids: data.visible_database_view_ids,
+    database_view_ids,
+  })
+}
+
+/// The published data of the database view, and the views of the database in that data.
+async fn get_published_database_data(
+  state: &AppState,
+  workspace_id: Uuid,
+  view_id: Uuid,
+) -> Result<(PublishDatabaseData, Vec<Uuid>), AppError> {
+  let not_published = || AppError::RecordNotFound(format!("view {} is not published", view_id));
+  match select_published_metadata_for_view_id(&state.pg_pool, &view_id).await? {
+    Some((published_workspace_id, _)) if published_workspace_id == workspace_id => {},
+    _ => return Err(not_published()),
+  }
+  let (metadata, blob) = state
+    .published_collab_store
+    .get_collab_with_view_metadata_by_view_id(&view_id)
+    .await?
+    .ok_or_else(not_published)?;
+  if !matches!(
+    metadata.view.layout,
+    ViewLayout::Grid | ViewLayout::Board | ViewLayout::Calendar
+  ) {
+    return Err(AppError::InvalidRequest(format!(
+      "published view {} is not a database",
+      view_id
+    )));
+  }
+  let data: PublishDatabaseData =
+    serde_json::from_slice::<PublishDatabaseDataWithNonUuidRelations>(&blob)?.into();
+  let database_view_ids = published_database_view_ids(&data)?;
+  Ok((data, database_view_ids))
+}
+
+fn published_database_view_ids(data: &PublishDatabaseData) -> Result<Vec<Uuid>, AppError> {
+  let client_id = default_client_id();
+  let collab = collab_from_doc_state(data.database_collab.clone(), &Uuid::default(), client_id)?;
+  let body = DatabaseBody::from_collab(
+    &collab,
+    Arc::new(NoPersistenceDatabaseCollabService::new(client_id)),
+    None,
+  )
+  .ok_or_else(|| AppError::RecordNotFound("no database body found".to_string()))?;
+  let txn = collab.context.transact();
+  let view_ids = body
+    .views
+    .get_all_views(&txn)
+    .into_iter()
+    .filter_map(|view| Uuid::parse_str(&view.id).ok())
+    .collect();
+  Ok(view_ids)
+}
@@ -25,12 +25,14 @@ use shared_entity::dto::publish_dto::{PublishDatabaseData, PublishViewInfo, Publ
 use shared_entity::dto::workspace_dto::ViewLayout;
 use sqlx::PgPool;
 use std::collections::HashSet;
+use std::ops::DerefMut;
 use std::time::{Duration, Instant};
 use std::{collections::HashMap, sync::Arc};
 
 use crate::biz::collab::folder_view::to_folder_view_icon;
 use crate::biz::collab::folder_view::to_folder_view_layout;
 use crate::biz::collab::utils::{collab_from_doc_state, get_latest_collab};
+use crate::middleware::deadline::apply_statement_timeout;
 use tracing::error;
 use uuid::Uuid;
 use workspace_template::gen_view_id;
@@ -119,7 +121,7 @@ pub struct PublishCollabDuplicator {
   is_readonly: bool,
 }
 
-fn deserialize_publish_database_data(
+pub(crate) fn deserialize_publish_database_data(
   published_blob: &[u8],
 ) -> Result<PublishDatabaseData, AppError> {
   match serde_json::from_slice::<PublishDatabaseData>(published_blob) {
@@ -211,6 +213,7 @@ impl PublishCollabDuplicator {
     // insert all collab object accumulated
     // for self.collabs_to_insert
     let mut txn = pg_pool.begin().await?;
+    apply_statement_timeout(txn.deref_mut()).await?;
     let start = Instant::now();
     for (oid, (collab_type, encoded_collab)) in collabs_to_insert.into_iter() {
       let params = CollabParams {
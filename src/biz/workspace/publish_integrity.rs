@@ -0,0 +1,296 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use collab::core::collab::default_client_id;
+use collab_document::document::DocumentBody;
+use database::collab::CollabStore;
+use database::publish::{select_published_metadata_for_view_id, select_published_row_filter};
+use database::publish_integrity::{
+  select_workspace_published_views_with_publisher,
+  select_workspaces_due_for_publish_integrity_scan, upsert_publish_integrity_report,
+};
+use shared_entity::dto::publish_dto::PublishViewMetaData;
+use shared_entity::dto::workspace_dto::{BrokenPublishedView, PublishIntegrityReport, ViewLayout};
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use super::page_view::{generate_publish_data_for_database, generate_publish_data_for_document};
+use super::publish::PublishedCollabStore;
+use super::publish_dup::deserialize_publish_database_data;
+use crate::biz::collab::utils::collab_from_doc_state;
+
+const PUBLISH_INTEGRITY_SCAN_INTERVAL_SECS: u64 = 86400;
+const PUBLISH_INTEGRITY_STALE_AFTER_DAYS: i32 = 7;
+const PUBLISH_INTEGRITY_WORKSPACES_PER_RUN: i64 = 50;
+
+/// Scans the published views of workspaces on a schedule. Broken views found by the scheduled
+/// scan are regenerated right away, so that public pages are fixed without their author having
+/// to republish them.
+pub async fn start_publish_integrity_task(
+  pg_pool: PgPool,
+  collab_storage: Arc<dyn CollabStore>,
+  published_collab_store: Arc<dyn PublishedCollabStore>,
+) {
+  let mut timer = tokio::time::interval(Duration::from_secs(PUBLISH_INTEGRITY_SCAN_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    if let Err(err) =
+      run_publish_integrity_task(&pg_pool, &collab_storage, &published_collab_store).await
+    {
+      error!("publish integrity task failed: {:?}", err);
+    }
+  }
+}
+
+async fn run_publish_integrity_task(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  published_collab_store: &Arc<dyn PublishedCollabStore>,
+) -> Result<(), AppError> {
+  let workspace_ids = select_workspaces_due_for_publish_integrity_scan(
+    pg_pool,
+    PUBLISH_INTEGRITY_STALE_AFTER_DAYS,
+    PUBLISH_INTEGRITY_WORKSPACES_PER_RUN,
+  )
+  .await?;
+  for workspace_id in workspace_ids {
+    match scan_workspace_publish_integrity(
+      pg_pool,
+      collab_storage,
+      published_collab_store,
+      workspace_id,
+      true,
+    )
+    .await
+    {
+      Ok(report) => info!(
+        "publish integrity scan of workspace {}: {} broken views, {} regenerated",
+        workspace_id,
+        report.broken_views.len(),
+        report
+          .broken_views
+          .iter()
+          .filter(|view| view.regenerated)
+          .count()
+      ),
+      Err(err) => warn!(
+        "failed to scan publish integrity of workspace {}: {:?}",
+        workspace_id, err
+      ),
+    }
+  }
+  Ok(())
+}
+
+/// Checks that the published data of every published view of the workspace can be read back
+/// as the layout of its publish metadata: a document collab for documents, and the database,
+/// row and row document collabs for databases. With `regenerate`, the published data of broken
+/// views is rebuilt from the current state of the view, keeping its publish name and settings.
+/// Views whose publish metadata is unreadable are reported but not regenerated, since their
+/// layout is unknown. The report is stored as the latest report of the workspace.
+#[instrument(
+  level = "debug",
+  skip(pg_pool, collab_storage, published_collab_store),
+  err
+)]
+pub async fn scan_workspace_publish_integrity(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  published_collab_store: &Arc<dyn PublishedCollabStore>,
+  workspace_id: Uuid,
+  regenerate: bool,
+) -> Result<PublishIntegrityReport, AppError> {
+  let published_views =
+    select_workspace_published_views_with_publisher(pg_pool, &workspace_id).await?;
+  let mut scanned_views = 0;
+  let mut broken_views = vec![];
+  for (view_id, publisher_uid) in published_views {
+    let layout = match published_view_layout(pg_pool, &view_id).await? {
+      Some(Ok(layout)) => layout,
+      Some(Err(err)) => {
+        scanned_views += 1;
+        broken_views.push(BrokenPublishedView {
+          view_id,
+          error: err,
+          regenerated: false,
+          regenerate_error: None,
+        });
+        continue;
+      },
+      // Unpublished since the list was read
+      None => continue,
+    };
+    scanned_views += 1;
+    let blob = match published_collab_store
+      .get_collab_with_view_metadata_by_view_id(&view_id)
+      .await
+    {
+      Ok(Some((_, blob))) => blob,
+      Ok(None) => continue,
+      Err(err) => {
+        broken_views.push(BrokenPublishedView {
+          view_id,
+          error: err.to_string(),
+          regenerated: false,
+          regenerate_error: None,
+        });
+        continue;
+      },
+    };
+    let checked_blob = blob.clone();
+    let check_layout = layout.clone();
+    let error = match tokio::task::spawn_blocking(move || {
+      verify_published_blob(&check_layout, &view_id, checked_blob)
+    })
+    .await?
+    {
+      Ok(()) => continue,
+      Err(err) => err,
+    };
+
+    let mut broken_view = BrokenPublishedView {
+      view_id,
+      error,
+      regenerated: false,
+      regenerate_error: None,
+    };
+    if regenerate {
+      match regenerate_published_blob(
+        pg_pool,
+        collab_storage,
+        published_collab_store,
+        workspace_id,
+        view_id,
+        publisher_uid,
+        &layout,
+        &blob,
+      )
+      .await
+      {
+        Ok(()) => broken_view.regenerated = true,
+        Err(err) => broken_view.regenerate_error = Some(err.to_string()),
+      }
+    }
+    broken_views.push(broken_view);
+  }
+
+  let report = PublishIntegrityReport {
+    workspace_id,
+    scanned_at: Utc::now(),
+    scanned_views,
+    broken_views,
+  };
+  upsert_publish_integrity_report(pg_pool, &report).await?;
+  Ok(report)
+}
+
+/// Layout of a published view according to its publish metadata. Returns `None` when the view
+/// is not published, and an error message when the metadata can't be read.
+async fn published_view_layout(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+) -> Result<Option<Result<ViewLayout, String>>, AppError> {
+  let metadata = match select_published_metadata_for_view_id(pg_pool, view_id).await? {
+    Some((_, metadata)) => metadata,
+    None => return Ok(None),
+  };
+  let layout = match serde_json::from_value::<PublishViewMetaData>(metadata) {
+    Ok(metadata) if metadata.view.view_id != view_id.to_string() => Err(format!(
+      "publish metadata belongs to view {}",
+      metadata.view.view_id
+    )),
+    Ok(metadata) => Ok(metadata.view.layout),
+    Err(err) => Err(format!("invalid publish metadata: {}", err)),
+  };
+  Ok(Some(layout))
+}
+
+fn verify_published_blob(layout: &ViewLayout, view_id: &Uuid, blob: Vec<u8>) -> Result<(), String> {
+  match layout {
+    ViewLayout::Document => {
+      let collab = collab_from_doc_state(blob, view_id, default_client_id())
+        .map_err(|err| format!("invalid document collab: {}", err))?;
+      if DocumentBody::from_collab(&collab).is_none() {
+        return Err("published collab is not a document".to_string());
+      }
+      Ok(())
+    },
+    ViewLayout::Grid | ViewLayout::Board | ViewLayout::Calendar => {
+      let data = deserialize_publish_database_data(&blob)
+        .map_err(|err| format!("invalid database data: {}", err))?;
+      let database_id = data
+        .database_relations
+        .keys()
+        .next()
+        .copied()
+        .ok_or_else(|| "database data has no database".to_string())?;
+      collab_from_doc_state(data.database_collab, &database_id, default_client_id())
+        .map_err(|err| format!("invalid database collab: {}", err))?;
+      for (row_id, doc_state) in data.database_row_collabs {
+        collab_from_doc_state(doc_state, &row_id, default_client_id())
+          .map_err(|err| format!("invalid collab for row {}: {}", row_id, err))?;
+      }
+      for (document_id, doc_state) in data.database_row_document_collabs {
+        collab_from_doc_state(doc_state, &document_id, default_client_id())
+          .map_err(|err| format!("invalid collab for row document {}: {}", document_id, err))?;
+      }
+      Ok(())
+    },
+    ViewLayout::Chat => Err("chats can't be published".to_string()),
+  }
+}
+
+/// Rebuilds the published data of the view as [super::page_view::publish_page] does, on behalf
+/// of its publisher. A database keeps its visible views when they can still be read from the
+/// broken data, and its row filter.
+#[allow(clippy::too_many_arguments)]
+async fn regenerate_published_blob(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  published_collab_store: &Arc<dyn PublishedCollabStore>,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  publisher_uid: i64,
+  layout: &ViewLayout,
+  broken_blob: &[u8],
+) -> Result<(), AppError> {
+  let blob = match layout {
+    ViewLayout::Document => {
+      generate_publish_data_for_document(collab_storage, publisher_uid, workspace_id, view_id)
+        .await?
+    },
+    ViewLayout::Grid | ViewLayout::Board | ViewLayout::Calendar => {
+      let visible_database_view_ids = deserialize_publish_database_data(broken_blob)
+        .ok()
+        .map(|data| data.visible_database_view_ids)
+        .filter(|ids| ids.contains(&view_id));
+      let row_filter = select_published_row_filter(pg_pool, &workspace_id, &view_id).await?;
+      generate_publish_data_for_database(
+        pg_pool,
+        collab_storage,
+        publisher_uid,
+        workspace_id,
+        view_id,
+        visible_database_view_ids,
+        row_filter.as_ref(),
+      )
+      .await?
+    },
+    ViewLayout::Chat => {
+      return Err(AppError::InvalidRequest(
+        "AI Chat cannot be published".to_string(),
+      ))
+    },
+  };
+  published_collab_store
+    .update_collab_blob(&workspace_id, &view_id, blob)
+    .await?;
+  info!(
+    "regenerated published data of view {} in workspace {}",
+    view_id, workspace_id
+  );
+  Ok(())
+}
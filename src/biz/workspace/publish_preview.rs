@@ -0,0 +1,308 @@
+use app_error::AppError;
+use appflowy_ai_client::client::AppFlowyAIClient;
+use collab::core::collab::default_client_id;
+use collab_document::document::Document;
+use database::publish::{
+  select_published_view_preview_description, upsert_published_view_preview_description,
+};
+use database::workspace::select_workspace_settings;
+use database_entity::dto::PublishCollabItem;
+use serde_json::{json, Map, Value};
+use shared_entity::dto::publish_dto::PublishViewMetaData;
+use shared_entity::dto::workspace_dto::ViewLayout;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::biz::collab::utils::collab_from_doc_state;
+use crate::state::AppState;
+
+const MAX_DESCRIPTION_CHARS: usize = 200;
+/// Only the beginning of the page is sent to the AI to write the description
+const MAX_AI_INPUT_CHARS: usize = 4000;
+
+/// What chat apps show when a publish link is shared.
+#[derive(Debug, Clone)]
+pub struct PublishedPagePreview {
+  pub title: String,
+  pub description: Option<String>,
+  pub image_url: Option<String>,
+  pub url: String,
+}
+
+/// A document that was just published, to be described by the AI.
+pub struct PublishedDocument {
+  pub view_id: Uuid,
+  pub title: String,
+  pub blob: Vec<u8>,
+}
+
+/// The documents among the published items.
+pub fn published_documents(
+  items: &[PublishCollabItem<serde_json::Value, Vec<u8>>],
+) -> Vec<PublishedDocument> {
+  items
+    .iter()
+    .filter_map(|item| {
+      let metadata: PublishViewMetaData =
+        serde_json::from_value(item.meta.metadata.clone()).ok()?;
+      if metadata.view.layout != ViewLayout::Document {
+        return None;
+      }
+      Some(PublishedDocument {
+        view_id: item.meta.view_id,
+        title: metadata.view.name.trim().to_string(),
+        blob: item.data.clone(),
+      })
+    })
+    .collect()
+}
+
+/// Writes the AI descriptions of the published documents in the background, one document at a
+/// time. Descriptions are only written when publishing, so that visits of the publish page
+/// never call the AI.
+pub fn describe_published_documents(
+  state: &AppState,
+  workspace_id: Uuid,
+  documents: Vec<PublishedDocument>,
+) {
+  if documents.is_empty() {
+    return;
+  }
+  let pg_pool = state.pg_pool.clone();
+  let ai_client = state.ai_client.clone();
+  tokio::spawn(async move {
+    for document in documents {
+      let text = match tokio::task::spawn_blocking(move || document_text(document.blob)).await {
+        Ok(text) => text,
+        Err(err) => {
+          warn!(
+            "failed to read published view {}: {:?}",
+            document.view_id, err
+          );
+          continue;
+        },
+      };
+      if text.is_empty() {
+        continue;
+      }
+      let content_hash = format!("{:x}", md5::compute(text.as_bytes()));
+      match select_published_view_preview_description(&pg_pool, &document.view_id, &content_hash)
+        .await
+      {
+        Ok(Some(_)) => continue,
+        Ok(None) => {},
+        Err(err) => {
+          warn!(
+            "failed to read the description of published view {}: {:?}",
+            document.view_id, err
+          );
+          continue;
+        },
+      }
+      generate_ai_description(
+        &pg_pool,
+        &ai_client,
+        workspace_id,
+        document.view_id,
+        content_hash,
+        document.title,
+        text,
+      )
+      .await;
+    }
+  });
+}
+
+/// Builds the preview of a published page. The description is the one the AI wrote when the
+/// page was published, and is otherwise taken from its first paragraphs.
+pub async fn get_published_page_preview(
+  state: &AppState,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<PublishedPagePreview, AppError> {
+  let metadata = state
+    .published_collab_store
+    .get_collab_metadata(publish_namespace, publish_name)
+    .await?;
+  let metadata: PublishViewMetaData = serde_json::from_value(metadata)?;
+  let url = format!(
+    "{}/{}/{}",
+    state.config.appflowy_web_url, publish_namespace, publish_name
+  );
+  let title = if metadata.view.name.trim().is_empty() {
+    publish_name.to_string()
+  } else {
+    metadata.view.name.trim().to_string()
+  };
+  let image_url = metadata.view.extra.as_deref().and_then(cover_image_url);
+
+  let mut description = None;
+  if metadata.view.layout == ViewLayout::Document {
+    let blob = state
+      .published_collab_store
+      .get_collab_blob_by_publish_namespace(publish_namespace, publish_name)
+      .await?;
+    let text = tokio::task::spawn_blocking(move || document_text(blob)).await?;
+    if !text.is_empty() {
+      let view_id: Uuid = metadata.view.view_id.parse()?;
+      let content_hash = format!("{:x}", md5::compute(text.as_bytes()));
+      description =
+        select_published_view_preview_description(&state.pg_pool, &view_id, &content_hash)
+          .await?
+          .or_else(|| Some(truncate_description(&text)));
+    }
+  }
+
+  Ok(PublishedPagePreview {
+    title,
+    description,
+    image_url,
+    url,
+  })
+}
+
+async fn generate_ai_description(
+  pg_pool: &PgPool,
+  ai_client: &AppFlowyAIClient,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  content_hash: String,
+  title: String,
+  text: String,
+) {
+  let ai_model = match select_workspace_settings(pg_pool, &workspace_id).await {
+    Ok(settings) => settings.unwrap_or_default().ai_model,
+    Err(err) => {
+      warn!(
+        "failed to read settings of workspace {}: {:?}",
+        workspace_id, err
+      );
+      return;
+    },
+  };
+  let content: String = text.chars().take(MAX_AI_INPUT_CHARS).collect();
+  let mut params = Map::new();
+  params.insert("title".to_string(), json!(title));
+  params.insert("content".to_string(), json!(content));
+  let description = match ai_client.summarize_row(&params, &ai_model).await {
+    Ok(resp) => truncate_description(resp.text.trim()),
+    Err(err) => {
+      warn!("failed to describe published view {}: {:?}", view_id, err);
+      return;
+    },
+  };
+  if description.is_empty() {
+    return;
+  }
+  if let Err(err) =
+    upsert_published_view_preview_description(pg_pool, &view_id, &content_hash, &description).await
+  {
+    warn!(
+      "failed to store the description of published view {}: {:?}",
+      view_id, err
+    );
+  }
+}
+
+fn document_text(blob: Vec<u8>) -> String {
+  let paragraphs = collab_from_doc_state(blob, &Uuid::default(), default_client_id())
+    .ok()
+    .and_then(|collab| Document::open(collab).ok())
+    .map(|document| document.paragraphs())
+    .unwrap_or_default();
+  paragraphs
+    .iter()
+    .map(|paragraph| paragraph.trim())
+    .filter(|paragraph| !paragraph.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn truncate_description(text: &str) -> String {
+  if text.chars().count() <= MAX_DESCRIPTION_CHARS {
+    return text.to_string();
+  }
+  let mut description: String = text.chars().take(MAX_DESCRIPTION_CHARS - 1).collect();
+  description.push('…');
+  description
+}
+
+/// Covers picked from an image URL. Colors and gradients are not images.
+fn cover_image_url(extra: &str) -> Option<String> {
+  let extra: Value = serde_json::from_str(extra).ok()?;
+  let value = extra.get("cover")?.get("value")?.as_str()?;
+  if value.starts_with("https://") || value.starts_with("http://") {
+    Some(value.to_string())
+  } else {
+    None
+  }
+}
+
+/// The Open Graph and Twitter card tags of the preview, to be placed in the `<head>` of the
+/// publish page.
+pub fn render_preview_head(preview: &PublishedPagePreview) -> String {
+  let mut tags = vec![
+    ("property", "og:type", "article".to_string()),
+    ("property", "og:title", preview.title.clone()),
+    ("property", "og:url", preview.url.clone()),
+    ("name", "twitter:title", preview.title.clone()),
+  ];
+  if let Some(description) = &preview.description {
+    tags.push(("name", "description", description.clone()));
+    tags.push(("property", "og:description", description.clone()));
+    tags.push(("name", "twitter:description", description.clone()));
+  }
+  match &preview.image_url {
+    Some(image_url) => {
+      tags.push(("property", "og:image", image_url.clone()));
+      tags.push(("name", "twitter:image", image_url.clone()));
+      tags.push(("name", "twitter:card", "summary_large_image".to_string()));
+    },
+    None => tags.push(("name", "twitter:card", "summary".to_string())),
+  }
+
+  let mut head = format!(
+    "<title>{}</title>\n",
+    html_escape::encode_text(&preview.title)
+  );
+  for (attribute, key, content) in tags {
+    head.push_str(&format!(
+      "<meta {}=\"{}\" content=\"{}\"/>\n",
+      attribute,
+      key,
+      html_escape::encode_double_quoted_attribute(&content)
+    ));
+  }
+  head
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn preview_head_escapes_page_content() {
+    let preview = PublishedPagePreview {
+      title: "Q3 \"plans\" <draft>".to_string(),
+      description: Some(truncate_description(&"a".repeat(300))),
+      image_url: cover_image_url(r#"{"cover":{"type":"custom","value":"https://img/1.png"}}"#),
+      url: "https://web/ns/plans".to_string(),
+    };
+    let head = render_preview_head(&preview);
+    assert!(head.contains("<title>Q3 \"plans\" &lt;draft&gt;</title>"));
+    assert!(
+      head.contains("<meta property=\"og:title\" content=\"Q3 &quot;plans&quot; &lt;draft&gt;\"/>")
+    );
+    assert!(head.contains("<meta property=\"og:image\" content=\"https://img/1.png\"/>"));
+    assert!(head.contains("<meta name=\"twitter:card\" content=\"summary_large_image\"/>"));
+    assert_eq!(
+      preview.description.unwrap().chars().count(),
+      MAX_DESCRIPTION_CHARS
+    );
+    assert_eq!(
+      cover_image_url(r#"{"cover":{"type":"gradient","value":"appflowy_them_color_gradient4"}}"#),
+      None
+    );
+  }
+}
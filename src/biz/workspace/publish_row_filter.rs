@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use app_error::AppError;
+use collab_database::entity::FieldType;
+use database::collab::CollabStore;
+use database::publish::{delete_published_row_filter, select_published_row_filter};
+use serde_json::Value;
+use shared_entity::dto::publish_dto::{PublishRowFilter, PublishRowFilterCondition};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::publish::check_workspace_owner_or_publisher;
+use crate::biz::collab::ops::list_database_row_details;
+use crate::biz::collab::utils::{field_by_id_name_uniq, get_latest_collab_database_body};
+
+pub async fn get_published_row_filter(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<PublishRowFilter, AppError> {
+  select_published_row_filter(pg_pool, workspace_id, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("view {} has no row filter", view_id)))
+}
+
+/// Removes the row filter of the database. The public data keeps the filtered rows until the
+/// database is published again.
+pub async fn remove_published_row_filter(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  if !delete_published_row_filter(pg_pool, workspace_id, view_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "view {} has no row filter",
+      view_id
+    )));
+  }
+  Ok(())
+}
+
+pub fn validate_row_filter(filter: &PublishRowFilter) -> Result<(), AppError> {
+  if filter.field.trim().is_empty() {
+    return Err(AppError::InvalidRequest(
+      "the field of the row filter cannot be empty".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+/// The rows of the database matching the filter. Rows that cannot be read are left out, so that
+/// a row is only published when it is known to match.
+pub async fn filter_published_rows(
+  collab_storage: &Arc<dyn CollabStore>,
+  uid: i64,
+  workspace_id: Uuid,
+  database_id: Uuid,
+  row_ids: &[Uuid],
+  filter: &PublishRowFilter,
+) -> Result<Vec<Uuid>, AppError> {
+  if row_ids.is_empty() {
+    return Ok(vec![]);
+  }
+  let split_options =
+    split_field_options(collab_storage, workspace_id, database_id, filter).await?;
+  let rows = list_database_row_details(
+    collab_storage,
+    uid,
+    workspace_id,
+    database_id,
+    row_ids,
+    &[],
+    false,
+  )
+  .await?;
+  let matching_row_ids: HashSet<Uuid> = rows
+    .iter()
+    .filter(|row| row_matches_filter(&row.cells, filter, split_options))
+    .filter_map(|row| Uuid::parse_str(&row.id).ok())
+    .collect();
+  // Keep the order of the rows in the database
+  Ok(
+    row_ids
+      .iter()
+      .filter(|row_id| matching_row_ids.contains(row_id))
+      .copied()
+      .collect(),
+  )
+}
+
+/// Whether the cells of the filtered field hold a list of options separated by commas, that
+/// are matched one by one. Fails when the database has no field with the name of the filter.
+async fn split_field_options(
+  collab_storage: &Arc<dyn CollabStore>,
+  workspace_id: Uuid,
+  database_id: Uuid,
+  filter: &PublishRowFilter,
+) -> Result<bool, AppError> {
+  let (db_collab, db_body) =
+    get_latest_collab_database_body(collab_storage, workspace_id, database_id).await?;
+  let fields = db_body.fields.get_all_fields(&db_collab.transact());
+  // Cells are keyed by the same unique field names
+  let field_type = field_by_id_name_uniq(fields)
+    .into_values()
+    .find(|field| field.name == filter.field)
+    .map(|field| FieldType::from(field.field_type))
+    .ok_or_else(|| {
+      AppError::InvalidRequest(format!("the database has no field named {}", filter.field))
+    })?;
+  Ok(matches!(
+    field_type,
+    FieldType::MultiSelect | FieldType::Checklist
+  ))
+}
+
+fn row_matches_filter(
+  cells: &HashMap<String, Value>,
+  filter: &PublishRowFilter,
+  split_options: bool,
+) -> bool {
+  let value = filter.value.trim().to_lowercase();
+  let texts = cells
+    .get(&filter.field)
+    .map(|cell| cell_texts(cell, split_options))
+    .unwrap_or_default();
+  let is = texts.iter().any(|text| *text == value);
+  match filter.condition {
+    PublishRowFilterCondition::Is => is,
+    PublishRowFilterCondition::IsNot => !is,
+    PublishRowFilterCondition::Contains => texts.iter().any(|text| text.contains(&value)),
+  }
+}
+
+/// Lowercase texts of the cell. With `split_options`, the cell holds options separated by
+/// commas and each of them is a text of its own; other cells are compared as a whole.
+fn cell_texts(value: &Value, split_options: bool) -> Vec<String> {
+  match value {
+    Value::Null => vec![],
+    Value::String(text) => {
+      let text = text.trim().to_lowercase();
+      if !split_options {
+        return vec![text];
+      }
+      text
+        .split(',')
+        .map(|option| option.trim().to_string())
+        .filter(|option| !option.is_empty())
+        .collect()
+    },
+    Value::Array(values) => values
+      .iter()
+      .flat_map(|value| cell_texts(value, split_options))
+      .collect(),
+    Value::Bool(_) | Value::Number(_) | Value::Object(_) => vec![value.to_string().to_lowercase()],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn filter(condition: PublishRowFilterCondition, value: &str) -> PublishRowFilter {
+    PublishRowFilter {
+      field: "Status".to_string(),
+      condition,
+      value: value.to_string(),
+    }
+  }
+
+  #[test]
+  fn match_rows_against_filter() {
+    let public = HashMap::from([("Status".to_string(), json!("Public"))]);
+    let tagged = HashMap::from([("Status".to_string(), json!("Draft, public"))]);
+    let empty = HashMap::from([("Status".to_string(), Value::Null)]);
+
+    let is_public = filter(PublishRowFilterCondition::Is, " public ");
+    assert!(row_matches_filter(&public, &is_public, false));
+    assert!(row_matches_filter(&tagged, &is_public, true));
+    assert!(!row_matches_filter(&empty, &is_public, true));
+
+    let is_not_public = filter(PublishRowFilterCondition::IsNot, "Public");
+    assert!(!row_matches_filter(&public, &is_not_public, false));
+    assert!(row_matches_filter(&empty, &is_not_public, false));
+
+    let contains = filter(PublishRowFilterCondition::Contains, "pub");
+    assert!(row_matches_filter(&public, &contains, false));
+    assert!(!row_matches_filter(&empty, &contains, false));
+  }
+
+  #[test]
+  fn plain_text_cells_are_not_split() {
+    let text = HashMap::from([("Status".to_string(), json!("Draft, public"))]);
+    assert!(!row_matches_filter(
+      &text,
+      &filter(PublishRowFilterCondition::Is, "public"),
+      false
+    ));
+    assert!(row_matches_filter(
+      &text,
+      &filter(PublishRowFilterCondition::Is, "draft, public"),
+      false
+    ));
+    assert!(row_matches_filter(
+      &text,
+      &filter(PublishRowFilterCondition::Contains, "public"),
+      false
+    ));
+    // The whole value of a multi-select cell is not one of its options
+    assert!(!row_matches_filter(
+      &text,
+      &filter(PublishRowFilterCondition::Is, "draft, public"),
+      true
+    ));
+  }
+}
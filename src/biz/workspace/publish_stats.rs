@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use dashmap::DashMap;
+use database::publish_stats::{
+  select_published_view_stats_due, upsert_published_view_stats_bulk,
+  upsert_published_view_stats_digest_sent_at,
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::biz::notification::ops::create_workspace_notification;
+
+const PUBLISH_VIEW_COUNTER_FLUSH_INTERVAL_SECS: u64 = 60;
+const PUBLISHED_VIEW_STATS_DIGEST_INTERVAL_SECS: u64 = 3600;
+const PUBLISHED_VIEW_STATS_PERIOD_DAYS: i64 = 7;
+const MAX_STATS_DIGESTS_PER_RUN: i64 = 1000;
+const PUBLISHED_VIEW_STATS_NOTIFICATION: &str = "published_view_weekly_stats";
+
+/// Counts the views of published pages in memory and periodically adds them to the daily
+/// counters in `af_published_view_stats_daily`, so serving a published page costs no
+/// database write.
+#[derive(Default)]
+pub struct PublishViewCounter {
+  pending: DashMap<(Uuid, String), i64>,
+}
+
+impl PublishViewCounter {
+  pub fn record(&self, workspace_id: Uuid, publish_name: &str) {
+    *self
+      .pending
+      .entry((workspace_id, publish_name.to_string()))
+      .or_default() += 1;
+  }
+
+  async fn flush(&self, pg_pool: &PgPool) -> Result<(), AppError> {
+    let keys: Vec<(Uuid, String)> = self
+      .pending
+      .iter()
+      .map(|entry| entry.key().clone())
+      .collect();
+    let items: Vec<(Uuid, String, i64)> = keys
+      .into_iter()
+      .filter_map(|key| self.pending.remove(&key))
+      .map(|((workspace_id, publish_name), views)| (workspace_id, publish_name, views))
+      .collect();
+    if items.is_empty() {
+      return Ok(());
+    }
+
+    if let Err(err) =
+      upsert_published_view_stats_bulk(pg_pool, Utc::now().date_naive(), &items).await
+    {
+      // Keep the views for the next flush instead of losing them.
+      for (workspace_id, publish_name, views) in items {
+        *self
+          .pending
+          .entry((workspace_id, publish_name))
+          .or_default() += views;
+      }
+      return Err(err);
+    }
+    Ok(())
+  }
+}
+
+pub async fn start_publish_view_counter_flush_task(
+  counter: Arc<PublishViewCounter>,
+  pg_pool: PgPool,
+) {
+  let mut timer = tokio::time::interval(Duration::from_secs(
+    PUBLISH_VIEW_COUNTER_FLUSH_INTERVAL_SECS,
+  ));
+  loop {
+    timer.tick().await;
+    if let Err(err) = counter.flush(&pg_pool).await {
+      error!("failed to flush published view counters: {:?}", err);
+    }
+  }
+}
+
+/// Sends the publisher of every published view a weekly summary of its views, reactions and
+/// new comments. Weeks without any activity are skipped silently, and publishers can opt out
+/// per view with [stats_digest_enabled](database_entity::dto::PatchPublishedCollab).
+pub async fn start_published_view_stats_digest_task(pg_pool: PgPool) {
+  let mut timer = tokio::time::interval(Duration::from_secs(
+    PUBLISHED_VIEW_STATS_DIGEST_INTERVAL_SECS,
+  ));
+  loop {
+    timer.tick().await;
+    if let Err(err) = send_published_view_stats_digest(&pg_pool).await {
+      error!("failed to send published view stats digest: {:?}", err);
+    }
+  }
+}
+
+async fn send_published_view_stats_digest(pg_pool: &PgPool) -> Result<(), AppError> {
+  let now = Utc::now();
+  let period_start = now - chrono::Duration::days(PUBLISHED_VIEW_STATS_PERIOD_DAYS);
+  for stats in
+    select_published_view_stats_due(pg_pool, period_start, MAX_STATS_DIGESTS_PER_RUN).await?
+  {
+    if stats.views > 0 || stats.reactions > 0 || stats.comments > 0 {
+      let payload = serde_json::json!({
+        "view_id": stats.view_id,
+        "publish_name": stats.publish_name,
+        "views": stats.views,
+        "reactions": stats.reactions,
+        "comments": stats.comments,
+        "since": stats.since.timestamp(),
+      });
+      if let Err(err) = create_workspace_notification(
+        pg_pool,
+        &stats.workspace_id,
+        PUBLISHED_VIEW_STATS_NOTIFICATION,
+        &payload,
+        Some(stats.published_by),
+      )
+      .await
+      {
+        warn!(
+          "failed to send stats digest of published view {}: {:?}",
+          stats.view_id, err
+        );
+        continue;
+      }
+    }
+    upsert_published_view_stats_digest_sent_at(pg_pool, &stats.workspace_id, &stats.view_id, now)
+      .await?;
+  }
+  Ok(())
+}
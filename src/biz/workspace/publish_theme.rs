@@ -0,0 +1,322 @@
+use std::sync::LazyLock;
+
+use app_error::AppError;
+use database::publish::{
+  select_publish_namespace_theme, select_published_collab_theme, select_published_view_themes,
+  update_publish_namespace_theme, update_published_collab_theme,
+};
+use fancy_regex::Regex;
+use reqwest::Url;
+use shared_entity::dto::publish_dto::PublishTheme;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::publish::check_workspace_owner_or_publisher;
+
+const MIN_FONT_SCALE: f32 = 0.5;
+const MAX_FONT_SCALE: f32 = 2.0;
+const MAX_HEADER_IMAGE_URL_LENGTH: usize = 2048;
+const MAX_FOOTER_HTML_LENGTH: usize = 4096;
+/// Tags kept in the footer html. Every attribute is dropped, except for the `href` of links.
+const FOOTER_HTML_TAGS: [&str; 10] = [
+  "a", "b", "br", "em", "i", "p", "small", "span", "strong", "u",
+];
+const VOID_FOOTER_HTML_TAGS: [&str; 1] = ["br"];
+
+static ACCENT_COLOR_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^#([0-9a-fA-F]{3}|[0-9a-fA-F]{6})$").unwrap());
+static HTML_TAG_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"<(/?)([a-zA-Z][a-zA-Z0-9]*)([^<>]*)>").unwrap());
+static HREF_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r#"(?i)(?:^|\s)href\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+))"#).unwrap()
+});
+
+pub async fn set_publish_namespace_theme(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  namespace: &str,
+  theme: PublishTheme,
+) -> Result<PublishTheme, AppError> {
+  let theme = validate_publish_theme(theme)?;
+  if !update_publish_namespace_theme(pg_pool, workspace_id, namespace, Some(&theme)).await? {
+    return Err(publish_namespace_not_found(workspace_id, namespace));
+  }
+  Ok(theme)
+}
+
+pub async fn remove_publish_namespace_theme(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  namespace: &str,
+) -> Result<(), AppError> {
+  if !update_publish_namespace_theme(pg_pool, workspace_id, namespace, None).await? {
+    return Err(publish_namespace_not_found(workspace_id, namespace));
+  }
+  Ok(())
+}
+
+pub async fn get_publish_namespace_theme(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  namespace: &str,
+) -> Result<PublishTheme, AppError> {
+  select_publish_namespace_theme(pg_pool, workspace_id, namespace)
+    .await?
+    .ok_or_else(|| publish_namespace_not_found(workspace_id, namespace))
+}
+
+pub async fn set_published_view_theme(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  theme: PublishTheme,
+) -> Result<PublishTheme, AppError> {
+  let theme = validate_publish_theme(theme)?;
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  if !update_published_collab_theme(pg_pool, workspace_id, view_id, Some(&theme)).await? {
+    return Err(published_view_not_found(view_id));
+  }
+  Ok(theme)
+}
+
+pub async fn remove_published_view_theme(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  if !update_published_collab_theme(pg_pool, workspace_id, view_id, None).await? {
+    return Err(published_view_not_found(view_id));
+  }
+  Ok(())
+}
+
+pub async fn get_published_view_theme(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<PublishTheme, AppError> {
+  select_published_collab_theme(pg_pool, workspace_id, view_id)
+    .await?
+    .ok_or_else(|| published_view_not_found(view_id))
+}
+
+/// Theme used to render a published view: the theme of the view, completed by the theme of
+/// the namespace it is accessed through.
+pub async fn get_effective_publish_theme(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<PublishTheme, AppError> {
+  let (namespace_theme, view_theme) =
+    select_published_view_themes(pg_pool, publish_namespace, publish_name)
+      .await?
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!(
+          "no view is published as {}/{}",
+          publish_namespace, publish_name
+        ))
+      })?;
+  Ok(
+    view_theme
+      .unwrap_or_default()
+      .or(namespace_theme.unwrap_or_default()),
+  )
+}
+
+fn publish_namespace_not_found(workspace_id: &Uuid, namespace: &str) -> AppError {
+  AppError::RecordNotFound(format!(
+    "publish namespace {} not found in workspace {}",
+    namespace, workspace_id
+  ))
+}
+
+fn published_view_not_found(view_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("view {} is not published", view_id))
+}
+
+/// Rejects invalid colors, font scales and header images, and sanitizes the footer html.
+/// Empty strings are treated as unset.
+fn validate_publish_theme(theme: PublishTheme) -> Result<PublishTheme, AppError> {
+  let non_empty = |value: Option<String>| {
+    value
+      .map(|value| value.trim().to_string())
+      .filter(|value| !value.is_empty())
+  };
+  let accent_color = non_empty(theme.accent_color);
+  if let Some(accent_color) = &accent_color {
+    if !ACCENT_COLOR_REGEX.is_match(accent_color).unwrap_or(false) {
+      return Err(AppError::InvalidRequest(format!(
+        "accent color must be a hex color such as #3366ff, got {}",
+        accent_color
+      )));
+    }
+  }
+  if let Some(font_scale) = theme.font_scale {
+    if !(MIN_FONT_SCALE..=MAX_FONT_SCALE).contains(&font_scale) {
+      return Err(AppError::InvalidRequest(format!(
+        "font scale must be between {} and {}",
+        MIN_FONT_SCALE, MAX_FONT_SCALE
+      )));
+    }
+  }
+  let header_image_url = non_empty(theme.header_image_url);
+  if let Some(header_image_url) = &header_image_url {
+    let is_http_url = Url::parse(header_image_url)
+      .map(|url| matches!(url.scheme(), "http" | "https"))
+      .unwrap_or(false);
+    if header_image_url.len() > MAX_HEADER_IMAGE_URL_LENGTH || !is_http_url {
+      return Err(AppError::InvalidRequest(
+        "header image must be an http(s) url".to_string(),
+      ));
+    }
+  }
+  let footer_html = non_empty(theme.footer_html);
+  if footer_html
+    .as_ref()
+    .is_some_and(|footer_html| footer_html.len() > MAX_FOOTER_HTML_LENGTH)
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "footer html must not exceed {} bytes",
+      MAX_FOOTER_HTML_LENGTH
+    )));
+  }
+  Ok(PublishTheme {
+    accent_color,
+    font_scale: theme.font_scale,
+    header_image_url,
+    footer_html: footer_html.map(|footer_html| sanitize_footer_html(&footer_html)),
+  })
+}
+
+/// Rebuilds the html from its text and allowed tags only. Text is escaped, attributes are
+/// dropped except for http(s) and mailto links, and tags are balanced so the footer cannot
+/// leak into the rest of the page.
+fn sanitize_footer_html(html: &str) -> String {
+  let mut sanitized = String::with_capacity(html.len());
+  let mut open_tags: Vec<&str> = vec![];
+  let mut last_end = 0;
+  for captures in HTML_TAG_REGEX.captures_iter(html).flatten() {
+    let (whole, name) = match (captures.get(0), captures.get(2)) {
+      (Some(whole), Some(name)) => (whole, name.as_str().to_lowercase()),
+      _ => continue,
+    };
+    push_escaped_text(&mut sanitized, &html[last_end..whole.start()]);
+    last_end = whole.end();
+    let tag = match FOOTER_HTML_TAGS.iter().find(|tag| **tag == name) {
+      Some(tag) => *tag,
+      None => continue,
+    };
+    let is_closing = captures
+      .get(1)
+      .is_some_and(|slash| !slash.as_str().is_empty());
+    if VOID_FOOTER_HTML_TAGS.contains(&tag) {
+      if !is_closing {
+        sanitized.push_str(&format!("<{}>", tag));
+      }
+    } else if is_closing {
+      if let Some(position) = open_tags.iter().rposition(|open_tag| *open_tag == tag) {
+        for open_tag in open_tags.drain(position..).rev() {
+          sanitized.push_str(&format!("</{}>", open_tag));
+        }
+      }
+    } else {
+      let attributes = captures.get(3).map(|m| m.as_str()).unwrap_or_default();
+      match footer_link_href(tag, attributes) {
+        Some(href) => sanitized.push_str(&format!(
+          r#"<a href="{}" rel="nofollow noopener noreferrer" target="_blank">"#,
+          html_escape::encode_double_quoted_attribute(&href)
+        )),
+        None => sanitized.push_str(&format!("<{}>", tag)),
+      }
+      open_tags.push(tag);
+    }
+  }
+  push_escaped_text(&mut sanitized, &html[last_end..]);
+  for open_tag in open_tags.into_iter().rev() {
+    sanitized.push_str(&format!("</{}>", open_tag));
+  }
+  sanitized
+}
+
+fn push_escaped_text(sanitized: &mut String, text: &str) {
+  let text = html_escape::decode_html_entities(text);
+  sanitized.push_str(&html_escape::encode_text(&text));
+}
+
+fn footer_link_href(tag: &str, attributes: &str) -> Option<String> {
+  if tag != "a" {
+    return None;
+  }
+  let captures = HREF_REGEX.captures(attributes).ok()??;
+  let href = (1..=3).find_map(|index| captures.get(index))?.as_str();
+  let href = html_escape::decode_html_entities(href).trim().to_string();
+  let url = Url::parse(&href).ok()?;
+  matches!(url.scheme(), "http" | "https" | "mailto").then(|| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sanitize_footer_html_keeps_formatting_and_safe_links() {
+    assert_eq!(
+      sanitize_footer_html(
+        r#"<p class="x" onclick="alert(1)">Made by <a href='https://example.com/?a=1&amp;b=2' style="color:red">us</a> &copy; 2026<br/></p>"#
+      ),
+      r#"<p>Made by <a href="https://example.com/?a=1&amp;b=2" rel="nofollow noopener noreferrer" target="_blank">us</a> © 2026<br></p>"#
+    );
+  }
+
+  #[test]
+  fn sanitize_footer_html_removes_scripts_and_unsafe_links() {
+    assert_eq!(
+      sanitize_footer_html(
+        r#"<script>alert("x")</script><a href="javascript:alert(1)">link</a><img src=x onerror=alert(1)>"#
+      ),
+      r#"alert("x")<a>link</a>"#
+    );
+    assert_eq!(
+      sanitize_footer_html("<b><i>bold</b> 1 < 2 </div>"),
+      "<b><i>bold</i></b> 1 &lt; 2 "
+    );
+    assert_eq!(
+      sanitize_footer_html("<strong>open"),
+      "<strong>open</strong>"
+    );
+  }
+
+  #[test]
+  fn validate_publish_theme_fields() {
+    let theme = validate_publish_theme(PublishTheme {
+      accent_color: Some(" #3366FF ".to_string()),
+      font_scale: Some(1.25),
+      header_image_url: Some(String::new()),
+      footer_html: None,
+    })
+    .unwrap();
+    assert_eq!(theme.accent_color.as_deref(), Some("#3366FF"));
+    assert_eq!(theme.header_image_url, None);
+
+    let invalid_themes = [
+      PublishTheme {
+        accent_color: Some("red".to_string()),
+        ..Default::default()
+      },
+      PublishTheme {
+        font_scale: Some(3.0),
+        ..Default::default()
+      },
+      PublishTheme {
+        header_image_url: Some("javascript:alert(1)".to_string()),
+        ..Default::default()
+      },
+    ];
+    for theme in invalid_themes {
+      assert!(validate_publish_theme(theme).is_err());
+    }
+  }
+}
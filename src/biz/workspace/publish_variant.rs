@@ -0,0 +1,232 @@
+use app_error::AppError;
+use database::publish::{
+  delete_published_view_variant, select_published_view_variants,
+  select_published_view_variants_by_publish_name, select_published_views_with_publisher,
+  select_view_is_in_variant_group, upsert_published_view_variant, AFPublishedViewVariantRow,
+};
+use shared_entity::dto::publish_dto::PublishedViewVariant;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::publish::check_workspace_owner_or_publisher;
+
+const MAX_ACCEPTED_LANGUAGES: usize = 8;
+
+/// Serves `variant_view_id` under the publish name of `view_id` when `lang` is requested. Both
+/// views must be published, and a view can only be the variant of one view.
+pub async fn set_published_view_variant(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  lang: &str,
+  variant_view_id: &Uuid,
+) -> Result<PublishedViewVariant, AppError> {
+  let lang = normalize_lang(lang)
+    .ok_or_else(|| AppError::InvalidRequest(format!("invalid language tag: {}", lang)))?;
+  if view_id == variant_view_id {
+    return Err(AppError::InvalidRequest(
+      "a view cannot be a variant of itself".to_string(),
+    ));
+  }
+  check_workspace_owner_or_publisher(
+    pg_pool,
+    user_uuid,
+    workspace_id,
+    &[*view_id, *variant_view_id],
+  )
+  .await?;
+  let published =
+    select_published_views_with_publisher(pg_pool, workspace_id, &[*view_id, *variant_view_id])
+      .await?;
+  let is_published = |id: &Uuid| {
+    published
+      .iter()
+      .any(|(published_id, _, _)| published_id == id)
+  };
+  if !is_published(view_id) || !is_published(variant_view_id) {
+    return Err(AppError::RecordNotFound(
+      "both the view and its variant must be published".to_string(),
+    ));
+  }
+  let publish_name = published
+    .iter()
+    .find(|(id, _, _)| id == variant_view_id)
+    .map(|(_, _, publish_name)| publish_name.clone())
+    .unwrap_or_default();
+
+  // Variants are not nested: the primary view is not a variant, and the variant has none.
+  let (view_is_variant, _) =
+    select_view_is_in_variant_group(pg_pool, workspace_id, view_id).await?;
+  let (_, variant_has_variants) =
+    select_view_is_in_variant_group(pg_pool, workspace_id, variant_view_id).await?;
+  if view_is_variant || variant_has_variants {
+    return Err(AppError::InvalidRequest(
+      "language variants cannot be nested".to_string(),
+    ));
+  }
+
+  upsert_published_view_variant(pg_pool, workspace_id, view_id, &lang, variant_view_id, uid)
+    .await?;
+  Ok(PublishedViewVariant {
+    lang,
+    view_id: *variant_view_id,
+    publish_name,
+  })
+}
+
+pub async fn remove_published_view_variant(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  lang: &str,
+) -> Result<(), AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  let lang = normalize_lang(lang).unwrap_or_default();
+  if !delete_published_view_variant(pg_pool, workspace_id, view_id, &lang).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "view {} has no {} variant",
+      view_id, lang
+    )));
+  }
+  Ok(())
+}
+
+pub async fn list_published_view_variants(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PublishedViewVariant>, AppError> {
+  let rows = select_published_view_variants(pg_pool, workspace_id, view_id).await?;
+  Ok(rows.into_iter().map(to_dto_variant).collect())
+}
+
+/// The publish name to serve for `publish_name`, given the language explicitly requested
+/// with `?lang=`, which takes precedence, and the `Accept-Language` header. Falls back to
+/// `publish_name` itself when no variant matches the most preferred language.
+pub async fn negotiate_publish_name(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+  lang: Option<&str>,
+  accept_language: Option<&str>,
+) -> Result<String, AppError> {
+  let mut languages: Vec<String> = lang.and_then(normalize_lang).into_iter().collect();
+  languages.extend(
+    accept_language
+      .map(parse_accept_language)
+      .unwrap_or_default(),
+  );
+  if languages.is_empty() {
+    return Ok(publish_name.to_string());
+  }
+  let variants =
+    select_published_view_variants_by_publish_name(pg_pool, publish_namespace, publish_name)
+      .await?;
+  Ok(
+    match_variant(&languages, &variants)
+      .map(|variant| variant.publish_name.clone())
+      .unwrap_or_else(|| publish_name.to_string()),
+  )
+}
+
+fn to_dto_variant(row: AFPublishedViewVariantRow) -> PublishedViewVariant {
+  PublishedViewVariant {
+    lang: row.lang,
+    view_id: row.variant_view_id,
+    publish_name: row.publish_name,
+  }
+}
+
+/// Language tags are compared lowercase, with `-` as separator: `pt_BR` becomes `pt-br`.
+fn normalize_lang(lang: &str) -> Option<String> {
+  let lang = lang.trim().replace('_', "-").to_lowercase();
+  let mut subtags = lang.split('-');
+  let primary = subtags.next()?;
+  let is_valid = (2..=3).contains(&primary.len())
+    && primary.chars().all(|c| c.is_ascii_alphabetic())
+    && subtags.all(|subtag| {
+      (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+  is_valid.then_some(lang)
+}
+
+/// Languages of the header, most preferred first. Wildcards and languages with a zero
+/// quality are dropped.
+fn parse_accept_language(header: &str) -> Vec<String> {
+  let mut languages: Vec<(String, f32)> = header
+    .split(',')
+    .filter_map(|item| {
+      let mut parts = item.split(';');
+      let lang = normalize_lang(parts.next()?)?;
+      let quality = parts
+        .find_map(|param| param.trim().strip_prefix("q="))
+        .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+        .unwrap_or(1.0);
+      (quality > 0.0).then_some((lang, quality))
+    })
+    .take(MAX_ACCEPTED_LANGUAGES)
+    .collect();
+  // Stable sort keeps the order of the header for equal qualities
+  languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+  languages.into_iter().map(|(lang, _)| lang).collect()
+}
+
+/// Only the most preferred language is matched: the primary view stands for every language
+/// without a variant, so it wins over variants of languages the client prefers less. An exact
+/// match wins over a match on the primary language, so that `fr-ca` is served the `fr`
+/// variant, and `fr` the `fr-fr` one.
+fn match_variant<'a>(
+  languages: &[String],
+  variants: &'a [AFPublishedViewVariantRow],
+) -> Option<&'a AFPublishedViewVariantRow> {
+  let primary_lang = |lang: &str| lang.split('-').next().unwrap_or_default().to_string();
+  let lang = languages.first()?;
+  variants
+    .iter()
+    .find(|variant| &variant.lang == lang)
+    .or_else(|| {
+      variants
+        .iter()
+        .find(|variant| primary_lang(&variant.lang) == primary_lang(lang))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn variant(lang: &str) -> AFPublishedViewVariantRow {
+    AFPublishedViewVariantRow {
+      lang: lang.to_string(),
+      variant_view_id: Uuid::new_v4(),
+      publish_name: format!("guide-{}", lang),
+    }
+  }
+
+  #[test]
+  fn negotiate_variant_language() {
+    assert_eq!(normalize_lang(" pt_BR "), Some("pt-br".to_string()));
+    assert_eq!(normalize_lang("*"), None);
+    assert_eq!(
+      parse_accept_language("fr-CA;q=0.8, de;q=0, en-US, *;q=0.5"),
+      vec!["en-us".to_string(), "fr-ca".to_string()]
+    );
+
+    let variants = vec![variant("fr"), variant("pt-br"), variant("pt-pt")];
+    let matched = |languages: &[&str]| {
+      let languages: Vec<String> = languages.iter().map(|l| l.to_string()).collect();
+      match_variant(&languages, &variants).map(|v| v.lang.clone())
+    };
+    assert_eq!(matched(&["fr-ca"]), Some("fr".to_string()));
+    assert_eq!(matched(&["pt-pt"]), Some("pt-pt".to_string()));
+    assert_eq!(matched(&["pt", "de"]), Some("pt-br".to_string()));
+    assert_eq!(matched(&["en-us"]), None);
+    // the primary view is preferred over a less wanted variant
+    assert_eq!(matched(&["de", "pt"]), None);
+    let languages = parse_accept_language("en-US, fr;q=0.5");
+    assert!(match_variant(&languages, &variants).is_none());
+  }
+}
@@ -1,7 +1,10 @@
+use std::ops::DerefMut;
+
 use app_error::AppError;
+use chrono::{DateTime, Utc};
 use database::quick_note::{
-  delete_quick_note_by_id, insert_new_quick_note, select_quick_notes_with_one_more_than_limit,
-  update_quick_note_by_id,
+  delete_quick_note_by_id, insert_new_quick_note, lock_quick_note_creation,
+  select_quick_notes_with_one_more_than_limit, select_recent_quick_notes, update_quick_note_by_id,
 };
 use serde_json::json;
 use sqlx::PgPool;
@@ -9,11 +12,17 @@ use uuid::Uuid;
 
 use database_entity::dto::{QuickNote, QuickNotes};
 
+use crate::middleware::deadline::apply_statement_timeout;
+
+/// Notes created by the user less than this long ago are checked for duplicates.
+const QUICK_NOTE_DEDUPE_WINDOW_SECS: i64 = 60;
+
 pub async fn create_quick_note(
   pg_pool: &PgPool,
   uid: i64,
   workspace_id: Uuid,
   data: Option<&serde_json::Value>,
+  dedupe: bool,
 ) -> Result<QuickNote, AppError> {
   let default_data = json!([
     {
@@ -24,10 +33,64 @@ pub async fn create_quick_note(
     }
   ]);
   let new_data = data.unwrap_or(&default_data);
-  let quick_note = insert_new_quick_note(pg_pool, workspace_id, uid, new_data).await?;
+  let text = quick_note_text(new_data);
+  if !dedupe || text.is_empty() {
+    return insert_new_quick_note(pg_pool, workspace_id, uid, new_data).await;
+  }
+
+  let mut tx = pg_pool.begin().await?;
+  apply_statement_timeout(tx.deref_mut()).await?;
+  lock_quick_note_creation(&mut tx, workspace_id, uid).await?;
+  let recent_notes = select_recent_quick_notes(
+    tx.as_mut(),
+    workspace_id,
+    uid,
+    QUICK_NOTE_DEDUPE_WINDOW_SECS,
+  )
+  .await?;
+  // Only a retry of the same note is a duplicate. A note that extends or shortens a recent one
+  // may be a note of its own, so it is kept apart and the recent note is left untouched.
+  let duplicate = recent_notes
+    .into_iter()
+    .find(|recent_note| quick_note_text(&recent_note.data) == text);
+  let quick_note = match duplicate {
+    Some(quick_note) => quick_note,
+    None => insert_new_quick_note(tx.as_mut(), workspace_id, uid, new_data).await?,
+  };
+  tx.commit().await?;
   Ok(quick_note)
 }
 
+/// Text of the blocks of a quick note with consecutive whitespace collapsed, so that notes
+/// differing only in formatting or spacing are considered identical.
+fn quick_note_text(data: &serde_json::Value) -> String {
+  fn collect_inserts<'a>(value: &'a serde_json::Value, inserts: &mut Vec<&'a str>) {
+    match value {
+      serde_json::Value::Object(map) => {
+        for (key, value) in map {
+          match value.as_str() {
+            Some(insert) if key == "insert" => inserts.push(insert),
+            _ => collect_inserts(value, inserts),
+          }
+        }
+      },
+      serde_json::Value::Array(values) => {
+        for value in values {
+          collect_inserts(value, inserts);
+        }
+      },
+      _ => {},
+    }
+  }
+  let mut inserts = vec![];
+  collect_inserts(data, &mut inserts);
+  inserts
+    .join(" ")
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
 pub async fn update_quick_note(
   pg_pool: &PgPool,
   quick_note_id: Uuid,
@@ -40,19 +103,31 @@ pub async fn delete_quick_note(pg_pool: &PgPool, quick_note_id: Uuid) -> Result<
   delete_quick_note_by_id(pg_pool, quick_note_id).await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn list_quick_notes(
   pg_pool: &PgPool,
   uid: i64,
   workspace_id: Uuid,
   search_term: Option<String>,
+  created_after: Option<DateTime<Utc>>,
+  created_before: Option<DateTime<Utc>>,
   offset: Option<i32>,
   limit: Option<i32>,
 ) -> Result<QuickNotes, AppError> {
+  if let (Some(created_after), Some(created_before)) = (created_after, created_before) {
+    if created_after >= created_before {
+      return Err(AppError::InvalidRequest(
+        "created_after must be earlier than created_before".to_string(),
+      ));
+    }
+  }
   let mut quick_notes_with_one_more_than_limit = select_quick_notes_with_one_more_than_limit(
     pg_pool,
     workspace_id,
     uid,
     search_term,
+    created_after,
+    created_before,
     offset,
     limit,
   )
@@ -0,0 +1,785 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use app_error::AppError;
+use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
+use aws_sdk_s3::config::{Credentials, Region, SharedCredentialsProvider};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_smithy_http_client::tls;
+use aws_smithy_runtime_api::client::dns::{DnsFuture, ResolveDns};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use database::collab::{CollabStore, GetCollabOrigin};
+use database::pg_row::AFScheduledExportRow;
+use database::scheduled_export::{
+  claim_due_scheduled_exports, delete_scheduled_export, insert_scheduled_export,
+  insert_scheduled_export_audit, insert_scheduled_export_run, select_scheduled_export,
+  select_scheduled_export_runs, select_scheduled_exports, update_scheduled_export,
+  update_scheduled_export_run,
+};
+use database_entity::dto::{
+  CreateScheduledExportParams, ExportFormat, ScheduledExport, ScheduledExportDestination,
+  ScheduledExportRun, ScheduledExportRunStatus, ScheduledExportRuns, ScheduledExports,
+  UpdateScheduledExportParams,
+};
+use secrecy::{ExposeSecret, Secret};
+use sqlx::PgPool;
+use std::ops::DerefMut;
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+use tokio_util::io::ReaderStream;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::biz::workspace::markdown_export::{markdown_files, write_export_bundle, MarkdownFile};
+use crate::middleware::deadline::apply_statement_timeout;
+
+const DEFAULT_INTERVAL_DAYS: i32 = 7;
+const MAX_INTERVAL_DAYS: i32 = 90;
+const MAX_SECRET_LEN: usize = 1024;
+const DUE_EXPORTS_PER_RUN: i64 = 10;
+const DEFAULT_RUNS_LIMIT: i64 = 20;
+const MAX_RUNS_LIMIT: i64 = 100;
+const MAX_ERROR_LEN: usize = 1000;
+const UPLOAD_TIMEOUT_SECS: u64 = 300;
+const NONCE_LEN: usize = 12;
+
+/// What an entry of the audit log of scheduled exports records.
+#[repr(i16)]
+#[derive(Debug, Clone, Copy)]
+enum ScheduledExportAuditAction {
+  Created = 0,
+  DestinationChanged = 1,
+  Deleted = 2,
+  Uploaded = 3,
+}
+
+/// Everything needed to run the exports, outside of a request.
+#[derive(Clone)]
+pub struct ScheduledExportContext<C> {
+  pub pg_pool: PgPool,
+  pub collab_storage: Arc<dyn CollabStore>,
+  pub collab_instance_cache: C,
+  pub secret_key: Secret<String>,
+}
+
+pub async fn create_scheduled_export(
+  pg_pool: &PgPool,
+  secret_key: &Secret<String>,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: CreateScheduledExportParams,
+) -> Result<ScheduledExport, AppError> {
+  validate_destination(&params.destination)?;
+  check_destination_address(&params.destination).await?;
+  let interval_days =
+    validate_interval_days(params.interval_days.unwrap_or(DEFAULT_INTERVAL_DAYS))?;
+  let encrypted_secret = encrypt_secret(secret_key, &validate_secret(&params.secret)?)?;
+  let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  let export = insert_scheduled_export(
+    txn.deref_mut(),
+    workspace_id,
+    uid,
+    &params.destination,
+    &encrypted_secret,
+    interval_days,
+  )
+  .await?;
+  insert_scheduled_export_audit(
+    txn.deref_mut(),
+    workspace_id,
+    &export.export_id,
+    ScheduledExportAuditAction::Created as i16,
+    Some(uid),
+    &export.destination,
+    None,
+  )
+  .await?;
+  txn.commit().await?;
+  Ok(export)
+}
+
+pub async fn update_workspace_scheduled_export(
+  pg_pool: &PgPool,
+  secret_key: &Secret<String>,
+  uid: i64,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+  params: UpdateScheduledExportParams,
+) -> Result<ScheduledExport, AppError> {
+  if let Some(destination) = &params.destination {
+    validate_destination(destination)?;
+    check_destination_address(destination).await?;
+    if params.secret.is_none() {
+      return Err(AppError::InvalidRequest(
+        "the secret of the new destination is required".to_string(),
+      ));
+    }
+  }
+  let interval_days = params
+    .interval_days
+    .map(validate_interval_days)
+    .transpose()?;
+  let encrypted_secret = params
+    .secret
+    .as_deref()
+    .map(|secret| encrypt_secret(secret_key, &validate_secret(secret)?))
+    .transpose()?;
+  let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  let export = update_scheduled_export(
+    txn.deref_mut(),
+    workspace_id,
+    export_id,
+    params.destination.as_ref(),
+    encrypted_secret.as_deref(),
+    interval_days,
+    params.enabled,
+  )
+  .await?
+  .ok_or_else(|| AppError::RecordNotFound(format!("scheduled export {} not found", export_id)))?;
+  if params.destination.is_some() {
+    insert_scheduled_export_audit(
+      txn.deref_mut(),
+      workspace_id,
+      export_id,
+      ScheduledExportAuditAction::DestinationChanged as i16,
+      Some(uid),
+      &export.destination,
+      None,
+    )
+    .await?;
+  }
+  txn.commit().await?;
+  Ok(export)
+}
+
+pub async fn delete_workspace_scheduled_export(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  let destination = delete_scheduled_export(txn.deref_mut(), workspace_id, export_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("scheduled export {} not found", export_id)))?;
+  insert_scheduled_export_audit(
+    txn.deref_mut(),
+    workspace_id,
+    export_id,
+    ScheduledExportAuditAction::Deleted as i16,
+    Some(uid),
+    &destination,
+    None,
+  )
+  .await?;
+  txn.commit().await?;
+  Ok(())
+}
+
+pub async fn list_scheduled_exports(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<ScheduledExports, AppError> {
+  let exports = select_scheduled_exports(pg_pool, workspace_id).await?;
+  Ok(ScheduledExports { exports })
+}
+
+pub async fn list_scheduled_export_runs(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+  limit: Option<i64>,
+) -> Result<ScheduledExportRuns, AppError> {
+  let limit = limit.unwrap_or(DEFAULT_RUNS_LIMIT).clamp(1, MAX_RUNS_LIMIT);
+  let runs = select_scheduled_export_runs(pg_pool, workspace_id, export_id, limit).await?;
+  Ok(ScheduledExportRuns { runs })
+}
+
+/// Starts a run of the export right away, independently of its schedule. The returned run is
+/// still running; its outcome shows up in the history of the export.
+pub async fn run_scheduled_export_now<C>(
+  context: ScheduledExportContext<C>,
+  workspace_id: &Uuid,
+  export_id: &Uuid,
+) -> Result<ScheduledExportRun, AppError>
+where
+  C: WorkspaceCollabInstanceCache + Clone + Send + Sync + 'static,
+{
+  let export = select_scheduled_export(&context.pg_pool, workspace_id, export_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("scheduled export {} not found", export_id)))?;
+  let run_id = insert_scheduled_export_run(&context.pg_pool, workspace_id, export_id).await?;
+  let run = ScheduledExportRun {
+    run_id,
+    export_id: *export_id,
+    started_at: Utc::now(),
+    finished_at: None,
+    status: ScheduledExportRunStatus::Running,
+    documents: 0,
+    bytes: 0,
+    object_key: None,
+    error: None,
+  };
+  tokio::spawn(async move {
+    execute_scheduled_export_run(&context, export, run_id).await;
+  });
+  Ok(run)
+}
+
+pub async fn start_scheduled_export_task<C>(
+  context: ScheduledExportContext<C>,
+  check_interval_secs: u64,
+) where
+  C: WorkspaceCollabInstanceCache + Clone + Send + Sync + 'static,
+{
+  let mut timer = tokio::time::interval(Duration::from_secs(check_interval_secs.max(60)));
+  loop {
+    timer.tick().await;
+    if let Err(err) = run_due_scheduled_exports(&context).await {
+      error!("scheduled export task failed: {:?}", err);
+    }
+  }
+}
+
+async fn run_due_scheduled_exports<C>(context: &ScheduledExportContext<C>) -> Result<(), AppError>
+where
+  C: WorkspaceCollabInstanceCache,
+{
+  let exports = claim_due_scheduled_exports(&context.pg_pool, DUE_EXPORTS_PER_RUN).await?;
+  for export in exports {
+    let run_id =
+      insert_scheduled_export_run(&context.pg_pool, &export.workspace_id, &export.export_id)
+        .await?;
+    execute_scheduled_export_run(context, export, run_id).await;
+  }
+  Ok(())
+}
+
+/// Exports the workspace and records the outcome in the run.
+async fn execute_scheduled_export_run<C>(
+  context: &ScheduledExportContext<C>,
+  export: AFScheduledExportRow,
+  run_id: Uuid,
+) where
+  C: WorkspaceCollabInstanceCache,
+{
+  let result = export_workspace(context, &export).await;
+  let update = match &result {
+    Ok(upload) => {
+      info!(
+        "scheduled export {} of workspace {}: uploaded {} documents to {}",
+        export.export_id, export.workspace_id, upload.documents, upload.object_key
+      );
+      update_scheduled_export_run(
+        &context.pg_pool,
+        &run_id,
+        ScheduledExportRunStatus::Succeeded as i16,
+        Utc::now(),
+        upload.documents,
+        upload.bytes,
+        Some(&upload.object_key),
+        None,
+      )
+      .await
+    },
+    Err(err) => {
+      warn!(
+        "scheduled export {} of workspace {} failed: {}",
+        export.export_id, export.workspace_id, err
+      );
+      let message: String = err.to_string().chars().take(MAX_ERROR_LEN).collect();
+      update_scheduled_export_run(
+        &context.pg_pool,
+        &run_id,
+        ScheduledExportRunStatus::Failed as i16,
+        Utc::now(),
+        0,
+        0,
+        None,
+        Some(&message),
+      )
+      .await
+    },
+  };
+  if let Err(err) = update {
+    error!(
+      "failed to record scheduled export run {}: {:?}",
+      run_id, err
+    );
+  }
+}
+
+struct UploadedBundle {
+  documents: i32,
+  bytes: i64,
+  object_key: String,
+}
+
+async fn export_workspace<C>(
+  context: &ScheduledExportContext<C>,
+  export: &AFScheduledExportRow,
+) -> Result<UploadedBundle, AppError>
+where
+  C: WorkspaceCollabInstanceCache,
+{
+  // The export sees the workspace as the owner who set it up
+  let uid = export.created_by.ok_or_else(|| {
+    AppError::InvalidRequest("the creator of the scheduled export no longer exists".to_string())
+  })?;
+  let secret = decrypt_secret(&context.secret_key, &export.encrypted_secret)?;
+  let folder = context
+    .collab_instance_cache
+    .get_folder(export.workspace_id)
+    .await?;
+  let files = markdown_files(&folder, &export.workspace_id, uid)?;
+  drop(folder);
+
+  // The bundle is written to a temporary file and streamed from there, so that large
+  // workspaces are never held in memory
+  let path = std::env::temp_dir().join(format!("scheduled_export_{}.zip", Uuid::new_v4()));
+  let result = export_to_file(context, export, uid, secret, files, &path).await;
+  if let Err(err) = tokio::fs::remove_file(&path).await {
+    if err.kind() != std::io::ErrorKind::NotFound {
+      warn!("failed to remove scheduled export file {:?}: {}", path, err);
+    }
+  }
+  result
+}
+
+async fn export_to_file<C>(
+  context: &ScheduledExportContext<C>,
+  export: &AFScheduledExportRow,
+  uid: i64,
+  secret: String,
+  files: Vec<MarkdownFile>,
+  path: &Path,
+) -> Result<UploadedBundle, AppError>
+where
+  C: WorkspaceCollabInstanceCache,
+{
+  let file = tokio::fs::File::create(path).await?.compat_write();
+  let (file, documents) = write_export_bundle(
+    file,
+    &context.collab_storage,
+    GetCollabOrigin::User { uid },
+    export.workspace_id,
+    files,
+    ExportFormat::Markdown,
+  )
+  .await?;
+  let file = file.into_inner();
+  file.sync_all().await?;
+  drop(file);
+  let bytes = tokio::fs::metadata(path).await?.len() as i64;
+
+  let file_name = format!(
+    "{}-{}.zip",
+    export.workspace_id,
+    Utc::now().format("%Y%m%d-%H%M%S")
+  );
+  let (object_key, address) =
+    upload_bundle(&export.destination, &secret, file_name, path, bytes).await?;
+  insert_scheduled_export_audit(
+    &context.pg_pool,
+    &export.workspace_id,
+    &export.export_id,
+    ScheduledExportAuditAction::Uploaded as i16,
+    Some(uid),
+    &export.destination,
+    address.map(|address| address.to_string()).as_deref(),
+  )
+  .await?;
+  Ok(UploadedBundle {
+    documents,
+    bytes,
+    object_key,
+  })
+}
+
+/// Uploads the bundle at `path`. Returns the key of the bundle in the destination and the
+/// address that was connected to, when the destination is not the default S3 endpoint.
+async fn upload_bundle(
+  destination: &ScheduledExportDestination,
+  secret: &str,
+  file_name: String,
+  path: &Path,
+  bytes: i64,
+) -> Result<(String, Option<SocketAddr>), AppError> {
+  match destination {
+    ScheduledExportDestination::S3 {
+      endpoint,
+      region,
+      bucket,
+      prefix,
+      access_key_id,
+    } => {
+      // The address is checked again right before connecting, since the name may resolve to
+      // something else than when the destination was saved
+      let address = match endpoint {
+        Some(endpoint) => Some(resolve_public_address(endpoint).await?.1),
+        None => None,
+      };
+      let credentials = Credentials::new(access_key_id, secret, None, None, "scheduled_export");
+      let config_builder = aws_sdk_s3::Config::builder()
+        .credentials_provider(SharedCredentialsProvider::new(credentials))
+        .force_path_style(true)
+        .region(Region::new(region.clone()));
+      let config = match (endpoint, address) {
+        // The connection is pinned to the checked address
+        (Some(endpoint), Some(address)) => config_builder
+          .endpoint_url(endpoint)
+          .http_client(
+            aws_smithy_http_client::Builder::new()
+              .tls_provider(tls::Provider::Rustls(
+                tls::rustls_provider::CryptoMode::AwsLc,
+              ))
+              .build_with_resolver(PinnedResolver(address.ip())),
+          )
+          .build(),
+        _ => config_builder.build(),
+      };
+      let body = ByteStream::from_path(path)
+        .await
+        .map_err(|err| AppError::Internal(err.into()))?;
+      let key = format!("{}{}", prefix, file_name);
+      aws_sdk_s3::Client::from_conf(config)
+        .put_object()
+        .bucket(bucket)
+        .key(&key)
+        .content_type("application/zip")
+        .content_length(bytes)
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| {
+          AppError::Internal(anyhow::anyhow!("failed to upload to the bucket: {}", err))
+        })?;
+      Ok((key, address))
+    },
+    ScheduledExportDestination::WebDav { url, username } => {
+      let (host, address) = resolve_public_address(url).await?;
+      // The connection is pinned to the checked address, and redirects are not followed since
+      // they could lead anywhere
+      let client = reqwest::Client::builder()
+        .resolve(&host, address)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|err| AppError::Internal(err.into()))?;
+      let file = tokio::fs::File::open(path).await?;
+      let url = format!("{}/{}", url.trim_end_matches('/'), file_name);
+      let response = client
+        .put(&url)
+        .basic_auth(username, Some(secret))
+        .header(reqwest::header::CONTENT_TYPE, "application/zip")
+        .header(reqwest::header::CONTENT_LENGTH, bytes)
+        .timeout(Duration::from_secs(UPLOAD_TIMEOUT_SECS))
+        .body(reqwest::Body::wrap_stream(ReaderStream::new(file)))
+        .send()
+        .await
+        .map_err(|err| AppError::Internal(anyhow::anyhow!("failed to upload: {}", err)))?;
+      if !response.status().is_success() {
+        return Err(AppError::Internal(anyhow::anyhow!(
+          "the WebDAV server answered {}",
+          response.status()
+        )));
+      }
+      Ok((url, Some(address)))
+    },
+  }
+}
+
+/// Fails when the host of the destination resolves to an address that is not public, so that
+/// exports cannot reach the internal network of the server.
+async fn check_destination_address(
+  destination: &ScheduledExportDestination,
+) -> Result<(), AppError> {
+  match destination {
+    ScheduledExportDestination::S3 {
+      endpoint: Some(endpoint),
+      ..
+    } => resolve_public_address(endpoint).await.map(|_| ()),
+    // The default endpoint is derived from the region, which is validated
+    ScheduledExportDestination::S3 { endpoint: None, .. } => Ok(()),
+    ScheduledExportDestination::WebDav { url, .. } => resolve_public_address(url).await.map(|_| ()),
+  }
+}
+
+/// Resolves the host of `url`. Returns the host and the first address it resolves to, or an
+/// error when any of its addresses is not public.
+async fn resolve_public_address(url: &str) -> Result<(String, SocketAddr), AppError> {
+  let parsed = reqwest::Url::parse(url)?;
+  let host = parsed
+    .host_str()
+    .ok_or_else(|| AppError::InvalidRequest(format!("{} has no host", url)))?
+    .to_string();
+  let port = parsed
+    .port_or_known_default()
+    .ok_or_else(|| AppError::InvalidRequest(format!("{} has no port", url)))?;
+  let addresses: Vec<SocketAddr> = match host
+    .trim_start_matches('[')
+    .trim_end_matches(']')
+    .parse::<IpAddr>()
+  {
+    Ok(ip) => vec![SocketAddr::new(ip, port)],
+    Err(_) => tokio::net::lookup_host((host.as_str(), port))
+      .await
+      .map_err(|err| AppError::InvalidRequest(format!("cannot resolve {}: {}", host, err)))?
+      .collect(),
+  };
+  if addresses.is_empty() || addresses.iter().any(|address| !is_public_ip(address.ip())) {
+    return Err(AppError::InvalidRequest(format!(
+      "{} does not resolve to a public address",
+      host
+    )));
+  }
+  Ok((host, addresses[0]))
+}
+
+/// Resolves every name to the address that was checked, so that a name resolving to something
+/// else by the time the S3 client connects can't lead it to an internal address.
+#[derive(Debug, Clone)]
+struct PinnedResolver(IpAddr);
+
+impl ResolveDns for PinnedResolver {
+  fn resolve_dns<'a>(&'a self, _name: &'a str) -> DnsFuture<'a> {
+    DnsFuture::ready(Ok(vec![self.0]))
+  }
+}
+
+/// Loopback, private, link-local (which includes the cloud metadata endpoints), shared,
+/// multicast and unspecified addresses are not public.
+fn is_public_ip(ip: IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => is_public_ipv4(ip),
+    IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+      Some(ip) => is_public_ipv4(ip),
+      None => is_public_ipv6(ip),
+    },
+  }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+  let octets = ip.octets();
+  !(ip.is_loopback()
+    || ip.is_private()
+    || ip.is_link_local()
+    || ip.is_unspecified()
+    || ip.is_broadcast()
+    || ip.is_multicast()
+    || ip.is_documentation()
+    || octets[0] == 0
+    || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+  let first_segment = ip.segments()[0];
+  !(ip.is_loopback()
+    || ip.is_unspecified()
+    || ip.is_multicast()
+    || (first_segment & 0xfe00) == 0xfc00
+    || (first_segment & 0xffc0) == 0xfe80)
+}
+
+fn validate_destination(destination: &ScheduledExportDestination) -> Result<(), AppError> {
+  match destination {
+    ScheduledExportDestination::S3 {
+      endpoint,
+      region,
+      bucket,
+      access_key_id,
+      ..
+    } => {
+      if let Some(endpoint) = endpoint {
+        validate_http_url(endpoint)?;
+      }
+      if region.trim().is_empty() || bucket.trim().is_empty() || access_key_id.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+          "region, bucket and access key id of the destination are required".to_string(),
+        ));
+      }
+      // Without an endpoint the host is derived from the region
+      if !region
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+      {
+        return Err(AppError::InvalidRequest(format!(
+          "{} is not a valid region",
+          region
+        )));
+      }
+    },
+    ScheduledExportDestination::WebDav { url, username } => {
+      validate_http_url(url)?;
+      if username.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+          "username of the destination is required".to_string(),
+        ));
+      }
+    },
+  }
+  Ok(())
+}
+
+fn validate_http_url(url: &str) -> Result<(), AppError> {
+  let parsed = reqwest::Url::parse(url)?;
+  if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+    return Err(AppError::InvalidRequest(format!(
+      "{} is not a http(s) url",
+      url
+    )));
+  }
+  Ok(())
+}
+
+fn validate_interval_days(interval_days: i32) -> Result<i32, AppError> {
+  if !(1..=MAX_INTERVAL_DAYS).contains(&interval_days) {
+    return Err(AppError::InvalidRequest(format!(
+      "interval must be between 1 and {} days",
+      MAX_INTERVAL_DAYS
+    )));
+  }
+  Ok(interval_days)
+}
+
+fn validate_secret(secret: &str) -> Result<String, AppError> {
+  if secret.is_empty() || secret.len() > MAX_SECRET_LEN {
+    return Err(AppError::InvalidRequest(
+      "secret of the destination is missing or too long".to_string(),
+    ));
+  }
+  Ok(secret.to_string())
+}
+
+fn cipher(secret_key: &Secret<String>) -> Result<Aes256Gcm, AppError> {
+  let key = STANDARD
+    .decode(secret_key.expose_secret())
+    .ok()
+    .filter(|key| !key.is_empty())
+    .ok_or_else(|| {
+      AppError::FeatureNotAvailable("scheduled exports are not configured".to_string())
+    })?;
+  Aes256Gcm::new_from_slice(&key).map_err(|_| {
+    AppError::Internal(anyhow::anyhow!(
+      "the scheduled export secret key must be 32 bytes"
+    ))
+  })
+}
+
+/// Returns the random nonce followed by the ciphertext.
+fn encrypt_secret(secret_key: &Secret<String>, secret: &str) -> Result<Vec<u8>, AppError> {
+  let cipher = cipher(secret_key)?;
+  let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+  let ciphertext = cipher
+    .encrypt(&nonce, secret.as_bytes())
+    .map_err(|_| AppError::Internal(anyhow::anyhow!("failed to encrypt the secret")))?;
+  let mut encrypted = nonce.to_vec();
+  encrypted.extend(ciphertext);
+  Ok(encrypted)
+}
+
+fn decrypt_secret(secret_key: &Secret<String>, encrypted: &[u8]) -> Result<String, AppError> {
+  if encrypted.len() <= NONCE_LEN {
+    return Err(AppError::Internal(anyhow::anyhow!(
+      "the encrypted secret is malformed"
+    )));
+  }
+  let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+  let secret = cipher(secret_key)?
+    .decrypt(Nonce::from_slice(nonce), ciphertext)
+    .map_err(|_| AppError::Internal(anyhow::anyhow!("failed to decrypt the secret")))?;
+  String::from_utf8(secret).map_err(|err| AppError::Internal(err.into()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn secret_round_trip() {
+    let secret_key = Secret::new(STANDARD.encode([7u8; 32]));
+    let encrypted = encrypt_secret(&secret_key, "s3cr3t").unwrap();
+    assert_ne!(&encrypted[NONCE_LEN..], b"s3cr3t");
+    assert_eq!(decrypt_secret(&secret_key, &encrypted).unwrap(), "s3cr3t");
+
+    let other_key = Secret::new(STANDARD.encode([8u8; 32]));
+    assert!(decrypt_secret(&other_key, &encrypted).is_err());
+    assert!(encrypt_secret(&Secret::new(String::new()), "s3cr3t").is_err());
+  }
+
+  #[test]
+  fn destination_validation() {
+    assert!(validate_destination(&ScheduledExportDestination::WebDav {
+      url: "https://dav.example.com/exports".to_string(),
+      username: "me".to_string(),
+    })
+    .is_ok());
+    assert!(validate_destination(&ScheduledExportDestination::WebDav {
+      url: "ftp://dav.example.com".to_string(),
+      username: "me".to_string(),
+    })
+    .is_err());
+    assert!(validate_destination(&ScheduledExportDestination::S3 {
+      endpoint: None,
+      region: "us-east-1".to_string(),
+      bucket: "".to_string(),
+      prefix: "".to_string(),
+      access_key_id: "key".to_string(),
+    })
+    .is_err());
+    assert!(validate_destination(&ScheduledExportDestination::S3 {
+      endpoint: None,
+      region: "evil.example.com/".to_string(),
+      bucket: "bucket".to_string(),
+      prefix: "".to_string(),
+      access_key_id: "key".to_string(),
+    })
+    .is_err());
+  }
+
+  #[test]
+  fn internal_addresses_are_not_public() {
+    for ip in [
+      "127.0.0.1",
+      "10.1.2.3",
+      "172.16.0.1",
+      "192.168.1.1",
+      "169.254.169.254",
+      "100.64.0.1",
+      "0.0.0.0",
+      "::1",
+      "fd00:ec2::254",
+      "fe80::1",
+      "::ffff:127.0.0.1",
+    ] {
+      assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+    }
+    for ip in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+      assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+    }
+  }
+
+  #[tokio::test]
+  async fn destination_pointing_at_metadata_endpoint_is_rejected() {
+    let destination = ScheduledExportDestination::WebDav {
+      url: "http://169.254.169.254/latest".to_string(),
+      username: "me".to_string(),
+    };
+    assert!(check_destination_address(&destination).await.is_err());
+    let destination = ScheduledExportDestination::S3 {
+      endpoint: Some("http://[::1]:9000".to_string()),
+      region: "us-east-1".to_string(),
+      bucket: "bucket".to_string(),
+      prefix: "".to_string(),
+      access_key_id: "key".to_string(),
+    };
+    assert!(check_destination_address(&destination).await.is_err());
+  }
+}
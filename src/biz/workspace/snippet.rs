@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use chrono::Utc;
+use database::snippet::{
+  delete_workspace_snippet, insert_workspace_snippet, select_workspace_snippet_by_id,
+  select_workspace_snippet_by_shortcut, select_workspace_snippets, update_workspace_snippet,
+};
+use database::user::select_name_from_uid;
+use database::workspace::{select_user_is_workspace_owner, select_workspace};
+use database_entity::dto::{
+  CreateWorkspaceSnippetParams, ExpandWorkspaceSnippetParams, ExpandedWorkspaceSnippet,
+  UpdateWorkspaceSnippetParams, WorkspaceSnippet, WorkspaceSnippets,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_SHORTCUT_LENGTH: usize = 32;
+const MAX_SNIPPET_NAME_LENGTH: usize = 100;
+const MAX_SNIPPET_DESCRIPTION_LENGTH: usize = 500;
+const MAX_SNIPPET_CONTENT_BYTES: usize = 64 * 1024;
+
+pub async fn create_workspace_snippet(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: CreateWorkspaceSnippetParams,
+) -> Result<WorkspaceSnippet, AppError> {
+  let shortcut = normalize_shortcut(&params.shortcut)?;
+  let name = validate_name(&params.name)?;
+  let description = params
+    .description
+    .as_deref()
+    .map(validate_description)
+    .transpose()?;
+  validate_content(&params.content)?;
+  insert_workspace_snippet(
+    pg_pool,
+    workspace_id,
+    uid,
+    &shortcut,
+    name,
+    description.filter(|description| !description.is_empty()),
+    &params.content,
+  )
+  .await
+}
+
+pub async fn update_workspace_snippet_by_id(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  snippet_id: &Uuid,
+  params: UpdateWorkspaceSnippetParams,
+) -> Result<WorkspaceSnippet, AppError> {
+  check_user_can_edit_snippet(pg_pool, user_uuid, uid, workspace_id, snippet_id).await?;
+  let shortcut = params
+    .shortcut
+    .as_deref()
+    .map(normalize_shortcut)
+    .transpose()?;
+  let name = params.name.as_deref().map(validate_name).transpose()?;
+  let description = params
+    .description
+    .as_deref()
+    .map(validate_description)
+    .transpose()?;
+  if let Some(content) = &params.content {
+    validate_content(content)?;
+  }
+  update_workspace_snippet(
+    pg_pool,
+    workspace_id,
+    snippet_id,
+    shortcut.as_deref(),
+    name,
+    description,
+    params.content.as_ref(),
+  )
+  .await?
+  .ok_or_else(|| snippet_not_found(snippet_id))
+}
+
+pub async fn delete_workspace_snippet_by_id(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  snippet_id: &Uuid,
+) -> Result<(), AppError> {
+  check_user_can_edit_snippet(pg_pool, user_uuid, uid, workspace_id, snippet_id).await?;
+  if !delete_workspace_snippet(pg_pool, workspace_id, snippet_id).await? {
+    return Err(snippet_not_found(snippet_id));
+  }
+  Ok(())
+}
+
+pub async fn list_workspace_snippets(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  prefix: Option<&str>,
+) -> Result<WorkspaceSnippets, AppError> {
+  let prefix = prefix
+    .map(|prefix| prefix.trim().trim_start_matches('/').to_lowercase())
+    .filter(|prefix| !prefix.is_empty());
+  let snippets = select_workspace_snippets(pg_pool, workspace_id, prefix.as_deref()).await?;
+  Ok(WorkspaceSnippets { snippets })
+}
+
+/// Returns the content of the snippet with its `{{name}}` placeholders filled in. The
+/// built-in `date`, `user_name` and `workspace_name` can be overridden by the caller, e.g.
+/// to use the date in the user's time zone. Unknown placeholders are kept as is.
+pub async fn expand_workspace_snippet(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  params: ExpandWorkspaceSnippetParams,
+) -> Result<ExpandedWorkspaceSnippet, AppError> {
+  let shortcut = normalize_shortcut(&params.shortcut)?;
+  let snippet = select_workspace_snippet_by_shortcut(pg_pool, workspace_id, &shortcut)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("no snippet with the shortcut {}", shortcut))
+    })?;
+
+  let mut variables = HashMap::from([
+    (
+      "date".to_string(),
+      Utc::now().format("%Y-%m-%d").to_string(),
+    ),
+    (
+      "user_name".to_string(),
+      select_name_from_uid(pg_pool, uid).await?,
+    ),
+    (
+      "workspace_name".to_string(),
+      select_workspace(pg_pool, workspace_id)
+        .await?
+        .workspace_name
+        .unwrap_or_default(),
+    ),
+  ]);
+  variables.extend(params.variables);
+
+  let mut content = snippet.content;
+  expand_placeholders(&mut content, &variables);
+  Ok(ExpandedWorkspaceSnippet {
+    snippet_id: snippet.snippet_id,
+    shortcut: snippet.shortcut,
+    content,
+  })
+}
+
+/// Snippets are shared by the whole workspace, so only their creator and the owners of the
+/// workspace may change or delete them.
+async fn check_user_can_edit_snippet(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  snippet_id: &Uuid,
+) -> Result<(), AppError> {
+  let snippet = select_workspace_snippet_by_id(pg_pool, workspace_id, snippet_id)
+    .await?
+    .ok_or_else(|| snippet_not_found(snippet_id))?;
+  if snippet.created_by == Some(uid) {
+    return Ok(());
+  }
+  if !select_user_is_workspace_owner(pg_pool, user_uuid, workspace_id).await? {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  Ok(())
+}
+
+fn snippet_not_found(snippet_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("snippet {} not found", snippet_id))
+}
+
+/// Shortcuts are matched case-insensitively and may be typed with the leading slash of the
+/// slash menu.
+fn normalize_shortcut(shortcut: &str) -> Result<String, AppError> {
+  let shortcut = shortcut.trim().trim_start_matches('/').to_lowercase();
+  let is_valid = !shortcut.is_empty()
+    && shortcut.len() <= MAX_SHORTCUT_LENGTH
+    && shortcut
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+  if !is_valid {
+    return Err(AppError::InvalidRequest(format!(
+      "a shortcut has 1 to {} letters, digits, '-' or '_'",
+      MAX_SHORTCUT_LENGTH
+    )));
+  }
+  Ok(shortcut)
+}
+
+fn validate_name(name: &str) -> Result<&str, AppError> {
+  let name = name.trim();
+  if name.is_empty() || name.chars().count() > MAX_SNIPPET_NAME_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "a snippet name has 1 to {} characters",
+      MAX_SNIPPET_NAME_LENGTH
+    )));
+  }
+  Ok(name)
+}
+
+fn validate_description(description: &str) -> Result<&str, AppError> {
+  let description = description.trim();
+  if description.chars().count() > MAX_SNIPPET_DESCRIPTION_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "a snippet description has at most {} characters",
+      MAX_SNIPPET_DESCRIPTION_LENGTH
+    )));
+  }
+  Ok(description)
+}
+
+fn validate_content(content: &serde_json::Value) -> Result<(), AppError> {
+  let is_block_list = content
+    .as_array()
+    .is_some_and(|blocks| !blocks.is_empty() && blocks.iter().all(|block| block.is_object()));
+  if !is_block_list {
+    return Err(AppError::InvalidRequest(
+      "snippet content must be a non-empty list of blocks".to_string(),
+    ));
+  }
+  if content.to_string().len() > MAX_SNIPPET_CONTENT_BYTES {
+    return Err(AppError::InvalidRequest(format!(
+      "snippet content must not exceed {} bytes",
+      MAX_SNIPPET_CONTENT_BYTES
+    )));
+  }
+  Ok(())
+}
+
+/// Fills in the placeholders of every text `insert` of the blocks, children included.
+fn expand_placeholders(value: &mut serde_json::Value, variables: &HashMap<String, String>) {
+  match value {
+    serde_json::Value::Array(values) => {
+      for value in values {
+        expand_placeholders(value, variables);
+      }
+    },
+    serde_json::Value::Object(object) => {
+      for (key, value) in object.iter_mut() {
+        match value {
+          serde_json::Value::String(text) if key == "insert" => {
+            *text = replace_placeholders(text, variables);
+          },
+          _ => expand_placeholders(value, variables),
+        }
+      }
+    },
+    _ => {},
+  }
+}
+
+fn replace_placeholders(text: &str, variables: &HashMap<String, String>) -> String {
+  let mut expanded = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find("{{") {
+    let Some(len) = rest[start + 2..].find("}}") else {
+      break;
+    };
+    let name = rest[start + 2..start + 2 + len].trim();
+    expanded.push_str(&rest[..start]);
+    match variables.get(name) {
+      Some(value) => expanded.push_str(value),
+      None => expanded.push_str(&rest[start..start + len + 4]),
+    }
+    rest = &rest[start + len + 4..];
+  }
+  expanded.push_str(rest);
+  expanded
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn normalize_snippet_shortcut() {
+    assert_eq!(
+      normalize_shortcut(" /Legal_Footer ").unwrap(),
+      "legal_footer"
+    );
+    assert!(normalize_shortcut("/").is_err());
+    assert!(normalize_shortcut("legal footer").is_err());
+    assert!(normalize_shortcut(&"a".repeat(MAX_SHORTCUT_LENGTH + 1)).is_err());
+  }
+
+  #[test]
+  fn expand_snippet_placeholders() {
+    let variables = HashMap::from([
+      ("user_name".to_string(), "Lucy".to_string()),
+      ("date".to_string(), "2026-10-17".to_string()),
+    ]);
+    let mut content = json!([
+      {
+        "type": "paragraph",
+        "delta": [{ "insert": "Reviewed by {{ user_name }} on {{date}}", "attributes": { "bold": true } }],
+        "children": [
+          { "type": "paragraph", "delta": [{ "insert": "{{unknown}} and {{ unclosed" }] }
+        ]
+      }
+    ]);
+    expand_placeholders(&mut content, &variables);
+    assert_eq!(
+      content,
+      json!([
+        {
+          "type": "paragraph",
+          "delta": [{ "insert": "Reviewed by Lucy on 2026-10-17", "attributes": { "bold": true } }],
+          "children": [
+            { "type": "paragraph", "delta": [{ "insert": "{{unknown}} and {{ unclosed" }] }
+          ]
+        }
+      ])
+    );
+  }
+}
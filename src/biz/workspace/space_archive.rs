@@ -0,0 +1,425 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::write_guard::CollabWriteGuard;
+use appflowy_collaborate::ws2::{CollabUpdatePublisher, WorkspaceCollabInstanceCache};
+use chrono::Utc;
+use collab_database::workspace_database::WorkspaceDatabase;
+use collab_entity::CollabType;
+use collab_folder::{CollabOrigin, Folder, ViewLayout};
+use collab_rt_entity::user::RealtimeUser;
+use database::archived_space::{
+  delete_archived_objects, delete_archived_space, insert_archived_objects, insert_archived_space,
+  select_archived_object_ids, select_archived_space_objects, select_archived_spaces,
+  select_workspaces_due_for_archived_space_sync, update_archived_space_synced_at, AFArchivedObject,
+  AFArchivedSpaceRow,
+};
+use database::collab::{CollabStore, GetCollabOrigin};
+use shared_entity::dto::workspace_dto::ArchivedSpace;
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::page_view::update_workspace_folder;
+use crate::biz::collab::folder_view::{check_if_view_is_space, get_view_and_children, ViewTree};
+use crate::biz::collab::ops::{get_latest_workspace_database, list_database_row_ids};
+use crate::middleware::deadline::apply_statement_timeout;
+use crate::state::AppState;
+
+const ARCHIVED_SPACE_SYNC_INTERVAL_SECS: u64 = 60;
+const ARCHIVED_SPACE_WORKSPACES_PER_RUN: i64 = 50;
+
+/// Archives a space: its pages are locked in the folder, and their collabs, along with the
+/// databases of the space and their rows, are recorded so that every write to them is rejected.
+pub async fn archive_space(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+  space_id: Uuid,
+) -> Result<ArchivedSpace, AppError> {
+  let uid = user.uid;
+  let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  check_is_space(&folder, &workspace_id, &space_id, uid)?;
+  let views = space_views(&folder, &space_id, uid)?
+    .ok_or_else(|| AppError::RecordNotFound(format!("space {} not found", space_id)))?;
+  let workspace_database = if views.iter().any(|(_, _, layout)| layout.is_database()) {
+    let (_, workspace_database) = get_latest_workspace_database(
+      &state.collab_storage,
+      &state.pg_pool,
+      GetCollabOrigin::Server,
+      workspace_id,
+    )
+    .await?;
+    Some(workspace_database)
+  } else {
+    None
+  };
+  let mut objects = archived_objects(&views, workspace_database.as_ref());
+  let database_ids = objects_database_ids(&objects, &views);
+  objects.extend(database_rows(&state.collab_storage, workspace_id, &database_ids).await);
+
+  let mut txn = state.pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  let row = insert_archived_space(&mut txn, &workspace_id, &space_id, uid, &objects).await?;
+  txn.commit().await?;
+  state.collab_write_guard.invalidate(&workspace_id);
+  // The folder is only changed once the space is recorded as archived, so that the two can't
+  // disagree when the commit fails
+  let view_ids: Vec<String> = views.iter().map(|(id, _, _)| id.to_string()).collect();
+  let folder_update = set_views_locked(&mut folder, &view_ids, true, uid);
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
+  Ok(to_dto_archived_space(row))
+}
+
+/// Unarchives a space. Pages get back the lock they had when the space was archived.
+pub async fn unarchive_space(
+  state: &AppState,
+  user: RealtimeUser,
+  workspace_id: Uuid,
+  space_id: Uuid,
+) -> Result<(), AppError> {
+  let uid = user.uid;
+  let mut txn = state.pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  let objects = delete_archived_space(&mut txn, &workspace_id, &space_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("space {} is not archived", space_id)))?;
+
+  let mut folder = state.ws_server.get_folder(workspace_id).await?;
+  let unlocked_view_ids: Vec<String> = objects
+    .iter()
+    .filter(|object| !object.was_locked && object.database_id.is_none())
+    .map(|object| object.object_id.to_string())
+    .filter(|view_id| folder.get_view(view_id, uid).is_some())
+    .collect();
+  txn.commit().await?;
+  state.collab_write_guard.invalidate(&workspace_id);
+  let folder_update = set_views_locked(&mut folder, &unlocked_view_ids, false, uid);
+  update_workspace_folder(state, user, workspace_id, folder_update).await?;
+  Ok(())
+}
+
+pub async fn list_archived_spaces(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<ArchivedSpace>, AppError> {
+  let rows = select_archived_spaces(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(to_dto_archived_space).collect())
+}
+
+/// Collab ids of the archived spaces of the workspace, to leave them out of search results and
+/// recent pages.
+pub async fn get_archived_object_ids(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<HashSet<Uuid>, AppError> {
+  let object_ids = select_archived_object_ids(pg_pool, workspace_id).await?;
+  Ok(object_ids.into_iter().collect())
+}
+
+/// Follows the pages moved into or out of archived spaces after they were archived. A workspace
+/// is checked when its folder was persisted after the last check.
+pub async fn start_archived_space_sync_task<C>(
+  pg_pool: PgPool,
+  collab_storage: Arc<dyn CollabStore>,
+  collab_server: C,
+  write_guard: Arc<CollabWriteGuard>,
+) where
+  C: WorkspaceCollabInstanceCache + CollabUpdatePublisher,
+{
+  let mut timer = tokio::time::interval(Duration::from_secs(ARCHIVED_SPACE_SYNC_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    let workspace_ids = match select_workspaces_due_for_archived_space_sync(
+      &pg_pool,
+      ARCHIVED_SPACE_WORKSPACES_PER_RUN,
+    )
+    .await
+    {
+      Ok(workspace_ids) => workspace_ids,
+      Err(err) => {
+        error!(
+          "failed to select workspaces with archived spaces: {:?}",
+          err
+        );
+        continue;
+      },
+    };
+    for workspace_id in workspace_ids {
+      match sync_archived_space_objects(&pg_pool, &collab_storage, &collab_server, workspace_id)
+        .await
+      {
+        Ok(()) => write_guard.invalidate(&workspace_id),
+        Err(err) => warn!(
+          "failed to sync archived spaces of workspace {}: {:?}",
+          workspace_id, err
+        ),
+      }
+    }
+  }
+}
+
+/// Compares the archived collabs of each archived space with the pages the space holds now.
+/// Pages moved into the space, with their databases and rows, are archived and locked. Pages
+/// moved out of it are unarchived and get back their lock.
+async fn sync_archived_space_objects<C>(
+  pg_pool: &PgPool,
+  collab_storage: &Arc<dyn CollabStore>,
+  collab_server: &C,
+  workspace_id: Uuid,
+) -> Result<(), AppError>
+where
+  C: WorkspaceCollabInstanceCache + CollabUpdatePublisher,
+{
+  // Taken before reading the folder: moves persisted meanwhile are caught on the next run.
+  let synced_at = Utc::now();
+  let spaces = select_archived_spaces(pg_pool, &workspace_id).await?;
+  let mut folder = collab_server.get_folder(workspace_id).await?;
+  let mut stored: HashMap<Uuid, Vec<AFArchivedObject>> = HashMap::new();
+  for (space_id, object) in select_archived_space_objects(pg_pool, &workspace_id).await? {
+    stored.entry(space_id).or_default().push(object);
+  }
+
+  let mut workspace_database = None;
+  let mut added: Vec<(Uuid, Vec<AFArchivedObject>)> = vec![];
+  let mut removed: Vec<(AFArchivedObject, i64)> = vec![];
+  let mut added_views: Vec<(Uuid, i64)> = vec![];
+  for space in &spaces {
+    // A space that is no longer in the folder, such as a trashed one, is left as it is
+    let views = match space_views(&folder, &space.space_id, space.archived_by)? {
+      Some(views) => views,
+      None => continue,
+    };
+    if workspace_database.is_none() && views.iter().any(|(_, _, layout)| layout.is_database()) {
+      let (_, database) = get_latest_workspace_database(
+        collab_storage,
+        pg_pool,
+        GetCollabOrigin::Server,
+        workspace_id,
+      )
+      .await?;
+      workspace_database = Some(database);
+    }
+    let current = archived_objects(&views, workspace_database.as_ref());
+    let stored_objects = stored.remove(&space.space_id).unwrap_or_default();
+    let stored_ids: HashSet<Uuid> = stored_objects.iter().map(|o| o.object_id).collect();
+    let current_ids: HashSet<Uuid> = current.iter().map(|o| o.object_id).collect();
+
+    let mut new_objects: Vec<AFArchivedObject> = current
+      .into_iter()
+      .filter(|object| !stored_ids.contains(&object.object_id))
+      .collect();
+    if new_objects.is_empty() && stored_ids.is_subset(&current_ids) {
+      continue;
+    }
+    let new_database_ids = objects_database_ids(&new_objects, &views);
+    let rows = database_rows(collab_storage, workspace_id, &new_database_ids).await;
+    added_views.extend(
+      views
+        .iter()
+        .filter(|(view_id, _, _)| new_objects.iter().any(|o| o.object_id == *view_id))
+        .map(|(view_id, _, _)| (*view_id, space.archived_by)),
+    );
+    new_objects.extend(rows);
+    added.push((space.space_id, new_objects));
+    removed.extend(
+      stored_objects
+        .into_iter()
+        .filter(|object| !current_ids.contains(&object.object_id))
+        .map(|object| (object, space.archived_by)),
+    );
+  }
+
+  let mut txn = pg_pool.begin().await?;
+  let removed_ids: Vec<Uuid> = removed.iter().map(|(object, _)| object.object_id).collect();
+  delete_archived_objects(&mut txn, &workspace_id, &removed_ids).await?;
+  for (space_id, objects) in &added {
+    insert_archived_objects(&mut txn, &workspace_id, space_id, objects).await?;
+  }
+  update_archived_space_synced_at(&mut txn, &workspace_id, synced_at).await?;
+  txn.commit().await?;
+
+  if added_views.is_empty() && removed.is_empty() {
+    return Ok(());
+  }
+  let folder_update = {
+    let mut txn = folder.collab.transact_mut();
+    for (view_id, uid) in &added_views {
+      folder.body.views.update_view(
+        &mut txn,
+        &view_id.to_string(),
+        |update| update.set_is_locked(Some(true)).done(),
+        *uid,
+      );
+    }
+    for (object, uid) in removed.iter().filter(|(object, _)| !object.was_locked) {
+      folder.body.views.update_view(
+        &mut txn,
+        &object.object_id.to_string(),
+        |update| update.set_is_locked(Some(false)).done(),
+        *uid,
+      );
+    }
+    txn.encode_update_v1()
+  };
+  collab_server
+    .publish_update(
+      workspace_id,
+      workspace_id,
+      CollabType::Folder,
+      &CollabOrigin::Server,
+      folder_update,
+    )
+    .await
+    .map_err(AppError::Internal)?;
+  Ok(())
+}
+
+fn check_is_space(
+  folder: &Folder,
+  workspace_id: &Uuid,
+  space_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  let view = folder
+    .get_view(&space_id.to_string(), uid)
+    .ok_or_else(|| AppError::RecordNotFound(format!("space {} not found", space_id)))?;
+  if !check_if_view_is_space(&view) || view.parent_view_id != workspace_id.to_string() {
+    return Err(AppError::InvalidRequest(format!(
+      "view {} is not a space",
+      space_id
+    )));
+  }
+  Ok(())
+}
+
+/// The space and the views it holds, with whether they are locked and their layout. Returns
+/// `None` when the space is not in the folder.
+fn space_views(
+  folder: &Folder,
+  space_id: &Uuid,
+  uid: i64,
+) -> Result<Option<Vec<(Uuid, bool, ViewLayout)>>, AppError> {
+  let tree = match get_view_and_children(folder, &space_id.to_string(), uid)? {
+    Some(tree) => tree,
+    None => return Ok(None),
+  };
+  let mut views = vec![];
+  flatten_view_tree(&tree, &mut views);
+  Ok(Some(views))
+}
+
+fn flatten_view_tree(tree: &ViewTree, views: &mut Vec<(Uuid, bool, ViewLayout)>) {
+  if let Ok(view_id) = Uuid::parse_str(&tree.view.id) {
+    views.push((
+      view_id,
+      tree.view.is_locked.unwrap_or(false),
+      tree.view.layout.clone(),
+    ));
+  }
+  for child in tree.children.iter() {
+    flatten_view_tree(child, views);
+  }
+}
+
+/// The collabs edited through the views: the documents, whose collab id is the view id, and the
+/// databases the database views display. Rows are not included.
+fn archived_objects(
+  views: &[(Uuid, bool, ViewLayout)],
+  workspace_database: Option<&WorkspaceDatabase>,
+) -> Vec<AFArchivedObject> {
+  let mut seen = HashSet::new();
+  let mut objects = vec![];
+  for (view_id, is_locked, layout) in views {
+    if seen.insert(*view_id) {
+      objects.push(AFArchivedObject {
+        object_id: *view_id,
+        was_locked: *is_locked,
+        database_id: None,
+      });
+    }
+    if !layout.is_database() {
+      continue;
+    }
+    let database_id = workspace_database
+      .and_then(|db| db.get_database_meta_with_view_id(&view_id.to_string()))
+      .and_then(|meta| Uuid::parse_str(&meta.database_id).ok());
+    if let Some(database_id) = database_id {
+      if seen.insert(database_id) {
+        objects.push(AFArchivedObject {
+          object_id: database_id,
+          was_locked: false,
+          database_id: None,
+        });
+      }
+    }
+  }
+  objects
+}
+
+/// Ids of the databases among `objects`, that is the objects that are not views.
+fn objects_database_ids(
+  objects: &[AFArchivedObject],
+  views: &[(Uuid, bool, ViewLayout)],
+) -> Vec<Uuid> {
+  let view_ids: HashSet<Uuid> = views.iter().map(|(view_id, _, _)| *view_id).collect();
+  objects
+    .iter()
+    .map(|object| object.object_id)
+    .filter(|object_id| !view_ids.contains(object_id))
+    .collect()
+}
+
+/// The rows of the databases. A database whose rows can't be read is archived without them.
+async fn database_rows(
+  collab_storage: &Arc<dyn CollabStore>,
+  workspace_id: Uuid,
+  database_ids: &[Uuid],
+) -> Vec<AFArchivedObject> {
+  let mut rows = vec![];
+  for database_id in database_ids {
+    match list_database_row_ids(collab_storage, workspace_id, *database_id).await {
+      Ok(database_rows) => rows.extend(database_rows.into_iter().filter_map(|row| {
+        Some(AFArchivedObject {
+          object_id: Uuid::parse_str(&row.id).ok()?,
+          was_locked: false,
+          database_id: Some(*database_id),
+        })
+      })),
+      Err(err) => warn!(
+        "failed to list the rows of archived database {}: {}",
+        database_id, err
+      ),
+    }
+  }
+  rows
+}
+
+fn set_views_locked(
+  folder: &mut Folder,
+  view_ids: &[String],
+  is_locked: bool,
+  uid: i64,
+) -> Vec<u8> {
+  let mut txn = folder.collab.transact_mut();
+  for view_id in view_ids {
+    folder.body.views.update_view(
+      &mut txn,
+      view_id,
+      |update| update.set_is_locked(Some(is_locked)).done(),
+      uid,
+    );
+  }
+  txn.encode_update_v1()
+}
+
+fn to_dto_archived_space(row: AFArchivedSpaceRow) -> ArchivedSpace {
+  ArchivedSpace {
+    space_id: row.space_id,
+    archived_by: row.archived_by,
+    archived_at: row.archived_at,
+  }
+}
@@ -15,6 +15,10 @@ pub struct PlanLimits {
   pub storage_unlimited: bool,
   /// Whether AI responses are unlimited
   pub ai_unlimited: bool,
+  /// Blobs not read for this many days are moved to infrequent access storage
+  pub blob_infrequent_access_after_days: i32,
+  /// Maximum bytes served from the blob and publish endpoints per month
+  pub egress_bytes_limit: i64,
 }
 
 impl PlanLimits {
@@ -28,6 +32,8 @@ impl PlanLimits {
         single_upload_limit: 300 * 1024 * 1024, // 300MB（免费版总云存储量为300MB，单文件上限与总存储量一致）
         storage_unlimited: false,
         ai_unlimited: false,
+        blob_infrequent_access_after_days: 30,
+        egress_bytes_limit: 1024 * 1024 * 1024, // 1GB
       },
       SubscriptionPlan::Basic => PlanLimits {
         member_limit: 2,
@@ -36,6 +42,8 @@ impl PlanLimits {
         single_upload_limit: 3 * 1024 * 1024 * 1024, // 3GB
         storage_unlimited: false,
         ai_unlimited: false,
+        blob_infrequent_access_after_days: 60,
+        egress_bytes_limit: 20 * 1024 * 1024 * 1024, // 20GB
       },
       SubscriptionPlan::Pro => PlanLimits {
         member_limit: 5,
@@ -44,6 +52,8 @@ impl PlanLimits {
         single_upload_limit: 3 * 1024 * 1024 * 1024, // 3GB
         storage_unlimited: false,
         ai_unlimited: false,
+        blob_infrequent_access_after_days: 90,
+        egress_bytes_limit: 100 * 1024 * 1024 * 1024, // 100GB
       },
       SubscriptionPlan::Team => PlanLimits {
         member_limit: 10,
@@ -52,6 +62,8 @@ impl PlanLimits {
         single_upload_limit: 3 * 1024 * 1024 * 1024, // 3GB
         storage_unlimited: false,
         ai_unlimited: false,
+        blob_infrequent_access_after_days: 180,
+        egress_bytes_limit: 300 * 1024 * 1024 * 1024, // 300GB
       },
       SubscriptionPlan::AiMax => {
         // AI Max: unlimited AI, but inherits storage/member limits from Pro
@@ -104,6 +116,11 @@ impl PlanLimits {
     file_size <= self.single_upload_limit
   }
 
+  /// Check if the monthly egress is still within the limit
+  pub fn can_serve_egress(&self, egress_bytes: i64) -> bool {
+    egress_bytes < self.egress_bytes_limit
+  }
+
   /// Check if AI responses are available
   pub fn can_use_ai(&self, current_count: i64) -> bool {
     if self.ai_unlimited {
@@ -163,6 +180,30 @@ mod tests {
     assert_eq!(limits.storage_bytes_limit, 50 * 1024 * 1024 * 1024);
   }
 
+  #[test]
+  fn test_blob_tiering_policy() {
+    assert_eq!(
+      PlanLimits::from_plan(&SubscriptionPlan::Free).blob_infrequent_access_after_days,
+      30
+    );
+    assert_eq!(
+      PlanLimits::from_plan(&SubscriptionPlan::Team).blob_infrequent_access_after_days,
+      180
+    );
+    assert_eq!(
+      PlanLimits::from_plan(&SubscriptionPlan::AiMax).blob_infrequent_access_after_days,
+      90
+    );
+  }
+
+  #[test]
+  fn test_can_serve_egress() {
+    let limits = PlanLimits::from_plan(&SubscriptionPlan::Free);
+    let gb: i64 = 1024 * 1024 * 1024;
+    assert!(limits.can_serve_egress(gb - 1));
+    assert!(!limits.can_serve_egress(gb));
+  }
+
   #[test]
   fn test_from_plan_code() {
     let standard = PlanLimits::from_plan_code("standard");
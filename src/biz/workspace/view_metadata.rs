@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use app_error::AppError;
+use appflowy_collaborate::ws2::WorkspaceCollabInstanceCache;
+use chrono::{DateTime, Utc};
+use collab_folder::Folder;
+use database::page_activity::insert_page_move_activity_if_unrecorded;
+use database::pg_row::AFViewSection;
+use database::view_metadata::{
+  is_workspace_view_metadata_synced, lock_workspace_view_metadata, select_view_metadata,
+  select_view_section_items, select_workspace_view_parents,
+  select_workspaces_due_for_view_metadata_sync, update_workspace_view_metadata, AFViewMetadataRow,
+  AFViewSectionItemRow,
+};
+use database::workspace::select_workspace_member_uids;
+use shared_entity::dto::workspace_dto::{FolderView, FolderViewMinimal, ViewIcon, ViewLayout};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::biz::collab::folder_view::{
+  parse_extra_field_as_json, to_dto_view_icon, to_dto_view_layout,
+};
+use crate::biz::collab::utils::DUMMY_UID;
+
+const VIEW_METADATA_SYNC_INTERVAL_SECS: u64 = 30;
+const VIEW_METADATA_WORKSPACES_PER_RUN: i64 = 100;
+
+/// Keeps `af_view_metadata` in line with the folder collabs. A workspace is synced when its
+/// folder was persisted after the last sync, so the read model lags the folder by at most the
+/// collab persistence interval plus [VIEW_METADATA_SYNC_INTERVAL_SECS]. Folder changes made
+/// through the HTTP api are synced right away by [refresh_workspace_view_metadata].
+pub async fn start_view_metadata_sync_task<C>(pg_pool: PgPool, collab_instance_cache: C)
+where
+  C: WorkspaceCollabInstanceCache,
+{
+  let mut timer = tokio::time::interval(Duration::from_secs(VIEW_METADATA_SYNC_INTERVAL_SECS));
+  loop {
+    timer.tick().await;
+    if let Err(err) = run_view_metadata_sync(&pg_pool, &collab_instance_cache).await {
+      error!("view metadata sync failed: {:?}", err);
+    }
+  }
+}
+
+async fn run_view_metadata_sync(
+  pg_pool: &PgPool,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+) -> Result<(), AppError> {
+  let workspace_ids =
+    select_workspaces_due_for_view_metadata_sync(pg_pool, VIEW_METADATA_WORKSPACES_PER_RUN).await?;
+  for workspace_id in workspace_ids {
+    if let Err(err) =
+      sync_workspace_view_metadata(pg_pool, collab_instance_cache, workspace_id).await
+    {
+      warn!(
+        "failed to sync view metadata of workspace {}: {:?}",
+        workspace_id, err
+      );
+    }
+  }
+  Ok(())
+}
+
+pub async fn sync_workspace_view_metadata(
+  pg_pool: &PgPool,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  workspace_id: Uuid,
+) -> Result<(), AppError> {
+  let mut txn = pg_pool.begin().await?;
+  lock_workspace_view_metadata(&mut txn, &workspace_id).await?;
+  // Taken before reading the folder: changes persisted while the rows are written make the
+  // workspace due again on the next run.
+  let synced_at = Utc::now();
+  let folder = collab_instance_cache.get_folder(workspace_id).await?;
+  let views = collect_view_metadata(&folder, &workspace_id.to_string());
+  let member_uids = select_workspace_member_uids(txn.as_mut(), &workspace_id).await?;
+  let section_items = collect_view_section_items(&folder, &member_uids);
+  let old_parents: HashMap<Uuid, Option<Uuid>> =
+    select_workspace_view_parents(txn.as_mut(), &workspace_id)
+      .await?
+      .into_iter()
+      .collect();
+  update_workspace_view_metadata(&mut txn, &workspace_id, &views, &section_items, synced_at)
+    .await?;
+  txn.commit().await?;
+  record_view_moves(pg_pool, &folder, workspace_id, &old_parents, &views).await;
+  Ok(())
+}
+
+/// Records the moves of views since the last sync in the activity feed of their page, which
+/// covers the moves made through the realtime folder. The folder keeps the last editor of a
+/// view, who is the user that moved it.
+async fn record_view_moves(
+  pg_pool: &PgPool,
+  folder: &Folder,
+  workspace_id: Uuid,
+  old_parents: &HashMap<Uuid, Option<Uuid>>,
+  views: &[AFViewMetadataRow],
+) {
+  for view in views {
+    let old_parent_view_id = match old_parents.get(&view.view_id) {
+      Some(old_parent_view_id) if *old_parent_view_id != view.parent_view_id => *old_parent_view_id,
+      // New views and views that stayed in place
+      _ => continue,
+    };
+    let actor_uid = folder
+      .get_view(&view.view_id.to_string(), DUMMY_UID)
+      .and_then(|folder_view| folder_view.last_edited_by);
+    if let Err(err) = insert_page_move_activity_if_unrecorded(
+      pg_pool,
+      &workspace_id,
+      &view.view_id,
+      actor_uid,
+      old_parent_view_id,
+      view.parent_view_id,
+    )
+    .await
+    {
+      warn!(
+        "failed to record the move of view {}: {}",
+        view.view_id, err
+      );
+    }
+  }
+}
+
+fn collect_view_metadata(folder: &Folder, workspace_id: &str) -> Vec<AFViewMetadataRow> {
+  let mut views = Vec::new();
+  let mut visited = HashSet::new();
+  let mut prev_view_ids: HashMap<String, Option<Uuid>> = HashMap::new();
+  let mut stack = vec![workspace_id.to_string()];
+  while let Some(view_id) = stack.pop() {
+    if !visited.insert(view_id.clone()) {
+      continue;
+    }
+    let view = match folder.get_view(&view_id, DUMMY_UID) {
+      Some(view) => view,
+      None => continue,
+    };
+    let mut prev_child_id = None;
+    for child in view.children.iter() {
+      prev_view_ids.insert(child.id.clone(), prev_child_id);
+      prev_child_id = Uuid::parse_str(&child.id).ok();
+    }
+    stack.extend(view.children.iter().map(|child| child.id.clone()));
+    // The workspace itself is the root of the folder, not a page
+    if view.id == workspace_id {
+      continue;
+    }
+    let view_uuid = match Uuid::parse_str(&view.id) {
+      Ok(view_uuid) => view_uuid,
+      Err(_) => continue,
+    };
+    views.push(AFViewMetadataRow {
+      view_id: view_uuid,
+      parent_view_id: Uuid::parse_str(&view.parent_view_id).ok(),
+      prev_view_id: prev_view_ids.get(&view.id).copied().flatten(),
+      name: view.name.clone(),
+      icon: view
+        .icon
+        .clone()
+        .and_then(|icon| serde_json::to_value(to_dto_view_icon(icon)).ok()),
+      layout: to_dto_view_layout(&view.layout) as i16,
+      created_at: DateTime::from_timestamp(view.created_at, 0).unwrap_or_default(),
+      created_by: view.created_by,
+      last_edited_by: view.last_edited_by,
+      last_edited_time: DateTime::from_timestamp(view.last_edited_time, 0).unwrap_or_default(),
+      is_locked: view.is_locked,
+      extra: view
+        .extra
+        .as_ref()
+        .map(|extra| parse_extra_field_as_json(extra)),
+    });
+  }
+  views
+}
+
+/// Favorite and recent items of every member, without the views in the member's trash.
+fn collect_view_section_items(folder: &Folder, member_uids: &[i64]) -> Vec<AFViewSectionItemRow> {
+  let mut items = Vec::new();
+  for &uid in member_uids {
+    let trash_view_ids: HashSet<String> = folder
+      .get_my_trash_sections(uid)
+      .into_iter()
+      .map(|item| item.id)
+      .collect();
+    for (section, section_items) in [
+      (
+        AFViewSection::Favorite,
+        folder.get_my_favorite_sections(uid),
+      ),
+      (AFViewSection::Recent, folder.get_my_recent_sections(uid)),
+    ] {
+      let mut seen = HashSet::new();
+      for item in section_items {
+        if trash_view_ids.contains(&item.id) || !seen.insert(item.id.clone()) {
+          continue;
+        }
+        let view_id = match Uuid::parse_str(&item.id) {
+          Ok(view_id) => view_id,
+          Err(_) => continue,
+        };
+        items.push(AFViewSectionItemRow {
+          uid,
+          section,
+          view_id,
+          timestamp: DateTime::from_timestamp(item.timestamp, 0).unwrap_or_default(),
+        });
+      }
+    }
+  }
+  items
+}
+
+fn view_layout_from_i16(layout: i16) -> ViewLayout {
+  match layout {
+    1 => ViewLayout::Grid,
+    2 => ViewLayout::Board,
+    3 => ViewLayout::Calendar,
+    4 => ViewLayout::Chat,
+    _ => ViewLayout::Document,
+  }
+}
+
+/// Name, icon and layout of the given views, read from the view metadata instead of the folder.
+/// Views created after the last sync are missing from the result.
+pub async fn get_view_metadata(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+) -> Result<HashMap<Uuid, FolderViewMinimal>, AppError> {
+  if view_ids.is_empty() {
+    return Ok(HashMap::new());
+  }
+  let rows = select_view_metadata(pg_pool, workspace_id, view_ids).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        let view = FolderViewMinimal {
+          view_id: row.view_id.to_string(),
+          name: row.name,
+          icon: row
+            .icon
+            .and_then(|icon| serde_json::from_value::<ViewIcon>(icon).ok()),
+          layout: view_layout_from_i16(row.layout),
+        };
+        (row.view_id, view)
+      })
+      .collect(),
+  )
+}
+
+/// Syncs the view metadata of the workspace after a change to its folder, so that the next
+/// read sees the change. A failure is left for the periodic sync to repair.
+pub async fn refresh_workspace_view_metadata(
+  pg_pool: &PgPool,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  workspace_id: Uuid,
+) {
+  if let Err(err) = sync_workspace_view_metadata(pg_pool, collab_instance_cache, workspace_id).await
+  {
+    warn!(
+      "failed to refresh view metadata of workspace {}: {:?}",
+      workspace_id, err
+    );
+  }
+}
+
+/// Views of a section of the user with the time they were added to it, in section order. A
+/// workspace that has never been synced is synced first.
+pub async fn get_section_views(
+  pg_pool: &PgPool,
+  collab_instance_cache: &impl WorkspaceCollabInstanceCache,
+  workspace_id: Uuid,
+  uid: i64,
+  section: AFViewSection,
+) -> Result<Vec<(AFViewMetadataRow, DateTime<Utc>)>, AppError> {
+  if !is_workspace_view_metadata_synced(pg_pool, &workspace_id).await? {
+    sync_workspace_view_metadata(pg_pool, collab_instance_cache, workspace_id).await?;
+  }
+  let items = select_view_section_items(pg_pool, &workspace_id, uid, section).await?;
+  let view_ids: Vec<Uuid> = items.iter().map(|(view_id, _)| *view_id).collect();
+  let mut rows: HashMap<Uuid, AFViewMetadataRow> =
+    select_view_metadata(pg_pool, &workspace_id, &view_ids)
+      .await?
+      .into_iter()
+      .map(|row| (row.view_id, row))
+      .collect();
+  Ok(
+    items
+      .into_iter()
+      .filter_map(|(view_id, timestamp)| Some((rows.remove(&view_id)?, timestamp)))
+      .collect(),
+  )
+}
+
+pub fn to_folder_view(row: AFViewMetadataRow, is_favorite: bool, is_published: bool) -> FolderView {
+  FolderView {
+    view_id: row.view_id,
+    parent_view_id: row.parent_view_id,
+    prev_view_id: row.prev_view_id,
+    name: row.name,
+    icon: row
+      .icon
+      .and_then(|icon| serde_json::from_value::<ViewIcon>(icon).ok()),
+    is_space: false,
+    is_private: false,
+    is_published,
+    is_favorite,
+    layout: view_layout_from_i16(row.layout),
+    created_at: row.created_at,
+    created_by: row.created_by,
+    last_edited_by: row.last_edited_by,
+    last_edited_time: row.last_edited_time,
+    is_locked: row.is_locked,
+    extra: row.extra,
+    children: vec![],
+    reactions: vec![],
+  }
+}
@@ -0,0 +1,97 @@
+use std::ops::DerefMut;
+
+use app_error::AppError;
+use database::view_slug::{
+  delete_view_slugs, select_current_view_slug, select_view_by_slug, upsert_current_view_slug,
+};
+use database_entity::dto::{ResolvedViewSlug, ViewSlug};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::middleware::deadline::apply_statement_timeout;
+
+const MAX_SLUG_LENGTH: usize = 100;
+
+pub async fn get_view_slug(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<ViewSlug, AppError> {
+  let slug = select_current_view_slug(pg_pool, workspace_id, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("view {} has no slug", view_id)))?;
+  Ok(ViewSlug {
+    view_id: *view_id,
+    slug,
+  })
+}
+
+pub async fn set_view_slug(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  slug: &str,
+) -> Result<ViewSlug, AppError> {
+  let slug = normalize_slug(slug)?;
+  let mut txn = pg_pool.begin().await?;
+  apply_statement_timeout(txn.deref_mut()).await?;
+  upsert_current_view_slug(&mut txn, workspace_id, view_id, &slug).await?;
+  txn.commit().await?;
+  Ok(ViewSlug {
+    view_id: *view_id,
+    slug,
+  })
+}
+
+pub async fn remove_view_slug(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_view_slugs(pg_pool, workspace_id, view_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "view {} has no slug",
+      view_id
+    )));
+  }
+  Ok(())
+}
+
+/// Resolves current and previous slugs, so that links keep working after a page got a new slug.
+pub async fn resolve_view_slug(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  slug: &str,
+) -> Result<ResolvedViewSlug, AppError> {
+  let requested = slug.trim().to_lowercase();
+  let (view_id, current_slug) = select_view_by_slug(pg_pool, workspace_id, &requested)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("no page with the slug {}", requested)))?;
+  Ok(ResolvedViewSlug {
+    view_id,
+    redirected: current_slug != requested,
+    slug: current_slug,
+  })
+}
+
+/// Slugs are lowercase ASCII letters and digits separated by single dashes, and can't look like
+/// a view id so that the web app can tell both apart in a URL.
+fn normalize_slug(slug: &str) -> Result<String, AppError> {
+  let slug = slug.trim().to_lowercase();
+  let is_valid = !slug.is_empty()
+    && slug.len() <= MAX_SLUG_LENGTH
+    && slug
+      .chars()
+      .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    && !slug.starts_with('-')
+    && !slug.ends_with('-')
+    && !slug.contains("--")
+    && Uuid::parse_str(&slug).is_err();
+  if !is_valid {
+    return Err(AppError::InvalidRequest(format!(
+      "a slug has 1 to {} letters or digits separated by single dashes",
+      MAX_SLUG_LENGTH
+    )));
+  }
+  Ok(slug)
+}
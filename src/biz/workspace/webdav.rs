@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use chrono::DateTime;
+
+use crate::biz::workspace::markdown_export::MarkdownFile;
+
+/// A resource of the WebDAV tree of a workspace, addressed by its path relative to the
+/// workspace directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebDavNode<'a> {
+  Directory { path: String, last_modified: i64 },
+  File(&'a MarkdownFile),
+}
+
+/// Resolves a path of the workspace tree. The empty path is the workspace directory itself.
+pub fn resolve_webdav_path<'a>(files: &'a [MarkdownFile], path: &str) -> Option<WebDavNode<'a>> {
+  let path = path.trim_matches('/');
+  if let Some(file) = files.iter().find(|file| file.path == path) {
+    return Some(WebDavNode::File(file));
+  }
+  let dir_prefix = if path.is_empty() {
+    String::new()
+  } else {
+    format!("{}/", path)
+  };
+  let mut contained = files
+    .iter()
+    .filter(|file| file.path.starts_with(&dir_prefix))
+    .peekable();
+  if !path.is_empty() && contained.peek().is_none() {
+    return None;
+  }
+  Some(WebDavNode::Directory {
+    path: path.to_string(),
+    last_modified: contained
+      .map(|file| file.last_edited_time)
+      .max()
+      .unwrap_or(0),
+  })
+}
+
+/// The files and directories right below a directory of the workspace tree.
+pub fn webdav_children<'a>(files: &'a [MarkdownFile], dir_path: &str) -> Vec<WebDavNode<'a>> {
+  let dir_path = dir_path.trim_matches('/');
+  let dir_prefix = if dir_path.is_empty() {
+    String::new()
+  } else {
+    format!("{}/", dir_path)
+  };
+  let mut directories: BTreeMap<&str, i64> = BTreeMap::new();
+  let mut children = vec![];
+  for file in files {
+    let Some(relative) = file.path.strip_prefix(&dir_prefix) else {
+      continue;
+    };
+    match relative.split_once('/') {
+      Some((dir_name, _)) => {
+        let last_modified = directories.entry(dir_name).or_default();
+        *last_modified = (*last_modified).max(file.last_edited_time);
+      },
+      None => children.push(WebDavNode::File(file)),
+    }
+  }
+  children.extend(
+    directories
+      .into_iter()
+      .map(|(dir_name, last_modified)| WebDavNode::Directory {
+        path: format!("{}{}", dir_prefix, dir_name),
+        last_modified,
+      }),
+  );
+  children
+}
+
+/// Builds the `multistatus` answer of a PROPFIND request.
+#[derive(Default)]
+pub struct MultiStatus {
+  body: String,
+}
+
+impl MultiStatus {
+  pub fn add_directory(&mut self, href: &str, display_name: &str, last_modified: i64) {
+    self.add_response(
+      href,
+      display_name,
+      last_modified,
+      "<D:resourcetype><D:collection/></D:resourcetype>",
+    );
+  }
+
+  /// Files are listed without their length, which is only known once the document is exported.
+  pub fn add_file(&mut self, href: &str, display_name: &str, last_modified: i64) {
+    self.add_response(
+      href,
+      display_name,
+      last_modified,
+      "<D:resourcetype/><D:getcontenttype>text/markdown; charset=utf-8</D:getcontenttype>",
+    );
+  }
+
+  fn add_response(
+    &mut self,
+    href: &str,
+    display_name: &str,
+    last_modified: i64,
+    resource_type: &str,
+  ) {
+    self.body.push_str(&format!(
+      "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+       <D:displayname>{}</D:displayname>{}<D:getlastmodified>{}</D:getlastmodified>\
+       </D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+      html_escape::encode_text(href),
+      html_escape::encode_text(display_name),
+      resource_type,
+      http_date(last_modified),
+    ));
+  }
+
+  pub fn into_xml(self) -> String {
+    format!(
+      "<?xml version=\"1.0\" encoding=\"utf-8\"?><D:multistatus xmlns:D=\"DAV:\">{}</D:multistatus>",
+      self.body
+    )
+  }
+}
+
+/// Percent-encodes every segment of the path, keeping the separators.
+pub fn encode_webdav_path(path: &str) -> String {
+  path
+    .split('/')
+    .map(|segment| urlencoding::encode(segment).into_owned())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Formats a timestamp in seconds as an HTTP date.
+pub fn http_date(timestamp: i64) -> String {
+  DateTime::from_timestamp(timestamp, 0)
+    .unwrap_or_default()
+    .format("%a, %d %b %Y %H:%M:%S GMT")
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use uuid::Uuid;
+
+  use super::*;
+
+  fn file(path: &str, last_edited_time: i64) -> MarkdownFile {
+    MarkdownFile {
+      view_id: Uuid::new_v4(),
+      path: path.to_string(),
+      last_edited_time,
+    }
+  }
+
+  #[test]
+  fn resolve_workspace_tree() {
+    let files = vec![
+      file("General.md", 10),
+      file("General/Notes.md", 20),
+      file("General/Ideas/Q1.md", 30),
+      file("Journal.md", 5),
+    ];
+    assert_eq!(
+      resolve_webdav_path(&files, "/General/"),
+      Some(WebDavNode::Directory {
+        path: "General".to_string(),
+        last_modified: 30,
+      })
+    );
+    assert_eq!(
+      resolve_webdav_path(&files, "General/Notes.md"),
+      Some(WebDavNode::File(&files[1]))
+    );
+    assert_eq!(resolve_webdav_path(&files, "Gen"), None);
+
+    let children = webdav_children(&files, "General");
+    assert_eq!(
+      children,
+      vec![
+        WebDavNode::File(&files[1]),
+        WebDavNode::Directory {
+          path: "General/Ideas".to_string(),
+          last_modified: 30,
+        },
+      ]
+    );
+    assert_eq!(webdav_children(&files, "").len(), 3);
+  }
+
+  #[test]
+  fn webdav_path_encoding() {
+    assert_eq!(
+      encode_webdav_path("My notes/a&b (2).md"),
+      "My%20notes/a%26b%20%282%29.md"
+    );
+    assert_eq!(http_date(0), "Thu, 01 Jan 1970 00:00:00 GMT");
+  }
+}
@@ -1,15 +1,19 @@
 use std::fmt::Display;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context};
+use appflowy_collaborate::collab::structure_limit::CollabStructureLimits;
 use async_openai::config::{AzureConfig, OpenAIConfig};
 use indexer::vector::embedder::get_open_ai_config;
 use infra::env_util::{get_env_var, get_env_var_opt};
+use log::LevelFilter;
 use mailer::config::MailerSetting;
 use secrecy::{ExposeSecret, Secret};
 use semver::Version;
 use serde::Deserialize;
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use sqlx::ConnectOptions;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -30,6 +34,9 @@ pub struct Config {
   pub apple_oauth: AppleOAuthSetting,
   pub appflowy_web_url: String,
   pub notification: NotificationSetting,
+  pub push_notification: PushNotificationSetting,
+  pub egress: EgressSetting,
+  pub scheduled_export: ScheduledExportSetting,
   pub open_ai_config: Option<OpenAIConfig>,
   pub azure_ai_config: Option<AzureConfig>,
 }
@@ -126,6 +133,8 @@ impl AppFlowyAISetting {
 pub struct ApplicationSetting {
   pub port: u16,
   pub host: String,
+  /// Upper bound of the budget a client can ask for with the `x-request-deadline-ms` header.
+  pub max_request_deadline_ms: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -137,6 +146,12 @@ pub struct DatabaseSetting {
   /// connections are reserved for system applications.
   /// When we exceed the limit of the database connection, then it shows an error message.
   pub max_connections: u32,
+  /// Size of a separate pool for the transactions persisting collabs. When not set, they share
+  /// the main pool with the HTTP handlers.
+  pub collab_persistence_max_connections: Option<u32>,
+  /// Statements running longer than this are logged with the route and workspace of the
+  /// request, and counted in the slow endpoint report.
+  pub slow_query_threshold_ms: u64,
 }
 
 impl Display for DatabaseSetting {
@@ -144,8 +159,11 @@ impl Display for DatabaseSetting {
     let masked_pg_conn_opts = self.pg_conn_opts.clone().password("********");
     write!(
       f,
-      "DatabaseSetting {{ pg_conn_opts: {:?}, require_ssl: {}, max_connections: {} }}",
-      masked_pg_conn_opts, self.require_ssl, self.max_connections
+      "DatabaseSetting {{ pg_conn_opts: {:?}, require_ssl: {}, max_connections: {}, collab_persistence_max_connections: {:?} }}",
+      masked_pg_conn_opts,
+      self.require_ssl,
+      self.max_connections,
+      self.collab_persistence_max_connections
     )
   }
 }
@@ -157,7 +175,10 @@ impl DatabaseSetting {
     } else {
       PgSslMode::Prefer
     };
-    let options = self.pg_conn_opts.clone();
+    let options = self.pg_conn_opts.clone().log_slow_statements(
+      LevelFilter::Warn,
+      Duration::from_millis(self.slow_query_threshold_ms),
+    );
     options.ssl_mode(ssl_mode)
   }
 }
@@ -169,6 +190,21 @@ pub struct CollabSetting {
   pub edit_state_max_count: u32,
   pub edit_state_max_secs: i64,
   pub s3_collab_threshold: u64,
+  /// Maximum nesting depth of the views of a workspace. 0 disables the limit.
+  pub max_view_depth: usize,
+  /// Maximum number of blocks of a document. 0 disables the limit.
+  pub max_document_blocks: usize,
+  /// Number of collabs kept decoded to check the structure limits of their updates.
+  pub structure_check_cache_size: usize,
+}
+
+impl CollabSetting {
+  pub fn structure_limits(&self) -> CollabStructureLimits {
+    CollabStructureLimits {
+      max_view_depth: self.max_view_depth,
+      max_document_blocks: self.max_document_blocks,
+    }
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -196,9 +232,58 @@ impl TryFrom<&str> for PublishedCollabStorageBackend {
 
 #[derive(Clone, Debug)]
 pub struct NotificationSetting {
-  pub enable_email_notification: bool,
   pub email_notification_interval_secs: u64,
   pub email_notification_grace_period_secs: u64,
+  /// Domain receiving the replies to comment notification emails. Replying by mail is only
+  /// offered when the domain and both secrets are set.
+  pub comment_reply_domain: Option<String>,
+  /// Signs the reply addresses of comment notification emails
+  pub comment_reply_secret: Secret<String>,
+  /// Shared with the inbound mail provider, which signs the replies it forwards with it
+  pub comment_reply_webhook_secret: Secret<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PushNotificationSetting {
+  /// Push notifications are only sent to iOS devices when set
+  pub apns: Option<ApnsSetting>,
+  /// Push notifications are only sent to Android devices when set
+  pub fcm: Option<FcmSetting>,
+  /// Tokens neither refreshed nor used by a connected device for this long are deleted
+  pub stale_token_days: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ApnsSetting {
+  pub team_id: String,
+  pub key_id: String,
+  /// PEM encoded .p8 signing key
+  pub private_key: Secret<String>,
+  /// Bundle id of the iOS app
+  pub topic: String,
+  pub sandbox: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct FcmSetting {
+  pub project_id: String,
+  /// Service account allowed to send messages to the Firebase project
+  pub client_email: String,
+  /// PEM encoded private key of the service account
+  pub private_key: Secret<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EgressSetting {
+  pub flush_interval_secs: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScheduledExportSetting {
+  /// Base64 encoded 32 bytes key encrypting the credentials of export destinations. Scheduled
+  /// exports cannot be created while it is not set.
+  pub secret_key: Secret<String>,
+  pub check_interval_secs: u64,
 }
 
 // Default values favor local development.
@@ -233,6 +318,15 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       max_connections: get_env_var("APPFLOWY_DATABASE_MAX_CONNECTIONS", "40")
         .parse()
         .context("fail to get APPFLOWY_DATABASE_MAX_CONNECTIONS")?,
+      collab_persistence_max_connections: get_env_var_opt(
+        "APPFLOWY_DATABASE_COLLAB_PERSISTENCE_MAX_CONNECTIONS",
+      )
+      .map(|value| value.parse())
+      .transpose()
+      .context("fail to get APPFLOWY_DATABASE_COLLAB_PERSISTENCE_MAX_CONNECTIONS")?,
+      slow_query_threshold_ms: get_env_var("APPFLOWY_DATABASE_SLOW_QUERY_THRESHOLD_MS", "1000")
+        .parse()
+        .context("fail to get APPFLOWY_DATABASE_SLOW_QUERY_THRESHOLD_MS")?,
     },
     gotrue: GoTrueSetting {
       base_url: get_env_var("APPFLOWY_GOTRUE_BASE_URL", "http://localhost:9999"),
@@ -242,6 +336,9 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
     application: ApplicationSetting {
       port: get_env_var("APPFLOWY_APPLICATION_PORT", "8000").parse()?,
       host: get_env_var("APPFLOWY_APPLICATION_HOST", "[::]"),
+      max_request_deadline_ms: get_env_var("APPFLOWY_APPLICATION_MAX_REQUEST_DEADLINE_MS", "60000")
+        .parse()
+        .context("fail to get APPFLOWY_APPLICATION_MAX_REQUEST_DEADLINE_MS")?,
     },
     websocket: WebsocketSetting {
       heartbeat_interval: get_env_var("APPFLOWY_WEBSOCKET_HEARTBEAT_INTERVAL", "6").parse()?,
@@ -299,6 +396,10 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       edit_state_max_count: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_COUNT", "100").parse()?,
       edit_state_max_secs: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_SECS", "60").parse()?,
       s3_collab_threshold: get_env_var("APPFLOWY_COLLAB_S3_THRESHOLD", "8000").parse()?,
+      max_view_depth: get_env_var("APPFLOWY_COLLAB_MAX_VIEW_DEPTH", "64").parse()?,
+      max_document_blocks: get_env_var("APPFLOWY_COLLAB_MAX_DOCUMENT_BLOCKS", "50000").parse()?,
+      structure_check_cache_size: get_env_var("APPFLOWY_COLLAB_STRUCTURE_CHECK_CACHE_SIZE", "1024")
+        .parse()?,
     },
     published_collab: PublishedCollabSetting {
       storage_backend: get_env_var("APPFLOWY_PUBLISHED_COLLAB_STORAGE_BACKEND", "postgres")
@@ -320,8 +421,6 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
     appflowy_web_url: get_env_var_opt("APPFLOWY_WEB_URL")
       .ok_or(anyhow!("APPFLOWY_WEB_URL has not been set"))?,
     notification: NotificationSetting {
-      enable_email_notification: get_env_var("APPFLOWY_NOTIFICATION_ENABLE_EMAIL", "false")
-        .parse()?,
       email_notification_interval_secs: get_env_var(
         "APPFLOWY_NOTIFICATION_EMAIL_INTERVAL_SECS",
         "900",
@@ -332,6 +431,27 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
         "450",
       )
       .parse()?,
+      comment_reply_domain: get_env_var_opt("APPFLOWY_NOTIFICATION_COMMENT_REPLY_DOMAIN")
+        .filter(|domain| !domain.is_empty()),
+      comment_reply_secret: get_env_var("APPFLOWY_NOTIFICATION_COMMENT_REPLY_SECRET", "").into(),
+      comment_reply_webhook_secret: get_env_var(
+        "APPFLOWY_NOTIFICATION_COMMENT_REPLY_WEBHOOK_SECRET",
+        "",
+      )
+      .into(),
+    },
+    push_notification: PushNotificationSetting {
+      apns: get_apns_setting(),
+      fcm: get_fcm_setting(),
+      stale_token_days: get_env_var("APPFLOWY_PUSH_STALE_TOKEN_DAYS", "60").parse()?,
+    },
+    egress: EgressSetting {
+      flush_interval_secs: get_env_var("APPFLOWY_EGRESS_FLUSH_INTERVAL_SECS", "60").parse()?,
+    },
+    scheduled_export: ScheduledExportSetting {
+      secret_key: get_env_var("APPFLOWY_SCHEDULED_EXPORT_SECRET_KEY", "").into(),
+      check_interval_secs: get_env_var("APPFLOWY_SCHEDULED_EXPORT_CHECK_INTERVAL_SECS", "600")
+        .parse()?,
     },
     open_ai_config,
     azure_ai_config,
@@ -339,6 +459,33 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
   Ok(config)
 }
 
+fn get_apns_setting() -> Option<ApnsSetting> {
+  let private_key = get_env_var_opt("APPFLOWY_PUSH_APNS_PRIVATE_KEY")?;
+  Some(ApnsSetting {
+    team_id: get_env_var("APPFLOWY_PUSH_APNS_TEAM_ID", ""),
+    key_id: get_env_var("APPFLOWY_PUSH_APNS_KEY_ID", ""),
+    private_key: pem_from_env(&private_key).into(),
+    topic: get_env_var("APPFLOWY_PUSH_APNS_TOPIC", ""),
+    sandbox: get_env_var("APPFLOWY_PUSH_APNS_SANDBOX", "false")
+      .parse()
+      .unwrap_or(false),
+  })
+}
+
+fn get_fcm_setting() -> Option<FcmSetting> {
+  let private_key = get_env_var_opt("APPFLOWY_PUSH_FCM_PRIVATE_KEY")?;
+  Some(FcmSetting {
+    project_id: get_env_var("APPFLOWY_PUSH_FCM_PROJECT_ID", ""),
+    client_email: get_env_var("APPFLOWY_PUSH_FCM_CLIENT_EMAIL", ""),
+    private_key: pem_from_env(&private_key).into(),
+  })
+}
+
+/// PEM keys are usually set on a single line, with escaped line breaks.
+fn pem_from_env(value: &str) -> String {
+  value.replace("\\n", "\n")
+}
+
 /// The possible runtime environment for our application.
 #[derive(Clone, Debug, Deserialize)]
 pub enum Environment {
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use arc_swap::ArcSwap;
+use async_openai::config::{AzureConfig, OpenAIConfig};
+use indexer::scheduler::IndexerScheduler;
+use tracing::{error, info};
+
+/// Path of an env file whose variables take precedence over the environment of the process
+/// when the runtime settings are loaded. Editing the file and reloading is the way to change
+/// these settings without restarting the server.
+const RUNTIME_CONFIG_FILE_ENV: &str = "APPFLOWY_RUNTIME_CONFIG_FILE";
+
+/// JSON bodies are never read beyond this size, whatever the runtime limit says. The runtime
+/// limit can only lower it.
+pub const MAX_JSON_PAYLOAD_LIMIT: usize = 20 * 1024 * 1024;
+
+pub type RuntimeConfig = Arc<ArcSwap<RuntimeSetting>>;
+
+/// Settings that can be changed while the server is running, by sending SIGHUP to the process
+/// or calling `POST /internal/reload-config`. Everything else in [crate::config::config::Config]
+/// needs a restart.
+#[derive(Clone, Debug)]
+pub struct RuntimeSetting {
+  pub otp_rate_limit: OtpRateLimitSetting,
+  pub open_ai_config: Option<OpenAIConfig>,
+  pub azure_ai_config: Option<AzureConfig>,
+  /// Reject blob and publish downloads of workspaces that used up the egress of their plan
+  pub egress_enforce_limit: bool,
+  /// Send notification emails, for page mentions and replies to comments
+  pub enable_email_notification: bool,
+  /// Largest JSON request body accepted, capped by [MAX_JSON_PAYLOAD_LIMIT]
+  pub json_payload_limit: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct OtpRateLimitSetting {
+  pub max_per_window: u64,
+  pub window_secs: i64,
+}
+
+impl RuntimeSetting {
+  pub fn load() -> Result<Self, anyhow::Error> {
+    let vars = RuntimeVars::load()?;
+    let (open_ai_config, azure_ai_config) = vars.ai_configs();
+    let json_payload_limit: usize = vars
+      .get("APPFLOWY_JSON_PAYLOAD_LIMIT", "20971520")
+      .parse()
+      .context("fail to get APPFLOWY_JSON_PAYLOAD_LIMIT")?;
+    Ok(Self {
+      otp_rate_limit: OtpRateLimitSetting {
+        max_per_window: vars
+          .get("APPFLOWY_OTP_MAX_PER_WINDOW", "1")
+          .parse()
+          .context("fail to get APPFLOWY_OTP_MAX_PER_WINDOW")?,
+        window_secs: vars
+          .get("APPFLOWY_OTP_WINDOW_SECS", "60")
+          .parse()
+          .context("fail to get APPFLOWY_OTP_WINDOW_SECS")?,
+      },
+      open_ai_config,
+      azure_ai_config,
+      egress_enforce_limit: vars
+        .get("APPFLOWY_EGRESS_ENFORCE_LIMIT", "false")
+        .parse()
+        .context("fail to get APPFLOWY_EGRESS_ENFORCE_LIMIT")?,
+      enable_email_notification: vars
+        .get("APPFLOWY_NOTIFICATION_ENABLE_EMAIL", "false")
+        .parse()
+        .context("fail to get APPFLOWY_NOTIFICATION_ENABLE_EMAIL")?,
+      json_payload_limit: json_payload_limit.min(MAX_JSON_PAYLOAD_LIMIT),
+    })
+  }
+}
+
+/// Reloads the runtime settings, and hands the AI keys to the indexer. The current settings are
+/// kept when the new ones are invalid.
+pub fn reload_runtime_config(
+  runtime_config: &RuntimeConfig,
+  indexer_scheduler: &IndexerScheduler,
+) -> Result<(), anyhow::Error> {
+  let setting = RuntimeSetting::load()?;
+  indexer_scheduler.set_ai_configs(
+    setting.open_ai_config.clone(),
+    setting.azure_ai_config.clone(),
+  );
+  runtime_config.store(Arc::new(setting));
+  info!("runtime configuration reloaded");
+  Ok(())
+}
+
+/// Reloads the runtime settings every time the process receives SIGHUP.
+#[cfg(unix)]
+pub async fn start_runtime_config_reload_task(
+  runtime_config: RuntimeConfig,
+  indexer_scheduler: Arc<IndexerScheduler>,
+) {
+  use tokio::signal::unix::{signal, SignalKind};
+
+  let mut hangup = match signal(SignalKind::hangup()) {
+    Ok(hangup) => hangup,
+    Err(err) => {
+      error!("failed to listen to SIGHUP: {}", err);
+      return;
+    },
+  };
+  while hangup.recv().await.is_some() {
+    if let Err(err) = reload_runtime_config(&runtime_config, &indexer_scheduler) {
+      error!("failed to reload runtime configuration: {:?}", err);
+    }
+  }
+}
+
+#[cfg(not(unix))]
+pub async fn start_runtime_config_reload_task(
+  _runtime_config: RuntimeConfig,
+  _indexer_scheduler: Arc<IndexerScheduler>,
+) {
+}
+
+struct RuntimeVars {
+  overrides: HashMap<String, String>,
+}
+
+impl RuntimeVars {
+  fn load() -> Result<Self, anyhow::Error> {
+    let mut overrides = HashMap::new();
+    if let Ok(path) = std::env::var(RUNTIME_CONFIG_FILE_ENV) {
+      let iter = dotenvy::from_path_iter(&path)
+        .with_context(|| format!("fail to read runtime config file {}", path))?;
+      for item in iter {
+        let (key, value) =
+          item.with_context(|| format!("fail to parse runtime config file {}", path))?;
+        overrides.insert(key, value);
+      }
+    }
+    Ok(Self { overrides })
+  }
+
+  fn get(&self, key: &str, default: &str) -> String {
+    self.get_opt(key).unwrap_or_else(|| default.to_string())
+  }
+
+  fn get_opt(&self, key: &str) -> Option<String> {
+    self
+      .overrides
+      .get(key)
+      .cloned()
+      .or_else(|| std::env::var(key).ok())
+      .filter(|value| !value.is_empty())
+  }
+
+  /// Same precedence as the indexer: the official OpenAI API wins over Azure.
+  fn ai_configs(&self) -> (Option<OpenAIConfig>, Option<AzureConfig>) {
+    if let Some(api_key) = self.get_opt("AI_OPENAI_API_KEY") {
+      let mut config = OpenAIConfig::default().with_api_key(api_key);
+      if let Some(api_base) = self.get_opt("AI_OPENAI_API_BASE") {
+        config = config.with_api_base(api_base);
+      }
+      return (Some(config), None);
+    }
+    (None, self.azure_ai_config())
+  }
+
+  fn azure_ai_config(&self) -> Option<AzureConfig> {
+    Some(
+      AzureConfig::new()
+        .with_api_key(self.get_opt("AI_AZURE_OPENAI_API_KEY")?)
+        .with_api_base(self.get_opt("AI_AZURE_OPENAI_API_BASE")?)
+        .with_api_version(self.get_opt("AI_AZURE_OPENAI_API_VERSION")?),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn runtime_vars_prefer_file_overrides() {
+    let vars = RuntimeVars {
+      overrides: HashMap::from([
+        (
+          "APPFLOWY_TEST_RUNTIME_VAR".to_string(),
+          "from-file".to_string(),
+        ),
+        ("APPFLOWY_TEST_RUNTIME_EMPTY".to_string(), String::new()),
+      ]),
+    };
+    assert_eq!(
+      vars.get("APPFLOWY_TEST_RUNTIME_VAR", "default"),
+      "from-file"
+    );
+    assert_eq!(
+      vars.get("APPFLOWY_TEST_RUNTIME_EMPTY", "default"),
+      "default"
+    );
+    assert_eq!(vars.get_opt("APPFLOWY_TEST_RUNTIME_MISSING"), None);
+  }
+}
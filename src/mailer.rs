@@ -6,6 +6,7 @@ pub const WORKSPACE_ACCESS_REQUEST_TEMPLATE_NAME: &str = "workspace_access_reque
 pub const WORKSPACE_ACCESS_REQUEST_APPROVED_NOTIFICATION_TEMPLATE_NAME: &str =
   "workspace_access_request_approved_notification";
 pub const PAGE_MENTION_NOTIFICATION_TEMPLATE_NAME: &str = "page_mention_notification";
+pub const COMMENT_REPLY_NOTIFICATION_TEMPLATE_NAME: &str = "comment_reply_notification";
 
 #[derive(Clone)]
 pub struct AFCloudMailer(Mailer);
@@ -107,6 +108,31 @@ impl AFCloudMailer {
       )
       .await
   }
+
+  /// When `reply_to` is set, replying to the email answers in the comment thread.
+  pub async fn send_comment_reply_notification(
+    &self,
+    recipient_name: &str,
+    email: &str,
+    param: &CommentReplyNotificationMailerParam,
+    reply_to: Option<&str>,
+  ) -> Result<(), anyhow::Error> {
+    let subject = format!(
+      "{} replied to your comment on {} in AppFlowy",
+      param.replier_name, param.page_name
+    );
+    self
+      .0
+      .send_email_template_with_reply_to(
+        Some(recipient_name.to_string()),
+        email,
+        COMMENT_REPLY_NOTIFICATION_TEMPLATE_NAME,
+        param,
+        &subject,
+        reply_to,
+      )
+      .await
+  }
 }
 
 async fn register_mailer(mailer: &mut Mailer) -> Result<(), anyhow::Error> {
@@ -119,6 +145,8 @@ async fn register_mailer(mailer: &mut Mailer) -> Result<(), anyhow::Error> {
   );
   let page_mention_notification_template =
     include_str!("../assets/mailer_templates/build_production/page_mention_notification.html");
+  let comment_reply_notification_template =
+    include_str!("../assets/mailer_templates/build_production/comment_reply_notification.html");
   let template_strings = HashMap::from([
     (WORKSPACE_INVITE_TEMPLATE_NAME, workspace_invite_template),
     (
@@ -133,6 +161,10 @@ async fn register_mailer(mailer: &mut Mailer) -> Result<(), anyhow::Error> {
       PAGE_MENTION_NOTIFICATION_TEMPLATE_NAME,
       page_mention_notification_template,
     ),
+    (
+      COMMENT_REPLY_NOTIFICATION_TEMPLATE_NAME,
+      comment_reply_notification_template,
+    ),
   ]);
 
   for (template_name, template_string) in template_strings {
@@ -182,3 +214,14 @@ pub struct PageMentionNotificationMailerParam {
   pub mentioned_page_url: String,
   pub mentioned_at: String,
 }
+
+#[derive(serde::Serialize)]
+pub struct CommentReplyNotificationMailerParam {
+  pub page_name: String,
+  pub page_url: String,
+  pub replier_icon_url: String,
+  pub replier_name: String,
+  pub reply_content: String,
+  pub replied_at: String,
+  pub reply_by_mail: bool,
+}
@@ -1,3 +1,4 @@
+use appflowy_cloud::admin_cli::{is_admin_command, run_admin_command};
 use appflowy_cloud::application::{init_state, Application};
 use appflowy_cloud::config::config::get_configuration;
 use appflowy_cloud::telemetry::init_subscriber;
@@ -15,6 +16,11 @@ async fn main() -> anyhow::Result<()> {
 
   init_subscriber(&conf.app_env);
 
+  let args: Vec<String> = std::env::args().collect();
+  if is_admin_command(&args) {
+    return run_admin_command(&conf, &args).await;
+  }
+
   let state = init_state(&conf)
     .await
     .map_err(|e| anyhow::anyhow!("Failed to initialize application state: {}", e))?;
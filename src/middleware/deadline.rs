@@ -0,0 +1,183 @@
+use std::future::{ready, Future, Ready};
+use std::time::Duration;
+
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use app_error::AppError;
+use futures_util::future::LocalBoxFuture;
+use shared_entity::response::AppResponseError;
+use sqlx::{Executor, Postgres};
+use tokio::time::Instant;
+
+/// Time budget of the request in milliseconds, counted from when the server receives it.
+pub const X_REQUEST_DEADLINE_MS: &str = "x-request-deadline-ms";
+
+/// Statement timeouts below this are not worth sending to Postgres: the statement would be
+/// cancelled before the round trip completes.
+const MIN_STATEMENT_TIMEOUT: Duration = Duration::from_millis(10);
+
+tokio::task_local! {
+  static REQUEST_DEADLINE: Instant;
+}
+
+/// Time left until the deadline of the current request. `None` when the client did not send
+/// [X_REQUEST_DEADLINE_MS] or when called outside of a request, e.g. by a background task.
+pub fn remaining_budget() -> Option<Duration> {
+  REQUEST_DEADLINE
+    .try_with(|deadline| deadline.saturating_duration_since(Instant::now()))
+    .ok()
+}
+
+fn deadline_exceeded() -> AppError {
+  AppError::RequestTimeout("request deadline exceeded".to_string())
+}
+
+/// Fails fast when the deadline of the current request already passed, so that ops can bail out
+/// before starting expensive work.
+pub fn check_deadline() -> Result<(), AppError> {
+  match remaining_budget() {
+    Some(remaining) if remaining.is_zero() => Err(deadline_exceeded()),
+    _ => Ok(()),
+  }
+}
+
+/// Runs `fut` within the remaining budget of the current request. Used for calls to external
+/// services, e.g. the AI server, whose own timeouts don't know about the request deadline.
+pub async fn within_deadline<F, T>(fut: F) -> Result<T, AppError>
+where
+  F: Future<Output = T>,
+{
+  match remaining_budget() {
+    Some(remaining) => tokio::time::timeout(remaining, fut)
+      .await
+      .map_err(|_| deadline_exceeded()),
+    None => Ok(fut.await),
+  }
+}
+
+/// Limits the statements of the transaction to the remaining budget of the current request.
+/// `SET LOCAL` is reset when the transaction ends, so the connection goes back to the pool with
+/// the server default.
+pub async fn apply_statement_timeout<'c, E>(executor: E) -> Result<(), AppError>
+where
+  E: Executor<'c, Database = Postgres>,
+{
+  let remaining = match remaining_budget() {
+    Some(remaining) => remaining,
+    None => return Ok(()),
+  };
+  if remaining < MIN_STATEMENT_TIMEOUT {
+    return Err(deadline_exceeded());
+  }
+  // SET doesn't accept bind parameters
+  executor
+    .execute(format!("SET LOCAL statement_timeout = {}", remaining.as_millis()).as_str())
+    .await?;
+  Ok(())
+}
+
+/// Derives the deadline of the request from [X_REQUEST_DEADLINE_MS], capped by
+/// `ApplicationSetting::max_request_deadline_ms`. Requests without the header run without a
+/// deadline.
+///
+/// For reads, the handler future is dropped when the deadline passes, which cancels whatever it
+/// was awaiting, and the client gets a `RequestTimeout` error. Writes are not dropped: a write
+/// may have committed by the time the deadline passes, and the client would retry a change that
+/// was applied. They are held to the deadline by the statement timeout of their transactions,
+/// see [apply_statement_timeout], so that a late write rolls back instead.
+pub struct DeadlineMiddleware {
+  max_deadline: Duration,
+}
+
+impl DeadlineMiddleware {
+  pub fn new(max_deadline: Duration) -> Self {
+    Self { max_deadline }
+  }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for DeadlineMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Transform = DeadlineMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(DeadlineMiddlewareService {
+      service,
+      max_deadline: self.max_deadline,
+    }))
+  }
+}
+
+pub struct DeadlineMiddlewareService<S> {
+  service: S,
+  max_deadline: Duration,
+}
+
+impl<S, B> Service<ServiceRequest> for DeadlineMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let budget = match requested_budget(&req) {
+      Some(budget) => budget.min(self.max_deadline),
+      None => return Box::pin(self.service.call(req)),
+    };
+    let deadline = Instant::now() + budget;
+    let is_read = req.method().is_safe();
+    let fut = self.service.call(req);
+    Box::pin(REQUEST_DEADLINE.scope(deadline, async move {
+      if !is_read {
+        return fut.await;
+      }
+      match tokio::time::timeout_at(deadline, fut).await {
+        Ok(result) => result,
+        Err(_) => Err(AppResponseError::from(deadline_exceeded()).into()),
+      }
+    }))
+  }
+}
+
+fn requested_budget(req: &ServiceRequest) -> Option<Duration> {
+  req
+    .headers()
+    .get(X_REQUEST_DEADLINE_MS)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse::<u64>().ok())
+    .map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn budget_is_only_enforced_inside_a_request() {
+    assert!(remaining_budget().is_none());
+    assert!(within_deadline(async { 1 }).await.is_ok());
+
+    let deadline = Instant::now() + Duration::from_millis(50);
+    REQUEST_DEADLINE
+      .scope(deadline, async {
+        assert!(remaining_budget().unwrap() <= Duration::from_millis(50));
+        let result = within_deadline(tokio::time::sleep(Duration::from_secs(5))).await;
+        assert!(matches!(result, Err(AppError::RequestTimeout(_))));
+        assert!(check_deadline().is_err());
+      })
+      .await;
+  }
+}
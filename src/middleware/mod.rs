@@ -1,2 +1,5 @@
+pub mod deadline;
 pub mod metrics_mw;
+pub mod payload_limit;
 pub mod request_id;
+pub mod slow_query;
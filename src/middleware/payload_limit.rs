@@ -0,0 +1,110 @@
+use std::future::{ready, Ready};
+
+use actix_http::error::PayloadError;
+use actix_http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use actix_http::Payload;
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::JsonPayloadError;
+use futures_util::future::LocalBoxFuture;
+use futures_util::StreamExt;
+
+use crate::config::runtime::RuntimeConfig;
+
+/// Rejects JSON requests whose body is above the limit of the runtime settings: up front when
+/// the declared length is above it, and while the body is read otherwise, such as for chunked
+/// bodies. The limit of `JsonConfig` is fixed when the workers start, so it only acts as the
+/// upper bound of this one.
+pub struct PayloadLimitMiddleware {
+  runtime_config: RuntimeConfig,
+}
+
+impl PayloadLimitMiddleware {
+  pub fn new(runtime_config: RuntimeConfig) -> Self {
+    Self { runtime_config }
+  }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for PayloadLimitMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Transform = PayloadLimitMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(PayloadLimitMiddlewareService {
+      service,
+      runtime_config: self.runtime_config.clone(),
+    }))
+  }
+}
+
+pub struct PayloadLimitMiddlewareService<S> {
+  service: S,
+  runtime_config: RuntimeConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for PayloadLimitMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, mut req: ServiceRequest) -> Self::Future {
+    if is_json(&req) {
+      let limit = self.runtime_config.load().json_payload_limit;
+      if let Some(length) = content_length(&req).filter(|length| *length > limit) {
+        return Box::pin(ready(Err(
+          JsonPayloadError::OverflowKnownLength { length, limit }.into(),
+        )));
+      }
+      req.set_payload(limit_payload(req.take_payload(), limit));
+    }
+    Box::pin(self.service.call(req))
+  }
+}
+
+/// Fails the body with [PayloadError::Overflow] once more than `limit` bytes were read.
+fn limit_payload(payload: Payload, limit: usize) -> Payload {
+  let mut read = 0;
+  let limited = payload.map(move |chunk| {
+    let chunk = chunk?;
+    read += chunk.len();
+    if read > limit {
+      return Err(PayloadError::Overflow);
+    }
+    Ok(chunk)
+  });
+  Payload::from(limited.boxed_local())
+}
+
+fn is_json(req: &ServiceRequest) -> bool {
+  req
+    .headers()
+    .get(CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| value.starts_with("application/json"))
+    .unwrap_or(false)
+}
+
+fn content_length(req: &ServiceRequest) -> Option<usize> {
+  req
+    .headers()
+    .get(CONTENT_LENGTH)?
+    .to_str()
+    .ok()?
+    .parse()
+    .ok()
+}
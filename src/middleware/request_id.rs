@@ -2,7 +2,9 @@ use actix_http::header::{HeaderName, HeaderValue};
 use std::future::{ready, Ready};
 use tracing::{span, Instrument, Level};
 
-use crate::api::util::{client_version_from_headers, device_id_from_headers};
+use crate::api::util::{
+  client_version_from_headers, device_id_from_headers, workspace_id_from_path,
+};
 use actix_service::{forward_ready, Service, Transform};
 use actix_web::dev::{ServiceRequest, ServiceResponse};
 use futures_util::future::LocalBoxFuture;
@@ -60,9 +62,12 @@ where
       });
 
       let client_info = get_client_info(&req);
+      let pattern = req.match_pattern().unwrap_or_default();
+      let workspace_id = workspace_id_from_path(&pattern, req.path());
       let span = span!(Level::INFO, "request",
         request_id = %request_id,
-        path = %req.match_pattern().unwrap_or_default(),
+        path = %pattern,
+        workspace_id = workspace_id.map(tracing::field::display),
         method = %req.method(),
         client_version = client_info.client_version,
         device_id = client_info.device_id,
@@ -0,0 +1,199 @@
+use std::fmt::Debug;
+use std::future::{ready, Ready};
+use std::sync::LazyLock;
+
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use dashmap::DashMap;
+use futures_util::future::LocalBoxFuture;
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+use uuid::Uuid;
+
+use crate::api::util::workspace_id_from_path;
+
+/// Route of the statements executed outside of any request, e.g. by background tasks
+const BACKGROUND_ROUTE: &str = "background";
+
+static SLOW_QUERY_STATS: LazyLock<DashMap<String, SlowEndpoint>> = LazyLock::new(DashMap::new);
+
+tokio::task_local! {
+  static REQUEST_CONTEXT: RequestContext;
+}
+
+#[derive(Clone)]
+struct RequestContext {
+  route: String,
+  workspace_id: Option<Uuid>,
+}
+
+/// Slow statements executed while serving one route, as reported by
+/// `GET /internal/slow-endpoints`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SlowEndpoint {
+  pub route: String,
+  pub count: u64,
+  pub total_millis: u64,
+  pub max_millis: u64,
+  pub last_statement: String,
+  pub last_workspace_id: Option<Uuid>,
+}
+
+/// The routes whose slow statements took the most time since the server started.
+pub fn top_slow_endpoints(limit: usize) -> Vec<SlowEndpoint> {
+  let mut endpoints: Vec<SlowEndpoint> = SLOW_QUERY_STATS
+    .iter()
+    .map(|entry| entry.value().clone())
+    .collect();
+  endpoints.sort_by(|a, b| b.total_millis.cmp(&a.total_millis));
+  endpoints.truncate(limit);
+  endpoints
+}
+
+fn record_slow_statement(statement: String, millis: u64) {
+  let context = REQUEST_CONTEXT.try_with(|context| context.clone()).ok();
+  let (route, workspace_id) = match context {
+    Some(context) => (context.route, context.workspace_id),
+    None => (BACKGROUND_ROUTE.to_string(), None),
+  };
+  let mut endpoint = SLOW_QUERY_STATS
+    .entry(route.clone())
+    .or_insert_with(|| SlowEndpoint {
+      route,
+      count: 0,
+      total_millis: 0,
+      max_millis: 0,
+      last_statement: String::new(),
+      last_workspace_id: None,
+    });
+  endpoint.count += 1;
+  endpoint.total_millis += millis;
+  endpoint.max_millis = endpoint.max_millis.max(millis);
+  endpoint.last_statement = statement;
+  endpoint.last_workspace_id = workspace_id;
+}
+
+/// Collects the slow statements logged by sqlx, see `DatabaseSetting::slow_query_threshold_ms`.
+/// The log line itself is tagged with the route and workspace id by the request span.
+pub struct SlowQueryLayer;
+
+impl<S: Subscriber> Layer<S> for SlowQueryLayer {
+  fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+    if event.metadata().target() != "sqlx::query" {
+      return;
+    }
+    let mut visitor = SlowStatementVisitor::default();
+    event.record(&mut visitor);
+    if visitor.message.starts_with("slow statement") {
+      record_slow_statement(visitor.summary, (visitor.elapsed_secs * 1000.0) as u64);
+    }
+  }
+}
+
+#[derive(Default)]
+struct SlowStatementVisitor {
+  message: String,
+  summary: String,
+  elapsed_secs: f64,
+}
+
+impl Visit for SlowStatementVisitor {
+  fn record_f64(&mut self, field: &Field, value: f64) {
+    if field.name() == "elapsed_secs" {
+      self.elapsed_secs = value;
+    }
+  }
+
+  fn record_str(&mut self, field: &Field, value: &str) {
+    if field.name() == "summary" {
+      self.summary = value.to_string();
+    }
+  }
+
+  fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+    match field.name() {
+      "message" => self.message = format!("{:?}", value),
+      "summary" => self.summary = format!("{:?}", value),
+      _ => {},
+    }
+  }
+}
+
+/// Makes the route and workspace of the request known to the statements it executes.
+pub struct SlowQueryMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for SlowQueryMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Transform = SlowQueryMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(SlowQueryMiddlewareService { service }))
+  }
+}
+
+pub struct SlowQueryMiddlewareService<S> {
+  service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for SlowQueryMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = actix_web::Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let pattern = req.match_pattern().unwrap_or_default();
+    let context = RequestContext {
+      workspace_id: workspace_id_from_path(&pattern, req.path()),
+      route: format!("{} {}", req.method(), pattern),
+    };
+    Box::pin(REQUEST_CONTEXT.scope(context, self.service.call(req)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn slow_statements_are_attributed_to_the_request_route() {
+    let workspace_id = Uuid::new_v4();
+    let context = RequestContext {
+      route: "GET /api/workspace/{workspace_id}/test-slow-query".to_string(),
+      workspace_id: Some(workspace_id),
+    };
+    REQUEST_CONTEXT
+      .scope(context, async {
+        record_slow_statement("SELECT 1".to_string(), 1200);
+        record_slow_statement("SELECT 2".to_string(), 3000);
+      })
+      .await;
+
+    let endpoint = top_slow_endpoints(usize::MAX)
+      .into_iter()
+      .find(|endpoint| endpoint.route == "GET /api/workspace/{workspace_id}/test-slow-query")
+      .unwrap();
+    assert_eq!(endpoint.count, 2);
+    assert_eq!(endpoint.total_millis, 4200);
+    assert_eq!(endpoint.max_millis, 3000);
+    assert_eq!(endpoint.last_statement, "SELECT 2");
+    assert_eq!(endpoint.last_workspace_id, Some(workspace_id));
+  }
+}
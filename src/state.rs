@@ -16,6 +16,9 @@ use app_error::AppError;
 use appflowy_ai_client::client::AppFlowyAIClient;
 use appflowy_ai_client::chat_client::ChatClient;
 use appflowy_collaborate::collab::cache::CollabCache;
+use appflowy_collaborate::collab::rejected_update::RejectedUpdateRecorder;
+use appflowy_collaborate::collab::structure_limit::CollabStructureGuard;
+use appflowy_collaborate::collab::write_guard::CollabWriteGuard;
 use appflowy_collaborate::metrics::CollabMetrics;
 use appflowy_collaborate::ws2::WsServer;
 use appflowy_collaborate::CollabRealtimeMetrics;
@@ -29,11 +32,18 @@ use indexer::metrics::EmbeddingMetrics;
 use indexer::scheduler::IndexerScheduler;
 use snowflake::Snowflake;
 
-use crate::api::metrics::{AppFlowyWebMetrics, PublishedCollabMetrics, RequestMetrics};
+use crate::api::metrics::{
+  AppFlowyWebMetrics, PoolMetrics, PublishedCollabMetrics, RequestMetrics,
+};
 use crate::biz::chat::metrics::AIMetrics;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::redis_health::RedisHealth;
+use crate::biz::workspace::egress::EgressMeter;
+use crate::biz::workspace::events::WorkspaceEvents;
+use crate::biz::workspace::publish_stats::PublishViewCounter;
 use crate::biz::workspace::publish::PublishedCollabStore;
 use crate::config::config::Config;
+use crate::config::runtime::RuntimeConfig;
 use crate::mailer::AFCloudMailer;
 
 pub type RedisConnectionManager = redis::aio::ConnectionManager;
@@ -47,7 +57,14 @@ pub struct AppState {
   pub redis_stream_router: Arc<StreamRouter>,
   pub awareness_gossip: Arc<AwarenessGossip>,
   pub redis_connection_manager: RedisConnectionManager,
+  pub redis_health: Arc<RedisHealth>,
   pub collab_cache: Arc<CollabCache>,
+  /// Refuses writes to read-only collabs, such as the pages of archived spaces
+  pub collab_write_guard: Arc<CollabWriteGuard>,
+  /// Refuses updates that make collabs too deep or too large
+  pub collab_structure_guard: Arc<CollabStructureGuard>,
+  /// Keeps the updates that were not applied, so that their authors can recover them
+  pub rejected_updates: Arc<RejectedUpdateRecorder>,
   pub collab_storage: Arc<dyn CollabStore>,
   pub collab_access_control: Arc<dyn CollabAccessControl>,
   pub workspace_access_control: Arc<dyn WorkspaceAccessControl>,
@@ -63,10 +80,16 @@ pub struct AppState {
   pub chat_client: Arc<ChatClient>,
   pub indexer_scheduler: Arc<IndexerScheduler>,
   pub ws_server: Addr<WsServer>,
+  /// Applies the side effects of workspace membership changes
+  pub workspace_events: Arc<WorkspaceEvents>,
   /// 七牛云客户端（用于AI图片和文件存储），可选
   pub qiniu_client: Option<Arc<infra::qiniu_client::QiniuClient>>,
   /// 七牛云S3兼容存储（用于文档文件上传，替代MinIO），可选
   pub qiniu_bucket_storage: Option<Arc<S3BucketStorage>>,
+  pub egress_meter: Arc<EgressMeter>,
+  pub publish_view_counter: Arc<PublishViewCounter>,
+  /// Settings that can be reloaded without restarting the server
+  pub runtime_config: RuntimeConfig,
 }
 
 impl AppState {
@@ -145,6 +168,7 @@ pub struct AppMetrics {
   pub embedding_metrics: Arc<EmbeddingMetrics>,
   pub collab_stream_metrics: Arc<CollabStreamMetrics>,
   pub ai_metrics: Arc<AIMetrics>,
+  pub pool_metrics: Arc<PoolMetrics>,
 }
 
 impl Default for AppMetrics {
@@ -165,6 +189,7 @@ impl AppMetrics {
     let embedding_metrics = Arc::new(EmbeddingMetrics::register(&mut registry));
     let collab_stream_metrics = Arc::new(CollabStreamMetrics::register(&mut registry));
     let ai_metrics = Arc::new(AIMetrics::register(&mut registry));
+    let pool_metrics = Arc::new(PoolMetrics::register(&mut registry));
     Self {
       registry: Arc::new(registry),
       request_metrics,
@@ -176,6 +201,7 @@ impl AppMetrics {
       embedding_metrics,
       collab_stream_metrics,
       ai_metrics,
+      pool_metrics,
     }
   }
 }
@@ -2,8 +2,11 @@ use crate::config::config::Environment;
 use actix_web::rt::task::JoinHandle;
 use chrono::Local;
 use tracing::subscriber::set_global_default;
+use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::{fmt::format::Writer, EnvFilter};
 
+use crate::middleware::slow_query::SlowQueryLayer;
+
 /// Register a subscriber as global default to process span data.
 ///
 /// It should only be called once!
@@ -30,12 +33,13 @@ pub fn init_subscriber(app_env: &Environment) {
           .with_target(false)
           .with_file(false)
           .pretty()
-          .finish();
+          .finish()
+          .with(SlowQueryLayer);
         set_global_default(subscriber).unwrap();
       }
     },
     Environment::Production => {
-      let subscriber = builder.json().finish();
+      let subscriber = builder.json().finish().with(SlowQueryLayer);
       set_global_default(subscriber).unwrap();
     },
   }
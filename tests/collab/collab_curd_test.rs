@@ -5,7 +5,7 @@ use collab::entity::EncodedCollab;
 use collab_document::document_data::default_document_collab_data;
 use collab_entity::CollabType;
 use database_entity::dto::{
-  CollabParams, CreateCollabData, CreateCollabParams, QueryCollab, QueryCollabParams,
+  AFRole, CollabParams, CreateCollabData, CreateCollabParams, QueryCollab, QueryCollabParams,
   QueryCollabResult,
 };
 
@@ -210,3 +210,93 @@ pub struct OldCreateCollabParams {
   inner: CreateCollabData,
   pub workspace_id: Uuid,
 }
+
+#[tokio::test]
+async fn collab_conflicts_require_workspace_access_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let conflicts = owner
+    .api_client
+    .get_collab_conflicts(&workspace_id, &workspace_id)
+    .await
+    .unwrap();
+  assert!(conflicts.conflicts.is_empty());
+
+  let stranger = TestClient::new_user_without_ws_conn().await;
+  let error = stranger
+    .api_client
+    .get_collab_conflicts(&workspace_id, &workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn rejected_full_sync_is_kept_as_collab_conflict_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+
+  let object_id = Uuid::new_v4();
+  let mut document = empty_document_editor(&object_id);
+  owner
+    .api_client
+    .create_collab(CreateCollabParams {
+      workspace_id,
+      object_id,
+      encoded_collab_v1: document.encode_collab().encode_to_bytes().unwrap(),
+      collab_type: CollabType::Document,
+    })
+    .await
+    .unwrap();
+
+  // the guest can only read the document: the server drops the edit, and sending it again
+  // doesn't keep it twice
+  document.insert_paragraphs(vec![
+    "Written offline by a guest who can only read this page.".to_string(),
+  ]);
+  let encoded_collab = document.encode_collab();
+  for _ in 0..2 {
+    guest
+      .api_client
+      .collab_full_sync(
+        &workspace_id,
+        &object_id,
+        CollabType::Document,
+        encoded_collab.doc_state.to_vec(),
+        encoded_collab.state_vector.to_vec(),
+      )
+      .await
+      .unwrap();
+  }
+
+  // conflicts are written in the background
+  let mut conflicts = vec![];
+  for _ in 0..20 {
+    conflicts = guest
+      .api_client
+      .get_collab_conflicts(&workspace_id, &object_id)
+      .await
+      .unwrap()
+      .conflicts;
+    if !conflicts.is_empty() {
+      break;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+  }
+  assert_eq!(conflicts.len(), 1);
+  assert_eq!(conflicts[0].reason, "permission_denied");
+  assert_eq!(conflicts[0].update_v1, encoded_collab.doc_state.to_vec());
+
+  // the update is only shown to its author
+  let conflicts = owner
+    .api_client
+    .get_collab_conflicts(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(conflicts.conflicts.is_empty());
+}
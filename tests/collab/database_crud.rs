@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use app_error::ErrorCode;
 use client_api_test::{generate_unique_registered_user_client, workspace_id_from_client};
 use collab_database::entity::FieldType;
 use serde_json::json;
@@ -146,6 +147,58 @@ async fn database_row_upsert() {
   }
 }
 
+#[tokio::test]
+async fn get_single_database_row_detail() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let databases = c.list_databases(&workspace_id).await.unwrap();
+  let todo_db = &databases[0];
+
+  let row_id = c
+    .upsert_database_item(
+      &workspace_id,
+      &todo_db.id,
+      "my_single_row".to_string(),
+      HashMap::from([
+        (String::from("Description"), json!("single row")),
+        (String::from("Status"), json!("Doing")),
+      ]),
+      Some("This is a document of a single row".to_string()),
+    )
+    .await
+    .unwrap();
+
+  let row_detail = c
+    .get_database_row_detail(&workspace_id, &todo_db.id, &row_id, true)
+    .await
+    .unwrap();
+  assert_eq!(row_detail.id, row_id);
+  assert_eq!(row_detail.cells["Description"], "single row");
+  assert_eq!(row_detail.cells["Status"], "Doing");
+  assert!(row_detail.has_doc);
+  assert_eq!(
+    row_detail.doc,
+    Some("This is a document of a single row".to_string())
+  );
+
+  let row_detail = c
+    .get_database_row_detail(&workspace_id, &todo_db.id, &row_id, false)
+    .await
+    .unwrap();
+  assert_eq!(row_detail.doc, None);
+
+  let error = c
+    .get_database_row_detail(
+      &workspace_id,
+      &todo_db.id,
+      &uuid::Uuid::new_v4().to_string(),
+      false,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+}
+
 #[tokio::test]
 async fn database_fields_crud() {
   let (c, _user) = generate_unique_registered_user_client().await;
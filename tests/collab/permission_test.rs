@@ -506,3 +506,35 @@ async fn multiple_user_with_read_only_permission_edit_same_collab_test() {
     .to_json_value();
   assert_json_eq!(expected_client_json, server_value);
 }
+
+#[tokio::test]
+async fn collab_access_check_test() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // the workspace id is also the object id of the workspace folder
+  let missing_object_id = Uuid::new_v4();
+  let items = owner
+    .api_client
+    .check_collab_access(&workspace_id, vec![workspace_id, missing_object_id])
+    .await
+    .unwrap();
+  assert_eq!(items.len(), 2);
+  assert!(items[0].exists);
+  assert!(items[0].can_read && items[0].can_write && items[0].can_share);
+  assert_eq!(items[1].object_id, missing_object_id);
+  assert!(!items[1].exists);
+  assert!(!items[1].can_read);
+
+  let items = member
+    .api_client
+    .check_collab_access(&workspace_id, vec![workspace_id])
+    .await
+    .unwrap();
+  assert!(items[0].can_read && items[0].can_write && items[0].can_share);
+}
@@ -34,3 +34,26 @@ async fn workspace_usage_put_and_then_delete_blob_test() {
   let usage = client.get_workspace_usage().await;
   assert_eq!(usage.consumed_capacity, 0);
 }
+
+#[tokio::test]
+async fn workspace_blob_tiering_summary_test() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let mime = mime::TEXT_PLAIN_UTF_8;
+  let file_id = uuid::Uuid::new_v4().to_string();
+  client.upload_blob(&file_id, "123", &mime).await;
+
+  let workspace_id = client.workspace_id().await;
+  let summary = client
+    .api_client
+    .get_blob_tiering_summary(&workspace_id)
+    .await
+    .unwrap();
+  // freshly uploaded blobs stay in the standard storage class
+  assert_eq!(summary.standard_blob_count, 1);
+  assert_eq!(summary.standard_bytes, 3);
+  assert_eq!(summary.infrequent_access_blob_count, 0);
+  assert_eq!(summary.estimated_monthly_savings, 0.0);
+  assert!(summary.infrequent_access_after_days > 0);
+
+  client.delete_file(&file_id).await;
+}
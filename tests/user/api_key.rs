@@ -0,0 +1,62 @@
+use client_api_test::TestClient;
+use database_entity::dto::CreateUserApiKeyParams;
+use reqwest::{Method, StatusCode};
+
+async fn propfind(base_url: &str, path: &str, api_key: &str) -> (StatusCode, String) {
+  let resp = reqwest::Client::new()
+    .request(
+      Method::from_bytes(b"PROPFIND").unwrap(),
+      format!("{}/webdav/{}", base_url, path),
+    )
+    .header("Depth", "1")
+    .basic_auth("webdav", Some(api_key))
+    .send()
+    .await
+    .unwrap();
+  let status = resp.status();
+  (status, resp.text().await.unwrap())
+}
+
+#[tokio::test]
+async fn browse_workspace_over_webdav_with_api_key() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let base_url = client.api_client.base_url().to_string();
+
+  let created = client
+    .api_client
+    .create_user_api_key(&CreateUserApiKeyParams {
+      name: "file manager".to_string(),
+    })
+    .await
+    .unwrap();
+  assert!(created.secret.starts_with(&created.api_key.key_prefix));
+  let api_keys = client.api_client.list_user_api_keys().await.unwrap();
+  assert_eq!(api_keys.api_keys.len(), 1);
+
+  let (status, body) = propfind(&base_url, "", &created.secret).await;
+  assert_eq!(status, StatusCode::MULTI_STATUS);
+  assert!(body.contains(&workspace_id.to_string()));
+
+  let (status, body) = propfind(&base_url, &workspace_id.to_string(), &created.secret).await;
+  assert_eq!(status, StatusCode::MULTI_STATUS);
+  assert!(body.contains(".md</D:href>"));
+
+  // the facade is read-only
+  let resp = reqwest::Client::new()
+    .put(format!("{}/webdav/{}/new.md", base_url, workspace_id))
+    .basic_auth("webdav", Some(&created.secret))
+    .body("# new")
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+  client
+    .api_client
+    .delete_user_api_key(created.api_key.key_id)
+    .await
+    .unwrap();
+  let (status, _) = propfind(&base_url, "", &created.secret).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,95 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+
+#[tokio::test]
+async fn store_and_restore_encrypted_backup() {
+  let client = TestClient::new_user_without_ws_conn().await;
+
+  let err = client.api_client.get_user_backup(None).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // The server stores the backup as is, whatever bytes it contains
+  let first_backup = vec![0u8, 159, 146, 150, 255, 1];
+  let info = client
+    .api_client
+    .put_user_backup(first_backup.clone(), Some(0))
+    .await
+    .unwrap();
+  assert_eq!(info.version, 1);
+  assert_eq!(info.size, first_backup.len() as i64);
+
+  // A new device of the same user recovers it
+  let new_device = TestClient::user_with_new_device(client.user.clone()).await;
+  let backup = new_device
+    .api_client
+    .get_user_backup(None)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(backup.version, 1);
+  assert_eq!(backup.data, first_backup);
+  // and only downloads it again once it changed
+  assert!(new_device
+    .api_client
+    .get_user_backup(Some(1))
+    .await
+    .unwrap()
+    .is_none());
+
+  let second_backup = vec![7u8; 1024];
+  let info = new_device
+    .api_client
+    .put_user_backup(second_backup.clone(), Some(1))
+    .await
+    .unwrap();
+  assert_eq!(info.version, 2);
+
+  // The first device can't overwrite the backup written by the new device since it read it
+  let err = client
+    .api_client
+    .put_user_backup(vec![1, 2, 3], Some(1))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::OverrideWithIncorrectData);
+  let err = client
+    .api_client
+    .put_user_backup(vec![1, 2, 3], Some(0))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::OverrideWithIncorrectData);
+  let backup = client
+    .api_client
+    .get_user_backup(Some(1))
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(backup.version, 2);
+  assert_eq!(backup.data, second_backup);
+
+  // Without an expected version, the backup is replaced
+  let info = client
+    .api_client
+    .put_user_backup(vec![1, 2, 3], None)
+    .await
+    .unwrap();
+  assert_eq!(info.version, 3);
+}
+
+#[tokio::test]
+async fn reject_empty_and_oversized_backup() {
+  let client = TestClient::new_user_without_ws_conn().await;
+
+  let err = client
+    .api_client
+    .put_user_backup(vec![], None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let err = client
+    .api_client
+    .put_user_backup(vec![0u8; 1024 * 1024 + 1], None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PayloadTooLarge);
+}
@@ -1,8 +1,13 @@
+mod api_key;
+mod backup;
 mod delete;
 mod image;
+mod notification;
+mod push_token;
 mod refresh;
 mod sign_in;
 mod sign_out;
 mod sign_up;
+mod subscription;
 mod update;
 mod user_awareness_test;
@@ -0,0 +1,95 @@
+use app_error::ErrorCode;
+use client_api::entity::{ListUserNotificationsQuery, UserNotification, WorkspaceInviteCodeParams};
+use client_api::notifications::{NotificationEvent, NotificationFeed};
+use client_api_test::generate_unique_registered_user_client;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn notification_read_state_is_shared_between_feeds() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner_client.get_workspaces().await.unwrap()[0].workspace_id;
+  let invitation_code = owner_client
+    .create_workspace_invitation_code(
+      &workspace_id,
+      &WorkspaceInviteCodeParams {
+        validity_period_hours: None,
+      },
+    )
+    .await
+    .unwrap()
+    .code
+    .unwrap();
+  let (member_client, _) = generate_unique_registered_user_client().await;
+  member_client
+    .join_workspace_by_invitation_code(&invitation_code)
+    .await
+    .unwrap();
+
+  let feed = NotificationFeed::new(member_client.clone());
+  let events = feed.refresh().await.unwrap();
+  let joined = events
+    .iter()
+    .find_map(|event| match event {
+      NotificationEvent::Received(notification)
+        if notification.notification_type == "workspace_joined_via_code" =>
+      {
+        Some(notification.clone())
+      },
+      _ => None,
+    })
+    .unwrap();
+  assert!(!joined.read);
+  assert_eq!(joined.workspace_id, Some(workspace_id));
+  // loading the same notifications again changes nothing
+  assert!(feed.refresh().await.unwrap().is_empty());
+
+  feed.mark_all_read().await.unwrap();
+  assert_eq!(feed.unread_count(), 0);
+
+  // the feed of another device of the member
+  let other_feed = NotificationFeed::new(member_client.clone());
+  other_feed.refresh().await.unwrap();
+  assert!(other_feed
+    .notifications()
+    .iter()
+    .all(|notification| notification.read));
+
+  let page = member_client
+    .list_inbox_notifications(&ListUserNotificationsQuery {
+      before: Some(joined.created_at),
+      limit: Some(10),
+    })
+    .await
+    .unwrap();
+  assert!(page.notifications.iter().all(|n| n.id != joined.id));
+
+  // notifications sent to every member of the workspace are read by each member separately
+  let member_joined = |notifications: &[UserNotification]| {
+    notifications
+      .iter()
+      .find(|n| n.notification_type == "workspace_member_joined")
+      .cloned()
+      .unwrap()
+  };
+  let query = ListUserNotificationsQuery::default();
+  let member_page = member_client
+    .list_inbox_notifications(&query)
+    .await
+    .unwrap();
+  assert!(member_joined(&member_page.notifications).read);
+  let owner_page = owner_client.list_inbox_notifications(&query).await.unwrap();
+  let owner_member_joined = member_joined(&owner_page.notifications);
+  assert!(!owner_member_joined.read);
+  owner_client
+    .mark_notifications_read(vec![owner_member_joined.id])
+    .await
+    .unwrap();
+  let owner_page = owner_client.list_inbox_notifications(&query).await.unwrap();
+  assert!(member_joined(&owner_page.notifications).read);
+
+  let err = member_client
+    .mark_notifications_read((0..501).map(|_| Uuid::new_v4()).collect())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
@@ -0,0 +1,104 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::{PushPlatform, RegisterPushTokenParams, SetQuietHoursParams};
+
+#[tokio::test]
+async fn register_and_unregister_push_token() {
+  let client = TestClient::new_user_without_ws_conn().await;
+
+  let err = client
+    .api_client
+    .register_push_token(&RegisterPushTokenParams {
+      platform: PushPlatform::Apns,
+      token: "  ".to_string(),
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  client
+    .api_client
+    .register_push_token(&RegisterPushTokenParams {
+      platform: PushPlatform::Apns,
+      token: "apns-token".to_string(),
+    })
+    .await
+    .unwrap();
+  // The device replaces its token, and another account on the same phone takes it over
+  client
+    .api_client
+    .register_push_token(&RegisterPushTokenParams {
+      platform: PushPlatform::Fcm,
+      token: "fcm-token".to_string(),
+    })
+    .await
+    .unwrap();
+  let other_client = TestClient::new_user_without_ws_conn().await;
+  other_client
+    .api_client
+    .register_push_token(&RegisterPushTokenParams {
+      platform: PushPlatform::Fcm,
+      token: "fcm-token".to_string(),
+    })
+    .await
+    .unwrap();
+
+  client.api_client.unregister_push_token().await.unwrap();
+  client.api_client.unregister_push_token().await.unwrap();
+}
+
+#[tokio::test]
+async fn set_and_remove_quiet_hours() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let params = SetQuietHoursParams {
+    start_minute: 22 * 60,
+    end_minute: 7 * 60,
+    timezone: "Asia/Shanghai".to_string(),
+    allow_mentions: true,
+  };
+  let quiet_hours = client
+    .api_client
+    .set_quiet_hours("phone", &params)
+    .await
+    .unwrap();
+  assert_eq!(quiet_hours.device_id, "phone");
+  let list = client.api_client.list_quiet_hours().await.unwrap();
+  assert_eq!(list.quiet_hours, vec![quiet_hours]);
+
+  for invalid in [
+    SetQuietHoursParams {
+      timezone: "Mars/Olympus".to_string(),
+      ..params.clone()
+    },
+    SetQuietHoursParams {
+      end_minute: 24 * 60,
+      ..params.clone()
+    },
+    SetQuietHoursParams {
+      end_minute: params.start_minute,
+      ..params.clone()
+    },
+  ] {
+    let err = client
+      .api_client
+      .set_quiet_hours("phone", &invalid)
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest);
+  }
+
+  client.api_client.remove_quiet_hours("phone").await.unwrap();
+  assert!(client
+    .api_client
+    .list_quiet_hours()
+    .await
+    .unwrap()
+    .quiet_hours
+    .is_empty());
+  let err = client
+    .api_client
+    .remove_quiet_hours("phone")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
@@ -0,0 +1,62 @@
+use client_api_test::TestClient;
+use shared_entity::dto::subscription_dto::{
+  BillingType, CancelSubscriptionRequest, SubscribeRequest, SubscriptionStatus,
+  SubscriptionUsageQuery, UsageRecordRequest, UsageType,
+};
+
+#[tokio::test]
+async fn subscribe_record_usage_and_cancel() {
+  let client = TestClient::new_user_without_ws_conn().await;
+
+  let plans = client.api_client.get_subscription_plans().await.unwrap();
+  assert!(!plans.is_empty());
+
+  // users without a subscription are put on the free plan
+  let current = client.api_client.get_current_subscription().await.unwrap();
+  assert_eq!(current.subscription.status, SubscriptionStatus::Active);
+  assert_eq!(current.usage.ai_chat_used_this_month, 0);
+
+  client
+    .api_client
+    .record_subscription_usage(&UsageRecordRequest {
+      usage_type: UsageType::AiChat,
+      usage_count: 2,
+      usage_date: None,
+    })
+    .await
+    .unwrap();
+  let usage = client
+    .api_client
+    .get_subscription_usage(&SubscriptionUsageQuery {
+      start_date: None,
+      end_date: None,
+    })
+    .await
+    .unwrap();
+  assert_eq!(usage.current_usage.ai_chat_used_this_month, 2);
+
+  let plan = plans
+    .iter()
+    .find(|plan| plan.is_active && plan.id != current.plan_details.id)
+    .unwrap();
+  let subscribed = client
+    .api_client
+    .subscribe_plan(&SubscribeRequest {
+      plan_id: plan.id,
+      billing_type: BillingType::Monthly,
+    })
+    .await
+    .unwrap();
+  assert_eq!(subscribed.plan_details.id, plan.id);
+  assert_eq!(subscribed.subscription.billing_type, BillingType::Monthly);
+
+  let canceled = client
+    .api_client
+    .cancel_user_subscription(&CancelSubscriptionRequest {
+      reason: Some("too expensive".to_string()),
+    })
+    .await
+    .unwrap();
+  assert_eq!(canceled.status, SubscriptionStatus::Canceled);
+  assert_eq!(canceled.cancel_reason.as_deref(), Some("too expensive"));
+}
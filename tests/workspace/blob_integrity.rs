@@ -0,0 +1,19 @@
+use client_api_test::generate_unique_registered_user_client;
+
+#[tokio::test]
+async fn scan_workspace_blob_integrity() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+
+  // no report until the workspace has been scanned
+  assert!(c.get_blob_integrity_report(&workspace_id).await.is_err());
+
+  let report = c.scan_blob_integrity(&workspace_id).await.unwrap();
+  assert_eq!(report.workspace_id, workspace_id);
+  assert!(report.scanned_documents > 0);
+  assert!(report.missing_blobs.is_empty());
+  assert!(report.lost_blobs.is_empty());
+
+  let latest = c.get_blob_integrity_report(&workspace_id).await.unwrap();
+  assert_eq!(latest.scanned_at, report.scanned_at);
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use client_api_test::generate_unique_registered_user_client;
+use database_entity::dto::AFWorkspaceSettingsChange;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  AppendBlockToPageParams, CreatePageParams, DeadReferenceKind, ViewLayout,
+};
+use tokio::time::sleep;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn scan_and_annotate_dead_references() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+
+  // no report until the workspace has been scanned
+  assert!(c.get_dead_reference_report(&workspace_id).await.is_err());
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let getting_started = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  let linked_page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id,
+        layout: ViewLayout::Document,
+        name: Some("Linked page".to_string()),
+        page_data: None,
+        view_id: None,
+        collab_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let removed_person_id = Uuid::new_v4();
+  c.append_block_to_page(
+    workspace_id,
+    &getting_started.view_id,
+    &AppendBlockToPageParams {
+      blocks: vec![json!({
+        "type": "paragraph",
+        "data": {
+          "delta": [
+            { "insert": "See " },
+            {
+              "insert": "$",
+              "attributes": {
+                "mention": { "type": "page", "page_id": linked_page.view_id.to_string() }
+              }
+            },
+            { "insert": " and ask " },
+            {
+              "insert": "$",
+              "attributes": {
+                "mention": { "type": "person", "person_id": removed_person_id.to_string() }
+              }
+            }
+          ]
+        }
+      })],
+    },
+  )
+  .await
+  .unwrap();
+  c.move_workspace_page_view_to_trash(workspace_id, &linked_page.view_id)
+    .await
+    .unwrap();
+  c.update_workspace_settings(
+    workspace_id.to_string(),
+    &AFWorkspaceSettingsChange::new().auto_annotate_dead_references(true),
+  )
+  .await
+  .unwrap();
+  sleep(Duration::from_secs(2)).await;
+
+  let report = c.scan_dead_references(&workspace_id).await.unwrap();
+  assert_eq!(report.workspace_id, workspace_id);
+  assert_eq!(report.dead_references.len(), 2);
+  assert_eq!(report.annotated, 2);
+  for reference in &report.dead_references {
+    assert_eq!(reference.object_id, getting_started.view_id);
+    if reference.target_id == linked_page.view_id {
+      assert_eq!(reference.kind, DeadReferenceKind::TrashedPage);
+    } else {
+      assert_eq!(reference.target_id, removed_person_id);
+      assert_eq!(reference.kind, DeadReferenceKind::RemovedMember);
+    }
+  }
+
+  // annotated mentions are still reported, but not annotated twice
+  sleep(Duration::from_secs(2)).await;
+  let report = c.scan_dead_references(&workspace_id).await.unwrap();
+  assert_eq!(report.dead_references.len(), 2);
+  assert_eq!(report.annotated, 0);
+
+  let latest = c.get_dead_reference_report(&workspace_id).await.unwrap();
+  assert_eq!(latest.scanned_at, report.scanned_at);
+}
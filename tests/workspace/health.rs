@@ -0,0 +1,51 @@
+use app_error::ErrorCode;
+use client_api::entity::AFRole;
+use client_api_test::TestClient;
+
+#[tokio::test]
+async fn owner_reads_workspace_health() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // the first read computes the report
+  let report = owner
+    .api_client
+    .get_workspace_health(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(report.workspace_id, workspace_id);
+  assert!(report.total_documents > 0);
+  assert_eq!(report.stale_documents, 0);
+  assert_eq!(report.unused_guests, 0);
+  let penalty: u32 = report
+    .recommendations
+    .iter()
+    .map(|recommendation| recommendation.penalty as u32)
+    .sum();
+  assert_eq!(report.score as u32, 100u32.saturating_sub(penalty));
+
+  let recomputed = owner
+    .api_client
+    .compute_workspace_health(&workspace_id)
+    .await
+    .unwrap();
+  assert!(recomputed.computed_at > report.computed_at);
+  let stored = owner
+    .api_client
+    .get_workspace_health(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(stored.computed_at, recomputed.computed_at);
+
+  let err = member
+    .api_client
+    .get_workspace_health(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
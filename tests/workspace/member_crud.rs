@@ -1,7 +1,7 @@
 use app_error::ErrorCode;
 use client_api::entity::AFWorkspaceInvitationStatus;
 use client_api_test::{api_client_with_email, TestClient};
-use database_entity::dto::AFRole;
+use database_entity::dto::{AFRole, CollabPermissionSyncStatus};
 use shared_entity::dto::workspace_dto::WorkspaceMemberInvitation;
 
 #[tokio::test]
@@ -435,3 +435,106 @@ async fn workspace_member_through_user_id() {
 
   assert_ne!(owner_member.role, member_1_member.role);
 }
+
+#[tokio::test]
+async fn export_workspace_members_as_csv() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member_1 = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member_1, AFRole::Member)
+    .await
+    .unwrap();
+  let invitee_email = format!("{}@appflowy.io", uuid::Uuid::new_v4());
+  owner
+    .api_client
+    .invite_workspace_members(
+      &workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: invitee_email.clone(),
+        role: AFRole::Guest,
+        skip_email_send: true,
+        ..Default::default()
+      }],
+    )
+    .await
+    .unwrap();
+
+  let csv = owner
+    .api_client
+    .export_workspace_members(&workspace_id)
+    .await
+    .unwrap();
+  let csv = String::from_utf8(csv.to_vec()).unwrap();
+  let lines: Vec<&str> = csv.lines().collect();
+  assert_eq!(
+    lines[0],
+    "status,name,email,role,joined_at,last_active_at,invited_at,invited_by"
+  );
+  assert_eq!(lines.len(), 4, "{}", csv);
+  assert!(lines[1].starts_with("member,"));
+  assert!(lines[1].contains(&owner.email().await));
+  assert!(lines[1].contains(",owner,"));
+  assert!(lines[2].contains(&member_1.email().await));
+  assert!(lines[2].contains(",member,"));
+  assert!(lines[3].starts_with(&format!("pending_invitation,,{},guest,", invitee_email)));
+
+  // Only the owner can export the members
+  let err = member_1
+    .api_client
+    .export_workspace_members(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn collab_permissions_are_synced_after_role_change() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member_uid = member.uid().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let err = owner
+    .api_client
+    .get_member_collab_permission_sync(&workspace_id, member_uid)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  owner
+    .try_update_workspace_member(&workspace_id, &member, AFRole::Guest)
+    .await
+    .unwrap();
+
+  let mut sync = None;
+  for _ in 0..20 {
+    let current = owner
+      .api_client
+      .get_member_collab_permission_sync(&workspace_id, member_uid)
+      .await
+      .unwrap();
+    if current.status != CollabPermissionSyncStatus::Running {
+      sync = Some(current);
+      break;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+  }
+  let sync = sync.expect("collab permission sync did not finish");
+  assert_eq!(sync.status, CollabPermissionSyncStatus::Succeeded);
+  assert_eq!(sync.role, AFRole::Guest);
+  assert_eq!(sync.synced_collabs, sync.total_collabs);
+  assert!(sync.finished_at.is_some());
+
+  // Only the owner can follow the sync
+  let err = member
+    .api_client
+    .get_member_collab_permission_sync(&workspace_id, member_uid)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
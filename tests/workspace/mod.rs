@@ -1,6 +1,9 @@
 mod access_request;
+mod blob_integrity;
+mod dead_reference;
 mod default_user_workspace;
 mod edit_workspace;
+mod health;
 mod import_test;
 mod invitation_crud;
 mod join_workspace;
@@ -10,7 +13,10 @@ mod person;
 mod publish;
 mod published_data;
 mod quick_note;
+mod scheduled_export;
+mod snippet;
 mod template;
+mod view_slug;
 mod workspace_crud;
 mod workspace_folder;
 mod workspace_settings;
@@ -1,6 +1,7 @@
 use std::{collections::HashSet, time::Duration};
 
-use client_api::entity::{QueryCollab, QueryCollabParams};
+use app_error::ErrorCode;
+use client_api::entity::{PageActivityKind, QueryCollab, QueryCollabParams, UpdateCollabWebParams};
 use client_api_test::{
   generate_unique_registered_user, generate_unique_registered_user_client, TestClient,
 };
@@ -8,15 +9,20 @@ use collab::core::origin::CollabClient;
 use collab_entity::CollabType;
 use collab_folder::{CollabOrigin, Folder};
 use serde_json::{json, Value};
+use shared_entity::dto::publish_dto::{
+  PublishDatabaseData, PublishRowFilter, PublishRowFilterCondition,
+};
 use shared_entity::dto::workspace_dto::{
   AddRecentPagesParams, AppendBlockToPageParams, CreateFolderViewParams,
   CreatePageDatabaseViewParams, CreatePageParams, CreateSpaceParams, DuplicatePageParams,
-  FavoritePageParams, IconType, MovePageParams, PublishPageParams, SpacePermission,
-  UpdatePageExtraParams, UpdatePageIconParams, UpdatePageNameParams, UpdatePageParams,
-  UpdateSpaceParams, ViewIcon, ViewLayout,
+  FavoritePageParams, IconType, MovePageParams, PageReactionCount, PageReactionParams,
+  PublishPageParams, QueryDocumentChunk, QueryPageActivity, QueryTrashViews,
+  ReorderPageViewsParams, SpacePermission, UpdatePageExtraParams, UpdatePageIconParams,
+  UpdatePageNameParams, UpdatePageParams, UpdateSpaceParams, ViewIcon, ViewLayout,
 };
 use tokio::time::sleep;
 use uuid::Uuid;
+use yrs::{Map, ReadTxn, StateVector, Transact};
 
 async fn get_latest_folder(test_client: &TestClient, workspace_id: &Uuid) -> Folder {
   // Wait for websocket updates
@@ -81,6 +87,89 @@ async fn get_page_view() {
   assert_eq!(resp.data.row_data.len(), 0);
 }
 
+#[tokio::test]
+async fn batch_get_page_views() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let todo_list_view_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id;
+  let getting_started_view_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap()
+    .view_id;
+  let missing_view_id = Uuid::new_v4();
+  let items = c
+    .batch_get_workspace_page_views(
+      workspace_id,
+      vec![todo_list_view_id, getting_started_view_id, missing_view_id],
+    )
+    .await
+    .unwrap();
+  assert_eq!(items.len(), 3);
+  assert_eq!(items[0].view_id, todo_list_view_id);
+  assert_eq!(
+    items[0].page_collab.as_ref().unwrap().data.row_data.len(),
+    5
+  );
+  assert_eq!(items[1].view_id, getting_started_view_id);
+  assert!(items[1].page_collab.is_some());
+  // A view that cannot be loaded does not fail the rest of the batch
+  assert_eq!(items[2].view_id, missing_view_id);
+  assert!(items[2].page_collab.is_none());
+  assert!(items[2].error.is_some());
+}
+
+#[tokio::test]
+async fn watch_and_unwatch_page() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let view_id = folder_view.children[0].children[0].view_id;
+  let status = c
+    .get_page_watch_status(workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert!(!status.watching);
+
+  let status = c.watch_page(workspace_id, &view_id).await.unwrap();
+  assert!(status.watching);
+  // watching twice is a no-op
+  c.watch_page(workspace_id, &view_id).await.unwrap();
+  let status = c
+    .get_page_watch_status(workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert!(status.watching);
+
+  let status = c.unwatch_page(workspace_id, &view_id).await.unwrap();
+  assert!(!status.watching);
+  let status = c
+    .get_page_watch_status(workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert!(!status.watching);
+}
+
 #[tokio::test]
 async fn create_new_page_with_database() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -343,6 +432,103 @@ async fn append_block_to_page() {
   .unwrap();
 }
 
+#[tokio::test]
+async fn get_page_view_chunk() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let getting_started = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  let chunk = c
+    .get_workspace_page_view_chunk(
+      workspace_id,
+      &getting_started.view_id,
+      &QueryDocumentChunk {
+        offset: Some(0),
+        limit: Some(1),
+      },
+    )
+    .await
+    .unwrap();
+  // The getting started document is small, so it is returned as a single chunk
+  assert!(!chunk.is_large);
+  assert!(!chunk.has_more);
+  assert_eq!(chunk.blocks.len() as u32, chunk.total_blocks);
+  assert!(chunk
+    .toc
+    .iter()
+    .all(|item| item.block_index < chunk.total_blocks));
+}
+
+#[tokio::test]
+async fn get_page_view_outline() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let getting_started = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  let outline = c
+    .get_workspace_page_view_outline(workspace_id, &getting_started.view_id)
+    .await
+    .unwrap();
+  assert_eq!(outline.view_id, getting_started.view_id);
+  assert!(!outline.headings.is_empty());
+
+  // Every heading of the table of contents appears exactly once in the outline
+  let chunk = c
+    .get_workspace_page_view_chunk(
+      workspace_id,
+      &getting_started.view_id,
+      &QueryDocumentChunk::default(),
+    )
+    .await
+    .unwrap();
+  let mut stack = outline.headings.iter().collect::<Vec<_>>();
+  let mut block_ids = vec![];
+  let mut anchors = HashSet::new();
+  while let Some(heading) = stack.pop() {
+    block_ids.push(heading.block_id.clone());
+    assert!(anchors.insert(heading.anchor.clone()));
+    assert!(heading
+      .children
+      .iter()
+      .all(|child| child.level > heading.level));
+    stack.extend(heading.children.iter());
+  }
+  block_ids.sort();
+  let mut toc_block_ids = chunk
+    .toc
+    .into_iter()
+    .map(|item| item.block_id)
+    .collect::<Vec<_>>();
+  toc_block_ids.sort();
+  assert_eq!(block_ids, toc_block_ids);
+}
+
 #[tokio::test]
 async fn create_new_chat_page() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -453,6 +639,70 @@ async fn move_page_to_another_space() {
   assert_eq!(first_children_id, todo_view_id);
 }
 
+#[tokio::test]
+async fn reorder_pages_within_space() {
+  let registered_user = generate_unique_registered_user().await;
+  let mut app_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let web_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = app_client.workspace_id().await;
+  app_client.open_workspace_collab(workspace_id).await;
+  app_client
+    .wait_object_sync_complete(&workspace_id)
+    .await
+    .unwrap();
+  let folder_view = web_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .clone();
+  let mut view_ids: Vec<String> = general_space
+    .children
+    .iter()
+    .map(|v| v.view_id.to_string())
+    .collect();
+  view_ids.reverse();
+  web_client
+    .api_client
+    .reorder_workspace_page_views(
+      workspace_id,
+      &ReorderPageViewsParams {
+        parent_view_id: general_space.view_id.to_string(),
+        view_ids: view_ids.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  let folder = get_latest_folder(&app_client, &workspace_id).await;
+  let children_ids: Vec<String> = folder
+    .get_view(&general_space.view_id.to_string(), web_client.uid().await)
+    .unwrap()
+    .children
+    .iter()
+    .map(|child| child.id.clone())
+    .collect();
+  assert_eq!(children_ids, view_ids);
+
+  // A partial list would silently drop the other children, so it is rejected
+  let err = web_client
+    .api_client
+    .reorder_workspace_page_views(
+      workspace_id,
+      &ReorderPageViewsParams {
+        parent_view_id: general_space.view_id.to_string(),
+        view_ids: view_ids[..1].to_vec(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
 #[tokio::test]
 async fn move_page_to_trash_then_restore() {
   let registered_user = generate_unique_registered_user().await;
@@ -548,6 +798,73 @@ async fn move_page_to_trash_then_restore() {
   assert!(!view_found);
 }
 
+#[tokio::test]
+async fn list_trash_by_page() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let uid = c.get_profile().await.unwrap().uid;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let general_space = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let view_ids = [
+    general_space.children[0].view_id,
+    general_space.children[1].view_id,
+  ];
+  for view_id in view_ids.iter() {
+    c.move_workspace_page_view_to_trash(workspace_id, view_id)
+      .await
+      .unwrap();
+    // deletion times are in seconds
+    sleep(Duration::from_millis(1100)).await;
+  }
+
+  let trash = c
+    .get_workspace_trash_page(
+      &workspace_id,
+      &QueryTrashViews {
+        offset: None,
+        limit: Some(1),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(trash.total, 2);
+  assert_eq!(trash.views.len(), 1);
+  let view = &trash.views[0];
+  // most recently deleted first
+  assert_eq!(view.view.view_id, view_ids[1]);
+  assert_eq!(view.deleted_by, Some(uid));
+  assert_eq!(
+    view
+      .parent_path
+      .iter()
+      .map(|v| v.view_id.clone())
+      .collect::<Vec<_>>(),
+    vec![general_space.view_id.to_string()]
+  );
+  assert!(view.size > 0);
+
+  let trash = c
+    .get_workspace_trash_page(
+      &workspace_id,
+      &QueryTrashViews {
+        offset: Some(1),
+        limit: Some(1),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(trash.views.len(), 1);
+  assert_eq!(trash.views[0].view.view_id, view_ids[0]);
+  let trash = c.get_workspace_trash(&workspace_id).await.unwrap();
+  assert_eq!(trash.views.len(), 2);
+}
+
 #[tokio::test]
 async fn move_page_with_child_to_trash_then_restore() {
   let registered_user = generate_unique_registered_user().await;
@@ -922,6 +1239,18 @@ async fn favorite_page() {
   let folder = get_latest_folder(&app_client, &workspace_id).await;
   let favorite_view = folder.get_view(&favorite_view_id.to_string(), uid).unwrap();
   assert!(favorite_view.is_favorite);
+
+  // the favorite list is served from the view metadata, which is synced by the request
+  let favorites = web_client
+    .api_client
+    .get_workspace_favorite(&workspace_id)
+    .await
+    .unwrap()
+    .views;
+  assert_eq!(favorites.len(), 1);
+  assert_eq!(favorites[0].view.view_id, favorite_view_id);
+  assert!(favorites[0].view.is_favorite);
+  assert!(favorites[0].is_pinned);
 }
 
 #[tokio::test]
@@ -1063,6 +1392,7 @@ async fn publish_page() {
           visible_database_view_ids: None,
           comments_enabled: None,
           duplicate_enabled: None,
+          row_filter: None,
         },
       )
       .await
@@ -1260,3 +1590,641 @@ async fn add_recent_pages() {
     .collect::<Vec<_>>();
   assert_eq!(recent_section_ids, child_view_ids)
 }
+
+#[tokio::test]
+async fn react_to_workspace_page() {
+  let registered_user = generate_unique_registered_user().await;
+  let mut app_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let web_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = app_client.workspace_id().await;
+  app_client.open_workspace_collab(workspace_id).await;
+  app_client
+    .wait_object_sync_complete(&workspace_id)
+    .await
+    .unwrap();
+  let folder_view = web_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .clone();
+  let view_id = general_space.children[0].view_id;
+  let thumbs_up = PageReactionParams {
+    reaction_type: "👍".to_string(),
+  };
+  for _ in 0..2 {
+    web_client
+      .api_client
+      .add_workspace_page_reaction(workspace_id, &view_id, &thumbs_up)
+      .await
+      .unwrap();
+  }
+  let reactions = web_client
+    .api_client
+    .get_workspace_page_reactions(workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    reactions,
+    vec![PageReactionCount {
+      reaction_type: "👍".to_string(),
+      count: 1,
+      reacted_by_me: true,
+    }]
+  );
+
+  let folder_view = web_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let page = folder_view
+    .children
+    .iter()
+    .flat_map(|space| space.children.iter())
+    .find(|v| v.view_id == view_id)
+    .unwrap();
+  assert_eq!(page.reactions, reactions);
+
+  web_client
+    .api_client
+    .remove_workspace_page_reaction(workspace_id, &view_id, &thumbs_up)
+    .await
+    .unwrap();
+  let error = web_client
+    .api_client
+    .remove_workspace_page_reaction(workspace_id, &view_id, &thumbs_up)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+  let reactions = web_client
+    .api_client
+    .get_workspace_page_reactions(workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert!(reactions.is_empty());
+}
+
+#[tokio::test]
+async fn trashed_page_is_unpublished() {
+  let registered_user = generate_unique_registered_user().await;
+  let web_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = web_client.workspace_id().await;
+  let folder_view = web_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let document_page_id = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap()
+    .view_id;
+  web_client
+    .api_client
+    .publish_page(
+      workspace_id,
+      &document_page_id,
+      &PublishPageParams {
+        publish_name: None,
+        visible_database_view_ids: None,
+        comments_enabled: None,
+        duplicate_enabled: None,
+        row_filter: None,
+      },
+    )
+    .await
+    .unwrap();
+  web_client
+    .api_client
+    .get_published_collab_info(&document_page_id)
+    .await
+    .unwrap();
+
+  web_client
+    .api_client
+    .move_workspace_page_view_to_trash(workspace_id, &document_page_id)
+    .await
+    .unwrap();
+  let error = web_client
+    .api_client
+    .get_published_collab_info(&document_page_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn page_activity_feed() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let user_uuid = c.get_profile().await.unwrap().uuid;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let view_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap()
+    .view_id;
+  let shared_space = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "Shared")
+    .unwrap();
+
+  c.move_workspace_page_view(
+    workspace_id,
+    &view_id,
+    &MovePageParams {
+      new_parent_view_id: shared_space.view_id.to_string(),
+      prev_view_id: None,
+    },
+  )
+  .await
+  .unwrap();
+  c.publish_page(
+    workspace_id,
+    &view_id,
+    &PublishPageParams {
+      publish_name: None,
+      visible_database_view_ids: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      row_filter: None,
+    },
+  )
+  .await
+  .unwrap();
+  c.unpublish_page(workspace_id, &view_id).await.unwrap();
+
+  let feed = c
+    .get_workspace_page_activity(workspace_id, &view_id, &QueryPageActivity::default())
+    .await
+    .unwrap();
+  let recorded: Vec<_> = feed
+    .activities
+    .iter()
+    .filter(|activity| activity.kind != PageActivityKind::Edited)
+    .collect();
+  let kinds: Vec<_> = recorded.iter().map(|activity| activity.kind).collect();
+  assert_eq!(
+    kinds,
+    vec![
+      PageActivityKind::Unpublished,
+      PageActivityKind::Published,
+      PageActivityKind::Moved
+    ]
+  );
+  assert!(recorded
+    .iter()
+    .all(|activity| activity.actor.as_ref().map(|actor| actor.uuid) == Some(user_uuid)));
+  assert_eq!(
+    recorded[2].details["new_parent_view_id"],
+    json!(shared_space.view_id.to_string())
+  );
+
+  // The feed is paginated by the time of the oldest activity received
+  let first_page = c
+    .get_workspace_page_activity(
+      workspace_id,
+      &view_id,
+      &QueryPageActivity {
+        before: None,
+        limit: Some(1),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(first_page.activities.len(), 1);
+  assert!(first_page.has_more);
+  let second_page = c
+    .get_workspace_page_activity(
+      workspace_id,
+      &view_id,
+      &QueryPageActivity {
+        before: Some(first_page.activities[0].occurred_at),
+        limit: Some(1),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(second_page.activities.len(), 1);
+  assert!(second_page.activities[0].occurred_at < first_page.activities[0].occurred_at);
+
+  // Only users with access to the page can read its activity
+  let (other, _) = generate_unique_registered_user_client().await;
+  let error = other
+    .get_workspace_page_activity(workspace_id, &view_id, &QueryPageActivity::default())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn archive_and_unarchive_space() {
+  let registered_user = generate_unique_registered_user().await;
+  let app_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = app_client.workspace_id().await;
+  let folder_view = app_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let child_view_ids: Vec<_> = general_space.children.iter().map(|v| v.view_id).collect();
+  app_client
+    .api_client
+    .add_recent_pages(
+      workspace_id,
+      &AddRecentPagesParams {
+        recent_view_ids: child_view_ids.iter().map(|id| id.to_string()).collect(),
+      },
+    )
+    .await
+    .unwrap();
+
+  // Only spaces can be archived
+  let err = app_client
+    .api_client
+    .archive_space(workspace_id, &child_view_ids[0])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let archived_space = app_client
+    .api_client
+    .archive_space(workspace_id, &general_space.view_id)
+    .await
+    .unwrap();
+  assert_eq!(archived_space.space_id, general_space.view_id);
+  let err = app_client
+    .api_client
+    .archive_space(workspace_id, &general_space.view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordAlreadyExists);
+  let archived_spaces = app_client
+    .api_client
+    .list_archived_spaces(workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(archived_spaces.len(), 1);
+
+  // Writes to the pages of the archived space are rejected on the HTTP path too
+  let web_doc = yrs::Doc::new();
+  let doc_data = web_doc.get_or_insert_map("data");
+  {
+    let mut txn = web_doc.transact_mut();
+    doc_data.insert(&mut txn, "paragraph", "content");
+  }
+  let err = app_client
+    .api_client
+    .update_web_collab(
+      &workspace_id,
+      &child_view_ids[0],
+      UpdateCollabWebParams {
+        doc_state: web_doc
+          .transact()
+          .encode_state_as_update_v1(&StateVector::default()),
+        collab_type: CollabType::Document,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let folder_view = app_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  assert!(general_space
+    .children
+    .iter()
+    .all(|v| v.is_locked == Some(true)));
+  let recent = app_client
+    .api_client
+    .get_workspace_recent(&workspace_id)
+    .await
+    .unwrap();
+  assert!(recent.views.is_empty());
+
+  app_client
+    .api_client
+    .unarchive_space(workspace_id, &general_space.view_id)
+    .await
+    .unwrap();
+  let folder_view = app_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  assert!(general_space
+    .children
+    .iter()
+    .all(|v| v.is_locked != Some(true)));
+  let recent = app_client
+    .api_client
+    .get_workspace_recent(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(recent.views.len(), child_view_ids.len());
+  let err = app_client
+    .api_client
+    .unarchive_space(workspace_id, &general_space.view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn update_visible_views_of_published_database() {
+  let registered_user = generate_unique_registered_user().await;
+  let web_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = web_client.workspace_id().await;
+  let folder_view = web_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let database_page_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id;
+  web_client
+    .api_client
+    .create_database_view(
+      workspace_id,
+      &database_page_id,
+      &CreatePageDatabaseViewParams {
+        layout: ViewLayout::Grid,
+        name: Some("Grid View".to_string()),
+      },
+    )
+    .await
+    .unwrap();
+  web_client
+    .api_client
+    .publish_page(
+      workspace_id,
+      &database_page_id,
+      &PublishPageParams {
+        publish_name: None,
+        visible_database_view_ids: None,
+        comments_enabled: None,
+        duplicate_enabled: None,
+        row_filter: None,
+      },
+    )
+    .await
+    .unwrap();
+
+  let views = web_client
+    .api_client
+    .get_published_database_views(&workspace_id, &database_page_id)
+    .await
+    .unwrap();
+  assert_eq!(views.visible_database_view_ids, vec![database_page_id]);
+  let grid_view_id = *views
+    .database_view_ids
+    .iter()
+    .find(|id| **id != database_page_id)
+    .unwrap();
+
+  let views = web_client
+    .api_client
+    .update_published_database_views(
+      &workspace_id,
+      &database_page_id,
+      vec![database_page_id, grid_view_id],
+    )
+    .await
+    .unwrap();
+  assert_eq!(
+    views.visible_database_view_ids,
+    vec![database_page_id, grid_view_id]
+  );
+  let views = web_client
+    .api_client
+    .get_published_database_views(&workspace_id, &database_page_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    views.visible_database_view_ids,
+    vec![database_page_id, grid_view_id]
+  );
+
+  // The published view stays visible, and other databases cannot be made visible
+  let err = web_client
+    .api_client
+    .update_published_database_views(&workspace_id, &database_page_id, vec![grid_view_id])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = web_client
+    .api_client
+    .update_published_database_views(
+      &workspace_id,
+      &database_page_id,
+      vec![database_page_id, Uuid::new_v4()],
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn publish_database_with_row_filter() {
+  let registered_user = generate_unique_registered_user().await;
+  let web_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = web_client.workspace_id().await;
+  let folder_view = web_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let database_page_id = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap()
+    .view_id;
+  let publish_params = |row_filter: Option<PublishRowFilter>| PublishPageParams {
+    publish_name: None,
+    visible_database_view_ids: None,
+    comments_enabled: None,
+    duplicate_enabled: None,
+    row_filter,
+  };
+  let doing = PublishRowFilter {
+    field: "Status".to_string(),
+    condition: PublishRowFilterCondition::Is,
+    value: "Doing".to_string(),
+  };
+
+  // Filtering on a field the database does not have is rejected
+  let err = web_client
+    .api_client
+    .publish_page(
+      workspace_id,
+      &database_page_id,
+      &publish_params(Some(PublishRowFilter {
+        field: "Visibility".to_string(),
+        ..doing.clone()
+      })),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  web_client
+    .api_client
+    .publish_page(
+      workspace_id,
+      &database_page_id,
+      &publish_params(Some(doing.clone())),
+    )
+    .await
+    .unwrap();
+  let published_row_count = || async {
+    let info = web_client
+      .api_client
+      .get_published_collab_info(&database_page_id)
+      .await
+      .unwrap();
+    let blob = web_client
+      .api_client
+      .get_published_collab_blob(&info.namespace, &info.publish_name)
+      .await
+      .unwrap();
+    serde_json::from_slice::<PublishDatabaseData>(&blob)
+      .unwrap()
+      .database_row_collabs
+      .len()
+  };
+  assert_eq!(published_row_count().await, 1);
+
+  // Republishing keeps the stored filter
+  web_client
+    .api_client
+    .publish_page(workspace_id, &database_page_id, &publish_params(None))
+    .await
+    .unwrap();
+  assert_eq!(published_row_count().await, 1);
+  let filter = web_client
+    .api_client
+    .get_published_row_filter(&workspace_id, &database_page_id)
+    .await
+    .unwrap();
+  assert_eq!(filter, doing);
+
+  web_client
+    .api_client
+    .delete_published_row_filter(&workspace_id, &database_page_id)
+    .await
+    .unwrap();
+  web_client
+    .api_client
+    .publish_page(workspace_id, &database_page_id, &publish_params(None))
+    .await
+    .unwrap();
+  assert_eq!(published_row_count().await, 5);
+  let err = web_client
+    .api_client
+    .delete_published_row_filter(&workspace_id, &database_page_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn create_page_beyond_max_view_depth() {
+  // Default of APPFLOWY_COLLAB_MAX_VIEW_DEPTH.
+  const MAX_VIEW_DEPTH: usize = 64;
+
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+
+  let new_page = |parent_view_id: Uuid| CreatePageParams {
+    parent_view_id,
+    layout: ViewLayout::Document,
+    name: Some("Nested".to_string()),
+    page_data: None,
+    view_id: None,
+    collab_id: None,
+  };
+  // The space is at depth 1.
+  let mut grandparent_view_id = workspace_id;
+  let mut parent_view_id = general_space.view_id;
+  for _ in 1..MAX_VIEW_DEPTH {
+    let page = c
+      .create_workspace_page_view(workspace_id, &new_page(parent_view_id))
+      .await
+      .unwrap();
+    grandparent_view_id = parent_view_id;
+    parent_view_id = page.view_id;
+  }
+  let err = c
+    .create_workspace_page_view(workspace_id, &new_page(parent_view_id))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::CollabStructureLimitExceeded);
+
+  // Pages can still be added next to the deepest one.
+  c.create_workspace_page_view(workspace_id, &new_page(grandparent_view_id))
+    .await
+    .unwrap();
+}
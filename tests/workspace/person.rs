@@ -1,7 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use client_api::entity::{AFRole, PageMentionUpdate, WorkspaceMemberProfile};
+use app_error::ErrorCode;
+use client_api::entity::{
+  AFRole, CreateWorkspaceMemberFieldParams, PageMentionUpdate, QueryWorkspaceDirectory,
+  WorkspaceDirectoryPerson, WorkspaceMemberProfile, WorkspaceMemberProfileLink,
+  WorkspaceMemberStatus,
+};
 use client_api_test::TestClient;
+use shared_entity::dto::workspace_dto::WorkspaceMemberChangeset;
 
 #[tokio::test]
 async fn workspace_mentionable_persons_crud() {
@@ -26,6 +32,7 @@ async fn workspace_mentionable_persons_crud() {
         cover_image_url: Some("cover image url".to_string()),
         custom_image_url: Some("custom image url".to_string()),
         description: Some("description override".to_string()),
+        ..Default::default()
       },
     )
     .await
@@ -120,3 +127,302 @@ async fn workspace_mentionable_persons_crud() {
     .can_access_page;
   assert!(owner_can_access);
 }
+
+#[tokio::test]
+async fn workspace_directory_search() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let guest = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+  let links = vec![WorkspaceMemberProfileLink {
+    label: "Blog".to_string(),
+    url: "https://example.com/blog".to_string(),
+  }];
+  member
+    .api_client
+    .update_workspace_member_profile(
+      &workspace_id,
+      &WorkspaceMemberProfile {
+        name: "directory member".to_string(),
+        title: Some("Staff Engineer".to_string()),
+        department: Some("Platform".to_string()),
+        pronouns: Some("they/them".to_string()),
+        links: links.clone(),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+
+  let directory = owner
+    .api_client
+    .get_workspace_directory(&workspace_id, &QueryWorkspaceDirectory::default())
+    .await
+    .unwrap();
+  assert_eq!(directory.total, 3);
+  assert_eq!(directory.people.len(), 3);
+
+  let directory = owner
+    .api_client
+    .get_workspace_directory(
+      &workspace_id,
+      &QueryWorkspaceDirectory {
+        q: Some("platform".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(directory.total, 1);
+  let person = &directory.people[0];
+  assert_eq!(person.name, "directory member");
+  assert_eq!(person.title.as_deref(), Some("Staff Engineer"));
+  assert_eq!(person.pronouns.as_deref(), Some("they/them"));
+  assert_eq!(person.links, links);
+
+  // LIKE wildcards in the search term match only themselves. Every test email starts with
+  // "user_", so an unescaped "user__" would match them all.
+  for q in ["%", "user__", "\\"] {
+    let directory = owner
+      .api_client
+      .get_workspace_directory(
+        &workspace_id,
+        &QueryWorkspaceDirectory {
+          q: Some(q.to_string()),
+          ..Default::default()
+        },
+      )
+      .await
+      .unwrap();
+    assert_eq!(directory.total, 0);
+  }
+
+  // links must be web urls
+  let err = member
+    .api_client
+    .update_workspace_member_profile(
+      &workspace_id,
+      &WorkspaceMemberProfile {
+        name: "directory member".to_string(),
+        links: vec![WorkspaceMemberProfileLink {
+          label: "Script".to_string(),
+          url: "javascript:alert(1)".to_string(),
+        }],
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // guests can not browse the directory
+  let err = guest
+    .api_client
+    .get_workspace_directory(&workspace_id, &QueryWorkspaceDirectory::default())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn workspace_member_custom_fields() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let member_uid = member.uid().await;
+  let employee_id = owner
+    .api_client
+    .create_workspace_member_field(
+      &workspace_id,
+      &CreateWorkspaceMemberFieldParams {
+        name: "Employee ID".to_string(),
+        editable_by_member: false,
+      },
+    )
+    .await
+    .unwrap();
+  let team = owner
+    .api_client
+    .create_workspace_member_field(
+      &workspace_id,
+      &CreateWorkspaceMemberFieldParams {
+        name: "Team".to_string(),
+        editable_by_member: true,
+      },
+    )
+    .await
+    .unwrap();
+
+  // only owners define the fields
+  let err = member
+    .api_client
+    .create_workspace_member_field(
+      &workspace_id,
+      &CreateWorkspaceMemberFieldParams {
+        name: "Badge".to_string(),
+        editable_by_member: true,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let fields = member
+    .api_client
+    .list_workspace_member_fields(&workspace_id)
+    .await
+    .unwrap()
+    .fields;
+  assert_eq!(fields.len(), 2);
+
+  member
+    .api_client
+    .update_workspace_member_profile(
+      &workspace_id,
+      &WorkspaceMemberProfile {
+        name: "custom member".to_string(),
+        custom_fields: Some(HashMap::from([(team.field_id, "Sync".to_string())])),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  // members can't set the fields reserved to owners
+  let err = member
+    .api_client
+    .update_workspace_member_profile(
+      &workspace_id,
+      &WorkspaceMemberProfile {
+        name: "custom member".to_string(),
+        custom_fields: Some(HashMap::from([(employee_id.field_id, "E-1".to_string())])),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  owner
+    .api_client
+    .update_workspace_member(
+      &workspace_id,
+      WorkspaceMemberChangeset::new(member_uid)
+        .with_custom_fields(HashMap::from([(employee_id.field_id, "E-42".to_string())])),
+    )
+    .await
+    .unwrap();
+
+  let find_member = |people: Vec<WorkspaceDirectoryPerson>| {
+    people
+      .into_iter()
+      .find(|person| person.name == "custom member")
+      .unwrap()
+  };
+  let person = find_member(
+    owner
+      .api_client
+      .get_workspace_directory(&workspace_id, &QueryWorkspaceDirectory::default())
+      .await
+      .unwrap()
+      .people,
+  );
+  assert_eq!(
+    person.custom_fields,
+    HashMap::from([
+      (employee_id.field_id, "E-42".to_string()),
+      (team.field_id, "Sync".to_string()),
+    ])
+  );
+
+  // deleting a field removes its values
+  owner
+    .api_client
+    .delete_workspace_member_field(&workspace_id, &team.field_id)
+    .await
+    .unwrap();
+  let person = find_member(
+    owner
+      .api_client
+      .get_workspace_directory(&workspace_id, &QueryWorkspaceDirectory::default())
+      .await
+      .unwrap()
+      .people,
+  );
+  assert_eq!(
+    person.custom_fields,
+    HashMap::from([(employee_id.field_id, "E-42".to_string())])
+  );
+}
+
+#[tokio::test]
+async fn workspace_member_status_crud() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let member_uid = member.uid().await;
+  let status = WorkspaceMemberStatus {
+    emoji: Some("🌴".to_string()),
+    text: "On vacation".to_string(),
+    until: Some(chrono::Utc::now() + chrono::Duration::days(7)),
+  };
+  member
+    .api_client
+    .set_workspace_member_status(&workspace_id, &status)
+    .await
+    .unwrap();
+
+  let members = owner
+    .api_client
+    .get_workspace_members(&workspace_id)
+    .await
+    .unwrap();
+  let away = members.iter().find(|m| m.uid == member_uid).unwrap();
+  assert_eq!(away.status.as_ref().unwrap().text, "On vacation");
+  let persons = owner
+    .api_client
+    .list_workspace_mentionable_persons(&workspace_id)
+    .await
+    .unwrap()
+    .persons;
+  assert_eq!(persons.iter().filter(|p| p.status.is_some()).count(), 1);
+
+  // an expiry in the past is rejected
+  let err = member
+    .api_client
+    .set_workspace_member_status(
+      &workspace_id,
+      &WorkspaceMemberStatus {
+        until: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
+        ..status
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  member
+    .api_client
+    .clear_workspace_member_status(&workspace_id)
+    .await
+    .unwrap();
+  let members = owner
+    .api_client
+    .get_workspace_members(&workspace_id)
+    .await
+    .unwrap();
+  assert!(members.iter().all(|m| m.status.is_none()));
+}
@@ -3,8 +3,8 @@ use app_error::ErrorCode;
 use appflowy_cloud::biz::collab::folder_view::collab_folder_to_folder_view;
 use appflowy_cloud::biz::collab::utils::collab_from_doc_state;
 use client_api::entity::{
-  AFRole, GlobalComment, PatchPublishedCollab, PublishCollabItem, PublishCollabMetadata,
-  PublishInfoMeta,
+  AFRole, AFWorkspaceSettingsChange, GlobalComment, PatchPublishedCollab, PublishCollabItem,
+  PublishCollabMetadata, PublishInfoMeta, PublishPermission, UpdatePublishNamespace,
 };
 use client_api_test::TestClient;
 use client_api_test::{generate_unique_registered_user_client, localhost_client};
@@ -22,7 +22,8 @@ use collab_folder::{CollabOrigin, Folder};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use shared_entity::dto::auth_dto::UpdateUserParams;
-use shared_entity::dto::publish_dto::PublishDatabaseData;
+use shared_entity::dto::publish_dto::{PublishDatabaseData, PublishTheme};
+use shared_entity::dto::workspace_dto::PublishPageParams;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::thread::sleep;
@@ -377,6 +378,7 @@ async fn test_publish_doc() {
           publish_name: Some(publish_name_2.to_string()),
           comments_enabled: None,
           duplicate_enabled: None,
+          stats_digest_enabled: None,
         }],
       )
       .await
@@ -393,6 +395,7 @@ async fn test_publish_doc() {
         publish_name: Some(new_publish_name_1.to_string()),
         comments_enabled: None,
         duplicate_enabled: None,
+        stats_digest_enabled: None,
       }],
     )
     .await
@@ -422,6 +425,7 @@ async fn test_publish_doc() {
         publish_name: Some(publish_name_1.to_string()),
         comments_enabled: None,
         duplicate_enabled: None,
+        stats_digest_enabled: None,
       }],
     )
     .await
@@ -757,6 +761,48 @@ async fn test_excessive_comment_length() {
   assert_eq!(resp.unwrap_err().code, ErrorCode::StringLengthLimitReached);
 }
 
+#[tokio::test]
+async fn test_comment_on_view_with_comments_disabled() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&client).await;
+  let published_view_namespace = Uuid::new_v4().to_string();
+  client
+    .set_workspace_publish_namespace(&workspace_id, published_view_namespace)
+    .await
+    .unwrap();
+
+  let view_id = Uuid::new_v4();
+  client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "published-view".to_string(),
+          metadata: MyCustomMetadata {
+            title: "some_title".to_string(),
+          },
+        },
+        data: "yrs_encoded_data_1".as_bytes(),
+        comments_enabled: false,
+        duplicate_enabled: true,
+      }],
+    )
+    .await
+    .unwrap();
+
+  let err = client
+    .create_comment_on_published_view(&view_id, "comment", &None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = client
+    .create_comment_on_published_view(&Uuid::new_v4(), "comment", &None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
 #[tokio::test]
 async fn test_publish_reactions() {
   let (page_owner_client, _) = generate_unique_registered_user_client().await;
@@ -969,6 +1015,200 @@ async fn workspace_member_publish_unpublish() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn publish_restricted_to_owners_and_allowlist() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_settings(
+      workspace_id.to_string(),
+      &AFWorkspaceSettingsChange::new().publish_permission(PublishPermission::Owners),
+    )
+    .await
+    .unwrap();
+
+  let view_id = Uuid::new_v4();
+  let publish_item = || PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: "restricted-publish".to_string(),
+      metadata: MyCustomMetadata {
+        title: "restricted".to_string(),
+      },
+    },
+    data: "yrs_encoded_data".as_bytes(),
+    comments_enabled: true,
+    duplicate_enabled: true,
+  };
+  let err = member
+    .api_client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(&workspace_id, vec![publish_item()])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  owner
+    .api_client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(&workspace_id, vec![publish_item()])
+    .await
+    .unwrap();
+  let err = member
+    .api_client
+    .unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  owner
+    .api_client
+    .update_workspace_settings(
+      workspace_id.to_string(),
+      &AFWorkspaceSettingsChange::new()
+        .publish_permission(PublishPermission::Allowlist)
+        .publish_allowlist(vec![member.uid().await]),
+    )
+    .await
+    .unwrap();
+  member
+    .api_client
+    .unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn member_cannot_change_publish_permission() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_settings(
+      workspace_id.to_string(),
+      &AFWorkspaceSettingsChange::new().publish_permission(PublishPermission::Owners),
+    )
+    .await
+    .unwrap();
+
+  let err = member
+    .api_client
+    .update_workspace_settings(
+      workspace_id.to_string(),
+      &AFWorkspaceSettingsChange::new().publish_permission(PublishPermission::Members),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .update_workspace_settings(
+      workspace_id.to_string(),
+      &AFWorkspaceSettingsChange::new().publish_allowlist(vec![member.uid().await]),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let settings = owner
+    .api_client
+    .get_workspace_settings(workspace_id.to_string())
+    .await
+    .unwrap();
+  assert_eq!(settings.publish_permission, PublishPermission::Owners);
+  assert!(settings.publish_allowlist.is_empty());
+}
+
+#[tokio::test]
+async fn published_page_language_variants() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let namespace = Uuid::new_v4().to_string();
+  client
+    .api_client
+    .set_workspace_publish_namespace(&workspace_id, namespace.clone())
+    .await
+    .unwrap();
+  let view_id = Uuid::new_v4();
+  let fr_view_id = Uuid::new_v4();
+  let publish_item = |view_id: Uuid, publish_name: &str, title: &str| PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id,
+      publish_name: publish_name.to_string(),
+      metadata: MyCustomMetadata {
+        title: title.to_string(),
+      },
+    },
+    data: title.as_bytes().to_vec(),
+    comments_enabled: true,
+    duplicate_enabled: true,
+  };
+  client
+    .api_client
+    .publish_collabs::<MyCustomMetadata, Vec<u8>>(
+      &workspace_id,
+      vec![
+        publish_item(view_id, "guide", "guide"),
+        publish_item(fr_view_id, "guide-fr", "guide fr"),
+      ],
+    )
+    .await
+    .unwrap();
+
+  let err = client
+    .api_client
+    .set_published_view_variant(&workspace_id, &view_id, "fr", &Uuid::new_v4())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let variant = client
+    .api_client
+    .set_published_view_variant(&workspace_id, &view_id, "FR", &fr_view_id)
+    .await
+    .unwrap();
+  assert_eq!(variant.lang, "fr");
+  assert_eq!(variant.publish_name, "guide-fr");
+  let variants = client
+    .api_client
+    .list_published_view_variants(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(variants, vec![variant]);
+
+  let fr = client
+    .api_client
+    .get_published_collab_in_language::<MyCustomMetadata>(&namespace, "guide", "fr-CA")
+    .await
+    .unwrap();
+  assert_eq!(fr.title, "guide fr");
+  let en = client
+    .api_client
+    .get_published_collab_in_language::<MyCustomMetadata>(&namespace, "guide", "en")
+    .await
+    .unwrap();
+  assert_eq!(en.title, "guide");
+
+  client
+    .api_client
+    .delete_published_view_variant(&workspace_id, &view_id, "fr")
+    .await
+    .unwrap();
+  let fr = client
+    .api_client
+    .get_published_collab_in_language::<MyCustomMetadata>(&namespace, "guide", "fr")
+    .await
+    .unwrap();
+  assert_eq!(fr.title, "guide");
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct MyCustomMetadata {
   title: String,
@@ -1906,8 +2146,336 @@ async fn test_republish_patch() {
       publish_name: Some(publish_name.to_string()),
       comments_enabled: None,
       duplicate_enabled: None,
+      stats_digest_enabled: None,
+    }],
+  )
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn publish_theme_for_namespace_and_view() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&c).await;
+  let namespace = c
+    .get_workspace_publish_namespace(&workspace_id)
+    .await
+    .unwrap();
+  let view_id = Uuid::new_v4();
+  let publish_name = "themed-page";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "themed".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+      comments_enabled: true,
+      duplicate_enabled: true,
+    }],
+  )
+  .await
+  .unwrap();
+
+  let namespace_theme = c
+    .set_publish_namespace_theme(
+      &workspace_id,
+      &namespace,
+      &PublishTheme {
+        accent_color: Some("#3366ff".to_string()),
+        font_scale: Some(1.0),
+        header_image_url: None,
+        footer_html: Some(r#"<b>Docs</b><script>alert(1)</script>"#.to_string()),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(
+    namespace_theme.footer_html.as_deref(),
+    Some("<b>Docs</b>alert(1)")
+  );
+  let err = c
+    .set_publish_namespace_theme(
+      &workspace_id,
+      &namespace,
+      &PublishTheme {
+        accent_color: Some("blue".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  c.set_published_view_theme(
+    &workspace_id,
+    &view_id,
+    &PublishTheme {
+      font_scale: Some(1.5),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+
+  // the view theme takes precedence, the namespace theme fills in the rest
+  let guest_client = localhost_client();
+  let theme = guest_client
+    .get_published_view_effective_theme(&namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(theme.font_scale, Some(1.5));
+  assert_eq!(theme.accent_color.as_deref(), Some("#3366ff"));
+  assert_eq!(theme.footer_html, namespace_theme.footer_html);
+
+  c.delete_published_view_theme(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  let theme = guest_client
+    .get_published_view_effective_theme(&namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(theme, namespace_theme);
+}
+
+#[tokio::test]
+async fn published_view_stats_digest_opt_out() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&c).await;
+  let namespace = c
+    .get_workspace_publish_namespace(&workspace_id)
+    .await
+    .unwrap();
+  let view_id = Uuid::new_v4();
+  let publish_name = "stats-page";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "stats".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+      comments_enabled: true,
+      duplicate_enabled: true,
+    }],
+  )
+  .await
+  .unwrap();
+
+  // the digest can be turned off without touching the other publish settings
+  c.patch_published_collabs(
+    &workspace_id,
+    &[PatchPublishedCollab {
+      view_id,
+      publish_name: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      stats_digest_enabled: Some(false),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let guest_client = localhost_client();
+  let blob = guest_client
+    .get_published_collab_blob(&namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "yrs_encoded_data".as_bytes());
+  let info = c.get_published_collab_info(&view_id).await.unwrap();
+  assert_eq!(info.publish_name, publish_name);
+  assert!(info.comments_enabled);
+  assert!(info.duplicate_enabled);
+}
+
+#[tokio::test]
+async fn update_publish_namespace_with_explicit_old_namespace() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&c).await;
+  let original_namespace = c
+    .get_workspace_publish_namespace(&workspace_id)
+    .await
+    .unwrap();
+
+  // the old namespace must belong to the workspace
+  let err = c
+    .update_workspace_publish_namespace(
+      &workspace_id,
+      &UpdatePublishNamespace {
+        old_namespace: Uuid::new_v4().to_string(),
+        new_namespace: Uuid::new_v4().to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound, "{:?}", err);
+
+  let first_namespace = Uuid::new_v4().to_string();
+  c.update_workspace_publish_namespace(
+    &workspace_id,
+    &UpdatePublishNamespace {
+      old_namespace: original_namespace,
+      new_namespace: first_namespace.clone(),
+    },
+  )
+  .await
+  .unwrap();
+  let second_namespace = Uuid::new_v4().to_string();
+  c.update_workspace_publish_namespace(
+    &workspace_id,
+    &UpdatePublishNamespace {
+      old_namespace: first_namespace,
+      new_namespace: second_namespace.clone(),
+    },
+  )
+  .await
+  .unwrap();
+  let namespace = c
+    .get_workspace_publish_namespace(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(namespace, second_namespace);
+}
+
+#[tokio::test]
+async fn views_that_were_not_received_are_not_readonly() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&c).await;
+  let view_id = Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "not-received".to_string(),
+        metadata: MyCustomMetadata {
+          title: "not received".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+      comments_enabled: true,
+      duplicate_enabled: true,
+    }],
+  )
+  .await
+  .unwrap();
+
+  let readonly = c
+    .get_received_published_collab_readonly(&view_id)
+    .await
+    .unwrap();
+  assert!(!readonly.is_received);
+  assert!(!readonly.is_readonly);
+
+  let published = c.list_all_published_views().await.unwrap();
+  let item = published
+    .items
+    .iter()
+    .find(|item| item.published_view_id == view_id)
+    .unwrap();
+  assert!(!item.is_received);
+  assert!(!item.is_readonly);
+}
+
+#[tokio::test]
+async fn regenerate_broken_published_document() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace(&c).await;
+  let view_id = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap()
+    .view_id;
+  c.publish_page(
+    workspace_id,
+    &view_id,
+    &PublishPageParams {
+      publish_name: None,
+      visible_database_view_ids: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      row_filter: None,
+    },
+  )
+  .await
+  .unwrap();
+  let report = c
+    .scan_publish_integrity(&workspace_id, false)
+    .await
+    .unwrap();
+  assert_eq!(report.scanned_views, 1);
+  assert!(report.broken_views.is_empty());
+
+  // Overwrite the published document with data that is not a collab
+  let publish_info = c.get_published_collab_info(&view_id).await.unwrap();
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_info.publish_name.clone(),
+        metadata: serde_json::json!({
+          "view": {
+            "view_id": view_id.to_string(),
+            "name": "Getting started",
+            "icon": null,
+            "layout": 0,
+            "extra": null,
+            "created_by": null,
+            "last_edited_by": null,
+            "last_edited_time": 0,
+            "created_at": 0,
+            "child_views": null,
+          },
+          "child_views": [],
+          "ancestor_views": [],
+        }),
+      },
+      data: "not a collab".as_bytes(),
+      comments_enabled: true,
+      duplicate_enabled: true,
     }],
   )
   .await
   .unwrap();
+
+  let report = c
+    .scan_publish_integrity(&workspace_id, false)
+    .await
+    .unwrap();
+  assert_eq!(report.broken_views.len(), 1);
+  assert_eq!(report.broken_views[0].view_id, view_id);
+  assert!(!report.broken_views[0].regenerated);
+
+  let report = c.scan_publish_integrity(&workspace_id, true).await.unwrap();
+  assert!(report.broken_views[0].regenerated);
+  let report = c
+    .scan_publish_integrity(&workspace_id, false)
+    .await
+    .unwrap();
+  assert!(report.broken_views.is_empty());
+  let latest = c.get_publish_integrity_report(&workspace_id).await.unwrap();
+  assert_eq!(latest.scanned_at, report.scanned_at);
+
+  let blob = c
+    .get_published_collab_blob(&publish_info.namespace, &publish_info.publish_name)
+    .await
+    .unwrap();
+  collab_from_doc_state(blob.to_vec(), &view_id, default_client_id()).unwrap();
 }
@@ -1,5 +1,8 @@
 use std::time::Duration;
 
+use app_error::ErrorCode;
+use chrono::{DateTime, Utc};
+use client_api::entity::{CreateQuickNoteParams, ListQuickNotesQueryParams};
 use client_api_test::TestClient;
 use serde_json::json;
 use tokio::time;
@@ -146,3 +149,122 @@ async fn quick_note_crud_test() {
   assert_eq!(quick_notes.quick_notes.len(), 1);
   assert_eq!(quick_notes.quick_notes[0].id, quick_note_id_2);
 }
+
+fn paragraph_note(text: &str) -> serde_json::Value {
+  json!([
+    {
+      "type": "paragraph",
+      "delta": {
+        "insert": text,
+      },
+    }
+  ])
+}
+
+#[tokio::test]
+async fn quick_note_dedupe_test() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let create = |data: serde_json::Value, dedupe: bool| {
+    let api_client = &client.api_client;
+    async move {
+      api_client
+        .create_quick_note_with_params(
+          workspace_id,
+          &CreateQuickNoteParams {
+            data: Some(data),
+            dedupe,
+          },
+        )
+        .await
+        .expect("create quick note")
+    }
+  };
+
+  let note = create(paragraph_note("call the  plumber"), true).await;
+  // A retry of the same note returns the note created first
+  let retried = create(paragraph_note("call the plumber "), true).await;
+  assert_eq!(retried.id, note.id);
+  // A note extending the text of a recent note is a note of its own
+  let extended = create(paragraph_note("call the plumber tomorrow"), true).await;
+  assert_ne!(extended.id, note.id);
+  assert_eq!(extended.data, paragraph_note("call the plumber tomorrow"));
+  // So is a note shortening it, and neither overwrites the other
+  let shortened = create(paragraph_note("call the"), true).await;
+  assert_ne!(shortened.id, note.id);
+  assert_ne!(shortened.id, extended.id);
+  let retried = create(paragraph_note("call the plumber tomorrow"), true).await;
+  assert_eq!(retried.id, extended.id);
+  assert_eq!(retried.data, paragraph_note("call the plumber tomorrow"));
+  // Different notes and notes created without dedupe are kept apart
+  let other = create(paragraph_note("water the plants"), true).await;
+  assert_ne!(other.id, note.id);
+  let duplicate = create(paragraph_note("water the plants"), false).await;
+  assert_ne!(duplicate.id, other.id);
+
+  let quick_notes = client
+    .api_client
+    .list_quick_notes(workspace_id, None, None, None)
+    .await
+    .expect("list quick notes");
+  assert_eq!(quick_notes.quick_notes.len(), 5);
+}
+
+#[tokio::test]
+async fn list_quick_notes_by_created_date_test() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let first = client
+    .api_client
+    .create_quick_note(workspace_id, None)
+    .await
+    .expect("create quick note");
+  time::sleep(Duration::from_millis(10)).await;
+  let second = client
+    .api_client
+    .create_quick_note(workspace_id, None)
+    .await
+    .expect("create quick note");
+
+  let list = |created_after: Option<DateTime<Utc>>, created_before: Option<DateTime<Utc>>| {
+    let api_client = &client.api_client;
+    async move {
+      api_client
+        .list_quick_notes_with_params(
+          workspace_id,
+          &ListQuickNotesQueryParams {
+            created_after,
+            created_before,
+            ..Default::default()
+          },
+        )
+        .await
+        .expect("list quick notes")
+        .quick_notes
+        .into_iter()
+        .map(|note| note.id)
+        .collect::<Vec<Uuid>>()
+    }
+  };
+  assert_eq!(list(Some(second.created_at), None).await, vec![second.id]);
+  assert_eq!(list(None, Some(second.created_at)).await, vec![first.id]);
+  assert_eq!(
+    list(Some(first.created_at), Some(second.created_at)).await,
+    vec![first.id]
+  );
+  assert_eq!(list(None, None).await.len(), 2);
+
+  let err = client
+    .api_client
+    .list_quick_notes_with_params(
+      workspace_id,
+      &ListQuickNotesQueryParams {
+        created_after: Some(second.created_at),
+        created_before: Some(first.created_at),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
@@ -0,0 +1,167 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use async_zip::base::read::mem::ZipFileReader;
+use database_entity::dto::{
+  AFRole, CreateScheduledExportParams, ExportFormat, ScheduledExportDestination,
+};
+
+fn webdav_export_params(interval_days: Option<i32>) -> CreateScheduledExportParams {
+  CreateScheduledExportParams {
+    destination: ScheduledExportDestination::WebDav {
+      url: "https://example.com/exports".to_string(),
+      username: "owner".to_string(),
+    },
+    secret: "password".to_string(),
+    interval_days,
+  }
+}
+
+#[tokio::test]
+async fn scheduled_export_is_owner_only() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let exports = owner
+    .api_client
+    .list_scheduled_exports(workspace_id)
+    .await
+    .unwrap();
+  assert!(exports.exports.is_empty());
+
+  let err = member
+    .api_client
+    .create_scheduled_export(workspace_id, &webdav_export_params(None))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .list_scheduled_exports(workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn scheduled_export_crud() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+
+  let err = owner
+    .api_client
+    .create_scheduled_export(workspace_id, &webdav_export_params(Some(0)))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // destinations in the network of the server are refused
+  let mut params = webdav_export_params(None);
+  params.destination = ScheduledExportDestination::WebDav {
+    url: "http://169.254.169.254/latest/meta-data".to_string(),
+    username: "owner".to_string(),
+  };
+  let err = owner
+    .api_client
+    .create_scheduled_export(workspace_id, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let export = match owner
+    .api_client
+    .create_scheduled_export(workspace_id, &webdav_export_params(None))
+    .await
+  {
+    Ok(export) => export,
+    // the server has no key to encrypt the credentials with
+    Err(err) => {
+      assert_eq!(err.code, ErrorCode::FeatureNotAvailable);
+      return;
+    },
+  };
+  assert_eq!(export.interval_days, 7);
+  assert!(export.enabled);
+
+  let run = owner
+    .api_client
+    .run_scheduled_export(workspace_id, export.export_id)
+    .await
+    .unwrap();
+  let runs = owner
+    .api_client
+    .list_scheduled_export_runs(workspace_id, export.export_id, None)
+    .await
+    .unwrap();
+  assert_eq!(runs.runs[0].run_id, run.run_id);
+
+  owner
+    .api_client
+    .delete_scheduled_export(workspace_id, export.export_id)
+    .await
+    .unwrap();
+  let exports = owner
+    .api_client
+    .list_scheduled_exports(workspace_id)
+    .await
+    .unwrap();
+  assert!(exports.exports.is_empty());
+}
+
+#[tokio::test]
+async fn export_single_space() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let folder_view = client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let getting_started = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+
+  let file_names = |bundle: Vec<u8>| async move {
+    let reader = ZipFileReader::new(bundle).await.unwrap();
+    reader
+      .file()
+      .entries()
+      .iter()
+      .map(|entry| entry.filename().as_str().unwrap().to_string())
+      .collect::<Vec<_>>()
+  };
+  let markdown = client
+    .api_client
+    .export_space(workspace_id, general_space.view_id, ExportFormat::Markdown)
+    .await
+    .unwrap();
+  let markdown_files = file_names(markdown.to_vec()).await;
+  assert!(markdown_files.contains(&"Getting started.md".to_string()));
+  let json = client
+    .api_client
+    .export_space(workspace_id, general_space.view_id, ExportFormat::Json)
+    .await
+    .unwrap();
+  let json_files = file_names(json.to_vec()).await;
+  assert!(json_files.contains(&"Getting started.json".to_string()));
+  assert_eq!(json_files.len(), markdown_files.len());
+
+  // only spaces can be exported
+  let err = client
+    .api_client
+    .export_space(workspace_id, getting_started.view_id, ExportFormat::Markdown)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
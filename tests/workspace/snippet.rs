@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use app_error::ErrorCode;
+use client_api::entity::AFRole;
+use client_api_test::TestClient;
+use database_entity::dto::{
+  CreateWorkspaceSnippetParams, ExpandWorkspaceSnippetParams, UpdateWorkspaceSnippetParams,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn workspace_snippet_crud_and_expand() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let content = json!([
+    {
+      "type": "paragraph",
+      "delta": [{ "insert": "Reviewed by {{user_name}} for {{client}}" }],
+    }
+  ]);
+  let snippet = client
+    .api_client
+    .create_workspace_snippet(
+      workspace_id,
+      &CreateWorkspaceSnippetParams {
+        shortcut: "/Review".to_string(),
+        name: "Review footer".to_string(),
+        description: None,
+        content: content.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(snippet.shortcut, "review");
+
+  // shortcuts are unique per workspace
+  let err = client
+    .api_client
+    .create_workspace_snippet(
+      workspace_id,
+      &CreateWorkspaceSnippetParams {
+        shortcut: "review".to_string(),
+        name: "Duplicate".to_string(),
+        description: None,
+        content: content.clone(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordAlreadyExists);
+
+  let snippets = client
+    .api_client
+    .list_workspace_snippets(workspace_id, Some("/rev".to_string()))
+    .await
+    .unwrap();
+  assert_eq!(snippets.snippets.len(), 1);
+
+  let expanded = client
+    .api_client
+    .expand_workspace_snippet(
+      workspace_id,
+      &ExpandWorkspaceSnippetParams {
+        shortcut: "review".to_string(),
+        variables: HashMap::from([("client".to_string(), "ACME".to_string())]),
+      },
+    )
+    .await
+    .unwrap();
+  let text = expanded.content[0]["delta"][0]["insert"].as_str().unwrap();
+  assert!(!text.contains("{{"), "{}", text);
+  assert!(text.ends_with("for ACME"), "{}", text);
+
+  let updated = client
+    .api_client
+    .update_workspace_snippet(
+      workspace_id,
+      snippet.snippet_id,
+      &UpdateWorkspaceSnippetParams {
+        shortcut: Some("final-review".to_string()),
+        description: Some("Appended to reviewed documents".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(updated.shortcut, "final-review");
+  assert_eq!(updated.name, "Review footer");
+  assert_eq!(updated.content, content);
+
+  client
+    .api_client
+    .delete_workspace_snippet(workspace_id, snippet.snippet_id)
+    .await
+    .unwrap();
+  let snippets = client
+    .api_client
+    .list_workspace_snippets(workspace_id, None)
+    .await
+    .unwrap();
+  assert!(snippets.snippets.is_empty());
+}
+
+#[tokio::test]
+async fn workspace_snippet_edited_by_creator_or_owner() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let content = json!([{ "type": "paragraph", "delta": [{ "insert": "Signature" }] }]);
+  let owner_snippet = owner
+    .api_client
+    .create_workspace_snippet(
+      workspace_id,
+      &CreateWorkspaceSnippetParams {
+        shortcut: "signature".to_string(),
+        name: "Signature".to_string(),
+        description: None,
+        content: content.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  let member_snippet = member
+    .api_client
+    .create_workspace_snippet(
+      workspace_id,
+      &CreateWorkspaceSnippetParams {
+        shortcut: "greeting".to_string(),
+        name: "Greeting".to_string(),
+        description: None,
+        content,
+      },
+    )
+    .await
+    .unwrap();
+
+  // members can only change their own snippets
+  let err = member
+    .api_client
+    .update_workspace_snippet(
+      workspace_id,
+      owner_snippet.snippet_id,
+      &UpdateWorkspaceSnippetParams {
+        name: Some("Renamed".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .delete_workspace_snippet(workspace_id, owner_snippet.snippet_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  member
+    .api_client
+    .update_workspace_snippet(
+      workspace_id,
+      member_snippet.snippet_id,
+      &UpdateWorkspaceSnippetParams {
+        name: Some("Hello".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+
+  // owners can change every snippet
+  owner
+    .api_client
+    .delete_workspace_snippet(workspace_id, member_snippet.snippet_id)
+    .await
+    .unwrap();
+  let snippets = member
+    .api_client
+    .list_workspace_snippets(workspace_id, None)
+    .await
+    .unwrap();
+  assert_eq!(snippets.snippets.len(), 1);
+}
@@ -0,0 +1,91 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::SetViewSlugParams;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn view_slug_resolves_previous_slugs() {
+  let client = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client.workspace_id().await;
+  let view_id = Uuid::new_v4();
+  let other_view_id = Uuid::new_v4();
+
+  let slug = client
+    .api_client
+    .set_view_slug(
+      workspace_id,
+      view_id,
+      &SetViewSlugParams {
+        slug: "Meeting-Notes".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(slug.slug, "meeting-notes");
+
+  // renaming the slug keeps the previous one as a redirect
+  client
+    .api_client
+    .set_view_slug(
+      workspace_id,
+      view_id,
+      &SetViewSlugParams {
+        slug: "weekly-meeting".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  let resolved = client
+    .api_client
+    .resolve_view_slug(workspace_id, "meeting-notes")
+    .await
+    .unwrap();
+  assert_eq!(resolved.view_id, view_id);
+  assert_eq!(resolved.slug, "weekly-meeting");
+  assert!(resolved.redirected);
+  let resolved = client
+    .api_client
+    .resolve_view_slug(workspace_id, "weekly-meeting")
+    .await
+    .unwrap();
+  assert!(!resolved.redirected);
+
+  // slugs, current or previous, are unique per workspace
+  let err = client
+    .api_client
+    .set_view_slug(
+      workspace_id,
+      other_view_id,
+      &SetViewSlugParams {
+        slug: "meeting-notes".to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordAlreadyExists);
+
+  let err = client
+    .api_client
+    .set_view_slug(
+      workspace_id,
+      other_view_id,
+      &SetViewSlugParams {
+        slug: "not a slug".to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  client
+    .api_client
+    .delete_view_slug(workspace_id, view_id)
+    .await
+    .unwrap();
+  let err = client
+    .api_client
+    .resolve_view_slug(workspace_id, "meeting-notes")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
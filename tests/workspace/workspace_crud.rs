@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
+use app_error::ErrorCode;
+use client_api::entity::UpdateCollabWebParams;
 use client_api_test::generate_unique_registered_user_client;
 use collab_entity::CollabType;
 use database_entity::dto::QueryCollabParams;
 use serde_json::json;
 use shared_entity::dto::workspace_dto::AFDatabaseField;
 use shared_entity::dto::workspace_dto::CreateWorkspaceParam;
+use shared_entity::dto::workspace_dto::MergeWorkspaceParams;
 use shared_entity::dto::workspace_dto::PatchWorkspaceParam;
+use shared_entity::dto::workspace_dto::PublishPageParams;
+use yrs::{Map, ReadTxn, StateVector, Transact};
 
 #[tokio::test]
 async fn workspace_list_database() {
@@ -177,6 +182,171 @@ async fn add_and_delete_workspace_for_user() {
   assert_eq!(workspaces.len(), 1);
 }
 
+#[tokio::test]
+async fn merge_workspace_into_another() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let destination_workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let source_workspace_id = c
+    .create_workspace(CreateWorkspaceParam {
+      workspace_name: Some("side project".to_string()),
+      workspace_icon: None,
+    })
+    .await
+    .unwrap()
+    .workspace_id;
+  let source_space_ids: Vec<_> = c
+    .get_workspace_folder(&source_workspace_id, Some(1), None)
+    .await
+    .unwrap()
+    .children
+    .into_iter()
+    .map(|view| view.view_id)
+    .collect();
+  assert!(!source_space_ids.is_empty());
+
+  // A workspace can't be merged into itself
+  c.merge_workspace(
+    &destination_workspace_id,
+    &MergeWorkspaceParams {
+      source_workspace_id: destination_workspace_id,
+      space_name: None,
+    },
+  )
+  .await
+  .unwrap_err();
+
+  let merged = c
+    .merge_workspace(
+      &destination_workspace_id,
+      &MergeWorkspaceParams {
+        source_workspace_id,
+        space_name: None,
+      },
+    )
+    .await
+    .unwrap();
+  let workspaces = c.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 1);
+  assert_eq!(workspaces[0].workspace_id, destination_workspace_id);
+
+  let merged_space = c
+    .get_workspace_folder(&destination_workspace_id, Some(2), None)
+    .await
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|view| view.view_id == merged.space_id)
+    .unwrap();
+  assert_eq!(merged_space.name, "side project");
+  let merged_view_ids: Vec<_> = merged_space
+    .children
+    .iter()
+    .map(|view| view.view_id)
+    .collect();
+  assert_eq!(merged_view_ids, source_space_ids);
+}
+
+#[tokio::test]
+async fn merge_workspace_with_archived_space_and_published_page() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let destination_workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let source_workspace_id = c
+    .create_workspace(CreateWorkspaceParam {
+      workspace_name: Some("side project".to_string()),
+      workspace_icon: None,
+    })
+    .await
+    .unwrap()
+    .workspace_id;
+  let source_space = c
+    .get_workspace_folder(&source_workspace_id, Some(2), None)
+    .await
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|view| !view.children.is_empty())
+    .unwrap();
+  let page_id = source_space.children[0].view_id;
+  c.publish_page(
+    source_workspace_id,
+    &page_id,
+    &PublishPageParams {
+      publish_name: None,
+      visible_database_view_ids: None,
+      comments_enabled: None,
+      duplicate_enabled: None,
+      row_filter: None,
+    },
+  )
+  .await
+  .unwrap();
+  let source_namespace = c
+    .get_workspace_publish_namespace(&source_workspace_id)
+    .await
+    .unwrap();
+  let published = c.get_published_collab_info(&page_id).await.unwrap();
+  c.archive_space(source_workspace_id, &source_space.view_id)
+    .await
+    .unwrap();
+
+  let merged = c
+    .merge_workspace(
+      &destination_workspace_id,
+      &MergeWorkspaceParams {
+        source_workspace_id,
+        space_name: None,
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(merged.unpublished_view_count, 0);
+
+  // The space stays archived, and its pages stay read only
+  let archived_spaces = c
+    .list_archived_spaces(destination_workspace_id)
+    .await
+    .unwrap();
+  assert!(archived_spaces
+    .iter()
+    .any(|space| space.space_id == source_space.view_id));
+  let web_doc = yrs::Doc::new();
+  let doc_data = web_doc.get_or_insert_map("data");
+  {
+    let mut txn = web_doc.transact_mut();
+    doc_data.insert(&mut txn, "paragraph", "content");
+  }
+  let err = c
+    .update_web_collab(
+      &destination_workspace_id,
+      &page_id,
+      UpdateCollabWebParams {
+        doc_state: web_doc
+          .transact()
+          .encode_state_as_update_v1(&StateVector::default()),
+        collab_type: CollabType::Document,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // The page is published under the namespace of the destination, and links with the
+  // namespace of the source keep working
+  let destination_namespace = c
+    .get_workspace_publish_namespace(&destination_workspace_id)
+    .await
+    .unwrap();
+  assert_ne!(destination_namespace, source_namespace);
+  let info = c.get_published_collab_info(&page_id).await.unwrap();
+  assert_eq!(info.namespace, destination_namespace);
+  assert_eq!(info.publish_name, published.publish_name);
+  for namespace in [&destination_namespace, &source_namespace] {
+    c.get_published_collab_blob(namespace, &info.publish_name)
+      .await
+      .unwrap();
+  }
+}
+
 #[tokio::test]
 async fn test_workspace_rename_and_icon_change() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -31,3 +31,34 @@ async fn get_workpace_folder() {
     .unwrap();
   assert_eq!(folder_view.children.len(), 2);
 }
+
+#[tokio::test]
+async fn get_workspace_folder_forest() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces[0].workspace_id;
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view.children[0];
+  let first_child = &general_space.children[0];
+  let forest = c
+    .get_workspace_folder_forest(
+      &workspace_id,
+      Some(1),
+      vec![
+        general_space.view_id,
+        uuid::Uuid::new_v4(),
+        first_child.view_id,
+      ],
+    )
+    .await
+    .unwrap();
+  // the unknown root is left out, the others keep the requested order
+  assert_eq!(forest.len(), 2);
+  assert_eq!(forest[0].view_id, general_space.view_id);
+  assert_eq!(forest[0].children.len(), 2);
+  assert_eq!(forest[1].view_id, first_child.view_id);
+}